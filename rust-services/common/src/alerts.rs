@@ -0,0 +1,401 @@
+//! Risk Alert Metadata
+//!
+//! Typed metadata for each [`RiskAlertType`], serialized with a `type` tag
+//! so consumers can rely on the fields. Metadata that does not match a
+//! known type (an alert from a newer producer, say) is kept as raw JSON
+//! rather than rejected.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::events::{AlertSeverity, RiskAlert, RiskAlertType};
+use crate::types::Symbol;
+
+/// Account margin fell below maintenance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarginCallMetadata {
+    pub account_id: Uuid,
+
+    /// Equity over maintenance margin
+    #[serde(with = "rust_decimal::serde::str")]
+    pub margin_ratio: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub maintenance_ratio: Decimal,
+
+    /// Collateral that restores the maintenance ratio, in `currency`
+    #[serde(with = "rust_decimal::serde::str")]
+    pub required_top_up: Decimal,
+
+    pub currency: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionLimitMetadata {
+    pub symbol: Symbol,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub position: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub limit: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExposureLimitMetadata {
+    pub asset: String,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub exposure: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub limit: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiquidationMetadata {
+    pub account_id: Uuid,
+    pub symbol: Symbol,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub quantity: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub price: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnomalousTradingMetadata {
+    pub symbol: Option<Symbol>,
+    pub reason: String,
+}
+
+/// A stablecoin price seen on one venue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepegObservation {
+    pub venue: String,
+    pub pair: String,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub price: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StablecoinDepegMetadata {
+    pub asset: String,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub deviation: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub band: Decimal,
+
+    /// Routing moved away from the asset
+    pub rerouted: bool,
+
+    pub observations: Vec<DepegObservation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClockDriftMetadata {
+    pub venue: String,
+    pub offset_ms: i64,
+    pub max_drift_ms: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AbnormalFillPriceMetadata {
+    pub exchange: String,
+    pub symbol: Symbol,
+    pub exchange_order_id: String,
+    pub trade_id: String,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub price: Decimal,
+
+    /// Internal midprice the fill was compared to
+    #[serde(with = "rust_decimal::serde::str")]
+    pub reference: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub deviation_bps: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub max_deviation_bps: Decimal,
+
+    pub route_paused: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EngineCapacityMetadata {
+    pub resting_orders: usize,
+    pub max_resting_orders: usize,
+    pub cancel_only: bool,
+}
+
+/// An on-chain transfer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainTransferInfo {
+    pub tx_hash: String,
+    pub log_index: u64,
+    pub block_number: u64,
+    pub token: String,
+    pub from: String,
+    pub to: String,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+}
+
+/// A transfer as recorded by the wallet service
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransferRecordInfo {
+    pub id: Uuid,
+    pub tx_hash: String,
+    pub tx_type: String,
+    pub status: String,
+    pub currency: String,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnreconciledTransferMetadata {
+    /// `unrecorded`, `mismatched` or `not_on_chain`
+    pub kind: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transfer: Option<ChainTransferInfo>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record: Option<TransferRecordInfo>,
+
+    /// Transfers seen on chain for a mismatched record
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub observed: Vec<ChainTransferInfo>,
+}
+
+/// An order rejected by a pre-trade risk limit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreTradeLimitMetadata {
+    pub order_id: Uuid,
+    pub symbol: Symbol,
+
+    /// `order_size`, `open_orders`, `notional_exposure` or `price_band`
+    pub check: String,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub value: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub limit: Decimal,
+}
+
+/// Funds moved between the gateway's wallets
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WalletMovementMetadata {
+    pub movement_id: Uuid,
+    pub chain_id: u64,
+    pub kind: String,
+    pub token: String,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+
+    pub from: String,
+    pub to: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+
+    pub status: String,
+}
+
+/// Risk alert metadata, tagged with its alert type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertMetadata {
+    MarginCall(MarginCallMetadata),
+    PositionLimit(PositionLimitMetadata),
+    ExposureLimit(ExposureLimitMetadata),
+    Liquidation(LiquidationMetadata),
+    AnomalousTrading(AnomalousTradingMetadata),
+    StablecoinDepeg(StablecoinDepegMetadata),
+    ClockDrift(ClockDriftMetadata),
+    AbnormalFillPrice(AbnormalFillPriceMetadata),
+    EngineCapacity(EngineCapacityMetadata),
+    UnreconciledTransfer(UnreconciledTransferMetadata),
+    PreTradeLimit(PreTradeLimitMetadata),
+    WalletMovement(WalletMovementMetadata),
+    /// Metadata of an unknown type or shape, passed through as is
+    #[serde(untagged)]
+    Raw(serde_json::Value),
+}
+
+impl AlertMetadata {
+    /// Alert type the metadata belongs to, `None` for raw metadata
+    pub fn alert_type(&self) -> Option<RiskAlertType> {
+        Some(match self {
+            Self::MarginCall(_) => RiskAlertType::MarginCall,
+            Self::PositionLimit(_) => RiskAlertType::PositionLimit,
+            Self::ExposureLimit(_) => RiskAlertType::ExposureLimit,
+            Self::Liquidation(_) => RiskAlertType::Liquidation,
+            Self::AnomalousTrading(_) => RiskAlertType::AnomalousTrading,
+            Self::StablecoinDepeg(_) => RiskAlertType::StablecoinDepeg,
+            Self::ClockDrift(_) => RiskAlertType::ClockDrift,
+            Self::AbnormalFillPrice(_) => RiskAlertType::AbnormalFillPrice,
+            Self::EngineCapacity(_) => RiskAlertType::EngineCapacity,
+            Self::UnreconciledTransfer(_) => RiskAlertType::UnreconciledTransfer,
+            Self::PreTradeLimit(_) => RiskAlertType::PreTradeLimit,
+            Self::WalletMovement(_) => RiskAlertType::WalletMovement,
+            Self::Raw(_) => return None,
+        })
+    }
+}
+
+macro_rules! impl_from_metadata {
+    ($($variant:ident($metadata:ty)),* $(,)?) => {
+        $(
+            impl From<$metadata> for AlertMetadata {
+                fn from(metadata: $metadata) -> Self {
+                    Self::$variant(metadata)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_metadata!(
+    MarginCall(MarginCallMetadata),
+    PositionLimit(PositionLimitMetadata),
+    ExposureLimit(ExposureLimitMetadata),
+    Liquidation(LiquidationMetadata),
+    AnomalousTrading(AnomalousTradingMetadata),
+    StablecoinDepeg(StablecoinDepegMetadata),
+    ClockDrift(ClockDriftMetadata),
+    AbnormalFillPrice(AbnormalFillPriceMetadata),
+    EngineCapacity(EngineCapacityMetadata),
+    UnreconciledTransfer(UnreconciledTransferMetadata),
+    PreTradeLimit(PreTradeLimitMetadata),
+    WalletMovement(WalletMovementMetadata),
+);
+
+impl RiskAlert {
+    /// Alert whose type follows from its metadata
+    pub fn builder<M>(metadata: M, message: impl Into<String>) -> RiskAlertBuilder
+    where
+        M: Into<AlertMetadata>,
+    {
+        let metadata = metadata.into();
+        let alert_type = metadata
+            .alert_type()
+            .expect("typed metadata has an alert type");
+        RiskAlertBuilder::new(alert_type, metadata, message.into())
+    }
+
+    /// Alert with untyped metadata, for types without a metadata struct yet
+    pub fn raw_builder(
+        alert_type: RiskAlertType,
+        metadata: serde_json::Value,
+        message: impl Into<String>,
+    ) -> RiskAlertBuilder {
+        RiskAlertBuilder::new(alert_type, AlertMetadata::Raw(metadata), message.into())
+    }
+}
+
+/// Builds a [`RiskAlert`]; severity defaults to Warning and the timestamp
+/// to now
+pub struct RiskAlertBuilder {
+    alert_type: RiskAlertType,
+    metadata: AlertMetadata,
+    message: String,
+    user_id: Option<Uuid>,
+    severity: AlertSeverity,
+    timestamp: Option<DateTime<Utc>>,
+}
+
+impl RiskAlertBuilder {
+    fn new(alert_type: RiskAlertType, metadata: AlertMetadata, message: String) -> Self {
+        Self {
+            alert_type,
+            metadata,
+            message,
+            user_id: None,
+            severity: AlertSeverity::Warning,
+            timestamp: None,
+        }
+    }
+
+    pub fn user_id(mut self, user_id: Uuid) -> Self {
+        self.user_id = Some(user_id);
+        self
+    }
+
+    pub fn severity(mut self, severity: AlertSeverity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    pub fn build(self) -> RiskAlert {
+        RiskAlert {
+            alert_id: Uuid::new_v4(),
+            user_id: self.user_id,
+            alert_type: self.alert_type,
+            severity: self.severity,
+            message: self.message,
+            metadata: self.metadata,
+            timestamp: self.timestamp.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_metadata_round_trips_with_tag() {
+        let alert = RiskAlert::builder(
+            MarginCallMetadata {
+                account_id: Uuid::new_v4(),
+                margin_ratio: Decimal::new(95, 2),
+                maintenance_ratio: Decimal::ONE,
+                required_top_up: Decimal::new(1250, 0),
+                currency: "USDT".to_string(),
+            },
+            "Margin ratio 0.95 below maintenance",
+        )
+        .severity(AlertSeverity::Critical)
+        .build();
+        assert!(matches!(alert.alert_type, RiskAlertType::MarginCall));
+
+        let json = serde_json::to_value(&alert).unwrap();
+        assert_eq!(json["metadata"]["type"], "margin_call");
+        assert_eq!(json["metadata"]["required_top_up"], "1250");
+
+        let parsed: RiskAlert = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.metadata, alert.metadata);
+    }
+
+    #[test]
+    fn test_unknown_metadata_kept_raw() {
+        let raw = serde_json::json!({"type": "funding_spike", "rate": "0.01"});
+        let metadata: AlertMetadata = serde_json::from_value(raw.clone()).unwrap();
+        assert_eq!(metadata, AlertMetadata::Raw(raw.clone()));
+        assert_eq!(metadata.alert_type(), None);
+        assert_eq!(serde_json::to_value(&metadata).unwrap(), raw);
+
+        // A known tag with missing fields is not dropped either
+        let partial = serde_json::json!({"type": "clock_drift", "venue": "okx"});
+        let metadata: AlertMetadata = serde_json::from_value(partial.clone()).unwrap();
+        assert_eq!(metadata, AlertMetadata::Raw(partial));
+    }
+}
@@ -0,0 +1,150 @@
+//! Tolerant Decimal Serde
+//!
+//! `rust_decimal::serde::str` rejects JSON numbers, which is what most
+//! REST clients send for prices and quantities. These helpers accept a
+//! decimal string or a JSON number and always serialize as a string, so
+//! responses keep exact values. Use them on API request types:
+//!
+//! ```ignore
+//! #[serde(with = "common::decimal::flex")]
+//! quantity: Decimal,
+//! #[serde(default, with = "common::decimal::flex_option")]
+//! price: Option<Decimal>,
+//! ```
+//!
+//! Floats are converted through their shortest round-trip representation,
+//! so `0.1` becomes exactly `0.1`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+use serde::de::{self, Visitor};
+use serde::Deserializer;
+
+struct DecimalVisitor;
+
+impl<'de> Visitor<'de> for DecimalVisitor {
+    type Value = Decimal;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a decimal number or numeric string")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Decimal, E> {
+        let v = v.trim();
+        Decimal::from_str(v)
+            .or_else(|_| Decimal::from_scientific(v))
+            .map_err(|_| E::custom(format!("invalid decimal `{v}`")))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Decimal, E> {
+        Ok(Decimal::from(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Decimal, E> {
+        Ok(Decimal::from(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Decimal, E> {
+        if !v.is_finite() {
+            return Err(E::custom(format!("invalid decimal `{v}`")));
+        }
+        self.visit_str(&v.to_string())
+    }
+}
+
+/// Decimal from a string or number, serialized as a string
+pub mod flex {
+    use super::*;
+    use serde::Serializer;
+
+    pub fn serialize<S: Serializer>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+        rust_decimal::serde::str::serialize(value, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+        deserializer.deserialize_any(DecimalVisitor)
+    }
+}
+
+/// Optional decimal from a string, number or null, serialized as a string
+pub mod flex_option {
+    use super::*;
+    use serde::Serializer;
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Decimal>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        rust_decimal::serde::str_option::serialize(value, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Decimal>, D::Error> {
+        struct OptionVisitor;
+
+        impl<'de> Visitor<'de> for OptionVisitor {
+            type Value = Option<Decimal>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a decimal number, numeric string or null")
+            }
+
+            fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+                d.deserialize_any(DecimalVisitor).map(Some)
+            }
+        }
+
+        deserializer.deserialize_option(OptionVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Request {
+        #[serde(with = "flex")]
+        quantity: Decimal,
+        #[serde(default, with = "flex_option")]
+        price: Option<Decimal>,
+    }
+
+    fn parse(json: &str) -> Result<Request, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    #[test]
+    fn test_accepts_strings_and_numbers() {
+        let req = parse(r#"{"quantity": 0.1, "price": 2000}"#).unwrap();
+        assert_eq!(req.quantity, Decimal::new(1, 1));
+        assert_eq!(req.price, Some(Decimal::new(2000, 0)));
+
+        let req = parse(r#"{"quantity": "1.50", "price": null}"#).unwrap();
+        assert_eq!(req.quantity.to_string(), "1.50");
+        assert_eq!(req.price, None);
+        assert_eq!(
+            parse(r#"{"quantity": "1e-3"}"#).unwrap().quantity,
+            Decimal::new(1, 3)
+        );
+
+        let err = parse(r#"{"quantity": "abc"}"#).unwrap_err();
+        assert!(err.to_string().contains("invalid decimal `abc`"));
+
+        // Always emitted as strings
+        let json = serde_json::to_string(&parse(r#"{"quantity": 2, "price": 1.5}"#).unwrap());
+        assert_eq!(json.unwrap(), r#"{"quantity":"2","price":"1.5"}"#);
+    }
+}
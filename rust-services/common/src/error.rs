@@ -31,6 +31,51 @@ pub enum TradingError {
 
     #[error("Self-trade prevention triggered")]
     SelfTradePrevention,
+
+    #[error("Engine not ready: {0}")]
+    EngineNotReady(String),
+
+    #[error("Notional {notional} is below minimum {minimum}")]
+    BelowMinNotional { notional: String, minimum: String },
+
+    #[error("Risk limit exceeded: {0}")]
+    RiskLimitExceeded(String),
+}
+
+impl TradingError {
+    /// Short label for rejection metrics
+    pub fn reason(&self) -> &'static str {
+        match self {
+            TradingError::OrderNotFound(_) => "order_not_found",
+            TradingError::InsufficientBalance { .. } => "insufficient_balance",
+            TradingError::InvalidOrder(_) => "invalid_order",
+            TradingError::OrderRejected(_) => "rejected",
+            TradingError::SymbolNotFound(_) => "unknown_symbol",
+            TradingError::RateLimitExceeded => "rate_limited",
+            TradingError::MarketClosed => "market_closed",
+            TradingError::SelfTradePrevention => "self_trade",
+            TradingError::EngineNotReady(_) => "not_ready",
+            TradingError::BelowMinNotional { .. } => "min_notional",
+            TradingError::RiskLimitExceeded(_) => "risk_limit",
+        }
+    }
+
+    /// Machine-readable code carried by order rejections
+    pub fn code(&self) -> &'static str {
+        match self {
+            TradingError::OrderNotFound(_) => "ORDER_NOT_FOUND",
+            TradingError::InsufficientBalance { .. } => "INSUFFICIENT_BALANCE",
+            TradingError::InvalidOrder(_) => "INVALID_ORDER",
+            TradingError::OrderRejected(_) => "ORDER_REJECTED",
+            TradingError::SymbolNotFound(_) => "SYMBOL_NOT_FOUND",
+            TradingError::RateLimitExceeded => "RATE_LIMITED",
+            TradingError::MarketClosed => "MARKET_CLOSED",
+            TradingError::SelfTradePrevention => "SELF_TRADE",
+            TradingError::EngineNotReady(_) => "ENGINE_NOT_READY",
+            TradingError::BelowMinNotional { .. } => "MIN_NOTIONAL",
+            TradingError::RiskLimitExceeded(_) => "RISK_LIMIT",
+        }
+    }
 }
 
 /// Data pipeline errors
@@ -124,6 +169,7 @@ impl ServiceError {
             ServiceError::Trading(TradingError::InsufficientBalance { .. }) => 400,
             ServiceError::Trading(TradingError::InvalidOrder(_)) => 400,
             ServiceError::Trading(TradingError::RateLimitExceeded) => 429,
+            ServiceError::Trading(TradingError::EngineNotReady(_)) => 503,
             ServiceError::Trading(_) => 400,
             ServiceError::Exchange(ExchangeError::RateLimited) => 429,
             ServiceError::Exchange(ExchangeError::AuthenticationFailed(_)) => 401,
@@ -144,6 +190,7 @@ impl ServiceError {
             }
             ServiceError::Trading(TradingError::InvalidOrder(_)) => "INVALID_ORDER",
             ServiceError::Trading(TradingError::RateLimitExceeded) => "RATE_LIMIT_EXCEEDED",
+            ServiceError::Trading(TradingError::EngineNotReady(_)) => "ENGINE_NOT_READY",
             ServiceError::Trading(_) => "TRADING_ERROR",
             ServiceError::Exchange(_) => "EXCHANGE_ERROR",
             ServiceError::Pipeline(_) => "PIPELINE_ERROR",
@@ -3,12 +3,18 @@
 //! All inter-service communication uses strongly-typed events
 //! published through Kafka for reliability and scalability.
 
-use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use chrono::{DateTime, NaiveDate, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::types::{Order, OrderStatus, Side, Symbol, Trade};
+use crate::alerts::AlertMetadata;
+use crate::types::{
+    Candle, MarketData, Order, OrderStatus, Side, Symbol, SymbolConfig, SymbolInfo, Trade,
+};
 
 /// Event envelope with metadata for tracing and replay
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,6 +60,113 @@ impl<T: Serialize> Event<T> {
     }
 }
 
+impl<T: TypedEvent> Event<T> {
+    /// Start building an event whose type, topic and key come from the payload
+    pub fn builder(payload: T) -> EventBuilder<T> {
+        EventBuilder {
+            payload,
+            correlation_id: None,
+            source: None,
+        }
+    }
+}
+
+/// Payload with a fixed event type, Kafka topic and partition key
+///
+/// Kafka only orders events within a partition, so the key decides what
+/// consumers may rely on. Order and trade events are keyed by symbol: all
+/// of a symbol's events on a topic land on one partition and are consumed
+/// in the order the engine published them. Nothing is promised across
+/// symbols or across topics.
+pub trait TypedEvent: Serialize {
+    const EVENT_TYPE: &'static str;
+    const TOPIC: &'static str;
+
+    /// Partition key; events that must stay ordered share a key
+    fn key(&self) -> String;
+}
+
+static SERVICE_NAME: OnceLock<String> = OnceLock::new();
+
+/// Last sequence per (source, topic)
+static SEQUENCES: OnceLock<Mutex<HashMap<(String, &'static str), u64>>> = OnceLock::new();
+
+/// Set the service name stamped on built events
+///
+/// Call once at startup; returns false if it was already set.
+pub fn init_service(name: &str) -> bool {
+    SERVICE_NAME.set(name.to_string()).is_ok()
+}
+
+/// Service name of this process, `unknown` until [`init_service`] is called
+pub fn service_name() -> &'static str {
+    SERVICE_NAME.get().map(String::as_str).unwrap_or("unknown")
+}
+
+/// Next sequence for `source` on `topic`, starting at 1 per process
+fn next_sequence(source: &str, topic: &'static str) -> u64 {
+    let mut sequences = SEQUENCES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    let sequence = sequences.entry((source.to_string(), topic)).or_insert(0);
+    *sequence += 1;
+    *sequence
+}
+
+/// Builder for an [`Event`] of a [`TypedEvent`] payload
+pub struct EventBuilder<T> {
+    payload: T,
+    correlation_id: Option<Uuid>,
+    source: Option<String>,
+}
+
+impl<T: TypedEvent> EventBuilder<T> {
+    pub fn correlation_id(mut self, correlation_id: Uuid) -> Self {
+        self.correlation_id = Some(correlation_id);
+        self
+    }
+
+    /// Override the process-wide service name
+    pub fn source(mut self, source: &str) -> Self {
+        self.source = Some(source.to_string());
+        self
+    }
+
+    /// Assign the next sequence and address the event to its topic
+    pub fn build(self) -> OutboundEvent<T> {
+        let source = self.source.unwrap_or_else(|| service_name().to_string());
+
+        OutboundEvent {
+            topic: T::TOPIC,
+            key: self.payload.key(),
+            event: Event {
+                id: Uuid::new_v4(),
+                event_type: T::EVENT_TYPE.to_string(),
+                correlation_id: self.correlation_id,
+                sequence: next_sequence(&source, T::TOPIC),
+                source,
+                timestamp: Utc::now(),
+                payload: self.payload,
+            },
+        }
+    }
+}
+
+/// Event ready to be produced to `topic` with partition `key`
+#[derive(Debug, Clone)]
+pub struct OutboundEvent<T> {
+    pub topic: &'static str,
+    pub key: String,
+    pub event: Event<T>,
+}
+
+impl<T: Serialize> OutboundEvent<T> {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(&self.event)
+    }
+}
+
 // ============== Order Events ==============
 
 /// New order submitted to matching engine
@@ -77,7 +190,11 @@ pub struct OrderAccepted {
 pub struct OrderRejected {
     pub order_id: Uuid,
     pub client_order_id: String,
+    pub symbol: Symbol,
     pub reason: String,
+    /// Machine-readable reason, e.g. `MIN_NOTIONAL`
+    #[serde(default)]
+    pub code: String,
     pub timestamp: DateTime<Utc>,
 }
 
@@ -98,9 +215,33 @@ pub struct OrderUpdated {
     #[serde(with = "rust_decimal::serde::str_option")]
     pub avg_fill_price: Option<Decimal>,
 
+    /// Trades that filled this order, capped in size (empty in lean mode)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fills: Vec<FillSummary>,
+
+    /// Set when `fills` was capped and omits some trades
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fills_truncated: bool,
+
     pub timestamp: DateTime<Utc>,
 }
 
+/// Single fill of an order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillSummary {
+    pub trade_id: u64,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub price: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub quantity: Decimal,
+
+    /// Fee charged in the quote asset
+    #[serde(with = "rust_decimal::serde::str")]
+    pub fee: Decimal,
+}
+
 /// Order cancelled
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderCancelled {
@@ -111,6 +252,48 @@ pub struct OrderCancelled {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Cancellation requested by an upstream service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderCancelRequested {
+    pub order_id: Uuid,
+    pub user_id: Uuid,
+    pub symbol: Symbol,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Cancel/replace requested by an upstream service
+///
+/// The resting order is cancelled and `replacement`, a new order with its
+/// own id, is submitted in its place. The replacement is rejected if the
+/// original is no longer on the book.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderReplaceRequested {
+    pub order_id: Uuid,
+    pub user_id: Uuid,
+    pub symbol: Symbol,
+    pub replacement: Order,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Command for the matching engine on the orders topic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum OrderCommandEnvelope {
+    Submit(OrderSubmitted),
+    Cancel(OrderCancelRequested),
+    Replace(OrderReplaceRequested),
+}
+
+impl OrderCommandEnvelope {
+    pub fn symbol(&self) -> &Symbol {
+        match self {
+            Self::Submit(submitted) => &submitted.order.symbol,
+            Self::Cancel(request) => &request.symbol,
+            Self::Replace(request) => &request.symbol,
+        }
+    }
+}
+
 // ============== Trade Events ==============
 
 /// Trade executed
@@ -131,6 +314,45 @@ pub struct OrderBookUpdate {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Incremental change to a single price level
+///
+/// `quantity` is the new aggregate size at the level; zero removes the level.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBookDelta {
+    pub symbol: Symbol,
+    pub side: Side,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub price: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub quantity: Decimal,
+
+    pub sequence: u64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Level changes of one book since its previous message
+///
+/// Changes within a conflation interval are merged, each level appearing
+/// once with its latest aggregate quantity; zero removes the level.
+/// `sequence` is the book sequence the message brings the book to and
+/// `prev_sequence` that of the delta or snapshot before it, so a
+/// `prev_sequence` other than the last one applied means a lost message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBookDeltas {
+    pub symbol: Symbol,
+    pub bids: Vec<(Decimal, Decimal)>,
+    pub asks: Vec<(Decimal, Decimal)>,
+    pub sequence: u64,
+    pub prev_sequence: u64,
+
+    /// Book changes merged into this message
+    pub changes: u32,
+
+    pub timestamp: DateTime<Utc>,
+}
+
 /// Price tick
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceTick {
@@ -146,9 +368,245 @@ pub struct PriceTick {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Conflated top-of-book midprice
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MidpriceTick {
+    pub symbol: Symbol,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub mid: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub bid: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub ask: Decimal,
+
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Price of a pair without a direct market, derived from the base and
+/// quote assets' markets against a common bridge asset
+///
+/// Volume, high and low are zero: a synthetic pair does not trade.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyntheticTicker {
+    #[serde(flatten)]
+    pub data: MarketData,
+
+    /// Always true, so mixed ticker streams can tell derived prices apart
+    pub synthetic: bool,
+
+    /// Asset both legs are quoted in, e.g. `USDT`
+    pub bridge: String,
+
+    /// Base/bridge and quote/bridge markets the price is derived from
+    pub legs: [Symbol; 2],
+}
+
+/// Execution quality of a symbol over a window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketQuality {
+    pub symbol: Symbol,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+
+    /// Incoming orders that reached the book
+    pub orders: u64,
+
+    /// Orders that filled at least partly on arrival
+    pub filled_orders: u64,
+
+    /// Orders that filled completely on arrival
+    pub fully_filled_orders: u64,
+
+    /// Filled over submitted quantity on arrival
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub fill_rate: Option<Decimal>,
+
+    /// Taker fills of limit orders
+    pub limit_fills: u64,
+
+    /// Limit fills executed better than the limit price
+    pub price_improved_fills: u64,
+
+    /// Total improvement over limit prices, in quote currency
+    #[serde(with = "rust_decimal::serde::str")]
+    pub price_improvement: Decimal,
+
+    /// Mean top-of-book spread seen by arriving orders, in bps of mid
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub quoted_spread_bps: Option<Decimal>,
+
+    /// Quantity-weighted twice the distance of fills from mid, in bps of mid
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub effective_spread_bps: Option<Decimal>,
+}
+
+/// Indicative outcome of a halted symbol's auction, were it to uncross now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndicativePrice {
+    pub symbol: Symbol,
+
+    /// Price that would execute the most quantity; `None` while the
+    /// collected orders do not cross
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub price: Option<Decimal>,
+
+    /// Quantity that would trade at `price`
+    #[serde(with = "rust_decimal::serde::str")]
+    pub paired_quantity: Decimal,
+
+    /// Quantity left unmatched at `price` on `imbalance_side`
+    #[serde(with = "rust_decimal::serde::str")]
+    pub imbalance_quantity: Decimal,
+
+    pub imbalance_side: Option<Side>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Official daily prices of a symbol for a UTC trading date
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementPrice {
+    pub symbol: Symbol,
+    pub trading_date: NaiveDate,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub open: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub close: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub settlement: Decimal,
+
+    /// Methodology that produced `settlement`, e.g. `vwap_30m`
+    pub method: String,
+
+    /// Quantity traded in the settlement window
+    #[serde(with = "rust_decimal::serde::str")]
+    pub window_volume: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub volume: Decimal,
+
+    pub trade_count: u64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Fees collected into a house account over a window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeesAccrued {
+    /// Asset the fees were charged in, the quote asset of the trades
+    pub asset: String,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+
+    /// Fees collected in the window
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+
+    /// House account balance at the end of the window
+    #[serde(with = "rust_decimal::serde::str")]
+    pub balance: Decimal,
+
+    /// Fee-paying fills in the window
+    pub fills: u64,
+}
+
+// ============== Venue Events ==============
+
+/// Fill of a house order on an external venue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VenueFill {
+    pub exchange: String,
+    pub symbol: Symbol,
+    pub exchange_order_id: String,
+    pub client_order_id: String,
+    pub trade_id: String,
+    pub side: Side,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub price: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub quantity: Decimal,
+
+    /// Order quantity filled so far, including this fill
+    #[serde(with = "rust_decimal::serde::str")]
+    pub cumulative_quantity: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub fee: Decimal,
+    pub fee_asset: Option<String>,
+
+    pub is_maker: bool,
+    pub executed_at: DateTime<Utc>,
+}
+
+/// Top of book and last trade of a symbol on an external venue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VenueTicker {
+    pub exchange: String,
+    pub symbol: Symbol,
+
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub bid: Option<Decimal>,
+
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub bid_quantity: Option<Decimal>,
+
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub ask: Option<Decimal>,
+
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub ask_quantity: Option<Decimal>,
+
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub last_price: Option<Decimal>,
+
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Smart order router's choice of venues for an external order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderRouted {
+    pub order_id: Uuid,
+    pub client_order_id: String,
+    pub symbol: Symbol,
+    pub side: Side,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub quantity: Decimal,
+
+    /// Venues and the share of the order sent to each, best price first
+    pub allocations: Vec<VenueAllocation>,
+
+    /// Venues whose books could not be read and were left out
+    pub skipped_venues: Vec<String>,
+
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Share of a routed order sent to one venue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VenueAllocation {
+    pub exchange: String,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub quantity: Decimal,
+
+    /// Average price of the visible liquidity the share takes
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub expected_price: Option<Decimal>,
+}
+
 // ============== Risk Events ==============
 
 /// Position update
+///
+/// `quantity` is signed: negative for a short position. PnL is in the
+/// quote asset, before fees.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PositionUpdate {
     pub user_id: Uuid,
@@ -160,9 +618,17 @@ pub struct PositionUpdate {
     #[serde(with = "rust_decimal::serde::str")]
     pub avg_entry_price: Decimal,
 
+    /// Last price the position was marked against
+    #[serde(default, with = "rust_decimal::serde::str")]
+    pub mark_price: Decimal,
+
     #[serde(with = "rust_decimal::serde::str")]
     pub unrealized_pnl: Decimal,
 
+    /// PnL locked in by reducing or closing the position
+    #[serde(default, with = "rust_decimal::serde::str")]
+    pub realized_pnl: Decimal,
+
     pub timestamp: DateTime<Utc>,
 }
 
@@ -174,7 +640,36 @@ pub struct RiskAlert {
     pub alert_type: RiskAlertType,
     pub severity: AlertSeverity,
     pub message: String,
-    pub metadata: serde_json::Value,
+    pub metadata: AlertMetadata,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Funds moved between the gateway's own wallets
+///
+/// Published to the audit topic for every attempt, failed ones included.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletMovement {
+    pub movement_id: Uuid,
+    pub chain_id: u64,
+
+    /// `sweep`
+    pub kind: String,
+    pub token: String,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+
+    /// Wallet names and addresses
+    pub from_wallet: String,
+    pub from: String,
+    pub to_wallet: String,
+    pub to: String,
+
+    pub tx_hash: Option<String>,
+
+    /// `confirmed` or `failed`
+    pub status: String,
+    pub error: Option<String>,
     pub timestamp: DateTime<Utc>,
 }
 
@@ -186,6 +681,13 @@ pub enum RiskAlertType {
     ExposureLimit,
     Liquidation,
     AnomalousTrading,
+    StablecoinDepeg,
+    ClockDrift,
+    AbnormalFillPrice,
+    EngineCapacity,
+    UnreconciledTransfer,
+    PreTradeLimit,
+    WalletMovement,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -196,6 +698,288 @@ pub enum AlertSeverity {
     Critical,
 }
 
+// ============== Reference Data Events ==============
+
+/// Symbol metadata changed
+///
+/// Applies on top of `previous_version`; a consumer at any other version
+/// has missed a change and must reload the snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferenceDataChanged {
+    pub version: u64,
+    pub previous_version: u64,
+    pub upserted: Vec<SymbolInfo>,
+    pub removed: Vec<Symbol>,
+    pub timestamp: DateTime<Utc>,
+}
+
+// ============== Symbol Lifecycle Events ==============
+
+/// Symbol listed on the matching engine at runtime
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolAdded {
+    pub symbol: Symbol,
+    /// Tick size, lot size and min notional, if constrained
+    pub config: Option<SymbolConfig>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Symbol delisted from the matching engine
+///
+/// Its resting orders were cancelled with reason `delisted` before this
+/// event was published.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolDelisted {
+    pub symbol: Symbol,
+    pub cancelled_orders: usize,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Matching halted on a symbol; its book collects orders for an auction
+/// until it resumes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolHalted {
+    pub symbol: Symbol,
+    /// `circuit_breaker` or `admin`
+    pub reason: String,
+    /// For a circuit breaker, the price the move started from
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub reference_price: Option<Decimal>,
+    /// For a circuit breaker, the trade price that tripped it
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub price: Option<Decimal>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Matching resumed on a halted symbol; its book is uncrossed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolResumed {
+    pub symbol: Symbol,
+    pub timestamp: DateTime<Utc>,
+}
+
+// ============== Event Routing ==============
+
+impl TypedEvent for OrderUpdated {
+    const EVENT_TYPE: &'static str = "order_updated";
+    const TOPIC: &'static str = topics::ORDERS;
+
+    fn key(&self) -> String {
+        self.symbol.to_string()
+    }
+}
+
+impl TypedEvent for OrderRejected {
+    const EVENT_TYPE: &'static str = "order_rejected";
+    const TOPIC: &'static str = topics::ORDERS;
+
+    fn key(&self) -> String {
+        self.symbol.to_string()
+    }
+}
+
+impl TypedEvent for OrderCancelled {
+    const EVENT_TYPE: &'static str = "order_cancelled";
+    const TOPIC: &'static str = topics::ORDERS;
+
+    fn key(&self) -> String {
+        self.symbol.to_string()
+    }
+}
+
+impl TypedEvent for TradeExecuted {
+    const EVENT_TYPE: &'static str = "trade_executed";
+    const TOPIC: &'static str = topics::TRADES;
+
+    fn key(&self) -> String {
+        self.trade.symbol.to_string()
+    }
+}
+
+/// Keyed by symbol so a cancel never overtakes the order it targets
+impl TypedEvent for OrderBookUpdate {
+    const EVENT_TYPE: &'static str = "orderbook_snapshot";
+    const TOPIC: &'static str = topics::ORDER_BOOK;
+
+    fn key(&self) -> String {
+        self.symbol.to_string()
+    }
+}
+
+impl TypedEvent for OrderBookDeltas {
+    const EVENT_TYPE: &'static str = "orderbook_deltas";
+    const TOPIC: &'static str = topics::ORDER_BOOK;
+
+    fn key(&self) -> String {
+        self.symbol.to_string()
+    }
+}
+
+impl TypedEvent for OrderCommandEnvelope {
+    const EVENT_TYPE: &'static str = "order_command";
+    const TOPIC: &'static str = topics::ORDERS;
+
+    fn key(&self) -> String {
+        self.symbol().to_string()
+    }
+}
+
+impl TypedEvent for MarketQuality {
+    const EVENT_TYPE: &'static str = "market_quality";
+    const TOPIC: &'static str = topics::MARKET_QUALITY;
+
+    fn key(&self) -> String {
+        self.symbol.to_string()
+    }
+}
+
+impl TypedEvent for IndicativePrice {
+    const EVENT_TYPE: &'static str = "indicative_price";
+    const TOPIC: &'static str = topics::AUCTIONS;
+
+    fn key(&self) -> String {
+        self.symbol.to_string()
+    }
+}
+
+impl TypedEvent for FeesAccrued {
+    const EVENT_TYPE: &'static str = "fees_accrued";
+    const TOPIC: &'static str = topics::FEES;
+
+    fn key(&self) -> String {
+        self.asset.clone()
+    }
+}
+
+impl TypedEvent for SettlementPrice {
+    const EVENT_TYPE: &'static str = "settlement_price";
+    const TOPIC: &'static str = topics::SETTLEMENT;
+
+    fn key(&self) -> String {
+        self.symbol.to_string()
+    }
+}
+
+impl TypedEvent for VenueFill {
+    const EVENT_TYPE: &'static str = "venue_fill";
+    const TOPIC: &'static str = topics::VENUE_FILLS;
+
+    fn key(&self) -> String {
+        self.exchange_order_id.clone()
+    }
+}
+
+impl TypedEvent for OrderRouted {
+    const EVENT_TYPE: &'static str = "order_routed";
+    const TOPIC: &'static str = topics::ROUTING;
+
+    fn key(&self) -> String {
+        self.symbol.to_string()
+    }
+}
+
+impl TypedEvent for VenueTicker {
+    const EVENT_TYPE: &'static str = "venue_ticker";
+    const TOPIC: &'static str = topics::VENUE_TICKERS;
+
+    fn key(&self) -> String {
+        format!("{}:{}", self.exchange, self.symbol)
+    }
+}
+
+impl TypedEvent for Candle {
+    const EVENT_TYPE: &'static str = "candle";
+    const TOPIC: &'static str = topics::CANDLES;
+
+    fn key(&self) -> String {
+        self.symbol.to_string()
+    }
+}
+
+impl TypedEvent for MarketData {
+    const EVENT_TYPE: &'static str = "market_data";
+    const TOPIC: &'static str = topics::PRICES;
+
+    fn key(&self) -> String {
+        self.symbol.to_string()
+    }
+}
+
+impl TypedEvent for SyntheticTicker {
+    const EVENT_TYPE: &'static str = "synthetic_ticker";
+    const TOPIC: &'static str = topics::PRICES;
+
+    fn key(&self) -> String {
+        self.data.symbol.to_string()
+    }
+}
+
+/// Keyed by user so a user's position updates stay in order
+impl TypedEvent for PositionUpdate {
+    const EVENT_TYPE: &'static str = "position_update";
+    const TOPIC: &'static str = topics::POSITIONS;
+
+    fn key(&self) -> String {
+        self.user_id.to_string()
+    }
+}
+
+/// Keyed by user so a user's alerts stay in order; alerts without a user
+/// are spread by alert id
+impl TypedEvent for RiskAlert {
+    const EVENT_TYPE: &'static str = "risk_alert";
+    const TOPIC: &'static str = topics::ALERTS;
+
+    fn key(&self) -> String {
+        self.user_id.unwrap_or(self.alert_id).to_string()
+    }
+}
+
+impl TypedEvent for WalletMovement {
+    const EVENT_TYPE: &'static str = "wallet_movement";
+    const TOPIC: &'static str = topics::AUDIT;
+
+    fn key(&self) -> String {
+        self.from.clone()
+    }
+}
+
+impl TypedEvent for SymbolAdded {
+    const EVENT_TYPE: &'static str = "symbol_added";
+    const TOPIC: &'static str = topics::SYMBOLS;
+
+    fn key(&self) -> String {
+        self.symbol.to_string()
+    }
+}
+
+impl TypedEvent for SymbolDelisted {
+    const EVENT_TYPE: &'static str = "symbol_delisted";
+    const TOPIC: &'static str = topics::SYMBOLS;
+
+    fn key(&self) -> String {
+        self.symbol.to_string()
+    }
+}
+
+impl TypedEvent for SymbolHalted {
+    const EVENT_TYPE: &'static str = "symbol_halted";
+    const TOPIC: &'static str = topics::SYMBOLS;
+
+    fn key(&self) -> String {
+        self.symbol.to_string()
+    }
+}
+
+impl TypedEvent for SymbolResumed {
+    const EVENT_TYPE: &'static str = "symbol_resumed";
+    const TOPIC: &'static str = topics::SYMBOLS;
+
+    fn key(&self) -> String {
+        self.symbol.to_string()
+    }
+}
+
 // ============== Kafka Topics ==============
 
 pub mod topics {
@@ -203,7 +987,118 @@ pub mod topics {
     pub const TRADES: &str = "trading.trades";
     pub const ORDER_BOOK: &str = "market.orderbook";
     pub const PRICES: &str = "market.prices";
+    pub const MIDPRICES: &str = "market.midprices";
+    pub const CANDLES: &str = "market.candles";
+    pub const VENUE_FILLS: &str = "exchange.fills";
+    pub const VENUE_TICKERS: &str = "exchange.tickers";
+    pub const ROUTING: &str = "exchange.routing";
+    pub const MARKET_QUALITY: &str = "market.quality";
+    pub const AUCTIONS: &str = "market.auctions";
+    pub const SETTLEMENT: &str = "market.settlement";
+    pub const FEES: &str = "trading.fees";
+    pub const SYMBOLS: &str = "trading.symbols";
     pub const POSITIONS: &str = "risk.positions";
     pub const ALERTS: &str = "risk.alerts";
     pub const AUDIT: &str = "audit.events";
+    pub const REFERENCE_DATA: &str = "reference.symbols";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_routes_and_sequences_per_topic() {
+        let cancelled = |order_id| OrderCancelled {
+            order_id,
+            client_order_id: "c1".to_string(),
+            symbol: Symbol::new("BTC", "USDT"),
+            reason: "user".to_string(),
+            timestamp: Utc::now(),
+        };
+
+        let order_id = Uuid::new_v4();
+        let first = Event::builder(cancelled(order_id))
+            .source("builder-test")
+            .build();
+        assert_eq!(first.topic, topics::ORDERS);
+        assert_eq!(first.key, "BTC-USDT");
+        assert_eq!(first.event.event_type, "order_cancelled");
+        assert_eq!(first.event.source, "builder-test");
+
+        let second = Event::builder(cancelled(Uuid::new_v4()))
+            .source("builder-test")
+            .build();
+        assert_eq!(second.event.sequence, first.event.sequence + 1);
+
+        // Other sources count independently
+        let other = Event::builder(cancelled(Uuid::new_v4()))
+            .source("builder-test-other")
+            .build();
+        assert_eq!(other.event.sequence, 1);
+    }
+
+    #[test]
+    fn test_order_lifecycle_events_share_the_symbol_key() {
+        let symbol = Symbol::new("BTC", "USDT");
+        let order_id = Uuid::new_v4();
+
+        let keys = [
+            OrderUpdated {
+                order_id,
+                client_order_id: "c1".to_string(),
+                symbol: symbol.clone(),
+                status: OrderStatus::Rejected,
+                filled_quantity: Decimal::ZERO,
+                remaining_quantity: Decimal::ONE,
+                avg_fill_price: None,
+                fills: vec![],
+                fills_truncated: false,
+                timestamp: Utc::now(),
+            }
+            .key(),
+            OrderRejected {
+                order_id,
+                client_order_id: "c1".to_string(),
+                symbol: symbol.clone(),
+                reason: "invalid".to_string(),
+                code: "INVALID_ORDER".to_string(),
+                timestamp: Utc::now(),
+            }
+            .key(),
+            // Other orders of the symbol share its partition
+            OrderCancelled {
+                order_id: Uuid::new_v4(),
+                client_order_id: "c2".to_string(),
+                symbol: symbol.clone(),
+                reason: "user".to_string(),
+                timestamp: Utc::now(),
+            }
+            .key(),
+        ];
+
+        assert!(keys.iter().all(|key| *key == symbol.to_string()));
+    }
+
+    #[test]
+    fn test_command_envelope_is_tagged_and_keyed_by_symbol() {
+        let symbol = Symbol::new("ETH", "USDT");
+        let command = OrderCommandEnvelope::Cancel(OrderCancelRequested {
+            order_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            symbol: symbol.clone(),
+            timestamp: Utc::now(),
+        });
+
+        let outbound = Event::builder(command).source("builder-test").build();
+        assert_eq!(outbound.key, symbol.to_string());
+        assert_eq!(outbound.event.event_type, "order_command");
+
+        let json: serde_json::Value = serde_json::from_str(&outbound.to_json().unwrap()).unwrap();
+        assert_eq!(json["payload"]["command"], "cancel");
+
+        let decoded: OrderCommandEnvelope =
+            serde_json::from_value(json["payload"].clone()).unwrap();
+        assert!(matches!(decoded, OrderCommandEnvelope::Cancel(r) if r.symbol == symbol));
+    }
 }
@@ -0,0 +1,226 @@
+//! Human-Readable Ids
+//!
+//! Ids are UUIDs on the wire and in storage, and `Display` on `Uuid` is
+//! always the hyphenated lowercase form. `ShortId` is the same 128 bits in
+//! Crockford base32: 26 characters, case-insensitive, free of the easily
+//! confused I, L, O and U, and sorted like the UUID's bytes. Conversion is
+//! lossless both ways, so either form names the same order and the UUID
+//! never changes.
+//!
+//! API path parameters and request fields can accept both forms:
+//!
+//! ```ignore
+//! Path(AnyId(order_id)): Path<AnyId>,
+//!
+//! #[serde(with = "common::ids::flex")]
+//! order_id: Uuid,
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+use uuid::Uuid;
+
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Characters in a short id; 26 × 5 bits covers 128 with two to spare
+const SHORT_LEN: usize = 26;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("invalid id `{0}`: expected a UUID or 26-character short id")]
+pub struct IdParseError(String);
+
+/// Crockford base32 form of a UUID, e.g. `01ARZ3NDEKTSV4RRFFQ69G5FAV`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShortId(Uuid);
+
+impl ShortId {
+    pub fn uuid(&self) -> Uuid {
+        self.0
+    }
+}
+
+impl From<Uuid> for ShortId {
+    fn from(uuid: Uuid) -> Self {
+        Self(uuid)
+    }
+}
+
+impl From<ShortId> for Uuid {
+    fn from(id: ShortId) -> Self {
+        id.0
+    }
+}
+
+impl fmt::Display for ShortId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self.0.as_u128();
+        let mut out = [0u8; SHORT_LEN];
+        for (i, c) in out.iter_mut().enumerate() {
+            let shift = 5 * (SHORT_LEN - 1 - i);
+            *c = ALPHABET[((value >> shift) & 0x1f) as usize];
+        }
+        f.write_str(std::str::from_utf8(&out).expect("ASCII alphabet"))
+    }
+}
+
+/// Value of a base32 digit; I and L read as 1, O as 0
+fn digit(c: u8) -> Option<u128> {
+    let value = match c.to_ascii_uppercase() {
+        b'I' | b'L' => 1,
+        b'O' => 0,
+        c => ALPHABET.iter().position(|&a| a == c)?,
+    };
+    Some(value as u128)
+}
+
+impl FromStr for ShortId {
+    type Err = IdParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || IdParseError(s.to_string());
+        if s.len() != SHORT_LEN {
+            return Err(invalid());
+        }
+        // The leading digit carries only the top 3 bits
+        if digit(s.as_bytes()[0]).ok_or_else(invalid)? > 7 {
+            return Err(invalid());
+        }
+
+        let mut value = 0u128;
+        for c in s.bytes() {
+            value = (value << 5) | digit(c).ok_or_else(invalid)?;
+        }
+        Ok(Self(Uuid::from_u128(value)))
+    }
+}
+
+impl Serialize for ShortId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ShortId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        parse_id(&String::deserialize(deserializer)?)
+            .map(ShortId)
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Parse an id given as a UUID or a short id
+pub fn parse_id(s: &str) -> Result<Uuid, IdParseError> {
+    let s = s.trim();
+    Uuid::parse_str(s)
+        .or_else(|_| ShortId::from_str(s).map(Uuid::from))
+        .map_err(|_| IdParseError(s.to_string()))
+}
+
+/// UUID accepted in either form, e.g. as an API path parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AnyId(pub Uuid);
+
+impl<'de> Deserialize<'de> for AnyId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        flex::deserialize(deserializer).map(AnyId)
+    }
+}
+
+/// UUID from either form, serialized as a UUID
+pub mod flex {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &Uuid, serializer: S) -> Result<S::Ok, S::Error> {
+        value.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Uuid, D::Error> {
+        parse_id(&String::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_id_round_trips() {
+        for uuid in [Uuid::nil(), Uuid::from_u128(u128::MAX), Uuid::new_v4()] {
+            let short = ShortId::from(uuid).to_string();
+            assert_eq!(short.len(), SHORT_LEN);
+            assert_eq!(short.parse::<ShortId>().unwrap().uuid(), uuid);
+            assert_eq!(parse_id(&short).unwrap(), uuid);
+            assert_eq!(parse_id(&short.to_lowercase()).unwrap(), uuid);
+        }
+        assert_eq!(
+            ShortId::from(Uuid::from_u128(u128::MAX)).to_string(),
+            "7ZZZZZZZZZZZZZZZZZZZZZZZZZ"
+        );
+    }
+
+    #[test]
+    fn test_short_ids_sort_like_uuids() {
+        let mut uuids: Vec<Uuid> = (0..50).map(|_| Uuid::new_v4()).collect();
+        let mut shorts: Vec<String> = uuids
+            .iter()
+            .map(|u| ShortId::from(*u).to_string())
+            .collect();
+        uuids.sort();
+        shorts.sort();
+
+        let sorted: Vec<Uuid> = shorts.iter().map(|s| parse_id(s).unwrap()).collect();
+        assert_eq!(sorted, uuids);
+    }
+
+    #[test]
+    fn test_parse_id_accepts_either_form() {
+        let uuid = Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+        let short = ShortId::from(uuid).to_string();
+
+        assert_eq!(
+            parse_id("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap(),
+            uuid
+        );
+        assert_eq!(parse_id(&short).unwrap(), uuid);
+
+        // Ambiguous letters read as the digits they resemble
+        let confusable = short.replace('1', "l").replace('0', "O");
+        assert_eq!(parse_id(&confusable).unwrap(), uuid);
+
+        assert!(parse_id("not-an-id").is_err());
+        // Overflows 128 bits
+        assert!(parse_id("8ZZZZZZZZZZZZZZZZZZZZZZZZZ").is_err());
+        assert!(parse_id("7ZZZZZZZZZZZZZZZZZZZZZZZZU").is_err());
+    }
+
+    #[test]
+    fn test_serde_forms() {
+        #[derive(Serialize, Deserialize)]
+        struct Request {
+            #[serde(with = "flex")]
+            order_id: Uuid,
+        }
+
+        let uuid = Uuid::new_v4();
+        let short = ShortId::from(uuid);
+
+        let request: Request =
+            serde_json::from_value(serde_json::json!({ "order_id": short.to_string() })).unwrap();
+        assert_eq!(request.order_id, uuid);
+        // Always written back as a UUID
+        assert_eq!(
+            serde_json::to_value(&request).unwrap()["order_id"],
+            uuid.to_string()
+        );
+
+        assert_eq!(
+            serde_json::to_value(short).unwrap(),
+            serde_json::json!(short.to_string())
+        );
+        let AnyId(parsed) = serde_json::from_value(serde_json::json!(uuid.to_string())).unwrap();
+        assert_eq!(parsed, uuid);
+    }
+}
@@ -0,0 +1,270 @@
+//! Account Ledger
+//!
+//! Per-user balances by asset, split into funds available to trade and
+//! funds locked by open orders. Orders lock funds when they are accepted,
+//! trades pay out of the paying order's own lock and credit the
+//! counterparty's available balance, and whatever an order did not use is
+//! released when it leaves the book. A payment beyond the order's lock
+//! comes out of the available balance, never out of other orders' locks.
+
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::TradingError;
+use crate::types::{Side, Trade};
+
+/// One user's holding of one asset
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Balance {
+    #[serde(with = "rust_decimal::serde::str")]
+    pub available: Decimal,
+
+    /// Held by open orders
+    #[serde(with = "rust_decimal::serde::str")]
+    pub locked: Decimal,
+}
+
+impl Balance {
+    pub fn total(&self) -> Decimal {
+        self.available + self.locked
+    }
+}
+
+/// A balance with its owner, as listed and snapshotted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountBalance {
+    pub user_id: Uuid,
+    pub asset: String,
+
+    #[serde(flatten)]
+    pub balance: Balance,
+}
+
+/// Fees each side of a trade pays, in the quote asset
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TradeFees {
+    pub buyer: Decimal,
+    pub seller: Decimal,
+}
+
+impl TradeFees {
+    /// Only the taker pays
+    pub fn taker(trade: &Trade, fee: Decimal) -> Self {
+        match trade.taker_side {
+            Side::Buy => Self {
+                buyer: fee,
+                seller: Decimal::ZERO,
+            },
+            Side::Sell => Self {
+                buyer: Decimal::ZERO,
+                seller: fee,
+            },
+        }
+    }
+}
+
+/// Part of each side's payment for a trade drawn from its order's lock
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TradeLocks {
+    pub buyer: Decimal,
+    pub seller: Decimal,
+}
+
+/// Balances of every user and asset
+#[derive(Debug, Clone, Default)]
+pub struct Ledger {
+    balances: HashMap<(Uuid, String), Balance>,
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn restore(balances: &[AccountBalance]) -> Self {
+        Self {
+            balances: balances
+                .iter()
+                .map(|b| ((b.user_id, b.asset.clone()), b.balance))
+                .collect(),
+        }
+    }
+
+    pub fn balance(&self, user_id: Uuid, asset: &str) -> Balance {
+        self.balances
+            .get(&(user_id, asset.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// A user's balances, by asset
+    pub fn user_balances(&self, user_id: Uuid) -> Vec<AccountBalance> {
+        let mut balances: Vec<AccountBalance> =
+            self.entries().filter(|b| b.user_id == user_id).collect();
+        balances.sort_by(|a, b| a.asset.cmp(&b.asset));
+        balances
+    }
+
+    /// Every balance, by user and asset
+    pub fn balances(&self) -> Vec<AccountBalance> {
+        let mut balances: Vec<AccountBalance> = self.entries().collect();
+        balances.sort_by(|a, b| (a.user_id, &a.asset).cmp(&(b.user_id, &b.asset)));
+        balances
+    }
+
+    fn entries(&self) -> impl Iterator<Item = AccountBalance> + '_ {
+        self.balances
+            .iter()
+            .map(|((user_id, asset), balance)| AccountBalance {
+                user_id: *user_id,
+                asset: asset.clone(),
+                balance: *balance,
+            })
+    }
+
+    fn entry(&mut self, user_id: Uuid, asset: &str) -> &mut Balance {
+        self.balances
+            .entry((user_id, asset.to_string()))
+            .or_default()
+    }
+
+    pub fn deposit(&mut self, user_id: Uuid, asset: &str, amount: Decimal) -> Balance {
+        let balance = self.entry(user_id, asset);
+        balance.available += amount;
+        *balance
+    }
+
+    /// Take `amount` out of the available balance
+    pub fn withdraw(
+        &mut self,
+        user_id: Uuid,
+        asset: &str,
+        amount: Decimal,
+    ) -> Result<Balance, TradingError> {
+        let balance = self.entry(user_id, asset);
+        check_available(balance, amount)?;
+        balance.available -= amount;
+        Ok(*balance)
+    }
+
+    /// Lock `amount` for an order
+    pub fn reserve(
+        &mut self,
+        user_id: Uuid,
+        asset: &str,
+        amount: Decimal,
+    ) -> Result<(), TradingError> {
+        let balance = self.entry(user_id, asset);
+        check_available(balance, amount)?;
+        balance.available -= amount;
+        balance.locked += amount;
+        Ok(())
+    }
+
+    /// Unlock up to `amount`
+    pub fn release(&mut self, user_id: Uuid, asset: &str, amount: Decimal) {
+        let balance = self.entry(user_id, asset);
+        let amount = amount.min(balance.locked);
+        balance.locked -= amount;
+        balance.available += amount;
+    }
+
+    /// Pay `amount`, up to `locked` of it out of the paying order's lock
+    /// and the rest out of the available balance
+    fn debit(&mut self, user_id: Uuid, asset: &str, amount: Decimal, locked: Decimal) {
+        let balance = self.entry(user_id, asset);
+        let locked = locked.min(amount).min(balance.locked);
+        balance.locked -= locked;
+        balance.available -= amount - locked;
+    }
+
+    /// Move a trade's assets between buyer and seller, net of fees, paying
+    /// out of the orders' locks first
+    pub fn settle(&mut self, trade: &Trade, fees: TradeFees, locks: TradeLocks) {
+        let (buyer, seller) = match trade.taker_side {
+            Side::Buy => (trade.taker_user_id, trade.maker_user_id),
+            Side::Sell => (trade.maker_user_id, trade.taker_user_id),
+        };
+        let (base, quote) = (trade.symbol.base(), trade.symbol.quote());
+
+        self.debit(buyer, quote, trade.quote_quantity + fees.buyer, locks.buyer);
+        self.entry(buyer, base).available += trade.quantity;
+        self.debit(seller, base, trade.quantity, locks.seller);
+        self.entry(seller, quote).available += trade.quote_quantity - fees.seller;
+    }
+}
+
+fn check_available(balance: &Balance, amount: Decimal) -> Result<(), TradingError> {
+    if balance.available < amount {
+        return Err(TradingError::InsufficientBalance {
+            required: amount.to_string(),
+            available: balance.available.to_string(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Symbol;
+    use chrono::Utc;
+
+    #[test]
+    fn test_reserve_settle_release() {
+        let buyer = Uuid::new_v4();
+        let seller = Uuid::new_v4();
+        let mut ledger = Ledger::new();
+        ledger.deposit(buyer, "USDT", Decimal::from(1000));
+        ledger.deposit(seller, "BTC", Decimal::from(2));
+
+        assert!(matches!(
+            ledger.reserve(buyer, "USDT", Decimal::from(1001)),
+            Err(TradingError::InsufficientBalance { .. })
+        ));
+        ledger.reserve(buyer, "USDT", Decimal::from(1000)).unwrap();
+        ledger.reserve(seller, "BTC", Decimal::ONE).unwrap();
+
+        // The buyer takes 1 BTC at 900 and pays a fee of 1
+        let trade = Trade {
+            id: Uuid::new_v4(),
+            trade_id: 1,
+            symbol: Symbol::new("BTC", "USDT"),
+            maker_order_id: Uuid::new_v4(),
+            maker_user_id: seller,
+            taker_order_id: Uuid::new_v4(),
+            taker_user_id: buyer,
+            price: Decimal::from(900),
+            quantity: Decimal::ONE,
+            quote_quantity: Decimal::from(900),
+            taker_side: Side::Buy,
+            executed_at: Utc::now(),
+        };
+        let locks = TradeLocks {
+            buyer: Decimal::from(901),
+            seller: Decimal::ONE,
+        };
+        ledger.settle(&trade, TradeFees::taker(&trade, Decimal::ONE), locks);
+
+        let usdt = ledger.balance(buyer, "USDT");
+        assert_eq!(usdt.locked, Decimal::from(99));
+        assert_eq!(usdt.available, Decimal::ZERO);
+        assert_eq!(ledger.balance(buyer, "BTC").available, Decimal::ONE);
+        assert_eq!(ledger.balance(seller, "BTC").total(), Decimal::ONE);
+        assert_eq!(ledger.balance(seller, "USDT").available, Decimal::from(900));
+
+        ledger.release(buyer, "USDT", Decimal::from(500));
+        assert_eq!(
+            ledger.balance(buyer, "USDT"),
+            Balance {
+                available: Decimal::from(99),
+                locked: Decimal::ZERO,
+            }
+        );
+        assert!(ledger.withdraw(buyer, "USDT", Decimal::from(100)).is_err());
+        assert_eq!(ledger.user_balances(buyer).len(), 2);
+    }
+}
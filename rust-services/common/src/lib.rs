@@ -3,10 +3,25 @@
 //! This crate provides shared data structures, error types, and utilities
 //! used across all microservices in the trading platform.
 
+pub mod alerts;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod decimal;
 pub mod error;
 pub mod events;
+pub mod ids;
+pub mod ledger;
+pub mod refdata;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;
+pub mod time;
 pub mod types;
 
+pub use alerts::*;
 pub use error::*;
 pub use events::*;
+pub use ids::{AnyId, ShortId};
+pub use ledger::*;
+pub use refdata::*;
+pub use time::*;
 pub use types::*;
@@ -0,0 +1,303 @@
+//! Symbol Reference Data
+//!
+//! The reference-data service owns the canonical, versioned symbol
+//! metadata. Every other service keeps a [`SymbolRegistry`] seeded from
+//! the service's snapshot API and kept current by
+//! [`ReferenceDataChanged`] events on `topics::REFERENCE_DATA`.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::events::ReferenceDataChanged;
+use crate::types::{Symbol, SymbolInfo};
+
+/// Full reference data at a version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferenceSnapshot {
+    pub version: u64,
+    pub symbols: Vec<SymbolInfo>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Result of applying a change event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApplyOutcome {
+    Applied,
+    /// Already at or past this version
+    Stale,
+    /// A change was missed; reload the snapshot
+    Gap,
+}
+
+#[derive(Debug, Default)]
+struct RegistryState {
+    version: u64,
+    symbols: HashMap<String, SymbolInfo>,
+}
+
+/// Versioned in-memory symbol metadata
+#[derive(Debug, Default)]
+pub struct SymbolRegistry {
+    state: RwLock<RegistryState>,
+}
+
+impl SymbolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry owned by the reference-data service, starting at `version`
+    pub fn with_symbols(version: u64, symbols: Vec<SymbolInfo>) -> Self {
+        let registry = Self::new();
+        registry.load_snapshot(ReferenceSnapshot {
+            version,
+            symbols,
+            timestamp: Utc::now(),
+        });
+        registry
+    }
+
+    pub fn version(&self) -> u64 {
+        self.state.read().unwrap().version
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.read().unwrap().symbols.is_empty()
+    }
+
+    pub fn get(&self, symbol: &Symbol) -> Option<SymbolInfo> {
+        self.state
+            .read()
+            .unwrap()
+            .symbols
+            .get(&symbol.to_string())
+            .cloned()
+    }
+
+    pub fn all(&self) -> Vec<SymbolInfo> {
+        self.state
+            .read()
+            .unwrap()
+            .symbols
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    pub fn snapshot(&self) -> ReferenceSnapshot {
+        let state = self.state.read().unwrap();
+        ReferenceSnapshot {
+            version: state.version,
+            symbols: state.symbols.values().cloned().collect(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Replace all symbols with a snapshot
+    pub fn load_snapshot(&self, snapshot: ReferenceSnapshot) {
+        let mut state = self.state.write().unwrap();
+        state.version = snapshot.version;
+        state.symbols = snapshot
+            .symbols
+            .into_iter()
+            .map(|info| (info.symbol.to_string(), info))
+            .collect();
+    }
+
+    /// Apply a change event from the reference-data service
+    pub fn apply_change(&self, change: &ReferenceDataChanged) -> ApplyOutcome {
+        let mut state = self.state.write().unwrap();
+
+        if change.version <= state.version {
+            return ApplyOutcome::Stale;
+        }
+        if change.previous_version != state.version {
+            return ApplyOutcome::Gap;
+        }
+
+        for symbol in &change.removed {
+            state.symbols.remove(&symbol.to_string());
+        }
+        for info in &change.upserted {
+            state.symbols.insert(info.symbol.to_string(), info.clone());
+        }
+        state.version = change.version;
+
+        ApplyOutcome::Applied
+    }
+
+    /// Mutate the registry and describe the change for publication
+    ///
+    /// Used by the reference-data service, the only writer.
+    pub fn commit(&self, upserted: Vec<SymbolInfo>, removed: Vec<Symbol>) -> ReferenceDataChanged {
+        let mut state = self.state.write().unwrap();
+
+        let change = ReferenceDataChanged {
+            version: state.version + 1,
+            previous_version: state.version,
+            upserted,
+            removed,
+            timestamp: Utc::now(),
+        };
+
+        for symbol in &change.removed {
+            state.symbols.remove(&symbol.to_string());
+        }
+        for info in &change.upserted {
+            state.symbols.insert(info.symbol.to_string(), info.clone());
+        }
+        state.version = change.version;
+
+        change
+    }
+}
+
+/// Keeps a registry in sync with the reference-data service
+#[cfg(feature = "refdata-client")]
+pub mod client {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use rdkafka::{
+        consumer::{Consumer, StreamConsumer},
+        ClientConfig, Message,
+    };
+    use tokio_stream::StreamExt;
+    use tracing::{info, warn};
+
+    use super::{ApplyOutcome, ReferenceSnapshot, SymbolRegistry};
+    use crate::events::{topics, Event, ReferenceDataChanged};
+
+    const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+    async fn fetch_snapshot(
+        http: &reqwest::Client,
+        service_url: &str,
+    ) -> reqwest::Result<ReferenceSnapshot> {
+        http.get(format!("{service_url}/snapshot"))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    /// Load the snapshot, retrying until the service answers
+    async fn reload(http: &reqwest::Client, service_url: &str, registry: &SymbolRegistry) {
+        loop {
+            match fetch_snapshot(http, service_url).await {
+                Ok(snapshot) => {
+                    info!(
+                        version = snapshot.version,
+                        symbols = snapshot.symbols.len(),
+                        "Reference data snapshot loaded"
+                    );
+                    registry.load_snapshot(snapshot);
+                    return;
+                }
+                Err(e) => {
+                    warn!("Failed to load reference data snapshot: {}", e);
+                    tokio::time::sleep(RETRY_INTERVAL).await;
+                }
+            }
+        }
+    }
+
+    /// Seed `registry` from the snapshot API and apply change events
+    ///
+    /// Each caller gets its own consumer group so every instance sees
+    /// every change.
+    pub async fn run_sync(
+        registry: Arc<SymbolRegistry>,
+        service_url: String,
+        kafka_brokers: String,
+        group_prefix: String,
+    ) -> anyhow::Result<()> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()?;
+
+        let consumer: StreamConsumer = ClientConfig::new()
+            .set("bootstrap.servers", &kafka_brokers)
+            .set(
+                "group.id",
+                format!("{group_prefix}-refdata-{}", uuid::Uuid::new_v4()),
+            )
+            .set("enable.auto.commit", "false")
+            .set("auto.offset.reset", "latest")
+            .create()?;
+        consumer.subscribe(&[topics::REFERENCE_DATA])?;
+
+        // Changes racing the snapshot show up as a gap and trigger a reload
+        reload(&http, &service_url, &registry).await;
+
+        let mut stream = consumer.stream();
+        while let Some(message) = stream.next().await {
+            let msg = match message {
+                Ok(msg) => msg,
+                Err(e) => {
+                    warn!("Kafka error: {}", e);
+                    continue;
+                }
+            };
+            let Some(payload) = msg.payload() else {
+                continue;
+            };
+
+            let change: Event<ReferenceDataChanged> = match serde_json::from_slice(payload) {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!("Invalid reference data event: {}", e);
+                    continue;
+                }
+            };
+
+            match registry.apply_change(&change.payload) {
+                ApplyOutcome::Applied => {
+                    info!(version = change.payload.version, "Reference data updated");
+                }
+                ApplyOutcome::Stale => {}
+                ApplyOutcome::Gap => {
+                    warn!(
+                        local = registry.version(),
+                        previous = change.payload.previous_version,
+                        "Reference data gap, reloading snapshot"
+                    );
+                    reload(&http, &service_url, &registry).await;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_applies_changes_in_order() {
+        let eth = SymbolInfo::from_symbol(&Symbol::new("ETH", "USDT"));
+        let source = SymbolRegistry::with_symbols(100, vec![eth.clone()]);
+        let replica = SymbolRegistry::new();
+        replica.load_snapshot(source.snapshot());
+
+        let sol = SymbolInfo::from_symbol(&Symbol::new("SOL", "USDT"));
+        let first = source.commit(vec![sol], vec![]);
+        let second = source.commit(vec![], vec![eth.symbol.clone()]);
+
+        // Out of order: the second change does not apply on top of 100
+        assert_eq!(replica.apply_change(&second), ApplyOutcome::Gap);
+        assert_eq!(replica.apply_change(&first), ApplyOutcome::Applied);
+        assert_eq!(replica.apply_change(&first), ApplyOutcome::Stale);
+        assert_eq!(replica.apply_change(&second), ApplyOutcome::Applied);
+
+        assert_eq!(replica.version(), 102);
+        assert!(replica.get(&Symbol::new("ETH", "USDT")).is_none());
+        assert!(replica.get(&Symbol::new("SOL", "USDT")).is_some());
+    }
+}
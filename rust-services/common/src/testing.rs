@@ -0,0 +1,151 @@
+//! Test Fixtures
+//!
+//! Builders for the shared types tests construct over and over. Available
+//! to this crate's tests and, with the `test-utils` feature, to tests of
+//! the crates depending on it.
+//!
+//! ```ignore
+//! let order = Order::builder().side(Side::Sell).price(2000).quantity(2).build();
+//! ```
+
+use chrono::Utc;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::{
+    Order, OrderStatus, OrderType, SelfTradePrevention, Side, Symbol, TimeInForce, TriggerSource,
+};
+
+/// Builds an order: by default a pending GTC limit buy of 1 ETH-USDT at
+/// 2000 by a new user
+#[derive(Debug, Clone)]
+pub struct OrderBuilder(Order);
+
+impl Order {
+    pub fn builder() -> OrderBuilder {
+        OrderBuilder::default()
+    }
+}
+
+impl Default for OrderBuilder {
+    fn default() -> Self {
+        let now = Utc::now();
+        Self(Order {
+            id: Uuid::new_v4(),
+            client_order_id: "test".to_string(),
+            user_id: Uuid::new_v4(),
+            symbol: Symbol::new("ETH", "USDT"),
+            side: Side::Buy,
+            order_type: OrderType::Limit,
+            time_in_force: TimeInForce::GTC,
+            status: OrderStatus::Pending,
+            price: Some(Decimal::new(2000, 0)),
+            stop_price: None,
+            trigger_source: None,
+            quantity: Decimal::ONE,
+            filled_quantity: Decimal::ZERO,
+            remaining_quantity: Decimal::ONE,
+            avg_fill_price: None,
+            sequence: 0,
+            created_at: now,
+            updated_at: now,
+            expire_at: None,
+            display_quantity: None,
+            self_trade_prevention: SelfTradePrevention::default(),
+        })
+    }
+}
+
+impl OrderBuilder {
+    pub fn client_order_id(mut self, client_order_id: &str) -> Self {
+        self.0.client_order_id = client_order_id.to_string();
+        self
+    }
+
+    pub fn user(mut self, user_id: Uuid) -> Self {
+        self.0.user_id = user_id;
+        self
+    }
+
+    pub fn symbol(mut self, symbol: Symbol) -> Self {
+        self.0.symbol = symbol;
+        self
+    }
+
+    pub fn side(mut self, side: Side) -> Self {
+        self.0.side = side;
+        self
+    }
+
+    pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.0.time_in_force = time_in_force;
+        self
+    }
+
+    pub fn status(mut self, status: OrderStatus) -> Self {
+        self.0.status = status;
+        self
+    }
+
+    /// Limit price
+    pub fn price(mut self, price: impl Into<Decimal>) -> Self {
+        self.0.price = Some(price.into());
+        self
+    }
+
+    /// Make it a stop-market order triggering at `stop_price` on `source`
+    pub fn stop_market(mut self, stop_price: impl Into<Decimal>, source: TriggerSource) -> Self {
+        self.0.order_type = OrderType::StopMarket;
+        self.0.price = None;
+        self.0.stop_price = Some(stop_price.into());
+        self.0.trigger_source = Some(source);
+        self
+    }
+
+    /// Total quantity, all of it unfilled
+    pub fn quantity(mut self, quantity: impl Into<Decimal>) -> Self {
+        let quantity = quantity.into();
+        self.0.quantity = quantity;
+        self.0.filled_quantity = Decimal::ZERO;
+        self.0.remaining_quantity = quantity;
+        self.0.avg_fill_price = None;
+        self
+    }
+
+    /// Fill `quantity` of the order at an average of `avg_price`
+    pub fn filled(mut self, quantity: impl Into<Decimal>, avg_price: impl Into<Decimal>) -> Self {
+        let quantity = quantity.into();
+        self.0.filled_quantity = quantity;
+        self.0.remaining_quantity = self.0.quantity - quantity;
+        self.0.avg_fill_price = Some(avg_price.into());
+        self
+    }
+
+    pub fn build(self) -> Order {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_builder() {
+        let order = Order::builder()
+            .side(Side::Sell)
+            .price(101)
+            .quantity(2)
+            .filled(1, Decimal::new(1015, 1))
+            .build();
+        assert_eq!(order.price, Some(Decimal::new(101, 0)));
+        assert_eq!(order.remaining_quantity, Decimal::ONE);
+        assert_eq!(order.avg_fill_price, Some(Decimal::new(1015, 1)));
+
+        let stop = Order::builder()
+            .stop_market(100, TriggerSource::MarkPrice)
+            .build();
+        assert!(stop.is_stop());
+        assert_eq!(stop.price, None);
+    }
+}
@@ -3,11 +3,15 @@
 //! Uses rust_decimal for exact decimal arithmetic - critical for
 //! financial calculations where floating point errors are unacceptable.
 
+use std::collections::{BTreeMap, HashMap};
+
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::error::TradingError;
+
 /// Trading pair symbol (e.g., "ETH-USDT")
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Symbol(pub String);
@@ -32,6 +36,239 @@ impl std::fmt::Display for Symbol {
     }
 }
 
+/// Trading status of a symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolStatus {
+    Trading,
+    Halted,
+    Delisted,
+    Unknown,
+}
+
+/// Reference metadata for a trading pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolInfo {
+    pub symbol: Symbol,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub status: SymbolStatus,
+
+    /// Decimal places used to display prices
+    pub price_precision: u32,
+
+    /// Decimal places used to display quantities
+    pub quantity_precision: u32,
+
+    /// Minimum price increment
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub tick_size: Option<Decimal>,
+
+    /// Minimum quantity increment
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub lot_size: Option<Decimal>,
+
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub min_quantity: Option<Decimal>,
+
+    /// Symbol as named on each external venue (e.g. "binance" -> "ETHUSDT")
+    #[serde(default)]
+    pub venues: HashMap<String, String>,
+}
+
+impl SymbolInfo {
+    /// Metadata derived from the symbol alone, for pairs without reference data
+    pub fn from_symbol(symbol: &Symbol) -> Self {
+        Self {
+            symbol: symbol.clone(),
+            base_asset: symbol.base().to_string(),
+            quote_asset: symbol.quote().to_string(),
+            status: SymbolStatus::Unknown,
+            price_precision: 8,
+            quantity_precision: 8,
+            tick_size: None,
+            lot_size: None,
+            min_quantity: None,
+            venues: HashMap::new(),
+        }
+    }
+
+    /// Check an order's price and quantity against trading status and
+    /// tick/lot constraints
+    pub fn validate_order(
+        &self,
+        price: Option<Decimal>,
+        quantity: Decimal,
+    ) -> Result<(), TradingError> {
+        if matches!(self.status, SymbolStatus::Halted | SymbolStatus::Delisted) {
+            return Err(TradingError::MarketClosed);
+        }
+        self.validate_increments(price, quantity)
+    }
+
+    /// Check an order's price and quantity against tick/lot constraints
+    /// only, whatever the trading status
+    pub fn validate_increments(
+        &self,
+        price: Option<Decimal>,
+        quantity: Decimal,
+    ) -> Result<(), TradingError> {
+        if let (Some(price), Some(tick)) = (price, self.tick_size) {
+            if !tick.is_zero() && !(price % tick).is_zero() {
+                return Err(TradingError::InvalidOrder(format!(
+                    "Price {price} is not a multiple of tick size {tick}"
+                )));
+            }
+        }
+
+        if let Some(lot) = self.lot_size {
+            if !lot.is_zero() && !(quantity % lot).is_zero() {
+                return Err(TradingError::InvalidOrder(format!(
+                    "Quantity {quantity} is not a multiple of lot size {lot}"
+                )));
+            }
+        }
+
+        if let Some(min) = self.min_quantity {
+            if quantity < min {
+                return Err(TradingError::InvalidOrder(format!(
+                    "Quantity {quantity} is below minimum {min}"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Engine-side trading rules for a symbol
+///
+/// Applied to every order on top of reference data, so books stay on a
+/// sane price/quantity grid even when the reference-data service is not
+/// configured.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolConfig {
+    /// Minimum price increment
+    #[serde(with = "rust_decimal::serde::str")]
+    pub tick_size: Decimal,
+
+    /// Minimum quantity increment
+    #[serde(with = "rust_decimal::serde::str")]
+    pub lot_size: Decimal,
+
+    /// Minimum price * quantity
+    #[serde(default, with = "rust_decimal::serde::str")]
+    pub min_notional: Decimal,
+
+    /// Smallest taker fee an order must be able to pay, in the quote asset;
+    /// raises the minimum notional to what pays it at the taker fee rate
+    #[serde(default, with = "rust_decimal::serde::str")]
+    pub min_fee: Decimal,
+
+    /// Trigger reference for stop orders that do not choose one
+    #[serde(default)]
+    pub default_trigger: TriggerSource,
+
+    /// Trigger references stop orders may choose; empty allows all
+    #[serde(default)]
+    pub allowed_triggers: Vec<TriggerSource>,
+
+    /// Furthest a limit price may be from the last trade, in basis
+    /// points; unset uses the engine default, 0 disables the band
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_band_bps: Option<u32>,
+
+    /// Price move within the breaker window that halts the symbol, in
+    /// basis points; unset uses the engine default, 0 disables it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circuit_breaker_bps: Option<u32>,
+}
+
+impl SymbolConfig {
+    pub fn new(tick_size: Decimal, lot_size: Decimal, min_notional: Decimal) -> Self {
+        Self {
+            tick_size,
+            lot_size,
+            min_notional,
+            min_fee: Decimal::ZERO,
+            default_trigger: TriggerSource::default(),
+            allowed_triggers: Vec::new(),
+            price_band_bps: None,
+            circuit_breaker_bps: None,
+        }
+    }
+
+    /// Check an order's price and quantity against tick size, lot size
+    /// and minimum notional
+    pub fn validate_order(
+        &self,
+        price: Option<Decimal>,
+        quantity: Decimal,
+    ) -> Result<(), TradingError> {
+        if let Some(price) = price {
+            if !self.tick_size.is_zero() && !(price % self.tick_size).is_zero() {
+                return Err(TradingError::InvalidOrder(format!(
+                    "Price {price} is not a multiple of tick size {}",
+                    self.tick_size
+                )));
+            }
+        }
+
+        if !self.lot_size.is_zero() && !(quantity % self.lot_size).is_zero() {
+            return Err(TradingError::InvalidOrder(format!(
+                "Quantity {quantity} is not a multiple of lot size {}",
+                self.lot_size
+            )));
+        }
+
+        if let Some(price) = price {
+            self.validate_notional(price * quantity, 0)?;
+        }
+
+        Ok(())
+    }
+
+    /// Smallest notional an order may have when takers pay `taker_fee_bps`
+    pub fn effective_min_notional(&self, taker_fee_bps: u32) -> Decimal {
+        if self.min_fee <= Decimal::ZERO || taker_fee_bps == 0 {
+            return self.min_notional;
+        }
+        let fee_floor = self.min_fee * Decimal::from(10_000) / Decimal::from(taker_fee_bps);
+        self.min_notional.max(fee_floor)
+    }
+
+    /// Check an order's notional against the effective minimum
+    pub fn validate_notional(
+        &self,
+        notional: Decimal,
+        taker_fee_bps: u32,
+    ) -> Result<(), TradingError> {
+        let minimum = self.effective_min_notional(taker_fee_bps);
+        if notional < minimum {
+            return Err(TradingError::BelowMinNotional {
+                notional: notional.normalize().to_string(),
+                minimum: minimum.normalize().to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Trigger reference for a stop order requesting `requested`
+    pub fn resolve_trigger(
+        &self,
+        requested: Option<TriggerSource>,
+    ) -> Result<TriggerSource, TradingError> {
+        let source = requested.unwrap_or(self.default_trigger);
+        if !self.allowed_triggers.is_empty() && !self.allowed_triggers.contains(&source) {
+            return Err(TradingError::InvalidOrder(format!(
+                "Stop trigger {} is not allowed",
+                source.as_str()
+            )));
+        }
+        Ok(source)
+    }
+}
+
 /// Order side - Buy or Sell
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -59,6 +296,32 @@ pub enum OrderType {
     StopMarket,
 }
 
+/// Reference price a stop order is triggered by
+///
+/// Mark and index prices are harder to push through a stop level with a
+/// single print than the last trade.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerSource {
+    /// Price of the symbol's last trade on this engine
+    #[default]
+    LastTrade,
+    /// Conflated midprice from the data pipeline
+    MarkPrice,
+    /// Index price from the data pipeline
+    IndexPrice,
+}
+
+impl TriggerSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TriggerSource::LastTrade => "last_trade",
+            TriggerSource::MarkPrice => "mark_price",
+            TriggerSource::IndexPrice => "index_price",
+        }
+    }
+}
+
 /// Time in force - How long the order remains active
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -86,6 +349,21 @@ pub enum OrderStatus {
     Expired,
 }
 
+/// What happens when an order would trade against its owner's resting order
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelfTradePrevention {
+    /// Cancel the resting order and keep matching
+    #[default]
+    CancelMaker,
+    /// Cancel the rest of the incoming order
+    CancelTaker,
+    /// Cancel the resting order and the rest of the incoming order
+    CancelBoth,
+    /// Reduce both by the overlap and cancel whichever reaches zero
+    DecrementAndCancel,
+}
+
 /// Core Order structure
 ///
 /// Designed for high-performance order matching with:
@@ -111,6 +389,11 @@ pub struct Order {
     #[serde(with = "rust_decimal::serde::str_option")]
     pub stop_price: Option<Decimal>,
 
+    /// Reference price the stop is triggered by; the symbol's default
+    /// when not chosen
+    #[serde(default)]
+    pub trigger_source: Option<TriggerSource>,
+
     /// Original order quantity
     #[serde(with = "rust_decimal::serde::str")]
     pub quantity: Decimal,
@@ -133,6 +416,17 @@ pub struct Order {
     /// Timestamps with nanosecond precision
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+
+    /// Time after which the order must not match (GTD or upstream deadline)
+    #[serde(default)]
+    pub expire_at: Option<DateTime<Utc>>,
+
+    /// Iceberg slice shown on the book; the rest rests hidden
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub display_quantity: Option<Decimal>,
+
+    #[serde(default)]
+    pub self_trade_prevention: SelfTradePrevention,
 }
 
 impl Order {
@@ -150,12 +444,60 @@ impl Order {
         )
     }
 
+    pub fn is_stop(&self) -> bool {
+        matches!(
+            self.order_type,
+            OrderType::StopLimit | OrderType::StopMarket
+        )
+    }
+
+    /// Whether a stop order is triggered by its reference at `price`
+    ///
+    /// Buy stops trigger at or above the stop price, sell stops at or
+    /// below it.
+    pub fn stop_triggered_at(&self, price: Decimal) -> bool {
+        match (self.stop_price, self.side) {
+            (Some(stop), Side::Buy) => price >= stop,
+            (Some(stop), Side::Sell) => price <= stop,
+            (None, _) => true,
+        }
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expire_at.is_some_and(|expire_at| expire_at <= now)
+    }
+
     pub fn can_match(&self) -> bool {
         matches!(
             self.status,
             OrderStatus::Open | OrderStatus::PartiallyFilled
         )
     }
+
+    /// Check the iceberg slice, if any, against the order
+    ///
+    /// Only resting limit orders can hide quantity, and the slice must be
+    /// positive and no larger than the order.
+    pub fn validate_display_quantity(&self) -> Result<(), TradingError> {
+        let Some(display) = self.display_quantity else {
+            return Ok(());
+        };
+
+        if self.order_type != OrderType::Limit
+            || matches!(self.time_in_force, TimeInForce::IOC | TimeInForce::FOK)
+        {
+            return Err(TradingError::InvalidOrder(
+                "Display quantity requires a resting limit order".to_string(),
+            ));
+        }
+        if display <= Decimal::ZERO || display > self.quantity {
+            return Err(TradingError::InvalidOrder(format!(
+                "Display quantity {display} must be positive and at most {}",
+                self.quantity
+            )));
+        }
+        Ok(())
+    }
 }
 
 /// Trade execution record - immutable after creation
@@ -202,7 +544,7 @@ pub struct PriceLevel {
 }
 
 /// Market data snapshot
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MarketData {
     pub symbol: Symbol,
 
@@ -224,9 +566,46 @@ pub struct MarketData {
     #[serde(with = "rust_decimal::serde::str")]
     pub low_24h: Decimal,
 
+    /// Last price less the price 24 hours earlier
+    #[serde(default, with = "rust_decimal::serde::str")]
+    pub price_change_24h: Decimal,
+
+    /// `price_change_24h` as a percentage of the price 24 hours earlier
+    #[serde(default, with = "rust_decimal::serde::str")]
+    pub percent_change_24h: Decimal,
+
+    /// Trade-derived reference prices; only the data pipeline sets these
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fair_value: Option<FairValue>,
+
     pub timestamp: DateTime<Utc>,
 }
 
+impl MarketData {
+    /// Change from `open` to `last` and the same as a percentage of
+    /// `open`, or zeros without an opening price
+    pub fn price_change(open: Decimal, last: Decimal) -> (Decimal, Decimal) {
+        if open <= Decimal::ZERO || last <= Decimal::ZERO {
+            return (Decimal::ZERO, Decimal::ZERO);
+        }
+        let change = last - open;
+        (change, (change / open * Decimal::ONE_HUNDRED).round_dp(4))
+    }
+}
+
+/// Fair-value references for execution algos and risk checks
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FairValue {
+    /// Volume-weighted average price per window (e.g. `1m`, `1h`, `24h`);
+    /// windows without trades are left out
+    #[serde(default)]
+    pub vwap: BTreeMap<String, Decimal>,
+
+    /// Time-weighted average of the sampled last price over the TWAP series
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub twap: Option<Decimal>,
+}
+
 /// OHLCV Candlestick
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Candle {
@@ -252,3 +631,88 @@ pub struct Candle {
     pub close_time: DateTime<Utc>,
     pub trade_count: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_config_validation() {
+        let config = SymbolConfig::new(Decimal::new(1, 2), Decimal::new(1, 4), Decimal::new(5, 0));
+
+        assert!(config
+            .validate_order(Some(Decimal::new(200001, 2)), Decimal::new(25, 4))
+            .is_ok());
+        // Market orders skip price checks
+        assert!(config.validate_order(None, Decimal::new(1, 4)).is_ok());
+
+        let dust_price = config.validate_order(Some(Decimal::new(2000001, 3)), Decimal::ONE);
+        assert!(matches!(dust_price, Err(TradingError::InvalidOrder(_))));
+        let dust_quantity = config.validate_order(Some(Decimal::new(2000, 0)), Decimal::new(1, 5));
+        assert!(matches!(dust_quantity, Err(TradingError::InvalidOrder(_))));
+        let below_notional = config.validate_order(Some(Decimal::new(2000, 0)), Decimal::new(1, 4));
+        assert!(matches!(
+            below_notional,
+            Err(TradingError::BelowMinNotional { .. })
+        ));
+    }
+
+    #[test]
+    fn test_min_fee_raises_min_notional() {
+        let mut config = SymbolConfig::new(Decimal::ONE, Decimal::ONE, Decimal::new(5, 0));
+        assert_eq!(config.effective_min_notional(10), Decimal::new(5, 0));
+
+        // 0.02 at 10 bps takes a notional of 20
+        config.min_fee = Decimal::new(2, 2);
+        assert_eq!(config.effective_min_notional(10), Decimal::new(20, 0));
+        assert_eq!(config.effective_min_notional(0), Decimal::new(5, 0));
+
+        let err = config
+            .validate_notional(Decimal::new(19, 0), 10)
+            .unwrap_err();
+        assert_eq!(err.code(), "MIN_NOTIONAL");
+        assert_eq!(err.to_string(), "Notional 19 is below minimum 20");
+        assert!(config.validate_notional(Decimal::new(20, 0), 10).is_ok());
+    }
+
+    #[test]
+    fn test_stop_trigger_resolution() {
+        let mut config = SymbolConfig::new(Decimal::ONE, Decimal::ONE, Decimal::ZERO);
+        assert_eq!(
+            config.resolve_trigger(None).unwrap(),
+            TriggerSource::LastTrade
+        );
+
+        config.default_trigger = TriggerSource::MarkPrice;
+        config.allowed_triggers = vec![TriggerSource::MarkPrice, TriggerSource::IndexPrice];
+        assert_eq!(
+            config.resolve_trigger(None).unwrap(),
+            TriggerSource::MarkPrice
+        );
+        assert_eq!(
+            config
+                .resolve_trigger(Some(TriggerSource::IndexPrice))
+                .unwrap(),
+            TriggerSource::IndexPrice
+        );
+        assert!(config
+            .resolve_trigger(Some(TriggerSource::LastTrade))
+            .is_err());
+    }
+
+    #[test]
+    fn test_price_change() {
+        assert_eq!(
+            MarketData::price_change(Decimal::new(200, 0), Decimal::new(210, 0)),
+            (Decimal::new(10, 0), Decimal::new(5, 0))
+        );
+        assert_eq!(
+            MarketData::price_change(Decimal::new(3, 0), Decimal::new(2, 0)),
+            (Decimal::new(-1, 0), Decimal::new(-333333, 4))
+        );
+        assert_eq!(
+            MarketData::price_change(Decimal::ZERO, Decimal::new(2, 0)),
+            (Decimal::ZERO, Decimal::ZERO)
+        );
+    }
+}
@@ -2,6 +2,11 @@
 //!
 //! Aggregates trades into OHLCV candles and maintains
 //! real-time price statistics.
+//!
+//! A candle closes when a trade arrives in a later interval, or once its
+//! interval has ended and the close grace has passed without one. Closed
+//! candles are stored in Redis and published to `market.candles`; trades
+//! for an interval that already closed are dropped from the candles.
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -9,14 +14,37 @@ use std::time::Duration;
 
 use chrono::{DateTime, Timelike, Utc};
 use dashmap::DashMap;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::ClientConfig;
 use rust_decimal::Decimal;
 use tokio::time;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::cache::RedisCache;
-use common::{Candle, MarketData, Symbol, Trade};
+use crate::config::Config;
+use crate::enrichment::{EnrichedTrade, TradeEnricher};
+use crate::fairvalue::{FairValueSettings, FairValueTracker, TwapPoint, TWAP_SAMPLE_INTERVAL};
+use crate::midprice::MidpriceConflator;
+use crate::notifications::FillNotification;
+use crate::rolling::RollingWindow;
+use crate::settlement::SettlementTracker;
+use crate::stream::StreamHub;
+use common::{events::Event, Candle, MarketData, SharedClock, Symbol, Trade};
+
+/// Candle intervals built for every symbol
+pub const CANDLE_INTERVALS: &[&str] = &["1m", "5m", "15m", "1h", "4h", "1d"];
+
+/// How often candles are checked for intervals that ended without a trade
+const CLOSE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often trades older than 24 hours are dropped from the stats
+const STATS_PRUNE_INTERVAL: Duration = Duration::from_secs(60);
 
 /// Real-time price data for a symbol
+///
+/// The 24-hour fields cover a rolling window of trades, refreshed as trades
+/// arrive and as the stats are pruned. VWAP and TWAP references are kept
+/// alongside.
 #[derive(Debug, Clone)]
 pub struct SymbolStats {
     pub symbol: Symbol,
@@ -24,48 +52,91 @@ pub struct SymbolStats {
     pub bid: Decimal,
     pub ask: Decimal,
     pub volume_24h: Decimal,
+    pub quote_volume_24h: Decimal,
+    pub volume_usd_24h: Decimal,
     pub high_24h: Decimal,
     pub low_24h: Decimal,
     pub open_24h: Decimal,
     pub trade_count_24h: u64,
     pub last_update: DateTime<Utc>,
+    window: RollingWindow,
+    fair_value: FairValueTracker,
 }
 
 impl SymbolStats {
-    pub fn new(symbol: Symbol) -> Self {
+    pub fn new(symbol: Symbol, fair_value: &FairValueSettings, now: DateTime<Utc>) -> Self {
         Self {
             symbol,
             last_price: Decimal::ZERO,
             bid: Decimal::ZERO,
             ask: Decimal::ZERO,
             volume_24h: Decimal::ZERO,
+            quote_volume_24h: Decimal::ZERO,
+            volume_usd_24h: Decimal::ZERO,
             high_24h: Decimal::ZERO,
-            low_24h: Decimal::MAX,
+            low_24h: Decimal::ZERO,
             open_24h: Decimal::ZERO,
             trade_count_24h: 0,
-            last_update: Utc::now(),
+            last_update: now,
+            window: RollingWindow::new(chrono::Duration::hours(24)),
+            fair_value: FairValueTracker::new(fair_value),
         }
     }
 
-    pub fn update_from_trade(&mut self, trade: &Trade) {
+    pub fn update_from_enriched(&mut self, enriched: &EnrichedTrade, now: DateTime<Utc>) {
+        let trade = &enriched.trade;
         self.last_price = trade.price;
-        self.volume_24h += trade.quantity;
-        self.trade_count_24h += 1;
+        self.last_update = trade.executed_at;
 
-        if trade.price > self.high_24h {
-            self.high_24h = trade.price;
-        }
-        if trade.price < self.low_24h {
-            self.low_24h = trade.price;
-        }
-        if self.open_24h == Decimal::ZERO {
-            self.open_24h = trade.price;
+        self.window.record(
+            trade.executed_at,
+            trade.price,
+            trade.quantity,
+            enriched.notional,
+            enriched.notional_usd,
+            now,
+        );
+        self.window.prune(now);
+        self.refresh();
+
+        self.fair_value.record(
+            trade.executed_at,
+            trade.price,
+            trade.quantity,
+            enriched.notional,
+            now,
+        );
+    }
+
+    /// Drop trades older than 24 hours before `now`
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        if self.window.prune(now) {
+            self.refresh();
         }
+    }
 
-        self.last_update = trade.executed_at;
+    /// Roll the VWAP windows forward and sample the last price for the TWAP
+    pub fn sample_fair_value(&mut self, now: DateTime<Utc>) {
+        self.fair_value.sample(self.last_price, now);
+    }
+
+    pub fn twap_series(&self) -> Vec<TwapPoint> {
+        self.fair_value.twap_series()
+    }
+
+    fn refresh(&mut self) {
+        self.volume_24h = self.window.volume();
+        self.quote_volume_24h = self.window.quote_volume();
+        self.volume_usd_24h = self.window.volume_usd();
+        self.high_24h = self.window.high().unwrap_or_default();
+        self.low_24h = self.window.low().unwrap_or_default();
+        self.open_24h = self.window.open().unwrap_or_default();
+        self.trade_count_24h = self.window.trade_count();
     }
 
     pub fn to_market_data(&self) -> MarketData {
+        let (price_change_24h, percent_change_24h) =
+            MarketData::price_change(self.open_24h, self.last_price);
         MarketData {
             symbol: self.symbol.clone(),
             bid: self.bid,
@@ -74,6 +145,9 @@ impl SymbolStats {
             volume_24h: self.volume_24h,
             high_24h: self.high_24h,
             low_24h: self.low_24h,
+            price_change_24h,
+            percent_change_24h,
+            fair_value: Some(self.fair_value.to_fair_value()),
             timestamp: self.last_update,
         }
     }
@@ -82,9 +156,7 @@ impl SymbolStats {
 /// Candle builder for a specific interval
 #[derive(Debug, Clone)]
 pub struct CandleBuilder {
-    #[allow(dead_code)]
     pub symbol: Symbol,
-    #[allow(dead_code)]
     pub interval: String,
     pub open_time: DateTime<Utc>,
     pub open: Decimal,
@@ -125,7 +197,11 @@ impl CandleBuilder {
         self.trade_count += 1;
     }
 
-    #[allow(dead_code)]
+    /// End of the candle's interval
+    pub fn close_time(&self) -> DateTime<Utc> {
+        self.open_time + interval_duration(&self.interval)
+    }
+
     pub fn to_candle(&self, close_time: DateTime<Utc>) -> Candle {
         Candle {
             symbol: self.symbol.clone(),
@@ -154,66 +230,185 @@ pub struct PriceAggregator {
     /// Candle builders per symbol per interval
     candles: DashMap<String, HashMap<String, CandleBuilder>>,
 
+    /// Candles closed by a trade, awaiting the closer
+    closed: parking_lot::Mutex<Vec<Candle>>,
+
     /// Redis cache for persistence
     cache: Arc<RedisCache>,
+
+    /// Trade enrichment (notional, fees, USD value)
+    enricher: TradeEnricher,
+
+    /// Conflated midprice stream
+    midprice: Arc<MidpriceConflator>,
+
+    /// WebSocket fan-out
+    stream: Arc<StreamHub>,
+
+    /// Daily settlement prices
+    settlement: Arc<SettlementTracker>,
+
+    /// VWAP windows and TWAP series shape for new symbols
+    fair_value: FairValueSettings,
+
+    /// Time source
+    clock: SharedClock,
 }
 
 impl PriceAggregator {
-    pub fn new(cache: Arc<RedisCache>) -> Self {
+    pub fn new(
+        cache: Arc<RedisCache>,
+        enricher: TradeEnricher,
+        midprice: Arc<MidpriceConflator>,
+        stream: Arc<StreamHub>,
+        settlement: Arc<SettlementTracker>,
+        fair_value: FairValueSettings,
+        clock: SharedClock,
+    ) -> Self {
         Self {
             stats: DashMap::new(),
             candles: DashMap::new(),
+            closed: parking_lot::Mutex::new(Vec::new()),
             cache,
+            enricher,
+            midprice,
+            stream,
+            settlement,
+            fair_value,
+            clock,
         }
     }
 
+    /// Process top-of-book update
+    pub fn update_top_of_book(&self, symbol: &Symbol, bid: Decimal, ask: Decimal) {
+        {
+            let mut stats = self.stats.entry(symbol.to_string()).or_insert_with(|| {
+                SymbolStats::new(symbol.clone(), &self.fair_value, self.clock.now())
+            });
+            stats.bid = bid;
+            stats.ask = ask;
+        }
+
+        self.midprice.offer(symbol, bid, ask);
+    }
+
     /// Process incoming trade
     pub async fn process_trade(&self, trade: Trade) -> anyhow::Result<()> {
         let symbol_key = trade.symbol.to_string();
 
+        // Enrich before anything is persisted
+        let enriched = self.enricher.enrich(trade).await;
+        let trade = &enriched.trade;
+
         // Update real-time stats
+        let now = self.clock.now();
         self.stats
             .entry(symbol_key.clone())
-            .or_insert_with(|| SymbolStats::new(trade.symbol.clone()))
-            .update_from_trade(&trade);
+            .or_insert_with(|| SymbolStats::new(trade.symbol.clone(), &self.fair_value, now))
+            .update_from_enriched(&enriched, now);
 
-        // Update candle builders
-        self.update_candles(&trade);
+        self.update_candles(trade);
+        self.settlement.record(trade);
 
-        // Cache latest price
+        // Cache latest price and enriched trade
         self.cache.set_price(&trade.symbol, trade.price).await?;
+        self.cache
+            .push_trade(&trade.symbol, &serde_json::to_string(&enriched)?)
+            .await?;
+        self.stream
+            .publish(&format!("trades:{}", trade.symbol), &enriched);
+
+        // Best effort: a missed push does not affect the trade's processing
+        let fills = FillNotification::for_trade(&enriched);
+        if let Err(e) = self.cache.publish_fills(&fills).await {
+            warn!(
+                trade_id = trade.trade_id,
+                "Failed to publish fill notifications: {}", e
+            );
+        }
 
         metrics::counter!("trades_processed").increment(1);
 
         Ok(())
     }
 
-    /// Update candle builders with trade
+    /// Update candle builders with trade, queueing the candles it closed
     fn update_candles(&self, trade: &Trade) {
         let symbol_key = trade.symbol.to_string();
-        let intervals = vec!["1m", "5m", "15m", "1h", "4h", "1d"];
 
         let mut candle_map = self.candles.entry(symbol_key).or_default();
+        let mut closed = Vec::new();
 
-        for interval in intervals {
+        for &interval in CANDLE_INTERVALS {
             let candle_open = get_candle_open_time(trade.executed_at, interval);
 
             let builder = candle_map
                 .entry(interval.to_string())
                 .or_insert_with(|| CandleBuilder::new(trade.symbol.clone(), interval, candle_open));
 
-            // Check if we need a new candle
+            if candle_open < builder.open_time {
+                metrics::counter!("candle_late_trades", "interval" => interval).increment(1);
+                continue;
+            }
             if builder.open_time != candle_open {
-                // TODO: Publish completed candle
+                metrics::counter!("candle_closures", "trigger" => "trade").increment(1);
+                closed.push(builder.to_candle(builder.close_time()));
                 *builder = CandleBuilder::new(trade.symbol.clone(), interval, candle_open);
             }
 
             builder.update(trade.price, trade.quantity);
         }
+
+        if !closed.is_empty() {
+            self.closed.lock().extend(closed);
+        }
+    }
+
+    /// Close every candle whose interval ended more than `grace` before
+    /// `now`, returning them with the candles trades have closed since the
+    /// last call
+    ///
+    /// A symbol's next candle starts with its next trade.
+    pub fn close_candles(&self, now: DateTime<Utc>, grace: chrono::Duration) -> Vec<Candle> {
+        let mut closed = std::mem::take(&mut *self.closed.lock());
+
+        for mut candle_map in self.candles.iter_mut() {
+            candle_map.retain(|_, builder| {
+                if now < builder.close_time() + grace {
+                    return true;
+                }
+                metrics::counter!("candle_closures", "trigger" => "timer").increment(1);
+                closed.push(builder.to_candle(builder.close_time()));
+                false
+            });
+        }
+        self.candles.retain(|_, candle_map| !candle_map.is_empty());
+
+        closed
+    }
+
+    /// Drop trades older than 24 hours from every symbol's stats
+    pub fn prune_stats(&self) {
+        let now = self.clock.now();
+        for mut stats in self.stats.iter_mut() {
+            stats.prune(now);
+        }
+    }
+
+    /// Sample every symbol's fair-value references
+    pub fn sample_fair_values(&self) {
+        let now = self.clock.now();
+        for mut stats in self.stats.iter_mut() {
+            stats.sample_fair_value(now);
+        }
+    }
+
+    /// WebSocket fan-out fed by this aggregator
+    pub fn stream(&self) -> &StreamHub {
+        &self.stream
     }
 
     /// Get current market data for symbol
-    #[allow(dead_code)]
     pub fn get_market_data(&self, symbol: &Symbol) -> Option<MarketData> {
         self.stats
             .get(&symbol.to_string())
@@ -228,17 +423,21 @@ impl PriceAggregator {
             .collect()
     }
 
+    /// TWAP series for symbol, oldest interval first
+    pub fn get_twap_series(&self, symbol: &Symbol) -> Option<Vec<TwapPoint>> {
+        self.stats.get(&symbol.to_string()).map(|s| s.twap_series())
+    }
+
     /// Get current candle for symbol and interval
-    #[allow(dead_code)]
     pub fn get_current_candle(&self, symbol: &Symbol, interval: &str) -> Option<Candle> {
         self.candles
             .get(&symbol.to_string())
-            .and_then(|map| map.get(interval).map(|b| b.to_candle(Utc::now())))
+            .and_then(|map| map.get(interval).map(|b| b.to_candle(self.clock.now())))
     }
 }
 
 /// Get candle open time for a given timestamp and interval
-fn get_candle_open_time(timestamp: DateTime<Utc>, interval: &str) -> DateTime<Utc> {
+pub fn get_candle_open_time(timestamp: DateTime<Utc>, interval: &str) -> DateTime<Utc> {
     let ts = timestamp;
 
     match interval {
@@ -292,16 +491,90 @@ fn get_candle_open_time(timestamp: DateTime<Utc>, interval: &str) -> DateTime<Ut
     }
 }
 
-/// Run candle aggregation task
-pub async fn run_candle_aggregation(_aggregator: Arc<PriceAggregator>) -> anyhow::Result<()> {
-    let mut interval = time::interval(Duration::from_secs(60));
+/// Length of a candle interval
+pub fn interval_duration(interval: &str) -> chrono::Duration {
+    match interval {
+        "1m" => chrono::Duration::minutes(1),
+        "5m" => chrono::Duration::minutes(5),
+        "15m" => chrono::Duration::minutes(15),
+        "1h" => chrono::Duration::hours(1),
+        "4h" => chrono::Duration::hours(4),
+        "1d" => chrono::Duration::days(1),
+        _ => chrono::Duration::zero(),
+    }
+}
+
+/// Roll the 24-hour stats forward for symbols that stopped trading
+pub async fn run_stats_pruning(aggregator: Arc<PriceAggregator>) -> anyhow::Result<()> {
+    let mut interval = time::interval(STATS_PRUNE_INTERVAL);
+    loop {
+        interval.tick().await;
+        aggregator.prune_stats();
+    }
+}
+
+/// Keep the VWAP windows current and build the TWAP series
+pub async fn run_fair_value_sampling(aggregator: Arc<PriceAggregator>) -> anyhow::Result<()> {
+    let mut interval = time::interval(TWAP_SAMPLE_INTERVAL);
+    loop {
+        interval.tick().await;
+        aggregator.sample_fair_values();
+    }
+}
+
+/// Close candles as their intervals end, storing each in Redis and
+/// publishing it to `market.candles`
+pub async fn run_candle_aggregation(
+    aggregator: Arc<PriceAggregator>,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let producer: FutureProducer = ClientConfig::new()
+        .set("bootstrap.servers", &config.kafka_brokers)
+        .set("message.timeout.ms", "5000")
+        .create()?;
+
+    let grace = chrono::Duration::seconds(config.candle_close_grace_secs as i64);
+    let mut interval = time::interval(CLOSE_CHECK_INTERVAL);
+
+    info!(
+        grace_secs = config.candle_close_grace_secs,
+        "Candle closer started"
+    );
 
     loop {
         interval.tick().await;
 
-        // Process candle closures
-        info!("Running candle aggregation tick");
+        for candle in aggregator.close_candles(aggregator.clock.now(), grace) {
+            if let Err(e) = aggregator.cache.store_candle(&candle).await {
+                warn!(
+                    symbol = %candle.symbol,
+                    interval = %candle.interval,
+                    "Failed to store candle: {}", e
+                );
+            }
 
-        // TODO: Close and publish completed candles
+            let outbound = Event::builder(candle.clone())
+                .source("data-pipeline")
+                .build();
+            let payload = outbound.to_json()?;
+
+            if let Err((e, _)) = producer
+                .send(
+                    FutureRecord::to(outbound.topic)
+                        .key(&outbound.key)
+                        .payload(&payload),
+                    Duration::from_secs(5),
+                )
+                .await
+            {
+                warn!(
+                    symbol = %candle.symbol,
+                    interval = %candle.interval,
+                    "Failed to publish candle: {}", e
+                );
+                continue;
+            }
+            metrics::counter!("candles_published", "interval" => candle.interval).increment(1);
+        }
     }
 }
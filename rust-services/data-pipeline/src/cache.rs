@@ -3,14 +3,22 @@
 //! Provides low-latency access to:
 //! - Current prices
 //! - Order book snapshots
-//! - User positions
+//! - User positions and PnL
+//! - Daily settlement prices
+//! - User fill notifications
+//! - Closed candles, pruned by retention and repaired by verification
 
 use anyhow::Result;
 use redis::aio::ConnectionManager;
 use redis::AsyncCommands;
 use rust_decimal::Decimal;
 
-use common::Symbol;
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::notifications::FillNotification;
+use crate::positions::Position;
+use crate::retention::Pruned;
+use common::{Candle, SettlementPrice, Symbol};
 
 pub struct RedisCache {
     conn: ConnectionManager,
@@ -32,7 +40,6 @@ impl RedisCache {
     }
 
     /// Get current price for symbol
-    #[allow(dead_code)]
     pub async fn get_price(&self, symbol: &Symbol) -> Result<Option<Decimal>> {
         let key = format!("price:{symbol}");
         let mut conn = self.conn.clone();
@@ -40,6 +47,167 @@ impl RedisCache {
         Ok(result.and_then(|s| s.parse().ok()))
     }
 
+    /// Append an enriched trade to the symbol's recent trades list
+    pub async fn push_trade(&self, symbol: &Symbol, trade: &str) -> Result<()> {
+        let key = format!("trades:{symbol}");
+        let mut conn = self.conn.clone();
+        conn.lpush::<_, _, ()>(&key, trade).await?;
+        conn.ltrim::<_, ()>(&key, 0, 999).await?;
+        Ok(())
+    }
+
+    /// Set and broadcast the conflated midprice for symbol
+    pub async fn set_midprice(&self, symbol: &Symbol, mid: Decimal) -> Result<()> {
+        let mut conn = self.conn.clone();
+        conn.set_ex::<_, _, ()>(format!("mid:{symbol}"), mid.to_string(), 60)
+            .await?;
+        conn.publish::<_, _, ()>(format!("midprice:{symbol}"), mid.to_string())
+            .await?;
+        Ok(())
+    }
+
+    /// Publish fill notifications to their users' channels
+    pub async fn publish_fills(&self, fills: &[FillNotification]) -> Result<()> {
+        let mut pipe = redis::pipe();
+        for fill in fills {
+            pipe.publish(fill.channel(), serde_json::to_string(fill)?)
+                .ignore();
+        }
+        let mut conn = self.conn.clone();
+        pipe.query_async::<_, ()>(&mut conn).await?;
+        Ok(())
+    }
+
+    /// Add a closed candle to its symbol and interval's set, scored by open time
+    pub async fn store_candle(&self, candle: &Candle) -> Result<()> {
+        let key = format!("candles:{}:{}", candle.symbol, candle.interval);
+        let mut conn = self.conn.clone();
+        conn.zadd::<_, _, _, ()>(
+            &key,
+            serde_json::to_string(candle)?,
+            candle.open_time.timestamp(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Closed candles opened in `[start, end]`, oldest first; the latest
+    /// `limit` when the range holds more
+    pub async fn get_candles(
+        &self,
+        symbol: &Symbol,
+        interval: &str,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Result<Vec<Candle>> {
+        let key = format!("candles:{symbol}:{interval}");
+        let min = start.map_or("-inf".to_string(), |t| t.timestamp().to_string());
+        let max = end.map_or("+inf".to_string(), |t| t.timestamp().to_string());
+        let mut conn = self.conn.clone();
+
+        let payloads: Vec<String> = conn
+            .zrevrangebyscore_limit(&key, max, min, 0, limit as isize)
+            .await?;
+        payloads
+            .iter()
+            .rev()
+            .map(|p| Ok(serde_json::from_str(p)?))
+            .collect()
+    }
+
+    /// Every closed candle opened in `[start, end]`, oldest first
+    pub async fn candles_between(
+        &self,
+        symbol: &Symbol,
+        interval: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Candle>> {
+        let key = format!("candles:{symbol}:{interval}");
+        let mut conn = self.conn.clone();
+        let payloads: Vec<String> = conn
+            .zrangebyscore(&key, start.timestamp(), end.timestamp())
+            .await?;
+        payloads
+            .iter()
+            .map(|p| Ok(serde_json::from_str(p)?))
+            .collect()
+    }
+
+    /// Replace whatever is stored for the candle's open time with `candle`
+    pub async fn replace_candle(&self, candle: &Candle) -> Result<()> {
+        let key = format!("candles:{}:{}", candle.symbol, candle.interval);
+        let score = candle.open_time.timestamp();
+        let mut conn = self.conn.clone();
+        redis::pipe()
+            .atomic()
+            .zrembyscore(&key, score, score)
+            .ignore()
+            .zadd(&key, serde_json::to_string(candle)?, score)
+            .ignore()
+            .query_async::<_, ()>(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Remove the candle of `interval` opened at `open_time`
+    pub async fn remove_candle(
+        &self,
+        symbol: &Symbol,
+        interval: &str,
+        open_time: DateTime<Utc>,
+    ) -> Result<()> {
+        let key = format!("candles:{symbol}:{interval}");
+        let score = open_time.timestamp();
+        let mut conn = self.conn.clone();
+        conn.zrembyscore::<_, _, _, ()>(&key, score, score).await?;
+        Ok(())
+    }
+
+    /// Remove candles of `interval` opened before `cutoff` from every symbol
+    ///
+    /// In a dry run nothing is removed and the bytes are estimated from each
+    /// set's share of prunable entries.
+    pub async fn prune_candles(
+        &self,
+        interval: &str,
+        cutoff: DateTime<Utc>,
+        dry_run: bool,
+    ) -> Result<Pruned> {
+        let mut conn = self.conn.clone();
+        let keys: Vec<String> = {
+            let mut iter = conn
+                .scan_match::<_, String>(format!("candles:*:{interval}"))
+                .await?;
+            let mut keys = Vec::new();
+            while let Some(key) = iter.next_item().await {
+                keys.push(key);
+            }
+            keys
+        };
+
+        // Scores are open times in seconds; the cutoff itself is kept
+        let max = format!("({}", cutoff.timestamp());
+        let mut pruned = Pruned::default();
+        for key in keys {
+            let before = memory_usage(&mut conn, &key).await?;
+            if dry_run {
+                let stale: u64 = conn.zcount(&key, "-inf", &max).await?;
+                let total: u64 = conn.zcard(&key).await?;
+                pruned.entries += stale;
+                pruned.bytes += before * stale / total.max(1);
+            } else {
+                let removed: u64 = conn.zrembyscore(&key, "-inf", &max).await?;
+                let after = memory_usage(&mut conn, &key).await?;
+                pruned.entries += removed;
+                pruned.bytes += before.saturating_sub(after);
+            }
+        }
+
+        Ok(pruned)
+    }
+
     /// Publish price update to Redis channel
     #[allow(dead_code)]
     pub async fn publish_price(&self, symbol: &Symbol, price: Decimal) -> Result<()> {
@@ -61,12 +229,85 @@ impl RedisCache {
         Ok(())
     }
 
-    /// Store user position
-    #[allow(dead_code)]
-    pub async fn set_position(&self, user_id: &str, symbol: &Symbol, position: &str) -> Result<()> {
-        let key = format!("position:{user_id}:{symbol}");
+    /// Store a position in its user's hash, keyed by symbol
+    pub async fn set_position(&self, position: &Position) -> Result<()> {
+        let key = format!("positions:{}", position.user_id);
         let mut conn = self.conn.clone();
-        conn.set_ex::<_, _, ()>(&key, position, 300).await?;
+        conn.hset::<_, _, _, ()>(
+            &key,
+            position.symbol.to_string(),
+            serde_json::to_string(position)?,
+        )
+        .await?;
         Ok(())
     }
+
+    /// Every stored position, for restoring the tracker at startup
+    pub async fn load_positions(&self) -> Result<Vec<Position>> {
+        let mut conn = self.conn.clone();
+        let keys: Vec<String> = {
+            let mut iter = conn.scan_match::<_, String>("positions:*").await?;
+            let mut keys = Vec::new();
+            while let Some(key) = iter.next_item().await {
+                keys.push(key);
+            }
+            keys
+        };
+
+        let mut positions = Vec::new();
+        for key in keys {
+            let payloads: Vec<String> = conn.hvals(&key).await?;
+            for payload in payloads {
+                positions.push(serde_json::from_str(&payload)?);
+            }
+        }
+        Ok(positions)
+    }
+
+    /// Store a settlement in the symbol's hash, keyed by trading date
+    pub async fn set_settlement(&self, settlement: &SettlementPrice) -> Result<()> {
+        let key = format!("settlement:{}", settlement.symbol);
+        let mut conn = self.conn.clone();
+        conn.hset::<_, _, _, ()>(
+            &key,
+            settlement.trading_date.to_string(),
+            serde_json::to_string(settlement)?,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Settlement for `date`, or the most recent one when `date` is None
+    pub async fn get_settlement(
+        &self,
+        symbol: &Symbol,
+        date: Option<NaiveDate>,
+    ) -> Result<Option<SettlementPrice>> {
+        let key = format!("settlement:{symbol}");
+        let mut conn = self.conn.clone();
+
+        let payload: Option<String> = match date {
+            Some(date) => conn.hget(&key, date.to_string()).await?,
+            None => {
+                // ISO dates sort chronologically
+                let fields: Vec<String> = conn.hkeys(&key).await?;
+                match fields.into_iter().max() {
+                    Some(latest) => conn.hget(&key, latest).await?,
+                    None => None,
+                }
+            }
+        };
+
+        Ok(payload.map(|p| serde_json::from_str(&p)).transpose()?)
+    }
+}
+
+/// Bytes a key occupies, 0 once it is gone
+async fn memory_usage(conn: &mut ConnectionManager, key: &str) -> Result<u64> {
+    let bytes: Option<u64> = redis::cmd("MEMORY")
+        .arg("USAGE")
+        .arg(key)
+        .query_async(conn)
+        .await?;
+    Ok(bytes.unwrap_or(0))
 }
@@ -1,9 +1,14 @@
 //! Data Pipeline Configuration
 
-use anyhow::Result;
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize)]
+use common::config::{schemes, Checks, Validate};
+
+use crate::settlement::SettlementMethod;
+use crate::stream::SlowConsumerPolicy;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     #[serde(default = "default_host")]
     pub host: String,
@@ -20,12 +25,151 @@ pub struct Config {
     #[serde(default = "default_kafka_group")]
     pub kafka_group_id: String,
 
+    /// Consumer group of the order book consumer; `kafka_group_id` with an
+    /// `-orderbook` suffix when unset
+    pub kafka_orderbook_group_id: Option<String>,
+
     #[serde(default = "default_publish_interval")]
     pub publish_interval_ms: u64,
 
+    /// Minimum interval between midprice ticks per symbol
+    #[serde(default = "default_midprice_conflation")]
+    pub midprice_conflation_ms: u64,
+
+    /// Fee schedule used for trade enrichment, in basis points
+    #[serde(default)]
+    pub maker_fee_bps: u32,
+
+    #[serde(default)]
+    pub taker_fee_bps: u32,
+
+    // Reference data
+    /// Reference-data service base URL; replaces the sources below when set
+    pub reference_data_url: Option<String>,
+
+    /// Matching engine base URL, source of listed symbols
+    pub matching_engine_url: Option<String>,
+
+    /// Exchange gateway base URL, source of precision and status
+    pub gateway_url: Option<String>,
+
+    #[serde(default = "default_gateway_exchange")]
+    pub gateway_exchange: String,
+
+    /// JSON file of per-symbol overrides
+    pub refdata_file: Option<String>,
+
+    #[serde(default = "default_refdata_refresh")]
+    pub refdata_refresh_secs: u64,
+
+    // WebSocket streaming
+    #[serde(default = "default_ws_max_clients")]
+    pub ws_max_clients: usize,
+
+    #[serde(default = "default_ws_max_channels_per_client")]
+    pub ws_max_channels_per_client: usize,
+
+    /// Messages buffered per client before the slow-consumer policy applies
+    #[serde(default = "default_ws_queue_capacity")]
+    pub ws_queue_capacity: usize,
+
+    /// `drop_oldest` or `disconnect`
+    #[serde(default)]
+    pub ws_slow_consumer_policy: SlowConsumerPolicy,
+
+    // Daily settlement
+    /// `vwap` or `last_trade`
+    #[serde(default)]
+    pub settlement_method: SettlementMethod,
+
+    /// End-of-day window the settlement VWAP is taken over
+    #[serde(default = "default_settlement_window_mins")]
+    pub settlement_window_mins: u64,
+
+    /// Wait after midnight UTC for in-flight trades before settling
+    #[serde(default = "default_settlement_grace_secs")]
+    pub settlement_grace_secs: u64,
+
+    /// Wait after a candle's interval ends for in-flight trades before
+    /// closing it
+    #[serde(default = "default_candle_close_grace_secs")]
+    pub candle_close_grace_secs: u64,
+
+    // Synthetic cross rates
+    /// Pairs without a direct market to derive, e.g. `SOL-ETH,BTC-ETH`
+    #[serde(default)]
+    pub synthetic_pairs: String,
+
+    /// Assets synthetic pairs may be derived through, in order of
+    /// preference
+    #[serde(default = "default_synthetic_bridges")]
+    pub synthetic_bridges: String,
+
+    /// How often open positions are re-marked against the last price
+    #[serde(default = "default_position_mark_interval")]
+    pub position_mark_interval_secs: u64,
+
+    // Fair-value references
+    /// VWAP windows reported per symbol, e.g. `1m,1h,24h`
+    #[serde(default = "default_vwap_windows")]
+    pub vwap_windows: String,
+
+    /// Length of each point in the TWAP series
+    #[serde(default = "default_twap_interval")]
+    pub twap_interval_secs: u64,
+
+    /// Points kept in the TWAP series; the reported TWAP covers all of them
+    #[serde(default = "default_twap_points")]
+    pub twap_points: usize,
+
     #[serde(default = "default_candle_intervals")]
     #[allow(dead_code)]
     pub candle_intervals: Vec<String>,
+
+    // Candle retention
+    /// Postgres holding the candle history; only Redis is pruned without it
+    pub database_url: Option<String>,
+
+    /// Per-interval retention, e.g. `1m=30d,1h=365d,1d=forever`
+    #[serde(default = "default_candle_retention")]
+    pub candle_retention: String,
+
+    #[serde(default = "default_retention_interval")]
+    pub retention_interval_secs: u64,
+
+    /// Directory Parquet archives of pruned Postgres candles are written to
+    #[serde(default = "default_retention_archive_dir")]
+    pub retention_archive_dir: String,
+
+    /// Report what would be pruned without deleting anything
+    #[serde(default)]
+    pub retention_dry_run: bool,
+
+    // Candle verification
+    /// Postgres holding the matching engine's trade history, which
+    /// candles are verified against
+    pub trade_archive_url: Option<String>,
+
+    /// Verify candles from this time against the trade history, print the
+    /// report and exit instead of running the pipeline
+    pub candle_verify_start: Option<DateTime<Utc>>,
+
+    /// End of the verified range; now when unset
+    pub candle_verify_end: Option<DateTime<Utc>>,
+
+    /// Comma-separated symbols to verify; every symbol that traded in the
+    /// range when empty
+    #[serde(default)]
+    pub candle_verify_symbols: String,
+
+    /// Rewrite candles that differ from the trades and remove candles
+    /// without any
+    #[serde(default)]
+    pub candle_verify_repair: bool,
+
+    // Observability
+    #[serde(default = "default_metrics_port")]
+    pub metrics_port: u16,
 }
 
 fn default_host() -> String {
@@ -43,6 +187,60 @@ fn default_kafka_group() -> String {
 fn default_publish_interval() -> u64 {
     100
 }
+fn default_midprice_conflation() -> u64 {
+    250
+}
+fn default_gateway_exchange() -> String {
+    "binance".to_string()
+}
+fn default_refdata_refresh() -> u64 {
+    300
+}
+fn default_ws_max_clients() -> usize {
+    1000
+}
+fn default_ws_max_channels_per_client() -> usize {
+    50
+}
+fn default_ws_queue_capacity() -> usize {
+    256
+}
+fn default_settlement_window_mins() -> u64 {
+    30
+}
+fn default_settlement_grace_secs() -> u64 {
+    60
+}
+fn default_candle_close_grace_secs() -> u64 {
+    2
+}
+fn default_synthetic_bridges() -> String {
+    "USDT,USDC,USD".to_string()
+}
+fn default_position_mark_interval() -> u64 {
+    5
+}
+fn default_vwap_windows() -> String {
+    "1m,1h,24h".to_string()
+}
+fn default_twap_interval() -> u64 {
+    60
+}
+fn default_twap_points() -> usize {
+    60
+}
+fn default_candle_retention() -> String {
+    "1m=30d,5m=90d,15m=180d,1h=365d,4h=730d,1d=forever".to_string()
+}
+fn default_retention_interval() -> u64 {
+    3600
+}
+fn default_retention_archive_dir() -> String {
+    "archive/candles".to_string()
+}
+fn default_metrics_port() -> u16 {
+    9090
+}
 fn default_candle_intervals() -> Vec<String> {
     vec![
         "1m".to_string(),
@@ -53,10 +251,72 @@ fn default_candle_intervals() -> Vec<String> {
 }
 
 impl Config {
-    pub fn load() -> Result<Self> {
-        let config = config::Config::builder()
-            .add_source(config::Environment::default().separator("__"))
-            .build()?;
-        Ok(config.try_deserialize()?)
+    /// Consumer group of the order book consumer, apart from the trade
+    /// consumer's so each gets every partition of its own topic
+    pub fn orderbook_group_id(&self) -> String {
+        self.kafka_orderbook_group_id
+            .clone()
+            .unwrap_or_else(|| format!("{}-orderbook", self.kafka_group_id))
+    }
+}
+
+impl Validate for Config {
+    fn validate(&self, checks: &mut Checks) {
+        checks.ports(&[("port", self.port), ("metrics_port", self.metrics_port)]);
+        checks.url("redis_url", &self.redis_url, schemes::REDIS);
+        checks.brokers("kafka_brokers", &self.kafka_brokers);
+        if self.orderbook_group_id() == self.kafka_group_id {
+            checks.fail(
+                "kafka_orderbook_group_id",
+                "must differ from kafka_group_id",
+            );
+        }
+        checks.optional_url(
+            "database_url",
+            self.database_url.as_deref(),
+            schemes::POSTGRES,
+        );
+        checks.optional_url(
+            "reference_data_url",
+            self.reference_data_url.as_deref(),
+            schemes::HTTP,
+        );
+        checks.optional_url(
+            "matching_engine_url",
+            self.matching_engine_url.as_deref(),
+            schemes::HTTP,
+        );
+        checks.optional_url("gateway_url", self.gateway_url.as_deref(), schemes::HTTP);
+        checks.optional_url(
+            "trade_archive_url",
+            self.trade_archive_url.as_deref(),
+            schemes::POSTGRES,
+        );
+        if let Some(start) = self.candle_verify_start {
+            if self.trade_archive_url.is_none() {
+                checks.fail("candle_verify_start", "requires trade_archive_url");
+            }
+            if self.candle_verify_end.is_some_and(|end| end <= start) {
+                checks.fail("candle_verify_end", "must be after candle_verify_start");
+            }
+        }
+
+        checks.duration("publish_interval_ms", self.publish_interval_ms);
+        checks.duration("refdata_refresh_secs", self.refdata_refresh_secs);
+        checks.duration("retention_interval_secs", self.retention_interval_secs);
+        checks.duration("settlement_window_mins", self.settlement_window_mins);
+        checks.non_zero("ws_queue_capacity", self.ws_queue_capacity as u64);
+        if let Err(e) = crate::fairvalue::parse_windows(&self.vwap_windows) {
+            checks.fail("vwap_windows", e);
+        }
+        if let Err(e) = crate::crossrate::parse_pairs(&self.synthetic_pairs) {
+            checks.fail("synthetic_pairs", e);
+        }
+        checks.duration("twap_interval_secs", self.twap_interval_secs);
+        checks.duration(
+            "position_mark_interval_secs",
+            self.position_mark_interval_secs,
+        );
+        checks.non_zero("twap_points", self.twap_points as u64);
     }
 }
@@ -1,25 +1,55 @@
-//! Kafka Consumer for trade events
+//! Kafka Consumers for trade and order book events
 
 use anyhow::Result;
 use rdkafka::{
-    consumer::{Consumer, StreamConsumer},
-    ClientConfig, Message,
+    consumer::{Consumer, ConsumerContext, StreamConsumer},
+    ClientConfig, ClientContext, Message, Statistics,
 };
+use rust_decimal::Decimal;
 use std::sync::Arc;
 use tokio_stream::StreamExt;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 
 use crate::aggregator::PriceAggregator;
 use crate::config::Config;
-use common::events::topics;
+use common::events::{topics, Event, OrderBookUpdate, TypedEvent};
+
+/// How often librdkafka reports statistics, including consumer lag
+const STATS_INTERVAL_MS: &str = "10000";
+
+/// Consumer context exporting per-partition lag from librdkafka statistics
+struct LagContext;
+
+impl ClientContext for LagContext {
+    fn stats(&self, statistics: Statistics) {
+        for (topic, stats) in &statistics.topics {
+            // Partition -1 is librdkafka's internal unassigned partition;
+            // lag is -1 until the partition has a committed position
+            for (partition, p) in stats.partitions.iter().filter(|(id, _)| **id >= 0) {
+                if p.consumer_lag < 0 {
+                    continue;
+                }
+                metrics::gauge!(
+                    "consumer_lag",
+                    "topic" => topic.clone(),
+                    "partition" => partition.to_string()
+                )
+                .set(p.consumer_lag as f64);
+            }
+        }
+    }
+}
+
+impl ConsumerContext for LagContext {}
 
 pub async fn run_trade_consumer(aggregator: Arc<PriceAggregator>, config: &Config) -> Result<()> {
-    let consumer: StreamConsumer = ClientConfig::new()
+    let consumer: StreamConsumer<LagContext> = ClientConfig::new()
         .set("bootstrap.servers", &config.kafka_brokers)
         .set("group.id", &config.kafka_group_id)
         .set("enable.auto.commit", "true")
         .set("auto.offset.reset", "latest")
-        .create()?;
+        .set("statistics.interval.ms", STATS_INTERVAL_MS)
+        .create_with_context(LagContext)?;
 
     consumer.subscribe(&[topics::TRADES])?;
 
@@ -54,3 +84,59 @@ pub async fn run_trade_consumer(aggregator: Arc<PriceAggregator>, config: &Confi
 
     Ok(())
 }
+
+pub async fn run_orderbook_consumer(
+    aggregator: Arc<PriceAggregator>,
+    config: &Config,
+) -> Result<()> {
+    let consumer: StreamConsumer<LagContext> = ClientConfig::new()
+        .set("bootstrap.servers", &config.kafka_brokers)
+        .set("group.id", config.orderbook_group_id())
+        .set("enable.auto.commit", "true")
+        .set("auto.offset.reset", "latest")
+        .set("statistics.interval.ms", STATS_INTERVAL_MS)
+        .create_with_context(LagContext)?;
+
+    consumer.subscribe(&[topics::ORDER_BOOK])?;
+
+    info!(
+        "Order book consumer started, subscribed to {}",
+        topics::ORDER_BOOK
+    );
+
+    let mut stream = consumer.stream();
+
+    while let Some(message) = stream.next().await {
+        match message {
+            Ok(msg) => {
+                if let Some(payload) = msg.payload() {
+                    match serde_json::from_slice::<Event<OrderBookUpdate>>(payload) {
+                        // Delta messages share the snapshot's shape but
+                        // carry only changed levels
+                        Ok(event) if event.event_type != OrderBookUpdate::EVENT_TYPE => {
+                            debug!("Skipping {} order book event", event.event_type);
+                        }
+                        Ok(event) => {
+                            let book = event.payload;
+                            let bid = book.bids.iter().map(|(p, _)| *p).max();
+                            let ask = book.asks.iter().map(|(p, _)| *p).min();
+                            aggregator.update_top_of_book(
+                                &book.symbol,
+                                bid.unwrap_or(Decimal::ZERO),
+                                ask.unwrap_or(Decimal::ZERO),
+                            );
+                        }
+                        Err(e) => {
+                            debug!("Skipping non-snapshot order book event: {}", e);
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                warn!("Kafka error: {}", e);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,189 @@
+//! Synthetic Cross Rates
+//!
+//! Prices for configured pairs without a direct market, derived from the
+//! base and quote assets' markets against a bridge asset: SOL-ETH is
+//! SOL-USDT over ETH-USDT. Bridges are tried in configured order and the
+//! first with both legs priced is used. A pair that gains a direct market
+//! is no longer derived.
+//!
+//! The synthetic bid sells the base into the bridge and buys the quote
+//! with it, so it uses the base leg's bid over the quote leg's ask; the ask
+//! is the reverse. The 24-hour change comes from the legs' opening prices.
+
+use anyhow::{anyhow, Result};
+use rust_decimal::Decimal;
+
+use crate::aggregator::PriceAggregator;
+use crate::config::Config;
+use common::{events::SyntheticTicker, MarketData, Symbol};
+
+/// Decimal places synthetic prices are kept to
+const PRICE_DP: u32 = 12;
+
+/// Configured synthetic pairs and the bridges they may be derived through
+#[derive(Debug, Clone, Default)]
+pub struct CrossRates {
+    pairs: Vec<Symbol>,
+    bridges: Vec<String>,
+}
+
+impl CrossRates {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Ok(Self {
+            pairs: parse_pairs(&config.synthetic_pairs)?,
+            bridges: config
+                .synthetic_bridges
+                .split(',')
+                .map(|b| b.trim().to_uppercase())
+                .filter(|b| !b.is_empty())
+                .collect(),
+        })
+    }
+
+    pub fn is_configured(&self, symbol: &Symbol) -> bool {
+        self.pairs.contains(symbol)
+    }
+
+    /// Synthetic ticker for `symbol`, if it is configured, has no direct
+    /// market and both legs are priced through some bridge
+    pub fn ticker(&self, aggregator: &PriceAggregator, symbol: &Symbol) -> Option<SyntheticTicker> {
+        if !self.is_configured(symbol) || aggregator.get_market_data(symbol).is_some() {
+            return None;
+        }
+
+        self.bridges.iter().find_map(|bridge| {
+            let base_leg = Symbol::new(symbol.base(), bridge);
+            let quote_leg = Symbol::new(symbol.quote(), bridge);
+            let data = cross(
+                symbol,
+                &aggregator.get_market_data(&base_leg)?,
+                &aggregator.get_market_data(&quote_leg)?,
+            )?;
+            Some(SyntheticTicker {
+                data,
+                synthetic: true,
+                bridge: bridge.clone(),
+                legs: [base_leg, quote_leg],
+            })
+        })
+    }
+
+    /// Every configured pair that can currently be derived
+    pub fn tickers(&self, aggregator: &PriceAggregator) -> Vec<SyntheticTicker> {
+        self.pairs
+            .iter()
+            .filter_map(|symbol| self.ticker(aggregator, symbol))
+            .collect()
+    }
+}
+
+/// Parse pairs such as `SOL-ETH,BTC-ETH`
+pub fn parse_pairs(spec: &str) -> Result<Vec<Symbol>> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|pair| {
+            pair.split_once('-')
+                .filter(|(base, quote)| !base.is_empty() && !quote.is_empty())
+                .map(|(base, quote)| Symbol::new(base, quote))
+                .ok_or_else(|| anyhow!("Invalid synthetic pair `{pair}`: use BASE-QUOTE"))
+        })
+        .collect()
+}
+
+/// Derive `symbol` from legs quoted in the same bridge asset, or `None`
+/// while either leg has no last price
+pub fn cross(symbol: &Symbol, base_leg: &MarketData, quote_leg: &MarketData) -> Option<MarketData> {
+    let divide = |numerator: Decimal, denominator: Decimal| {
+        if numerator > Decimal::ZERO && denominator > Decimal::ZERO {
+            (numerator / denominator).round_dp(PRICE_DP)
+        } else {
+            Decimal::ZERO
+        }
+    };
+
+    let last = divide(base_leg.last, quote_leg.last);
+    if last.is_zero() {
+        return None;
+    }
+    let open = divide(
+        base_leg.last - base_leg.price_change_24h,
+        quote_leg.last - quote_leg.price_change_24h,
+    );
+    let (price_change_24h, percent_change_24h) = MarketData::price_change(open, last);
+
+    Some(MarketData {
+        symbol: symbol.clone(),
+        bid: divide(base_leg.bid, quote_leg.ask),
+        ask: divide(base_leg.ask, quote_leg.bid),
+        last,
+        volume_24h: Decimal::ZERO,
+        high_24h: Decimal::ZERO,
+        low_24h: Decimal::ZERO,
+        price_change_24h,
+        percent_change_24h,
+        fair_value: None,
+        timestamp: base_leg.timestamp.max(quote_leg.timestamp),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn market(symbol: Symbol, bid: i64, ask: i64, last: i64, change: i64) -> MarketData {
+        MarketData {
+            symbol,
+            bid: Decimal::from(bid),
+            ask: Decimal::from(ask),
+            last: Decimal::from(last),
+            volume_24h: Decimal::from(1000),
+            high_24h: Decimal::ZERO,
+            low_24h: Decimal::ZERO,
+            price_change_24h: Decimal::from(change),
+            percent_change_24h: Decimal::ZERO,
+            fair_value: None,
+            timestamp: Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_cross_through_bridge() {
+        let symbol = Symbol::new("SOL", "ETH");
+        let sol = market(Symbol::new("SOL", "USDT"), 99, 101, 100, 10);
+        let eth = market(Symbol::new("ETH", "USDT"), 1980, 2020, 2000, 0);
+
+        let data = cross(&symbol, &sol, &eth).unwrap();
+        assert_eq!(data.last, Decimal::new(5, 2));
+        // Sell SOL at 99, buy ETH at 2020
+        assert_eq!(
+            data.bid,
+            (Decimal::from(99) / Decimal::from(2020)).round_dp(PRICE_DP)
+        );
+        assert_eq!(
+            data.ask,
+            (Decimal::from(101) / Decimal::from(1980)).round_dp(PRICE_DP)
+        );
+        assert!(data.bid < data.last && data.last < data.ask);
+        assert_eq!(data.volume_24h, Decimal::ZERO);
+
+        // Opened at 90 / 2000 = 0.045
+        assert_eq!(data.price_change_24h, Decimal::new(5, 3));
+        assert_eq!(data.percent_change_24h, Decimal::new(111111, 4));
+
+        let unpriced = market(Symbol::new("ETH", "USDT"), 0, 0, 0, 0);
+        assert!(cross(&symbol, &sol, &unpriced).is_none());
+    }
+
+    #[test]
+    fn test_parse_pairs() {
+        let pairs = parse_pairs("sol-eth, BTC-ETH,").unwrap();
+        assert_eq!(
+            pairs,
+            vec![Symbol::new("SOL", "ETH"), Symbol::new("BTC", "ETH")]
+        );
+        assert!(parse_pairs("SOLETH").is_err());
+        assert!(parse_pairs("SOL-").is_err());
+    }
+}
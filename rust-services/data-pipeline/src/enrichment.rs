@@ -0,0 +1,95 @@
+//! Trade Enrichment
+//!
+//! Adds quote notional, fees and USD-normalized values to consumed trades
+//! so downstream analytics and volume stats share a common currency.
+//!
+//! USD rates come from the cached index prices: USD stablecoins are taken
+//! at parity, any other quote asset is converted via its `{ASSET}-USDT`
+//! price.
+
+use std::sync::Arc;
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use tracing::debug;
+
+use crate::cache::RedisCache;
+use crate::config::Config;
+use common::{Symbol, Trade};
+
+/// Quote assets treated as USD at parity
+const USD_ASSETS: [&str; 4] = ["USD", "USDT", "USDC", "DAI"];
+
+/// Trade with derived notional and fee values
+#[derive(Debug, Clone, Serialize)]
+pub struct EnrichedTrade {
+    pub trade: Trade,
+
+    /// Notional in the quote asset
+    #[serde(with = "rust_decimal::serde::str")]
+    pub notional: Decimal,
+
+    /// Fees in the quote asset
+    #[serde(with = "rust_decimal::serde::str")]
+    pub maker_fee: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub taker_fee: Decimal,
+
+    /// Quote asset to USD rate, if known
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub usd_rate: Option<Decimal>,
+
+    /// Notional in USD, if the rate is known
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub notional_usd: Option<Decimal>,
+}
+
+pub struct TradeEnricher {
+    cache: Arc<RedisCache>,
+    maker_fee_rate: Decimal,
+    taker_fee_rate: Decimal,
+}
+
+impl TradeEnricher {
+    pub fn new(cache: Arc<RedisCache>, config: &Config) -> Self {
+        Self {
+            cache,
+            maker_fee_rate: Decimal::new(config.maker_fee_bps as i64, 4),
+            taker_fee_rate: Decimal::new(config.taker_fee_bps as i64, 4),
+        }
+    }
+
+    /// Enrich a trade with notional, fee and USD values
+    pub async fn enrich(&self, trade: Trade) -> EnrichedTrade {
+        let notional = trade.price * trade.quantity;
+        let usd_rate = self.usd_rate(trade.symbol.quote()).await;
+
+        if usd_rate.is_none() {
+            debug!(symbol = %trade.symbol, "No USD rate for quote asset");
+            metrics::counter!("trades_missing_usd_rate").increment(1);
+        }
+
+        EnrichedTrade {
+            notional,
+            maker_fee: notional * self.maker_fee_rate,
+            taker_fee: notional * self.taker_fee_rate,
+            usd_rate,
+            notional_usd: usd_rate.map(|rate| notional * rate),
+            trade,
+        }
+    }
+
+    /// Get the USD rate for an asset from the index price cache
+    async fn usd_rate(&self, asset: &str) -> Option<Decimal> {
+        if USD_ASSETS.contains(&asset) {
+            return Some(Decimal::ONE);
+        }
+
+        self.cache
+            .get_price(&Symbol::new(asset, "USDT"))
+            .await
+            .ok()
+            .flatten()
+    }
+}
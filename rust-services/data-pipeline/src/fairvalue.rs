@@ -0,0 +1,282 @@
+//! Fair-Value References
+//!
+//! Per-symbol VWAP over the configured windows and a TWAP series of the
+//! last price. VWAP windows are fed by trades; the TWAP series is fed by
+//! sampling the last price once a second, and each point averages the
+//! samples taken in its interval. Samples are evenly spaced, so the mean
+//! is the time-weighted price. The TWAP reported with market data covers
+//! the whole series.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, DurationRound, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::rolling::RollingWindow;
+use common::FairValue;
+
+/// How often the last price is sampled into the TWAP series
+pub const TWAP_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Buckets per VWAP window, bounded to between a second and a minute each
+const VWAP_BUCKETS: i32 = 60;
+
+/// VWAP windows and TWAP series shape shared by every symbol
+#[derive(Debug, Clone)]
+pub struct FairValueSettings {
+    vwap_windows: Vec<(String, chrono::Duration)>,
+    twap_interval: chrono::Duration,
+    twap_points: usize,
+}
+
+impl FairValueSettings {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Ok(Self {
+            vwap_windows: parse_windows(&config.vwap_windows)?,
+            twap_interval: chrono::Duration::seconds(config.twap_interval_secs as i64),
+            twap_points: config.twap_points,
+        })
+    }
+}
+
+/// Parse windows such as `1m,1h,24h`; units are s, m, h and d
+pub fn parse_windows(spec: &str) -> Result<Vec<(String, chrono::Duration)>> {
+    let mut windows = Vec::new();
+    for window in spec.split(',').map(str::trim).filter(|w| !w.is_empty()) {
+        let (count, unit) = window.split_at(window.len().saturating_sub(1));
+        let count: i64 = count
+            .parse()
+            .map_err(|_| anyhow!("Invalid VWAP window `{window}`"))?;
+        let length = match unit {
+            "s" => chrono::Duration::seconds(count),
+            "m" => chrono::Duration::minutes(count),
+            "h" => chrono::Duration::hours(count),
+            "d" => chrono::Duration::days(count),
+            _ => bail!("Invalid VWAP window `{window}`: use s, m, h or d"),
+        };
+        if length <= chrono::Duration::zero() {
+            bail!("VWAP window `{window}` must be positive");
+        }
+        windows.push((window.to_string(), length));
+    }
+    Ok(windows)
+}
+
+/// One interval of the TWAP series
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TwapPoint {
+    pub start: DateTime<Utc>,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub twap: Decimal,
+    pub samples: u32,
+}
+
+#[derive(Debug, Clone)]
+struct TwapBucket {
+    start: DateTime<Utc>,
+    sum: Decimal,
+    samples: u32,
+}
+
+/// Fair-value state for one symbol
+#[derive(Debug, Clone)]
+pub struct FairValueTracker {
+    vwap: Vec<(String, RollingWindow)>,
+    twap_interval: chrono::Duration,
+    twap_points: usize,
+    /// Oldest first, one per interval that was sampled
+    twap: VecDeque<TwapBucket>,
+}
+
+impl FairValueTracker {
+    pub fn new(settings: &FairValueSettings) -> Self {
+        let vwap = settings
+            .vwap_windows
+            .iter()
+            .map(|(name, length)| {
+                let bucket = (*length / VWAP_BUCKETS)
+                    .clamp(chrono::Duration::seconds(1), chrono::Duration::minutes(1));
+                (name.clone(), RollingWindow::with_bucket(*length, bucket))
+            })
+            .collect();
+
+        Self {
+            vwap,
+            twap_interval: settings.twap_interval,
+            twap_points: settings.twap_points,
+            twap: VecDeque::new(),
+        }
+    }
+
+    /// Add a trade to every VWAP window
+    pub fn record(
+        &mut self,
+        executed_at: DateTime<Utc>,
+        price: Decimal,
+        quantity: Decimal,
+        notional: Decimal,
+        now: DateTime<Utc>,
+    ) {
+        for (_, window) in &mut self.vwap {
+            window.record(executed_at, price, quantity, notional, None, now);
+            window.prune(now);
+        }
+    }
+
+    /// Roll the VWAP windows forward and add a sample of `last_price` to
+    /// the TWAP series; a zero price (no trades yet) is not sampled
+    pub fn sample(&mut self, last_price: Decimal, now: DateTime<Utc>) {
+        for (_, window) in &mut self.vwap {
+            window.prune(now);
+        }
+
+        // Drop intervals that have left the series, including across gaps
+        let oldest = now - self.twap_interval * self.twap_points as i32;
+        while self
+            .twap
+            .front()
+            .is_some_and(|b| b.start + self.twap_interval <= oldest)
+        {
+            self.twap.pop_front();
+        }
+
+        if last_price <= Decimal::ZERO {
+            return;
+        }
+
+        let start = now.duration_trunc(self.twap_interval).unwrap_or(now);
+        match self.twap.back_mut() {
+            Some(bucket) if bucket.start == start => {
+                bucket.sum += last_price;
+                bucket.samples += 1;
+            }
+            _ => self.twap.push_back(TwapBucket {
+                start,
+                sum: last_price,
+                samples: 1,
+            }),
+        }
+        while self.twap.len() > self.twap_points {
+            self.twap.pop_front();
+        }
+    }
+
+    /// Time-weighted average over the whole series
+    pub fn twap(&self) -> Option<Decimal> {
+        let samples: u32 = self.twap.iter().map(|b| b.samples).sum();
+        (samples > 0).then(|| {
+            let sum: Decimal = self.twap.iter().map(|b| b.sum).sum();
+            sum / Decimal::from(samples)
+        })
+    }
+
+    /// TWAP per interval, oldest first
+    pub fn twap_series(&self) -> Vec<TwapPoint> {
+        self.twap
+            .iter()
+            .map(|b| TwapPoint {
+                start: b.start,
+                twap: b.sum / Decimal::from(b.samples),
+                samples: b.samples,
+            })
+            .collect()
+    }
+
+    pub fn to_fair_value(&self) -> FairValue {
+        FairValue {
+            vwap: self
+                .vwap
+                .iter()
+                .filter_map(|(name, window)| window.vwap().map(|vwap| (name.clone(), vwap)))
+                .collect(),
+            twap: self.twap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn settings() -> FairValueSettings {
+        FairValueSettings {
+            vwap_windows: parse_windows("1m,1h").unwrap(),
+            twap_interval: chrono::Duration::minutes(1),
+            twap_points: 2,
+        }
+    }
+
+    fn at(minute: u32, second: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 2, 10, minute, second)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_windows() {
+        let windows = parse_windows("1m, 1h,24h").unwrap();
+        assert_eq!(windows.len(), 3);
+        assert_eq!(windows[2], ("24h".to_string(), chrono::Duration::hours(24)));
+
+        assert!(parse_windows("1w").is_err());
+        assert!(parse_windows("0m").is_err());
+        assert!(parse_windows("m").is_err());
+    }
+
+    #[test]
+    fn test_vwap_per_window() {
+        let mut tracker = FairValueTracker::new(&settings());
+        let now = at(30, 0);
+        tracker.record(
+            at(10, 0),
+            Decimal::from(100),
+            Decimal::ONE,
+            Decimal::from(100),
+            now,
+        );
+        tracker.record(
+            at(29, 30),
+            Decimal::from(110),
+            Decimal::ONE,
+            Decimal::from(110),
+            now,
+        );
+
+        let fair_value = tracker.to_fair_value();
+        assert_eq!(fair_value.vwap["1m"], Decimal::from(110));
+        assert_eq!(fair_value.vwap["1h"], Decimal::from(105));
+
+        // The 1m window empties once the trade ages out
+        tracker.sample(Decimal::from(110), at(31, 0));
+        let fair_value = tracker.to_fair_value();
+        assert!(!fair_value.vwap.contains_key("1m"));
+        assert_eq!(fair_value.vwap["1h"], Decimal::from(105));
+    }
+
+    #[test]
+    fn test_twap_series_keeps_latest_intervals() {
+        let mut tracker = FairValueTracker::new(&settings());
+        assert_eq!(tracker.twap(), None);
+
+        tracker.sample(Decimal::ZERO, at(0, 0));
+        tracker.sample(Decimal::from(100), at(0, 10));
+        tracker.sample(Decimal::from(102), at(0, 20));
+        tracker.sample(Decimal::from(110), at(1, 0));
+        tracker.sample(Decimal::from(120), at(2, 0));
+
+        let series = tracker.twap_series();
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].start, at(1, 0));
+        assert_eq!(series[1].twap, Decimal::from(120));
+        assert_eq!(tracker.twap(), Some(Decimal::from(115)));
+
+        // A gap longer than the series leaves only the new interval
+        tracker.sample(Decimal::from(130), at(10, 0));
+        assert_eq!(tracker.twap_series().len(), 1);
+        assert_eq!(tracker.twap(), Some(Decimal::from(130)));
+    }
+}
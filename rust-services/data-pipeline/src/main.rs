@@ -2,9 +2,14 @@
 //!
 //! High-throughput data pipeline for:
 //! - Real-time price aggregation
+//! - VWAP and TWAP fair-value references
 //! - Trade stream processing
 //! - Market data distribution
+//! - Synthetic cross rates for pairs without a direct market
 //! - Position and PnL calculation
+//! - Daily settlement prices
+//! - User fill notifications
+//! - Candle verification against the trade history
 
 use anyhow::Result;
 use std::sync::Arc;
@@ -15,14 +20,27 @@ mod aggregator;
 mod cache;
 mod config;
 mod consumer;
+mod crossrate;
+mod enrichment;
+mod fairvalue;
+mod metrics;
+mod midprice;
+mod notifications;
+mod positions;
 mod publisher;
+mod refdata;
+mod retention;
+mod rolling;
+mod settlement;
+mod stream;
+mod verify;
 
 use config::Config;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenvy::dotenv().ok();
-    let config = Config::load()?;
+    let config: Config = common::config::load()?;
 
     // Initialize tracing
     tracing_subscriber::registry()
@@ -35,11 +53,43 @@ async fn main() -> Result<()> {
         env!("CARGO_PKG_VERSION")
     );
 
+    // Initialize metrics
+    metrics::init_metrics(&config)?;
+
     // Initialize Redis cache
     let cache = Arc::new(cache::RedisCache::new(&config.redis_url).await?);
 
+    // Verify: check candles against the trade history, print and exit
+    if let Some(start) = config.candle_verify_start {
+        let end = config.candle_verify_end.unwrap_or_else(chrono::Utc::now);
+        let verifier = verify::CandleVerifier::from_config(&config, &cache).await?;
+        let report = verifier.run(start, end).await?;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    // WebSocket fan-out
+    let stream = Arc::new(stream::StreamHub::new(stream::StreamLimits::from_config(
+        &config,
+    )));
+    stream::log_limits(&stream);
+
     // Initialize price aggregator
-    let aggregator = Arc::new(aggregator::PriceAggregator::new(cache.clone()));
+    let enricher = enrichment::TradeEnricher::new(cache.clone(), &config);
+    let midprice = Arc::new(midprice::MidpriceConflator::new());
+    let settlement = Arc::new(settlement::SettlementTracker::from_config(&config));
+    let fair_value = fairvalue::FairValueSettings::from_config(&config)?;
+    let cross_rates = Arc::new(crossrate::CrossRates::from_config(&config)?);
+    let clock: common::SharedClock = Arc::new(common::HybridClock::system());
+    let aggregator = Arc::new(aggregator::PriceAggregator::new(
+        cache.clone(),
+        enricher,
+        midprice.clone(),
+        stream.clone(),
+        settlement.clone(),
+        fair_value,
+        clock.clone(),
+    ));
 
     // Start trade consumer
     let agg_clone = aggregator.clone();
@@ -50,25 +100,148 @@ async fn main() -> Result<()> {
         }
     });
 
+    // Start order book consumer
+    let agg_clone = aggregator.clone();
+    let config_clone = config.clone();
+    tokio::spawn(async move {
+        if let Err(e) = consumer::run_orderbook_consumer(agg_clone, &config_clone).await {
+            tracing::error!("Order book consumer error: {}", e);
+        }
+    });
+
+    // Start conflated midprice publisher
+    let cache_clone = cache.clone();
+    let stream_clone = stream.clone();
+    let config_clone = config.clone();
+    tokio::spawn(async move {
+        if let Err(e) =
+            midprice::run_midprice_publisher(midprice, cache_clone, stream_clone, &config_clone)
+                .await
+        {
+            tracing::error!("Midprice publisher error: {}", e);
+        }
+    });
+
+    // Keep user positions and PnL
+    let positions = Arc::new(positions::PositionTracker::new());
+    let positions_clone = positions.clone();
+    let cache_clone = cache.clone();
+    let config_clone = config.clone();
+    tokio::spawn(async move {
+        if let Err(e) =
+            positions::run_position_consumer(positions_clone, cache_clone, &config_clone).await
+        {
+            tracing::error!("Position consumer error: {}", e);
+        }
+    });
+
+    let positions_clone = positions.clone();
+    let cache_clone = cache.clone();
+    let config_clone = config.clone();
+    tokio::spawn(async move {
+        if let Err(e) =
+            positions::run_position_marking(positions_clone, cache_clone, &config_clone).await
+        {
+            tracing::error!("Position marking error: {}", e);
+        }
+    });
+
     // Start price publisher
     let agg_clone = aggregator.clone();
+    let cross_rates_clone = cross_rates.clone();
     let config_clone = config.clone();
     tokio::spawn(async move {
-        if let Err(e) = publisher::run_price_publisher(agg_clone, &config_clone).await {
+        if let Err(e) =
+            publisher::run_price_publisher(agg_clone, cross_rates_clone, &config_clone).await
+        {
             tracing::error!("Price publisher error: {}", e);
         }
     });
 
+    // Start daily settlement publisher
+    let cache_clone = cache.clone();
+    let clock_clone = clock.clone();
+    let config_clone = config.clone();
+    tokio::spawn(async move {
+        if let Err(e) = settlement::run_settlement_publisher(
+            settlement,
+            cache_clone,
+            clock_clone,
+            &config_clone,
+        )
+        .await
+        {
+            tracing::error!("Settlement publisher error: {}", e);
+        }
+    });
+
+    // Roll 24-hour stats forward
+    let agg_clone = aggregator.clone();
+    tokio::spawn(async move {
+        if let Err(e) = aggregator::run_stats_pruning(agg_clone).await {
+            tracing::error!("Stats pruning error: {}", e);
+        }
+    });
+
+    // Sample VWAP and TWAP references
+    let agg_clone = aggregator.clone();
+    tokio::spawn(async move {
+        if let Err(e) = aggregator::run_fair_value_sampling(agg_clone).await {
+            tracing::error!("Fair-value sampling error: {}", e);
+        }
+    });
+
     // Start candle aggregation
     let agg_clone = aggregator.clone();
+    let config_clone = config.clone();
     tokio::spawn(async move {
-        if let Err(e) = aggregator::run_candle_aggregation(agg_clone).await {
+        if let Err(e) = aggregator::run_candle_aggregation(agg_clone, &config_clone).await {
             tracing::error!("Candle aggregation error: {}", e);
         }
     });
 
-    // Run HTTP API for health checks and metrics
-    publisher::run_api_server(&config).await?;
+    // Prune candles past retention
+    let retention =
+        Arc::new(retention::RetentionController::from_config(&config, cache.clone(), clock).await?);
+    let config_clone = config.clone();
+    tokio::spawn(async move {
+        if let Err(e) = retention::run_retention(retention, &config_clone).await {
+            tracing::error!("Candle retention error: {}", e);
+        }
+    });
+
+    // Load symbol reference data and keep it fresh
+    let refdata = Arc::new(refdata::ReferenceData::new());
+    if let Some(url) = config.reference_data_url.clone() {
+        let registry = refdata.registry();
+        let brokers = config.kafka_brokers.clone();
+        let group = config.kafka_group_id.clone();
+        tokio::spawn(async move {
+            if let Err(e) = common::refdata::client::run_sync(registry, url, brokers, group).await {
+                tracing::error!("Reference data sync error: {}", e);
+            }
+        });
+    } else {
+        let refdata_clone = refdata.clone();
+        let config_clone = config.clone();
+        tokio::spawn(async move {
+            if let Err(e) = refdata::run_refdata_loader(refdata_clone, &config_clone).await {
+                tracing::error!("Reference data loader error: {}", e);
+            }
+        });
+    }
+
+    // Run HTTP API for health checks and market data
+    publisher::run_api_server(
+        aggregator,
+        cross_rates,
+        positions,
+        refdata,
+        stream,
+        cache,
+        &config,
+    )
+    .await?;
 
     Ok(())
 }
@@ -0,0 +1,119 @@
+//! Prometheus metrics for observability
+//!
+//! Exposes metrics for:
+//! - Trade throughput and enrichment gaps
+//! - Candle closures
+//! - Kafka consumer lag
+//! - Published market data
+//! - WebSocket clients and slow consumers
+//! - Candle retention
+
+use anyhow::Result;
+use metrics_exporter_prometheus::PrometheusBuilder;
+use std::net::SocketAddr;
+
+use crate::config::Config;
+
+/// Initialize metrics exporter
+pub fn init_metrics(config: &Config) -> Result<()> {
+    let addr: SocketAddr = format!("0.0.0.0:{}", config.metrics_port).parse()?;
+
+    PrometheusBuilder::new()
+        .with_http_listener(addr)
+        .install()?;
+
+    // Register pipeline metrics
+    metrics::describe_counter!("trades_processed", "Total trades aggregated");
+
+    metrics::describe_counter!(
+        "trades_missing_usd_rate",
+        "Trades enriched without a USD conversion rate"
+    );
+
+    metrics::describe_counter!(
+        "candle_closures",
+        "Candles closed, by a trade in a later interval or by the timer"
+    );
+
+    metrics::describe_counter!(
+        "candle_late_trades",
+        "Trades left out of an interval's candle because it had closed"
+    );
+
+    metrics::describe_counter!(
+        "candles_published",
+        "Closed candles published to Kafka, by interval"
+    );
+
+    metrics::describe_gauge!(
+        "consumer_lag",
+        "Messages behind the partition high watermark"
+    );
+
+    metrics::describe_counter!(
+        "midprice_updates_conflated",
+        "Midprice updates dropped by conflation"
+    );
+
+    metrics::describe_counter!("midprice_ticks_published", "Midprice ticks published");
+    metrics::describe_counter!(
+        "position_updates_published",
+        "Position updates published to risk.positions"
+    );
+    metrics::describe_counter!(
+        "synthetic_tickers_published",
+        "Synthetic cross-rate tickers published to market.prices"
+    );
+    metrics::describe_counter!(
+        "market_data_published",
+        "Market data snapshots published to market.prices"
+    );
+
+    metrics::describe_gauge!("last_price", "Last traded price per symbol");
+
+    metrics::describe_gauge!("ws_clients", "Connected WebSocket clients");
+
+    metrics::describe_counter!(
+        "ws_clients_rejected",
+        "WebSocket connections refused at the client limit"
+    );
+
+    metrics::describe_counter!(
+        "ws_messages_dropped",
+        "Messages dropped from full client send queues"
+    );
+
+    metrics::describe_counter!(
+        "ws_messages_conflated",
+        "Queued messages replaced by a newer value on the same channel"
+    );
+
+    metrics::describe_counter!(
+        "ws_slow_consumer_disconnects",
+        "Clients disconnected for a full send queue"
+    );
+
+    metrics::describe_counter!(
+        "retention_entries_pruned",
+        "Candles pruned past retention, by store and interval"
+    );
+
+    metrics::describe_counter!(
+        "retention_bytes_reclaimed",
+        "Bytes freed by retention, by store"
+    );
+
+    metrics::describe_counter!(
+        "retention_rows_archived",
+        "Postgres candles written to Parquet before deletion"
+    );
+
+    metrics::describe_counter!(
+        "candle_discrepancies",
+        "Stored candles that differ from the trade history, by store and interval"
+    );
+
+    tracing::info!("Metrics server started on port {}", config.metrics_port);
+
+    Ok(())
+}
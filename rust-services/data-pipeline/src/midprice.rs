@@ -0,0 +1,136 @@
+//! Conflated Midprice Stream
+//!
+//! Publishes top-of-book midprices to Redis and Kafka at most once per
+//! conflation window per symbol. Updates arriving within a window replace
+//! the pending value (latest wins) and are counted as conflated.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use dashmap::DashMap;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::ClientConfig;
+use rust_decimal::Decimal;
+use tokio::time;
+use tracing::{info, warn};
+
+use crate::cache::RedisCache;
+use crate::config::Config;
+use crate::stream::StreamHub;
+use common::{
+    events::{topics, Event, MidpriceTick},
+    Symbol,
+};
+
+/// Latest-wins buffer of pending midprice ticks per symbol
+#[derive(Default)]
+pub struct MidpriceConflator {
+    pending: DashMap<String, MidpriceTick>,
+}
+
+impl MidpriceConflator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Offer a new top of book; returns true if it replaced a pending tick
+    pub fn offer(&self, symbol: &Symbol, bid: Decimal, ask: Decimal) -> bool {
+        if bid <= Decimal::ZERO || ask <= Decimal::ZERO {
+            return false;
+        }
+
+        let tick = MidpriceTick {
+            symbol: symbol.clone(),
+            mid: (bid + ask) / Decimal::TWO,
+            bid,
+            ask,
+            timestamp: Utc::now(),
+        };
+
+        let replaced = self.pending.insert(symbol.to_string(), tick).is_some();
+        if replaced {
+            metrics::counter!("midprice_updates_conflated", "symbol" => symbol.to_string())
+                .increment(1);
+        }
+        replaced
+    }
+
+    /// Take all pending ticks, leaving the buffer empty
+    pub fn drain(&self) -> Vec<MidpriceTick> {
+        let keys: Vec<String> = self.pending.iter().map(|e| e.key().clone()).collect();
+        keys.into_iter()
+            .filter_map(|k| self.pending.remove(&k).map(|(_, tick)| tick))
+            .collect()
+    }
+}
+
+/// Run the midprice publisher task
+pub async fn run_midprice_publisher(
+    conflator: Arc<MidpriceConflator>,
+    cache: Arc<RedisCache>,
+    stream: Arc<StreamHub>,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let producer: FutureProducer = ClientConfig::new()
+        .set("bootstrap.servers", &config.kafka_brokers)
+        .set("message.timeout.ms", "5000")
+        .create()?;
+
+    let mut interval = time::interval(Duration::from_millis(config.midprice_conflation_ms));
+
+    info!(
+        "Midprice publisher started with {}ms conflation window",
+        config.midprice_conflation_ms
+    );
+
+    loop {
+        interval.tick().await;
+
+        for tick in conflator.drain() {
+            if let Err(e) = cache.set_midprice(&tick.symbol, tick.mid).await {
+                warn!(symbol = %tick.symbol, "Failed to cache midprice: {}", e);
+            }
+
+            stream.publish(&format!("midprice:{}", tick.symbol), &tick);
+
+            let key = tick.symbol.to_string();
+            let event = Event::new("midprice_tick", "data-pipeline", tick);
+            let payload = serde_json::to_string(&event)?;
+
+            if let Err((e, _)) = producer
+                .send(
+                    FutureRecord::to(topics::MIDPRICES)
+                        .key(&key)
+                        .payload(&payload),
+                    Duration::from_secs(5),
+                )
+                .await
+            {
+                warn!(symbol = %key, "Failed to publish midprice: {}", e);
+                continue;
+            }
+
+            metrics::counter!("midprice_ticks_published", "symbol" => key).increment(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latest_tick_wins_within_window() {
+        let conflator = MidpriceConflator::new();
+        let symbol = Symbol::new("ETH", "USDT");
+
+        assert!(!conflator.offer(&symbol, Decimal::new(1999, 0), Decimal::new(2001, 0)));
+        assert!(conflator.offer(&symbol, Decimal::new(2001, 0), Decimal::new(2003, 0)));
+
+        let ticks = conflator.drain();
+        assert_eq!(ticks.len(), 1);
+        assert_eq!(ticks[0].mid, Decimal::new(2002, 0));
+        assert!(conflator.drain().is_empty());
+    }
+}
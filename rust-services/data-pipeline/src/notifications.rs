@@ -0,0 +1,130 @@
+//! User Fill Notifications
+//!
+//! Every consumed trade is fanned out as one concise fill notification per
+//! side to the Redis channel `fills:{user_id}`, so the WebSocket gateway
+//! and notification service can push "your order filled" messages
+//! without consuming Kafka themselves.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::enrichment::EnrichedTrade;
+use common::{Side, Symbol};
+
+/// Whether the filled order was resting on the book or took liquidity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Liquidity {
+    Maker,
+    Taker,
+}
+
+/// One user's side of a trade
+#[derive(Debug, Clone, Serialize)]
+pub struct FillNotification {
+    pub user_id: Uuid,
+    pub order_id: Uuid,
+    pub trade_id: u64,
+    pub symbol: Symbol,
+    /// Side of the user's order
+    pub side: Side,
+    pub liquidity: Liquidity,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub price: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub quantity: Decimal,
+    /// Fee in the quote asset
+    #[serde(with = "rust_decimal::serde::str")]
+    pub fee: Decimal,
+    pub executed_at: DateTime<Utc>,
+}
+
+impl FillNotification {
+    /// Maker and taker notifications for a trade
+    pub fn for_trade(enriched: &EnrichedTrade) -> [Self; 2] {
+        let trade = &enriched.trade;
+        let maker_side = match trade.taker_side {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        };
+        let fill = |user_id, order_id, side, liquidity, fee| Self {
+            user_id,
+            order_id,
+            trade_id: trade.trade_id,
+            symbol: trade.symbol.clone(),
+            side,
+            liquidity,
+            price: trade.price,
+            quantity: trade.quantity,
+            fee,
+            executed_at: trade.executed_at,
+        };
+
+        [
+            fill(
+                trade.maker_user_id,
+                trade.maker_order_id,
+                maker_side,
+                Liquidity::Maker,
+                enriched.maker_fee,
+            ),
+            fill(
+                trade.taker_user_id,
+                trade.taker_order_id,
+                trade.taker_side,
+                Liquidity::Taker,
+                enriched.taker_fee,
+            ),
+        ]
+    }
+
+    /// Redis channel the notification is published to
+    pub fn channel(&self) -> String {
+        format!("fills:{}", self.user_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::Trade;
+
+    #[test]
+    fn test_fills_for_both_sides() {
+        let trade = Trade {
+            id: Uuid::new_v4(),
+            trade_id: 7,
+            symbol: Symbol::new("ETH", "USDT"),
+            maker_order_id: Uuid::new_v4(),
+            maker_user_id: Uuid::new_v4(),
+            taker_order_id: Uuid::new_v4(),
+            taker_user_id: Uuid::new_v4(),
+            price: Decimal::new(2000, 0),
+            quantity: Decimal::ONE,
+            quote_quantity: Decimal::new(2000, 0),
+            taker_side: Side::Buy,
+            executed_at: Utc::now(),
+        };
+        let enriched = EnrichedTrade {
+            notional: trade.quote_quantity,
+            maker_fee: Decimal::ONE,
+            taker_fee: Decimal::TWO,
+            usd_rate: None,
+            notional_usd: None,
+            trade: trade.clone(),
+        };
+
+        let [maker, taker] = FillNotification::for_trade(&enriched);
+        assert_eq!(maker.channel(), format!("fills:{}", trade.maker_user_id));
+        assert_eq!(
+            (maker.side, maker.liquidity),
+            (Side::Sell, Liquidity::Maker)
+        );
+        assert_eq!(maker.fee, Decimal::ONE);
+        assert_eq!(taker.order_id, trade.taker_order_id);
+        assert_eq!((taker.side, taker.liquidity), (Side::Buy, Liquidity::Taker));
+        assert_eq!(taker.fee, Decimal::TWO);
+    }
+}
@@ -0,0 +1,428 @@
+//! Position and PnL Calculation
+//!
+//! Consumes executed trades on its own consumer group and keeps every
+//! user's net position per symbol: signed quantity, average entry price,
+//! realized PnL and unrealized PnL marked against the symbol's last trade
+//! price. A fill in the position's direction moves the average entry; a
+//! fill against it realizes PnL on the closed quantity, and any excess
+//! opens the other way at the fill price. PnL is in the quote asset,
+//! before fees.
+//!
+//! Positions are cached in Redis and restored from there at startup. Each
+//! fill publishes a `PositionUpdate` for the two users involved; other
+//! holders are re-marked and published on an interval.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use rdkafka::{
+    consumer::{Consumer, StreamConsumer},
+    producer::{FutureProducer, FutureRecord},
+    ClientConfig, Message,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tokio::time;
+use tokio_stream::StreamExt;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::cache::RedisCache;
+use crate::config::Config;
+use common::events::{topics, Event, PositionUpdate, TradeExecuted};
+use common::{Side, Symbol, Trade};
+
+/// One user's net position in a symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub user_id: Uuid,
+    pub symbol: Symbol,
+    /// Negative when short
+    #[serde(with = "rust_decimal::serde::str")]
+    pub quantity: Decimal,
+    /// Zero when flat
+    #[serde(with = "rust_decimal::serde::str")]
+    pub avg_entry_price: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub realized_pnl: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub mark_price: Decimal,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Position {
+    pub fn new(user_id: Uuid, symbol: Symbol, now: DateTime<Utc>) -> Self {
+        Self {
+            user_id,
+            symbol,
+            quantity: Decimal::ZERO,
+            avg_entry_price: Decimal::ZERO,
+            realized_pnl: Decimal::ZERO,
+            mark_price: Decimal::ZERO,
+            updated_at: now,
+        }
+    }
+
+    /// Apply a fill of the user's order, returning the PnL it realized
+    pub fn apply_fill(
+        &mut self,
+        side: Side,
+        price: Decimal,
+        quantity: Decimal,
+        at: DateTime<Utc>,
+    ) -> Decimal {
+        let fill = match side {
+            Side::Buy => quantity,
+            Side::Sell => -quantity,
+        };
+        let held = self.quantity;
+        let mut realized = Decimal::ZERO;
+
+        if held.is_zero() || held.is_sign_positive() == fill.is_sign_positive() {
+            let size = held.abs() + quantity;
+            self.avg_entry_price = (self.avg_entry_price * held.abs() + price * quantity) / size;
+        } else {
+            let closed = held.abs().min(quantity);
+            realized = (price - self.avg_entry_price) * closed * held.signum();
+            if quantity > held.abs() {
+                // Flipped: the excess opens at the fill price
+                self.avg_entry_price = price;
+            } else if quantity == held.abs() {
+                self.avg_entry_price = Decimal::ZERO;
+            }
+        }
+
+        self.quantity = held + fill;
+        self.realized_pnl += realized;
+        self.mark_price = price;
+        self.updated_at = at;
+        realized
+    }
+
+    /// PnL of the open quantity at the mark price
+    pub fn unrealized_pnl(&self) -> Decimal {
+        if self.quantity.is_zero() || self.mark_price.is_zero() {
+            return Decimal::ZERO;
+        }
+        (self.mark_price - self.avg_entry_price) * self.quantity
+    }
+
+    pub fn to_update(&self) -> PositionUpdate {
+        PositionUpdate {
+            user_id: self.user_id,
+            symbol: self.symbol.clone(),
+            quantity: self.quantity,
+            avg_entry_price: self.avg_entry_price,
+            mark_price: self.mark_price,
+            unrealized_pnl: self.unrealized_pnl(),
+            realized_pnl: self.realized_pnl,
+            timestamp: self.updated_at,
+        }
+    }
+}
+
+/// Positions per symbol per user
+#[derive(Default)]
+pub struct PositionTracker {
+    positions: DashMap<String, HashMap<Uuid, Position>>,
+    /// Latest trade price of symbols traded since they were last re-marked
+    marks: DashMap<String, (Decimal, DateTime<Utc>)>,
+}
+
+impl PositionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restore positions, e.g. from the Redis cache at startup
+    pub fn restore(&self, positions: Vec<Position>) {
+        for position in positions {
+            self.positions
+                .entry(position.symbol.to_string())
+                .or_default()
+                .insert(position.user_id, position);
+        }
+    }
+
+    /// Apply both sides of a trade, returning the positions it changed
+    ///
+    /// A self-trade applies a buy and a sell of the same quantity to one
+    /// position.
+    pub fn on_trade(&self, trade: &Trade) -> Vec<Position> {
+        let maker_side = match trade.taker_side {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        };
+
+        let mut book = self.positions.entry(trade.symbol.to_string()).or_default();
+        for (user_id, side) in [
+            (trade.taker_user_id, trade.taker_side),
+            (trade.maker_user_id, maker_side),
+        ] {
+            book.entry(user_id)
+                .or_insert_with(|| Position::new(user_id, trade.symbol.clone(), trade.executed_at))
+                .apply_fill(side, trade.price, trade.quantity, trade.executed_at);
+        }
+
+        let mut changed: Vec<Position> = Vec::new();
+        for user_id in [trade.taker_user_id, trade.maker_user_id] {
+            if !changed.iter().any(|p| p.user_id == user_id) {
+                changed.extend(book.get(&user_id).cloned());
+            }
+        }
+        drop(book);
+
+        self.marks
+            .insert(trade.symbol.to_string(), (trade.price, trade.executed_at));
+        changed
+    }
+
+    /// Mark open positions against the latest trade prices, returning the
+    /// positions whose mark moved
+    pub fn mark(&self) -> Vec<Position> {
+        let symbols: Vec<String> = self.marks.iter().map(|e| e.key().clone()).collect();
+
+        let mut marked = Vec::new();
+        for (symbol, (price, at)) in symbols.iter().filter_map(|s| self.marks.remove(s)) {
+            let Some(mut book) = self.positions.get_mut(&symbol) else {
+                continue;
+            };
+            for position in book.values_mut() {
+                if position.quantity.is_zero() || position.mark_price == price {
+                    continue;
+                }
+                position.mark_price = price;
+                position.updated_at = at;
+                marked.push(position.clone());
+            }
+        }
+        marked
+    }
+
+    /// A user's positions, including closed ones with realized PnL
+    pub fn user_positions(&self, user_id: Uuid) -> Vec<Position> {
+        let mut positions: Vec<Position> = self
+            .positions
+            .iter()
+            .filter_map(|book| book.get(&user_id).cloned())
+            .collect();
+        positions.sort_by(|a, b| a.symbol.0.cmp(&b.symbol.0));
+        positions
+    }
+}
+
+/// Cache and publish position updates
+struct PositionPublisher {
+    cache: Arc<RedisCache>,
+    producer: FutureProducer,
+}
+
+impl PositionPublisher {
+    async fn publish(&self, position: &Position) {
+        if let Err(e) = self.cache.set_position(position).await {
+            warn!(user_id = %position.user_id, symbol = %position.symbol, "Failed to cache position: {}", e);
+        }
+
+        let outbound = Event::builder(position.to_update())
+            .source("data-pipeline")
+            .build();
+        let payload = match outbound.to_json() {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("Failed to serialize position update: {}", e);
+                return;
+            }
+        };
+        if let Err((e, _)) = self
+            .producer
+            .send(
+                FutureRecord::to(outbound.topic)
+                    .key(&outbound.key)
+                    .payload(&payload),
+                Duration::from_secs(5),
+            )
+            .await
+        {
+            warn!(user_id = %position.user_id, "Failed to publish position update: {}", e);
+            return;
+        }
+        metrics::counter!("position_updates_published").increment(1);
+    }
+}
+
+fn producer(config: &Config) -> Result<FutureProducer> {
+    Ok(ClientConfig::new()
+        .set("bootstrap.servers", &config.kafka_brokers)
+        .set("message.timeout.ms", "5000")
+        .create()?)
+}
+
+/// Apply executed trades to positions, caching and publishing every
+/// position a trade changed
+pub async fn run_position_consumer(
+    tracker: Arc<PositionTracker>,
+    cache: Arc<RedisCache>,
+    config: &Config,
+) -> Result<()> {
+    let restored = cache.load_positions().await?;
+    info!("Restored {} positions from cache", restored.len());
+    tracker.restore(restored);
+
+    let publisher = PositionPublisher {
+        cache,
+        producer: producer(config)?,
+    };
+
+    // A separate group, so positions see every trade the aggregator sees
+    let consumer: StreamConsumer = ClientConfig::new()
+        .set("bootstrap.servers", &config.kafka_brokers)
+        .set("group.id", format!("{}-positions", config.kafka_group_id))
+        .set("enable.auto.commit", "true")
+        .set("auto.offset.reset", "latest")
+        .create()?;
+    consumer.subscribe(&[topics::TRADES])?;
+
+    info!(
+        "Position consumer started, subscribed to {}",
+        topics::TRADES
+    );
+
+    let mut stream = consumer.stream();
+    while let Some(message) = stream.next().await {
+        let msg = match message {
+            Ok(msg) => msg,
+            Err(e) => {
+                warn!("Kafka error: {}", e);
+                continue;
+            }
+        };
+        let Some(payload) = msg.payload() else {
+            continue;
+        };
+        match serde_json::from_slice::<Event<TradeExecuted>>(payload) {
+            Ok(event) => {
+                for position in tracker.on_trade(&event.payload.trade) {
+                    publisher.publish(&position).await;
+                }
+            }
+            Err(e) => warn!("Failed to parse trade event for positions: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Re-mark open positions against the last price and publish the ones
+/// that moved
+pub async fn run_position_marking(
+    tracker: Arc<PositionTracker>,
+    cache: Arc<RedisCache>,
+    config: &Config,
+) -> Result<()> {
+    let publisher = PositionPublisher {
+        cache,
+        producer: producer(config)?,
+    };
+    let mut interval = time::interval(Duration::from_secs(config.position_mark_interval_secs));
+
+    loop {
+        interval.tick().await;
+        for position in tracker.mark() {
+            publisher.publish(&position).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 2, 10, minute, 0).unwrap()
+    }
+
+    fn position() -> Position {
+        Position::new(Uuid::new_v4(), Symbol::new("ETH", "USDT"), at(0))
+    }
+
+    #[test]
+    fn test_adding_moves_average_entry() {
+        let mut position = position();
+        position.apply_fill(Side::Buy, Decimal::from(100), Decimal::ONE, at(1));
+        position.apply_fill(Side::Buy, Decimal::from(130), Decimal::TWO, at(2));
+
+        assert_eq!(position.quantity, Decimal::from(3));
+        assert_eq!(position.avg_entry_price, Decimal::from(120));
+        assert_eq!(position.realized_pnl, Decimal::ZERO);
+        assert_eq!(position.unrealized_pnl(), Decimal::from(30));
+    }
+
+    #[test]
+    fn test_reducing_realizes_and_flipping_reopens() {
+        let mut position = position();
+        position.apply_fill(Side::Buy, Decimal::from(100), Decimal::from(2), at(1));
+
+        let realized = position.apply_fill(Side::Sell, Decimal::from(110), Decimal::ONE, at(2));
+        assert_eq!(realized, Decimal::from(10));
+        assert_eq!(position.avg_entry_price, Decimal::from(100));
+
+        // Sell 3 more: closes the last 1 at +5 and opens 2 short at 105
+        let realized = position.apply_fill(Side::Sell, Decimal::from(105), Decimal::from(3), at(3));
+        assert_eq!(realized, Decimal::from(5));
+        assert_eq!(position.quantity, Decimal::from(-2));
+        assert_eq!(position.avg_entry_price, Decimal::from(105));
+        assert_eq!(position.realized_pnl, Decimal::from(15));
+
+        // Short loses as the price rises
+        position.mark_price = Decimal::from(108);
+        assert_eq!(position.unrealized_pnl(), Decimal::from(-6));
+
+        position.apply_fill(Side::Buy, Decimal::from(100), Decimal::from(2), at(4));
+        assert!(position.quantity.is_zero());
+        assert_eq!(position.avg_entry_price, Decimal::ZERO);
+        assert_eq!(position.realized_pnl, Decimal::from(25));
+        assert_eq!(position.unrealized_pnl(), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_trades_update_both_sides_and_mark_holders() {
+        let tracker = PositionTracker::new();
+        let symbol = Symbol::new("ETH", "USDT");
+        let (alice, bob, carol) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let trade = |taker, maker, taker_side, price: i64| Trade {
+            id: Uuid::new_v4(),
+            trade_id: 1,
+            symbol: symbol.clone(),
+            maker_order_id: Uuid::new_v4(),
+            maker_user_id: maker,
+            taker_order_id: Uuid::new_v4(),
+            taker_user_id: taker,
+            price: Decimal::from(price),
+            quantity: Decimal::ONE,
+            quote_quantity: Decimal::from(price),
+            taker_side,
+            executed_at: at(1),
+        };
+
+        let changed = tracker.on_trade(&trade(alice, bob, Side::Buy, 100));
+        assert_eq!(changed.len(), 2);
+        assert_eq!(tracker.user_positions(alice)[0].quantity, Decimal::ONE);
+        assert_eq!(tracker.user_positions(bob)[0].quantity, -Decimal::ONE);
+        // Both parties were marked by the trade itself
+        assert!(tracker.mark().is_empty());
+
+        tracker.on_trade(&trade(carol, carol, Side::Sell, 110));
+        assert!(tracker.user_positions(carol)[0].quantity.is_zero());
+
+        let marked = tracker.mark();
+        assert_eq!(marked.len(), 2);
+        let alice_position = &tracker.user_positions(alice)[0];
+        assert_eq!(alice_position.mark_price, Decimal::from(110));
+        assert_eq!(alice_position.unrealized_pnl(), Decimal::from(10));
+    }
+}
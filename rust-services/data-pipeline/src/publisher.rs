@@ -1,21 +1,54 @@
 //! Price Publisher and API Server
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
-use axum::{routing::get, Json, Router};
+use axum::{
+    extract::{FromRef, Path, Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::ClientConfig;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use tokio::time;
 use tower_http::trace::TraceLayer;
-use tracing::info;
+use tracing::{info, warn};
+use uuid::Uuid;
 
-use crate::aggregator::PriceAggregator;
+use crate::aggregator::{PriceAggregator, CANDLE_INTERVALS};
+use crate::cache::RedisCache;
 use crate::config::Config;
+use crate::crossrate::CrossRates;
+use crate::fairvalue::TwapPoint;
+use crate::positions::{Position, PositionTracker};
+use crate::refdata::ReferenceData;
+use crate::stream::{self, StreamHub};
+use common::{
+    events::{Event, PositionUpdate, SyntheticTicker, TypedEvent},
+    Candle, FairValue, MarketData, SettlementPrice, Symbol, SymbolInfo,
+};
 
 /// Run price publisher task
+///
+/// Tickers, synthetic ones included, go to WebSocket subscribers every
+/// interval; they are published to `market.prices` only when they changed
+/// since they were last published.
 pub async fn run_price_publisher(
     aggregator: Arc<PriceAggregator>,
+    cross_rates: Arc<CrossRates>,
     config: &Config,
 ) -> anyhow::Result<()> {
+    let producer: FutureProducer = ClientConfig::new()
+        .set("bootstrap.servers", &config.kafka_brokers)
+        .set("message.timeout.ms", "5000")
+        .create()?;
+
+    let mut published: HashMap<String, MarketData> = HashMap::new();
     let mut interval = time::interval(Duration::from_millis(config.publish_interval_ms));
 
     info!(
@@ -30,19 +63,108 @@ pub async fn run_price_publisher(
         let market_data = aggregator.get_all_market_data();
 
         for data in market_data {
-            // Publish to Redis pub/sub
-            // This is picked up by WebSocket servers
+            let channel = format!("ticker:{}", data.symbol);
+            if aggregator.stream().has_subscribers(&channel) {
+                aggregator.stream().publish(&channel, &data);
+            }
+
             metrics::gauge!("last_price", "symbol" => data.symbol.to_string())
                 .set(data.last.to_string().parse::<f64>().unwrap_or(0.0));
+
+            let key = data.symbol.to_string();
+            if published.get(&key) == Some(&data) {
+                continue;
+            }
+            if send(&producer, data.clone()).await? {
+                metrics::counter!("market_data_published").increment(1);
+                published.insert(key, data);
+            }
+        }
+
+        for ticker in cross_rates.tickers(&aggregator) {
+            let channel = format!("ticker:{}", ticker.data.symbol);
+            if aggregator.stream().has_subscribers(&channel) {
+                aggregator.stream().publish(&channel, &ticker);
+            }
+
+            let key = ticker.data.symbol.to_string();
+            if published.get(&key) == Some(&ticker.data) {
+                continue;
+            }
+            let data = ticker.data.clone();
+            if send(&producer, ticker).await? {
+                metrics::counter!("synthetic_tickers_published").increment(1);
+                published.insert(key, data);
+            }
         }
     }
 }
 
-/// Run API server for health checks
-pub async fn run_api_server(config: &Config) -> anyhow::Result<()> {
+/// Publish to the payload's topic, returning false if the send failed
+async fn send<T: TypedEvent>(producer: &FutureProducer, payload: T) -> anyhow::Result<bool> {
+    let outbound = Event::builder(payload).source("data-pipeline").build();
+    let json = outbound.to_json()?;
+
+    if let Err((e, _)) = producer
+        .send(
+            FutureRecord::to(outbound.topic)
+                .key(&outbound.key)
+                .payload(&json),
+            Duration::from_secs(5),
+        )
+        .await
+    {
+        warn!(key = %outbound.key, "Failed to publish to {}: {}", outbound.topic, e);
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+#[derive(Clone)]
+struct AppState {
+    aggregator: Arc<PriceAggregator>,
+    cross_rates: Arc<CrossRates>,
+    positions: Arc<PositionTracker>,
+    refdata: Arc<ReferenceData>,
+    stream: Arc<StreamHub>,
+    cache: Arc<RedisCache>,
+}
+
+impl FromRef<AppState> for Arc<StreamHub> {
+    fn from_ref(state: &AppState) -> Self {
+        state.stream.clone()
+    }
+}
+
+/// Run API server for health checks and market data
+pub async fn run_api_server(
+    aggregator: Arc<PriceAggregator>,
+    cross_rates: Arc<CrossRates>,
+    positions: Arc<PositionTracker>,
+    refdata: Arc<ReferenceData>,
+    stream: Arc<StreamHub>,
+    cache: Arc<RedisCache>,
+    config: &Config,
+) -> anyhow::Result<()> {
     let app = Router::new()
         .route("/health", get(health))
         .route("/ready", get(ready))
+        .route("/symbols", get(list_symbols))
+        .route("/tickers", get(list_tickers))
+        .route("/tickers/:symbol", get(get_ticker))
+        .route("/candles/:symbol", get(get_candles))
+        .route("/twap/:symbol", get(get_twap_series))
+        .route("/settlements/:symbol", get(get_settlement))
+        .route("/positions/:user_id", get(get_positions))
+        .route("/ws", get(stream::ws_handler))
+        .with_state(AppState {
+            aggregator,
+            cross_rates,
+            positions,
+            refdata,
+            stream,
+            cache,
+        })
         .layer(TraceLayer::new_for_http());
 
     let addr = format!("{}:{}", config.host, config.port);
@@ -66,3 +188,277 @@ async fn ready() -> Json<serde_json::Value> {
         "ready": true
     }))
 }
+
+// ============== Market Data ==============
+
+/// Ticker with prices rounded to the symbol's display precision
+///
+/// Synthetic tickers are flagged and name the markets they derive from.
+#[derive(Debug, Serialize)]
+struct TickerResponse {
+    symbol: SymbolInfo,
+    #[serde(with = "rust_decimal::serde::str")]
+    bid: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    ask: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    last: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    volume_24h: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    high_24h: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    low_24h: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    price_change_24h: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    percent_change_24h: Decimal,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fair_value: Option<FairValue>,
+    synthetic: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    legs: Vec<Symbol>,
+    timestamp: DateTime<Utc>,
+}
+
+impl TickerResponse {
+    fn new(data: MarketData, symbol: SymbolInfo) -> Self {
+        let price = |p: Decimal| p.round_dp(symbol.price_precision);
+        let quantity = |q: Decimal| q.round_dp(symbol.quantity_precision);
+
+        Self {
+            bid: price(data.bid),
+            ask: price(data.ask),
+            last: price(data.last),
+            volume_24h: quantity(data.volume_24h),
+            high_24h: price(data.high_24h),
+            low_24h: price(data.low_24h),
+            price_change_24h: price(data.price_change_24h),
+            percent_change_24h: data.percent_change_24h,
+            fair_value: data.fair_value.map(|fair_value| FairValue {
+                vwap: fair_value
+                    .vwap
+                    .into_iter()
+                    .map(|(window, vwap)| (window, price(vwap)))
+                    .collect(),
+                twap: fair_value.twap.map(price),
+            }),
+            synthetic: false,
+            legs: Vec::new(),
+            timestamp: data.timestamp,
+            symbol,
+        }
+    }
+
+    fn synthetic(ticker: SyntheticTicker, symbol: SymbolInfo) -> Self {
+        Self {
+            synthetic: true,
+            legs: ticker.legs.to_vec(),
+            ..Self::new(ticker.data, symbol)
+        }
+    }
+}
+
+/// Candle with prices rounded to the symbol's display precision
+#[derive(Debug, Serialize)]
+struct CandleResponse {
+    symbol: SymbolInfo,
+    interval: String,
+    open_time: DateTime<Utc>,
+    close_time: DateTime<Utc>,
+    #[serde(with = "rust_decimal::serde::str")]
+    open: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    high: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    low: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    close: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    volume: Decimal,
+    trade_count: u32,
+}
+
+impl CandleResponse {
+    fn new(candle: Candle, symbol: SymbolInfo) -> Self {
+        let price = |p: Decimal| p.round_dp(symbol.price_precision);
+
+        Self {
+            interval: candle.interval,
+            open_time: candle.open_time,
+            close_time: candle.close_time,
+            open: price(candle.open),
+            high: price(candle.high),
+            low: price(candle.low),
+            close: price(candle.close),
+            volume: candle.volume.round_dp(symbol.quantity_precision),
+            trade_count: candle.trade_count,
+            symbol,
+        }
+    }
+}
+
+/// Closed candles returned when no limit is given
+const DEFAULT_CANDLE_LIMIT: usize = 100;
+
+const MAX_CANDLE_LIMIT: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+struct CandleQuery {
+    interval: Option<String>,
+    /// Earliest open time of a closed candle
+    start: Option<DateTime<Utc>>,
+    /// Latest open time of a closed candle; the candle still open is only
+    /// included when omitted
+    end: Option<DateTime<Utc>>,
+    limit: Option<usize>,
+}
+
+fn parse_symbol(symbol: &str) -> Result<Symbol, StatusCode> {
+    symbol
+        .split_once('-')
+        .map(|(base, quote)| Symbol::new(base, quote))
+        .ok_or(StatusCode::BAD_REQUEST)
+}
+
+async fn list_symbols(State(state): State<AppState>) -> Json<Vec<SymbolInfo>> {
+    Json(state.refdata.all())
+}
+
+async fn list_tickers(State(state): State<AppState>) -> Json<Vec<TickerResponse>> {
+    let native = state
+        .aggregator
+        .get_all_market_data()
+        .into_iter()
+        .map(|data| {
+            let info = state.refdata.get(&data.symbol);
+            TickerResponse::new(data, info)
+        });
+    let synthetic = state
+        .cross_rates
+        .tickers(&state.aggregator)
+        .into_iter()
+        .map(|ticker| {
+            let info = state.refdata.get(&ticker.data.symbol);
+            TickerResponse::synthetic(ticker, info)
+        });
+
+    Json(native.chain(synthetic).collect())
+}
+
+async fn get_ticker(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+) -> Result<Json<TickerResponse>, StatusCode> {
+    let symbol = parse_symbol(&symbol)?;
+    let info = state.refdata.get(&symbol);
+    if let Some(data) = state.aggregator.get_market_data(&symbol) {
+        return Ok(Json(TickerResponse::new(data, info)));
+    }
+
+    let ticker = state
+        .cross_rates
+        .ticker(&state.aggregator, &symbol)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(TickerResponse::synthetic(ticker, info)))
+}
+
+/// Closed candles oldest first, followed by the candle still open
+async fn get_candles(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+    Query(query): Query<CandleQuery>,
+) -> Result<Json<Vec<CandleResponse>>, StatusCode> {
+    let symbol = parse_symbol(&symbol)?;
+    let interval = query.interval.as_deref().unwrap_or("1m");
+    if !CANDLE_INTERVALS.contains(&interval) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_CANDLE_LIMIT)
+        .clamp(1, MAX_CANDLE_LIMIT);
+
+    let mut candles = state
+        .cache
+        .get_candles(&symbol, interval, query.start, query.end, limit)
+        .await
+        .map_err(|e| {
+            tracing::warn!(symbol = %symbol, "Failed to load candles: {}", e);
+            StatusCode::SERVICE_UNAVAILABLE
+        })?;
+    if query.end.is_none() {
+        candles.extend(state.aggregator.get_current_candle(&symbol, interval));
+    }
+
+    let info = state.refdata.get(&symbol);
+    Ok(Json(
+        candles
+            .into_iter()
+            .map(|candle| CandleResponse::new(candle, info.clone()))
+            .collect(),
+    ))
+}
+
+/// TWAP per interval, oldest first
+async fn get_twap_series(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+) -> Result<Json<Vec<TwapPoint>>, StatusCode> {
+    let symbol = parse_symbol(&symbol)?;
+    let series = state
+        .aggregator
+        .get_twap_series(&symbol)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let precision = state.refdata.get(&symbol).price_precision;
+    Ok(Json(
+        series
+            .into_iter()
+            .map(|point| TwapPoint {
+                twap: point.twap.round_dp(precision),
+                ..point
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+struct SettlementQuery {
+    /// Trading date; the latest settlement when omitted
+    date: Option<NaiveDate>,
+}
+
+async fn get_settlement(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+    Query(query): Query<SettlementQuery>,
+) -> Result<Json<SettlementPrice>, StatusCode> {
+    let symbol = parse_symbol(&symbol)?;
+    let settlement = state
+        .cache
+        .get_settlement(&symbol, query.date)
+        .await
+        .map_err(|e| {
+            tracing::warn!(symbol = %symbol, "Failed to load settlement: {}", e);
+            StatusCode::SERVICE_UNAVAILABLE
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(settlement))
+}
+
+/// A user's positions with PnL at the last mark
+async fn get_positions(
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> Json<Vec<PositionUpdate>> {
+    Json(
+        state
+            .positions
+            .user_positions(user_id)
+            .iter()
+            .map(Position::to_update)
+            .collect(),
+    )
+}
@@ -0,0 +1,225 @@
+//! Symbol Reference Data
+//!
+//! Display precision, base/quote assets and status per symbol. Synced
+//! from the reference-data service when configured, otherwise merged
+//! from, in increasing priority:
+//! - the matching engine's `/symbols` (which pairs are listed)
+//! - the exchange gateway's symbol metadata (precision and status)
+//! - a local JSON file of `SymbolInfo` overrides
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use parking_lot::RwLock;
+use tokio::time;
+use tracing::{info, warn};
+
+use crate::config::Config;
+use common::{Symbol, SymbolInfo, SymbolRegistry, SymbolStatus};
+
+#[derive(Default)]
+pub struct ReferenceData {
+    symbols: RwLock<HashMap<String, SymbolInfo>>,
+
+    /// Synced from the reference-data service; preferred once loaded
+    registry: Arc<SymbolRegistry>,
+}
+
+impl ReferenceData {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn registry(&self) -> Arc<SymbolRegistry> {
+        self.registry.clone()
+    }
+
+    /// Metadata for `symbol`, derived from the symbol itself if unknown
+    pub fn get(&self, symbol: &Symbol) -> SymbolInfo {
+        if !self.registry.is_empty() {
+            return self
+                .registry
+                .get(symbol)
+                .unwrap_or_else(|| SymbolInfo::from_symbol(symbol));
+        }
+
+        self.symbols
+            .read()
+            .get(&symbol.to_string())
+            .cloned()
+            .unwrap_or_else(|| SymbolInfo::from_symbol(symbol))
+    }
+
+    /// All known symbols
+    pub fn all(&self) -> Vec<SymbolInfo> {
+        if !self.registry.is_empty() {
+            return self.registry.all();
+        }
+
+        self.symbols.read().values().cloned().collect()
+    }
+
+    fn replace(&self, symbols: HashMap<String, SymbolInfo>) {
+        *self.symbols.write() = symbols;
+    }
+}
+
+/// Merge the configured sources into one symbol map
+///
+/// Gateway metadata only fills in symbols the engine lists, unless the
+/// engine is not configured as a source.
+fn merge(
+    engine: Option<Vec<Symbol>>,
+    gateway: Vec<SymbolInfo>,
+    overrides: Vec<SymbolInfo>,
+) -> HashMap<String, SymbolInfo> {
+    let mut gateway: HashMap<String, SymbolInfo> = gateway
+        .into_iter()
+        .map(|info| (info.symbol.to_string(), info))
+        .collect();
+
+    let mut merged: HashMap<String, SymbolInfo> = match engine {
+        Some(listed) => listed
+            .iter()
+            .map(|symbol| {
+                let mut info = gateway
+                    .remove(&symbol.to_string())
+                    .unwrap_or_else(|| SymbolInfo::from_symbol(symbol));
+                // Listed on our own venue means tradable here
+                info.status = SymbolStatus::Trading;
+                (symbol.to_string(), info)
+            })
+            .collect(),
+        None => gateway,
+    };
+
+    for info in overrides {
+        merged.insert(info.symbol.to_string(), info);
+    }
+
+    merged
+}
+
+async fn fetch_engine_symbols(client: &reqwest::Client, url: &str) -> Result<Vec<Symbol>> {
+    let symbols: Vec<String> = client
+        .get(format!("{url}/symbols"))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(symbols
+        .iter()
+        .filter_map(|s| s.split_once('-'))
+        .map(|(base, quote)| Symbol::new(base, quote))
+        .collect())
+}
+
+async fn fetch_gateway_symbols(
+    client: &reqwest::Client,
+    url: &str,
+    exchange: &str,
+) -> Result<Vec<SymbolInfo>> {
+    Ok(client
+        .get(format!("{url}/exchanges/{exchange}/symbols"))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+fn read_overrides(path: &str) -> Result<Vec<SymbolInfo>> {
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+/// Load reference data from all configured sources
+///
+/// A failing source is logged and skipped so one unavailable service
+/// does not blank out the rest.
+pub async fn load(refdata: &ReferenceData, client: &reqwest::Client, config: &Config) {
+    let engine = match &config.matching_engine_url {
+        Some(url) => match fetch_engine_symbols(client, url).await {
+            Ok(symbols) => Some(symbols),
+            Err(e) => {
+                // Without the listing the gateway would add every exchange
+                // pair; keep the previous snapshot instead
+                warn!("Failed to load engine symbols: {}", e);
+                return;
+            }
+        },
+        None => None,
+    };
+
+    let gateway = match &config.gateway_url {
+        Some(url) => fetch_gateway_symbols(client, url, &config.gateway_exchange)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Failed to load gateway symbol metadata: {}", e);
+                Vec::new()
+            }),
+        None => Vec::new(),
+    };
+
+    let overrides = match &config.refdata_file {
+        Some(path) => read_overrides(path).unwrap_or_else(|e| {
+            warn!(path = %path, "Failed to read reference data file: {}", e);
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+
+    let merged = merge(engine, gateway, overrides);
+    info!(symbols = merged.len(), "Reference data loaded");
+    refdata.replace(merged);
+}
+
+/// Periodically reload reference data
+pub async fn run_refdata_loader(
+    refdata: std::sync::Arc<ReferenceData>,
+    config: &Config,
+) -> Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?;
+    let mut interval = time::interval(Duration::from_secs(config.refdata_refresh_secs));
+
+    loop {
+        interval.tick().await;
+        load(&refdata, &client, config).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_priority() {
+        let eth = Symbol::new("ETH", "USDT");
+        let sol = Symbol::new("SOL", "USDT");
+
+        let mut gateway_eth = SymbolInfo::from_symbol(&eth);
+        gateway_eth.price_precision = 2;
+        gateway_eth.status = SymbolStatus::Halted;
+        let gateway_btc = SymbolInfo::from_symbol(&Symbol::new("BTC", "USDT"));
+
+        let mut override_sol = SymbolInfo::from_symbol(&sol);
+        override_sol.quantity_precision = 3;
+
+        let merged = merge(
+            Some(vec![eth.clone(), sol.clone()]),
+            vec![gateway_eth, gateway_btc],
+            vec![override_sol],
+        );
+
+        // Gateway-only symbols are not listed on the engine
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged["ETH-USDT"].price_precision, 2);
+        assert_eq!(merged["ETH-USDT"].status, SymbolStatus::Trading);
+        assert_eq!(merged["SOL-USDT"].quantity_precision, 3);
+    }
+}
@@ -0,0 +1,419 @@
+//! Candle Retention
+//!
+//! Candles are kept per interval for a configured period, e.g. 1m candles
+//! for 30 days and 1d candles forever. On each pass:
+//!
+//! - Redis: closed candles older than their interval's retention are
+//!   removed from the `candles:{symbol}:{interval}` sorted sets.
+//! - Postgres: for every `candles` hypertable chunk wholly older than the
+//!   cutoff, the interval's rows are written to a Parquet file under the
+//!   archive directory and then deleted. Chunks left empty are dropped.
+//!
+//! In dry-run mode nothing is written or deleted; the pass only reports
+//! what it would prune. Pruned entries and reclaimed bytes are counted
+//! per store, with a `mode` label separating dry runs.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use parquet::basic::Compression;
+use parquet::data_type::{ByteArray, ByteArrayType, DataType, Int32Type, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::{SerializedFileWriter, SerializedRowGroupWriter};
+use parquet::schema::parser::parse_message_type;
+use rust_decimal::Decimal;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::Row;
+use tokio::time;
+use tracing::{info, warn};
+
+use crate::cache::RedisCache;
+use crate::config::Config;
+use common::SharedClock;
+
+const ARCHIVE_SCHEMA: &str = "
+    message candle {
+        REQUIRED BYTE_ARRAY symbol (UTF8);
+        REQUIRED BYTE_ARRAY interval (UTF8);
+        REQUIRED INT64 open_time (TIMESTAMP_MILLIS);
+        REQUIRED BYTE_ARRAY open (UTF8);
+        REQUIRED BYTE_ARRAY high (UTF8);
+        REQUIRED BYTE_ARRAY low (UTF8);
+        REQUIRED BYTE_ARRAY close (UTF8);
+        REQUIRED BYTE_ARRAY volume (UTF8);
+        REQUIRED INT64 close_time (TIMESTAMP_MILLIS);
+        REQUIRED BYTE_ARRAY quote_volume (UTF8);
+        REQUIRED INT32 trade_count;
+    }
+";
+
+/// How long candles of each interval are kept; `None` is forever
+///
+/// Intervals without an entry are kept forever.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetentionPolicy {
+    intervals: BTreeMap<String, Option<chrono::Duration>>,
+}
+
+impl RetentionPolicy {
+    /// Parse `interval=period` pairs, e.g. `1m=30d,1h=12h,1d=forever`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut intervals = BTreeMap::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (interval, period) = entry
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Invalid retention entry `{entry}`"))?;
+            intervals.insert(interval.trim().to_string(), parse_period(period.trim())?);
+        }
+        Ok(Self { intervals })
+    }
+
+    /// Intervals with a finite retention and their cutoff at `now`
+    pub fn cutoffs(&self, now: DateTime<Utc>) -> Vec<(&str, DateTime<Utc>)> {
+        self.intervals
+            .iter()
+            .filter_map(|(interval, keep)| keep.map(|keep| (interval.as_str(), now - keep)))
+            .collect()
+    }
+}
+
+fn parse_period(period: &str) -> Result<Option<chrono::Duration>> {
+    if period.eq_ignore_ascii_case("forever") {
+        return Ok(None);
+    }
+    let (count, unit) = period.split_at(period.len().saturating_sub(1));
+    let count: i64 = count
+        .parse()
+        .map_err(|_| anyhow!("Invalid retention period `{period}`"))?;
+    let keep = match unit {
+        "d" => chrono::Duration::days(count),
+        "h" => chrono::Duration::hours(count),
+        _ => bail!("Invalid retention period `{period}`: use d, h or forever"),
+    };
+    if keep <= chrono::Duration::zero() {
+        bail!("Retention period `{period}` must be positive");
+    }
+    Ok(Some(keep))
+}
+
+/// Outcome of pruning one store for one interval
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Pruned {
+    pub entries: u64,
+    pub bytes: u64,
+}
+
+/// Candle row as stored in Postgres
+#[derive(Debug, Clone, PartialEq)]
+struct CandleRow {
+    symbol: String,
+    interval: String,
+    open_time: DateTime<Utc>,
+    open: Decimal,
+    high: Decimal,
+    low: Decimal,
+    close: Decimal,
+    volume: Decimal,
+    close_time: DateTime<Utc>,
+    quote_volume: Decimal,
+    trade_count: i32,
+}
+
+pub struct RetentionController {
+    cache: Arc<RedisCache>,
+    db: Option<PgPool>,
+    policy: RetentionPolicy,
+    archive_dir: PathBuf,
+    dry_run: bool,
+    clock: SharedClock,
+}
+
+impl RetentionController {
+    pub async fn from_config(
+        config: &Config,
+        cache: Arc<RedisCache>,
+        clock: SharedClock,
+    ) -> Result<Self> {
+        let db = match &config.database_url {
+            Some(url) => Some(PgPoolOptions::new().max_connections(2).connect(url).await?),
+            None => None,
+        };
+
+        Ok(Self {
+            cache,
+            db,
+            policy: RetentionPolicy::parse(&config.candle_retention)?,
+            archive_dir: PathBuf::from(&config.retention_archive_dir),
+            dry_run: config.retention_dry_run,
+            clock,
+        })
+    }
+
+    /// Prune every store once
+    pub async fn run_once(&self) -> Result<()> {
+        let now = self.clock.now();
+
+        for (interval, cutoff) in self.policy.cutoffs(now) {
+            let pruned = self
+                .cache
+                .prune_candles(interval, cutoff, self.dry_run)
+                .await?;
+            self.record("redis", interval, cutoff, pruned);
+
+            if let Some(db) = &self.db {
+                let pruned = self.prune_postgres(db, interval, cutoff).await?;
+                self.record("postgres", interval, cutoff, pruned);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Archive and delete an interval's candles from chunks older than `cutoff`
+    async fn prune_postgres(
+        &self,
+        db: &PgPool,
+        interval: &str,
+        cutoff: DateTime<Utc>,
+    ) -> Result<Pruned> {
+        let chunks: Vec<String> = sqlx::query_scalar(
+            "SELECT show_chunks('candles', older_than => $1::timestamptz)::text",
+        )
+        .bind(cutoff)
+        .fetch_all(db)
+        .await?;
+
+        let mut pruned = Pruned::default();
+        for chunk in chunks {
+            // Chunk names come from TimescaleDB, not from input
+            let rows = sqlx::query(&format!(
+                "SELECT symbol, interval, open_time, open, high, low, close, volume, \
+                 close_time, quote_volume, trade_count FROM {chunk} WHERE interval = $1 \
+                 ORDER BY symbol, open_time"
+            ))
+            .bind(interval)
+            .fetch_all(db)
+            .await?
+            .iter()
+            .map(candle_row)
+            .collect::<Result<Vec<_>, _>>()?;
+            if rows.is_empty() {
+                continue;
+            }
+
+            // A chunk holding only this interval's rows is dropped outright
+            let total: i64 = sqlx::query_scalar(&format!("SELECT count(*) FROM {chunk}"))
+                .fetch_one(db)
+                .await?;
+            let emptied = total as usize == rows.len();
+            pruned.entries += rows.len() as u64;
+            if emptied {
+                let size: i64 = sqlx::query_scalar("SELECT pg_total_relation_size($1::regclass)")
+                    .bind(&chunk)
+                    .fetch_one(db)
+                    .await?;
+                pruned.bytes += size.max(0) as u64;
+            }
+            if self.dry_run {
+                continue;
+            }
+
+            let path = self
+                .archive_dir
+                .join(interval)
+                .join(format!("{}.parquet", chunk.replace('.', "_")));
+            let archived = rows.len();
+            tokio::task::spawn_blocking(move || write_archive(&path, &rows)).await??;
+            metrics::counter!("retention_rows_archived", "interval" => interval.to_string())
+                .increment(archived as u64);
+
+            if emptied {
+                sqlx::query(&format!("DROP TABLE {chunk}"))
+                    .execute(db)
+                    .await?;
+            } else {
+                sqlx::query(&format!("DELETE FROM {chunk} WHERE interval = $1"))
+                    .bind(interval)
+                    .execute(db)
+                    .await?;
+            }
+        }
+
+        Ok(pruned)
+    }
+
+    fn record(&self, store: &'static str, interval: &str, cutoff: DateTime<Utc>, pruned: Pruned) {
+        let mode = if self.dry_run { "dry_run" } else { "applied" };
+        metrics::counter!(
+            "retention_entries_pruned",
+            "store" => store,
+            "interval" => interval.to_string(),
+            "mode" => mode
+        )
+        .increment(pruned.entries);
+        metrics::counter!("retention_bytes_reclaimed", "store" => store, "mode" => mode)
+            .increment(pruned.bytes);
+
+        if pruned.entries > 0 {
+            info!(
+                store,
+                interval,
+                cutoff = %cutoff,
+                entries = pruned.entries,
+                bytes = pruned.bytes,
+                dry_run = self.dry_run,
+                "Pruned candles past retention"
+            );
+        }
+    }
+}
+
+fn candle_row(row: &sqlx::postgres::PgRow) -> Result<CandleRow, sqlx::Error> {
+    Ok(CandleRow {
+        symbol: row.try_get("symbol")?,
+        interval: row.try_get("interval")?,
+        open_time: row.try_get("open_time")?,
+        open: row.try_get("open")?,
+        high: row.try_get("high")?,
+        low: row.try_get("low")?,
+        close: row.try_get("close")?,
+        volume: row.try_get("volume")?,
+        close_time: row.try_get("close_time")?,
+        quote_volume: row.try_get("quote_volume")?,
+        trade_count: row.try_get("trade_count")?,
+    })
+}
+
+/// Write candles to a Snappy-compressed Parquet file, decimals as strings
+fn write_archive(path: &Path, rows: &[CandleRow]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let schema = Arc::new(parse_message_type(ARCHIVE_SCHEMA)?);
+    let props = Arc::new(
+        WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build(),
+    );
+    let mut writer = SerializedFileWriter::new(File::create(path)?, schema, props)?;
+
+    let text = |f: fn(&CandleRow) -> String| -> Vec<ByteArray> {
+        rows.iter()
+            .map(|r| ByteArray::from(f(r).as_str()))
+            .collect()
+    };
+    let millis = |f: fn(&CandleRow) -> DateTime<Utc>| -> Vec<i64> {
+        rows.iter().map(|r| f(r).timestamp_millis()).collect()
+    };
+
+    let mut row_group = writer.next_row_group()?;
+    write_column::<ByteArrayType>(&mut row_group, &text(|r| r.symbol.clone()))?;
+    write_column::<ByteArrayType>(&mut row_group, &text(|r| r.interval.clone()))?;
+    write_column::<Int64Type>(&mut row_group, &millis(|r| r.open_time))?;
+    write_column::<ByteArrayType>(&mut row_group, &text(|r| r.open.to_string()))?;
+    write_column::<ByteArrayType>(&mut row_group, &text(|r| r.high.to_string()))?;
+    write_column::<ByteArrayType>(&mut row_group, &text(|r| r.low.to_string()))?;
+    write_column::<ByteArrayType>(&mut row_group, &text(|r| r.close.to_string()))?;
+    write_column::<ByteArrayType>(&mut row_group, &text(|r| r.volume.to_string()))?;
+    write_column::<Int64Type>(&mut row_group, &millis(|r| r.close_time))?;
+    write_column::<ByteArrayType>(&mut row_group, &text(|r| r.quote_volume.to_string()))?;
+    write_column::<Int32Type>(
+        &mut row_group,
+        &rows.iter().map(|r| r.trade_count).collect::<Vec<_>>(),
+    )?;
+    row_group.close()?;
+    writer.close()?;
+
+    Ok(())
+}
+
+fn write_column<T: DataType>(
+    row_group: &mut SerializedRowGroupWriter<'_, File>,
+    values: &[T::T],
+) -> Result<()> {
+    let mut column = row_group
+        .next_column()?
+        .ok_or_else(|| anyhow!("Archive schema has fewer columns than written"))?;
+    column.typed::<T>().write_batch(values, None, None)?;
+    column.close()?;
+    Ok(())
+}
+
+/// Prune candles past retention every `retention_interval_secs`
+pub async fn run_retention(controller: Arc<RetentionController>, config: &Config) -> Result<()> {
+    let mut interval = time::interval(Duration::from_secs(config.retention_interval_secs));
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = controller.run_once().await {
+            warn!("Retention pass failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    #[test]
+    fn test_policy_parses_periods_and_forever() {
+        let policy = RetentionPolicy::parse("1m=30d, 1h=12h,1d=forever").unwrap();
+        let now = Utc::now();
+
+        let cutoffs = policy.cutoffs(now);
+        assert_eq!(
+            cutoffs,
+            vec![
+                ("1h", now - chrono::Duration::hours(12)),
+                ("1m", now - chrono::Duration::days(30)),
+            ]
+        );
+
+        assert!(RetentionPolicy::parse("1m=30").is_err());
+        assert!(RetentionPolicy::parse("1m=0d").is_err());
+        assert!(RetentionPolicy::parse("1m").is_err());
+        assert!(RetentionPolicy::parse("").unwrap().cutoffs(now).is_empty());
+    }
+
+    #[test]
+    fn test_archive_writes_every_row() {
+        let row = CandleRow {
+            symbol: "BTC-USDT".to_string(),
+            interval: "1m".to_string(),
+            open_time: Utc::now(),
+            open: Decimal::new(50_000, 0),
+            high: Decimal::new(50_100, 0),
+            low: Decimal::new(49_900, 0),
+            close: Decimal::new(50_050, 0),
+            volume: Decimal::new(125, 2),
+            close_time: Utc::now(),
+            quote_volume: Decimal::new(62_500, 0),
+            trade_count: 42,
+        };
+        let path = std::env::temp_dir()
+            .join(format!("retention-{}", uuid::Uuid::new_v4()))
+            .join("1m")
+            .join("chunk.parquet");
+
+        write_archive(&path, &[row.clone(), row]).unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+        assert_eq!(
+            reader
+                .metadata()
+                .file_metadata()
+                .schema()
+                .get_fields()
+                .len(),
+            11
+        );
+        std::fs::remove_dir_all(path.parent().unwrap().parent().unwrap()).unwrap();
+    }
+}
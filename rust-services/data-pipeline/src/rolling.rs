@@ -0,0 +1,286 @@
+//! Rolling Trade Window
+//!
+//! Trade statistics over a trailing window, kept in one-minute buckets
+//! unless another bucket length is given. A trade adds to the bucket it
+//! executed in, and a bucket is dropped once its whole span has left the
+//! window, so totals cover the window to the bucket. Running totals are
+//! adjusted as buckets come and go; the high and low are recomputed from
+//! the remaining buckets.
+
+use std::collections::VecDeque;
+
+use chrono::{DateTime, DurationRound, Utc};
+use rust_decimal::Decimal;
+
+#[derive(Debug, Clone)]
+struct Bucket {
+    start: DateTime<Utc>,
+    open: Decimal,
+    high: Decimal,
+    low: Decimal,
+    volume: Decimal,
+    quote_volume: Decimal,
+    volume_usd: Decimal,
+    trade_count: u64,
+}
+
+impl Bucket {
+    fn new(start: DateTime<Utc>, price: Decimal) -> Self {
+        Self {
+            start,
+            open: price,
+            high: price,
+            low: price,
+            volume: Decimal::ZERO,
+            quote_volume: Decimal::ZERO,
+            volume_usd: Decimal::ZERO,
+            trade_count: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RollingWindow {
+    length: chrono::Duration,
+    bucket: chrono::Duration,
+    /// Oldest first, one per bucket span that had trades
+    buckets: VecDeque<Bucket>,
+    volume: Decimal,
+    quote_volume: Decimal,
+    volume_usd: Decimal,
+    trade_count: u64,
+    high: Option<Decimal>,
+    low: Option<Decimal>,
+}
+
+impl RollingWindow {
+    pub fn new(length: chrono::Duration) -> Self {
+        Self::with_bucket(length, chrono::Duration::minutes(1))
+    }
+
+    /// Window kept in buckets of `bucket`, which should divide a day
+    pub fn with_bucket(length: chrono::Duration, bucket: chrono::Duration) -> Self {
+        Self {
+            length,
+            bucket,
+            buckets: VecDeque::new(),
+            volume: Decimal::ZERO,
+            quote_volume: Decimal::ZERO,
+            volume_usd: Decimal::ZERO,
+            trade_count: 0,
+            high: None,
+            low: None,
+        }
+    }
+
+    /// Add a trade, returning false when it executed before the window
+    /// ending at `now`
+    pub fn record(
+        &mut self,
+        executed_at: DateTime<Utc>,
+        price: Decimal,
+        quantity: Decimal,
+        notional: Decimal,
+        notional_usd: Option<Decimal>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let start = self.bucket_start(executed_at);
+        if start + self.bucket <= now - self.length {
+            return false;
+        }
+
+        let index = match self.buckets.back() {
+            Some(last) if last.start == start => self.buckets.len() - 1,
+            Some(last) if last.start > start => {
+                // Late trade: find or insert its bucket
+                match self.buckets.binary_search_by_key(&start, |b| b.start) {
+                    Ok(index) => index,
+                    Err(index) => {
+                        self.buckets.insert(index, Bucket::new(start, price));
+                        index
+                    }
+                }
+            }
+            _ => {
+                self.buckets.push_back(Bucket::new(start, price));
+                self.buckets.len() - 1
+            }
+        };
+
+        let bucket = &mut self.buckets[index];
+        bucket.high = bucket.high.max(price);
+        bucket.low = bucket.low.min(price);
+        bucket.volume += quantity;
+        bucket.quote_volume += notional;
+        bucket.volume_usd += notional_usd.unwrap_or_default();
+        bucket.trade_count += 1;
+
+        self.volume += quantity;
+        self.quote_volume += notional;
+        self.volume_usd += notional_usd.unwrap_or_default();
+        self.trade_count += 1;
+        self.high = Some(self.high.map_or(price, |high| high.max(price)));
+        self.low = Some(self.low.map_or(price, |low| low.min(price)));
+        true
+    }
+
+    /// Drop buckets that have left the window ending at `now`, returning
+    /// whether any were dropped
+    pub fn prune(&mut self, now: DateTime<Utc>) -> bool {
+        let cutoff = now - self.length;
+        let mut dropped = false;
+        while let Some(bucket) = self.buckets.front() {
+            if bucket.start + self.bucket > cutoff {
+                break;
+            }
+            let bucket = self.buckets.pop_front().expect("front bucket");
+            self.volume -= bucket.volume;
+            self.quote_volume -= bucket.quote_volume;
+            self.volume_usd -= bucket.volume_usd;
+            self.trade_count -= bucket.trade_count;
+            dropped = true;
+        }
+
+        if dropped {
+            self.high = self.buckets.iter().map(|b| b.high).max();
+            self.low = self.buckets.iter().map(|b| b.low).min();
+        }
+        dropped
+    }
+
+    /// First price in the window
+    pub fn open(&self) -> Option<Decimal> {
+        self.buckets.front().map(|b| b.open)
+    }
+
+    pub fn high(&self) -> Option<Decimal> {
+        self.high
+    }
+
+    pub fn low(&self) -> Option<Decimal> {
+        self.low
+    }
+
+    pub fn volume(&self) -> Decimal {
+        self.volume
+    }
+
+    pub fn quote_volume(&self) -> Decimal {
+        self.quote_volume
+    }
+
+    pub fn volume_usd(&self) -> Decimal {
+        self.volume_usd
+    }
+
+    pub fn trade_count(&self) -> u64 {
+        self.trade_count
+    }
+
+    /// Volume-weighted average price, `None` without volume
+    pub fn vwap(&self) -> Option<Decimal> {
+        (self.volume > Decimal::ZERO).then(|| self.quote_volume / self.volume)
+    }
+
+    fn bucket_start(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        at.duration_trunc(self.bucket).unwrap_or(at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 2, hour, minute, 30).unwrap()
+    }
+
+    fn trade(window: &mut RollingWindow, time: DateTime<Utc>, price: i64, now: DateTime<Utc>) {
+        let price = Decimal::from(price);
+        assert!(window.record(time, price, Decimal::ONE, price, None, now));
+    }
+
+    #[test]
+    fn test_trades_roll_off_after_a_day() {
+        let mut window = RollingWindow::new(chrono::Duration::hours(24));
+        trade(&mut window, at(0, 0), 100, at(0, 0));
+        trade(&mut window, at(6, 0), 120, at(6, 0));
+        trade(&mut window, at(12, 0), 90, at(12, 0));
+
+        assert_eq!(window.open(), Some(Decimal::from(100)));
+        assert_eq!(window.high(), Some(Decimal::from(120)));
+        assert_eq!(window.volume(), Decimal::from(3));
+
+        // A day after the first trade's minute ended
+        let next_day = at(0, 1) + chrono::Duration::days(1);
+        assert!(window.prune(next_day));
+        assert_eq!(window.open(), Some(Decimal::from(120)));
+        assert_eq!(window.low(), Some(Decimal::from(90)));
+        assert_eq!(window.volume(), Decimal::from(2));
+        assert_eq!(window.quote_volume(), Decimal::from(210));
+        assert_eq!(window.trade_count(), 2);
+        assert!(!window.prune(next_day));
+
+        assert!(window.prune(at(12, 1) + chrono::Duration::days(1)));
+        assert_eq!(window.open(), None);
+        assert_eq!(window.high(), None);
+        assert_eq!(window.volume(), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_late_trades_join_their_minute() {
+        let mut window = RollingWindow::new(chrono::Duration::hours(24));
+        trade(&mut window, at(1, 0), 100, at(1, 5));
+        trade(&mut window, at(1, 5), 110, at(1, 5));
+        trade(&mut window, at(1, 3), 95, at(1, 5));
+        trade(&mut window, at(1, 0), 105, at(1, 5));
+
+        assert_eq!(window.buckets.len(), 3);
+        assert_eq!(window.open(), Some(Decimal::from(100)));
+        assert_eq!(window.low(), Some(Decimal::from(95)));
+        assert_eq!(window.trade_count(), 4);
+
+        // Already outside the window
+        let stale = at(1, 0) - chrono::Duration::days(1);
+        assert!(!window.record(
+            stale,
+            Decimal::ONE,
+            Decimal::ONE,
+            Decimal::ONE,
+            None,
+            at(1, 5)
+        ));
+        assert_eq!(window.trade_count(), 4);
+    }
+
+    #[test]
+    fn test_vwap_over_second_buckets() {
+        let mut window =
+            RollingWindow::with_bucket(chrono::Duration::minutes(1), chrono::Duration::seconds(1));
+        let now = at(1, 0);
+        assert_eq!(window.vwap(), None);
+
+        // 1 @ 100 and 3 @ 104
+        assert!(window.record(
+            now - chrono::Duration::seconds(50),
+            Decimal::from(100),
+            Decimal::ONE,
+            Decimal::from(100),
+            None,
+            now
+        ));
+        assert!(window.record(
+            now,
+            Decimal::from(104),
+            Decimal::from(3),
+            Decimal::from(312),
+            None,
+            now
+        ));
+        assert_eq!(window.vwap(), Some(Decimal::from(103)));
+
+        assert!(window.prune(now + chrono::Duration::seconds(15)));
+        assert_eq!(window.vwap(), Some(Decimal::from(104)));
+    }
+}
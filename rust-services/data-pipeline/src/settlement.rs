@@ -0,0 +1,290 @@
+//! Daily Settlement Prices
+//!
+//! Tracks each symbol's UTC trading day and, once the day is over,
+//! computes its official open, close and settlement price. Settlement is
+//! the VWAP of the last `settlement_window_mins` of the day, falling back
+//! to the close when nothing traded in the window, or simply the close
+//! under the `last_trade` methodology.
+//!
+//! A day is closed `settlement_grace_secs` after midnight so trades still
+//! in flight are included; trades for a day that is already closed are
+//! dropped. Days in progress are kept in memory only.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use dashmap::DashMap;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::ClientConfig;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tokio::time;
+use tracing::{info, warn};
+
+use crate::cache::RedisCache;
+use crate::config::Config;
+use common::{events::Event, SettlementPrice, SharedClock, Symbol, Trade};
+
+/// How settlement prices are derived
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettlementMethod {
+    /// Volume-weighted average over the end-of-day window
+    #[default]
+    Vwap,
+    /// Last trade of the day
+    LastTrade,
+}
+
+#[derive(Debug)]
+struct TradingDay {
+    symbol: Symbol,
+    date: NaiveDate,
+    open: Decimal,
+    close: Decimal,
+    volume: Decimal,
+    trade_count: u64,
+    window_notional: Decimal,
+    window_volume: Decimal,
+}
+
+impl TradingDay {
+    fn new(symbol: Symbol, date: NaiveDate, price: Decimal) -> Self {
+        Self {
+            symbol,
+            date,
+            open: price,
+            close: price,
+            volume: Decimal::ZERO,
+            trade_count: 0,
+            window_notional: Decimal::ZERO,
+            window_volume: Decimal::ZERO,
+        }
+    }
+}
+
+/// Per-symbol trading days awaiting settlement
+pub struct SettlementTracker {
+    method: SettlementMethod,
+    window: chrono::Duration,
+    grace: chrono::Duration,
+    days: DashMap<String, TradingDay>,
+
+    /// Days superseded by a trade on a later date, before the grace expired
+    completed: parking_lot::Mutex<Vec<SettlementPrice>>,
+}
+
+impl SettlementTracker {
+    pub fn new(method: SettlementMethod, window_mins: u64, grace_secs: u64) -> Self {
+        Self {
+            method,
+            window: chrono::Duration::minutes(window_mins as i64),
+            grace: chrono::Duration::seconds(grace_secs as i64),
+            days: DashMap::new(),
+            completed: parking_lot::Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            config.settlement_method,
+            config.settlement_window_mins,
+            config.settlement_grace_secs,
+        )
+    }
+
+    /// Methodology name recorded on VWAP settlements
+    fn vwap_method(&self) -> String {
+        format!("vwap_{}m", self.window.num_minutes())
+    }
+
+    pub fn record(&self, trade: &Trade) {
+        let date = trade.executed_at.date_naive();
+        let mut day = self
+            .days
+            .entry(trade.symbol.to_string())
+            .or_insert_with(|| TradingDay::new(trade.symbol.clone(), date, trade.price));
+
+        if date < day.date {
+            warn!(symbol = %trade.symbol, trade_date = %date, "Trade for a settled day dropped");
+            metrics::counter!("settlement_late_trades").increment(1);
+            return;
+        }
+        if date > day.date {
+            let next = TradingDay::new(trade.symbol.clone(), date, trade.price);
+            let finished = std::mem::replace(&mut *day, next);
+            self.completed
+                .lock()
+                .push(self.settle(finished, trade.executed_at));
+        }
+
+        day.close = trade.price;
+        day.volume += trade.quantity;
+        day.trade_count += 1;
+        if trade.executed_at >= day_end(date) - self.window {
+            day.window_notional += trade.price * trade.quantity;
+            day.window_volume += trade.quantity;
+        }
+    }
+
+    /// Settle every day whose grace period has passed by `now`
+    pub fn close_days(&self, now: DateTime<Utc>) -> Vec<SettlementPrice> {
+        let mut settled = std::mem::take(&mut *self.completed.lock());
+
+        let due: Vec<String> = self
+            .days
+            .iter()
+            .filter(|day| now >= day_end(day.date) + self.grace)
+            .map(|day| day.key().clone())
+            .collect();
+
+        for key in due {
+            if let Some((_, day)) = self
+                .days
+                .remove_if(&key, |_, day| now >= day_end(day.date) + self.grace)
+            {
+                settled.push(self.settle(day, now));
+            }
+        }
+
+        settled
+    }
+
+    fn settle(&self, day: TradingDay, now: DateTime<Utc>) -> SettlementPrice {
+        let (settlement, method) = match self.method {
+            SettlementMethod::Vwap if !day.window_volume.is_zero() => {
+                (day.window_notional / day.window_volume, self.vwap_method())
+            }
+            _ => (day.close, "last_trade".to_string()),
+        };
+
+        SettlementPrice {
+            symbol: day.symbol,
+            trading_date: day.date,
+            open: day.open,
+            close: day.close,
+            settlement,
+            method,
+            window_volume: day.window_volume,
+            volume: day.volume,
+            trade_count: day.trade_count,
+            timestamp: now,
+        }
+    }
+}
+
+/// Midnight UTC ending `date`
+fn day_end(date: NaiveDate) -> DateTime<Utc> {
+    (date + chrono::Duration::days(1))
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight is a valid time")
+        .and_utc()
+}
+
+/// Persist and publish settlements as trading days close
+pub async fn run_settlement_publisher(
+    tracker: Arc<SettlementTracker>,
+    cache: Arc<RedisCache>,
+    clock: SharedClock,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let producer: FutureProducer = ClientConfig::new()
+        .set("bootstrap.servers", &config.kafka_brokers)
+        .set("message.timeout.ms", "5000")
+        .create()?;
+
+    let mut interval = time::interval(Duration::from_secs(10));
+
+    info!(
+        method = ?config.settlement_method,
+        window_mins = config.settlement_window_mins,
+        "Settlement publisher started"
+    );
+
+    loop {
+        interval.tick().await;
+
+        for settlement in tracker.close_days(clock.now()) {
+            if let Err(e) = cache.set_settlement(&settlement).await {
+                warn!(symbol = %settlement.symbol, "Failed to persist settlement: {}", e);
+            }
+
+            let outbound = Event::builder(settlement.clone())
+                .source("data-pipeline")
+                .build();
+            let payload = outbound.to_json()?;
+
+            if let Err((e, _)) = producer
+                .send(
+                    FutureRecord::to(outbound.topic)
+                        .key(&outbound.key)
+                        .payload(&payload),
+                    Duration::from_secs(5),
+                )
+                .await
+            {
+                warn!(symbol = %settlement.symbol, "Failed to publish settlement: {}", e);
+                continue;
+            }
+
+            info!(
+                symbol = %settlement.symbol,
+                date = %settlement.trading_date,
+                settlement = %settlement.settlement,
+                method = %settlement.method,
+                "Settlement published"
+            );
+            metrics::counter!("settlements_published").increment(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use common::Side;
+    use uuid::Uuid;
+
+    fn trade(at: DateTime<Utc>, price: i64, quantity: i64) -> Trade {
+        Trade {
+            id: Uuid::new_v4(),
+            trade_id: 1,
+            symbol: Symbol::new("BTC", "USDT"),
+            maker_order_id: Uuid::new_v4(),
+            maker_user_id: Uuid::new_v4(),
+            taker_order_id: Uuid::new_v4(),
+            taker_user_id: Uuid::new_v4(),
+            price: Decimal::new(price, 0),
+            quantity: Decimal::new(quantity, 0),
+            quote_quantity: Decimal::new(price * quantity, 0),
+            taker_side: Side::Buy,
+            executed_at: at,
+        }
+    }
+
+    #[test]
+    fn test_vwap_over_closing_window() {
+        let tracker = SettlementTracker::new(SettlementMethod::Vwap, 30, 60);
+        let at = |h, m| Utc.with_ymd_and_hms(2024, 3, 1, h, m, 0).unwrap();
+
+        tracker.record(&trade(at(9, 0), 100, 5));
+        tracker.record(&trade(at(23, 40), 110, 1));
+        tracker.record(&trade(at(23, 50), 120, 3));
+
+        // Still within the grace period
+        let midnight = Utc.with_ymd_and_hms(2024, 3, 2, 0, 0, 30).unwrap();
+        assert!(tracker.close_days(midnight).is_empty());
+
+        let settled = tracker.close_days(midnight + chrono::Duration::minutes(1));
+        assert_eq!(settled.len(), 1);
+        let day = &settled[0];
+        assert_eq!(day.open, Decimal::new(100, 0));
+        assert_eq!(day.close, Decimal::new(120, 0));
+        assert_eq!(day.settlement, Decimal::new(1175, 1));
+        assert_eq!(day.method, "vwap_30m");
+        assert_eq!(day.volume, Decimal::new(9, 0));
+        assert_eq!(day.window_volume, Decimal::new(4, 0));
+    }
+}
@@ -0,0 +1,420 @@
+//! WebSocket Market Data Fan-Out
+//!
+//! Streams tickers, midprices and trades to WebSocket clients. Each client
+//! has a bounded send queue so a slow consumer only ever hurts itself:
+//! when its queue is full the oldest message is dropped or the client is
+//! disconnected, depending on the configured policy. Ticker and midprice
+//! channels are conflated, so a client that falls behind receives the
+//! latest value per channel instead of a backlog.
+//!
+//! Clients subscribe with `GET /ws?channels=ticker:BTC-USDT,trades:ETH-USDT`.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+use tracing::{debug, info};
+
+use crate::config::Config;
+
+/// Channel prefixes clients may subscribe to
+const CHANNEL_KINDS: [&str; 3] = ["ticker", "midprice", "trades"];
+
+/// What to do with a client whose send queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlowConsumerPolicy {
+    /// Drop the oldest queued message to make room
+    #[default]
+    DropOldest,
+    /// Close the connection
+    Disconnect,
+}
+
+/// Fan-out limits
+#[derive(Debug, Clone, Copy)]
+pub struct StreamLimits {
+    pub max_clients: usize,
+    pub max_channels_per_client: usize,
+    pub queue_capacity: usize,
+    pub policy: SlowConsumerPolicy,
+}
+
+impl StreamLimits {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_clients: config.ws_max_clients,
+            max_channels_per_client: config.ws_max_channels_per_client,
+            queue_capacity: config.ws_queue_capacity.max(1),
+            policy: config.ws_slow_consumer_policy,
+        }
+    }
+}
+
+/// Only the latest message of a conflated channel matters
+fn is_conflated(channel: &str) -> bool {
+    !channel.starts_with("trades:")
+}
+
+#[derive(Debug)]
+struct Queued {
+    channel: Arc<str>,
+    frame: Arc<str>,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    messages: VecDeque<Queued>,
+    closed: bool,
+}
+
+/// Bounded send queue of one client
+#[derive(Debug)]
+pub struct ClientQueue {
+    id: u64,
+    capacity: usize,
+    policy: SlowConsumerPolicy,
+    state: Mutex<QueueState>,
+    notify: Notify,
+}
+
+impl ClientQueue {
+    fn new(id: u64, limits: &StreamLimits) -> Self {
+        Self {
+            id,
+            capacity: limits.queue_capacity,
+            policy: limits.policy,
+            state: Mutex::new(QueueState::default()),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Queue a frame; returns false once the client has been closed
+    fn push(&self, channel: &Arc<str>, frame: &Arc<str>) -> bool {
+        let mut state = self.state.lock();
+        if state.closed {
+            return false;
+        }
+
+        if is_conflated(channel) {
+            if let Some(pending) = state.messages.iter_mut().find(|m| m.channel == *channel) {
+                pending.frame = frame.clone();
+                metrics::counter!("ws_messages_conflated", "channel" => channel.to_string())
+                    .increment(1);
+                return true;
+            }
+        }
+
+        if state.messages.len() >= self.capacity {
+            match self.policy {
+                SlowConsumerPolicy::DropOldest => {
+                    if let Some(dropped) = state.messages.pop_front() {
+                        metrics::counter!(
+                            "ws_messages_dropped",
+                            "channel" => dropped.channel.to_string()
+                        )
+                        .increment(1);
+                    }
+                }
+                SlowConsumerPolicy::Disconnect => {
+                    state.closed = true;
+                    drop(state);
+                    metrics::counter!("ws_slow_consumer_disconnects").increment(1);
+                    debug!(client = self.id, "Disconnecting slow WebSocket consumer");
+                    self.notify.notify_one();
+                    return false;
+                }
+            }
+        }
+
+        state.messages.push_back(Queued {
+            channel: channel.clone(),
+            frame: frame.clone(),
+        });
+        drop(state);
+        self.notify.notify_one();
+        true
+    }
+
+    /// Wait for queued frames; `None` once the client is closed
+    async fn next_batch(&self) -> Option<Vec<Arc<str>>> {
+        loop {
+            {
+                let mut state = self.state.lock();
+                if state.closed {
+                    return None;
+                }
+                if !state.messages.is_empty() {
+                    return Some(state.messages.drain(..).map(|m| m.frame).collect());
+                }
+            }
+            self.notify.notified().await;
+        }
+    }
+
+    fn close(&self) {
+        self.state.lock().closed = true;
+        self.notify.notify_one();
+    }
+
+    fn is_closed(&self) -> bool {
+        self.state.lock().closed
+    }
+}
+
+#[derive(Serialize)]
+struct Frame<'a, T> {
+    channel: &'a str,
+    data: &'a T,
+}
+
+/// Registry of subscribed clients per channel
+pub struct StreamHub {
+    limits: StreamLimits,
+    subscribers: DashMap<String, Vec<Arc<ClientQueue>>>,
+    clients: AtomicUsize,
+    next_id: AtomicU64,
+}
+
+impl StreamHub {
+    pub fn new(limits: StreamLimits) -> Self {
+        Self {
+            limits,
+            subscribers: DashMap::new(),
+            clients: AtomicUsize::new(0),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn has_subscribers(&self, channel: &str) -> bool {
+        self.subscribers
+            .get(channel)
+            .is_some_and(|queues| !queues.is_empty())
+    }
+
+    /// Queue `data` for every subscriber of `channel`
+    ///
+    /// Never blocks; the frame is serialized once and shared by all clients.
+    pub fn publish<T: Serialize>(&self, channel: &str, data: &T) {
+        let Some(mut queues) = self.subscribers.get_mut(channel) else {
+            return;
+        };
+        if queues.is_empty() {
+            return;
+        }
+
+        let frame: Arc<str> = match serde_json::to_string(&Frame { channel, data }) {
+            Ok(frame) => frame.into(),
+            Err(_) => return,
+        };
+        let channel: Arc<str> = channel.into();
+
+        queues.retain(|queue| queue.push(&channel, &frame));
+    }
+
+    fn is_full(&self) -> bool {
+        self.clients.load(Ordering::SeqCst) >= self.limits.max_clients
+    }
+
+    /// Register a client on `channels`, or `None` if the hub is full
+    fn register(&self, channels: &[String]) -> Option<Arc<ClientQueue>> {
+        let admitted = self
+            .clients
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                (n < self.limits.max_clients).then_some(n + 1)
+            })
+            .is_ok();
+        if !admitted {
+            return None;
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let queue = Arc::new(ClientQueue::new(id, &self.limits));
+        for channel in channels {
+            self.subscribers
+                .entry(channel.clone())
+                .or_default()
+                .push(queue.clone());
+        }
+
+        metrics::gauge!("ws_clients").increment(1.0);
+        Some(queue)
+    }
+
+    fn unregister(&self, queue: &Arc<ClientQueue>, channels: &[String]) {
+        queue.close();
+        for channel in channels {
+            if let Some(mut queues) = self.subscribers.get_mut(channel) {
+                queues.retain(|q| !Arc::ptr_eq(q, queue));
+            }
+        }
+        self.subscribers.retain(|_, queues| !queues.is_empty());
+
+        self.clients.fetch_sub(1, Ordering::SeqCst);
+        metrics::gauge!("ws_clients").decrement(1.0);
+    }
+}
+
+/// Parse `kind:BASE-QUOTE` channel names, normalising the symbol's case
+fn parse_channels(raw: &str) -> Result<Vec<String>, String> {
+    let mut channels = Vec::new();
+    for channel in raw.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+        let (kind, symbol) = channel
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid channel: {channel}"))?;
+        if !CHANNEL_KINDS.contains(&kind) || !symbol.contains('-') {
+            return Err(format!("Invalid channel: {channel}"));
+        }
+        let channel = format!("{kind}:{}", symbol.to_uppercase());
+        if !channels.contains(&channel) {
+            channels.push(channel);
+        }
+    }
+    Ok(channels)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreamQuery {
+    channels: String,
+}
+
+/// Upgrade to a WebSocket streaming the requested channels
+pub async fn ws_handler(
+    State(hub): State<Arc<StreamHub>>,
+    Query(query): Query<StreamQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let channels = match parse_channels(&query.channels) {
+        Ok(channels) if channels.is_empty() => {
+            return (StatusCode::BAD_REQUEST, "No channels requested").into_response();
+        }
+        Ok(channels) => channels,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    if channels.len() > hub.limits.max_channels_per_client {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!(
+                "At most {} channels per connection",
+                hub.limits.max_channels_per_client
+            ),
+        )
+            .into_response();
+    }
+
+    if hub.is_full() {
+        metrics::counter!("ws_clients_rejected").increment(1);
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Too many streaming clients",
+        )
+            .into_response();
+    }
+
+    ws.on_upgrade(move |socket| serve_client(socket, hub, channels))
+}
+
+async fn serve_client(mut socket: WebSocket, hub: Arc<StreamHub>, channels: Vec<String>) {
+    // Registered only once upgraded so failed handshakes never hold a slot
+    let Some(queue) = hub.register(&channels) else {
+        metrics::counter!("ws_clients_rejected").increment(1);
+        let _ = socket.send(Message::Close(None)).await;
+        return;
+    };
+    debug!(client = queue.id, channels = ?channels, "WebSocket client connected");
+
+    'serve: loop {
+        tokio::select! {
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Pings are answered by axum; nothing else is expected
+                Some(Ok(_)) => {}
+            },
+            batch = queue.next_batch() => {
+                let Some(batch) = batch else {
+                    // Closed by the slow-consumer policy
+                    let _ = socket.send(Message::Close(None)).await;
+                    break;
+                };
+                for frame in batch {
+                    if socket.send(Message::Text(frame.to_string())).await.is_err() {
+                        break 'serve;
+                    }
+                }
+            }
+        }
+    }
+
+    let slow = queue.is_closed();
+    hub.unregister(&queue, &channels);
+    debug!(client = queue.id, slow, "WebSocket client disconnected");
+}
+
+/// Log the configured limits at startup
+pub fn log_limits(hub: &StreamHub) {
+    info!(
+        max_clients = hub.limits.max_clients,
+        max_channels = hub.limits.max_channels_per_client,
+        queue_capacity = hub.limits.queue_capacity,
+        policy = ?hub.limits.policy,
+        "WebSocket streaming enabled"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(policy: SlowConsumerPolicy) -> StreamLimits {
+        StreamLimits {
+            max_clients: 1,
+            max_channels_per_client: 10,
+            queue_capacity: 2,
+            policy,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_consumer_policies() {
+        let hub = StreamHub::new(limits(SlowConsumerPolicy::DropOldest));
+        let channels = parse_channels("trades:btc-usdt,ticker:BTC-USDT").unwrap();
+        let queue = hub.register(&channels).unwrap();
+        assert!(hub.register(&channels).is_none());
+
+        // Ticker is conflated to the latest value
+        hub.publish("ticker:BTC-USDT", &1);
+        hub.publish("ticker:BTC-USDT", &2);
+        // A full queue drops its oldest message, here the ticker
+        hub.publish("trades:BTC-USDT", &3);
+        hub.publish("trades:BTC-USDT", &4);
+
+        let batch = queue.next_batch().await.unwrap();
+        assert_eq!(batch.len(), 2);
+        assert!(batch[0].contains("\"data\":3"));
+        assert!(batch[1].contains("\"data\":4"));
+
+        hub.unregister(&queue, &channels);
+        assert!(!hub.has_subscribers("trades:BTC-USDT"));
+
+        // Disconnect closes the client on overflow
+        let hub = StreamHub::new(limits(SlowConsumerPolicy::Disconnect));
+        let queue = hub.register(&channels).unwrap();
+        for i in 0..3 {
+            hub.publish("trades:BTC-USDT", &i);
+        }
+        assert!(queue.next_batch().await.is_none());
+        assert!(!hub.has_subscribers("trades:BTC-USDT"));
+    }
+}
@@ -0,0 +1,546 @@
+//! Candle Verification
+//!
+//! Recomputes OHLCV candles from the matching engine's trade history
+//! (`engine_trades`) and compares them with the candles stored in Redis
+//! and, when configured, the Postgres `candles` table. Used after an
+//! aggregator bug or a partial outage to find, and optionally repair,
+//! candles that no longer match the trades.
+//!
+//! Setting `candle_verify_start` runs one verification instead of the
+//! pipeline: the report is printed as JSON and the process exits. Only
+//! candles that opened and closed inside the range are checked. With
+//! `candle_verify_repair`, mismatched and missing candles are rewritten
+//! from the trades and stored candles without trades are removed.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::Row;
+use tokio_stream::StreamExt;
+use tracing::info;
+
+use crate::aggregator::{get_candle_open_time, interval_duration, CandleBuilder, CANDLE_INTERVALS};
+use crate::cache::RedisCache;
+use crate::config::Config;
+use common::{Candle, Symbol};
+
+/// Decimal places of prices and volumes in the Postgres `candles` table
+const POSTGRES_SCALE: u32 = 8;
+
+/// A candle by symbol, interval and open time
+type CandleKey = (String, String, DateTime<Utc>);
+
+/// A trade as archived by the matching engine
+#[derive(Debug, Clone)]
+pub struct ArchivedTrade {
+    pub symbol: Symbol,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub quote_quantity: Decimal,
+    pub executed_at: DateTime<Utc>,
+}
+
+/// A candle recomputed from trades
+#[derive(Debug, Clone)]
+pub struct Expected {
+    pub candle: Candle,
+    pub quote_volume: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscrepancyKind {
+    /// Trades in the interval but no stored candle
+    Missing,
+    /// Stored candle differs from the trades
+    Mismatched,
+    /// Stored candle for an interval without trades
+    Phantom,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Discrepancy {
+    pub store: &'static str,
+    pub kind: DiscrepancyKind,
+    pub symbol: String,
+    pub interval: String,
+    pub open_time: DateTime<Utc>,
+    pub stored: Option<Candle>,
+    pub expected: Option<Candle>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyReport {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub symbols: Vec<String>,
+    pub trades: u64,
+    /// Candles recomputed from the trades
+    pub candles: usize,
+    pub discrepancies: Vec<Discrepancy>,
+    pub repaired: usize,
+}
+
+/// Candles wholly inside `[start, end)` built from `trades`, which must
+/// be in execution order
+pub fn recompute(
+    trades: &[ArchivedTrade],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> BTreeMap<CandleKey, Expected> {
+    let mut builders: BTreeMap<CandleKey, (CandleBuilder, Decimal)> = BTreeMap::new();
+    for trade in trades {
+        for &interval in CANDLE_INTERVALS {
+            let open_time = get_candle_open_time(trade.executed_at, interval);
+            let (builder, quote_volume) = builders
+                .entry((trade.symbol.to_string(), interval.to_string(), open_time))
+                .or_insert_with(|| {
+                    (
+                        CandleBuilder::new(trade.symbol.clone(), interval, open_time),
+                        Decimal::ZERO,
+                    )
+                });
+            builder.update(trade.price, trade.quantity);
+            *quote_volume += trade.quote_quantity;
+        }
+    }
+
+    builders
+        .into_iter()
+        .filter(|((_, _, open_time), (builder, _))| {
+            *open_time >= start && builder.close_time() <= end
+        })
+        .map(|(key, (builder, quote_volume))| {
+            let candle = builder.to_candle(builder.close_time());
+            (
+                key,
+                Expected {
+                    candle,
+                    quote_volume,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Differences between `stored` and `expected` candles of one store
+///
+/// `precision` rounds expected values to what the store can hold.
+pub fn compare(
+    store: &'static str,
+    expected: &BTreeMap<CandleKey, Expected>,
+    stored: &BTreeMap<CandleKey, Candle>,
+    precision: Option<u32>,
+) -> Vec<Discrepancy> {
+    let keys: BTreeSet<&CandleKey> = expected.keys().chain(stored.keys()).collect();
+
+    keys.into_iter()
+        .filter_map(|key| {
+            let want = expected
+                .get(key)
+                .map(|e| precision.map_or(e.candle.clone(), |dp| round(&e.candle, dp)));
+            let have = stored.get(key);
+            let kind = match (&want, have) {
+                (Some(_), None) => DiscrepancyKind::Missing,
+                (None, Some(_)) => DiscrepancyKind::Phantom,
+                (Some(want), Some(have)) if !same_ohlcv(want, have) => DiscrepancyKind::Mismatched,
+                _ => return None,
+            };
+            let (symbol, interval, open_time) = key.clone();
+            Some(Discrepancy {
+                store,
+                kind,
+                symbol,
+                interval,
+                open_time,
+                stored: have.cloned(),
+                expected: want,
+            })
+        })
+        .collect()
+}
+
+fn same_ohlcv(a: &Candle, b: &Candle) -> bool {
+    a.open == b.open
+        && a.high == b.high
+        && a.low == b.low
+        && a.close == b.close
+        && a.volume == b.volume
+        && a.trade_count == b.trade_count
+}
+
+fn round(candle: &Candle, dp: u32) -> Candle {
+    Candle {
+        open: candle.open.round_dp(dp),
+        high: candle.high.round_dp(dp),
+        low: candle.low.round_dp(dp),
+        close: candle.close.round_dp(dp),
+        volume: candle.volume.round_dp(dp),
+        ..candle.clone()
+    }
+}
+
+pub struct CandleVerifier<'a> {
+    cache: &'a RedisCache,
+    trades: PgPool,
+    candles: Option<PgPool>,
+    symbols: Vec<String>,
+    repair: bool,
+}
+
+impl<'a> CandleVerifier<'a> {
+    pub async fn from_config(config: &Config, cache: &'a RedisCache) -> Result<Self> {
+        let archive_url = config
+            .trade_archive_url
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("trade_archive_url is not set"))?;
+        let trades = PgPoolOptions::new()
+            .max_connections(2)
+            .connect(archive_url)
+            .await?;
+        let candles = match &config.database_url {
+            Some(url) => Some(PgPoolOptions::new().max_connections(2).connect(url).await?),
+            None => None,
+        };
+
+        Ok(Self {
+            cache,
+            trades,
+            candles,
+            symbols: config
+                .candle_verify_symbols
+                .split(',')
+                .map(|s| s.trim().to_uppercase())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            repair: config.candle_verify_repair,
+        })
+    }
+
+    /// Verify, and repair if configured, candles in `[start, end)`
+    pub async fn run(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<VerifyReport> {
+        let trades = self.load_trades(start, end).await?;
+        let expected = recompute(&trades, start, end);
+
+        let mut symbols: BTreeSet<String> = self.symbols.iter().cloned().collect();
+        if symbols.is_empty() {
+            symbols.extend(trades.iter().map(|t| t.symbol.to_string()));
+        }
+
+        let stored = self.redis_candles(&symbols, start, end).await?;
+        let mut discrepancies = compare("redis", &expected, &stored, None);
+        if let Some(db) = &self.candles {
+            let stored = postgres_candles(db, &symbols, start, end).await?;
+            discrepancies.extend(compare(
+                "postgres",
+                &expected,
+                &stored,
+                Some(POSTGRES_SCALE),
+            ));
+        }
+
+        let mut repaired = 0;
+        if self.repair {
+            for discrepancy in &discrepancies {
+                self.repair(discrepancy, &expected).await?;
+                repaired += 1;
+            }
+        }
+
+        for discrepancy in &discrepancies {
+            metrics::counter!(
+                "candle_discrepancies",
+                "store" => discrepancy.store,
+                "interval" => discrepancy.interval.clone()
+            )
+            .increment(1);
+        }
+        info!(
+            start = %start,
+            end = %end,
+            trades = trades.len(),
+            candles = expected.len(),
+            discrepancies = discrepancies.len(),
+            repaired,
+            "Candle verification complete"
+        );
+
+        Ok(VerifyReport {
+            start,
+            end,
+            symbols: symbols.into_iter().collect(),
+            trades: trades.len() as u64,
+            candles: expected.len(),
+            discrepancies,
+            repaired,
+        })
+    }
+
+    /// Archived trades in `[start, end)`, in execution order
+    async fn load_trades(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<ArchivedTrade>> {
+        let symbols: Vec<&str> = self.symbols.iter().map(String::as_str).collect();
+        let mut rows = sqlx::query(
+            "SELECT symbol, price, quantity, quote_quantity, executed_at
+             FROM engine_trades
+             WHERE executed_at >= $1 AND executed_at < $2
+               AND (cardinality($3::TEXT[]) = 0 OR symbol = ANY($3))
+             ORDER BY executed_at, trade_id",
+        )
+        .bind(start)
+        .bind(end)
+        .bind(&symbols)
+        .fetch(&self.trades);
+
+        let mut trades = Vec::new();
+        while let Some(row) = rows.next().await {
+            let row = row?;
+            trades.push(ArchivedTrade {
+                symbol: Symbol(row.try_get("symbol")?),
+                price: row.try_get("price")?,
+                quantity: row.try_get("quantity")?,
+                quote_quantity: row.try_get("quote_quantity")?,
+                executed_at: row.try_get("executed_at")?,
+            });
+        }
+        Ok(trades)
+    }
+
+    /// Closed candles in Redis wholly inside `[start, end)`
+    async fn redis_candles(
+        &self,
+        symbols: &BTreeSet<String>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<BTreeMap<CandleKey, Candle>> {
+        let mut stored = BTreeMap::new();
+        for symbol in symbols {
+            for &interval in CANDLE_INTERVALS {
+                let last_open = end - interval_duration(interval);
+                let candles = self
+                    .cache
+                    .candles_between(&Symbol(symbol.clone()), interval, start, last_open)
+                    .await?;
+                for candle in candles {
+                    stored.insert(
+                        (symbol.clone(), interval.to_string(), candle.open_time),
+                        candle,
+                    );
+                }
+            }
+        }
+        Ok(stored)
+    }
+
+    async fn repair(
+        &self,
+        discrepancy: &Discrepancy,
+        expected: &BTreeMap<CandleKey, Expected>,
+    ) -> Result<()> {
+        let key = (
+            discrepancy.symbol.clone(),
+            discrepancy.interval.clone(),
+            discrepancy.open_time,
+        );
+        let symbol = Symbol(discrepancy.symbol.clone());
+
+        match (discrepancy.store, expected.get(&key)) {
+            ("redis", Some(expected)) => self.cache.replace_candle(&expected.candle).await,
+            ("redis", None) => {
+                self.cache
+                    .remove_candle(&symbol, &discrepancy.interval, discrepancy.open_time)
+                    .await
+            }
+            (_, expected) => {
+                let Some(db) = &self.candles else {
+                    return Ok(());
+                };
+                match expected {
+                    Some(expected) => upsert_candle(db, expected).await,
+                    None => {
+                        sqlx::query(
+                            "DELETE FROM candles
+                             WHERE symbol = $1 AND interval = $2 AND open_time = $3",
+                        )
+                        .bind(&discrepancy.symbol)
+                        .bind(&discrepancy.interval)
+                        .bind(discrepancy.open_time)
+                        .execute(db)
+                        .await?;
+                        Ok(())
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Candles in Postgres wholly inside `[start, end)`
+async fn postgres_candles(
+    db: &PgPool,
+    symbols: &BTreeSet<String>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<BTreeMap<CandleKey, Candle>> {
+    let symbols: Vec<&str> = symbols.iter().map(String::as_str).collect();
+    let intervals: Vec<&str> = CANDLE_INTERVALS.to_vec();
+    let rows = sqlx::query(
+        "SELECT symbol, interval, open_time, open, high, low, close, volume, close_time,
+                trade_count
+         FROM candles
+         WHERE symbol = ANY($1) AND interval = ANY($2)
+           AND open_time >= $3 AND close_time <= $4",
+    )
+    .bind(&symbols)
+    .bind(&intervals)
+    .bind(start)
+    .bind(end)
+    .fetch_all(db)
+    .await?;
+
+    let mut stored = BTreeMap::new();
+    for row in rows {
+        let symbol: String = row.try_get("symbol")?;
+        let interval: String = row.try_get("interval")?;
+        let open_time: DateTime<Utc> = row.try_get("open_time")?;
+        let trade_count: i32 = row.try_get("trade_count")?;
+        let candle = Candle {
+            symbol: Symbol(symbol.clone()),
+            interval: interval.clone(),
+            open_time,
+            open: row.try_get("open")?,
+            high: row.try_get("high")?,
+            low: row.try_get("low")?,
+            close: row.try_get("close")?,
+            volume: row.try_get("volume")?,
+            close_time: row.try_get("close_time")?,
+            trade_count: trade_count as u32,
+        };
+        stored.insert((symbol, interval, open_time), candle);
+    }
+    Ok(stored)
+}
+
+async fn upsert_candle(db: &PgPool, expected: &Expected) -> Result<()> {
+    let candle = &expected.candle;
+    sqlx::query(
+        "INSERT INTO candles (symbol, interval, open_time, open, high, low, close, volume,
+                              close_time, quote_volume, trade_count)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+         ON CONFLICT (symbol, interval, open_time) DO UPDATE SET
+             open = EXCLUDED.open, high = EXCLUDED.high, low = EXCLUDED.low,
+             close = EXCLUDED.close, volume = EXCLUDED.volume,
+             close_time = EXCLUDED.close_time, quote_volume = EXCLUDED.quote_volume,
+             trade_count = EXCLUDED.trade_count",
+    )
+    .bind(&candle.symbol.0)
+    .bind(&candle.interval)
+    .bind(candle.open_time)
+    .bind(candle.open)
+    .bind(candle.high)
+    .bind(candle.low)
+    .bind(candle.close)
+    .bind(candle.volume)
+    .bind(candle.close_time)
+    .bind(expected.quote_volume)
+    .bind(candle.trade_count as i32)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(h: u32, m: u32, s: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, h, m, s).unwrap()
+    }
+
+    fn trade(executed_at: DateTime<Utc>, price: i64, quantity: i64) -> ArchivedTrade {
+        ArchivedTrade {
+            symbol: Symbol::new("BTC", "USDT"),
+            price: Decimal::from(price),
+            quantity: Decimal::from(quantity),
+            quote_quantity: Decimal::from(price * quantity),
+            executed_at,
+        }
+    }
+
+    fn minute(key_minute: u32) -> CandleKey {
+        (
+            "BTC-USDT".to_string(),
+            "1m".to_string(),
+            at(10, key_minute, 0),
+        )
+    }
+
+    #[test]
+    fn test_recompute_keeps_only_candles_inside_the_range() {
+        let trades = [
+            trade(at(10, 0, 5), 100, 1),
+            trade(at(10, 0, 30), 104, 2),
+            trade(at(10, 0, 50), 98, 1),
+            trade(at(10, 1, 10), 101, 3),
+        ];
+
+        let expected = recompute(&trades, at(10, 0, 0), at(10, 2, 0));
+
+        // Only the two 1m candles close inside the range
+        assert_eq!(expected.len(), 2);
+        let first = &expected[&minute(0)];
+        assert_eq!(first.candle.open, Decimal::from(100));
+        assert_eq!(first.candle.high, Decimal::from(104));
+        assert_eq!(first.candle.low, Decimal::from(98));
+        assert_eq!(first.candle.close, Decimal::from(98));
+        assert_eq!(first.candle.volume, Decimal::from(4));
+        assert_eq!(first.candle.trade_count, 3);
+        assert_eq!(first.candle.close_time, at(10, 1, 0));
+        assert_eq!(first.quote_volume, Decimal::from(406));
+    }
+
+    #[test]
+    fn test_compare_finds_missing_mismatched_and_phantom() {
+        let trades = [trade(at(10, 0, 5), 100, 1), trade(at(10, 1, 5), 101, 1)];
+        let expected = recompute(&trades, at(10, 0, 0), at(10, 3, 0));
+
+        let mut wrong = expected[&minute(1)].candle.clone();
+        wrong.volume = Decimal::from(2);
+        let mut phantom = wrong.clone();
+        phantom.open_time = at(10, 2, 0);
+        let stored = BTreeMap::from([(minute(1), wrong), (minute(2), phantom)]);
+
+        let found = compare("redis", &expected, &stored, None);
+        let kinds: Vec<_> = found.iter().map(|d| (d.open_time, d.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (at(10, 0, 0), DiscrepancyKind::Missing),
+                (at(10, 1, 0), DiscrepancyKind::Mismatched),
+                (at(10, 2, 0), DiscrepancyKind::Phantom),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_rounds_to_the_store_precision() {
+        let mut fine = trade(at(10, 0, 5), 100, 1);
+        fine.quantity = Decimal::new(1_000_000_001, 9);
+        let expected = recompute(&[fine], at(10, 0, 0), at(10, 1, 0));
+
+        let mut stored = expected[&minute(0)].candle.clone();
+        stored.volume = Decimal::new(100_000_000, 8);
+        let stored = BTreeMap::from([(minute(0), stored)]);
+
+        assert!(compare("postgres", &expected, &stored, Some(8)).is_empty());
+        assert_eq!(compare("redis", &expected, &stored, None).len(), 1);
+    }
+}
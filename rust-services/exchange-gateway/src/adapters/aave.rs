@@ -0,0 +1,83 @@
+//! Aave Lending Adapter
+//!
+//! Reads current variable borrow rates from Aave V3 on mainnet. Aave has
+//! no rate history endpoint, so the collector builds the series by
+//! sampling on its schedule.
+
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ethers::{
+    prelude::*,
+    providers::{Http, Provider},
+};
+use std::sync::Arc;
+
+use super::traits::*;
+use super::uniswap::{from_base_units, resolve_token};
+use common::ExchangeError;
+
+// Aave V3 PoolDataProvider address on mainnet
+const AAVE_DATA_PROVIDER: &str = "0x7B4EB56E7CD4b454BA8ff71E4518426369a138a3";
+
+/// Aave rates are expressed in ray (27 decimals)
+const RAY_DECIMALS: u32 = 27;
+
+abigen!(
+    IPoolDataProvider,
+    r#"[
+        function getReserveData(address asset) external view returns (uint256 unbacked, uint256 accruedToTreasuryScaled, uint256 totalAToken, uint256 totalStableDebt, uint256 totalVariableDebt, uint256 liquidityRate, uint256 variableBorrowRate, uint256 stableBorrowRate, uint256 averageStableBorrowRate, uint256 liquidityIndex, uint256 variableBorrowIndex, uint40 lastUpdateTimestamp)
+    ]"#
+);
+
+pub struct AaveAdapter {
+    provider: Arc<Provider<Http>>,
+}
+
+impl AaveAdapter {
+    pub fn new(rpc_url: &str) -> Result<Self, ExchangeError> {
+        let provider = Provider::<Http>::try_from(rpc_url)
+            .map_err(|e| ExchangeError::ConnectionFailed(e.to_string()))?;
+
+        Ok(Self {
+            provider: Arc::new(provider),
+        })
+    }
+}
+
+#[async_trait]
+impl RateSource for AaveAdapter {
+    async fn get_borrow_rates(
+        &self,
+        asset: &str,
+        _since: DateTime<Utc>,
+    ) -> ExchangeResult<Vec<BorrowRate>> {
+        // Aave lists wrapped BTC; ETH resolves to WETH
+        let token = match asset.to_uppercase().as_str() {
+            "BTC" => resolve_token("WBTC")?,
+            other => resolve_token(other)?,
+        };
+
+        let data_provider = IPoolDataProvider::new(
+            AAVE_DATA_PROVIDER
+                .parse::<Address>()
+                .expect("valid data provider address"),
+            self.provider.clone(),
+        );
+
+        let reserve = data_provider
+            .get_reserve_data(token.address)
+            .call()
+            .await
+            .map_err(|e| ExchangeError::ApiError {
+                code: -1,
+                message: e.to_string(),
+            })?;
+
+        Ok(vec![BorrowRate {
+            annual_rate: from_base_units(reserve.6, RAY_DECIMALS)?,
+            timestamp: Utc::now(),
+        }])
+    }
+}
@@ -0,0 +1,224 @@
+//! ERC-20 Approvals
+//!
+//! A DEX router can only pull a token from the wallet up to the allowance
+//! the wallet granted it. Before a swap the allowance is checked and, if
+//! short, an `approve` is sent and mined. Under the `exact` policy each
+//! swap approves just its input; under `max` the router is approved once
+//! for an unlimited amount.
+//!
+//! Allowances are cached per token and spender and drawn down as swaps
+//! spend them, so the chain is only queried when the cache cannot cover a
+//! swap. Tokens like USDT refuse to change a non-zero allowance, so one is
+//! reset to zero before it is raised.
+
+use dashmap::DashMap;
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+use super::traits::ExchangeResult;
+use super::uniswap::IERC20;
+use crate::wallet::SignerClient;
+use common::ExchangeError;
+
+/// How much to approve when an allowance falls short
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalPolicy {
+    /// The amount being swapped
+    #[default]
+    Exact,
+    /// Unlimited, so later swaps need no approval
+    Max,
+}
+
+impl ApprovalPolicy {
+    /// Allowance to grant for a swap of `amount`
+    fn approval_amount(&self, amount: U256) -> U256 {
+        match self {
+            ApprovalPolicy::Exact => amount,
+            ApprovalPolicy::Max => U256::MAX,
+        }
+    }
+}
+
+pub struct Approvals {
+    client: Arc<SignerClient>,
+    policy: ApprovalPolicy,
+
+    /// Last known allowance by (token, spender)
+    allowances: DashMap<(Address, Address), U256>,
+
+    /// Serializes approvals so concurrent swaps do not race each other
+    approving: tokio::sync::Mutex<()>,
+}
+
+impl Approvals {
+    pub fn new(client: Arc<SignerClient>, policy: ApprovalPolicy) -> Self {
+        Self {
+            client,
+            policy,
+            allowances: DashMap::new(),
+            approving: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    fn cached(&self, token: Address, spender: Address) -> Option<U256> {
+        self.allowances.get(&(token, spender)).map(|a| *a)
+    }
+
+    /// Allowance the wallet has granted `spender` on `token`, from the chain
+    pub async fn allowance(&self, token: Address, spender: Address) -> ExchangeResult<U256> {
+        let allowance = IERC20::new(token, self.client.clone())
+            .allowance(self.client.address(), spender)
+            .call()
+            .await
+            .map_err(|e| ExchangeError::ApiError {
+                code: -1,
+                message: format!("allowance failed: {e}"),
+            })?;
+        self.allowances.insert((token, spender), allowance);
+        Ok(allowance)
+    }
+
+    /// Make sure `spender` may pull `amount` of `token`, approving if not
+    ///
+    /// Returns the approval transaction hash when one was sent.
+    pub async fn ensure(
+        &self,
+        token: Address,
+        spender: Address,
+        amount: U256,
+    ) -> ExchangeResult<Option<TxHash>> {
+        if self.cached(token, spender).is_some_and(|a| a >= amount) {
+            return Ok(None);
+        }
+
+        let _approving = self.approving.lock().await;
+        let current = self.allowance(token, spender).await?;
+        if current >= amount {
+            return Ok(None);
+        }
+
+        if !current.is_zero() {
+            self.approve(token, spender, U256::zero()).await?;
+        }
+        let tx_hash = self
+            .approve(token, spender, self.policy.approval_amount(amount))
+            .await?;
+        Ok(Some(tx_hash))
+    }
+
+    /// Record that a swap pulled `amount` of `token` through `spender`
+    pub fn spent(&self, token: Address, spender: Address, amount: U256) {
+        if let Some(mut allowance) = self.allowances.get_mut(&(token, spender)) {
+            // Unlimited approvals are not drawn down
+            if *allowance != U256::MAX {
+                *allowance = allowance.saturating_sub(amount);
+            }
+        }
+    }
+
+    async fn approve(
+        &self,
+        token: Address,
+        spender: Address,
+        amount: U256,
+    ) -> ExchangeResult<TxHash> {
+        let contract = IERC20::new(token, self.client.clone());
+        let call = contract.approve(spender, amount);
+        let pending = call
+            .send()
+            .await
+            .map_err(|e| ExchangeError::OrderRejected(format!("Approval not sent: {e}")))?;
+        let tx_hash = pending.tx_hash();
+        info!(
+            token = ?token,
+            spender = ?spender,
+            amount = %amount,
+            tx_hash = ?tx_hash,
+            "Approval submitted"
+        );
+
+        let receipt = pending
+            .await
+            .map_err(|e| ExchangeError::ConnectionFailed(format!("Approval {tx_hash:?}: {e}")))?
+            .ok_or_else(|| ExchangeError::ApiError {
+                code: -1,
+                message: format!("Approval {tx_hash:?} dropped from the mempool"),
+            })?;
+        if receipt.status != Some(1u64.into()) {
+            return Err(ExchangeError::OrderRejected(format!(
+                "Approval {tx_hash:?} reverted"
+            )));
+        }
+
+        self.allowances.insert((token, spender), amount);
+        Ok(tx_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approvals(policy: ApprovalPolicy) -> Approvals {
+        let provider = Provider::<Http>::try_from("http://localhost:8545").unwrap();
+        // Well-known development key
+        let wallet: LocalWallet =
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+                .parse()
+                .unwrap();
+        Approvals::new(
+            Arc::new(SignerMiddleware::new(Arc::new(provider), wallet)),
+            policy,
+        )
+    }
+
+    #[test]
+    fn test_policy_amounts() {
+        let amount = U256::from(1_000_000u64);
+        assert_eq!(ApprovalPolicy::Exact.approval_amount(amount), amount);
+        assert_eq!(ApprovalPolicy::Max.approval_amount(amount), U256::MAX);
+        assert_eq!(
+            serde_json::from_str::<ApprovalPolicy>("\"max\"").unwrap(),
+            ApprovalPolicy::Max
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cached_allowance_covers_swaps_until_spent() {
+        let approvals = approvals(ApprovalPolicy::Exact);
+        let token = Address::repeat_byte(1);
+        let router = Address::repeat_byte(2);
+        approvals
+            .allowances
+            .insert((token, router), U256::from(100u64));
+
+        // Served from the cache without an RPC
+        assert_eq!(
+            approvals
+                .ensure(token, router, U256::from(60u64))
+                .await
+                .unwrap(),
+            None
+        );
+
+        approvals.spent(token, router, U256::from(60u64));
+        assert_eq!(approvals.cached(token, router), Some(U256::from(40u64)));
+        approvals.spent(token, router, U256::from(60u64));
+        assert_eq!(approvals.cached(token, router), Some(U256::zero()));
+    }
+
+    #[test]
+    fn test_unlimited_allowance_is_not_drawn_down() {
+        let approvals = approvals(ApprovalPolicy::Max);
+        let token = Address::repeat_byte(1);
+        let router = Address::repeat_byte(2);
+        approvals.allowances.insert((token, router), U256::MAX);
+
+        approvals.spent(token, router, U256::from(60u64));
+        assert_eq!(approvals.cached(token, router), Some(U256::MAX));
+    }
+}
@@ -5,34 +5,145 @@
 #![allow(dead_code)]
 
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, TimeZone, Utc};
 use hmac::{Hmac, Mac};
 use reqwest::Client;
 use rust_decimal::Decimal;
 use sha2::Sha256;
 use std::collections::HashMap;
-use tracing::info;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
 
+use super::clock::VenueClock;
+use super::retry::{Recovery, RetryPolicy};
 use super::traits::*;
-use common::{ExchangeError, MarketData, Order, Symbol, Trade};
+use common::{
+    Candle, ExchangeError, MarketData, Order, OrderStatus, Symbol, SymbolInfo, SymbolRegistry,
+    SymbolStatus, Trade,
+};
 
 const BINANCE_API_URL: &str = "https://api.binance.com";
+const BINANCE_FUTURES_API_URL: &str = "https://fapi.binance.com";
+
+/// Maximum funding rate entries per request
+const FUNDING_HISTORY_LIMIT: u32 = 1000;
+
+/// Symbol entry from `/api/v3/exchangeInfo`
+#[derive(serde::Deserialize)]
+struct BinanceSymbol {
+    symbol: String,
+    #[serde(rename = "baseAsset")]
+    base_asset: String,
+    #[serde(rename = "quoteAsset")]
+    quote_asset: String,
+    status: String,
+    #[serde(rename = "baseAssetPrecision")]
+    base_asset_precision: u32,
+    #[serde(rename = "quotePrecision")]
+    quote_precision: u32,
+    #[serde(default)]
+    filters: Vec<serde_json::Value>,
+}
+
+impl BinanceSymbol {
+    /// Non-zero value of a filter field such as `tickSize` or `stepSize`
+    fn filter_value(&self, filter_type: &str, field: &str) -> Option<Decimal> {
+        self.filters
+            .iter()
+            .find(|f| f["filterType"] == filter_type)
+            .and_then(|f| f[field].as_str())
+            .and_then(|v| v.parse::<Decimal>().ok())
+            .filter(|v| !v.is_zero())
+            .map(|v| v.normalize())
+    }
+
+    /// Decimal places of a filter increment
+    fn filter_precision(&self, filter_type: &str, field: &str) -> Option<u32> {
+        self.filter_value(filter_type, field).map(|v| v.scale())
+    }
+
+    fn to_symbol_info(&self) -> SymbolInfo {
+        SymbolInfo {
+            symbol: Symbol::new(&self.base_asset, &self.quote_asset),
+            base_asset: self.base_asset.clone(),
+            quote_asset: self.quote_asset.clone(),
+            status: match self.status.as_str() {
+                "TRADING" => SymbolStatus::Trading,
+                "HALT" | "BREAK" => SymbolStatus::Halted,
+                "END_OF_DAY" => SymbolStatus::Delisted,
+                _ => SymbolStatus::Unknown,
+            },
+            price_precision: self
+                .filter_precision("PRICE_FILTER", "tickSize")
+                .unwrap_or(self.quote_precision),
+            quantity_precision: self
+                .filter_precision("LOT_SIZE", "stepSize")
+                .unwrap_or(self.base_asset_precision),
+            tick_size: self.filter_value("PRICE_FILTER", "tickSize"),
+            lot_size: self.filter_value("LOT_SIZE", "stepSize"),
+            min_quantity: self.filter_value("LOT_SIZE", "minQty"),
+            venues: HashMap::from([("binance".to_string(), self.symbol.clone())]),
+        }
+    }
+}
 
 pub struct BinanceAdapter {
     client: Client,
     api_key: String,
     api_secret: String,
+
+    /// Spot API root, overridable for testing
+    base_url: String,
+
+    /// Shared reference data for venue symbol mapping
+    symbols: Arc<SymbolRegistry>,
+
+    /// Retries for transient venue errors
+    retry: RetryPolicy,
+
+    /// Venue clock for signed request timestamps, shared across accounts
+    clock: Arc<VenueClock>,
 }
 
 impl BinanceAdapter {
-    pub fn new(api_key: String, api_secret: String) -> Self {
+    pub fn new(api_key: String, api_secret: String, symbols: Arc<SymbolRegistry>) -> Self {
         Self {
             client: Client::new(),
             api_key,
             api_secret,
+            base_url: BINANCE_API_URL.to_string(),
+            symbols,
+            retry: RetryPolicy::default(),
+            clock: Arc::new(VenueClock::new()),
         }
     }
 
+    /// Share a venue clock with other Binance adapters
+    pub fn with_clock(mut self, clock: Arc<VenueClock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Point spot requests at another API root, e.g. the testnet
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Binance symbol for `symbol`, e.g. `BTCUSDT`
+    fn venue_symbol(&self, symbol: &Symbol) -> String {
+        self.symbols
+            .get(symbol)
+            .and_then(|info| info.venues.get("binance").cloned())
+            .unwrap_or_else(|| format!("{}{}", symbol.base(), symbol.quote()))
+    }
+
     fn sign(&self, query_string: &str) -> String {
         let mut mac = Hmac::<Sha256>::new_from_slice(self.api_secret.as_bytes())
             .expect("HMAC can take key of any size");
@@ -40,51 +151,257 @@ impl BinanceAdapter {
         hex::encode(mac.finalize().into_bytes())
     }
 
+    /// Request parameters for a new order
+    fn order_params(&self, order: &Order) -> ExchangeResult<HashMap<String, String>> {
+        let binance_symbol = self.venue_symbol(&order.symbol);
+        let (quantity, price) = order_precision(&self.symbols, order)?;
+
+        let mut params = HashMap::new();
+        params.insert("symbol".to_string(), binance_symbol);
+        params.insert(
+            "side".to_string(),
+            if order.side == common::Side::Buy {
+                "BUY"
+            } else {
+                "SELL"
+            }
+            .to_string(),
+        );
+        params.insert(
+            "type".to_string(),
+            match order.order_type {
+                common::OrderType::Market => "MARKET",
+                common::OrderType::Limit => "LIMIT",
+                _ => "LIMIT",
+            }
+            .to_string(),
+        );
+        params.insert("quantity".to_string(), quantity.to_string());
+
+        if let Some(price) = price {
+            params.insert("price".to_string(), price.to_string());
+            params.insert("timeInForce".to_string(), "GTC".to_string());
+        }
+
+        params.insert(
+            "newClientOrderId".to_string(),
+            order.client_order_id.clone(),
+        );
+
+        Ok(params)
+    }
+
+    async fn exchange_info(&self) -> ExchangeResult<Vec<BinanceSymbol>> {
+        #[derive(serde::Deserialize)]
+        struct ExchangeInfo {
+            symbols: Vec<BinanceSymbol>,
+        }
+
+        let info: ExchangeInfo = self.public_request("/api/v3/exchangeInfo").await?;
+
+        Ok(info.symbols)
+    }
+
+    async fn public_request<T: serde::de::DeserializeOwned>(
+        &self,
+        endpoint: &str,
+    ) -> ExchangeResult<T> {
+        let url = format!("{}{endpoint}", self.base_url);
+        self.execute(|| self.client.get(&url)).await
+    }
+
     async fn signed_request<T: serde::de::DeserializeOwned>(
         &self,
         method: reqwest::Method,
         endpoint: &str,
-        params: &mut HashMap<String, String>,
+        params: &HashMap<String, String>,
     ) -> ExchangeResult<T> {
-        // Add timestamp
-        params.insert(
-            "timestamp".to_string(),
-            Utc::now().timestamp_millis().to_string(),
-        );
+        // Timestamp and signature are renewed on every attempt
+        self.execute(|| {
+            let mut params = params.clone();
+            params.insert("timestamp".to_string(), self.clock.now_ms().to_string());
 
-        // Build query string
-        let query_string: String = params
-            .iter()
-            .map(|(k, v)| format!("{k}={v}"))
-            .collect::<Vec<_>>()
-            .join("&");
+            // Build query string
+            let query_string: String = params
+                .iter()
+                .map(|(k, v)| format!("{k}={v}"))
+                .collect::<Vec<_>>()
+                .join("&");
 
-        // Sign
-        let signature = self.sign(&query_string);
-        let url = format!("{BINANCE_API_URL}{endpoint}?{query_string}&signature={signature}",);
+            // Sign
+            let signature = self.sign(&query_string);
+            let url = format!(
+                "{}{endpoint}?{query_string}&signature={signature}",
+                self.base_url
+            );
 
+            self.client
+                .request(method.clone(), &url)
+                .header("X-MBX-APIKEY", &self.api_key)
+        })
+        .await
+    }
+
+    /// Send a request, retrying transient failures under the retry policy
+    async fn execute<T: serde::de::DeserializeOwned>(
+        &self,
+        request: impl Fn() -> reqwest::RequestBuilder,
+    ) -> ExchangeResult<T> {
+        let mut attempt = 1;
+        loop {
+            let (error, recovery) = match request().send().await {
+                Ok(response) => {
+                    let status = response.status().as_u16();
+                    let retry_after = retry_after(&response);
+                    match read_response(response).await {
+                        Ok(value) => return Ok(value),
+                        Err(error) => {
+                            let recovery = recovery(status, &error, retry_after);
+                            (error, recovery)
+                        }
+                    }
+                }
+                // The request never reached the venue
+                Err(e) if e.is_connect() => (
+                    ExchangeError::ConnectionFailed(e.to_string()),
+                    Recovery::Backoff(None),
+                ),
+                Err(e) => return Err(ExchangeError::ConnectionFailed(e.to_string())),
+            };
+
+            if recovery == Recovery::Fail || !self.retry.allows_retry(attempt) {
+                return Err(error);
+            }
+
+            warn!(attempt, error = %error, reason = recovery.reason(), "Retrying Binance request");
+            metrics::counter!(
+                "exchange_retries",
+                "exchange" => "binance",
+                "reason" => recovery.reason()
+            )
+            .increment(1);
+
+            match recovery {
+                Recovery::Backoff(hint) => {
+                    tokio::time::sleep(self.retry.delay(attempt, hint)).await;
+                }
+                Recovery::ResyncClock => {
+                    if let Err(e) = self.resync_clock().await {
+                        warn!("Binance clock resync failed: {}", e);
+                        return Err(error);
+                    }
+                }
+                Recovery::Fail => unreachable!("permanent errors are returned above"),
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Measure the venue clock offset from `/api/v3/time`
+    async fn resync_clock(&self) -> ExchangeResult<i64> {
+        #[derive(serde::Deserialize)]
+        struct ServerTime {
+            #[serde(rename = "serverTime")]
+            server_time: i64,
+        }
+
+        let sent_at = Utc::now().timestamp_millis();
         let response = self
             .client
-            .request(method, &url)
-            .header("X-MBX-APIKEY", &self.api_key)
+            .get(format!("{}/api/v3/time", self.base_url))
             .send()
             .await
             .map_err(|e| ExchangeError::ConnectionFailed(e.to_string()))?;
+        let time: ServerTime = read_response(response).await?;
+        let received_at = Utc::now().timestamp_millis();
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
-            return Err(ExchangeError::ApiError {
-                code: status.as_u16() as i32,
-                message: text,
-            });
-        }
+        let offset = self.clock.observe(time.server_time, sent_at, received_at);
+        debug!(offset_ms = offset, "Resynced Binance clock");
+        Ok(offset)
+    }
+}
+
+/// Recovery for a failed Binance request
+///
+/// -1021 means the timestamp fell outside the receive window; 429 and
+/// -1003/-1015 are request and order rate limits. A 418 is an IP ban and
+/// a 5xx leaves the request's outcome unknown, so neither is retried.
+fn recovery(status: u16, error: &ExchangeError, retry_after: Option<Duration>) -> Recovery {
+    match (status, error) {
+        (418, _) => Recovery::Fail,
+        (_, ExchangeError::ApiError { code: -1021, .. }) => Recovery::ResyncClock,
+        (_, ExchangeError::RateLimited)
+        | (
+            _,
+            ExchangeError::ApiError {
+                code: -1003 | -1015,
+                ..
+            },
+        ) => Recovery::Backoff(retry_after),
+        _ => Recovery::Fail,
+    }
+}
+
+/// Order status from Binance's upper-case order states
+fn order_status(status: &str) -> OrderStatus {
+    match status {
+        "PENDING_NEW" => OrderStatus::Pending,
+        "PARTIALLY_FILLED" => OrderStatus::PartiallyFilled,
+        "FILLED" => OrderStatus::Filled,
+        "CANCELED" => OrderStatus::Cancelled,
+        "REJECTED" => OrderStatus::Rejected,
+        "EXPIRED" | "EXPIRED_IN_MATCH" => OrderStatus::Expired,
+        // NEW, PENDING_CANCEL and anything unknown are still working
+        _ => OrderStatus::Open,
+    }
+}
+
+/// `Retry-After` hint in seconds, sent with rate limit responses
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+        .map(Duration::from_secs)
+}
 
-        response.json().await.map_err(|e| ExchangeError::ApiError {
+/// Decode a Binance response, mapping failures onto `ExchangeError`
+///
+/// Error bodies carry Binance's own `{code, msg}`; orders the matching
+/// rules refuse come back as 400 with code -2010.
+pub(super) async fn read_response<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+) -> ExchangeResult<T> {
+    #[derive(serde::Deserialize)]
+    struct BinanceError {
+        code: i32,
+        msg: String,
+    }
+
+    let status = response.status();
+    if status.is_success() {
+        return response.json().await.map_err(|e| ExchangeError::ApiError {
             code: -1,
             message: e.to_string(),
-        })
+        });
     }
+
+    let text = response.text().await.unwrap_or_default();
+    let error = serde_json::from_str::<BinanceError>(&text).ok();
+
+    Err(match (status.as_u16(), error) {
+        (429 | 418, _) => ExchangeError::RateLimited,
+        (401 | 403, error) => {
+            ExchangeError::AuthenticationFailed(error.map(|e| e.msg).unwrap_or(text))
+        }
+        (_, Some(BinanceError { code: -2010, msg })) => ExchangeError::OrderRejected(msg),
+        (_, Some(BinanceError { code, msg })) => ExchangeError::ApiError { code, message: msg },
+        (status, None) => ExchangeError::ApiError {
+            code: status as i32,
+            message: text,
+        },
+    })
 }
 
 #[async_trait]
@@ -94,51 +411,36 @@ impl ExchangeAdapter for BinanceAdapter {
     }
 
     async fn is_available(&self) -> bool {
-        let result = self
-            .client
-            .get(format!("{BINANCE_API_URL}/api/v3/ping"))
+        self.client
+            .get(format!("{}/api/v3/ping", self.base_url))
             .send()
-            .await;
-        result.is_ok()
+            .await
+            .is_ok_and(|r| r.status().is_success())
     }
 
-    async fn get_symbols(&self) -> ExchangeResult<Vec<Symbol>> {
-        #[derive(serde::Deserialize)]
-        struct ExchangeInfo {
-            symbols: Vec<SymbolInfo>,
-        }
-
-        #[derive(serde::Deserialize)]
-        struct SymbolInfo {
-            symbol: String,
-            #[serde(rename = "baseAsset")]
-            base_asset: String,
-            #[serde(rename = "quoteAsset")]
-            quote_asset: String,
-            status: String,
-        }
-
-        let info: ExchangeInfo = self
-            .client
-            .get(format!("{BINANCE_API_URL}/api/v3/exchangeInfo"))
-            .send()
-            .await
-            .map_err(|e| ExchangeError::ConnectionFailed(e.to_string()))?
-            .json()
-            .await
-            .map_err(|e| ExchangeError::ApiError {
-                code: -1,
-                message: e.to_string(),
-            })?;
+    async fn sync_clock(&self) -> ExchangeResult<Option<i64>> {
+        self.resync_clock().await.map(Some)
+    }
 
-        Ok(info
-            .symbols
+    async fn get_symbols(&self) -> ExchangeResult<Vec<Symbol>> {
+        Ok(self
+            .exchange_info()
+            .await?
             .into_iter()
             .filter(|s| s.status == "TRADING")
             .map(|s| Symbol::new(&s.base_asset, &s.quote_asset))
             .collect())
     }
 
+    async fn get_symbol_info(&self) -> ExchangeResult<Vec<SymbolInfo>> {
+        Ok(self
+            .exchange_info()
+            .await?
+            .iter()
+            .map(BinanceSymbol::to_symbol_info)
+            .collect())
+    }
+
     async fn get_market_data(&self, symbol: &Symbol) -> ExchangeResult<MarketData> {
         #[derive(serde::Deserialize)]
         struct Ticker {
@@ -155,24 +457,17 @@ impl ExchangeAdapter for BinanceAdapter {
             high_price: String,
             #[serde(rename = "lowPrice")]
             low_price: String,
+            #[serde(rename = "priceChange", default)]
+            price_change: String,
+            #[serde(rename = "priceChangePercent", default)]
+            price_change_percent: String,
         }
 
-        let binance_symbol = format!("{}{}", symbol.base(), symbol.quote());
+        let binance_symbol = self.venue_symbol(symbol);
 
         let ticker: Ticker = self
-            .client
-            .get(format!(
-                "{BINANCE_API_URL}/api/v3/ticker/24hr?symbol={binance_symbol}",
-            ))
-            .send()
-            .await
-            .map_err(|e| ExchangeError::ConnectionFailed(e.to_string()))?
-            .json()
-            .await
-            .map_err(|e| ExchangeError::ApiError {
-                code: -1,
-                message: e.to_string(),
-            })?;
+            .public_request(&format!("/api/v3/ticker/24hr?symbol={binance_symbol}"))
+            .await?;
 
         Ok(MarketData {
             symbol: symbol.clone(),
@@ -182,6 +477,9 @@ impl ExchangeAdapter for BinanceAdapter {
             volume_24h: ticker.volume.parse().unwrap_or_default(),
             high_24h: ticker.high_price.parse().unwrap_or_default(),
             low_24h: ticker.low_price.parse().unwrap_or_default(),
+            price_change_24h: ticker.price_change.parse().unwrap_or_default(),
+            percent_change_24h: ticker.price_change_percent.parse().unwrap_or_default(),
+            fair_value: None,
             timestamp: Utc::now(),
         })
     }
@@ -199,9 +497,9 @@ impl ExchangeAdapter for BinanceAdapter {
             locked: String,
         }
 
-        let mut params = HashMap::new();
+        let params = HashMap::new();
         let account: AccountInfo = self
-            .signed_request(reqwest::Method::GET, "/api/v3/account", &mut params)
+            .signed_request(reqwest::Method::GET, "/api/v3/account", &params)
             .await?;
 
         Ok(account
@@ -221,39 +519,7 @@ impl ExchangeAdapter for BinanceAdapter {
     }
 
     async fn place_order(&self, order: &Order) -> ExchangeResult<ExchangeOrder> {
-        let binance_symbol = format!("{}{}", order.symbol.base(), order.symbol.quote());
-
-        let mut params = HashMap::new();
-        params.insert("symbol".to_string(), binance_symbol);
-        params.insert(
-            "side".to_string(),
-            if order.side == common::Side::Buy {
-                "BUY"
-            } else {
-                "SELL"
-            }
-            .to_string(),
-        );
-        params.insert(
-            "type".to_string(),
-            match order.order_type {
-                common::OrderType::Market => "MARKET",
-                common::OrderType::Limit => "LIMIT",
-                _ => "LIMIT",
-            }
-            .to_string(),
-        );
-        params.insert("quantity".to_string(), order.quantity.to_string());
-
-        if let Some(price) = order.price {
-            params.insert("price".to_string(), price.to_string());
-            params.insert("timeInForce".to_string(), "GTC".to_string());
-        }
-
-        params.insert(
-            "newClientOrderId".to_string(),
-            order.client_order_id.clone(),
-        );
+        let params = self.order_params(order)?;
 
         #[derive(serde::Deserialize)]
         struct OrderResponse {
@@ -269,7 +535,7 @@ impl ExchangeAdapter for BinanceAdapter {
         }
 
         let response: OrderResponse = self
-            .signed_request(reqwest::Method::POST, "/api/v3/order", &mut params)
+            .signed_request(reqwest::Method::POST, "/api/v3/order", &params)
             .await?;
 
         info!(
@@ -282,21 +548,84 @@ impl ExchangeAdapter for BinanceAdapter {
             exchange_order_id: response.order_id.to_string(),
             client_order_id: response.client_order_id,
             symbol: order.symbol.clone(),
-            status: response.status,
+            status: order_status(&response.status),
             filled_quantity: response.executed_qty.parse().unwrap_or_default(),
             avg_price: response.avg_price.and_then(|p| p.parse().ok()),
         })
     }
 
+    /// Cancel and place in one request with `cancelReplace`
+    ///
+    /// `STOP_ON_FAILURE` leaves no replacement when the cancel fails
+    /// (-2021); -2022 means the original was cancelled but the replacement
+    /// was refused.
+    async fn replace_order(&self, order_id: &str, order: &Order) -> ExchangeResult<ExchangeOrder> {
+        let mut params = self.order_params(order)?;
+        params.insert("cancelOrderId".to_string(), order_id.to_string());
+        params.insert(
+            "cancelReplaceMode".to_string(),
+            "STOP_ON_FAILURE".to_string(),
+        );
+
+        #[derive(serde::Deserialize)]
+        struct OrderResponse {
+            #[serde(rename = "orderId")]
+            order_id: u64,
+            #[serde(rename = "clientOrderId")]
+            client_order_id: String,
+            status: String,
+            #[serde(rename = "executedQty")]
+            executed_qty: String,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct CancelReplaceResponse {
+            #[serde(rename = "newOrderResponse")]
+            new_order: OrderResponse,
+        }
+
+        let response: CancelReplaceResponse = self
+            .signed_request(
+                reqwest::Method::POST,
+                "/api/v3/order/cancelReplace",
+                &params,
+            )
+            .await
+            .map_err(|e| match e {
+                ExchangeError::ApiError {
+                    code: code @ (-2021 | -2022),
+                    message,
+                } => ExchangeError::OrderRejected(format!("{message} ({code})")),
+                e => e,
+            })?;
+        let response = response.new_order;
+
+        info!(
+            cancelled = order_id,
+            order_id = response.order_id,
+            status = %response.status,
+            "Order replaced on Binance"
+        );
+
+        Ok(ExchangeOrder {
+            exchange_order_id: response.order_id.to_string(),
+            client_order_id: response.client_order_id,
+            symbol: order.symbol.clone(),
+            status: order_status(&response.status),
+            filled_quantity: response.executed_qty.parse().unwrap_or_default(),
+            avg_price: None,
+        })
+    }
+
     async fn cancel_order(&self, symbol: &Symbol, order_id: &str) -> ExchangeResult<()> {
-        let binance_symbol = format!("{}{}", symbol.base(), symbol.quote());
+        let binance_symbol = self.venue_symbol(symbol);
 
         let mut params = HashMap::new();
         params.insert("symbol".to_string(), binance_symbol);
         params.insert("orderId".to_string(), order_id.to_string());
 
         let _: serde_json::Value = self
-            .signed_request(reqwest::Method::DELETE, "/api/v3/order", &mut params)
+            .signed_request(reqwest::Method::DELETE, "/api/v3/order", &params)
             .await?;
 
         info!(order_id = order_id, "Order cancelled on Binance");
@@ -305,7 +634,7 @@ impl ExchangeAdapter for BinanceAdapter {
     }
 
     async fn get_order(&self, symbol: &Symbol, order_id: &str) -> ExchangeResult<ExchangeOrder> {
-        let binance_symbol = format!("{}{}", symbol.base(), symbol.quote());
+        let binance_symbol = self.venue_symbol(symbol);
 
         let mut params = HashMap::new();
         params.insert("symbol".to_string(), binance_symbol);
@@ -325,21 +654,575 @@ impl ExchangeAdapter for BinanceAdapter {
         }
 
         let response: OrderResponse = self
-            .signed_request(reqwest::Method::GET, "/api/v3/order", &mut params)
+            .signed_request(reqwest::Method::GET, "/api/v3/order", &params)
             .await?;
 
         Ok(ExchangeOrder {
             exchange_order_id: response.order_id.to_string(),
             client_order_id: response.client_order_id,
             symbol: symbol.clone(),
-            status: response.status,
+            status: order_status(&response.status),
             filled_quantity: response.executed_qty.parse().unwrap_or_default(),
             avg_price: response.avg_price.and_then(|p| p.parse().ok()),
         })
     }
 
-    async fn get_trades(&self, _symbol: &Symbol, _limit: u32) -> ExchangeResult<Vec<Trade>> {
-        // Implementation would fetch recent trades
-        Ok(vec![])
+    /// Top `limit` levels from `/api/v3/depth`
+    async fn get_depth(&self, symbol: &Symbol, limit: u32) -> ExchangeResult<OrderBookDepth> {
+        #[derive(serde::Deserialize)]
+        struct Depth {
+            bids: Vec<Vec<serde_json::Value>>,
+            asks: Vec<Vec<serde_json::Value>>,
+        }
+
+        let binance_symbol = self.venue_symbol(symbol);
+        let depth: Depth = self
+            .public_request(&format!(
+                "/api/v3/depth?symbol={binance_symbol}&limit={limit}"
+            ))
+            .await?;
+
+        Ok(OrderBookDepth {
+            symbol: symbol.clone(),
+            bids: parse_levels(&depth.bids),
+            asks: parse_levels(&depth.asks),
+            timestamp: Utc::now(),
+        })
+    }
+
+    /// Recent public trades; venue trades carry no order or user ids
+    async fn get_trades(&self, symbol: &Symbol, limit: u32) -> ExchangeResult<Vec<Trade>> {
+        #[derive(serde::Deserialize)]
+        struct PublicTrade {
+            id: u64,
+            price: String,
+            qty: String,
+            #[serde(rename = "quoteQty")]
+            quote_qty: String,
+            time: i64,
+            #[serde(rename = "isBuyerMaker")]
+            is_buyer_maker: bool,
+        }
+
+        let binance_symbol = self.venue_symbol(symbol);
+        let trades: Vec<PublicTrade> = self
+            .public_request(&format!(
+                "/api/v3/trades?symbol={binance_symbol}&limit={limit}"
+            ))
+            .await?;
+
+        Ok(trades
+            .into_iter()
+            .filter_map(|t| {
+                Some(Trade {
+                    id: uuid::Uuid::new_v4(),
+                    trade_id: t.id,
+                    symbol: symbol.clone(),
+                    maker_order_id: uuid::Uuid::nil(),
+                    maker_user_id: uuid::Uuid::nil(),
+                    taker_order_id: uuid::Uuid::nil(),
+                    taker_user_id: uuid::Uuid::nil(),
+                    price: t.price.parse().ok()?,
+                    quantity: t.qty.parse().ok()?,
+                    quote_quantity: t.quote_qty.parse().ok()?,
+                    taker_side: if t.is_buyer_maker {
+                        common::Side::Sell
+                    } else {
+                        common::Side::Buy
+                    },
+                    executed_at: Utc.timestamp_millis_opt(t.time).single()?,
+                })
+            })
+            .collect())
+    }
+
+    async fn get_klines(
+        &self,
+        symbol: &Symbol,
+        interval: &str,
+        start: DateTime<Utc>,
+        limit: u32,
+    ) -> ExchangeResult<Vec<Candle>> {
+        // [open time, open, high, low, close, volume, close time,
+        //  quote volume, trades, taker base, taker quote, ignore]
+        type Kline = (
+            i64,
+            String,
+            String,
+            String,
+            String,
+            String,
+            i64,
+            String,
+            u32,
+            String,
+            String,
+            String,
+        );
+
+        let binance_symbol = self.venue_symbol(symbol);
+        let klines: Vec<Kline> = self
+            .public_request(&format!(
+                "/api/v3/klines?symbol={binance_symbol}&interval={interval}&startTime={}&limit={limit}",
+                start.timestamp_millis()
+            ))
+            .await?;
+
+        Ok(klines
+            .into_iter()
+            .filter_map(|k| {
+                Some(Candle {
+                    symbol: symbol.clone(),
+                    interval: interval.to_string(),
+                    open_time: Utc.timestamp_millis_opt(k.0).single()?,
+                    open: k.1.parse().ok()?,
+                    high: k.2.parse().ok()?,
+                    low: k.3.parse().ok()?,
+                    close: k.4.parse().ok()?,
+                    volume: k.5.parse().ok()?,
+                    close_time: Utc.timestamp_millis_opt(k.6).single()?,
+                    trade_count: k.8,
+                })
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl RateSource for BinanceAdapter {
+    async fn get_funding_rates(
+        &self,
+        symbol: &Symbol,
+        since: DateTime<Utc>,
+    ) -> ExchangeResult<Vec<FundingRate>> {
+        #[derive(serde::Deserialize)]
+        struct FundingEntry {
+            #[serde(rename = "fundingTime")]
+            funding_time: i64,
+            #[serde(rename = "fundingRate")]
+            funding_rate: String,
+            #[serde(rename = "markPrice", default)]
+            mark_price: Option<String>,
+        }
+
+        // USD-M perpetuals share the spot symbol
+        let binance_symbol = self.venue_symbol(symbol);
+
+        let entries: Vec<FundingEntry> = self
+            .client
+            .get(format!(
+                "{BINANCE_FUTURES_API_URL}/fapi/v1/fundingRate?symbol={binance_symbol}&startTime={}&limit={FUNDING_HISTORY_LIMIT}",
+                since.timestamp_millis(),
+            ))
+            .send()
+            .await
+            .map_err(|e| ExchangeError::ConnectionFailed(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| ExchangeError::ApiError {
+                code: -1,
+                message: e.to_string(),
+            })?;
+
+        Ok(entries
+            .into_iter()
+            .filter_map(|e| {
+                Some(FundingRate {
+                    rate: e.funding_rate.parse().ok()?,
+                    mark_price: e.mark_price.and_then(|p| p.parse().ok()),
+                    funding_time: Utc.timestamp_millis_opt(e.funding_time).single()?,
+                })
+            })
+            .collect())
+    }
+
+    async fn get_borrow_rates(
+        &self,
+        asset: &str,
+        since: DateTime<Utc>,
+    ) -> ExchangeResult<Vec<BorrowRate>> {
+        #[derive(serde::Deserialize)]
+        struct InterestRateEntry {
+            #[serde(rename = "dailyInterestRate")]
+            daily_interest_rate: String,
+            timestamp: i64,
+        }
+
+        let mut params = HashMap::new();
+        params.insert("asset".to_string(), asset.to_uppercase());
+        params.insert(
+            "startTime".to_string(),
+            since.timestamp_millis().to_string(),
+        );
+
+        let entries: Vec<InterestRateEntry> = self
+            .signed_request(
+                reqwest::Method::GET,
+                "/sapi/v1/margin/interestRateHistory",
+                &params,
+            )
+            .await?;
+
+        Ok(entries
+            .into_iter()
+            .filter_map(|e| {
+                let daily: Decimal = e.daily_interest_rate.parse().ok()?;
+                Some(BorrowRate {
+                    annual_rate: daily * Decimal::from(365),
+                    timestamp: Utc.timestamp_millis_opt(e.timestamp).single()?,
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::conformance::{
+        self, fixture, ConformanceTarget, MockVenue, RecordedRequest,
+    };
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    struct Binance;
+
+    #[async_trait]
+    impl ConformanceTarget for Binance {
+        type Adapter = BinanceAdapter;
+
+        fn adapter(&self, base_url: &str, symbols: Arc<SymbolRegistry>) -> BinanceAdapter {
+            BinanceAdapter::new("key".to_string(), "secret".to_string(), symbols)
+                .with_base_url(base_url)
+                .with_retry_policy(RetryPolicy::new(3, Duration::from_millis(1)))
+        }
+
+        async fn mount_fixtures(&self, venue: &MockVenue) {
+            let order = json!({
+                "orderId": 12345,
+                "clientOrderId": "c1",
+                "status": "NEW",
+                "executedQty": fixture::FILLED.to_string(),
+            });
+
+            venue
+                .mock(Method::GET, "/api/v3/ping", StatusCode::OK, json!({}))
+                .await;
+            venue
+                .mock(
+                    Method::GET,
+                    "/api/v3/exchangeInfo",
+                    StatusCode::OK,
+                    json!({ "symbols": [{
+                        "symbol": "BTCUSDT",
+                        "baseAsset": fixture::BASE,
+                        "quoteAsset": fixture::QUOTE,
+                        "status": "TRADING",
+                        "baseAssetPrecision": 8,
+                        "quotePrecision": 8,
+                    }]}),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::GET,
+                    "/api/v3/ticker/24hr",
+                    StatusCode::OK,
+                    json!({
+                        "symbol": "BTCUSDT",
+                        "bidPrice": fixture::BID.to_string(),
+                        "askPrice": fixture::ASK.to_string(),
+                        "lastPrice": fixture::LAST.to_string(),
+                        "volume": "10",
+                        "highPrice": "105",
+                        "lowPrice": "95",
+                    }),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::GET,
+                    "/api/v3/trades",
+                    StatusCode::OK,
+                    json!([{
+                        "id": 1,
+                        "price": fixture::TRADE_PRICE.to_string(),
+                        "qty": "0.1",
+                        "quoteQty": "10.1",
+                        "time": 1_700_000_000_000_i64,
+                        "isBuyerMaker": true,
+                    }]),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::GET,
+                    "/api/v3/account",
+                    StatusCode::OK,
+                    json!({ "balances": [
+                        {
+                            "asset": "BTC",
+                            "free": fixture::BTC_FREE.to_string(),
+                            "locked": fixture::BTC_LOCKED.to_string(),
+                        },
+                        { "asset": "ETH", "free": "0", "locked": "0" },
+                    ]}),
+                )
+                .await;
+            venue
+                .mock(Method::POST, "/api/v3/order", StatusCode::OK, order.clone())
+                .await;
+            venue
+                .mock(
+                    Method::POST,
+                    "/api/v3/order/cancelReplace",
+                    StatusCode::OK,
+                    json!({
+                        "cancelResult": "SUCCESS",
+                        "newOrderResult": "SUCCESS",
+                        "newOrderResponse": order.clone(),
+                    }),
+                )
+                .await;
+            venue
+                .mock(Method::GET, "/api/v3/order", StatusCode::OK, order)
+                .await;
+            venue
+                .mock(Method::DELETE, "/api/v3/order", StatusCode::OK, json!({}))
+                .await;
+        }
+
+        fn order_endpoint(&self) -> (Method, &'static str) {
+            (Method::POST, "/api/v3/order")
+        }
+
+        fn cancel_endpoint(&self) -> (Method, &'static str) {
+            (Method::DELETE, "/api/v3/order")
+        }
+
+        fn market_data_endpoint(&self) -> (Method, &'static str) {
+            (Method::GET, "/api/v3/ticker/24hr")
+        }
+
+        fn is_signed(&self, request: &RecordedRequest) -> bool {
+            let Some((payload, signature)) = request.query.split_once("&signature=") else {
+                return false;
+            };
+            let adapter = self.adapter("", Arc::new(SymbolRegistry::new()));
+            request
+                .headers
+                .get("X-MBX-APIKEY")
+                .is_some_and(|k| k == "key")
+                && adapter.sign(payload) == signature
+        }
+
+        fn sent_order(&self, request: &RecordedRequest) -> (Decimal, Option<Decimal>) {
+            let params = request.params();
+            (
+                params["quantity"].parse().unwrap(),
+                params.get("price").map(|p| p.parse().unwrap()),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conformance() {
+        conformance::run_conformance(&Binance).await;
+    }
+
+    #[tokio::test]
+    async fn test_clock_sync_shared_across_accounts() {
+        let venue = MockVenue::start().await;
+        let server_time = Utc::now().timestamp_millis() + 60_000;
+        venue
+            .mock(
+                Method::GET,
+                "/api/v3/time",
+                StatusCode::OK,
+                json!({ "serverTime": server_time }),
+            )
+            .await;
+
+        let clock = Arc::new(VenueClock::new());
+        let house = Binance
+            .adapter(venue.url(), Arc::new(SymbolRegistry::new()))
+            .with_clock(clock.clone());
+        let offset = house.sync_clock().await.unwrap().expect("offset");
+        assert!((59_000..=60_000).contains(&offset));
+
+        // A sub-account sharing the clock signs with venue time
+        let sub = Binance
+            .adapter(venue.url(), Arc::new(SymbolRegistry::new()))
+            .with_clock(clock);
+        venue
+            .mock(
+                Method::GET,
+                "/api/v3/account",
+                StatusCode::OK,
+                json!({ "balances": [] }),
+            )
+            .await;
+        sub.get_balances().await.unwrap();
+        let sent = venue
+            .last_request(&Method::GET, "/api/v3/account")
+            .await
+            .unwrap();
+        let timestamp: i64 = sent.params()["timestamp"].parse().unwrap();
+        assert!(timestamp >= server_time - 1_000);
+    }
+
+    #[tokio::test]
+    async fn test_depth_levels() {
+        let venue = MockVenue::start().await;
+        let adapter = Binance.adapter(venue.url(), Arc::new(SymbolRegistry::new()));
+        venue
+            .mock(
+                Method::GET,
+                "/api/v3/depth",
+                StatusCode::OK,
+                json!({
+                    "lastUpdateId": 1027024,
+                    "bids": [["4.00000000", "431.00000000"], ["3.99000000", "9.00000000"]],
+                    "asks": [["4.00000200", "12.00000000"]],
+                }),
+            )
+            .await;
+
+        let depth = adapter
+            .get_depth(&Symbol::new("BTC", "USDT"), 5)
+            .await
+            .unwrap();
+        assert_eq!(depth.bids.len(), 2);
+        assert_eq!(depth.bids[0].price, Decimal::new(4, 0));
+        assert_eq!(depth.asks[0].quantity, Decimal::new(12, 0));
+
+        let sent = venue
+            .last_request(&Method::GET, "/api/v3/depth")
+            .await
+            .unwrap();
+        assert_eq!(sent.params()["symbol"], "BTCUSDT");
+        assert_eq!(sent.params()["limit"], "5");
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_errors_only() {
+        let venue = MockVenue::start().await;
+        Binance.mount_fixtures(&venue).await;
+        let adapter = Binance.adapter(venue.url(), Arc::new(SymbolRegistry::new()));
+        let server_time = Utc::now().timestamp_millis() + 60_000;
+        venue
+            .mock(
+                Method::GET,
+                "/api/v3/time",
+                StatusCode::OK,
+                json!({ "serverTime": server_time }),
+            )
+            .await;
+        let orders_sent = || venue.request_count(&Method::POST, "/api/v3/order");
+
+        // Clock drift: resync, then resend with a corrected timestamp
+        venue
+            .mock_once(
+                Method::POST,
+                "/api/v3/order",
+                StatusCode::BAD_REQUEST,
+                json!({ "code": -1021, "msg": "Timestamp outside recvWindow" }),
+            )
+            .await;
+        let mut params = HashMap::new();
+        params.insert("symbol".to_string(), "BTCUSDT".to_string());
+        let _: serde_json::Value = adapter
+            .signed_request(reqwest::Method::POST, "/api/v3/order", &params)
+            .await
+            .expect("retried after resync");
+        assert_eq!(orders_sent().await, 2);
+        let sent = venue
+            .last_request(&Method::POST, "/api/v3/order")
+            .await
+            .unwrap();
+        let timestamp: i64 = sent.params()["timestamp"].parse().unwrap();
+        assert!(timestamp >= server_time - 1_000);
+
+        // Rate limits back off and retry until attempts run out
+        for _ in 0..3 {
+            venue
+                .mock_once(
+                    Method::POST,
+                    "/api/v3/order",
+                    StatusCode::TOO_MANY_REQUESTS,
+                    json!({ "code": -1003, "msg": "Too many requests" }),
+                )
+                .await;
+        }
+        let result: ExchangeResult<serde_json::Value> = adapter
+            .signed_request(reqwest::Method::POST, "/api/v3/order", &params)
+            .await;
+        assert!(matches!(result, Err(ExchangeError::RateLimited)));
+        assert_eq!(orders_sent().await, 5);
+
+        // Unknown outcome and rejections are surfaced at once
+        for (status, body) in [
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                json!({ "code": -1001, "msg": "Disconnected" }),
+            ),
+            (
+                StatusCode::BAD_REQUEST,
+                json!({ "code": -2010, "msg": "Insufficient balance" }),
+            ),
+        ] {
+            venue
+                .mock_once(Method::POST, "/api/v3/order", status, body)
+                .await;
+            let result: ExchangeResult<serde_json::Value> = adapter
+                .signed_request(reqwest::Method::POST, "/api/v3/order", &params)
+                .await;
+            assert!(result.is_err());
+        }
+        assert_eq!(orders_sent().await, 7);
+    }
+
+    #[tokio::test]
+    async fn test_replace_uses_cancel_replace() {
+        let venue = MockVenue::start().await;
+        Binance.mount_fixtures(&venue).await;
+        let adapter = Binance.adapter(venue.url(), Arc::new(SymbolRegistry::new()));
+        let mut order =
+            conformance::limit_order(common::Side::Buy, Decimal::ONE, Decimal::new(101, 0));
+        order.client_order_id = "replacement".to_string();
+
+        let replaced = adapter.replace_order("12345", &order).await.unwrap();
+        assert_eq!(replaced.exchange_order_id, "12345");
+        let sent = venue
+            .last_request(&Method::POST, "/api/v3/order/cancelReplace")
+            .await
+            .unwrap();
+        let params = sent.params();
+        assert_eq!(params["cancelOrderId"], "12345");
+        assert_eq!(params["cancelReplaceMode"], "STOP_ON_FAILURE");
+        assert_eq!(params["newClientOrderId"], "replacement");
+        assert_eq!(params["price"], "101");
+        // No separate cancel or place
+        assert!(venue
+            .last_request(&Method::DELETE, "/api/v3/order")
+            .await
+            .is_none());
+        assert!(venue
+            .last_request(&Method::POST, "/api/v3/order")
+            .await
+            .is_none());
+
+        venue
+            .mock_once(
+                Method::POST,
+                "/api/v3/order/cancelReplace",
+                StatusCode::BAD_REQUEST,
+                json!({ "code": -2021, "msg": "Order cancel-replace failed." }),
+            )
+            .await;
+        assert!(matches!(
+            adapter.replace_order("12345", &order).await,
+            Err(ExchangeError::OrderRejected(_))
+        ));
     }
 }
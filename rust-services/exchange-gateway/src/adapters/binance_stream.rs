@@ -0,0 +1,559 @@
+//! Binance WebSocket Streams
+//!
+//! The REST adapter only learns of fills by polling. The user-data stream
+//! pushes execution reports as they happen; its listenKey is created over
+//! REST, kept alive every 30 minutes and replaced when Binance expires it.
+//! Market streams combine the trade and bookTicker streams of configured
+//! symbols into tickers. Fills and tickers are published to Kafka, and
+//! dropped connections are reopened after a short delay.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{TimeZone, Utc};
+use futures_util::{SinkExt, StreamExt};
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use tokio::time::{self, Instant};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+
+use super::binance::read_response;
+use super::traits::ExchangeResult;
+use crate::events::EventPublisher;
+use crate::fillcheck::FillPriceCheck;
+use common::events::{VenueFill, VenueTicker};
+use common::{ExchangeError, Side, Symbol, SymbolRegistry};
+
+const BINANCE_API_URL: &str = "https://api.binance.com";
+const BINANCE_WS_URL: &str = "wss://stream.binance.com:9443";
+
+/// listenKeys expire 60 minutes after creation or the last keepalive
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Quote assets tried when a venue symbol has no reference data
+const QUOTE_ASSETS: [&str; 6] = ["USDT", "USDC", "FDUSD", "BTC", "ETH", "BNB"];
+
+/// User-data stream event, tagged by `e`
+#[derive(Debug, Deserialize)]
+#[serde(tag = "e")]
+enum UserDataEvent {
+    #[serde(rename = "executionReport")]
+    ExecutionReport(ExecutionReport),
+    #[serde(rename = "listenKeyExpired")]
+    ListenKeyExpired,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExecutionReport {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "c")]
+    client_order_id: String,
+    #[serde(rename = "S")]
+    side: String,
+    /// Execution type; `TRADE` for fills
+    #[serde(rename = "x")]
+    execution_type: String,
+    #[serde(rename = "i")]
+    order_id: u64,
+    #[serde(rename = "l")]
+    last_quantity: String,
+    #[serde(rename = "L")]
+    last_price: String,
+    #[serde(rename = "z")]
+    cumulative_quantity: String,
+    #[serde(rename = "n")]
+    commission: String,
+    #[serde(rename = "N")]
+    commission_asset: Option<String>,
+    #[serde(rename = "T")]
+    transaction_time: i64,
+    #[serde(rename = "t")]
+    trade_id: i64,
+    #[serde(rename = "m")]
+    is_maker: bool,
+}
+
+/// Envelope of combined market streams
+#[derive(Debug, Deserialize)]
+struct Combined {
+    stream: String,
+    data: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct BookTicker {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "b")]
+    bid: String,
+    #[serde(rename = "B")]
+    bid_quantity: String,
+    #[serde(rename = "a")]
+    ask: String,
+    #[serde(rename = "A")]
+    ask_quantity: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MarketTrade {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "p")]
+    price: String,
+    #[serde(rename = "T")]
+    trade_time: i64,
+}
+
+pub struct BinanceStreams {
+    client: Client,
+    api_key: String,
+
+    /// REST and WebSocket roots, overridable for testing
+    rest_url: String,
+    ws_url: String,
+
+    /// Shared reference data for venue symbol mapping
+    symbols: Arc<SymbolRegistry>,
+
+    publisher: Arc<EventPublisher>,
+
+    /// Flags fills far from the internal midprice, when enabled
+    fill_check: Option<Arc<FillPriceCheck>>,
+}
+
+impl BinanceStreams {
+    pub fn new(
+        api_key: String,
+        symbols: Arc<SymbolRegistry>,
+        publisher: Arc<EventPublisher>,
+    ) -> Self {
+        Self {
+            client: Client::new(),
+            api_key,
+            rest_url: BINANCE_API_URL.to_string(),
+            ws_url: BINANCE_WS_URL.to_string(),
+            symbols,
+            publisher,
+            fill_check: None,
+        }
+    }
+
+    /// Check every fill against the internal midprice before publishing it
+    pub fn with_fill_check(mut self, fill_check: Arc<FillPriceCheck>) -> Self {
+        self.fill_check = Some(fill_check);
+        self
+    }
+
+    /// Point requests at other roots, e.g. the testnet
+    pub fn with_urls(mut self, rest_url: impl Into<String>, ws_url: impl Into<String>) -> Self {
+        self.rest_url = rest_url.into();
+        self.ws_url = ws_url.into();
+        self
+    }
+
+    /// Binance symbol for `symbol`, e.g. `BTCUSDT`
+    fn venue_symbol(&self, symbol: &Symbol) -> String {
+        self.symbols
+            .get(symbol)
+            .and_then(|info| info.venues.get("binance").cloned())
+            .unwrap_or_else(|| format!("{}{}", symbol.base(), symbol.quote()))
+    }
+
+    /// Internal symbol for a Binance symbol
+    fn internal_symbol(&self, venue_symbol: &str) -> Option<Symbol> {
+        if let Some(info) = self
+            .symbols
+            .all()
+            .into_iter()
+            .find(|info| info.venues.get("binance").map(String::as_str) == Some(venue_symbol))
+        {
+            return Some(info.symbol);
+        }
+        QUOTE_ASSETS.iter().find_map(|quote| {
+            let base = venue_symbol.strip_suffix(quote)?;
+            (!base.is_empty()).then(|| Symbol::new(base, quote))
+        })
+    }
+
+    /// Create a user-data listenKey
+    async fn create_listen_key(&self) -> ExchangeResult<String> {
+        #[derive(Deserialize)]
+        struct ListenKey {
+            #[serde(rename = "listenKey")]
+            listen_key: String,
+        }
+
+        let response = self
+            .client
+            .post(format!("{}/api/v3/userDataStream", self.rest_url))
+            .header("X-MBX-APIKEY", &self.api_key)
+            .send()
+            .await
+            .map_err(|e| ExchangeError::ConnectionFailed(e.to_string()))?;
+        let key: ListenKey = read_response(response).await?;
+        Ok(key.listen_key)
+    }
+
+    /// Extend a listenKey's validity by 60 minutes
+    async fn keepalive(&self, listen_key: &str) -> ExchangeResult<()> {
+        let response = self
+            .client
+            .put(format!(
+                "{}/api/v3/userDataStream?listenKey={listen_key}",
+                self.rest_url
+            ))
+            .header("X-MBX-APIKEY", &self.api_key)
+            .send()
+            .await
+            .map_err(|e| ExchangeError::ConnectionFailed(e.to_string()))?;
+        let _: serde_json::Value = read_response(response).await?;
+        Ok(())
+    }
+
+    /// Publish fills from the user-data stream until the task is dropped
+    pub async fn run_user_data(&self) -> Result<()> {
+        loop {
+            match self.user_data_session().await {
+                Ok(()) => info!("Binance user-data stream closed, reconnecting"),
+                Err(e) => warn!("Binance user-data stream failed: {}", e),
+            }
+            metrics::counter!("binance_stream_reconnects", "stream" => "user_data").increment(1);
+            time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    /// One user-data connection, until it closes or its listenKey expires
+    async fn user_data_session(&self) -> Result<()> {
+        let listen_key = self.create_listen_key().await?;
+        let (socket, _) =
+            tokio_tungstenite::connect_async(format!("{}/ws/{listen_key}", self.ws_url)).await?;
+        let (mut write, mut read) = socket.split();
+        let mut keepalive =
+            time::interval_at(Instant::now() + KEEPALIVE_INTERVAL, KEEPALIVE_INTERVAL);
+        info!("Binance user-data stream connected");
+
+        loop {
+            tokio::select! {
+                _ = keepalive.tick() => {
+                    self.keepalive(&listen_key).await?;
+                    debug!("Binance listenKey kept alive");
+                }
+                message = read.next() => {
+                    let text = match message {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(Message::Ping(payload))) => {
+                            write.send(Message::Pong(payload)).await?;
+                            continue;
+                        }
+                        Some(Ok(Message::Close(_))) | None => return Ok(()),
+                        Some(Ok(_)) => continue,
+                        Some(Err(e)) => return Err(e.into()),
+                    };
+
+                    match serde_json::from_str::<UserDataEvent>(&text) {
+                        Ok(UserDataEvent::ExecutionReport(report)) => {
+                            if let Some(fill) = self.fill(&report) {
+                                self.publish_fill(fill).await;
+                            }
+                        }
+                        Ok(UserDataEvent::ListenKeyExpired) => {
+                            info!("Binance listenKey expired");
+                            return Ok(());
+                        }
+                        Ok(UserDataEvent::Other) => {}
+                        Err(e) => debug!("Unreadable Binance user-data event: {}", e),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fill carried by an execution report, if it reports a trade
+    fn fill(&self, report: &ExecutionReport) -> Option<VenueFill> {
+        if report.execution_type != "TRADE" {
+            return None;
+        }
+        let Some(symbol) = self.internal_symbol(&report.symbol) else {
+            warn!(symbol = %report.symbol, "Fill for unknown Binance symbol");
+            return None;
+        };
+
+        Some(VenueFill {
+            exchange: "binance".to_string(),
+            symbol,
+            exchange_order_id: report.order_id.to_string(),
+            client_order_id: report.client_order_id.clone(),
+            trade_id: report.trade_id.to_string(),
+            side: if report.side == "BUY" {
+                Side::Buy
+            } else {
+                Side::Sell
+            },
+            price: report.last_price.parse().ok()?,
+            quantity: report.last_quantity.parse().ok()?,
+            cumulative_quantity: report.cumulative_quantity.parse().ok()?,
+            fee: report.commission.parse().unwrap_or_default(),
+            fee_asset: report.commission_asset.clone(),
+            is_maker: report.is_maker,
+            executed_at: Utc.timestamp_millis_opt(report.transaction_time).single()?,
+        })
+    }
+
+    async fn publish_fill(&self, fill: VenueFill) {
+        if let Some(fill_check) = &self.fill_check {
+            fill_check.check(&fill).await;
+        }
+        let order_id = fill.exchange_order_id.clone();
+        match self.publisher.publish(fill).await {
+            Ok(()) => metrics::counter!("binance_stream_fills").increment(1),
+            Err(e) => warn!(order_id = %order_id, "Failed to publish Binance fill: {}", e),
+        }
+    }
+
+    /// Publish tickers of `markets` until the task is dropped
+    pub async fn run_market(&self, markets: Vec<Symbol>) -> Result<()> {
+        if markets.is_empty() {
+            return Ok(());
+        }
+        loop {
+            match self.market_session(&markets).await {
+                Ok(()) => info!("Binance market stream closed, reconnecting"),
+                Err(e) => warn!("Binance market stream failed: {}", e),
+            }
+            metrics::counter!("binance_stream_reconnects", "stream" => "market").increment(1);
+            time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    /// One combined trade and bookTicker connection
+    async fn market_session(&self, markets: &[Symbol]) -> Result<()> {
+        let venue_symbols: HashMap<String, Symbol> = markets
+            .iter()
+            .map(|symbol| (self.venue_symbol(symbol), symbol.clone()))
+            .collect();
+        let streams: Vec<String> = venue_symbols
+            .keys()
+            .flat_map(|s| {
+                let s = s.to_lowercase();
+                [format!("{s}@trade"), format!("{s}@bookTicker")]
+            })
+            .collect();
+
+        let (socket, _) = tokio_tungstenite::connect_async(format!(
+            "{}/stream?streams={}",
+            self.ws_url,
+            streams.join("/")
+        ))
+        .await?;
+        let (mut write, mut read) = socket.split();
+        let mut tickers = Tickers::new(venue_symbols);
+        info!(symbols = markets.len(), "Binance market stream connected");
+
+        while let Some(message) = read.next().await {
+            let text = match message? {
+                Message::Text(text) => text,
+                Message::Ping(payload) => {
+                    write.send(Message::Pong(payload)).await?;
+                    continue;
+                }
+                Message::Close(_) => break,
+                _ => continue,
+            };
+
+            let Some(ticker) = tickers.apply(&text) else {
+                continue;
+            };
+            if let Err(e) = self.publisher.publish(ticker).await {
+                warn!("Failed to publish Binance ticker: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Latest ticker per symbol, built from trade and bookTicker messages
+struct Tickers {
+    venue_symbols: HashMap<String, Symbol>,
+    latest: HashMap<String, VenueTicker>,
+}
+
+impl Tickers {
+    fn new(venue_symbols: HashMap<String, Symbol>) -> Self {
+        Self {
+            venue_symbols,
+            latest: HashMap::new(),
+        }
+    }
+
+    /// Apply a combined-stream message, returning the updated ticker
+    fn apply(&mut self, text: &str) -> Option<VenueTicker> {
+        let combined: Combined = serde_json::from_str(text).ok()?;
+        let parse = |s: &str| s.parse::<Decimal>().ok();
+
+        if combined.stream.ends_with("@bookTicker") {
+            let book: BookTicker = serde_json::from_value(combined.data).ok()?;
+            let ticker = self.ticker(&book.symbol)?;
+            ticker.bid = parse(&book.bid);
+            ticker.bid_quantity = parse(&book.bid_quantity);
+            ticker.ask = parse(&book.ask);
+            ticker.ask_quantity = parse(&book.ask_quantity);
+            ticker.timestamp = Utc::now();
+            Some(ticker.clone())
+        } else if combined.stream.ends_with("@trade") {
+            let trade: MarketTrade = serde_json::from_value(combined.data).ok()?;
+            let ticker = self.ticker(&trade.symbol)?;
+            ticker.last_price = parse(&trade.price);
+            ticker.timestamp = Utc.timestamp_millis_opt(trade.trade_time).single()?;
+            Some(ticker.clone())
+        } else {
+            None
+        }
+    }
+
+    fn ticker(&mut self, venue_symbol: &str) -> Option<&mut VenueTicker> {
+        let symbol = self.venue_symbols.get(venue_symbol)?.clone();
+        Some(
+            self.latest
+                .entry(venue_symbol.to_string())
+                .or_insert_with(|| VenueTicker {
+                    exchange: "binance".to_string(),
+                    symbol,
+                    bid: None,
+                    bid_quantity: None,
+                    ask: None,
+                    ask_quantity: None,
+                    last_price: None,
+                    timestamp: Utc::now(),
+                }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::conformance::MockVenue;
+    use crate::config::Config;
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    fn publisher() -> Arc<EventPublisher> {
+        let config: Config = serde_json::from_value(json!({
+            "eth_rpc_url": "http://localhost:8545",
+            "kafka_brokers": "localhost:9092",
+        }))
+        .unwrap();
+        Arc::new(EventPublisher::new(&config).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_listen_key_and_fills() {
+        let venue = MockVenue::start().await;
+        venue
+            .mock(
+                Method::POST,
+                "/api/v3/userDataStream",
+                StatusCode::OK,
+                json!({ "listenKey": "abc123" }),
+            )
+            .await;
+        venue
+            .mock(
+                Method::PUT,
+                "/api/v3/userDataStream",
+                StatusCode::OK,
+                json!({}),
+            )
+            .await;
+        let streams = BinanceStreams::new(
+            "key".to_string(),
+            Arc::new(SymbolRegistry::new()),
+            publisher(),
+        )
+        .with_urls(venue.url(), "ws://localhost");
+
+        assert_eq!(streams.create_listen_key().await.unwrap(), "abc123");
+        streams.keepalive("abc123").await.unwrap();
+        let sent = venue
+            .last_request(&Method::PUT, "/api/v3/userDataStream")
+            .await
+            .unwrap();
+        assert_eq!(sent.params()["listenKey"], "abc123");
+        assert_eq!(sent.headers["X-MBX-APIKEY"], "key");
+
+        let event = |execution_type: &str| {
+            json!({
+                "e": "executionReport", "E": 1700000000001i64, "s": "ETHUSDT",
+                "c": "c1", "S": "SELL", "o": "LIMIT", "x": execution_type,
+                "X": "PARTIALLY_FILLED", "i": 42, "l": "0.5", "L": "2000.10",
+                "z": "0.75", "n": "1.0005", "N": "USDT", "T": 1700000000000i64,
+                "t": 7, "m": true
+            })
+            .to_string()
+        };
+        let UserDataEvent::ExecutionReport(report) = serde_json::from_str(&event("TRADE")).unwrap()
+        else {
+            panic!("expected an execution report");
+        };
+        let fill = streams.fill(&report).unwrap();
+        assert_eq!(fill.symbol, Symbol::new("ETH", "USDT"));
+        assert_eq!((fill.side, fill.is_maker), (Side::Sell, true));
+        assert_eq!(fill.price, Decimal::new(200010, 2));
+        assert_eq!(fill.cumulative_quantity, Decimal::new(75, 2));
+        assert_eq!(fill.exchange_order_id, "42");
+
+        // New, cancel and other reports carry no fill
+        let UserDataEvent::ExecutionReport(report) = serde_json::from_str(&event("NEW")).unwrap()
+        else {
+            panic!("expected an execution report");
+        };
+        assert!(streams.fill(&report).is_none());
+
+        assert!(matches!(
+            serde_json::from_str(r#"{"e":"listenKeyExpired","E":1,"listenKey":"abc123"}"#),
+            Ok(UserDataEvent::ListenKeyExpired)
+        ));
+        assert!(matches!(
+            serde_json::from_str(r#"{"e":"outboundAccountPosition","E":1}"#),
+            Ok(UserDataEvent::Other)
+        ));
+    }
+
+    #[test]
+    fn test_tickers_combine_trades_and_book() {
+        let mut tickers = Tickers::new(HashMap::from([(
+            "BTCUSDT".to_string(),
+            Symbol::new("BTC", "USDT"),
+        )]));
+
+        let book = json!({
+            "stream": "btcusdt@bookTicker",
+            "data": { "u": 1, "s": "BTCUSDT", "b": "50000.00", "B": "1.5", "a": "50001.00", "A": "2" }
+        });
+        let ticker = tickers.apply(&book.to_string()).unwrap();
+        assert_eq!(ticker.bid, Some(Decimal::new(50000, 0)));
+        assert_eq!(ticker.last_price, None);
+
+        let trade = json!({
+            "stream": "btcusdt@trade",
+            "data": { "e": "trade", "E": 2, "s": "BTCUSDT", "t": 9, "p": "50000.50", "q": "0.1", "T": 1700000000000i64, "m": false }
+        });
+        let ticker = tickers.apply(&trade.to_string()).unwrap();
+        assert_eq!(ticker.last_price, Some(Decimal::new(5000050, 2)));
+        // Book fields are kept from the earlier update
+        assert_eq!(ticker.ask, Some(Decimal::new(50001, 0)));
+
+        let other =
+            json!({ "stream": "ethusdt@trade", "data": { "s": "ETHUSDT", "p": "1", "T": 1 } });
+        assert!(tickers.apply(&other.to_string()).is_none());
+    }
+}
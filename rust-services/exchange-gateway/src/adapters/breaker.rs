@@ -0,0 +1,361 @@
+//! Venue Circuit Breaker
+//!
+//! A venue that is down answers every request with a connection failure or
+//! a server error, each after a timeout and the adapter's retries. After
+//! `threshold` such failures in a row the venue's breaker opens: requests
+//! fail at once and the router sends orders elsewhere. Once `cooldown` has
+//! passed the breaker half-opens and lets a single probe request through;
+//! a response closes it again, and another failure reopens it for a
+//! further cooldown.
+//!
+//! Only `ConnectionFailed` and `ApiError` count as failures. Rejections
+//! and rate limits mean the venue answered, so they count as successes.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tracing::{info, warn};
+
+use super::traits::*;
+use common::{Candle, ExchangeError, MarketData, Order, Symbol, SymbolInfo, Trade};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    /// Requests flow normally
+    Closed,
+    /// Requests fail fast until the cooldown ends
+    Open,
+    /// One probe request is allowed to test the venue
+    HalfOpen,
+}
+
+impl BreakerState {
+    fn gauge(&self) -> f64 {
+        match self {
+            Self::Closed => 0.0,
+            Self::HalfOpen => 1.0,
+            Self::Open => 2.0,
+        }
+    }
+}
+
+/// Breaker state for `/exchanges/:name/status`
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakerStatus {
+    pub state: BreakerState,
+    pub consecutive_failures: u32,
+    /// Milliseconds until an open breaker half-opens
+    pub retry_in_ms: Option<u64>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: BreakerState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// A half-open probe is in flight
+    probing: bool,
+}
+
+pub struct CircuitBreaker {
+    venue: &'static str,
+    threshold: u32,
+    cooldown: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(venue: &'static str, threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            venue,
+            threshold: threshold.max(1),
+            cooldown,
+            inner: Mutex::new(Inner {
+                state: BreakerState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                probing: false,
+            }),
+        }
+    }
+
+    fn set_state(&self, inner: &mut Inner, state: BreakerState) {
+        inner.state = state;
+        metrics::gauge!("exchange_breaker_state", "exchange" => self.venue).set(state.gauge());
+    }
+
+    fn cooled_down(&self, inner: &Inner, now: Instant) -> bool {
+        inner
+            .opened_at
+            .is_some_and(|opened| now.saturating_duration_since(opened) >= self.cooldown)
+    }
+
+    /// Whether a request may go to the venue now
+    ///
+    /// Admitting a request after the cooldown makes it the half-open probe.
+    pub fn allow(&self, now: Instant) -> bool {
+        let mut inner = self.inner.lock();
+        match inner.state {
+            BreakerState::Closed => true,
+            BreakerState::Open if self.cooled_down(&inner, now) => {
+                self.set_state(&mut inner, BreakerState::HalfOpen);
+                inner.probing = true;
+                info!(
+                    exchange = self.venue,
+                    "Circuit breaker half-open, probing venue"
+                );
+                true
+            }
+            BreakerState::Open => false,
+            BreakerState::HalfOpen if inner.probing => false,
+            BreakerState::HalfOpen => {
+                inner.probing = true;
+                true
+            }
+        }
+    }
+
+    /// Whether routing should avoid the venue
+    ///
+    /// False once the cooldown has passed, so the next routed request can
+    /// serve as the probe.
+    pub fn is_open(&self, now: Instant) -> bool {
+        let inner = self.inner.lock();
+        match inner.state {
+            BreakerState::Closed => false,
+            BreakerState::Open => !self.cooled_down(&inner, now),
+            BreakerState::HalfOpen => inner.probing,
+        }
+    }
+
+    /// Record the outcome of an admitted request
+    pub fn record<T>(&self, result: &ExchangeResult<T>, now: Instant) {
+        let failed = matches!(
+            result,
+            Err(ExchangeError::ConnectionFailed(_) | ExchangeError::ApiError { .. })
+        );
+        let mut inner = self.inner.lock();
+        inner.probing = false;
+
+        if !failed {
+            if inner.state != BreakerState::Closed {
+                info!(exchange = self.venue, "Circuit breaker closed");
+                self.set_state(&mut inner, BreakerState::Closed);
+            }
+            inner.consecutive_failures = 0;
+            inner.opened_at = None;
+            return;
+        }
+
+        inner.consecutive_failures += 1;
+        let trips = match inner.state {
+            BreakerState::Closed => inner.consecutive_failures >= self.threshold,
+            BreakerState::HalfOpen => true,
+            BreakerState::Open => false,
+        };
+        if trips {
+            warn!(
+                exchange = self.venue,
+                failures = inner.consecutive_failures,
+                cooldown_secs = self.cooldown.as_secs(),
+                "Circuit breaker open"
+            );
+            metrics::counter!("exchange_breaker_trips", "exchange" => self.venue).increment(1);
+            self.set_state(&mut inner, BreakerState::Open);
+            inner.opened_at = Some(now);
+        }
+    }
+
+    pub fn status(&self, now: Instant) -> BreakerStatus {
+        let inner = self.inner.lock();
+        BreakerStatus {
+            state: inner.state,
+            consecutive_failures: inner.consecutive_failures,
+            retry_in_ms: match (inner.state, inner.opened_at) {
+                (BreakerState::Open, Some(opened)) => Some(
+                    self.cooldown
+                        .saturating_sub(now.saturating_duration_since(opened))
+                        .as_millis() as u64,
+                ),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Adapter whose requests pass through its venue's circuit breaker
+pub struct Guarded<A> {
+    breaker: Arc<CircuitBreaker>,
+    inner: A,
+}
+
+impl<A> Guarded<A> {
+    pub fn new(breaker: Arc<CircuitBreaker>, inner: A) -> Self {
+        Self { breaker, inner }
+    }
+
+    async fn call<T>(
+        &self,
+        request: impl std::future::Future<Output = ExchangeResult<T>>,
+    ) -> ExchangeResult<T> {
+        if !self.breaker.allow(Instant::now()) {
+            return Err(ExchangeError::ConnectionFailed(format!(
+                "{} circuit breaker open",
+                self.breaker.venue
+            )));
+        }
+        let result = request.await;
+        self.breaker.record(&result, Instant::now());
+        result
+    }
+}
+
+#[async_trait]
+impl<A: ExchangeAdapter> ExchangeAdapter for Guarded<A> {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    async fn is_available(&self) -> bool {
+        self.inner.is_available().await
+    }
+
+    async fn sync_clock(&self) -> ExchangeResult<Option<i64>> {
+        self.call(self.inner.sync_clock()).await
+    }
+
+    async fn get_symbols(&self) -> ExchangeResult<Vec<Symbol>> {
+        self.call(self.inner.get_symbols()).await
+    }
+
+    async fn get_symbol_info(&self) -> ExchangeResult<Vec<SymbolInfo>> {
+        self.call(self.inner.get_symbol_info()).await
+    }
+
+    async fn get_market_data(&self, symbol: &Symbol) -> ExchangeResult<MarketData> {
+        self.call(self.inner.get_market_data(symbol)).await
+    }
+
+    async fn get_balances(&self) -> ExchangeResult<Vec<ExchangeBalance>> {
+        self.call(self.inner.get_balances()).await
+    }
+
+    async fn place_order(&self, order: &Order) -> ExchangeResult<ExchangeOrder> {
+        self.call(self.inner.place_order(order)).await
+    }
+
+    async fn replace_order(&self, order_id: &str, order: &Order) -> ExchangeResult<ExchangeOrder> {
+        self.call(self.inner.replace_order(order_id, order)).await
+    }
+
+    async fn cancel_order(&self, symbol: &Symbol, order_id: &str) -> ExchangeResult<()> {
+        self.call(self.inner.cancel_order(symbol, order_id)).await
+    }
+
+    async fn get_order(&self, symbol: &Symbol, order_id: &str) -> ExchangeResult<ExchangeOrder> {
+        self.call(self.inner.get_order(symbol, order_id)).await
+    }
+
+    async fn get_trades(&self, symbol: &Symbol, limit: u32) -> ExchangeResult<Vec<Trade>> {
+        self.call(self.inner.get_trades(symbol, limit)).await
+    }
+
+    async fn get_depth(&self, symbol: &Symbol, limit: u32) -> ExchangeResult<OrderBookDepth> {
+        self.call(self.inner.get_depth(symbol, limit)).await
+    }
+
+    async fn get_klines(
+        &self,
+        symbol: &Symbol,
+        interval: &str,
+        start: DateTime<Utc>,
+        limit: u32,
+    ) -> ExchangeResult<Vec<Candle>> {
+        self.call(self.inner.get_klines(symbol, interval, start, limit))
+            .await
+    }
+}
+
+#[async_trait]
+impl<A: RateSource> RateSource for Guarded<A> {
+    async fn get_funding_rates(
+        &self,
+        symbol: &Symbol,
+        since: DateTime<Utc>,
+    ) -> ExchangeResult<Vec<FundingRate>> {
+        self.call(self.inner.get_funding_rates(symbol, since)).await
+    }
+
+    async fn get_borrow_rates(
+        &self,
+        asset: &str,
+        since: DateTime<Utc>,
+    ) -> ExchangeResult<Vec<BorrowRate>> {
+        self.call(self.inner.get_borrow_rates(asset, since)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure() -> ExchangeResult<()> {
+        Err(ExchangeError::ConnectionFailed("timeout".to_string()))
+    }
+
+    #[test]
+    fn test_trips_after_consecutive_failures_only() {
+        let breaker = CircuitBreaker::new("test", 3, Duration::from_secs(30));
+        let now = Instant::now();
+
+        breaker.record(&failure(), now);
+        breaker.record(&failure(), now);
+        breaker.record(&Ok(()), now);
+        breaker.record(&failure(), now);
+        breaker.record(&failure(), now);
+        assert!(breaker.allow(now));
+
+        // Rejections mean the venue is up
+        breaker.record::<()>(&Err(ExchangeError::OrderRejected("size".to_string())), now);
+        assert_eq!(breaker.status(now).consecutive_failures, 0);
+
+        for _ in 0..3 {
+            breaker.record(&failure(), now);
+        }
+        assert_eq!(breaker.status(now).state, BreakerState::Open);
+        assert!(breaker.is_open(now));
+        assert!(!breaker.allow(now));
+        assert_eq!(breaker.status(now).retry_in_ms, Some(30_000));
+    }
+
+    #[test]
+    fn test_half_open_admits_one_probe() {
+        let breaker = CircuitBreaker::new("test", 1, Duration::from_secs(30));
+        let now = Instant::now();
+        breaker.record(&failure(), now);
+
+        let later = now + Duration::from_secs(30);
+        assert!(!breaker.is_open(later));
+        assert!(breaker.allow(later));
+        assert_eq!(breaker.status(later).state, BreakerState::HalfOpen);
+        // Only the one probe
+        assert!(!breaker.allow(later));
+        assert!(breaker.is_open(later));
+
+        // A failed probe reopens for another cooldown
+        breaker.record(&failure(), later);
+        assert_eq!(breaker.status(later).state, BreakerState::Open);
+        assert!(!breaker.allow(later + Duration::from_secs(29)));
+
+        let much_later = later + Duration::from_secs(30);
+        assert!(breaker.allow(much_later));
+        breaker.record(&Ok(()), much_later);
+        assert_eq!(breaker.status(much_later).state, BreakerState::Closed);
+        assert!(breaker.allow(much_later));
+    }
+}
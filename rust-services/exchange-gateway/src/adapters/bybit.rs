@@ -0,0 +1,938 @@
+//! Bybit Exchange Adapter
+//!
+//! Integration with Bybit v5 unified API for spot trading. Private
+//! requests are signed over timestamp, API key, receive window and the
+//! query string or JSON body.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use rust_decimal::Decimal;
+use sha2::Sha256;
+use tracing::{debug, info, warn};
+
+use super::clock::VenueClock;
+use super::retry::{Recovery, RetryPolicy};
+use super::traits::*;
+use common::{
+    ExchangeError, MarketData, Order, OrderStatus, Side, Symbol, SymbolInfo, SymbolRegistry,
+    SymbolStatus, Trade,
+};
+
+const BYBIT_API_URL: &str = "https://api.bybit.com";
+
+/// Milliseconds a signed request stays valid after its timestamp
+const RECV_WINDOW_MS: &str = "5000";
+
+/// Spot instrument from `/v5/market/instruments-info`
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BybitInstrument {
+    symbol: String,
+    base_coin: String,
+    quote_coin: String,
+    status: String,
+    lot_size_filter: LotSizeFilter,
+    price_filter: PriceFilter,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LotSizeFilter {
+    base_precision: String,
+    #[serde(default)]
+    min_order_qty: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PriceFilter {
+    tick_size: String,
+}
+
+impl BybitInstrument {
+    fn symbol(&self) -> Symbol {
+        Symbol::new(&self.base_coin, &self.quote_coin)
+    }
+
+    fn to_symbol_info(&self) -> SymbolInfo {
+        let tick_size = increment(&self.price_filter.tick_size);
+        let lot_size = increment(&self.lot_size_filter.base_precision);
+
+        SymbolInfo {
+            symbol: self.symbol(),
+            base_asset: self.base_coin.clone(),
+            quote_asset: self.quote_coin.clone(),
+            status: match self.status.as_str() {
+                "Trading" => SymbolStatus::Trading,
+                "PreLaunch" | "Delivering" => SymbolStatus::Halted,
+                "Closed" => SymbolStatus::Delisted,
+                _ => SymbolStatus::Unknown,
+            },
+            price_precision: tick_size.map(|t| t.scale()).unwrap_or(8),
+            quantity_precision: lot_size.map(|l| l.scale()).unwrap_or(8),
+            tick_size,
+            lot_size,
+            min_quantity: increment(&self.lot_size_filter.min_order_qty),
+            venues: HashMap::from([("bybit".to_string(), self.symbol.clone())]),
+        }
+    }
+}
+
+/// Order status from Bybit's `orderStatus`
+fn order_status(status: &str) -> OrderStatus {
+    match status {
+        "Created" | "Untriggered" => OrderStatus::Pending,
+        "PartiallyFilled" => OrderStatus::PartiallyFilled,
+        "Filled" => OrderStatus::Filled,
+        "Cancelled" | "PartiallyFilledCanceled" | "Deactivated" => OrderStatus::Cancelled,
+        "Rejected" => OrderStatus::Rejected,
+        // New, Triggered and anything unknown are still working
+        _ => OrderStatus::Open,
+    }
+}
+
+/// Non-zero increment such as a tick size
+fn increment(value: &str) -> Option<Decimal> {
+    value
+        .parse::<Decimal>()
+        .ok()
+        .filter(|v| !v.is_zero())
+        .map(|v| v.normalize())
+}
+
+/// Order from `/v5/order/realtime` and `/v5/order/history`
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BybitOrder {
+    order_id: String,
+    #[serde(default)]
+    order_link_id: String,
+    order_status: String,
+    #[serde(default)]
+    cum_exec_qty: String,
+    #[serde(default)]
+    avg_price: String,
+}
+
+/// Paged `result` of list endpoints
+#[derive(serde::Deserialize)]
+struct List<T> {
+    list: Vec<T>,
+}
+
+pub struct BybitAdapter {
+    client: Client,
+    api_key: String,
+    api_secret: String,
+
+    /// API root, overridable for testing
+    base_url: String,
+
+    /// Shared reference data for venue symbol mapping
+    symbols: Arc<SymbolRegistry>,
+
+    /// Retries for transient venue errors
+    retry: RetryPolicy,
+
+    /// Venue clock for signed request timestamps, shared across accounts
+    clock: Arc<VenueClock>,
+}
+
+impl BybitAdapter {
+    pub fn new(api_key: String, api_secret: String, symbols: Arc<SymbolRegistry>) -> Self {
+        Self {
+            client: Client::new(),
+            api_key,
+            api_secret,
+            base_url: BYBIT_API_URL.to_string(),
+            symbols,
+            retry: RetryPolicy::default(),
+            clock: Arc::new(VenueClock::new()),
+        }
+    }
+
+    /// Share a venue clock with other Bybit adapters
+    pub fn with_clock(mut self, clock: Arc<VenueClock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Point requests at another API root, e.g. the testnet
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Bybit symbol for `symbol`, e.g. `BTCUSDT`
+    fn venue_symbol(&self, symbol: &Symbol) -> String {
+        self.symbols
+            .get(symbol)
+            .and_then(|info| info.venues.get("bybit").cloned())
+            .unwrap_or_else(|| format!("{}{}", symbol.base(), symbol.quote()))
+    }
+
+    /// `X-BAPI-SIGN`: hex HMAC-SHA256 of timestamp, API key, receive
+    /// window and the query string or body
+    fn sign(&self, timestamp: &str, payload: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.api_secret.as_bytes())
+            .expect("HMAC can take key of any size");
+        mac.update(format!("{timestamp}{}{RECV_WINDOW_MS}{payload}", self.api_key).as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    async fn instruments(&self) -> ExchangeResult<Vec<BybitInstrument>> {
+        let instruments: List<BybitInstrument> = self
+            .public_request("/v5/market/instruments-info?category=spot")
+            .await?;
+        Ok(instruments.list)
+    }
+
+    async fn public_request<T: serde::de::DeserializeOwned>(
+        &self,
+        endpoint: &str,
+    ) -> ExchangeResult<T> {
+        let url = format!("{}{endpoint}", self.base_url);
+        self.execute(|| self.client.get(&url)).await
+    }
+
+    /// Signed GET with `query` as its query string
+    async fn signed_get<T: serde::de::DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        query: &str,
+    ) -> ExchangeResult<T> {
+        let url = format!("{}{endpoint}?{query}", self.base_url);
+        self.execute(|| self.signed(self.client.get(&url), query))
+            .await
+    }
+
+    /// Signed POST with a JSON body
+    async fn signed_post<T: serde::de::DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        body: &serde_json::Value,
+    ) -> ExchangeResult<T> {
+        let url = format!("{}{endpoint}", self.base_url);
+        let body = body.to_string();
+        self.execute(|| {
+            self.signed(self.client.post(&url), &body)
+                .header("Content-Type", "application/json")
+                .body(body.clone())
+        })
+        .await
+    }
+
+    /// Add auth headers; timestamp and signature are renewed on every attempt
+    fn signed(&self, request: reqwest::RequestBuilder, payload: &str) -> reqwest::RequestBuilder {
+        let timestamp = self.clock.now_ms().to_string();
+        let signature = self.sign(&timestamp, payload);
+        request
+            .header("X-BAPI-API-KEY", &self.api_key)
+            .header("X-BAPI-SIGN", signature)
+            .header("X-BAPI-TIMESTAMP", timestamp)
+            .header("X-BAPI-RECV-WINDOW", RECV_WINDOW_MS)
+    }
+
+    /// Send a request, retrying transient failures under the retry policy
+    async fn execute<T: serde::de::DeserializeOwned>(
+        &self,
+        request: impl Fn() -> reqwest::RequestBuilder,
+    ) -> ExchangeResult<T> {
+        let mut attempt = 1;
+        loop {
+            let (error, recovery) = match request().send().await {
+                Ok(response) => {
+                    let retry_after = rate_limit_reset(&response);
+                    match read_response(response).await {
+                        Ok(value) => return Ok(value),
+                        Err(error) => {
+                            let recovery = recovery(&error, retry_after);
+                            (error, recovery)
+                        }
+                    }
+                }
+                // The request never reached the venue
+                Err(e) if e.is_connect() => (
+                    ExchangeError::ConnectionFailed(e.to_string()),
+                    Recovery::Backoff(None),
+                ),
+                Err(e) => return Err(ExchangeError::ConnectionFailed(e.to_string())),
+            };
+
+            if recovery == Recovery::Fail || !self.retry.allows_retry(attempt) {
+                return Err(error);
+            }
+
+            warn!(attempt, error = %error, reason = recovery.reason(), "Retrying Bybit request");
+            metrics::counter!(
+                "exchange_retries",
+                "exchange" => "bybit",
+                "reason" => recovery.reason()
+            )
+            .increment(1);
+
+            match recovery {
+                Recovery::Backoff(hint) => {
+                    tokio::time::sleep(self.retry.delay(attempt, hint)).await;
+                }
+                Recovery::ResyncClock => {
+                    if let Err(e) = self.resync_clock().await {
+                        warn!("Bybit clock resync failed: {}", e);
+                        return Err(error);
+                    }
+                }
+                Recovery::Fail => unreachable!("permanent errors are returned above"),
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Measure the venue clock offset from `/v5/market/time`
+    async fn resync_clock(&self) -> ExchangeResult<i64> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ServerTime {
+            time_nano: String,
+        }
+
+        let sent_at = Utc::now().timestamp_millis();
+        let response = self
+            .client
+            .get(format!("{}/v5/market/time", self.base_url))
+            .send()
+            .await
+            .map_err(|e| ExchangeError::ConnectionFailed(e.to_string()))?;
+        let time: ServerTime = read_response(response).await?;
+        let received_at = Utc::now().timestamp_millis();
+
+        let server_time = time
+            .time_nano
+            .parse::<i64>()
+            .map(|nanos| nanos / 1_000_000)
+            .map_err(|e| ExchangeError::ApiError {
+                code: -1,
+                message: e.to_string(),
+            })?;
+        let offset = self.clock.observe(server_time, sent_at, received_at);
+        debug!(offset_ms = offset, "Resynced Bybit clock");
+        Ok(offset)
+    }
+
+    /// Look an order up among open orders, then in order history
+    async fn find_order(&self, venue_symbol: &str, order_id: &str) -> ExchangeResult<BybitOrder> {
+        let query = format!("category=spot&symbol={venue_symbol}&orderId={order_id}");
+        for endpoint in ["/v5/order/realtime", "/v5/order/history"] {
+            let orders: List<BybitOrder> = self.signed_get(endpoint, &query).await?;
+            if let Some(order) = orders.list.into_iter().next() {
+                return Ok(order);
+            }
+        }
+        Err(ExchangeError::ApiError {
+            code: -1,
+            message: format!("Unknown order {order_id}"),
+        })
+    }
+}
+
+/// Recovery for a failed Bybit request
+///
+/// 10006 is Bybit's per-endpoint rate limit and 10002 a timestamp outside
+/// the receive window. Other errors, including 5xx whose outcome is
+/// unknown, are final.
+fn recovery(error: &ExchangeError, retry_after: Option<Duration>) -> Recovery {
+    match error {
+        ExchangeError::ApiError { code: 10002, .. } => Recovery::ResyncClock,
+        ExchangeError::RateLimited => Recovery::Backoff(retry_after),
+        _ => Recovery::Fail,
+    }
+}
+
+/// Wait until `X-Bapi-Limit-Reset-Timestamp`, sent with rate limit errors
+fn rate_limit_reset(response: &reqwest::Response) -> Option<Duration> {
+    let reset_ms: i64 = response
+        .headers()
+        .get("X-Bapi-Limit-Reset-Timestamp")?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()?;
+    let wait = reset_ms - Utc::now().timestamp_millis();
+    (wait > 0).then(|| Duration::from_millis(wait as u64))
+}
+
+/// Map a Bybit `retCode` onto `ExchangeError`
+fn map_error(code: i32, message: String) -> ExchangeError {
+    match code {
+        10006 | 10018 => ExchangeError::RateLimited,
+        10003 | 10004 | 10005 | 10007 | 10009 | 10010 | 33004 => {
+            ExchangeError::AuthenticationFailed(message)
+        }
+        110000..=110999 | 170000..=170999 => ExchangeError::OrderRejected(message),
+        code => ExchangeError::ApiError { code, message },
+    }
+}
+
+/// Decode a Bybit response, mapping failures onto `ExchangeError`
+///
+/// Bybit answers most errors with HTTP 200 and a non-zero `retCode`; an
+/// HTTP 403 means the IP exceeded its request limit.
+async fn read_response<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+) -> ExchangeResult<T> {
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct BybitResponse {
+        ret_code: i32,
+        #[serde(default)]
+        ret_msg: String,
+        #[serde(default)]
+        result: serde_json::Value,
+    }
+
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    let body = serde_json::from_str::<BybitResponse>(&text).ok();
+
+    if !status.is_success() {
+        return Err(match (status.as_u16(), body) {
+            (429 | 403, _) => ExchangeError::RateLimited,
+            (_, Some(body)) if body.ret_code != 0 => map_error(body.ret_code, body.ret_msg),
+            (401, _) => ExchangeError::AuthenticationFailed(text),
+            (status, _) => ExchangeError::ApiError {
+                code: status as i32,
+                message: text,
+            },
+        });
+    }
+
+    let body = body.ok_or_else(|| ExchangeError::ApiError {
+        code: -1,
+        message: text.clone(),
+    })?;
+    if body.ret_code != 0 {
+        return Err(map_error(body.ret_code, body.ret_msg));
+    }
+
+    serde_json::from_value(body.result).map_err(|e| ExchangeError::ApiError {
+        code: -1,
+        message: e.to_string(),
+    })
+}
+
+#[async_trait]
+impl ExchangeAdapter for BybitAdapter {
+    fn name(&self) -> &'static str {
+        "Bybit"
+    }
+
+    async fn is_available(&self) -> bool {
+        self.public_request::<serde_json::Value>("/v5/market/time")
+            .await
+            .is_ok()
+    }
+
+    async fn sync_clock(&self) -> ExchangeResult<Option<i64>> {
+        self.resync_clock().await.map(Some)
+    }
+
+    async fn get_symbols(&self) -> ExchangeResult<Vec<Symbol>> {
+        Ok(self
+            .instruments()
+            .await?
+            .iter()
+            .filter(|i| i.status == "Trading")
+            .map(BybitInstrument::symbol)
+            .collect())
+    }
+
+    async fn get_symbol_info(&self) -> ExchangeResult<Vec<SymbolInfo>> {
+        Ok(self
+            .instruments()
+            .await?
+            .iter()
+            .map(BybitInstrument::to_symbol_info)
+            .collect())
+    }
+
+    async fn get_market_data(&self, symbol: &Symbol) -> ExchangeResult<MarketData> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Ticker {
+            last_price: String,
+            bid1_price: String,
+            ask1_price: String,
+            volume24h: String,
+            high_price24h: String,
+            low_price24h: String,
+            #[serde(default)]
+            prev_price24h: String,
+        }
+
+        let venue_symbol = self.venue_symbol(symbol);
+        let tickers: List<Ticker> = self
+            .public_request(&format!(
+                "/v5/market/tickers?category=spot&symbol={venue_symbol}"
+            ))
+            .await?;
+        let ticker = tickers
+            .list
+            .into_iter()
+            .next()
+            .ok_or_else(|| ExchangeError::ApiError {
+                code: -1,
+                message: format!("No ticker for {venue_symbol}"),
+            })?;
+
+        let last = ticker.last_price.parse().unwrap_or_default();
+        let (price_change_24h, percent_change_24h) =
+            MarketData::price_change(ticker.prev_price24h.parse().unwrap_or_default(), last);
+        Ok(MarketData {
+            symbol: symbol.clone(),
+            bid: ticker.bid1_price.parse().unwrap_or_default(),
+            ask: ticker.ask1_price.parse().unwrap_or_default(),
+            last,
+            volume_24h: ticker.volume24h.parse().unwrap_or_default(),
+            high_24h: ticker.high_price24h.parse().unwrap_or_default(),
+            low_24h: ticker.low_price24h.parse().unwrap_or_default(),
+            price_change_24h,
+            percent_change_24h,
+            fair_value: None,
+            timestamp: Utc::now(),
+        })
+    }
+
+    async fn get_balances(&self) -> ExchangeResult<Vec<ExchangeBalance>> {
+        #[derive(serde::Deserialize)]
+        struct Wallet {
+            coin: Vec<Coin>,
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Coin {
+            coin: String,
+            wallet_balance: String,
+            #[serde(default)]
+            locked: String,
+        }
+
+        let wallets: List<Wallet> = self
+            .signed_get("/v5/account/wallet-balance", "accountType=UNIFIED")
+            .await?;
+
+        Ok(wallets
+            .list
+            .into_iter()
+            .flat_map(|w| w.coin)
+            .filter_map(|c| {
+                let total: Decimal = c.wallet_balance.parse().ok()?;
+                let locked: Decimal = c.locked.parse().unwrap_or_default();
+                Some(ExchangeBalance {
+                    asset: c.coin,
+                    free: total - locked,
+                    locked,
+                })
+            })
+            .filter(|b| b.free > Decimal::ZERO || b.locked > Decimal::ZERO)
+            .collect())
+    }
+
+    async fn place_order(&self, order: &Order) -> ExchangeResult<ExchangeOrder> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Created {
+            order_id: String,
+            #[serde(default)]
+            order_link_id: String,
+        }
+
+        let (quantity, price) = order_precision(&self.symbols, order)?;
+        let mut body = serde_json::json!({
+            "category": "spot",
+            "symbol": self.venue_symbol(&order.symbol),
+            "side": match order.side {
+                Side::Buy => "Buy",
+                Side::Sell => "Sell",
+            },
+            "qty": quantity.to_string(),
+            "orderLinkId": order.client_order_id,
+        });
+        match price {
+            Some(price) => {
+                body["orderType"] = "Limit".into();
+                body["price"] = price.to_string().into();
+                body["timeInForce"] = "GTC".into();
+            }
+            None => {
+                // Market buys are sized in the quote coin unless told otherwise
+                body["orderType"] = "Market".into();
+                body["marketUnit"] = "baseCoin".into();
+            }
+        }
+
+        let created: Created = self.signed_post("/v5/order/create", &body).await?;
+
+        info!(order_id = %created.order_id, "Order placed on Bybit");
+
+        Ok(ExchangeOrder {
+            exchange_order_id: created.order_id,
+            client_order_id: created.order_link_id,
+            symbol: order.symbol.clone(),
+            status: OrderStatus::Open,
+            filled_quantity: Decimal::ZERO,
+            avg_price: None,
+        })
+    }
+
+    async fn cancel_order(&self, symbol: &Symbol, order_id: &str) -> ExchangeResult<()> {
+        let body = serde_json::json!({
+            "category": "spot",
+            "symbol": self.venue_symbol(symbol),
+            "orderId": order_id,
+        });
+
+        let _: serde_json::Value = self.signed_post("/v5/order/cancel", &body).await?;
+
+        info!(order_id = order_id, "Order cancelled on Bybit");
+
+        Ok(())
+    }
+
+    async fn get_order(&self, symbol: &Symbol, order_id: &str) -> ExchangeResult<ExchangeOrder> {
+        let order = self
+            .find_order(&self.venue_symbol(symbol), order_id)
+            .await?;
+
+        Ok(ExchangeOrder {
+            exchange_order_id: order.order_id,
+            client_order_id: order.order_link_id,
+            symbol: symbol.clone(),
+            status: order_status(&order.order_status),
+            filled_quantity: order.cum_exec_qty.parse().unwrap_or_default(),
+            avg_price: increment(&order.avg_price),
+        })
+    }
+
+    /// Top `limit` levels from `/v5/market/orderbook`
+    async fn get_depth(&self, symbol: &Symbol, limit: u32) -> ExchangeResult<OrderBookDepth> {
+        #[derive(serde::Deserialize)]
+        struct Book {
+            b: Vec<Vec<serde_json::Value>>,
+            a: Vec<Vec<serde_json::Value>>,
+        }
+
+        let venue_symbol = self.venue_symbol(symbol);
+        let book: Book = self
+            .public_request(&format!(
+                "/v5/market/orderbook?category=spot&symbol={venue_symbol}&limit={limit}"
+            ))
+            .await?;
+
+        Ok(OrderBookDepth {
+            symbol: symbol.clone(),
+            bids: parse_levels(&book.b),
+            asks: parse_levels(&book.a),
+            timestamp: Utc::now(),
+        })
+    }
+
+    /// Recent public trades; venue trades carry no order or user ids
+    async fn get_trades(&self, symbol: &Symbol, limit: u32) -> ExchangeResult<Vec<Trade>> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct BybitTrade {
+            exec_id: String,
+            price: String,
+            size: String,
+            side: String,
+            time: String,
+        }
+
+        let venue_symbol = self.venue_symbol(symbol);
+        let trades: List<BybitTrade> = self
+            .public_request(&format!(
+                "/v5/market/recent-trade?category=spot&symbol={venue_symbol}&limit={limit}"
+            ))
+            .await?;
+
+        Ok(trades
+            .list
+            .into_iter()
+            .filter_map(|t| {
+                let price: Decimal = t.price.parse().ok()?;
+                let quantity: Decimal = t.size.parse().ok()?;
+                Some(Trade {
+                    id: uuid::Uuid::new_v4(),
+                    trade_id: t.exec_id.parse().unwrap_or_default(),
+                    symbol: symbol.clone(),
+                    maker_order_id: uuid::Uuid::nil(),
+                    maker_user_id: uuid::Uuid::nil(),
+                    taker_order_id: uuid::Uuid::nil(),
+                    taker_user_id: uuid::Uuid::nil(),
+                    price,
+                    quantity,
+                    quote_quantity: price * quantity,
+                    taker_side: if t.side == "Buy" {
+                        Side::Buy
+                    } else {
+                        Side::Sell
+                    },
+                    executed_at: Utc.timestamp_millis_opt(t.time.parse().ok()?).single()?,
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::conformance::{
+        self, fixture, ConformanceTarget, MockVenue, RecordedRequest,
+    };
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    struct Bybit;
+
+    fn ok(result: serde_json::Value) -> serde_json::Value {
+        json!({ "retCode": 0, "retMsg": "OK", "result": result, "time": 1_700_000_000_000_i64 })
+    }
+
+    #[async_trait]
+    impl ConformanceTarget for Bybit {
+        type Adapter = BybitAdapter;
+
+        fn adapter(&self, base_url: &str, symbols: Arc<SymbolRegistry>) -> BybitAdapter {
+            BybitAdapter::new("key".to_string(), "secret".to_string(), symbols)
+                .with_base_url(base_url)
+                .with_retry_policy(RetryPolicy::new(3, Duration::from_millis(1)))
+        }
+
+        async fn mount_fixtures(&self, venue: &MockVenue) {
+            let order = json!({ "list": [{
+                "orderId": fixture::ORDER_ID,
+                "orderLinkId": "c1",
+                "orderStatus": "PartiallyFilled",
+                "cumExecQty": fixture::FILLED.to_string(),
+                "avgPrice": "",
+            }]});
+
+            venue
+                .mock(
+                    Method::GET,
+                    "/v5/market/time",
+                    StatusCode::OK,
+                    ok(json!({
+                        "timeSecond": Utc::now().timestamp().to_string(),
+                        "timeNano": Utc::now().timestamp_nanos_opt().unwrap().to_string(),
+                    })),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::GET,
+                    "/v5/market/instruments-info",
+                    StatusCode::OK,
+                    ok(json!({ "category": "spot", "list": [{
+                        "symbol": "BTCUSDT",
+                        "baseCoin": fixture::BASE,
+                        "quoteCoin": fixture::QUOTE,
+                        "status": "Trading",
+                        "lotSizeFilter": { "basePrecision": "0.000001", "minOrderQty": "0.000048" },
+                        "priceFilter": { "tickSize": "0.01" },
+                    }]})),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::GET,
+                    "/v5/market/tickers",
+                    StatusCode::OK,
+                    ok(json!({ "category": "spot", "list": [{
+                        "symbol": "BTCUSDT",
+                        "lastPrice": fixture::LAST.to_string(),
+                        "bid1Price": fixture::BID.to_string(),
+                        "ask1Price": fixture::ASK.to_string(),
+                        "volume24h": "10",
+                        "highPrice24h": "105",
+                        "lowPrice24h": "95",
+                    }]})),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::GET,
+                    "/v5/market/recent-trade",
+                    StatusCode::OK,
+                    ok(json!({ "category": "spot", "list": [{
+                        "execId": "1",
+                        "symbol": "BTCUSDT",
+                        "price": fixture::TRADE_PRICE.to_string(),
+                        "size": "0.1",
+                        "side": "Sell",
+                        "time": "1700000000000",
+                    }]})),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::GET,
+                    "/v5/account/wallet-balance",
+                    StatusCode::OK,
+                    ok(json!({ "list": [{ "coin": [
+                        {
+                            "coin": "BTC",
+                            "walletBalance": (fixture::BTC_FREE + fixture::BTC_LOCKED).to_string(),
+                            "locked": fixture::BTC_LOCKED.to_string(),
+                        },
+                        { "coin": "ETH", "walletBalance": "0", "locked": "0" },
+                    ]}]})),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::POST,
+                    "/v5/order/create",
+                    StatusCode::OK,
+                    ok(json!({ "orderId": fixture::ORDER_ID, "orderLinkId": "c1" })),
+                )
+                .await;
+            venue
+                .mock(Method::GET, "/v5/order/realtime", StatusCode::OK, ok(order))
+                .await;
+            venue
+                .mock(
+                    Method::POST,
+                    "/v5/order/cancel",
+                    StatusCode::OK,
+                    ok(json!({ "orderId": fixture::ORDER_ID, "orderLinkId": "c1" })),
+                )
+                .await;
+        }
+
+        fn order_endpoint(&self) -> (Method, &'static str) {
+            (Method::POST, "/v5/order/create")
+        }
+
+        fn cancel_endpoint(&self) -> (Method, &'static str) {
+            (Method::POST, "/v5/order/cancel")
+        }
+
+        fn market_data_endpoint(&self) -> (Method, &'static str) {
+            (Method::GET, "/v5/market/tickers")
+        }
+
+        fn is_signed(&self, request: &RecordedRequest) -> bool {
+            let header = |name: &str| {
+                request
+                    .headers
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+            };
+            let payload = if request.method == Method::GET {
+                &request.query
+            } else {
+                &request.body
+            };
+            let adapter = self.adapter("", Arc::new(SymbolRegistry::new()));
+
+            header("X-BAPI-API-KEY") == "key"
+                && header("X-BAPI-RECV-WINDOW") == RECV_WINDOW_MS
+                && header("X-BAPI-SIGN") == adapter.sign(header("X-BAPI-TIMESTAMP"), payload)
+        }
+
+        fn sent_order(&self, request: &RecordedRequest) -> (Decimal, Option<Decimal>) {
+            let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+            (
+                body["qty"].as_str().unwrap().parse().unwrap(),
+                body["price"].as_str().map(|p| p.parse().unwrap()),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conformance() {
+        conformance::run_conformance(&Bybit).await;
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_retried_and_closed_orders_found() {
+        let venue = MockVenue::start().await;
+        Bybit.mount_fixtures(&venue).await;
+        let adapter = Bybit.adapter(venue.url(), Arc::new(SymbolRegistry::new()));
+        let symbol = Symbol::new(fixture::BASE, fixture::QUOTE);
+
+        // 10006 arrives with HTTP 200 and is retried
+        venue
+            .mock_once(
+                Method::GET,
+                "/v5/account/wallet-balance",
+                StatusCode::OK,
+                json!({ "retCode": 10006, "retMsg": "Too many visits!", "result": {} }),
+            )
+            .await;
+        assert_eq!(adapter.get_balances().await.unwrap().len(), 1);
+        let attempts = venue
+            .requests()
+            .await
+            .iter()
+            .filter(|r| r.path == "/v5/account/wallet-balance")
+            .count();
+        assert_eq!(attempts, 2);
+
+        // Orders no longer open are looked up in history
+        venue
+            .mock(
+                Method::GET,
+                "/v5/order/realtime",
+                StatusCode::OK,
+                ok(json!({ "list": [] })),
+            )
+            .await;
+        venue
+            .mock(
+                Method::GET,
+                "/v5/order/history",
+                StatusCode::OK,
+                ok(json!({ "list": [{
+                    "orderId": fixture::ORDER_ID,
+                    "orderStatus": "Filled",
+                    "cumExecQty": "1",
+                    "avgPrice": "100.5",
+                }]})),
+            )
+            .await;
+        let order = adapter.get_order(&symbol, fixture::ORDER_ID).await.unwrap();
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.avg_price, Some(Decimal::new(1005, 1)));
+
+        // Insufficient balance is an order rejection
+        venue
+            .mock_once(
+                Method::POST,
+                "/v5/order/cancel",
+                StatusCode::OK,
+                json!({ "retCode": 170131, "retMsg": "Insufficient balance.", "result": {} }),
+            )
+            .await;
+        assert!(matches!(
+            adapter.cancel_order(&symbol, fixture::ORDER_ID).await,
+            Err(ExchangeError::OrderRejected(_))
+        ));
+    }
+}
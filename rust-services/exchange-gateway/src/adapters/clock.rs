@@ -0,0 +1,58 @@
+//! Venue Clock
+//!
+//! Offset of a venue's clock from the local clock, applied to signed
+//! request timestamps. One clock is shared by every adapter talking to the
+//! same venue, so a resync by any of them (periodic or after a timestamp
+//! rejection) corrects all sub-accounts at once.
+
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use chrono::Utc;
+
+#[derive(Debug, Default)]
+pub struct VenueClock {
+    /// Venue clock minus local clock
+    offset_ms: AtomicI64,
+}
+
+impl VenueClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current venue time in milliseconds since the epoch
+    pub fn now_ms(&self) -> i64 {
+        Utc::now().timestamp_millis() + self.offset_ms()
+    }
+
+    pub fn offset_ms(&self) -> i64 {
+        self.offset_ms.load(Ordering::Relaxed)
+    }
+
+    /// Record a server time read between `sent_at_ms` and `received_at_ms`
+    /// local time, returning the new offset
+    ///
+    /// The server is assumed to have read its clock halfway through the
+    /// round trip.
+    pub fn observe(&self, server_time_ms: i64, sent_at_ms: i64, received_at_ms: i64) -> i64 {
+        let offset = server_time_ms - (sent_at_ms + received_at_ms) / 2;
+        self.offset_ms.store(offset, Ordering::Relaxed);
+        offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_uses_round_trip_midpoint() {
+        let clock = VenueClock::new();
+        assert_eq!(clock.observe(10_500, 1_000, 1_200), 9_400);
+        assert_eq!(clock.offset_ms(), 9_400);
+
+        let local = Utc::now().timestamp_millis();
+        assert!(clock.now_ms() - local >= 9_400);
+        assert_eq!(clock.observe(1_000, 1_100, 1_100), -100);
+    }
+}
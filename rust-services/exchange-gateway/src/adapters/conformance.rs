@@ -0,0 +1,403 @@
+//! Adapter Conformance Suite
+//!
+//! Runs any `ExchangeAdapter` against a wiremock mock of its venue and
+//! checks every trait method for the behavior the router relies on:
+//! decoded market data and balances, signed private requests, orders
+//! rounded to the symbol's tick and lot size, and venue failures mapped
+//! onto the right `ExchangeError` variant.
+//!
+//! An adapter opts in by implementing [`ConformanceTarget`], which serves
+//! the [`fixture`] values in the venue's own wire format, and calling
+//! [`run_conformance`] from an async test. Available to tests and behind
+//! the `test-utils` feature.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::http::{HeaderMap, Method, StatusCode};
+use rust_decimal::Decimal;
+use wiremock::{matchers, Mock, MockServer, Request, ResponseTemplate};
+
+use super::traits::ExchangeAdapter;
+use common::{ExchangeError, Order, Side, Symbol, SymbolInfo, SymbolRegistry, SymbolStatus};
+
+/// Values every target's mock venue must serve
+pub mod fixture {
+    use rust_decimal::Decimal;
+
+    pub const BASE: &str = "BTC";
+    pub const QUOTE: &str = "USDT";
+
+    /// Ticker
+    pub const BID: Decimal = Decimal::from_parts(1005, 0, 0, false, 1);
+    pub const ASK: Decimal = Decimal::from_parts(1015, 0, 0, false, 1);
+    pub const LAST: Decimal = Decimal::from_parts(101, 0, 0, false, 0);
+
+    /// The only non-zero balance: BTC, plus a zero ETH balance to filter out
+    pub const BTC_FREE: Decimal = Decimal::from_parts(15, 0, 0, false, 1);
+    pub const BTC_LOCKED: Decimal = Decimal::from_parts(5, 0, 0, false, 1);
+
+    /// Venue order id for placed and queried orders
+    pub const ORDER_ID: &str = "12345";
+    /// Filled quantity reported when the order is queried
+    pub const FILLED: Decimal = Decimal::from_parts(5, 0, 0, false, 1);
+
+    /// Price of the single recent public trade
+    pub const TRADE_PRICE: Decimal = Decimal::from_parts(101, 0, 0, false, 0);
+
+    /// Symbol increments the suite registers
+    pub const TICK_SIZE: Decimal = Decimal::from_parts(1, 0, 0, false, 2);
+    pub const LOT_SIZE: Decimal = Decimal::from_parts(1, 0, 0, false, 3);
+}
+
+/// Request received by the mock venue
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: Method,
+    pub path: String,
+    pub query: String,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    /// Query-string and form-body parameters, body taking precedence
+    pub fn params(&self) -> HashMap<String, String> {
+        [self.query.as_str(), self.body.as_str()]
+            .iter()
+            .flat_map(|s| s.split('&'))
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+}
+
+impl From<&Request> for RecordedRequest {
+    fn from(request: &Request) -> Self {
+        Self {
+            method: request.method.clone(),
+            path: request.url.path().to_string(),
+            query: request.url.query().unwrap_or_default().to_string(),
+            headers: request.headers.clone(),
+            body: String::from_utf8_lossy(&request.body).into_owned(),
+        }
+    }
+}
+
+/// Priority of one-off responses, ahead of every standing mock
+const ONCE_PRIORITY: u8 = 1;
+
+/// Mock venue answering with canned responses per method and path
+pub struct MockVenue {
+    server: MockServer,
+    url: String,
+    /// Priority of the next standing mock; each outranks those before it
+    next_priority: AtomicU8,
+}
+
+impl MockVenue {
+    pub async fn start() -> Self {
+        let server = MockServer::start().await;
+        let url = server.uri();
+        Self {
+            server,
+            url,
+            next_priority: AtomicU8::new(u8::MAX),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Answer `method` requests to `path` with `status` and a JSON body,
+    /// in place of any earlier mock for the route
+    pub async fn mock(
+        &self,
+        method: Method,
+        path: &str,
+        status: StatusCode,
+        body: serde_json::Value,
+    ) {
+        let priority = self.next_priority.fetch_sub(1, Ordering::Relaxed);
+        assert!(priority > ONCE_PRIORITY, "too many mocks on the venue");
+        route(method, path, status, body)
+            .with_priority(priority)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Answer the next `method` request to `path` with `status` and a JSON
+    /// body, then fall back to the standing mock; queued in call order
+    pub async fn mock_once(
+        &self,
+        method: Method,
+        path: &str,
+        status: StatusCode,
+        body: serde_json::Value,
+    ) {
+        route(method, path, status, body)
+            .with_priority(ONCE_PRIORITY)
+            .up_to_n_times(1)
+            .mount(&self.server)
+            .await;
+    }
+
+    pub async fn requests(&self) -> Vec<RecordedRequest> {
+        self.server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(RecordedRequest::from)
+            .collect()
+    }
+
+    /// Number of `method` requests to `path`
+    pub async fn request_count(&self, method: &Method, path: &str) -> usize {
+        self.requests()
+            .await
+            .iter()
+            .filter(|r| r.method == *method && r.path == path)
+            .count()
+    }
+
+    /// Most recent request to `path`
+    pub async fn last_request(&self, method: &Method, path: &str) -> Option<RecordedRequest> {
+        self.requests()
+            .await
+            .into_iter()
+            .rev()
+            .find(|r| r.method == *method && r.path == path)
+    }
+}
+
+fn route(method: Method, path: &str, status: StatusCode, body: serde_json::Value) -> Mock {
+    Mock::given(matchers::method(method.as_str()))
+        .and(matchers::path(path))
+        .respond_with(ResponseTemplate::new(status.as_u16()).set_body_json(body))
+}
+
+/// Venue-specific half of the suite
+#[async_trait]
+pub trait ConformanceTarget {
+    type Adapter: ExchangeAdapter;
+
+    /// Adapter talking to `base_url` with test credentials
+    fn adapter(&self, base_url: &str, symbols: Arc<SymbolRegistry>) -> Self::Adapter;
+
+    /// Serve the [`fixture`] values on every endpoint the adapter calls
+    async fn mount_fixtures(&self, venue: &MockVenue);
+
+    /// Endpoint `place_order` calls
+    fn order_endpoint(&self) -> (Method, &'static str);
+
+    /// Endpoint `cancel_order` calls
+    fn cancel_endpoint(&self) -> (Method, &'static str);
+
+    /// Endpoint `get_market_data` calls
+    fn market_data_endpoint(&self) -> (Method, &'static str);
+
+    /// Whether a private request carries valid credentials and signature
+    fn is_signed(&self, request: &RecordedRequest) -> bool;
+
+    /// Quantity and limit price as sent in an order request
+    fn sent_order(&self, request: &RecordedRequest) -> (Decimal, Option<Decimal>);
+}
+
+fn symbol() -> Symbol {
+    Symbol::new(fixture::BASE, fixture::QUOTE)
+}
+
+fn registry() -> Arc<SymbolRegistry> {
+    let mut info = SymbolInfo::from_symbol(&symbol());
+    info.status = SymbolStatus::Trading;
+    info.tick_size = Some(fixture::TICK_SIZE);
+    info.lot_size = Some(fixture::LOT_SIZE);
+    Arc::new(SymbolRegistry::with_symbols(1, vec![info]))
+}
+
+/// GTC limit order for the fixture symbol
+pub fn limit_order(side: Side, quantity: Decimal, price: Decimal) -> Order {
+    let mut order = Order::builder()
+        .symbol(symbol())
+        .side(side)
+        .price(price)
+        .quantity(quantity)
+        .build();
+    order.client_order_id = order.id.simple().to_string();
+    order
+}
+
+/// Run every conformance check against `target`, panicking on the first
+/// failure
+pub async fn run_conformance<T: ConformanceTarget>(target: &T) {
+    let venue = MockVenue::start().await;
+    target.mount_fixtures(&venue).await;
+    let adapter = target.adapter(venue.url(), registry());
+    let name = adapter.name();
+
+    // Market data
+    assert!(adapter.is_available().await, "{name}: not available");
+
+    let symbols = adapter.get_symbols().await.expect("get_symbols");
+    assert!(
+        symbols.contains(&symbol()),
+        "{name}: fixture symbol missing"
+    );
+
+    let data = adapter
+        .get_market_data(&symbol())
+        .await
+        .expect("get_market_data");
+    assert_eq!(
+        (data.bid, data.ask, data.last),
+        (fixture::BID, fixture::ASK, fixture::LAST),
+        "{name}: ticker decoded wrongly"
+    );
+
+    let trades = adapter.get_trades(&symbol(), 10).await.expect("get_trades");
+    assert!(
+        trades.iter().any(|t| t.price == fixture::TRADE_PRICE),
+        "{name}: recent trades missing the fixture trade"
+    );
+
+    // Account
+    let balances = adapter.get_balances().await.expect("get_balances");
+    assert_eq!(balances.len(), 1, "{name}: zero balances not filtered");
+    assert_eq!(
+        (
+            balances[0].asset.as_str(),
+            balances[0].free,
+            balances[0].locked
+        ),
+        ("BTC", fixture::BTC_FREE, fixture::BTC_LOCKED),
+        "{name}: balance decoded wrongly"
+    );
+
+    // Orders are rounded to lot and tick, never to a worse price
+    let (method, path) = target.order_endpoint();
+    let buy = limit_order(Side::Buy, Decimal::new(12345, 5), Decimal::new(100567, 3));
+    let placed = adapter.place_order(&buy).await.expect("place_order");
+    assert_eq!(placed.exchange_order_id, fixture::ORDER_ID);
+    let request = venue
+        .last_request(&method, path)
+        .await
+        .expect("order request sent");
+    assert!(target.is_signed(&request), "{name}: order not signed");
+    assert_eq!(
+        target.sent_order(&request),
+        (Decimal::new(123, 3), Some(Decimal::new(10056, 2))),
+        "{name}: buy not rounded down to lot and tick"
+    );
+
+    let sell = limit_order(Side::Sell, Decimal::new(12345, 5), Decimal::new(100561, 3));
+    adapter.place_order(&sell).await.expect("place_order");
+    let request = venue.last_request(&method, path).await.unwrap();
+    assert_eq!(
+        target.sent_order(&request).1,
+        Some(Decimal::new(10057, 2)),
+        "{name}: sell price not rounded up to tick"
+    );
+
+    let sent_before = venue.requests().await.len();
+    let dust = limit_order(Side::Buy, Decimal::new(1, 4), Decimal::new(100, 0));
+    assert!(
+        matches!(
+            adapter.place_order(&dust).await,
+            Err(ExchangeError::OrderRejected(_))
+        ),
+        "{name}: order below lot size not rejected"
+    );
+    assert_eq!(
+        venue.requests().await.len(),
+        sent_before,
+        "{name}: order below lot size reached the venue"
+    );
+
+    let queried = adapter
+        .get_order(&symbol(), fixture::ORDER_ID)
+        .await
+        .expect("get_order");
+    assert_eq!(queried.filled_quantity, fixture::FILLED);
+
+    adapter
+        .cancel_order(&symbol(), fixture::ORDER_ID)
+        .await
+        .expect("cancel_order");
+    let (cancel_method, cancel_path) = target.cancel_endpoint();
+    let cancel = venue
+        .last_request(&cancel_method, cancel_path)
+        .await
+        .expect("cancel request sent");
+    assert!(target.is_signed(&cancel), "{name}: cancel not signed");
+
+    let replaced = adapter
+        .replace_order(fixture::ORDER_ID, &buy)
+        .await
+        .expect("replace_order");
+    assert_eq!(
+        replaced.exchange_order_id,
+        fixture::ORDER_ID,
+        "{name}: replacement not returned"
+    );
+
+    // Error mapping
+    for (status, expected) in [
+        (StatusCode::TOO_MANY_REQUESTS, "RateLimited"),
+        (StatusCode::UNAUTHORIZED, "AuthenticationFailed"),
+        (StatusCode::INTERNAL_SERVER_ERROR, "ApiError"),
+    ] {
+        venue
+            .mock(
+                method.clone(),
+                path,
+                status,
+                serde_json::json!({ "error": "injected" }),
+            )
+            .await;
+        let err = adapter.place_order(&buy).await.expect_err("injected error");
+        let matched = match (&err, expected) {
+            (ExchangeError::RateLimited, "RateLimited") => true,
+            (ExchangeError::AuthenticationFailed(_), "AuthenticationFailed") => true,
+            (ExchangeError::ApiError { code, .. }, "ApiError") => *code == status.as_u16() as i32,
+            _ => false,
+        };
+        assert!(
+            matched,
+            "{name}: HTTP {status} mapped to {err:?}, expected {expected}"
+        );
+    }
+
+    let (method, path) = target.market_data_endpoint();
+    venue
+        .mock(
+            method,
+            path,
+            StatusCode::SERVICE_UNAVAILABLE,
+            serde_json::json!({ "error": "injected" }),
+        )
+        .await;
+    assert!(
+        adapter.get_market_data(&symbol()).await.is_err(),
+        "{name}: venue outage returned market data"
+    );
+
+    // Unreachable venue
+    let offline = target.adapter("http://127.0.0.1:1", registry());
+    assert!(
+        !offline.is_available().await,
+        "{name}: offline venue available"
+    );
+    assert!(
+        matches!(
+            offline.get_balances().await,
+            Err(ExchangeError::ConnectionFailed(_))
+        ),
+        "{name}: unreachable venue not reported as a connection failure"
+    );
+}
@@ -0,0 +1,905 @@
+//! Kraken Exchange Adapter
+//!
+//! Integration with Kraken spot trading API. Kraken names some assets with
+//! legacy codes (`XXBT`, `ZUSD`) and calls bitcoin `XBT`; both are
+//! normalized to the common asset names used by `Symbol`.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use rust_decimal::Decimal;
+use sha2::{Digest, Sha256, Sha512};
+use tracing::{info, warn};
+
+use super::retry::{Recovery, RetryPolicy};
+use super::traits::*;
+use common::{
+    ExchangeError, MarketData, Order, OrderStatus, Side, Symbol, SymbolInfo, SymbolRegistry,
+    SymbolStatus, Trade,
+};
+
+const KRAKEN_API_URL: &str = "https://api.kraken.com";
+
+/// Legacy Kraken asset codes and their common names
+const LEGACY_ASSETS: [(&str, &str); 19] = [
+    ("XXBT", "BTC"),
+    ("XBT", "BTC"),
+    ("XXDG", "DOGE"),
+    ("XDG", "DOGE"),
+    ("XETH", "ETH"),
+    ("XETC", "ETC"),
+    ("XLTC", "LTC"),
+    ("XXRP", "XRP"),
+    ("XXLM", "XLM"),
+    ("XXMR", "XMR"),
+    ("XZEC", "ZEC"),
+    ("XMLN", "MLN"),
+    ("XREP", "REP"),
+    ("ZUSD", "USD"),
+    ("ZEUR", "EUR"),
+    ("ZGBP", "GBP"),
+    ("ZCAD", "CAD"),
+    ("ZJPY", "JPY"),
+    ("ZAUD", "AUD"),
+];
+
+/// Common name of a Kraken asset code, e.g. `XXBT` -> `BTC`
+pub fn normalize_asset(code: &str) -> String {
+    LEGACY_ASSETS
+        .iter()
+        .find(|(legacy, _)| *legacy == code)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| code.to_string())
+}
+
+/// Kraken's name for a common asset in pair names, e.g. `BTC` -> `XBT`
+fn venue_asset(asset: &str) -> &str {
+    match asset {
+        "BTC" => "XBT",
+        "DOGE" => "XDG",
+        other => other,
+    }
+}
+
+/// Response envelope shared by every Kraken endpoint
+#[derive(serde::Deserialize)]
+struct KrakenResponse<T> {
+    #[serde(default)]
+    error: Vec<String>,
+    result: Option<T>,
+}
+
+/// Pair entry from `/0/public/AssetPairs`
+#[derive(serde::Deserialize)]
+struct KrakenPair {
+    altname: String,
+    base: String,
+    quote: String,
+    #[serde(default)]
+    status: Option<String>,
+    pair_decimals: u32,
+    lot_decimals: u32,
+    #[serde(default)]
+    tick_size: Option<String>,
+    #[serde(default)]
+    ordermin: Option<String>,
+}
+
+impl KrakenPair {
+    fn symbol(&self) -> Symbol {
+        Symbol::new(&normalize_asset(&self.base), &normalize_asset(&self.quote))
+    }
+
+    fn is_online(&self) -> bool {
+        self.status.as_deref().is_none_or(|s| s == "online")
+    }
+
+    fn to_symbol_info(&self) -> SymbolInfo {
+        let symbol = self.symbol();
+        SymbolInfo {
+            base_asset: symbol.base().to_string(),
+            quote_asset: symbol.quote().to_string(),
+            status: match self.status.as_deref() {
+                Some("online") | None => SymbolStatus::Trading,
+                Some("cancel_only" | "post_only" | "limit_only" | "reduce_only") => {
+                    SymbolStatus::Halted
+                }
+                Some("delisted") => SymbolStatus::Delisted,
+                Some(_) => SymbolStatus::Unknown,
+            },
+            price_precision: self.pair_decimals,
+            quantity_precision: self.lot_decimals,
+            tick_size: self
+                .tick_size
+                .as_deref()
+                .and_then(|t| t.parse::<Decimal>().ok())
+                .map(|t| t.normalize()),
+            lot_size: Some(Decimal::new(1, self.lot_decimals)),
+            min_quantity: self.ordermin.as_deref().and_then(|m| m.parse().ok()),
+            venues: HashMap::from([("kraken".to_string(), self.altname.clone())]),
+            symbol,
+        }
+    }
+}
+
+/// Order entry from `/0/private/QueryOrders`
+#[derive(serde::Deserialize)]
+struct KrakenOrder {
+    status: String,
+    vol_exec: String,
+    #[serde(default)]
+    price: Option<String>,
+    #[serde(default)]
+    cl_ord_id: Option<String>,
+}
+
+pub struct KrakenAdapter {
+    client: Client,
+    api_key: String,
+    /// Base64-encoded private key
+    api_secret: String,
+
+    /// API root, overridable for testing
+    base_url: String,
+
+    /// Shared reference data for venue symbol mapping
+    symbols: Arc<SymbolRegistry>,
+
+    /// Retries for transient venue errors
+    retry: RetryPolicy,
+
+    /// Last nonce sent; Kraken requires them to increase per API key
+    last_nonce: AtomicU64,
+}
+
+impl KrakenAdapter {
+    pub fn new(api_key: String, api_secret: String, symbols: Arc<SymbolRegistry>) -> Self {
+        Self {
+            client: Client::new(),
+            api_key,
+            api_secret,
+            base_url: KRAKEN_API_URL.to_string(),
+            symbols,
+            retry: RetryPolicy::default(),
+            last_nonce: AtomicU64::new(0),
+        }
+    }
+
+    /// Point requests at another API root
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Kraken pair for `symbol`, e.g. `XBTUSDT`
+    fn venue_symbol(&self, symbol: &Symbol) -> String {
+        self.symbols
+            .get(symbol)
+            .and_then(|info| info.venues.get("kraken").cloned())
+            .unwrap_or_else(|| {
+                format!(
+                    "{}{}",
+                    venue_asset(symbol.base()),
+                    venue_asset(symbol.quote())
+                )
+            })
+    }
+
+    /// Microsecond timestamp, bumped past the last nonce if the clock
+    /// has not moved or went backwards
+    fn next_nonce(&self) -> u64 {
+        let now = Utc::now().timestamp_micros() as u64;
+        let previous = self
+            .last_nonce
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |last| {
+                Some(now.max(last + 1))
+            })
+            .unwrap_or_default();
+        now.max(previous + 1)
+    }
+
+    /// `API-Sign`: HMAC-SHA512 over the path and SHA-256 of nonce + body,
+    /// keyed with the decoded secret
+    fn sign(&self, path: &str, nonce: u64, body: &str) -> ExchangeResult<String> {
+        let secret = BASE64.decode(&self.api_secret).map_err(|_| {
+            ExchangeError::AuthenticationFailed("API secret is not valid base64".to_string())
+        })?;
+
+        let digest = Sha256::digest(format!("{nonce}{body}").as_bytes());
+        let mut mac =
+            Hmac::<Sha512>::new_from_slice(&secret).expect("HMAC can take key of any size");
+        mac.update(path.as_bytes());
+        mac.update(&digest);
+        Ok(BASE64.encode(mac.finalize().into_bytes()))
+    }
+
+    async fn asset_pairs(&self) -> ExchangeResult<HashMap<String, KrakenPair>> {
+        self.public_request("/0/public/AssetPairs").await
+    }
+
+    async fn public_request<T: serde::de::DeserializeOwned>(
+        &self,
+        endpoint: &str,
+    ) -> ExchangeResult<T> {
+        let url = format!("{}{endpoint}", self.base_url);
+        self.execute(|| Ok(self.client.get(&url))).await
+    }
+
+    async fn private_request<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> ExchangeResult<T> {
+        let url = format!("{}{path}", self.base_url);
+
+        // Nonce and signature are renewed on every attempt
+        self.execute(|| {
+            let nonce = self.next_nonce();
+            let body = std::iter::once(format!("nonce={nonce}"))
+                .chain(params.iter().map(|(k, v)| format!("{k}={v}")))
+                .collect::<Vec<_>>()
+                .join("&");
+            let signature = self.sign(path, nonce, &body)?;
+
+            Ok(self
+                .client
+                .post(&url)
+                .header("API-Key", &self.api_key)
+                .header("API-Sign", signature)
+                .header("Content-Type", "application/x-www-form-urlencoded")
+                .body(body))
+        })
+        .await
+    }
+
+    /// Send a request, retrying transient failures under the retry policy
+    async fn execute<T: serde::de::DeserializeOwned>(
+        &self,
+        request: impl Fn() -> ExchangeResult<reqwest::RequestBuilder>,
+    ) -> ExchangeResult<T> {
+        let mut attempt = 1;
+        loop {
+            let error = match request()?.send().await {
+                Ok(response) => match read_response(response).await {
+                    Ok(value) => return Ok(value),
+                    Err(error) => error,
+                },
+                // The request never reached the venue
+                Err(e) if e.is_connect() => ExchangeError::ConnectionFailed(e.to_string()),
+                Err(e) => return Err(ExchangeError::ConnectionFailed(e.to_string())),
+            };
+
+            let recovery = recovery(&error);
+            if recovery == Recovery::Fail || !self.retry.allows_retry(attempt) {
+                return Err(error);
+            }
+
+            warn!(attempt, error = %error, "Retrying Kraken request");
+            metrics::counter!(
+                "exchange_retries",
+                "exchange" => "kraken",
+                "reason" => recovery.reason()
+            )
+            .increment(1);
+            if let Recovery::Backoff(hint) = recovery {
+                tokio::time::sleep(self.retry.delay(attempt, hint)).await;
+            }
+            attempt += 1;
+        }
+    }
+}
+
+/// Recovery for a failed Kraken request
+///
+/// Rate limits and nonces overtaken by a concurrent request are retried;
+/// a request that could not connect never reached the venue. Everything
+/// else, including `EService` errors whose outcome is unknown, is final.
+fn recovery(error: &ExchangeError) -> Recovery {
+    match error {
+        ExchangeError::RateLimited | ExchangeError::ConnectionFailed(_) => Recovery::Backoff(None),
+        ExchangeError::AuthenticationFailed(msg) if msg.contains("Invalid nonce") => {
+            Recovery::Backoff(None)
+        }
+        _ => Recovery::Fail,
+    }
+}
+
+/// Map a Kraken error string such as `EOrder:Insufficient funds`
+fn map_error(message: &str) -> ExchangeError {
+    if message.contains("Rate limit exceeded") || message.contains("Too many requests") {
+        return ExchangeError::RateLimited;
+    }
+    match message.split_once(':').map(|(category, _)| category) {
+        Some("EAPI") if message.starts_with("EAPI:Invalid") => {
+            ExchangeError::AuthenticationFailed(message.to_string())
+        }
+        Some("EGeneral") if message.contains("Permission denied") => {
+            ExchangeError::AuthenticationFailed(message.to_string())
+        }
+        Some("EOrder" | "EFunding") => ExchangeError::OrderRejected(message.to_string()),
+        _ => ExchangeError::ApiError {
+            code: -1,
+            message: message.to_string(),
+        },
+    }
+}
+
+/// Decode a Kraken response, mapping failures onto `ExchangeError`
+///
+/// Kraken reports most errors with HTTP 200 and an `error` list of
+/// `Category:Message` strings.
+async fn read_response<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+) -> ExchangeResult<T> {
+    let status = response.status();
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(match status.as_u16() {
+            429 => ExchangeError::RateLimited,
+            401 | 403 => ExchangeError::AuthenticationFailed(text),
+            status => ExchangeError::ApiError {
+                code: status as i32,
+                message: text,
+            },
+        });
+    }
+
+    let body: KrakenResponse<T> = response.json().await.map_err(|e| ExchangeError::ApiError {
+        code: -1,
+        message: e.to_string(),
+    })?;
+    if let Some(error) = body.error.first() {
+        return Err(map_error(error));
+    }
+    body.result.ok_or_else(|| ExchangeError::ApiError {
+        code: -1,
+        message: "Response has no result".to_string(),
+    })
+}
+
+/// Order status from Kraken's lower-case order `status`
+fn order_status(status: &str) -> OrderStatus {
+    match status {
+        "pending" => OrderStatus::Pending,
+        "closed" => OrderStatus::Filled,
+        "canceled" => OrderStatus::Cancelled,
+        "expired" => OrderStatus::Expired,
+        // open and anything unknown are still working
+        _ => OrderStatus::Open,
+    }
+}
+
+fn parse_decimal(value: &serde_json::Value) -> Option<Decimal> {
+    value.as_str()?.parse().ok()
+}
+
+#[async_trait]
+impl ExchangeAdapter for KrakenAdapter {
+    fn name(&self) -> &'static str {
+        "Kraken"
+    }
+
+    async fn is_available(&self) -> bool {
+        #[derive(serde::Deserialize)]
+        struct SystemStatus {
+            status: String,
+        }
+
+        self.public_request::<SystemStatus>("/0/public/SystemStatus")
+            .await
+            .is_ok_and(|s| s.status == "online")
+    }
+
+    async fn get_symbols(&self) -> ExchangeResult<Vec<Symbol>> {
+        Ok(self
+            .asset_pairs()
+            .await?
+            .values()
+            .filter(|p| p.is_online())
+            .map(KrakenPair::symbol)
+            .collect())
+    }
+
+    async fn get_symbol_info(&self) -> ExchangeResult<Vec<SymbolInfo>> {
+        Ok(self
+            .asset_pairs()
+            .await?
+            .values()
+            .map(KrakenPair::to_symbol_info)
+            .collect())
+    }
+
+    async fn get_market_data(&self, symbol: &Symbol) -> ExchangeResult<MarketData> {
+        /// Price arrays lead with the price; volume/high/low carry
+        /// [today, last 24 hours]; `o` is today's opening price
+        #[derive(serde::Deserialize)]
+        struct Ticker {
+            a: Vec<serde_json::Value>,
+            b: Vec<serde_json::Value>,
+            c: Vec<serde_json::Value>,
+            v: Vec<serde_json::Value>,
+            h: Vec<serde_json::Value>,
+            l: Vec<serde_json::Value>,
+            #[serde(default)]
+            o: serde_json::Value,
+        }
+
+        let pair = self.venue_symbol(symbol);
+        let tickers: HashMap<String, Ticker> = self
+            .public_request(&format!("/0/public/Ticker?pair={pair}"))
+            .await?;
+        let ticker = tickers
+            .into_values()
+            .next()
+            .ok_or_else(|| ExchangeError::ApiError {
+                code: -1,
+                message: format!("No ticker for {pair}"),
+            })?;
+
+        let field = |values: &[serde_json::Value], index: usize| {
+            values
+                .get(index)
+                .and_then(parse_decimal)
+                .unwrap_or_default()
+        };
+        let last = field(&ticker.c, 0);
+        // Kraken has no rolling 24-hour open, so the change is since 00:00 UTC
+        let (price_change_24h, percent_change_24h) =
+            MarketData::price_change(parse_decimal(&ticker.o).unwrap_or_default(), last);
+        Ok(MarketData {
+            symbol: symbol.clone(),
+            bid: field(&ticker.b, 0),
+            ask: field(&ticker.a, 0),
+            last,
+            volume_24h: field(&ticker.v, 1),
+            high_24h: field(&ticker.h, 1),
+            low_24h: field(&ticker.l, 1),
+            price_change_24h,
+            percent_change_24h,
+            fair_value: None,
+            timestamp: Utc::now(),
+        })
+    }
+
+    async fn get_balances(&self) -> ExchangeResult<Vec<ExchangeBalance>> {
+        #[derive(serde::Deserialize)]
+        struct BalanceEx {
+            balance: String,
+            #[serde(default)]
+            hold_trade: Option<String>,
+        }
+
+        let balances: HashMap<String, BalanceEx> =
+            self.private_request("/0/private/BalanceEx", &[]).await?;
+
+        let mut balances: Vec<ExchangeBalance> = balances
+            .into_iter()
+            .filter_map(|(asset, b)| {
+                let total: Decimal = b.balance.parse().ok()?;
+                let locked: Decimal = b
+                    .hold_trade
+                    .and_then(|h| h.parse().ok())
+                    .unwrap_or_default();
+                Some(ExchangeBalance {
+                    asset: normalize_asset(&asset),
+                    free: total - locked,
+                    locked,
+                })
+            })
+            .filter(|b| b.free > Decimal::ZERO || b.locked > Decimal::ZERO)
+            .collect();
+        balances.sort_by(|a, b| a.asset.cmp(&b.asset));
+        Ok(balances)
+    }
+
+    async fn place_order(&self, order: &Order) -> ExchangeResult<ExchangeOrder> {
+        #[derive(serde::Deserialize)]
+        struct AddOrderResult {
+            txid: Vec<String>,
+        }
+
+        let (quantity, price) = order_precision(&self.symbols, order)?;
+        let mut params = vec![
+            ("pair", self.venue_symbol(&order.symbol)),
+            (
+                "type",
+                match order.side {
+                    Side::Buy => "buy",
+                    Side::Sell => "sell",
+                }
+                .to_string(),
+            ),
+            (
+                "ordertype",
+                if price.is_some() { "limit" } else { "market" }.to_string(),
+            ),
+            ("volume", quantity.to_string()),
+            ("cl_ord_id", order.client_order_id.clone()),
+        ];
+        if let Some(price) = price {
+            params.push(("price", price.to_string()));
+        }
+
+        let result: AddOrderResult = self.private_request("/0/private/AddOrder", &params).await?;
+        let txid = result
+            .txid
+            .into_iter()
+            .next()
+            .ok_or_else(|| ExchangeError::ApiError {
+                code: -1,
+                message: "AddOrder returned no txid".to_string(),
+            })?;
+
+        info!(order_id = %txid, "Order placed on Kraken");
+
+        Ok(ExchangeOrder {
+            exchange_order_id: txid,
+            client_order_id: order.client_order_id.clone(),
+            symbol: order.symbol.clone(),
+            status: OrderStatus::Pending,
+            filled_quantity: Decimal::ZERO,
+            avg_price: None,
+        })
+    }
+
+    async fn cancel_order(&self, _symbol: &Symbol, order_id: &str) -> ExchangeResult<()> {
+        let _: serde_json::Value = self
+            .private_request("/0/private/CancelOrder", &[("txid", order_id.to_string())])
+            .await?;
+
+        info!(order_id = order_id, "Order cancelled on Kraken");
+
+        Ok(())
+    }
+
+    async fn get_order(&self, symbol: &Symbol, order_id: &str) -> ExchangeResult<ExchangeOrder> {
+        let orders: HashMap<String, KrakenOrder> = self
+            .private_request("/0/private/QueryOrders", &[("txid", order_id.to_string())])
+            .await?;
+        let order = orders
+            .into_values()
+            .next()
+            .ok_or_else(|| ExchangeError::ApiError {
+                code: -1,
+                message: format!("Unknown order {order_id}"),
+            })?;
+
+        Ok(ExchangeOrder {
+            exchange_order_id: order_id.to_string(),
+            client_order_id: order.cl_ord_id.unwrap_or_default(),
+            symbol: symbol.clone(),
+            status: order_status(&order.status),
+            filled_quantity: order.vol_exec.parse().unwrap_or_default(),
+            avg_price: order
+                .price
+                .and_then(|p| p.parse::<Decimal>().ok())
+                .filter(|p| !p.is_zero()),
+        })
+    }
+
+    /// Top `limit` levels from `/0/public/Depth`
+    async fn get_depth(&self, symbol: &Symbol, limit: u32) -> ExchangeResult<OrderBookDepth> {
+        /// Levels are [price, volume, timestamp]
+        #[derive(serde::Deserialize)]
+        struct Book {
+            bids: Vec<Vec<serde_json::Value>>,
+            asks: Vec<Vec<serde_json::Value>>,
+        }
+
+        let pair = self.venue_symbol(symbol);
+        let books: HashMap<String, Book> = self
+            .public_request(&format!("/0/public/Depth?pair={pair}&count={limit}"))
+            .await?;
+        let book = books
+            .into_values()
+            .next()
+            .ok_or_else(|| ExchangeError::ApiError {
+                code: -1,
+                message: format!("No book for {pair}"),
+            })?;
+
+        Ok(OrderBookDepth {
+            symbol: symbol.clone(),
+            bids: parse_levels(&book.bids),
+            asks: parse_levels(&book.asks),
+            timestamp: Utc::now(),
+        })
+    }
+
+    /// Recent public trades; venue trades carry no order or user ids
+    ///
+    /// Entries are `[price, volume, time, side, type, misc, trade_id]`.
+    async fn get_trades(&self, symbol: &Symbol, limit: u32) -> ExchangeResult<Vec<Trade>> {
+        let pair = self.venue_symbol(symbol);
+        let result: HashMap<String, serde_json::Value> = self
+            .public_request(&format!("/0/public/Trades?pair={pair}&count={limit}"))
+            .await?;
+
+        let entries = result
+            .into_iter()
+            .find(|(key, _)| key != "last")
+            .and_then(|(_, trades)| trades.as_array().cloned())
+            .unwrap_or_default();
+
+        Ok(entries
+            .iter()
+            .filter_map(|t| {
+                let price = parse_decimal(&t[0])?;
+                let quantity = parse_decimal(&t[1])?;
+                let time = t[2].as_f64()?;
+                Some(Trade {
+                    id: uuid::Uuid::new_v4(),
+                    trade_id: t[6].as_u64().unwrap_or_default(),
+                    symbol: symbol.clone(),
+                    maker_order_id: uuid::Uuid::nil(),
+                    maker_user_id: uuid::Uuid::nil(),
+                    taker_order_id: uuid::Uuid::nil(),
+                    taker_user_id: uuid::Uuid::nil(),
+                    price,
+                    quantity,
+                    quote_quantity: price * quantity,
+                    taker_side: if t[3] == "b" { Side::Buy } else { Side::Sell },
+                    executed_at: Utc.timestamp_millis_opt((time * 1000.0) as i64).single()?,
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::conformance::{
+        self, fixture, ConformanceTarget, MockVenue, RecordedRequest,
+    };
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+    use std::time::Duration;
+
+    struct Kraken;
+
+    #[async_trait]
+    impl ConformanceTarget for Kraken {
+        type Adapter = KrakenAdapter;
+
+        fn adapter(&self, base_url: &str, symbols: Arc<SymbolRegistry>) -> KrakenAdapter {
+            KrakenAdapter::new("key".to_string(), BASE64.encode("secret"), symbols)
+                .with_base_url(base_url)
+                .with_retry_policy(RetryPolicy::new(3, Duration::from_millis(1)))
+        }
+
+        async fn mount_fixtures(&self, venue: &MockVenue) {
+            let ok = |result: serde_json::Value| json!({ "error": [], "result": result });
+
+            venue
+                .mock(
+                    Method::GET,
+                    "/0/public/SystemStatus",
+                    StatusCode::OK,
+                    ok(json!({ "status": "online" })),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::GET,
+                    "/0/public/AssetPairs",
+                    StatusCode::OK,
+                    ok(json!({ "XBTUSDT": {
+                        "altname": "XBTUSDT",
+                        "base": "XXBT",
+                        "quote": fixture::QUOTE,
+                        "status": "online",
+                        "pair_decimals": 1,
+                        "lot_decimals": 8,
+                        "tick_size": "0.1",
+                        "ordermin": "0.00005",
+                    }})),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::GET,
+                    "/0/public/Ticker",
+                    StatusCode::OK,
+                    ok(json!({ "XBTUSDT": {
+                        "a": [fixture::ASK.to_string(), "1", "1.000"],
+                        "b": [fixture::BID.to_string(), "1", "1.000"],
+                        "c": [fixture::LAST.to_string(), "0.1"],
+                        "v": ["5", "10"],
+                        "h": ["104", "105"],
+                        "l": ["96", "95"],
+                    }})),
+                )
+                .await;
+            venue.mock(
+                Method::GET,
+                "/0/public/Trades",
+                StatusCode::OK,
+                ok(json!({
+                    "XBTUSDT": [[
+                        fixture::TRADE_PRICE.to_string(), "0.1", 1_700_000_000.5, "s", "l", "", 1,
+                    ]],
+                    "last": "1700000000500000000",
+                })),
+            ).await;
+            venue
+                .mock(
+                    Method::POST,
+                    "/0/private/BalanceEx",
+                    StatusCode::OK,
+                    ok(json!({
+                        "XXBT": {
+                            "balance": (fixture::BTC_FREE + fixture::BTC_LOCKED).to_string(),
+                            "hold_trade": fixture::BTC_LOCKED.to_string(),
+                        },
+                        "XETH": { "balance": "0", "hold_trade": "0" },
+                    })),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::POST,
+                    "/0/private/AddOrder",
+                    StatusCode::OK,
+                    ok(json!({ "descr": { "order": "buy" }, "txid": [fixture::ORDER_ID] })),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::POST,
+                    "/0/private/QueryOrders",
+                    StatusCode::OK,
+                    ok(json!({ fixture::ORDER_ID: {
+                        "status": "open",
+                        "vol": "1",
+                        "vol_exec": fixture::FILLED.to_string(),
+                        "price": "0",
+                    }})),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::POST,
+                    "/0/private/CancelOrder",
+                    StatusCode::OK,
+                    ok(json!({ "count": 1 })),
+                )
+                .await;
+        }
+
+        fn order_endpoint(&self) -> (Method, &'static str) {
+            (Method::POST, "/0/private/AddOrder")
+        }
+
+        fn cancel_endpoint(&self) -> (Method, &'static str) {
+            (Method::POST, "/0/private/CancelOrder")
+        }
+
+        fn market_data_endpoint(&self) -> (Method, &'static str) {
+            (Method::GET, "/0/public/Ticker")
+        }
+
+        fn is_signed(&self, request: &RecordedRequest) -> bool {
+            let Some(nonce) = request.params().get("nonce").and_then(|n| n.parse().ok()) else {
+                return false;
+            };
+            let adapter = self.adapter("", Arc::new(SymbolRegistry::new()));
+            let expected = adapter.sign(&request.path, nonce, &request.body).unwrap();
+            request.headers.get("API-Key").is_some_and(|k| k == "key")
+                && request
+                    .headers
+                    .get("API-Sign")
+                    .is_some_and(|s| *s == expected)
+        }
+
+        fn sent_order(&self, request: &RecordedRequest) -> (Decimal, Option<Decimal>) {
+            let params = request.params();
+            (
+                params["volume"].parse().unwrap(),
+                params.get("price").map(|p| p.parse().unwrap()),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conformance() {
+        conformance::run_conformance(&Kraken).await;
+    }
+
+    #[test]
+    fn test_asset_names_and_errors() {
+        assert_eq!(normalize_asset("XXBT"), "BTC");
+        assert_eq!(normalize_asset("XBT"), "BTC");
+        assert_eq!(normalize_asset("ZUSD"), "USD");
+        assert_eq!(normalize_asset("USDT"), "USDT");
+
+        let adapter = Kraken.adapter("", Arc::new(SymbolRegistry::new()));
+        assert_eq!(adapter.venue_symbol(&Symbol::new("BTC", "USDT")), "XBTUSDT");
+        assert!(adapter.next_nonce() < adapter.next_nonce());
+
+        assert!(matches!(
+            map_error("EAPI:Rate limit exceeded"),
+            ExchangeError::RateLimited
+        ));
+        assert!(matches!(
+            map_error("EAPI:Invalid key"),
+            ExchangeError::AuthenticationFailed(_)
+        ));
+        assert!(matches!(
+            map_error("EOrder:Insufficient funds"),
+            ExchangeError::OrderRejected(_)
+        ));
+        assert!(matches!(
+            map_error("EService:Unavailable"),
+            ExchangeError::ApiError { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_replace_emulated_and_safe_to_retry() {
+        let venue = MockVenue::start().await;
+        Kraken.mount_fixtures(&venue).await;
+        let adapter = Kraken.adapter(venue.url(), Arc::new(SymbolRegistry::new()));
+        let order = conformance::limit_order(common::Side::Buy, Decimal::ONE, Decimal::new(101, 0));
+        let placed = || venue.request_count(&Method::POST, "/0/private/AddOrder");
+        let unknown_order = json!({ "error": ["EOrder:Unknown order"] });
+
+        adapter.replace_order("OLD", &order).await.unwrap();
+        assert!(venue
+            .last_request(&Method::POST, "/0/private/CancelOrder")
+            .await
+            .is_some());
+        assert_eq!(placed().await, 1);
+
+        // The original is still open: nothing is placed
+        venue
+            .mock_once(
+                Method::POST,
+                "/0/private/CancelOrder",
+                StatusCode::OK,
+                unknown_order.clone(),
+            )
+            .await;
+        assert!(adapter.replace_order("OLD", &order).await.is_err());
+        assert_eq!(placed().await, 1);
+
+        // A retry after the cancel went through only places
+        venue
+            .mock_once(
+                Method::POST,
+                "/0/private/CancelOrder",
+                StatusCode::OK,
+                unknown_order,
+            )
+            .await;
+        venue
+            .mock_once(
+                Method::POST,
+                "/0/private/QueryOrders",
+                StatusCode::OK,
+                json!({ "error": [], "result": { "OLD": {
+                    "status": "canceled",
+                    "vol": "1",
+                    "vol_exec": "0",
+                    "price": "0",
+                }}}),
+            )
+            .await;
+        adapter.replace_order("OLD", &order).await.unwrap();
+        assert_eq!(placed().await, 2);
+    }
+}
@@ -0,0 +1,493 @@
+//! Venue Rate Limiting
+//!
+//! Venues ban keys and IPs that exceed their request limits, so requests
+//! are budgeted locally first. Each venue has a token bucket per endpoint
+//! class (orders, account queries, market data) refilled at the venue's
+//! published rate, and every operation costs its configured weight. When a
+//! bucket runs dry the request queues for the refill, up to `max_wait`,
+//! or under the `reject` mode fails at once; either way the caller sees
+//! `ExchangeError::RateLimited` before the venue does.
+//!
+//! Limits are written `venue.class=requests/window`, e.g.
+//! `binance.order=50/10s,okx.market=20/2s`, and weights
+//! `venue.operation=weight`, e.g. `binance.get_depth=5`. Classes without a
+//! limit are not throttled and operations weigh 1 unless configured.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use super::traits::*;
+use common::{Candle, ExchangeError, MarketData, Order, Symbol, SymbolInfo, Trade};
+
+/// Group of venue endpoints sharing one request budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndpointClass {
+    /// Placing and cancelling orders
+    Order,
+    /// Balances and order status
+    Account,
+    /// Public market and reference data
+    Market,
+}
+
+impl EndpointClass {
+    /// Class of an adapter operation
+    pub fn of(operation: &str) -> Self {
+        match operation {
+            "place_order" | "replace_order" | "cancel_order" => Self::Order,
+            "get_order" | "get_balances" => Self::Account,
+            _ => Self::Market,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Order => "order",
+            Self::Account => "account",
+            Self::Market => "market",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "order" => Some(Self::Order),
+            "account" => Some(Self::Account),
+            "market" => Some(Self::Market),
+            _ => None,
+        }
+    }
+}
+
+/// What to do with a request its bucket cannot cover
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LimitMode {
+    /// Wait for the refill, up to the configured maximum
+    #[default]
+    Queue,
+    /// Fail immediately
+    Reject,
+}
+
+/// `requests` per `window`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limit {
+    pub requests: u32,
+    pub window: Duration,
+}
+
+impl Limit {
+    /// Parse `1200/60s`, `10/1s` or `6000/1m`
+    fn parse(s: &str) -> Option<Self> {
+        let (requests, window) = s.split_once('/')?;
+        let requests: u32 = requests.trim().parse().ok().filter(|r| *r > 0)?;
+        let window = window.trim();
+        let secs: u64 = if let Some(m) = window.strip_suffix('m') {
+            m.parse::<u64>().ok()? * 60
+        } else {
+            window.strip_suffix('s').unwrap_or(window).parse().ok()?
+        };
+        (secs > 0).then(|| Self {
+            requests,
+            window: Duration::from_secs(secs),
+        })
+    }
+
+    fn per_sec(&self) -> f64 {
+        self.requests as f64 / self.window.as_secs_f64()
+    }
+}
+
+/// Token bucket holding up to a limit's requests
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    fn new(limit: &Limit, now: Instant) -> Self {
+        Self {
+            tokens: limit.requests as f64,
+            refilled_at: now,
+        }
+    }
+
+    /// Reserve `weight` tokens, returning how long to wait before using them
+    ///
+    /// Queued reservations borrow against the refill, so later requests
+    /// wait behind earlier ones. Returns `None`, reserving nothing, when
+    /// the wait would exceed `max_wait`.
+    fn reserve(
+        &mut self,
+        weight: u32,
+        limit: &Limit,
+        max_wait: Duration,
+        now: Instant,
+    ) -> Option<Duration> {
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.per_sec()).min(limit.requests as f64);
+        self.refilled_at = now;
+
+        // A request heavier than the whole budget still gets through alone
+        let weight = weight.min(limit.requests) as f64;
+        let wait = Duration::from_secs_f64(((weight - self.tokens) / limit.per_sec()).max(0.0));
+        if wait > max_wait {
+            return None;
+        }
+        self.tokens -= weight;
+        Some(wait)
+    }
+}
+
+/// Per-venue limits and operation weights
+#[derive(Debug, Clone, Default)]
+pub struct RateLimits {
+    limits: HashMap<(String, EndpointClass), Limit>,
+    weights: HashMap<(String, String), u32>,
+}
+
+impl RateLimits {
+    /// Parse comma-separated limits and weights, e.g. from config
+    pub fn parse(limits: &str, weights: &str) -> Result<Self, String> {
+        let entries = |spec: &str| -> Result<Vec<(String, String, String)>, String> {
+            spec.split(',')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .map(|entry| {
+                    let (key, value) = entry
+                        .split_once('=')
+                        .ok_or_else(|| format!("expected `venue.key=value`, got `{entry}`"))?;
+                    let (venue, key) = key
+                        .trim()
+                        .split_once('.')
+                        .ok_or_else(|| format!("expected `venue.key=value`, got `{entry}`"))?;
+                    Ok((
+                        venue.to_lowercase(),
+                        key.to_string(),
+                        value.trim().to_string(),
+                    ))
+                })
+                .collect()
+        };
+
+        let mut parsed = Self::default();
+        for (venue, class, limit) in entries(limits)? {
+            let class = EndpointClass::parse(&class)
+                .ok_or_else(|| format!("unknown endpoint class `{class}` for {venue}"))?;
+            let limit = Limit::parse(&limit)
+                .ok_or_else(|| format!("invalid limit `{limit}` for {venue}.{}", class.as_str()))?;
+            parsed.limits.insert((venue, class), limit);
+        }
+        for (venue, operation, weight) in entries(weights)? {
+            let weight = weight
+                .parse()
+                .ok()
+                .filter(|w| *w > 0)
+                .ok_or_else(|| format!("invalid weight `{weight}` for {venue}.{operation}"))?;
+            parsed.weights.insert((venue, operation), weight);
+        }
+        Ok(parsed)
+    }
+
+    /// Limiter for `venue`, to share between all of its adapters
+    pub fn limiter(
+        &self,
+        venue: &'static str,
+        mode: LimitMode,
+        max_wait: Duration,
+    ) -> Arc<VenueLimiter> {
+        let now = Instant::now();
+        let buckets = self
+            .limits
+            .iter()
+            .filter(|((v, _), _)| v == venue)
+            .map(|((_, class), limit)| (*class, (*limit, Mutex::new(Bucket::new(limit, now)))))
+            .collect();
+        let weights = self
+            .weights
+            .iter()
+            .filter(|((v, _), _)| v == venue)
+            .map(|((_, operation), weight)| (operation.clone(), *weight))
+            .collect();
+
+        Arc::new(VenueLimiter {
+            venue,
+            buckets,
+            weights,
+            max_wait: match mode {
+                LimitMode::Queue => max_wait,
+                LimitMode::Reject => Duration::ZERO,
+            },
+        })
+    }
+}
+
+/// Request budget of one venue
+pub struct VenueLimiter {
+    venue: &'static str,
+    buckets: HashMap<EndpointClass, (Limit, Mutex<Bucket>)>,
+    weights: HashMap<String, u32>,
+    max_wait: Duration,
+}
+
+impl VenueLimiter {
+    fn weight(&self, operation: &str) -> u32 {
+        self.weights.get(operation).copied().unwrap_or(1)
+    }
+
+    /// Wait until `operation` fits the venue's budget, or fail if it will not soon
+    pub async fn acquire(&self, operation: &'static str) -> ExchangeResult<()> {
+        let class = EndpointClass::of(operation);
+        let Some((limit, bucket)) = self.buckets.get(&class) else {
+            return Ok(());
+        };
+
+        let reserved =
+            bucket
+                .lock()
+                .reserve(self.weight(operation), limit, self.max_wait, Instant::now());
+        let outcome = match reserved {
+            None => "rejected",
+            Some(wait) if wait.is_zero() => return Ok(()),
+            Some(_) => "queued",
+        };
+        metrics::counter!(
+            "venue_requests_throttled",
+            "exchange" => self.venue,
+            "class" => class.as_str(),
+            "outcome" => outcome
+        )
+        .increment(1);
+
+        match reserved {
+            Some(wait) => {
+                tokio::time::sleep(wait).await;
+                Ok(())
+            }
+            None => Err(ExchangeError::RateLimited),
+        }
+    }
+}
+
+/// Adapter whose requests are held to its venue's rate limits
+pub struct Throttled<A> {
+    limiter: Arc<VenueLimiter>,
+    inner: A,
+}
+
+impl<A> Throttled<A> {
+    pub fn new(limiter: Arc<VenueLimiter>, inner: A) -> Self {
+        Self { limiter, inner }
+    }
+}
+
+#[async_trait]
+impl<A: ExchangeAdapter> ExchangeAdapter for Throttled<A> {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    async fn is_available(&self) -> bool {
+        self.inner.is_available().await
+    }
+
+    async fn sync_clock(&self) -> ExchangeResult<Option<i64>> {
+        self.limiter.acquire("sync_clock").await?;
+        self.inner.sync_clock().await
+    }
+
+    async fn get_symbols(&self) -> ExchangeResult<Vec<Symbol>> {
+        self.limiter.acquire("get_symbols").await?;
+        self.inner.get_symbols().await
+    }
+
+    async fn get_symbol_info(&self) -> ExchangeResult<Vec<SymbolInfo>> {
+        self.limiter.acquire("get_symbol_info").await?;
+        self.inner.get_symbol_info().await
+    }
+
+    async fn get_market_data(&self, symbol: &Symbol) -> ExchangeResult<MarketData> {
+        self.limiter.acquire("get_market_data").await?;
+        self.inner.get_market_data(symbol).await
+    }
+
+    async fn get_balances(&self) -> ExchangeResult<Vec<ExchangeBalance>> {
+        self.limiter.acquire("get_balances").await?;
+        self.inner.get_balances().await
+    }
+
+    async fn place_order(&self, order: &Order) -> ExchangeResult<ExchangeOrder> {
+        self.limiter.acquire("place_order").await?;
+        self.inner.place_order(order).await
+    }
+
+    async fn replace_order(&self, order_id: &str, order: &Order) -> ExchangeResult<ExchangeOrder> {
+        self.limiter.acquire("replace_order").await?;
+        self.inner.replace_order(order_id, order).await
+    }
+
+    async fn cancel_order(&self, symbol: &Symbol, order_id: &str) -> ExchangeResult<()> {
+        self.limiter.acquire("cancel_order").await?;
+        self.inner.cancel_order(symbol, order_id).await
+    }
+
+    async fn get_order(&self, symbol: &Symbol, order_id: &str) -> ExchangeResult<ExchangeOrder> {
+        self.limiter.acquire("get_order").await?;
+        self.inner.get_order(symbol, order_id).await
+    }
+
+    async fn get_trades(&self, symbol: &Symbol, limit: u32) -> ExchangeResult<Vec<Trade>> {
+        self.limiter.acquire("get_trades").await?;
+        self.inner.get_trades(symbol, limit).await
+    }
+
+    async fn get_depth(&self, symbol: &Symbol, limit: u32) -> ExchangeResult<OrderBookDepth> {
+        self.limiter.acquire("get_depth").await?;
+        self.inner.get_depth(symbol, limit).await
+    }
+
+    async fn get_klines(
+        &self,
+        symbol: &Symbol,
+        interval: &str,
+        start: DateTime<Utc>,
+        limit: u32,
+    ) -> ExchangeResult<Vec<Candle>> {
+        self.limiter.acquire("get_klines").await?;
+        self.inner.get_klines(symbol, interval, start, limit).await
+    }
+}
+
+#[async_trait]
+impl<A: RateSource> RateSource for Throttled<A> {
+    async fn get_funding_rates(
+        &self,
+        symbol: &Symbol,
+        since: DateTime<Utc>,
+    ) -> ExchangeResult<Vec<FundingRate>> {
+        self.limiter.acquire("get_funding_rates").await?;
+        self.inner.get_funding_rates(symbol, since).await
+    }
+
+    async fn get_borrow_rates(
+        &self,
+        asset: &str,
+        since: DateTime<Utc>,
+    ) -> ExchangeResult<Vec<BorrowRate>> {
+        self.limiter.acquire("get_borrow_rates").await?;
+        self.inner.get_borrow_rates(asset, since).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(requests: u32, secs: u64) -> Limit {
+        Limit {
+            requests,
+            window: Duration::from_secs(secs),
+        }
+    }
+
+    #[test]
+    fn test_parse_limits_and_weights() {
+        let limits = RateLimits::parse(
+            "binance.order=50/10s, binance.market=6000/1m,okx.account=10/2",
+            "binance.get_depth=5",
+        )
+        .unwrap();
+        assert_eq!(
+            limits.limits[&("binance".to_string(), EndpointClass::Order)],
+            limit(50, 10)
+        );
+        assert_eq!(
+            limits.limits[&("binance".to_string(), EndpointClass::Market)],
+            limit(6000, 60)
+        );
+        assert_eq!(
+            limits.limits[&("okx".to_string(), EndpointClass::Account)],
+            limit(10, 2)
+        );
+
+        let binance = limits.limiter("binance", LimitMode::Queue, Duration::ZERO);
+        assert_eq!(binance.weight("get_depth"), 5);
+        assert_eq!(binance.weight("get_trades"), 1);
+        assert!(!binance.buckets.contains_key(&EndpointClass::Account));
+
+        assert!(RateLimits::parse("binance.orders=1/1s", "").is_err());
+        assert!(RateLimits::parse("binance.order=0/1s", "").is_err());
+        assert!(RateLimits::parse("", "binance.get_depth=x").is_err());
+    }
+
+    #[test]
+    fn test_operations_map_to_classes() {
+        assert_eq!(EndpointClass::of("place_order"), EndpointClass::Order);
+        assert_eq!(EndpointClass::of("cancel_order"), EndpointClass::Order);
+        assert_eq!(EndpointClass::of("get_balances"), EndpointClass::Account);
+        assert_eq!(EndpointClass::of("get_depth"), EndpointClass::Market);
+    }
+
+    #[test]
+    fn test_bucket_rejects_once_spent_without_queueing() {
+        let limit = limit(2, 1);
+        let now = Instant::now();
+        let mut bucket = Bucket::new(&limit, now);
+
+        assert_eq!(
+            bucket.reserve(1, &limit, Duration::ZERO, now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(
+            bucket.reserve(1, &limit, Duration::ZERO, now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(bucket.reserve(1, &limit, Duration::ZERO, now), None);
+
+        // Refilled at 2 per second
+        let later = now + Duration::from_millis(500);
+        assert_eq!(
+            bucket.reserve(1, &limit, Duration::ZERO, later),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(bucket.reserve(1, &limit, Duration::ZERO, later), None);
+    }
+
+    #[test]
+    fn test_queued_requests_wait_behind_each_other() {
+        let limit = limit(10, 1);
+        let now = Instant::now();
+        let mut bucket = Bucket::new(&limit, now);
+        let max_wait = Duration::from_secs(1);
+
+        assert_eq!(
+            bucket.reserve(10, &limit, max_wait, now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(
+            bucket.reserve(5, &limit, max_wait, now),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(
+            bucket.reserve(5, &limit, max_wait, now),
+            Some(Duration::from_secs(1))
+        );
+        // Would wait 1.5s
+        assert_eq!(bucket.reserve(5, &limit, max_wait, now), None);
+
+        // Oversized requests are capped at the whole budget
+        let later = now + Duration::from_secs(5);
+        assert_eq!(
+            bucket.reserve(50, &limit, max_wait, later),
+            Some(Duration::ZERO)
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! Metered Adapter
+//!
+//! Wraps any adapter and records request counts, latency and outcome per
+//! venue and operation. The router wraps every adapter it registers, so
+//! adapters themselves stay free of metrics code.
+
+use std::future::Future;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use super::traits::*;
+use common::{Candle, MarketData, Order, Symbol, SymbolInfo, Trade};
+
+pub struct Metered<A> {
+    venue: &'static str,
+    inner: A,
+}
+
+impl<A> Metered<A> {
+    pub fn new(venue: &'static str, inner: A) -> Self {
+        Self { venue, inner }
+    }
+
+    /// Run one venue request, recording its latency and outcome
+    async fn observe<T>(
+        &self,
+        operation: &'static str,
+        request: impl Future<Output = ExchangeResult<T>>,
+    ) -> ExchangeResult<T> {
+        let start = Instant::now();
+        let result = request.await;
+
+        metrics::histogram!(
+            "exchange_request_latency_us",
+            "exchange" => self.venue,
+            "operation" => operation
+        )
+        .record(start.elapsed().as_micros() as f64);
+        metrics::counter!(
+            "exchange_requests",
+            "exchange" => self.venue,
+            "operation" => operation,
+            "outcome" => outcome(&result)
+        )
+        .increment(1);
+
+        result
+    }
+}
+
+fn outcome<T>(result: &ExchangeResult<T>) -> &'static str {
+    if result.is_ok() {
+        "ok"
+    } else {
+        "error"
+    }
+}
+
+#[async_trait]
+impl<A: ExchangeAdapter> ExchangeAdapter for Metered<A> {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    async fn is_available(&self) -> bool {
+        let available = self.inner.is_available().await;
+
+        metrics::counter!(
+            "exchange_health_checks",
+            "exchange" => self.venue,
+            "result" => if available { "up" } else { "down" }
+        )
+        .increment(1);
+        metrics::gauge!("exchange_available", "exchange" => self.venue).set(if available {
+            1.0
+        } else {
+            0.0
+        });
+
+        available
+    }
+
+    async fn sync_clock(&self) -> ExchangeResult<Option<i64>> {
+        self.observe("sync_clock", self.inner.sync_clock()).await
+    }
+
+    async fn get_symbols(&self) -> ExchangeResult<Vec<Symbol>> {
+        self.observe("get_symbols", self.inner.get_symbols()).await
+    }
+
+    async fn get_symbol_info(&self) -> ExchangeResult<Vec<SymbolInfo>> {
+        self.observe("get_symbol_info", self.inner.get_symbol_info())
+            .await
+    }
+
+    async fn get_market_data(&self, symbol: &Symbol) -> ExchangeResult<MarketData> {
+        self.observe("get_market_data", self.inner.get_market_data(symbol))
+            .await
+    }
+
+    async fn get_balances(&self) -> ExchangeResult<Vec<ExchangeBalance>> {
+        self.observe("get_balances", self.inner.get_balances())
+            .await
+    }
+
+    async fn place_order(&self, order: &Order) -> ExchangeResult<ExchangeOrder> {
+        let result = self
+            .observe("place_order", self.inner.place_order(order))
+            .await;
+
+        metrics::counter!(
+            "orders_placed",
+            "exchange" => self.venue,
+            "outcome" => outcome(&result)
+        )
+        .increment(1);
+
+        result
+    }
+
+    async fn replace_order(&self, order_id: &str, order: &Order) -> ExchangeResult<ExchangeOrder> {
+        self.observe("replace_order", self.inner.replace_order(order_id, order))
+            .await
+    }
+
+    async fn cancel_order(&self, symbol: &Symbol, order_id: &str) -> ExchangeResult<()> {
+        self.observe("cancel_order", self.inner.cancel_order(symbol, order_id))
+            .await
+    }
+
+    async fn get_order(&self, symbol: &Symbol, order_id: &str) -> ExchangeResult<ExchangeOrder> {
+        self.observe("get_order", self.inner.get_order(symbol, order_id))
+            .await
+    }
+
+    async fn get_trades(&self, symbol: &Symbol, limit: u32) -> ExchangeResult<Vec<Trade>> {
+        self.observe("get_trades", self.inner.get_trades(symbol, limit))
+            .await
+    }
+
+    async fn get_depth(&self, symbol: &Symbol, limit: u32) -> ExchangeResult<OrderBookDepth> {
+        self.observe("get_depth", self.inner.get_depth(symbol, limit))
+            .await
+    }
+
+    async fn get_klines(
+        &self,
+        symbol: &Symbol,
+        interval: &str,
+        start: DateTime<Utc>,
+        limit: u32,
+    ) -> ExchangeResult<Vec<Candle>> {
+        self.observe(
+            "get_klines",
+            self.inner.get_klines(symbol, interval, start, limit),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<A: DexAdapter> DexAdapter for Metered<A> {
+    async fn get_quote(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_in: Decimal,
+    ) -> ExchangeResult<Decimal> {
+        self.observe(
+            "get_quote",
+            self.inner.get_quote(token_in, token_out, amount_in),
+        )
+        .await
+    }
+
+    async fn get_route_quote(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_in: Decimal,
+    ) -> ExchangeResult<RouteQuote> {
+        self.observe(
+            "get_route_quote",
+            self.inner.get_route_quote(token_in, token_out, amount_in),
+        )
+        .await
+    }
+
+    async fn get_limited_quote(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_in: Decimal,
+        limits: &QuoteLimits,
+    ) -> ExchangeResult<RouteQuote> {
+        self.observe(
+            "get_limited_quote",
+            self.inner
+                .get_limited_quote(token_in, token_out, amount_in, limits),
+        )
+        .await
+    }
+
+    async fn swap(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_in: Decimal,
+        min_amount_out: Decimal,
+        deadline: u64,
+    ) -> ExchangeResult<String> {
+        let result = self
+            .observe(
+                "swap",
+                self.inner
+                    .swap(token_in, token_out, amount_in, min_amount_out, deadline),
+            )
+            .await;
+
+        metrics::counter!(
+            "swaps_executed",
+            "exchange" => self.venue,
+            "outcome" => outcome(&result)
+        )
+        .increment(1);
+
+        result
+    }
+
+    async fn get_pool_info(&self, token_a: &str, token_b: &str) -> ExchangeResult<PoolInfo> {
+        self.observe("get_pool_info", self.inner.get_pool_info(token_a, token_b))
+            .await
+    }
+}
+
+#[async_trait]
+impl<A: RateSource> RateSource for Metered<A> {
+    async fn get_funding_rates(
+        &self,
+        symbol: &Symbol,
+        since: DateTime<Utc>,
+    ) -> ExchangeResult<Vec<FundingRate>> {
+        self.observe(
+            "get_funding_rates",
+            self.inner.get_funding_rates(symbol, since),
+        )
+        .await
+    }
+
+    async fn get_borrow_rates(
+        &self,
+        asset: &str,
+        since: DateTime<Utc>,
+    ) -> ExchangeResult<Vec<BorrowRate>> {
+        self.observe(
+            "get_borrow_rates",
+            self.inner.get_borrow_rates(asset, since),
+        )
+        .await
+    }
+}
@@ -2,10 +2,36 @@
 //!
 //! Unified interface for different exchanges and protocols
 
+pub mod aave;
+pub mod approvals;
 pub mod binance;
+pub mod binance_stream;
+pub mod breaker;
+pub mod bybit;
+pub mod clock;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod conformance;
+pub mod kraken;
+pub mod limiter;
+pub mod metered;
+pub mod okx;
+pub mod retry;
+pub mod sushiswap;
 pub mod traits;
 pub mod uniswap;
 
+pub use aave::AaveAdapter;
+pub use approvals::{ApprovalPolicy, Approvals};
 pub use binance::BinanceAdapter;
+pub use binance_stream::BinanceStreams;
+pub use breaker::{BreakerStatus, CircuitBreaker, Guarded};
+pub use bybit::BybitAdapter;
+pub use clock::VenueClock;
+pub use kraken::KrakenAdapter;
+pub use limiter::{LimitMode, RateLimits, Throttled};
+pub use metered::Metered;
+pub use okx::OkxAdapter;
+pub use retry::RetryPolicy;
+pub use sushiswap::SushiswapAdapter;
 pub use traits::*;
 pub use uniswap::UniswapAdapter;
@@ -0,0 +1,924 @@
+//! OKX Exchange Adapter
+//!
+//! Integration with OKX v5 spot trading API. Private requests are signed
+//! with the API secret over timestamp, method, path and body, and carry
+//! the passphrase chosen when the key was created.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{SecondsFormat, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Method};
+use rust_decimal::Decimal;
+use sha2::Sha256;
+use tracing::{debug, info, warn};
+
+use super::clock::VenueClock;
+use super::retry::{Recovery, RetryPolicy};
+use super::traits::*;
+use common::{
+    ExchangeError, MarketData, Order, OrderStatus, Side, Symbol, SymbolInfo, SymbolRegistry,
+    SymbolStatus, Trade,
+};
+
+const OKX_API_URL: &str = "https://www.okx.com";
+
+/// Longest client order id OKX accepts
+const MAX_CLIENT_ORDER_ID_LEN: usize = 32;
+
+/// Spot instrument from `/api/v5/public/instruments`
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OkxInstrument {
+    inst_id: String,
+    base_ccy: String,
+    quote_ccy: String,
+    state: String,
+    tick_sz: String,
+    lot_sz: String,
+    #[serde(default)]
+    min_sz: String,
+}
+
+impl OkxInstrument {
+    fn symbol(&self) -> Symbol {
+        Symbol::new(&self.base_ccy, &self.quote_ccy)
+    }
+
+    fn to_symbol_info(&self) -> SymbolInfo {
+        let increment = |value: &str| {
+            value
+                .parse::<Decimal>()
+                .ok()
+                .filter(|v| !v.is_zero())
+                .map(|v| v.normalize())
+        };
+        let tick_size = increment(&self.tick_sz);
+        let lot_size = increment(&self.lot_sz);
+
+        SymbolInfo {
+            symbol: self.symbol(),
+            base_asset: self.base_ccy.clone(),
+            quote_asset: self.quote_ccy.clone(),
+            status: match self.state.as_str() {
+                "live" => SymbolStatus::Trading,
+                "suspend" | "preopen" => SymbolStatus::Halted,
+                "expired" => SymbolStatus::Delisted,
+                _ => SymbolStatus::Unknown,
+            },
+            price_precision: tick_size.map(|t| t.scale()).unwrap_or(8),
+            quantity_precision: lot_size.map(|l| l.scale()).unwrap_or(8),
+            tick_size,
+            lot_size,
+            min_quantity: increment(&self.min_sz),
+            venues: HashMap::from([("okx".to_string(), self.inst_id.clone())]),
+        }
+    }
+}
+
+/// Order from `/api/v5/trade/order`
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OkxOrder {
+    ord_id: String,
+    #[serde(default)]
+    cl_ord_id: String,
+    state: String,
+    #[serde(default)]
+    acc_fill_sz: String,
+    #[serde(default)]
+    avg_px: String,
+}
+
+pub struct OkxAdapter {
+    client: Client,
+    api_key: String,
+    api_secret: String,
+    passphrase: String,
+
+    /// API root, overridable for testing
+    base_url: String,
+
+    /// Shared reference data for venue symbol mapping
+    symbols: Arc<SymbolRegistry>,
+
+    /// Retries for transient venue errors
+    retry: RetryPolicy,
+
+    /// Venue clock for signed request timestamps, shared across accounts
+    clock: Arc<VenueClock>,
+}
+
+impl OkxAdapter {
+    pub fn new(
+        api_key: String,
+        api_secret: String,
+        passphrase: String,
+        symbols: Arc<SymbolRegistry>,
+    ) -> Self {
+        Self {
+            client: Client::new(),
+            api_key,
+            api_secret,
+            passphrase,
+            base_url: OKX_API_URL.to_string(),
+            symbols,
+            retry: RetryPolicy::default(),
+            clock: Arc::new(VenueClock::new()),
+        }
+    }
+
+    /// Share a venue clock with other OKX adapters
+    pub fn with_clock(mut self, clock: Arc<VenueClock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Point requests at another API root
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// OKX instrument for `symbol`, e.g. `BTC-USDT`
+    fn venue_symbol(&self, symbol: &Symbol) -> String {
+        self.symbols
+            .get(symbol)
+            .and_then(|info| info.venues.get("okx").cloned())
+            .unwrap_or_else(|| format!("{}-{}", symbol.base(), symbol.quote()))
+    }
+
+    /// `OK-ACCESS-SIGN`: base64 HMAC-SHA256 of timestamp, method, request
+    /// path with query, and body
+    fn sign(&self, timestamp: &str, method: &str, request_path: &str, body: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.api_secret.as_bytes())
+            .expect("HMAC can take key of any size");
+        mac.update(format!("{timestamp}{method}{request_path}{body}").as_bytes());
+        BASE64.encode(mac.finalize().into_bytes())
+    }
+
+    /// Venue time in the ISO 8601 form OKX expects
+    fn timestamp(&self) -> String {
+        Utc.timestamp_millis_opt(self.clock.now_ms())
+            .single()
+            .unwrap_or_else(Utc::now)
+            .to_rfc3339_opts(SecondsFormat::Millis, true)
+    }
+
+    async fn instruments(&self) -> ExchangeResult<Vec<OkxInstrument>> {
+        self.public_request("/api/v5/public/instruments?instType=SPOT")
+            .await
+    }
+
+    async fn public_request<T: serde::de::DeserializeOwned>(
+        &self,
+        endpoint: &str,
+    ) -> ExchangeResult<T> {
+        let url = format!("{}{endpoint}", self.base_url);
+        self.execute(|| self.client.get(&url)).await
+    }
+
+    /// Signed request; `request_path` includes any query string and `body`
+    /// is sent as JSON
+    async fn signed_request<T: serde::de::DeserializeOwned>(
+        &self,
+        method: Method,
+        request_path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> ExchangeResult<T> {
+        let url = format!("{}{request_path}", self.base_url);
+        let body = body.map(|b| b.to_string()).unwrap_or_default();
+
+        // Timestamp and signature are renewed on every attempt
+        self.execute(|| {
+            let timestamp = self.timestamp();
+            let signature = self.sign(&timestamp, method.as_str(), request_path, &body);
+
+            let request = self
+                .client
+                .request(method.clone(), &url)
+                .header("OK-ACCESS-KEY", &self.api_key)
+                .header("OK-ACCESS-SIGN", signature)
+                .header("OK-ACCESS-TIMESTAMP", timestamp)
+                .header("OK-ACCESS-PASSPHRASE", &self.passphrase);
+            if body.is_empty() {
+                request
+            } else {
+                request
+                    .header("Content-Type", "application/json")
+                    .body(body.clone())
+            }
+        })
+        .await
+    }
+
+    /// Send a request, retrying transient failures under the retry policy
+    async fn execute<T: serde::de::DeserializeOwned>(
+        &self,
+        request: impl Fn() -> reqwest::RequestBuilder,
+    ) -> ExchangeResult<T> {
+        let mut attempt = 1;
+        loop {
+            let (error, recovery) = match request().send().await {
+                Ok(response) => match read_response(response).await {
+                    Ok(value) => return Ok(value),
+                    Err(error) => {
+                        let recovery = recovery(&error);
+                        (error, recovery)
+                    }
+                },
+                // The request never reached the venue
+                Err(e) if e.is_connect() => (
+                    ExchangeError::ConnectionFailed(e.to_string()),
+                    Recovery::Backoff(None),
+                ),
+                Err(e) => return Err(ExchangeError::ConnectionFailed(e.to_string())),
+            };
+
+            if recovery == Recovery::Fail || !self.retry.allows_retry(attempt) {
+                return Err(error);
+            }
+
+            warn!(attempt, error = %error, reason = recovery.reason(), "Retrying OKX request");
+            metrics::counter!(
+                "exchange_retries",
+                "exchange" => "okx",
+                "reason" => recovery.reason()
+            )
+            .increment(1);
+
+            match recovery {
+                Recovery::Backoff(hint) => {
+                    tokio::time::sleep(self.retry.delay(attempt, hint)).await;
+                }
+                Recovery::ResyncClock => {
+                    if let Err(e) = self.resync_clock().await {
+                        warn!("OKX clock resync failed: {}", e);
+                        return Err(error);
+                    }
+                }
+                Recovery::Fail => unreachable!("permanent errors are returned above"),
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Measure the venue clock offset from `/api/v5/public/time`
+    async fn resync_clock(&self) -> ExchangeResult<i64> {
+        #[derive(serde::Deserialize)]
+        struct ServerTime {
+            ts: String,
+        }
+
+        let sent_at = Utc::now().timestamp_millis();
+        let response = self
+            .client
+            .get(format!("{}/api/v5/public/time", self.base_url))
+            .send()
+            .await
+            .map_err(|e| ExchangeError::ConnectionFailed(e.to_string()))?;
+        let times: Vec<ServerTime> = read_response(response).await?;
+        let received_at = Utc::now().timestamp_millis();
+
+        let server_time = times
+            .first()
+            .and_then(|t| t.ts.parse().ok())
+            .ok_or_else(|| ExchangeError::ApiError {
+                code: -1,
+                message: "Server time missing".to_string(),
+            })?;
+        let offset = self.clock.observe(server_time, sent_at, received_at);
+        debug!(offset_ms = offset, "Resynced OKX clock");
+        Ok(offset)
+    }
+}
+
+/// Recovery for a failed OKX request
+///
+/// 50102 means the request timestamp expired; 50011 and 50061 are rate
+/// limits. Other errors, including 5xx whose outcome is unknown, are final.
+fn recovery(error: &ExchangeError) -> Recovery {
+    match error {
+        ExchangeError::ApiError { code: 50102, .. } => Recovery::ResyncClock,
+        ExchangeError::RateLimited => Recovery::Backoff(None),
+        _ => Recovery::Fail,
+    }
+}
+
+/// Map an OKX error code onto `ExchangeError`
+fn map_error(code: i32, message: String) -> ExchangeError {
+    match code {
+        50011 | 50061 => ExchangeError::RateLimited,
+        50101 | 50103..=50105 | 50110 | 50111 | 50113 | 50114 => {
+            ExchangeError::AuthenticationFailed(message)
+        }
+        51000..=51999 => ExchangeError::OrderRejected(message),
+        code => ExchangeError::ApiError { code, message },
+    }
+}
+
+/// Decode an OKX response, mapping failures onto `ExchangeError`
+///
+/// Error statuses usually carry an OKX code, which takes precedence over
+/// the status; an expired timestamp comes back as 401 with code 50102.
+/// Responses wrap their `data` in `{code, msg}`, code "0" meaning success.
+/// Order requests refused individually carry the reason in the first
+/// entry's `sCode`/`sMsg`.
+async fn read_response<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+) -> ExchangeResult<T> {
+    #[derive(serde::Deserialize)]
+    struct OkxResponse {
+        code: String,
+        #[serde(default)]
+        msg: String,
+        #[serde(default)]
+        data: serde_json::Value,
+    }
+
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    let body = serde_json::from_str::<OkxResponse>(&text).ok();
+
+    if !status.is_success() {
+        let code = body.as_ref().and_then(|b| b.code.parse::<i32>().ok());
+        return Err(match (status.as_u16(), code, body) {
+            (429, _, _) => ExchangeError::RateLimited,
+            (_, Some(code), Some(body)) => map_error(code, body.msg),
+            (401 | 403, _, _) => ExchangeError::AuthenticationFailed(text),
+            (status, _, _) => ExchangeError::ApiError {
+                code: status as i32,
+                message: text,
+            },
+        });
+    }
+
+    let body = body.ok_or_else(|| ExchangeError::ApiError {
+        code: -1,
+        message: text.clone(),
+    })?;
+    if body.code != "0" {
+        let detail = &body.data[0];
+        let (code, message) = match detail["sCode"].as_str() {
+            Some(code) if code != "0" => (code, detail["sMsg"].as_str().unwrap_or_default()),
+            _ => (body.code.as_str(), body.msg.as_str()),
+        };
+        return Err(map_error(code.parse().unwrap_or(-1), message.to_string()));
+    }
+
+    serde_json::from_value(body.data).map_err(|e| ExchangeError::ApiError {
+        code: -1,
+        message: e.to_string(),
+    })
+}
+
+/// OKX accepts only alphanumeric client order ids of up to 32 characters
+fn venue_client_order_id(client_order_id: &str) -> String {
+    client_order_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .take(MAX_CLIENT_ORDER_ID_LEN)
+        .collect()
+}
+
+/// Order status from OKX's order `state`
+fn order_status(state: &str) -> OrderStatus {
+    match state {
+        "partially_filled" => OrderStatus::PartiallyFilled,
+        "filled" => OrderStatus::Filled,
+        "canceled" | "mmp_canceled" => OrderStatus::Cancelled,
+        // live and anything unknown are still working
+        _ => OrderStatus::Open,
+    }
+}
+
+fn parse_decimal(value: &str) -> Option<Decimal> {
+    value.parse().ok()
+}
+
+#[async_trait]
+impl ExchangeAdapter for OkxAdapter {
+    fn name(&self) -> &'static str {
+        "OKX"
+    }
+
+    async fn is_available(&self) -> bool {
+        self.public_request::<serde_json::Value>("/api/v5/public/time")
+            .await
+            .is_ok()
+    }
+
+    async fn sync_clock(&self) -> ExchangeResult<Option<i64>> {
+        self.resync_clock().await.map(Some)
+    }
+
+    async fn get_symbols(&self) -> ExchangeResult<Vec<Symbol>> {
+        Ok(self
+            .instruments()
+            .await?
+            .iter()
+            .filter(|i| i.state == "live")
+            .map(OkxInstrument::symbol)
+            .collect())
+    }
+
+    async fn get_symbol_info(&self) -> ExchangeResult<Vec<SymbolInfo>> {
+        Ok(self
+            .instruments()
+            .await?
+            .iter()
+            .map(OkxInstrument::to_symbol_info)
+            .collect())
+    }
+
+    async fn get_market_data(&self, symbol: &Symbol) -> ExchangeResult<MarketData> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Ticker {
+            last: String,
+            ask_px: String,
+            bid_px: String,
+            vol24h: String,
+            high24h: String,
+            low24h: String,
+            #[serde(default)]
+            open24h: String,
+        }
+
+        let inst_id = self.venue_symbol(symbol);
+        let tickers: Vec<Ticker> = self
+            .public_request(&format!("/api/v5/market/ticker?instId={inst_id}"))
+            .await?;
+        let ticker = tickers
+            .into_iter()
+            .next()
+            .ok_or_else(|| ExchangeError::ApiError {
+                code: -1,
+                message: format!("No ticker for {inst_id}"),
+            })?;
+
+        let last = ticker.last.parse().unwrap_or_default();
+        let (price_change_24h, percent_change_24h) =
+            MarketData::price_change(ticker.open24h.parse().unwrap_or_default(), last);
+        Ok(MarketData {
+            symbol: symbol.clone(),
+            bid: ticker.bid_px.parse().unwrap_or_default(),
+            ask: ticker.ask_px.parse().unwrap_or_default(),
+            last,
+            volume_24h: ticker.vol24h.parse().unwrap_or_default(),
+            high_24h: ticker.high24h.parse().unwrap_or_default(),
+            low_24h: ticker.low24h.parse().unwrap_or_default(),
+            price_change_24h,
+            percent_change_24h,
+            fair_value: None,
+            timestamp: Utc::now(),
+        })
+    }
+
+    async fn get_balances(&self) -> ExchangeResult<Vec<ExchangeBalance>> {
+        #[derive(serde::Deserialize)]
+        struct Account {
+            details: Vec<Detail>,
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Detail {
+            ccy: String,
+            avail_bal: String,
+            #[serde(default)]
+            frozen_bal: String,
+        }
+
+        let accounts: Vec<Account> = self
+            .signed_request(Method::GET, "/api/v5/account/balance", None)
+            .await?;
+
+        Ok(accounts
+            .into_iter()
+            .flat_map(|a| a.details)
+            .map(|d| ExchangeBalance {
+                asset: d.ccy,
+                free: d.avail_bal.parse().unwrap_or_default(),
+                locked: d.frozen_bal.parse().unwrap_or_default(),
+            })
+            .filter(|b| b.free > Decimal::ZERO || b.locked > Decimal::ZERO)
+            .collect())
+    }
+
+    async fn place_order(&self, order: &Order) -> ExchangeResult<ExchangeOrder> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Placed {
+            ord_id: String,
+            #[serde(default)]
+            cl_ord_id: String,
+        }
+
+        let (quantity, price) = order_precision(&self.symbols, order)?;
+        let mut body = serde_json::json!({
+            "instId": self.venue_symbol(&order.symbol),
+            "tdMode": "cash",
+            "clOrdId": venue_client_order_id(&order.client_order_id),
+            "side": match order.side {
+                Side::Buy => "buy",
+                Side::Sell => "sell",
+            },
+            "sz": quantity.to_string(),
+        });
+        match price {
+            Some(price) => {
+                body["ordType"] = "limit".into();
+                body["px"] = price.to_string().into();
+            }
+            None => {
+                // Market buys are sized in the quote asset unless told otherwise
+                body["ordType"] = "market".into();
+                body["tgtCcy"] = "base_ccy".into();
+            }
+        }
+
+        let placed: Vec<Placed> = self
+            .signed_request(Method::POST, "/api/v5/trade/order", Some(&body))
+            .await?;
+        let placed = placed
+            .into_iter()
+            .next()
+            .ok_or_else(|| ExchangeError::ApiError {
+                code: -1,
+                message: "Order response has no data".to_string(),
+            })?;
+
+        info!(order_id = %placed.ord_id, "Order placed on OKX");
+
+        Ok(ExchangeOrder {
+            exchange_order_id: placed.ord_id,
+            client_order_id: placed.cl_ord_id,
+            symbol: order.symbol.clone(),
+            status: OrderStatus::Open,
+            filled_quantity: Decimal::ZERO,
+            avg_price: None,
+        })
+    }
+
+    async fn cancel_order(&self, symbol: &Symbol, order_id: &str) -> ExchangeResult<()> {
+        let body = serde_json::json!({
+            "instId": self.venue_symbol(symbol),
+            "ordId": order_id,
+        });
+
+        let _: serde_json::Value = self
+            .signed_request(Method::POST, "/api/v5/trade/cancel-order", Some(&body))
+            .await?;
+
+        info!(order_id = order_id, "Order cancelled on OKX");
+
+        Ok(())
+    }
+
+    async fn get_order(&self, symbol: &Symbol, order_id: &str) -> ExchangeResult<ExchangeOrder> {
+        let path = format!(
+            "/api/v5/trade/order?instId={}&ordId={order_id}",
+            self.venue_symbol(symbol)
+        );
+        let orders: Vec<OkxOrder> = self.signed_request(Method::GET, &path, None).await?;
+        let order = orders
+            .into_iter()
+            .next()
+            .ok_or_else(|| ExchangeError::ApiError {
+                code: -1,
+                message: format!("Unknown order {order_id}"),
+            })?;
+
+        Ok(ExchangeOrder {
+            exchange_order_id: order.ord_id,
+            client_order_id: order.cl_ord_id,
+            symbol: symbol.clone(),
+            status: order_status(&order.state),
+            filled_quantity: order.acc_fill_sz.parse().unwrap_or_default(),
+            avg_price: parse_decimal(&order.avg_px).filter(|p| !p.is_zero()),
+        })
+    }
+
+    /// Top `limit` levels from `/api/v5/market/books`
+    async fn get_depth(&self, symbol: &Symbol, limit: u32) -> ExchangeResult<OrderBookDepth> {
+        /// Levels are [price, size, deprecated, order count]
+        #[derive(serde::Deserialize)]
+        struct Book {
+            bids: Vec<Vec<serde_json::Value>>,
+            asks: Vec<Vec<serde_json::Value>>,
+        }
+
+        let inst_id = self.venue_symbol(symbol);
+        let books: Vec<Book> = self
+            .public_request(&format!("/api/v5/market/books?instId={inst_id}&sz={limit}"))
+            .await?;
+        let book = books
+            .into_iter()
+            .next()
+            .ok_or_else(|| ExchangeError::ApiError {
+                code: -1,
+                message: format!("No book for {inst_id}"),
+            })?;
+
+        Ok(OrderBookDepth {
+            symbol: symbol.clone(),
+            bids: parse_levels(&book.bids),
+            asks: parse_levels(&book.asks),
+            timestamp: Utc::now(),
+        })
+    }
+
+    /// Recent public trades; venue trades carry no order or user ids
+    async fn get_trades(&self, symbol: &Symbol, limit: u32) -> ExchangeResult<Vec<Trade>> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct OkxTrade {
+            trade_id: String,
+            px: String,
+            sz: String,
+            side: String,
+            ts: String,
+        }
+
+        let inst_id = self.venue_symbol(symbol);
+        let trades: Vec<OkxTrade> = self
+            .public_request(&format!(
+                "/api/v5/market/trades?instId={inst_id}&limit={limit}"
+            ))
+            .await?;
+
+        Ok(trades
+            .into_iter()
+            .filter_map(|t| {
+                let price = parse_decimal(&t.px)?;
+                let quantity = parse_decimal(&t.sz)?;
+                Some(Trade {
+                    id: uuid::Uuid::new_v4(),
+                    trade_id: t.trade_id.parse().unwrap_or_default(),
+                    symbol: symbol.clone(),
+                    maker_order_id: uuid::Uuid::nil(),
+                    maker_user_id: uuid::Uuid::nil(),
+                    taker_order_id: uuid::Uuid::nil(),
+                    taker_user_id: uuid::Uuid::nil(),
+                    price,
+                    quantity,
+                    quote_quantity: price * quantity,
+                    taker_side: if t.side == "buy" {
+                        Side::Buy
+                    } else {
+                        Side::Sell
+                    },
+                    executed_at: Utc.timestamp_millis_opt(t.ts.parse().ok()?).single()?,
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::conformance::{
+        self, fixture, ConformanceTarget, MockVenue, RecordedRequest,
+    };
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+    use std::time::Duration;
+
+    struct Okx;
+
+    #[async_trait]
+    impl ConformanceTarget for Okx {
+        type Adapter = OkxAdapter;
+
+        fn adapter(&self, base_url: &str, symbols: Arc<SymbolRegistry>) -> OkxAdapter {
+            OkxAdapter::new(
+                "key".to_string(),
+                "secret".to_string(),
+                "phrase".to_string(),
+                symbols,
+            )
+            .with_base_url(base_url)
+            .with_retry_policy(RetryPolicy::new(3, Duration::from_millis(1)))
+        }
+
+        async fn mount_fixtures(&self, venue: &MockVenue) {
+            let ok = |data: serde_json::Value| json!({ "code": "0", "msg": "", "data": data });
+
+            venue
+                .mock(
+                    Method::GET,
+                    "/api/v5/public/time",
+                    StatusCode::OK,
+                    ok(json!([{ "ts": Utc::now().timestamp_millis().to_string() }])),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::GET,
+                    "/api/v5/public/instruments",
+                    StatusCode::OK,
+                    ok(json!([{
+                        "instId": "BTC-USDT",
+                        "baseCcy": fixture::BASE,
+                        "quoteCcy": fixture::QUOTE,
+                        "state": "live",
+                        "tickSz": "0.1",
+                        "lotSz": "0.00000001",
+                        "minSz": "0.00001",
+                    }])),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::GET,
+                    "/api/v5/market/ticker",
+                    StatusCode::OK,
+                    ok(json!([{
+                        "instId": "BTC-USDT",
+                        "last": fixture::LAST.to_string(),
+                        "askPx": fixture::ASK.to_string(),
+                        "bidPx": fixture::BID.to_string(),
+                        "vol24h": "10",
+                        "high24h": "105",
+                        "low24h": "95",
+                    }])),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::GET,
+                    "/api/v5/market/trades",
+                    StatusCode::OK,
+                    ok(json!([{
+                        "instId": "BTC-USDT",
+                        "tradeId": "1",
+                        "px": fixture::TRADE_PRICE.to_string(),
+                        "sz": "0.1",
+                        "side": "sell",
+                        "ts": "1700000000000",
+                    }])),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::GET,
+                    "/api/v5/account/balance",
+                    StatusCode::OK,
+                    ok(json!([{ "details": [
+                        {
+                            "ccy": "BTC",
+                            "availBal": fixture::BTC_FREE.to_string(),
+                            "frozenBal": fixture::BTC_LOCKED.to_string(),
+                        },
+                        { "ccy": "ETH", "availBal": "0", "frozenBal": "0" },
+                    ]}])),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::POST,
+                    "/api/v5/trade/order",
+                    StatusCode::OK,
+                    ok(json!([{
+                        "ordId": fixture::ORDER_ID,
+                        "clOrdId": "c1",
+                        "sCode": "0",
+                        "sMsg": "",
+                    }])),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::GET,
+                    "/api/v5/trade/order",
+                    StatusCode::OK,
+                    ok(json!([{
+                        "ordId": fixture::ORDER_ID,
+                        "clOrdId": "c1",
+                        "state": "partially_filled",
+                        "accFillSz": fixture::FILLED.to_string(),
+                        "avgPx": "",
+                    }])),
+                )
+                .await;
+            venue
+                .mock(
+                    Method::POST,
+                    "/api/v5/trade/cancel-order",
+                    StatusCode::OK,
+                    ok(json!([{ "ordId": fixture::ORDER_ID, "sCode": "0", "sMsg": "" }])),
+                )
+                .await;
+        }
+
+        fn order_endpoint(&self) -> (Method, &'static str) {
+            (Method::POST, "/api/v5/trade/order")
+        }
+
+        fn cancel_endpoint(&self) -> (Method, &'static str) {
+            (Method::POST, "/api/v5/trade/cancel-order")
+        }
+
+        fn market_data_endpoint(&self) -> (Method, &'static str) {
+            (Method::GET, "/api/v5/market/ticker")
+        }
+
+        fn is_signed(&self, request: &RecordedRequest) -> bool {
+            let header = |name: &str| {
+                request
+                    .headers
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+            };
+            let request_path = if request.query.is_empty() {
+                request.path.clone()
+            } else {
+                format!("{}?{}", request.path, request.query)
+            };
+            let adapter = self.adapter("", Arc::new(SymbolRegistry::new()));
+            let expected = adapter.sign(
+                header("OK-ACCESS-TIMESTAMP"),
+                request.method.as_str(),
+                &request_path,
+                &request.body,
+            );
+
+            header("OK-ACCESS-KEY") == "key"
+                && header("OK-ACCESS-PASSPHRASE") == "phrase"
+                && header("OK-ACCESS-SIGN") == expected
+        }
+
+        fn sent_order(&self, request: &RecordedRequest) -> (Decimal, Option<Decimal>) {
+            let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+            (
+                body["sz"].as_str().unwrap().parse().unwrap(),
+                body["px"].as_str().map(|p| p.parse().unwrap()),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conformance() {
+        conformance::run_conformance(&Okx).await;
+    }
+
+    #[tokio::test]
+    async fn test_order_errors_and_clock_resync() {
+        let venue = MockVenue::start().await;
+        Okx.mount_fixtures(&venue).await;
+        let adapter = Okx.adapter(venue.url(), Arc::new(SymbolRegistry::new()));
+        let symbol = Symbol::new(fixture::BASE, fixture::QUOTE);
+
+        // Per-order failures carry their reason in sCode/sMsg
+        venue
+            .mock_once(
+                Method::POST,
+                "/api/v5/trade/cancel-order",
+                StatusCode::OK,
+                json!({ "code": "1", "msg": "", "data": [{
+                    "ordId": fixture::ORDER_ID,
+                    "sCode": "51400",
+                    "sMsg": "Order cancellation failed as the order has been filled",
+                }]}),
+            )
+            .await;
+        assert!(matches!(
+            adapter.cancel_order(&symbol, fixture::ORDER_ID).await,
+            Err(ExchangeError::OrderRejected(_))
+        ));
+
+        // An expired timestamp resyncs the clock and retries
+        venue
+            .mock_once(
+                Method::GET,
+                "/api/v5/account/balance",
+                StatusCode::UNAUTHORIZED,
+                json!({ "code": "50102", "msg": "Timestamp request expired", "data": [] }),
+            )
+            .await;
+        assert_eq!(adapter.get_balances().await.unwrap().len(), 1);
+        assert!(venue
+            .last_request(&Method::GET, "/api/v5/public/time")
+            .await
+            .is_some());
+
+        assert_eq!(
+            venue_client_order_id("5f0c-4b2e-9a1d-7e3f-8c6b-2a4d-1e9f-3b7c"),
+            "5f0c4b2e9a1d7e3f8c6b2a4d1e9f3b7c"
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! Venue Request Retries
+//!
+//! Each adapter classifies its venue's failures into a [`Recovery`]:
+//! permanent errors are returned as-is, while transient ones are retried
+//! under a [`RetryPolicy`] after the adapter corrects what it can, such as
+//! resyncing its clock with the venue or backing off after a rate limit.
+//! Only failures where the venue certainly did not act on the request are
+//! retried, so an order is never placed twice.
+
+use std::time::Duration;
+
+/// How a failed venue request can be recovered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    /// Permanent; surface the error
+    Fail,
+    /// Transient; retry after a delay, honoring the venue's hint if any
+    Backoff(Option<Duration>),
+    /// Request timestamp rejected; resync with the venue clock and retry
+    ResyncClock,
+}
+
+impl Recovery {
+    /// Metric label for a retry
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::Fail => "none",
+            Self::Backoff(_) => "backoff",
+            Self::ResyncClock => "clock_resync",
+        }
+    }
+}
+
+/// Attempts and exponential backoff for transient venue errors
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Total attempts including the first; 1 disables retries
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled on each further retry
+    pub base_delay: Duration,
+    /// Upper bound on any single delay, including venue hints
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32, base_delay: Duration) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            base_delay,
+            ..Self::default()
+        }
+    }
+
+    /// Whether another attempt may follow failed attempt `attempt` (1-based)
+    pub fn allows_retry(&self, attempt: u32) -> bool {
+        attempt < self.max_attempts
+    }
+
+    /// Delay before retrying failed attempt `attempt` (1-based)
+    pub fn delay(&self, attempt: u32, hint: Option<Duration>) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16));
+        hint.unwrap_or(backoff).min(self.max_delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_and_is_capped() {
+        let policy = RetryPolicy::new(4, Duration::from_millis(100));
+
+        assert_eq!(policy.delay(1, None), Duration::from_millis(100));
+        assert_eq!(policy.delay(3, None), Duration::from_millis(400));
+        assert_eq!(policy.delay(30, None), policy.max_delay);
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(2))),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(60))),
+            policy.max_delay
+        );
+
+        assert!(policy.allows_retry(3));
+        assert!(!policy.allows_retry(4));
+        assert!(!RetryPolicy::new(0, Duration::ZERO).allows_retry(1));
+    }
+}
@@ -0,0 +1,414 @@
+//! SushiSwap DEX Adapter
+//!
+//! SushiSwap's classic AMM is a Uniswap V2 fork: one constant-product pool
+//! per pair with a flat 0.3% fee. Quotes come from the router's
+//! `getAmountsOut` over the same direct and one-hop candidate paths as
+//! Uniswap, and swaps go through `swapExactTokensForTokens`. The V2 router
+//! does not estimate gas, so routes carry a fixed per-hop estimate.
+
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+use chrono::Utc;
+use ethers::{
+    prelude::*,
+    providers::{Http, Provider},
+    types::Address,
+};
+use rust_decimal::Decimal;
+use std::sync::Arc;
+use tracing::{debug, info};
+
+use super::approvals::{ApprovalPolicy, Approvals};
+use super::traits::*;
+use super::uniswap::{candidate_paths, from_base_units, resolve_token, to_base_units, Token};
+use crate::wallet::SignerClient;
+use common::{ExchangeError, MarketData, Order, Symbol, Trade};
+
+// SushiSwap V2 Router02 address on mainnet
+const SUSHISWAP_ROUTER: &str = "0xd9e1cE17f2641f24aE83637ab66a2cca9C378B9F";
+
+// SushiSwap V2 Factory address on mainnet
+const SUSHISWAP_FACTORY: &str = "0xC0AEe478e3658e2610c5F7A4A2E1777cE9e4f2Ac";
+
+/// Pool fee in hundredths of a basis point, the same on every pair
+const POOL_FEE: u32 = 3000;
+
+/// Approximate gas of a single-pool swap, and of each further hop
+const SWAP_GAS: u64 = 110_000;
+const HOP_GAS: u64 = 60_000;
+
+abigen!(
+    IUniswapV2Router02,
+    r#"[
+        function getAmountsOut(uint256 amountIn, address[] path) external view returns (uint256[] amounts)
+        function swapExactTokensForTokens(uint256 amountIn, uint256 amountOutMin, address[] path, address to, uint256 deadline) external returns (uint256[] amounts)
+    ]"#
+);
+
+abigen!(
+    IUniswapV2Factory,
+    r#"[
+        function getPair(address tokenA, address tokenB) external view returns (address pair)
+    ]"#
+);
+
+abigen!(
+    IUniswapV2Pair,
+    r#"[
+        function getReserves() external view returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast)
+        function token0() external view returns (address)
+    ]"#
+);
+
+/// Gas estimate of a swap through `hops` pools
+fn swap_gas(hops: usize) -> u64 {
+    SWAP_GAS + HOP_GAS * hops.saturating_sub(1) as u64
+}
+
+pub struct SushiswapAdapter {
+    provider: Arc<Provider<Http>>,
+    chain_id: u64,
+
+    /// Signs and sends swaps; swaps are refused without one
+    signer: Option<Arc<SignerClient>>,
+
+    /// Router allowances of the signing wallet
+    approvals: Option<Approvals>,
+}
+
+impl SushiswapAdapter {
+    pub fn new(rpc_url: &str, chain_id: u64) -> Result<Self, ExchangeError> {
+        let provider = Provider::<Http>::try_from(rpc_url)
+            .map_err(|e| ExchangeError::ConnectionFailed(e.to_string()))?;
+
+        Ok(Self {
+            provider: Arc::new(provider),
+            chain_id,
+            signer: None,
+            approvals: None,
+        })
+    }
+
+    /// Send swaps from `wallet`, approving the router under `policy`
+    pub fn with_wallet(mut self, wallet: LocalWallet, policy: ApprovalPolicy) -> Self {
+        let wallet = wallet.with_chain_id(self.chain_id);
+        let signer = Arc::new(SignerMiddleware::new(self.provider.clone(), wallet));
+        self.approvals = Some(Approvals::new(signer.clone(), policy));
+        self.signer = Some(signer);
+        self
+    }
+
+    fn parse_address(addr: &str) -> Result<Address, ExchangeError> {
+        addr.parse().map_err(|_| ExchangeError::ApiError {
+            code: -1,
+            message: format!("Invalid address: {addr}"),
+        })
+    }
+
+    /// Quote a token path via the router's `getAmountsOut`
+    async fn quote_route(&self, path: &[Token], amount_in: Decimal) -> ExchangeResult<RouteQuote> {
+        let router = IUniswapV2Router02::new(
+            Self::parse_address(SUSHISWAP_ROUTER)?,
+            self.provider.clone(),
+        );
+        let token_in = &path[0];
+        let token_out = &path[path.len() - 1];
+        let addresses: Vec<Address> = path.iter().map(|t| t.address).collect();
+
+        let amounts = router
+            .get_amounts_out(to_base_units(amount_in, token_in.decimals)?, addresses)
+            .call()
+            .await
+            .map_err(|e| ExchangeError::ApiError {
+                code: -1,
+                message: format!("Quote failed: {e}"),
+            })?;
+        let amount_out = amounts.last().copied().unwrap_or_default();
+
+        Ok(RouteQuote {
+            tokens: path.iter().map(|t| t.symbol.to_string()).collect(),
+            fees: vec![POOL_FEE; path.len() - 1],
+            encoded_path: None,
+            amount_in,
+            amount_out: from_base_units(amount_out, token_out.decimals)?,
+            gas_estimate: Some(swap_gas(path.len() - 1)),
+        })
+    }
+
+    /// Best route for a swap across direct and one-hop paths
+    pub async fn find_best_route(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_in: Decimal,
+    ) -> ExchangeResult<RouteQuote> {
+        let token_in = resolve_token(token_in)?;
+        let token_out = resolve_token(token_out)?;
+
+        let mut best: Option<RouteQuote> = None;
+        for path in candidate_paths(&token_in, &token_out) {
+            let quote = match self.quote_route(&path, amount_in).await {
+                Ok(quote) => quote,
+                Err(e) => {
+                    debug!(error = %e, "Skipping unquotable route");
+                    continue;
+                }
+            };
+            if best
+                .as_ref()
+                .is_none_or(|b| quote.amount_out > b.amount_out)
+            {
+                best = Some(quote);
+            }
+        }
+
+        best.ok_or_else(|| ExchangeError::ApiError {
+            code: -1,
+            message: format!("No route from {} to {}", token_in.symbol, token_out.symbol),
+        })
+    }
+}
+
+#[async_trait]
+impl ExchangeAdapter for SushiswapAdapter {
+    fn name(&self) -> &'static str {
+        "SushiSwap"
+    }
+
+    async fn is_available(&self) -> bool {
+        self.provider.get_block_number().await.is_ok()
+    }
+
+    async fn get_symbols(&self) -> ExchangeResult<Vec<Symbol>> {
+        Ok(vec![
+            Symbol::new("ETH", "USDC"),
+            Symbol::new("ETH", "USDT"),
+            Symbol::new("WBTC", "ETH"),
+            Symbol::new("LINK", "ETH"),
+        ])
+    }
+
+    async fn get_market_data(&self, symbol: &Symbol) -> ExchangeResult<MarketData> {
+        Ok(MarketData {
+            symbol: symbol.clone(),
+            bid: Decimal::ZERO,
+            ask: Decimal::ZERO,
+            last: Decimal::ZERO,
+            volume_24h: Decimal::ZERO,
+            high_24h: Decimal::ZERO,
+            low_24h: Decimal::ZERO,
+            price_change_24h: Decimal::ZERO,
+            percent_change_24h: Decimal::ZERO,
+            fair_value: None,
+            timestamp: Utc::now(),
+        })
+    }
+
+    async fn get_balances(&self) -> ExchangeResult<Vec<ExchangeBalance>> {
+        Ok(vec![])
+    }
+
+    async fn place_order(&self, _order: &Order) -> ExchangeResult<ExchangeOrder> {
+        Err(ExchangeError::UnsupportedOperation(
+            "Use swap() for DEX trades".to_string(),
+        ))
+    }
+
+    async fn cancel_order(&self, _symbol: &Symbol, _order_id: &str) -> ExchangeResult<()> {
+        Err(ExchangeError::UnsupportedOperation(
+            "DEX orders cannot be cancelled".to_string(),
+        ))
+    }
+
+    async fn get_order(&self, _symbol: &Symbol, _order_id: &str) -> ExchangeResult<ExchangeOrder> {
+        Err(ExchangeError::UnsupportedOperation(
+            "Use transaction hash for DEX trades".to_string(),
+        ))
+    }
+
+    async fn get_trades(&self, _symbol: &Symbol, _limit: u32) -> ExchangeResult<Vec<Trade>> {
+        Ok(vec![])
+    }
+}
+
+#[async_trait]
+impl DexAdapter for SushiswapAdapter {
+    async fn get_quote(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_in: Decimal,
+    ) -> ExchangeResult<Decimal> {
+        Ok(self
+            .get_route_quote(token_in, token_out, amount_in)
+            .await?
+            .amount_out)
+    }
+
+    async fn get_route_quote(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_in: Decimal,
+    ) -> ExchangeResult<RouteQuote> {
+        let quote = self.find_best_route(token_in, token_out, amount_in).await?;
+
+        info!(
+            route = %quote.tokens.join(" -> "),
+            amount_out = %quote.amount_out,
+            "Best SushiSwap route selected"
+        );
+
+        Ok(quote)
+    }
+
+    async fn swap(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_in: Decimal,
+        min_amount_out: Decimal,
+        deadline: u64,
+    ) -> ExchangeResult<String> {
+        let signer = self.signer.as_ref().ok_or_else(|| {
+            ExchangeError::UnsupportedOperation(
+                "Swap execution requires wallet configuration".to_string(),
+            )
+        })?;
+
+        info!(
+            token_in = token_in,
+            token_out = token_out,
+            amount_in = %amount_in,
+            min_out = %min_amount_out,
+            "Executing SushiSwap swap"
+        );
+
+        let route = self.find_best_route(token_in, token_out, amount_in).await?;
+        if route.amount_out < min_amount_out {
+            return Err(ExchangeError::OrderRejected(format!(
+                "Best route returns {}, below the minimum {min_amount_out}",
+                route.amount_out
+            )));
+        }
+
+        let token_in = resolve_token(token_in)?;
+        let token_out = resolve_token(token_out)?;
+        let path = route
+            .tokens
+            .iter()
+            .map(|symbol| resolve_token(symbol).map(|t| t.address))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let router_address = Self::parse_address(SUSHISWAP_ROUTER)?;
+        let amount_in_units = to_base_units(amount_in, token_in.decimals)?;
+        if let Some(approvals) = &self.approvals {
+            approvals
+                .ensure(token_in.address, router_address, amount_in_units)
+                .await?;
+        }
+
+        let router = IUniswapV2Router02::new(router_address, signer.clone());
+        let call = router.swap_exact_tokens_for_tokens(
+            amount_in_units,
+            to_base_units(min_amount_out, token_out.decimals)?,
+            path,
+            signer.address(),
+            U256::from(deadline),
+        );
+
+        let pending = call
+            .send()
+            .await
+            .map_err(|e| ExchangeError::OrderRejected(format!("Swap not sent: {e}")))?;
+        let tx_hash = pending.tx_hash();
+        info!(tx_hash = ?tx_hash, route = %route.tokens.join(" -> "), "Swap submitted");
+
+        let receipt = pending
+            .await
+            .map_err(|e| ExchangeError::ConnectionFailed(format!("Swap {tx_hash:?}: {e}")))?
+            .ok_or_else(|| ExchangeError::ApiError {
+                code: -1,
+                message: format!("Swap {tx_hash:?} dropped from the mempool"),
+            })?;
+        if receipt.status != Some(1u64.into()) {
+            return Err(ExchangeError::OrderRejected(format!(
+                "Swap {tx_hash:?} reverted"
+            )));
+        }
+        if let Some(approvals) = &self.approvals {
+            approvals.spent(token_in.address, router_address, amount_in_units);
+        }
+
+        Ok(format!("{tx_hash:?}"))
+    }
+
+    /// Reserves and price of the pair's pool
+    async fn get_pool_info(&self, token_a: &str, token_b: &str) -> ExchangeResult<PoolInfo> {
+        let token_a = resolve_token(token_a)?;
+        let token_b = resolve_token(token_b)?;
+        let no_pool = || ExchangeError::ApiError {
+            code: -1,
+            message: format!("No pool for {}/{}", token_a.symbol, token_b.symbol),
+        };
+        let call_failed = |e: ContractError<Provider<Http>>| ExchangeError::ApiError {
+            code: -1,
+            message: format!("Pool query failed: {e}"),
+        };
+
+        let factory = IUniswapV2Factory::new(
+            Self::parse_address(SUSHISWAP_FACTORY)?,
+            self.provider.clone(),
+        );
+        let address = factory
+            .get_pair(token_a.address, token_b.address)
+            .call()
+            .await
+            .map_err(call_failed)?;
+        if address == Address::zero() {
+            return Err(no_pool());
+        }
+
+        let pair = IUniswapV2Pair::new(address, self.provider.clone());
+        let (reserve0, reserve1, _) = pair.get_reserves().call().await.map_err(call_failed)?;
+        let token0 = pair.token_0().call().await.map_err(call_failed)?;
+        let (raw_a, raw_b) = if token0 == token_a.address {
+            (reserve0, reserve1)
+        } else {
+            (reserve1, reserve0)
+        };
+
+        let reserve_a = from_base_units(U256::from(raw_a), token_a.decimals)?;
+        let reserve_b = from_base_units(U256::from(raw_b), token_b.decimals)?;
+        if reserve_a.is_zero() {
+            return Err(no_pool());
+        }
+        let price = (reserve_b / reserve_a).round_dp(token_b.decimals);
+
+        Ok(PoolInfo {
+            token_a: token_a.symbol.to_string(),
+            token_b: token_b.symbol.to_string(),
+            pool_address: Some(format!("{address:?}")),
+            reserve_a,
+            reserve_b,
+            fee: Decimal::new(POOL_FEE as i64, 6),
+            price,
+            liquidity: None,
+            tick: None,
+            tick_spacing: None,
+            tvl: reserve_a * price + reserve_b,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gas_estimate_grows_per_hop() {
+        assert_eq!(swap_gas(1), SWAP_GAS);
+        assert_eq!(swap_gas(2), SWAP_GAS + HOP_GAS);
+    }
+}
@@ -5,32 +5,68 @@
 #![allow(dead_code)]
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ethers::types::U256;
 use rust_decimal::Decimal;
+use serde::Serialize;
 
-use common::{ExchangeError, MarketData, Order, Symbol, Trade};
+use common::{
+    Candle, ExchangeError, MarketData, Order, OrderStatus, PriceLevel, Side, Symbol, SymbolInfo,
+    SymbolRegistry, Trade,
+};
 
 /// Result type for exchange operations
 pub type ExchangeResult<T> = Result<T, ExchangeError>;
 
 /// Order response from exchange
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ExchangeOrder {
     pub exchange_order_id: String,
     pub client_order_id: String,
     pub symbol: Symbol,
-    pub status: String,
+    /// Venue order state mapped by the adapter
+    pub status: OrderStatus,
+    #[serde(with = "rust_decimal::serde::str")]
     pub filled_quantity: Decimal,
+    #[serde(with = "rust_decimal::serde::str_option")]
     pub avg_price: Option<Decimal>,
 }
 
 /// Balance on exchange
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ExchangeBalance {
     pub asset: String,
+    #[serde(with = "rust_decimal::serde::str")]
     pub free: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
     pub locked: Decimal,
 }
 
+/// Order book snapshot from a venue, best levels first
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderBookDepth {
+    pub symbol: Symbol,
+    pub bids: Vec<PriceLevel>,
+    pub asks: Vec<PriceLevel>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Price levels from venue `[price, quantity, ...]` arrays of decimal strings
+///
+/// Venues that do not report order counts leave them at zero.
+pub fn parse_levels(levels: &[Vec<serde_json::Value>]) -> Vec<PriceLevel> {
+    levels
+        .iter()
+        .filter_map(|level| {
+            Some(PriceLevel {
+                price: level.first()?.as_str()?.parse().ok()?,
+                quantity: level.get(1)?.as_str()?.parse().ok()?,
+                order_count: 0,
+            })
+        })
+        .collect()
+}
+
 /// Unified exchange adapter interface
 #[async_trait]
 pub trait ExchangeAdapter: Send + Sync {
@@ -40,9 +76,29 @@ pub trait ExchangeAdapter: Send + Sync {
     /// Check if exchange is available
     async fn is_available(&self) -> bool;
 
+    /// Measure the venue clock's offset from the local clock in
+    /// milliseconds and apply it to request timestamps
+    ///
+    /// `None` for venues that do not timestamp requests.
+    async fn sync_clock(&self) -> ExchangeResult<Option<i64>> {
+        Ok(None)
+    }
+
     /// Get supported symbols
     async fn get_symbols(&self) -> ExchangeResult<Vec<Symbol>>;
 
+    /// Get reference metadata for supported symbols
+    ///
+    /// Adapters without an instrument endpoint derive it from `get_symbols`.
+    async fn get_symbol_info(&self) -> ExchangeResult<Vec<SymbolInfo>> {
+        Ok(self
+            .get_symbols()
+            .await?
+            .iter()
+            .map(SymbolInfo::from_symbol)
+            .collect())
+    }
+
     /// Get current market data
     async fn get_market_data(&self, symbol: &Symbol) -> ExchangeResult<MarketData>;
 
@@ -55,11 +111,85 @@ pub trait ExchangeAdapter: Send + Sync {
     /// Cancel order
     async fn cancel_order(&self, symbol: &Symbol, order_id: &str) -> ExchangeResult<()>;
 
+    /// Replace an open order with `order`, returning the replacement
+    ///
+    /// Venues without a native amend cancel and place. A retry after the
+    /// cancel went through finds the original cancelled and only places;
+    /// callers keep `order.client_order_id` fixed across retries so the
+    /// venue refuses a second replacement.
+    async fn replace_order(&self, order_id: &str, order: &Order) -> ExchangeResult<ExchangeOrder> {
+        if let Err(e) = self.cancel_order(&order.symbol, order_id).await {
+            let cancelled = self
+                .get_order(&order.symbol, order_id)
+                .await
+                .is_ok_and(|o| o.status == OrderStatus::Cancelled);
+            if !cancelled {
+                return Err(e);
+            }
+        }
+        self.place_order(order).await
+    }
+
     /// Get order status
     async fn get_order(&self, symbol: &Symbol, order_id: &str) -> ExchangeResult<ExchangeOrder>;
 
     /// Get recent trades
     async fn get_trades(&self, symbol: &Symbol, limit: u32) -> ExchangeResult<Vec<Trade>>;
+
+    /// Get up to `limit` order book levels per side
+    async fn get_depth(&self, symbol: &Symbol, _limit: u32) -> ExchangeResult<OrderBookDepth> {
+        Err(ExchangeError::UnsupportedOperation(format!(
+            "depth for {symbol}"
+        )))
+    }
+
+    /// Get up to `limit` klines of `interval` (e.g. `1m`, `1h`) opening at
+    /// or after `start`, oldest first; the newest may still be open
+    async fn get_klines(
+        &self,
+        symbol: &Symbol,
+        _interval: &str,
+        _start: DateTime<Utc>,
+        _limit: u32,
+    ) -> ExchangeResult<Vec<Candle>> {
+        Err(ExchangeError::UnsupportedOperation(format!(
+            "klines for {symbol}"
+        )))
+    }
+}
+
+/// Round an order's quantity down to the symbol's lot size and its price
+/// to the tick size, never to a worse price than requested
+///
+/// Orders for symbols without reference data are sent unchanged.
+pub fn order_precision(
+    symbols: &SymbolRegistry,
+    order: &Order,
+) -> ExchangeResult<(Decimal, Option<Decimal>)> {
+    let Some(info) = symbols.get(&order.symbol) else {
+        return Ok((order.quantity, order.price));
+    };
+
+    let quantity = match info.lot_size {
+        Some(lot) => (order.quantity / lot).floor() * lot,
+        None => order.quantity,
+    };
+    if quantity <= Decimal::ZERO {
+        return Err(ExchangeError::OrderRejected(format!(
+            "Quantity {} is below the lot size",
+            order.quantity
+        )));
+    }
+
+    let price = match (order.price, info.tick_size) {
+        (Some(price), Some(tick)) => Some(match order.side {
+            Side::Buy => (price / tick).floor() * tick,
+            Side::Sell => (price / tick).ceil() * tick,
+        }),
+        (price, _) => price,
+    };
+
+    Ok((quantity.normalize(), price.map(|p| p.normalize())))
 }
 
 /// DEX-specific adapter interface
@@ -73,6 +203,32 @@ pub trait DexAdapter: ExchangeAdapter {
         amount_in: Decimal,
     ) -> ExchangeResult<Decimal>;
 
+    /// Get quote for swap together with the route that produces it
+    async fn get_route_quote(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_in: Decimal,
+    ) -> ExchangeResult<RouteQuote>;
+
+    /// Get quote for swap through a single pool under `limits`
+    ///
+    /// Unconstrained quotes fall back to the best route.
+    async fn get_limited_quote(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_in: Decimal,
+        limits: &QuoteLimits,
+    ) -> ExchangeResult<RouteQuote> {
+        if limits.is_empty() {
+            return self.get_route_quote(token_in, token_out, amount_in).await;
+        }
+        Err(ExchangeError::UnsupportedOperation(
+            "Quote limits are not supported".to_string(),
+        ))
+    }
+
     /// Execute swap
     async fn swap(
         &self,
@@ -87,11 +243,107 @@ pub trait DexAdapter: ExchangeAdapter {
     async fn get_pool_info(&self, token_a: &str, token_b: &str) -> ExchangeResult<PoolInfo>;
 }
 
-#[derive(Debug, Clone)]
+/// Swap quote along a specific route
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteQuote {
+    /// Token symbols visited, from input to output
+    pub tokens: Vec<String>,
+    /// Fee tier per hop in hundredths of a basis point
+    pub fees: Vec<u32>,
+    /// Protocol-encoded path (hex), if the venue uses one
+    pub encoded_path: Option<String>,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount_in: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount_out: Decimal,
+    /// Gas the swap is estimated to use, if the venue quotes it
+    pub gas_estimate: Option<u64>,
+}
+
+/// Constraints on a single-pool swap quote
+#[derive(Debug, Clone, Default)]
+pub struct QuoteLimits {
+    /// Quote this fee tier only, in hundredths of a basis point
+    pub fee: Option<u32>,
+    /// Stop the swap once the pool reaches this Uniswap V3 `sqrtPriceX96`
+    pub sqrt_price_limit_x96: Option<U256>,
+}
+
+impl QuoteLimits {
+    pub fn is_empty(&self) -> bool {
+        self.fee.is_none() && self.sqrt_price_limit_x96.is_none()
+    }
+}
+
+/// Liquidity of a DEX pool
+///
+/// Concentrated-liquidity fields are `None` for V2-style pools.
+#[derive(Debug, Clone, Serialize)]
 pub struct PoolInfo {
     pub token_a: String,
     pub token_b: String,
+    pub pool_address: Option<String>,
+    #[serde(with = "rust_decimal::serde::str")]
     pub reserve_a: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
     pub reserve_b: Decimal,
+    /// Swap fee as a fraction of the input
+    #[serde(with = "rust_decimal::serde::str")]
     pub fee: Decimal,
+    /// Price of `token_a` in `token_b`
+    #[serde(with = "rust_decimal::serde::str")]
+    pub price: Decimal,
+    /// Active in-range liquidity
+    pub liquidity: Option<u128>,
+    pub tick: Option<i32>,
+    pub tick_spacing: Option<i32>,
+    /// Total value locked in units of `token_b`
+    #[serde(with = "rust_decimal::serde::str")]
+    pub tvl: Decimal,
+}
+
+/// Perpetual funding rate for one funding interval
+#[derive(Debug, Clone, Serialize)]
+pub struct FundingRate {
+    #[serde(with = "rust_decimal::serde::str")]
+    pub rate: Decimal,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub mark_price: Option<Decimal>,
+    pub funding_time: DateTime<Utc>,
+}
+
+/// Annualized borrow rate of an asset
+#[derive(Debug, Clone, Serialize)]
+pub struct BorrowRate {
+    #[serde(with = "rust_decimal::serde::str")]
+    pub annual_rate: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Venue publishing funding and/or borrow rates
+#[async_trait]
+pub trait RateSource: Send + Sync {
+    /// Get perpetual funding rates at or after `since`
+    async fn get_funding_rates(
+        &self,
+        symbol: &Symbol,
+        _since: DateTime<Utc>,
+    ) -> ExchangeResult<Vec<FundingRate>> {
+        Err(ExchangeError::UnsupportedOperation(format!(
+            "funding rates for {symbol}"
+        )))
+    }
+
+    /// Get borrow rates at or after `since`
+    ///
+    /// Venues without history return the current rate only.
+    async fn get_borrow_rates(
+        &self,
+        asset: &str,
+        _since: DateTime<Utc>,
+    ) -> ExchangeResult<Vec<BorrowRate>> {
+        Err(ExchangeError::UnsupportedOperation(format!(
+            "borrow rates for {asset}"
+        )))
+    }
 }
@@ -1,6 +1,18 @@
 //! Uniswap DEX Adapter
 //!
 //! Integration with Uniswap V3 for on-chain swaps
+//!
+//! Quotes are routed through up to one intermediate token when no direct
+//! pool exists or an indirect route pays more. Direct swaps are quoted at
+//! every fee tier via QuoterV2's `quoteExactInputSingle`, which also honours
+//! a `sqrtPriceLimitX96`, and the best output wins. Multi-hop routes use the
+//! deepest pool per hop and are quoted via `quoteExactInput` with a packed
+//! V3 path.
+//!
+//! Swaps take the best route through the SwapRouter's `exactInput`, signed
+//! by the gateway wallet, and wait for the transaction to be mined. The
+//! router is approved to spend the input token first when its allowance
+//! falls short (see `approvals`).
 
 #![allow(dead_code)]
 
@@ -11,19 +23,205 @@ use ethers::{
     providers::{Http, Provider},
     types::Address,
 };
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use std::sync::Arc;
-use tracing::info;
+use tracing::{debug, info};
 
+use super::approvals::{ApprovalPolicy, Approvals};
 use super::traits::*;
+use crate::wallet::SignerClient;
 use common::{ExchangeError, MarketData, Order, Symbol, Trade};
 
 // Uniswap V3 Router address on mainnet
 const UNISWAP_ROUTER: &str = "0xE592427A0AEce92De3Edee1F18E0157C05861564";
 
+// Uniswap V3 Factory address on mainnet
+const UNISWAP_FACTORY: &str = "0x1F98431c8aD98523631AE4a59f267346ea31F984";
+
+// Uniswap V3 QuoterV2 address on mainnet
+const UNISWAP_QUOTER: &str = "0x61fFE014bA17989E743c5F6cB21bF9697530B21e";
+
+/// Pool fee tiers in hundredths of a basis point
+const FEE_TIERS: [u32; 4] = [100, 500, 3000, 10000];
+
+/// Tokens considered as intermediate hops
+const INTERMEDIATE_TOKENS: [&str; 4] = ["WETH", "USDC", "USDT", "DAI"];
+
+/// Known mainnet tokens: (symbol, address, decimals)
+const TOKENS: [(&str, &str, u32); 7] = [
+    ("WETH", "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2", 18),
+    ("USDC", "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", 6),
+    ("USDT", "0xdAC17F958D2ee523a2206206994597C13D831ec7", 6),
+    ("DAI", "0x6B175474E89094C44Da98b954EedeAC495271d0F", 18),
+    ("WBTC", "0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599", 8),
+    ("LINK", "0x514910771AF9Ca656af840dff83E8264EcF986CA", 18),
+    ("UNI", "0x1f9840a85d5aF5bf1D1762F925BDADdC4201F984", 18),
+];
+
+abigen!(
+    IUniswapV3Factory,
+    r#"[
+        function getPool(address tokenA, address tokenB, uint24 fee) external view returns (address pool)
+    ]"#
+);
+
+abigen!(
+    IUniswapV3Pool,
+    r#"[
+        function liquidity() external view returns (uint128)
+        function slot0() external view returns (uint160 sqrtPriceX96, int24 tick, uint16 observationIndex, uint16 observationCardinality, uint16 observationCardinalityNext, uint8 feeProtocol, bool unlocked)
+        function tickSpacing() external view returns (int24)
+        function token0() external view returns (address)
+    ]"#
+);
+
+abigen!(
+    ISwapRouter,
+    r#"[
+        struct ExactInputParams { bytes path; address recipient; uint256 deadline; uint256 amountIn; uint256 amountOutMinimum; }
+        function exactInput(ExactInputParams params) external payable returns (uint256 amountOut)
+    ]"#
+);
+
+abigen!(
+    IERC20,
+    r#"[
+        function balanceOf(address account) external view returns (uint256)
+        function allowance(address owner, address spender) external view returns (uint256)
+        function approve(address spender, uint256 amount) external returns (bool)
+        function transfer(address to, uint256 amount) external returns (bool)
+    ]"#
+);
+
+abigen!(
+    IQuoterV2,
+    r#"[
+        struct QuoteExactInputSingleParams { address tokenIn; address tokenOut; uint256 amountIn; uint24 fee; uint160 sqrtPriceLimitX96; }
+        function quoteExactInput(bytes path, uint256 amountIn) external returns (uint256 amountOut, uint160[] sqrtPriceX96AfterList, uint32[] initializedTicksCrossedList, uint256 gasEstimate)
+        function quoteExactInputSingle(QuoteExactInputSingleParams params) external returns (uint256 amountOut, uint160 sqrtPriceX96After, uint32 initializedTicksCrossed, uint256 gasEstimate)
+    ]"#
+);
+
+/// Token known to the adapter
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Token {
+    pub(crate) symbol: &'static str,
+    pub(crate) address: Address,
+    pub(crate) decimals: u32,
+}
+
+/// Resolve a token symbol or address against the known token list
+///
+/// Native ETH is treated as WETH.
+pub(crate) fn resolve_token(token: &str) -> Result<Token, ExchangeError> {
+    let wanted = token.to_uppercase();
+    let wanted = if wanted == "ETH" {
+        "WETH".to_string()
+    } else {
+        wanted
+    };
+
+    TOKENS
+        .iter()
+        .find(|(symbol, address, _)| *symbol == wanted || address.to_uppercase() == wanted)
+        .map(|&(symbol, address, decimals)| Token {
+            symbol,
+            address: address.parse().expect("valid token address"),
+            decimals,
+        })
+        .ok_or_else(|| ExchangeError::ApiError {
+            code: -1,
+            message: format!("Unknown token: {token}"),
+        })
+}
+
+/// Candidate token paths from `token_in` to `token_out`
+///
+/// The direct path comes first, followed by one path per intermediate token.
+pub(super) fn candidate_paths(token_in: &Token, token_out: &Token) -> Vec<Vec<Token>> {
+    let mut paths = vec![vec![token_in.clone(), token_out.clone()]];
+
+    for symbol in INTERMEDIATE_TOKENS {
+        if symbol == token_in.symbol || symbol == token_out.symbol {
+            continue;
+        }
+        if let Ok(mid) = resolve_token(symbol) {
+            paths.push(vec![token_in.clone(), mid, token_out.clone()]);
+        }
+    }
+
+    paths
+}
+
+/// Encode a V3 `exactInput` path: token (20) | fee (3) | token (20) | ...
+fn encode_path(tokens: &[Address], fees: &[u32]) -> Bytes {
+    let mut path = Vec::with_capacity(tokens.len() * 20 + fees.len() * 3);
+
+    for (i, token) in tokens.iter().enumerate() {
+        path.extend_from_slice(token.as_bytes());
+        if let Some(fee) = fees.get(i) {
+            path.extend_from_slice(&fee.to_be_bytes()[1..]);
+        }
+    }
+
+    Bytes::from(path)
+}
+
+/// Price of `token_a` in `token_b` from a pool's `sqrtPriceX96`
+///
+/// The pool price is token1 per token0 in base units, so it is scaled by
+/// the decimals difference and inverted when `token_a` is token1.
+fn pool_price(sqrt_price_x96: U256, decimals_0: u32, decimals_1: u32, a_is_token0: bool) -> f64 {
+    let sqrt_price = sqrt_price_x96.to_string().parse::<f64>().unwrap_or(0.0) / 2f64.powi(96);
+    let price = sqrt_price * sqrt_price * 10f64.powi(decimals_0 as i32 - decimals_1 as i32);
+
+    match (a_is_token0, price > 0.0) {
+        (true, _) => price,
+        (false, true) => 1.0 / price,
+        (false, false) => 0.0,
+    }
+}
+
+/// Convert a decimal token amount to its on-chain integer representation
+pub(crate) fn to_base_units(amount: Decimal, decimals: u32) -> Result<U256, ExchangeError> {
+    10u64
+        .checked_pow(decimals)
+        .and_then(|scale| amount.checked_mul(Decimal::from(scale)))
+        .and_then(|units| units.trunc().to_u128())
+        .map(U256::from)
+        .ok_or_else(|| ExchangeError::ApiError {
+            code: -1,
+            message: format!("Amount out of range: {amount}"),
+        })
+}
+
+/// Convert an on-chain integer amount to a decimal token amount
+pub(crate) fn from_base_units(amount: U256, decimals: u32) -> Result<Decimal, ExchangeError> {
+    if amount > U256::from(i128::MAX as u128) {
+        return Err(ExchangeError::ApiError {
+            code: -1,
+            message: format!("Amount out of range: {amount}"),
+        });
+    }
+
+    Decimal::try_from_i128_with_scale(amount.as_u128() as i128, decimals).map_err(|e| {
+        ExchangeError::ApiError {
+            code: -1,
+            message: e.to_string(),
+        }
+    })
+}
+
 pub struct UniswapAdapter {
     provider: Arc<Provider<Http>>,
     chain_id: u64,
+
+    /// Signs and sends swaps; swaps are refused without one
+    signer: Option<Arc<SignerClient>>,
+
+    /// Router allowances of the signing wallet
+    approvals: Option<Approvals>,
 }
 
 impl UniswapAdapter {
@@ -34,15 +232,246 @@ impl UniswapAdapter {
         Ok(Self {
             provider: Arc::new(provider),
             chain_id,
+            signer: None,
+            approvals: None,
         })
     }
 
+    /// Send swaps from `wallet`, approving the router under `policy`
+    pub fn with_wallet(mut self, wallet: LocalWallet, policy: ApprovalPolicy) -> Self {
+        let wallet = wallet.with_chain_id(self.chain_id);
+        let signer = Arc::new(SignerMiddleware::new(self.provider.clone(), wallet));
+        self.approvals = Some(Approvals::new(signer.clone(), policy));
+        self.signer = Some(signer);
+        self
+    }
+
     fn parse_address(addr: &str) -> Result<Address, ExchangeError> {
         addr.parse().map_err(|_| ExchangeError::ApiError {
             code: -1,
             message: format!("Invalid address: {addr}"),
         })
     }
+
+    /// Pick the fee tier with the deepest pool for a single hop
+    ///
+    /// Returns `None` if no pool exists for the pair at any tier.
+    async fn best_fee_tier(&self, token_a: Address, token_b: Address) -> Option<u32> {
+        self.deepest_pool(token_a, token_b)
+            .await
+            .map(|(fee, _, _)| fee)
+    }
+
+    /// Fee tier, address and in-range liquidity of the pair's deepest pool
+    async fn deepest_pool(
+        &self,
+        token_a: Address,
+        token_b: Address,
+    ) -> Option<(u32, Address, u128)> {
+        let factory = IUniswapV3Factory::new(
+            Self::parse_address(UNISWAP_FACTORY).ok()?,
+            self.provider.clone(),
+        );
+
+        let mut best: Option<(u32, Address, u128)> = None;
+
+        for fee in FEE_TIERS {
+            let pool = match factory.get_pool(token_a, token_b, fee).call().await {
+                Ok(pool) if pool != Address::zero() => pool,
+                _ => continue,
+            };
+
+            let liquidity = IUniswapV3Pool::new(pool, self.provider.clone())
+                .liquidity()
+                .call()
+                .await
+                .unwrap_or(0);
+
+            if liquidity > 0 && best.is_none_or(|(_, _, l)| liquidity > l) {
+                best = Some((fee, pool, liquidity));
+            }
+        }
+
+        best
+    }
+
+    /// Token balance held by `holder`
+    async fn token_balance(&self, token: &Token, holder: Address) -> ExchangeResult<Decimal> {
+        let balance = IERC20::new(token.address, self.provider.clone())
+            .balance_of(holder)
+            .call()
+            .await
+            .map_err(|e| ExchangeError::ApiError {
+                code: -1,
+                message: format!("balanceOf failed: {e}"),
+            })?;
+        from_base_units(balance, token.decimals)
+    }
+
+    fn quoter(&self) -> ExchangeResult<IQuoterV2<Provider<Http>>> {
+        Ok(IQuoterV2::new(
+            Self::parse_address(UNISWAP_QUOTER)?,
+            self.provider.clone(),
+        ))
+    }
+
+    /// Quote an encoded path via the Quoter contract
+    ///
+    /// Returns the output amount and the gas estimate.
+    async fn quote_path(&self, path: Bytes, amount_in: U256) -> ExchangeResult<(U256, U256)> {
+        let (amount_out, _, _, gas_estimate) = self
+            .quoter()?
+            .quote_exact_input(path, amount_in)
+            .call()
+            .await
+            .map_err(|e| ExchangeError::ApiError {
+                code: -1,
+                message: format!("Quote failed: {e}"),
+            })?;
+        Ok((amount_out, gas_estimate))
+    }
+
+    /// Quote a direct swap through one pool via `quoteExactInputSingle`
+    ///
+    /// Every fee tier is tried unless `limits` fixes one; the tier paying
+    /// the most wins. Tiers without a pool revert and are skipped.
+    async fn quote_single(
+        &self,
+        token_in: &Token,
+        token_out: &Token,
+        amount_in: Decimal,
+        limits: &QuoteLimits,
+    ) -> ExchangeResult<RouteQuote> {
+        let tiers = match limits.fee {
+            Some(fee) if FEE_TIERS.contains(&fee) => vec![fee],
+            Some(fee) => {
+                return Err(ExchangeError::ApiError {
+                    code: -1,
+                    message: format!("Unsupported fee tier: {fee}"),
+                })
+            }
+            None => FEE_TIERS.to_vec(),
+        };
+        let amount_in_units = to_base_units(amount_in, token_in.decimals)?;
+        let quoter = self.quoter()?;
+
+        let mut best: Option<(u32, U256, U256)> = None;
+        let mut last_error = None;
+        for fee in tiers {
+            let params = QuoteExactInputSingleParams {
+                token_in: token_in.address,
+                token_out: token_out.address,
+                amount_in: amount_in_units,
+                fee,
+                sqrt_price_limit_x96: limits.sqrt_price_limit_x96.unwrap_or_default(),
+            };
+            match quoter.quote_exact_input_single(params).call().await {
+                Ok((amount_out, _, _, gas_estimate)) => {
+                    if best.is_none_or(|(_, out, _)| amount_out > out) {
+                        best = Some((fee, amount_out, gas_estimate));
+                    }
+                }
+                Err(e) => {
+                    debug!(fee, error = %e, "No quote at fee tier");
+                    last_error = Some(e.to_string());
+                }
+            }
+        }
+
+        let (fee, amount_out, gas_estimate) = best.ok_or_else(|| ExchangeError::ApiError {
+            code: -1,
+            message: format!(
+                "Quote failed: {}",
+                last_error.unwrap_or_else(|| "no pool".to_string())
+            ),
+        })?;
+        let encoded = encode_path(&[token_in.address, token_out.address], &[fee]);
+
+        Ok(RouteQuote {
+            tokens: vec![token_in.symbol.to_string(), token_out.symbol.to_string()],
+            fees: vec![fee],
+            encoded_path: Some(format!("0x{}", hex::encode(&encoded))),
+            amount_in,
+            amount_out: from_base_units(amount_out, token_out.decimals)?,
+            gas_estimate: Some(gas_estimate.low_u64()),
+        })
+    }
+
+    /// Quote a multi-hop path through the deepest pool of each hop
+    async fn quote_route(&self, path: &[Token], amount_in: Decimal) -> ExchangeResult<RouteQuote> {
+        let mut fees = Vec::with_capacity(path.len() - 1);
+        for hop in path.windows(2) {
+            let fee = self
+                .best_fee_tier(hop[0].address, hop[1].address)
+                .await
+                .ok_or_else(|| ExchangeError::ApiError {
+                    code: -1,
+                    message: format!("No pool for {}/{}", hop[0].symbol, hop[1].symbol),
+                })?;
+            fees.push(fee);
+        }
+
+        let token_in = &path[0];
+        let token_out = &path[path.len() - 1];
+        let addresses: Vec<Address> = path.iter().map(|t| t.address).collect();
+        let encoded = encode_path(&addresses, &fees);
+        let (amount_out, gas_estimate) = self
+            .quote_path(
+                encoded.clone(),
+                to_base_units(amount_in, token_in.decimals)?,
+            )
+            .await?;
+
+        Ok(RouteQuote {
+            tokens: path.iter().map(|t| t.symbol.to_string()).collect(),
+            fees,
+            encoded_path: Some(format!("0x{}", hex::encode(&encoded))),
+            amount_in,
+            amount_out: from_base_units(amount_out, token_out.decimals)?,
+            gas_estimate: Some(gas_estimate.low_u64()),
+        })
+    }
+
+    /// Discover the best route for a swap across direct and one-hop paths
+    pub async fn find_best_route(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_in: Decimal,
+    ) -> ExchangeResult<RouteQuote> {
+        let token_in = resolve_token(token_in)?;
+        let token_out = resolve_token(token_out)?;
+
+        let mut best: Option<RouteQuote> = None;
+
+        for path in candidate_paths(&token_in, &token_out) {
+            let quote = if path.len() == 2 {
+                self.quote_single(&path[0], &path[1], amount_in, &QuoteLimits::default())
+                    .await
+            } else {
+                self.quote_route(&path, amount_in).await
+            };
+            let quote = match quote {
+                Ok(quote) => quote,
+                Err(e) => {
+                    debug!(error = %e, "Skipping unquotable route");
+                    continue;
+                }
+            };
+
+            if best
+                .as_ref()
+                .is_none_or(|b| quote.amount_out > b.amount_out)
+            {
+                best = Some(quote);
+            }
+        }
+
+        best.ok_or_else(|| ExchangeError::ApiError {
+            code: -1,
+            message: format!("No route from {} to {}", token_in.symbol, token_out.symbol),
+        })
+    }
 }
 
 #[async_trait]
@@ -77,6 +506,9 @@ impl ExchangeAdapter for UniswapAdapter {
             volume_24h: Decimal::ZERO,
             high_24h: Decimal::ZERO,
             low_24h: Decimal::ZERO,
+            price_change_24h: Decimal::ZERO,
+            percent_change_24h: Decimal::ZERO,
+            fair_value: None,
             timestamp: Utc::now(),
         })
     }
@@ -117,6 +549,18 @@ impl DexAdapter for UniswapAdapter {
         token_out: &str,
         amount_in: Decimal,
     ) -> ExchangeResult<Decimal> {
+        Ok(self
+            .get_route_quote(token_in, token_out, amount_in)
+            .await?
+            .amount_out)
+    }
+
+    async fn get_route_quote(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_in: Decimal,
+    ) -> ExchangeResult<RouteQuote> {
         info!(
             token_in = token_in,
             token_out = token_out,
@@ -124,9 +568,33 @@ impl DexAdapter for UniswapAdapter {
             "Getting Uniswap quote"
         );
 
-        // Would call Uniswap Quoter contract
-        // This is a placeholder
-        Ok(amount_in)
+        let quote = self.find_best_route(token_in, token_out, amount_in).await?;
+
+        info!(
+            route = %quote.tokens.join(" -> "),
+            fees = ?quote.fees,
+            amount_out = %quote.amount_out,
+            "Best Uniswap route selected"
+        );
+
+        Ok(quote)
+    }
+
+    /// Quote the direct pool only, at the requested fee tier and price limit
+    async fn get_limited_quote(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_in: Decimal,
+        limits: &QuoteLimits,
+    ) -> ExchangeResult<RouteQuote> {
+        if limits.is_empty() {
+            return self.get_route_quote(token_in, token_out, amount_in).await;
+        }
+        let token_in = resolve_token(token_in)?;
+        let token_out = resolve_token(token_out)?;
+        self.quote_single(&token_in, &token_out, amount_in, limits)
+            .await
     }
 
     async fn swap(
@@ -135,8 +603,14 @@ impl DexAdapter for UniswapAdapter {
         token_out: &str,
         amount_in: Decimal,
         min_amount_out: Decimal,
-        _deadline: u64,
+        deadline: u64,
     ) -> ExchangeResult<String> {
+        let signer = self.signer.as_ref().ok_or_else(|| {
+            ExchangeError::UnsupportedOperation(
+                "Swap execution requires wallet configuration".to_string(),
+            )
+        })?;
+
         info!(
             token_in = token_in,
             token_out = token_out,
@@ -145,22 +619,208 @@ impl DexAdapter for UniswapAdapter {
             "Executing Uniswap swap"
         );
 
-        // Would build and send swap transaction
-        // This requires wallet/signer integration
+        let route = self.find_best_route(token_in, token_out, amount_in).await?;
+        if route.amount_out < min_amount_out {
+            return Err(ExchangeError::OrderRejected(format!(
+                "Best route returns {}, below the minimum {min_amount_out}",
+                route.amount_out
+            )));
+        }
 
-        Err(ExchangeError::UnsupportedOperation(
-            "Swap execution requires wallet configuration".to_string(),
-        ))
+        let token_in = resolve_token(token_in)?;
+        let token_out = resolve_token(token_out)?;
+        let path = route
+            .encoded_path
+            .as_deref()
+            .and_then(|path| hex::decode(path.trim_start_matches("0x")).ok())
+            .ok_or_else(|| ExchangeError::ApiError {
+                code: -1,
+                message: "Route has no encoded path".to_string(),
+            })?;
+
+        let router_address = Self::parse_address(UNISWAP_ROUTER)?;
+        let amount_in_units = to_base_units(amount_in, token_in.decimals)?;
+        if let Some(approvals) = &self.approvals {
+            approvals
+                .ensure(token_in.address, router_address, amount_in_units)
+                .await?;
+        }
+
+        let router = ISwapRouter::new(router_address, signer.clone());
+        let call = router.exact_input(ExactInputParams {
+            path: Bytes::from(path),
+            recipient: signer.address(),
+            deadline: U256::from(deadline),
+            amount_in: amount_in_units,
+            amount_out_minimum: to_base_units(min_amount_out, token_out.decimals)?,
+        });
+
+        let pending = call
+            .send()
+            .await
+            .map_err(|e| ExchangeError::OrderRejected(format!("Swap not sent: {e}")))?;
+        let tx_hash = pending.tx_hash();
+        info!(tx_hash = ?tx_hash, route = %route.tokens.join(" -> "), "Swap submitted");
+
+        let receipt = pending
+            .await
+            .map_err(|e| ExchangeError::ConnectionFailed(format!("Swap {tx_hash:?}: {e}")))?
+            .ok_or_else(|| ExchangeError::ApiError {
+                code: -1,
+                message: format!("Swap {tx_hash:?} dropped from the mempool"),
+            })?;
+        if receipt.status != Some(1u64.into()) {
+            return Err(ExchangeError::OrderRejected(format!(
+                "Swap {tx_hash:?} reverted"
+            )));
+        }
+        if let Some(approvals) = &self.approvals {
+            approvals.spent(token_in.address, router_address, amount_in_units);
+        }
+
+        Ok(format!("{tx_hash:?}"))
     }
 
+    /// Reserves, price and depth of the pair's deepest fee tier
+    ///
+    /// Reserves are the pool's token balances; `liquidity` is the active
+    /// in-range liquidity that prices the next swap.
     async fn get_pool_info(&self, token_a: &str, token_b: &str) -> ExchangeResult<PoolInfo> {
-        // Would query pool contract for reserves
+        let token_a = resolve_token(token_a)?;
+        let token_b = resolve_token(token_b)?;
+
+        let (fee, address, liquidity) = self
+            .deepest_pool(token_a.address, token_b.address)
+            .await
+            .ok_or_else(|| ExchangeError::ApiError {
+            code: -1,
+            message: format!("No pool for {}/{}", token_a.symbol, token_b.symbol),
+        })?;
+
+        let pool = IUniswapV3Pool::new(address, self.provider.clone());
+        let call_failed = |e: ContractError<Provider<Http>>| ExchangeError::ApiError {
+            code: -1,
+            message: format!("Pool query failed: {e}"),
+        };
+        let (sqrt_price_x96, tick, ..) = pool.slot_0().call().await.map_err(call_failed)?;
+        let tick_spacing = pool.tick_spacing().call().await.map_err(call_failed)?;
+        let token0 = pool.token_0().call().await.map_err(call_failed)?;
+
+        let reserve_a = self.token_balance(&token_a, address).await?;
+        let reserve_b = self.token_balance(&token_b, address).await?;
+
+        let a_is_token0 = token0 == token_a.address;
+        let (decimals_0, decimals_1) = if a_is_token0 {
+            (token_a.decimals, token_b.decimals)
+        } else {
+            (token_b.decimals, token_a.decimals)
+        };
+        let price = Decimal::from_f64_retain(pool_price(
+            sqrt_price_x96,
+            decimals_0,
+            decimals_1,
+            a_is_token0,
+        ))
+        .unwrap_or_default()
+        .round_dp(token_b.decimals);
+
         Ok(PoolInfo {
-            token_a: token_a.to_string(),
-            token_b: token_b.to_string(),
-            reserve_a: Decimal::ZERO,
-            reserve_b: Decimal::ZERO,
-            fee: Decimal::new(3, 3), // 0.3%
+            token_a: token_a.symbol.to_string(),
+            token_b: token_b.symbol.to_string(),
+            pool_address: Some(format!("{address:?}")),
+            reserve_a,
+            reserve_b,
+            // Fee tiers are in hundredths of a basis point
+            fee: Decimal::new(fee as i64, 6),
+            price,
+            liquidity: Some(liquidity),
+            tick: Some(tick),
+            tick_spacing: Some(tick_spacing),
+            tvl: reserve_a * price + reserve_b,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_path_packs_tokens_and_fees() {
+        let weth = resolve_token("ETH").unwrap();
+        let usdc = resolve_token("USDC").unwrap();
+        let dai = resolve_token("dai").unwrap();
+
+        let path = encode_path(&[weth.address, usdc.address, dai.address], &[500, 100]);
+
+        assert_eq!(path.len(), 20 * 3 + 3 * 2);
+        assert_eq!(&path[..20], weth.address.as_bytes());
+        assert_eq!(&path[20..23], &[0x00, 0x01, 0xf4]);
+        assert_eq!(&path[23..43], usdc.address.as_bytes());
+        assert_eq!(&path[43..46], &[0x00, 0x00, 0x64]);
+        assert_eq!(&path[46..], dai.address.as_bytes());
+    }
+
+    #[test]
+    fn test_pool_price_scales_decimals_and_orientation() {
+        // USDC (6) / WETH (18) pool at 2000 USDC per WETH: token1/token0 in
+        // base units is 1e18 / 2000e6 = 5e8, so sqrtPriceX96 = sqrt(5e8) * 2^96
+        let sqrt_price = U256::from_dec_str("1771595571142957166518320255467520").unwrap();
+
+        let usdc_in_weth = pool_price(sqrt_price, 6, 18, true);
+        let weth_in_usdc = pool_price(sqrt_price, 6, 18, false);
+
+        assert!((usdc_in_weth - 0.0005).abs() < 1e-9);
+        assert!((weth_in_usdc - 2000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_base_units_out_of_range_is_an_error() {
+        assert_eq!(
+            to_base_units(Decimal::new(15, 1), 18).unwrap(),
+            U256::from(1_500_000_000_000_000_000u128)
+        );
+
+        // Too large for a Decimal once scaled to 18 decimals
+        assert!(matches!(
+            to_base_units(Decimal::MAX, 18),
+            Err(ExchangeError::ApiError { .. })
+        ));
+        // 10^20 does not fit a u64
+        assert!(matches!(
+            to_base_units(Decimal::ONE, 20),
+            Err(ExchangeError::ApiError { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_limited_quote_rejects_unknown_fee_tier() {
+        let adapter = UniswapAdapter::new("http://localhost:8545", 1).unwrap();
+        let limits = QuoteLimits {
+            fee: Some(2500),
+            sqrt_price_limit_x96: None,
+        };
+
+        // Refused before any RPC is made
+        let err = adapter
+            .get_limited_quote("WETH", "USDC", Decimal::ONE, &limits)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Unsupported fee tier: 2500"));
+    }
+
+    #[test]
+    fn test_candidate_paths_skip_endpoints_as_intermediates() {
+        let link = resolve_token("LINK").unwrap();
+        let usdc = resolve_token("USDC").unwrap();
+
+        let paths = candidate_paths(&link, &usdc);
+
+        // Direct plus WETH, USDT and DAI hops
+        assert_eq!(paths.len(), 4);
+        assert_eq!(paths[0].len(), 2);
+        assert!(paths[1..]
+            .iter()
+            .all(|p| p.len() == 3 && p[1].symbol != "USDC" && p[1].symbol != "LINK"));
+    }
+}
@@ -0,0 +1,686 @@
+//! Execution Algorithms
+//!
+//! Splits a parent order into child orders routed through the normal
+//! exchange path:
+//! - TWAP: equal slices spread evenly over a duration
+//! - Iceberg: one visible clip at a time, the next placed once filled
+//! - POV: slices sized to a share of observed market volume
+//!
+//! Each parent runs on its own task and can be paused, resumed or
+//! cancelled. Cancelling also cancels the outstanding child order. A child
+//! the venue cancels, rejects or expires returns its unfilled quantity to
+//! later slices, so the parent completes only once fully filled.
+
+#![allow(dead_code)]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tokio::time;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::adapters::{ExchangeAdapter, ExchangeOrder};
+use crate::router::ExchangeRouter;
+use common::{ExchangeError, Order, OrderStatus};
+
+/// How often child order state and market volume are polled
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Execution algorithm parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Algo {
+    /// Equal slices spread evenly over `duration_secs`
+    Twap { duration_secs: u64, slices: u32 },
+
+    /// Show at most `clip_size` at a time
+    Iceberg {
+        #[serde(with = "common::decimal::flex")]
+        clip_size: Decimal,
+    },
+
+    /// Trade `participation_rate` of market volume, in clips of at most `max_clip`
+    Pov {
+        #[serde(with = "common::decimal::flex")]
+        participation_rate: Decimal,
+        #[serde(with = "common::decimal::flex")]
+        max_clip: Decimal,
+    },
+}
+
+/// Parent order lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlgoState {
+    Running,
+    Paused,
+    Cancelled,
+    Completed,
+    Failed,
+}
+
+impl AlgoState {
+    fn is_final(&self) -> bool {
+        matches!(
+            self,
+            AlgoState::Cancelled | AlgoState::Completed | AlgoState::Failed
+        )
+    }
+}
+
+/// Child order placed on an exchange
+#[derive(Debug, Clone, Serialize)]
+pub struct ChildOrder {
+    pub exchange: String,
+    pub exchange_order_id: String,
+    pub client_order_id: String,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub quantity: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub filled_quantity: Decimal,
+
+    pub status: OrderStatus,
+    pub placed_at: DateTime<Utc>,
+}
+
+impl ChildOrder {
+    /// Whether the venue is done with the child
+    fn is_final(&self) -> bool {
+        matches!(
+            self.status,
+            OrderStatus::Filled
+                | OrderStatus::Cancelled
+                | OrderStatus::Rejected
+                | OrderStatus::Expired
+        )
+    }
+}
+
+/// Parent order and its children
+#[derive(Debug, Clone, Serialize)]
+pub struct ParentOrder {
+    pub id: Uuid,
+    pub order: Order,
+    pub algo: Algo,
+    pub state: AlgoState,
+
+    /// Quantity sent to exchanges so far
+    #[serde(with = "rust_decimal::serde::str")]
+    pub released_quantity: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub filled_quantity: Decimal,
+
+    pub children: Vec<ChildOrder>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ParentOrder {
+    fn unreleased(&self) -> Decimal {
+        self.order.quantity - self.released_quantity
+    }
+
+    /// Roll a child's latest fills and status up to the parent
+    ///
+    /// A child ending cancelled, rejected or expired hands its unfilled
+    /// quantity back to be released in a later slice.
+    fn update_child(&mut self, index: usize, filled_quantity: Decimal, status: OrderStatus) {
+        let child = &mut self.children[index];
+        let previous = child.filled_quantity;
+        child.filled_quantity = filled_quantity;
+        child.status = status;
+        let returned = if child.is_final() {
+            (child.quantity - child.filled_quantity).max(Decimal::ZERO)
+        } else {
+            Decimal::ZERO
+        };
+
+        self.filled_quantity += filled_quantity - previous;
+        self.released_quantity -= returned;
+        self.updated_at = Utc::now();
+    }
+}
+
+/// Size of the next child slice
+///
+/// `market_volume` is the volume traded since the last slice and is only
+/// used by POV.
+pub fn next_slice(
+    algo: &Algo,
+    total: Decimal,
+    unreleased: Decimal,
+    market_volume: Decimal,
+) -> Decimal {
+    let size = match algo {
+        Algo::Twap { slices, .. } => total / Decimal::from((*slices).max(1)),
+        Algo::Iceberg { clip_size } => *clip_size,
+        Algo::Pov {
+            participation_rate,
+            max_clip,
+        } => (market_volume * participation_rate).min(*max_clip),
+    };
+
+    size.max(Decimal::ZERO).min(unreleased)
+}
+
+/// Runs parent orders and tracks their children
+pub struct AlgoEngine {
+    router: Arc<ExchangeRouter>,
+    parents: DashMap<Uuid, Arc<RwLock<ParentOrder>>>,
+}
+
+impl AlgoEngine {
+    pub fn new(router: Arc<ExchangeRouter>) -> Self {
+        Self {
+            router,
+            parents: DashMap::new(),
+        }
+    }
+
+    /// Accept a parent order and start executing it
+    pub fn submit(self: &Arc<Self>, order: Order, algo: Algo) -> Result<Uuid, ExchangeError> {
+        match &algo {
+            Algo::Twap {
+                duration_secs,
+                slices,
+            } if *duration_secs == 0 || *slices == 0 => {
+                return Err(ExchangeError::OrderRejected(
+                    "TWAP requires a positive duration and slice count".to_string(),
+                ));
+            }
+            Algo::Iceberg { clip_size } if *clip_size <= Decimal::ZERO => {
+                return Err(ExchangeError::OrderRejected(
+                    "Iceberg clip size must be positive".to_string(),
+                ));
+            }
+            Algo::Pov {
+                participation_rate,
+                max_clip,
+            } if *participation_rate <= Decimal::ZERO
+                || *participation_rate > Decimal::ONE
+                || *max_clip <= Decimal::ZERO =>
+            {
+                return Err(ExchangeError::OrderRejected(
+                    "POV requires a rate in (0, 1] and a positive max clip".to_string(),
+                ));
+            }
+            _ => {}
+        }
+
+        if self
+            .router
+            .route_for_user(order.user_id, &order.symbol)
+            .is_none()
+        {
+            return Err(ExchangeError::UnsupportedOperation(format!(
+                "No exchange routes {}",
+                order.symbol
+            )));
+        }
+
+        let now = Utc::now();
+        let parent = ParentOrder {
+            id: Uuid::new_v4(),
+            order,
+            algo,
+            state: AlgoState::Running,
+            released_quantity: Decimal::ZERO,
+            filled_quantity: Decimal::ZERO,
+            children: Vec::new(),
+            error: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let id = parent.id;
+
+        info!(parent_id = %id, algo = ?parent.algo, "Algo parent order accepted");
+
+        let parent = Arc::new(RwLock::new(parent));
+        self.parents.insert(id, parent.clone());
+
+        let engine = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = engine.run_parent(parent.clone()).await {
+                warn!(parent_id = %id, "Algo parent failed: {}", e);
+                let mut p = parent.write();
+                p.state = AlgoState::Failed;
+                p.error = Some(e.to_string());
+                p.updated_at = Utc::now();
+            }
+        });
+
+        Ok(id)
+    }
+
+    /// Get a snapshot of a parent order
+    pub fn get(&self, id: Uuid) -> Option<ParentOrder> {
+        self.parents.get(&id).map(|p| p.read().clone())
+    }
+
+    /// List all parent orders
+    pub fn list(&self) -> Vec<ParentOrder> {
+        self.parents.iter().map(|p| p.read().clone()).collect()
+    }
+
+    /// Pause a running parent; no new children are released
+    pub fn pause(&self, id: Uuid) -> Result<(), ExchangeError> {
+        self.transition(id, AlgoState::Running, AlgoState::Paused)
+    }
+
+    /// Resume a paused parent
+    pub fn resume(&self, id: Uuid) -> Result<(), ExchangeError> {
+        self.transition(id, AlgoState::Paused, AlgoState::Running)
+    }
+
+    /// Cancel a parent and its outstanding child order
+    pub async fn cancel(&self, id: Uuid) -> Result<(), ExchangeError> {
+        let parent =
+            self.parents.get(&id).map(|p| p.clone()).ok_or_else(|| {
+                ExchangeError::OrderRejected(format!("Unknown parent order {id}"))
+            })?;
+
+        let (user_id, symbol, open_children) = {
+            let mut p = parent.write();
+            if p.state.is_final() {
+                return Err(ExchangeError::OrderRejected(format!(
+                    "Parent order {id} is already {:?}",
+                    p.state
+                )));
+            }
+            p.state = AlgoState::Cancelled;
+            p.updated_at = Utc::now();
+
+            let open: Vec<ChildOrder> = p
+                .children
+                .iter()
+                .filter(|c| !c.is_final())
+                .cloned()
+                .collect();
+            (p.order.user_id, p.order.symbol.clone(), open)
+        };
+
+        for child in open_children {
+            if let Some(exchange) = self.router.get_user_exchange(user_id, &child.exchange) {
+                if let Err(e) = exchange
+                    .cancel_order(&symbol, &child.exchange_order_id)
+                    .await
+                {
+                    warn!(
+                        parent_id = %id,
+                        child = %child.exchange_order_id,
+                        "Failed to cancel child order: {}",
+                        e
+                    );
+                }
+            }
+        }
+
+        info!(parent_id = %id, "Algo parent order cancelled");
+        Ok(())
+    }
+
+    fn transition(&self, id: Uuid, from: AlgoState, to: AlgoState) -> Result<(), ExchangeError> {
+        let parent = self
+            .parents
+            .get(&id)
+            .ok_or_else(|| ExchangeError::OrderRejected(format!("Unknown parent order {id}")))?;
+
+        let mut p = parent.write();
+        if p.state != from {
+            return Err(ExchangeError::OrderRejected(format!(
+                "Parent order {id} is {:?}, expected {:?}",
+                p.state, from
+            )));
+        }
+        p.state = to;
+        p.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Drive a parent order until it completes or is cancelled
+    async fn run_parent(&self, parent: Arc<RwLock<ParentOrder>>) -> Result<(), ExchangeError> {
+        let (user_id, symbol, algo, total) = {
+            let p = parent.read();
+            (
+                p.order.user_id,
+                p.order.symbol.clone(),
+                p.algo.clone(),
+                p.order.quantity,
+            )
+        };
+
+        // Children go to the user's sub-account when one is mapped
+        let (exchange_name, exchange) =
+            self.router
+                .route_for_user(user_id, &symbol)
+                .ok_or_else(|| {
+                    ExchangeError::UnsupportedOperation(format!("No exchange routes {symbol}"))
+                })?;
+
+        let slice_interval = match &algo {
+            Algo::Twap {
+                duration_secs,
+                slices,
+            } => Duration::from_secs(*duration_secs) / (*slices).max(1),
+            _ => POLL_INTERVAL,
+        };
+
+        let mut last_volume = match &algo {
+            Algo::Pov { .. } => Some(exchange.get_market_data(&symbol).await?.volume_24h),
+            _ => None,
+        };
+
+        let mut ticker = time::interval(slice_interval);
+
+        loop {
+            ticker.tick().await;
+
+            self.refresh_children(&parent, exchange.as_ref()).await;
+
+            let (state, unreleased, has_open_child) = {
+                let p = parent.read();
+                (
+                    p.state,
+                    p.unreleased(),
+                    p.children.iter().any(|c| !c.is_final()),
+                )
+            };
+
+            match state {
+                AlgoState::Paused => continue,
+                s if s.is_final() => return Ok(()),
+                _ => {}
+            }
+
+            if unreleased == Decimal::ZERO {
+                if !has_open_child {
+                    let mut p = parent.write();
+                    p.state = AlgoState::Completed;
+                    p.updated_at = Utc::now();
+                    info!(parent_id = %p.id, filled = %p.filled_quantity, "Algo parent completed");
+                    return Ok(());
+                }
+                continue;
+            }
+
+            // Iceberg and POV keep a single child working at a time
+            if has_open_child && !matches!(algo, Algo::Twap { .. }) {
+                continue;
+            }
+
+            let market_volume = match (&algo, last_volume) {
+                (Algo::Pov { .. }, Some(prev)) => {
+                    let current = exchange.get_market_data(&symbol).await?.volume_24h;
+                    last_volume = Some(current);
+                    (current - prev).max(Decimal::ZERO)
+                }
+                _ => Decimal::ZERO,
+            };
+
+            let quantity = next_slice(&algo, total, unreleased, market_volume);
+            if quantity == Decimal::ZERO {
+                continue;
+            }
+
+            self.place_child(&parent, exchange.as_ref(), &exchange_name, quantity)
+                .await?;
+        }
+    }
+
+    /// Place a child slice and record it on the parent
+    async fn place_child(
+        &self,
+        parent: &Arc<RwLock<ParentOrder>>,
+        exchange: &dyn ExchangeAdapter,
+        exchange_name: &str,
+        quantity: Decimal,
+    ) -> Result<(), ExchangeError> {
+        let child = {
+            let p = parent.read();
+            let mut child = p.order.clone();
+            child.id = Uuid::new_v4();
+            child.client_order_id = format!("{}-{}", p.order.client_order_id, p.children.len());
+            child.quantity = quantity;
+            child.remaining_quantity = quantity;
+            child.filled_quantity = Decimal::ZERO;
+            child.status = OrderStatus::Pending;
+            child.created_at = Utc::now();
+            child.updated_at = child.created_at;
+            child
+        };
+
+        let placed: ExchangeOrder = exchange.place_order(&child).await?;
+
+        let mut p = parent.write();
+        p.released_quantity += quantity;
+        p.children.push(ChildOrder {
+            exchange: exchange_name.to_string(),
+            exchange_order_id: placed.exchange_order_id,
+            client_order_id: placed.client_order_id,
+            quantity,
+            filled_quantity: Decimal::ZERO,
+            status: OrderStatus::Pending,
+            placed_at: child.created_at,
+        });
+        let index = p.children.len() - 1;
+        p.update_child(index, placed.filled_quantity, placed.status);
+
+        metrics::counter!("algo_child_orders_placed").increment(1);
+        Ok(())
+    }
+
+    /// Poll open children and roll their fills up to the parent
+    async fn refresh_children(
+        &self,
+        parent: &Arc<RwLock<ParentOrder>>,
+        exchange: &dyn ExchangeAdapter,
+    ) {
+        let (symbol, open): (_, Vec<(usize, String)>) = {
+            let p = parent.read();
+            (
+                p.order.symbol.clone(),
+                p.children
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, c)| !c.is_final())
+                    .map(|(i, c)| (i, c.exchange_order_id.clone()))
+                    .collect(),
+            )
+        };
+
+        for (index, order_id) in open {
+            match exchange.get_order(&symbol, &order_id).await {
+                Ok(update) => {
+                    parent
+                        .write()
+                        .update_child(index, update.filled_quantity, update.status);
+                }
+                Err(e) => {
+                    warn!(child = %order_id, "Failed to refresh child order: {}", e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::bybit::BybitAdapter;
+    use crate::adapters::conformance::MockVenue;
+    use crate::adapters::kraken::KrakenAdapter;
+    use crate::adapters::okx::OkxAdapter;
+    use axum::http::{Method, StatusCode};
+    use common::{Symbol, SymbolRegistry};
+    use serde_json::json;
+
+    #[test]
+    fn test_next_slice_sizes() {
+        let total = Decimal::new(10, 0);
+
+        let twap = Algo::Twap {
+            duration_secs: 60,
+            slices: 4,
+        };
+        assert_eq!(
+            next_slice(&twap, total, total, Decimal::ZERO),
+            Decimal::new(25, 1)
+        );
+        // Last slice never exceeds what is left
+        assert_eq!(
+            next_slice(&twap, total, Decimal::ONE, Decimal::ZERO),
+            Decimal::ONE
+        );
+
+        let iceberg = Algo::Iceberg {
+            clip_size: Decimal::new(3, 0),
+        };
+        assert_eq!(
+            next_slice(&iceberg, total, Decimal::new(2, 0), Decimal::ZERO),
+            Decimal::new(2, 0)
+        );
+
+        let pov = Algo::Pov {
+            participation_rate: Decimal::new(1, 1),
+            max_clip: Decimal::new(5, 0),
+        };
+        assert_eq!(
+            next_slice(&pov, total, total, Decimal::new(20, 0)),
+            Decimal::new(2, 0)
+        );
+        assert_eq!(
+            next_slice(&pov, total, total, Decimal::new(100, 0)),
+            Decimal::new(5, 0)
+        );
+    }
+
+    /// Iceberg parent for 10 with a 4 child open on `exchange`
+    fn parent_with_child(exchange: &str) -> ParentOrder {
+        let now = Utc::now();
+        ParentOrder {
+            id: Uuid::new_v4(),
+            order: Order::builder().quantity(10).build(),
+            algo: Algo::Iceberg {
+                clip_size: Decimal::new(4, 0),
+            },
+            state: AlgoState::Running,
+            released_quantity: Decimal::new(4, 0),
+            filled_quantity: Decimal::ZERO,
+            children: vec![ChildOrder {
+                exchange: exchange.to_string(),
+                exchange_order_id: "1".to_string(),
+                client_order_id: "test-0".to_string(),
+                quantity: Decimal::new(4, 0),
+                filled_quantity: Decimal::ZERO,
+                status: OrderStatus::Open,
+                placed_at: now,
+            }],
+            error: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_cancelled_child_returns_its_remainder() {
+        let mut parent = parent_with_child("binance");
+
+        parent.update_child(0, Decimal::ONE, OrderStatus::PartiallyFilled);
+        assert_eq!(parent.filled_quantity, Decimal::ONE);
+        assert_eq!(parent.unreleased(), Decimal::new(6, 0));
+
+        // The 3 left unfilled go back to be sliced again
+        parent.update_child(0, Decimal::ONE, OrderStatus::Cancelled);
+        assert_eq!(parent.filled_quantity, Decimal::ONE);
+        assert_eq!(parent.released_quantity, Decimal::ONE);
+        assert_eq!(parent.unreleased(), Decimal::new(9, 0));
+    }
+
+    #[tokio::test]
+    async fn test_children_end_in_every_venue_spelling() {
+        let venue = MockVenue::start().await;
+        let symbols = Arc::new(SymbolRegistry::new());
+        let symbol = Symbol::new("BTC", "USDT");
+
+        // Each venue reports a full fill, then a cancel after 1 of the 4
+        let kraken = KrakenAdapter::new("key".to_string(), "c2VjcmV0".to_string(), symbols.clone())
+            .with_base_url(venue.url());
+        for (status, filled) in [("closed", "4"), ("canceled", "1")] {
+            venue
+                .mock_once(
+                    Method::POST,
+                    "/0/private/QueryOrders",
+                    StatusCode::OK,
+                    json!({ "error": [], "result": { "1": {
+                        "status": status,
+                        "vol_exec": filled,
+                    }}}),
+                )
+                .await;
+        }
+        let bybit = BybitAdapter::new("key".to_string(), "secret".to_string(), symbols.clone())
+            .with_base_url(venue.url());
+        for (status, filled) in [("Filled", "4"), ("Cancelled", "1")] {
+            venue
+                .mock_once(
+                    Method::GET,
+                    "/v5/order/realtime",
+                    StatusCode::OK,
+                    json!({ "retCode": 0, "retMsg": "OK", "result": { "list": [{
+                        "orderId": "1",
+                        "orderStatus": status,
+                        "cumExecQty": filled,
+                    }]}}),
+                )
+                .await;
+        }
+        let okx = OkxAdapter::new(
+            "key".to_string(),
+            "secret".to_string(),
+            "passphrase".to_string(),
+            symbols,
+        )
+        .with_base_url(venue.url());
+        for (state, filled) in [("filled", "4"), ("canceled", "1")] {
+            venue
+                .mock_once(
+                    Method::GET,
+                    "/api/v5/trade/order",
+                    StatusCode::OK,
+                    json!({ "code": "0", "msg": "", "data": [{
+                        "ordId": "1",
+                        "state": state,
+                        "accFillSz": filled,
+                    }]}),
+                )
+                .await;
+        }
+
+        let adapters: [&dyn ExchangeAdapter; 3] = [&kraken, &bybit, &okx];
+        for adapter in adapters {
+            // A filled child leaves 6 to release, a cancelled one hands back 3
+            for unreleased in [6, 9] {
+                let mut parent = parent_with_child(adapter.name());
+                let update = adapter.get_order(&symbol, "1").await.unwrap();
+                parent.update_child(0, update.filled_quantity, update.status);
+                assert!(
+                    parent.children[0].is_final(),
+                    "{}: {:?} not final",
+                    adapter.name(),
+                    update.status
+                );
+                assert_eq!(parent.unreleased(), Decimal::from(unreleased));
+            }
+        }
+    }
+}
@@ -1,24 +1,104 @@
 //! Exchange Gateway API
 
 use axum::{
-    extract::{Path, State},
-    routing::get,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{delete, get, post, put},
     Json, Router,
 };
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Deserialize;
 use std::sync::Arc;
 use tower_http::trace::TraceLayer;
+use uuid::Uuid;
 
+use crate::adapters::{
+    BorrowRate, ExchangeAdapter, ExchangeBalance, ExchangeOrder, FundingRate, PoolInfo,
+    QuoteLimits, RouteQuote,
+};
+use crate::algo::{Algo, AlgoEngine, ParentOrder};
 use crate::config::Config;
-use crate::router::ExchangeRouter;
+use crate::market::{MarketDataProxy, MarketQuery};
+use crate::rates::{RateSeries, RateStore};
+use crate::rebalance::{RebalanceReport, RebalanceRequest, Rebalancer};
+use crate::replace::{ReplaceLedger, ReplaceStart};
+use crate::router::{AggregatedQuote, ExchangeRouter};
+use crate::smart::{RoutedOrder, SmartRouter};
+use crate::subaccounts::SubAccountInfo;
+use common::{
+    AnyId, ExchangeError, Order, OrderRouted, OrderStatus, OrderType, SelfTradePrevention, Side,
+    Symbol, SymbolInfo, TimeInForce,
+};
 
-type AppState = Arc<ExchangeRouter>;
+#[derive(Clone)]
+struct AppState {
+    router: Arc<ExchangeRouter>,
+    algos: Arc<AlgoEngine>,
+    rates: Arc<RateStore>,
+    market: Arc<MarketDataProxy>,
+    smart: Arc<SmartRouter>,
+    rebalancer: Arc<Rebalancer>,
+    replacements: Arc<ReplaceLedger>,
+}
 
-pub async fn run_server(router: Arc<ExchangeRouter>, config: &Config) -> anyhow::Result<()> {
+pub async fn run_server(
+    router: Arc<ExchangeRouter>,
+    algos: Arc<AlgoEngine>,
+    rates: Arc<RateStore>,
+    market: Arc<MarketDataProxy>,
+    smart: Arc<SmartRouter>,
+    rebalancer: Arc<Rebalancer>,
+    config: &Config,
+) -> anyhow::Result<()> {
     let app = Router::new()
         .route("/health", get(health))
         .route("/exchanges", get(list_exchanges))
         .route("/exchanges/:name/status", get(exchange_status))
-        .with_state(router)
+        .route("/exchanges/:name/symbols", get(exchange_symbols))
+        .route("/exchanges/:name/orders", post(place_order))
+        .route(
+            "/exchanges/:name/orders/:id",
+            get(get_order).delete(cancel_order),
+        )
+        .route("/exchanges/:name/balances", get(exchange_balances))
+        .route("/orders", post(route_order))
+        .route("/orders/route", post(preview_route))
+        .route("/orders/:id", put(replace_order))
+        .route("/routes/paused", get(paused_routes))
+        .route("/routes/paused/:exchange/:symbol", delete(resume_route))
+        .route("/market/:exchange/:symbol/ticker", get(market_ticker))
+        .route("/market/:exchange/:symbol/depth", get(market_depth))
+        .route("/market/:exchange/:symbol/trades", get(market_trades))
+        .route(
+            "/algo-orders",
+            post(submit_algo_order).get(list_algo_orders),
+        )
+        .route(
+            "/algo-orders/:id",
+            get(get_algo_order).delete(cancel_algo_order),
+        )
+        .route("/algo-orders/:id/pause", post(pause_algo_order))
+        .route("/algo-orders/:id/resume", post(resume_algo_order))
+        .route("/dex/pools/:token_a/:token_b", get(dex_pools))
+        .route("/dex/quote/:token_in/:token_out", get(dex_quotes))
+        .route("/swap/best", post(best_swap))
+        .route("/accounts/:user_id", get(user_sub_accounts))
+        .route("/accounts/:user_id/balances", get(user_balances))
+        .route("/rebalance", post(rebalance))
+        .route("/rates", get(latest_rates))
+        .route("/rates/funding/:symbol", get(funding_rates))
+        .route("/rates/borrow/:asset", get(borrow_rates))
+        .with_state(AppState {
+            router,
+            algos,
+            rates,
+            market,
+            smart,
+            rebalancer,
+            replacements: Arc::new(ReplaceLedger::new()),
+        })
         .layer(TraceLayer::new_for_http());
 
     let addr = format!("{}:{}", config.host, config.port);
@@ -30,6 +110,30 @@ pub async fn run_server(router: Arc<ExchangeRouter>, config: &Config) -> anyhow:
     Ok(())
 }
 
+type ApiResult<T> = Result<Json<T>, (StatusCode, Json<serde_json::Value>)>;
+
+fn api_error(status: StatusCode, e: impl ToString) -> (StatusCode, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({ "error": e.to_string() })))
+}
+
+/// Status for an error returned by a venue
+fn venue_error(e: ExchangeError) -> (StatusCode, Json<serde_json::Value>) {
+    let status = match e {
+        ExchangeError::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+        ExchangeError::UnsupportedOperation(_) => StatusCode::NOT_IMPLEMENTED,
+        ExchangeError::OrderRejected(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        _ => StatusCode::BAD_GATEWAY,
+    };
+    api_error(status, e)
+}
+
+fn parse_symbol(symbol: &str) -> Result<Symbol, (StatusCode, Json<serde_json::Value>)> {
+    let (base, quote) = symbol
+        .split_once('-')
+        .ok_or_else(|| api_error(StatusCode::BAD_REQUEST, "Invalid symbol format"))?;
+    Ok(Symbol::new(base, quote))
+}
+
 async fn health() -> Json<serde_json::Value> {
     Json(serde_json::json!({
         "status": "healthy",
@@ -37,18 +141,690 @@ async fn health() -> Json<serde_json::Value> {
     }))
 }
 
-async fn list_exchanges(State(router): State<AppState>) -> Json<Vec<String>> {
-    Json(router.list_exchanges())
+async fn list_exchanges(State(state): State<AppState>) -> Json<Vec<String>> {
+    Json(state.router.list_exchanges())
 }
 
 async fn exchange_status(
-    State(router): State<AppState>,
+    State(state): State<AppState>,
     Path(name): Path<String>,
 ) -> Json<serde_json::Value> {
-    let available = router.is_exchange_available(&name).await;
+    let available = state.router.is_exchange_available(&name).await;
+    let breaker = state.router.breaker_status(&name);
 
     Json(serde_json::json!({
         "exchange": name,
-        "available": available
+        "available": available,
+        "breaker": breaker
+    }))
+}
+
+async fn exchange_symbols(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<Vec<SymbolInfo>> {
+    let exchange = state
+        .router
+        .get_exchange(&name)
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Unknown exchange"))?;
+
+    exchange
+        .get_symbol_info()
+        .await
+        .map(Json)
+        .map_err(|e| api_error(StatusCode::BAD_GATEWAY, e))
+}
+
+// ============== Venue Orders ==============
+
+#[derive(Debug, Deserialize)]
+struct OrderRequest {
+    user_id: Uuid,
+    client_order_id: Option<String>,
+    symbol: String,
+    side: Side,
+    order_type: OrderType,
+    time_in_force: Option<TimeInForce>,
+    #[serde(default, with = "common::decimal::flex_option")]
+    price: Option<Decimal>,
+    #[serde(with = "common::decimal::flex")]
+    quantity: Decimal,
+    /// While the quote asset is avoided, trade another stablecoin at the
+    /// observed rate instead of refusing the order
+    #[serde(default)]
+    reroute_quote: bool,
+}
+
+impl OrderRequest {
+    /// The order, off an avoided quote asset if the caller opted in
+    fn into_routed_order(
+        self,
+        router: &ExchangeRouter,
+    ) -> Result<Order, (StatusCode, Json<serde_json::Value>)> {
+        let reroute = self.reroute_quote;
+        let order = self.into_order()?;
+        router.reroute(&order, reroute).map_err(venue_error)
+    }
+
+    fn into_order(self) -> Result<Order, (StatusCode, Json<serde_json::Value>)> {
+        let symbol = parse_symbol(&self.symbol)?;
+        if self.quantity <= Decimal::ZERO {
+            return Err(api_error(
+                StatusCode::BAD_REQUEST,
+                "Quantity must be positive",
+            ));
+        }
+        if self.order_type == OrderType::Limit && self.price.is_none() {
+            return Err(api_error(
+                StatusCode::BAD_REQUEST,
+                "Limit orders require a price",
+            ));
+        }
+
+        let now = Utc::now();
+        let id = Uuid::new_v4();
+        Ok(Order {
+            id,
+            client_order_id: self.client_order_id.unwrap_or_else(|| id.to_string()),
+            user_id: self.user_id,
+            symbol,
+            side: self.side,
+            order_type: self.order_type,
+            time_in_force: self.time_in_force.unwrap_or(TimeInForce::GTC),
+            status: OrderStatus::Pending,
+            price: self.price,
+            stop_price: None,
+            trigger_source: None,
+            quantity: self.quantity,
+            filled_quantity: Decimal::ZERO,
+            remaining_quantity: self.quantity,
+            avg_fill_price: None,
+            sequence: 0,
+            created_at: now,
+            updated_at: now,
+            expire_at: None,
+            display_quantity: None,
+            self_trade_prevention: SelfTradePrevention::default(),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct VenueOrderQuery {
+    symbol: String,
+    user_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+struct VenueBalanceQuery {
+    user_id: Option<Uuid>,
+}
+
+/// Adapter for a user's account on `name`: their sub-account when mapped,
+/// otherwise the gateway's own unless sub-accounts are required
+fn venue_for_user(
+    state: &AppState,
+    name: &str,
+    user_id: Uuid,
+) -> Result<Arc<dyn ExchangeAdapter>, (StatusCode, Json<serde_json::Value>)> {
+    if state.router.get_exchange(name).is_none() {
+        return Err(api_error(StatusCode::NOT_FOUND, "Unknown exchange"));
+    }
+    state
+        .router
+        .get_user_exchange(user_id, name)
+        .cloned()
+        .ok_or_else(|| api_error(StatusCode::FORBIDDEN, "No sub-account on exchange"))
+}
+
+/// Place an order directly on a venue
+async fn place_order(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<OrderRequest>,
+) -> ApiResult<ExchangeOrder> {
+    let exchange = venue_for_user(&state, &name, req.user_id)?;
+    let order = req.into_routed_order(&state.router)?;
+
+    exchange
+        .place_order(&order)
+        .await
+        .map(Json)
+        .map_err(venue_error)
+}
+
+async fn get_order(
+    State(state): State<AppState>,
+    Path((name, order_id)): Path<(String, String)>,
+    Query(query): Query<VenueOrderQuery>,
+) -> ApiResult<ExchangeOrder> {
+    let exchange = venue_for_user(&state, &name, query.user_id)?;
+    let symbol = parse_symbol(&query.symbol)?;
+
+    exchange
+        .get_order(&symbol, &order_id)
+        .await
+        .map(Json)
+        .map_err(venue_error)
+}
+
+async fn cancel_order(
+    State(state): State<AppState>,
+    Path((name, order_id)): Path<(String, String)>,
+    Query(query): Query<VenueOrderQuery>,
+) -> ApiResult<serde_json::Value> {
+    let exchange = venue_for_user(&state, &name, query.user_id)?;
+    let symbol = parse_symbol(&query.symbol)?;
+
+    exchange
+        .cancel_order(&symbol, &order_id)
+        .await
+        .map_err(venue_error)?;
+
+    Ok(Json(serde_json::json!({
+        "exchange": name,
+        "order_id": order_id,
+        "cancelled": true
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+struct ReplaceOrderRequest {
+    exchange: String,
+    #[serde(flatten)]
+    order: OrderRequest,
+}
+
+/// Replace an open venue order
+///
+/// Venues that cannot amend cancel and place. Retries with the same
+/// client order id return the replacement instead of replacing again.
+async fn replace_order(
+    State(state): State<AppState>,
+    Path(order_id): Path<String>,
+    Json(req): Json<ReplaceOrderRequest>,
+) -> ApiResult<ExchangeOrder> {
+    let exchange = venue_for_user(&state, &req.exchange, req.order.user_id)?;
+    let client_order_id = req.order.client_order_id.clone();
+    let order = req.order.into_order()?;
+
+    match state
+        .replacements
+        .start(&req.exchange, &order_id, client_order_id.as_deref())
+    {
+        ReplaceStart::Proceed => {}
+        ReplaceStart::Done(replacement) => return Ok(Json(replacement)),
+        ReplaceStart::InFlight => {
+            return Err(api_error(
+                StatusCode::CONFLICT,
+                "Order is already being replaced",
+            ))
+        }
+        ReplaceStart::Replaced => {
+            return Err(api_error(
+                StatusCode::CONFLICT,
+                "Order was already replaced",
+            ))
+        }
+    }
+
+    let result = exchange.replace_order(&order_id, &order).await;
+    state.replacements.finish(&req.exchange, &order_id, &result);
+    result.map(Json).map_err(venue_error)
+}
+
+/// Balances on a venue, for a user's account or the gateway's own
+async fn exchange_balances(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(query): Query<VenueBalanceQuery>,
+) -> ApiResult<Vec<ExchangeBalance>> {
+    let exchange = match query.user_id {
+        Some(user_id) => venue_for_user(&state, &name, user_id)?,
+        None => state
+            .router
+            .get_exchange(&name)
+            .cloned()
+            .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Unknown exchange"))?,
+    };
+
+    exchange.get_balances().await.map(Json).map_err(venue_error)
+}
+
+/// Place an order on the venue or venues chosen by the order router
+async fn route_order(
+    State(state): State<AppState>,
+    Json(req): Json<OrderRequest>,
+) -> ApiResult<Vec<RoutedOrder>> {
+    let order = req.into_routed_order(&state.router)?;
+    state
+        .smart
+        .execute(&order)
+        .await
+        .map(Json)
+        .map_err(venue_error)
+}
+
+/// Venues the order router would choose, without placing the order
+async fn preview_route(
+    State(state): State<AppState>,
+    Json(req): Json<OrderRequest>,
+) -> ApiResult<OrderRouted> {
+    let order = req.into_routed_order(&state.router)?;
+    state
+        .smart
+        .route(&order)
+        .await
+        .map(Json)
+        .map_err(venue_error)
+}
+
+/// Venue routes paused after abnormal fills, as (exchange, symbol)
+async fn paused_routes(State(state): State<AppState>) -> Json<Vec<(String, String)>> {
+    Json(state.router.paused_routes())
+}
+
+async fn resume_route(
+    State(state): State<AppState>,
+    Path((exchange, symbol)): Path<(String, String)>,
+) -> ApiResult<serde_json::Value> {
+    let symbol = parse_symbol(&symbol)?;
+    if !state.router.is_route_paused(&exchange, &symbol) {
+        return Err(api_error(StatusCode::NOT_FOUND, "Route is not paused"));
+    }
+    state.router.set_route_paused(&exchange, &symbol, false);
+    tracing::info!(exchange = %exchange, symbol = %symbol, "Route resumed");
+
+    Ok(Json(serde_json::json!({
+        "exchange": exchange,
+        "symbol": symbol.to_string(),
+        "paused": false
+    })))
+}
+
+// ============== Market Data Proxy ==============
+
+#[derive(Debug, Deserialize)]
+struct MarketLimitQuery {
+    limit: Option<u32>,
+}
+
+async fn market_ticker(
+    State(state): State<AppState>,
+    Path((exchange, symbol)): Path<(String, String)>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    proxy_market_data(&state, &exchange, &symbol, MarketQuery::Ticker).await
+}
+
+async fn market_depth(
+    State(state): State<AppState>,
+    Path((exchange, symbol)): Path<(String, String)>,
+    Query(query): Query<MarketLimitQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let limit = MarketDataProxy::clamp_limit(query.limit);
+    proxy_market_data(&state, &exchange, &symbol, MarketQuery::Depth(limit)).await
+}
+
+async fn market_trades(
+    State(state): State<AppState>,
+    Path((exchange, symbol)): Path<(String, String)>,
+    Query(query): Query<MarketLimitQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let limit = MarketDataProxy::clamp_limit(query.limit);
+    proxy_market_data(&state, &exchange, &symbol, MarketQuery::Trades(limit)).await
+}
+
+/// Venue market data through the proxy, with its cache status in `X-Cache`
+async fn proxy_market_data(
+    state: &AppState,
+    exchange: &str,
+    symbol: &str,
+    query: MarketQuery,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    if state.router.get_exchange(exchange).is_none() {
+        return Err(api_error(StatusCode::NOT_FOUND, "Unknown exchange"));
+    }
+    let (base, quote) = symbol
+        .split_once('-')
+        .ok_or_else(|| api_error(StatusCode::BAD_REQUEST, "Invalid symbol format"))?;
+
+    let (data, cache) = state
+        .market
+        .get(exchange, &Symbol::new(base, quote), query)
+        .await
+        .map_err(venue_error)?;
+
+    Ok(([("x-cache", cache.as_str())], Json(data)))
+}
+
+// ============== DEX Liquidity ==============
+
+#[derive(Debug, serde::Serialize)]
+struct DexPool {
+    dex: String,
+    pool: Option<PoolInfo>,
+    error: Option<String>,
+}
+
+/// Depth of the pair's pool on every DEX
+async fn dex_pools(
+    State(state): State<AppState>,
+    Path((token_a, token_b)): Path<(String, String)>,
+) -> Json<Vec<DexPool>> {
+    let pools = state
+        .router
+        .dex_pools(&token_a, &token_b)
+        .await
+        .into_iter()
+        .map(|(dex, result)| match result {
+            Ok(pool) => DexPool {
+                dex,
+                pool: Some(pool),
+                error: None,
+            },
+            Err(e) => DexPool {
+                dex,
+                pool: None,
+                error: Some(e.to_string()),
+            },
+        })
+        .collect();
+
+    Json(pools)
+}
+
+#[derive(Debug, Deserialize)]
+struct QuoteQuery {
+    #[serde(with = "rust_decimal::serde::str")]
+    amount_in: Decimal,
+    /// Fee tier in hundredths of a basis point
+    fee: Option<u32>,
+    /// Decimal `sqrtPriceX96` the swap may not move the pool past
+    sqrt_price_limit_x96: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct DexQuote {
+    dex: String,
+    quote: Option<RouteQuote>,
+    error: Option<String>,
+}
+
+/// On-chain swap quote for the pair on every DEX
+async fn dex_quotes(
+    State(state): State<AppState>,
+    Path((token_in, token_out)): Path<(String, String)>,
+    Query(query): Query<QuoteQuery>,
+) -> ApiResult<Vec<DexQuote>> {
+    if query.amount_in <= Decimal::ZERO {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "amount_in must be positive",
+        ));
+    }
+    let sqrt_price_limit_x96 = query
+        .sqrt_price_limit_x96
+        .map(|limit| ethers::types::U256::from_dec_str(&limit))
+        .transpose()
+        .map_err(|_| api_error(StatusCode::BAD_REQUEST, "Invalid sqrt_price_limit_x96"))?;
+    let limits = QuoteLimits {
+        fee: query.fee,
+        sqrt_price_limit_x96,
+    };
+
+    let quotes = state
+        .router
+        .dex_quotes(&token_in, &token_out, query.amount_in, &limits)
+        .await
+        .into_iter()
+        .map(|(dex, result)| match result {
+            Ok(quote) => DexQuote {
+                dex,
+                quote: Some(quote),
+                error: None,
+            },
+            Err(e) => DexQuote {
+                dex,
+                quote: None,
+                error: Some(e.to_string()),
+            },
+        })
+        .collect();
+
+    Ok(Json(quotes))
+}
+
+#[derive(Debug, Deserialize)]
+struct BestSwapRequest {
+    token_in: String,
+    token_out: String,
+    #[serde(with = "rust_decimal::serde::str")]
+    amount_in: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    min_amount_out: Decimal,
+    /// Unix seconds after which the swap reverts; two minutes by default
+    deadline: Option<u64>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct BestSwap {
+    #[serde(flatten)]
+    route: AggregatedQuote,
+    tx_hash: String,
+}
+
+/// Swap on whichever DEX returns the most after gas
+async fn best_swap(
+    State(state): State<AppState>,
+    Json(req): Json<BestSwapRequest>,
+) -> ApiResult<BestSwap> {
+    if req.amount_in <= Decimal::ZERO {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "amount_in must be positive",
+        ));
+    }
+    let deadline = req
+        .deadline
+        .unwrap_or_else(|| (Utc::now().timestamp() + 120) as u64);
+
+    let (route, tx_hash) = state
+        .router
+        .aggregator()
+        .swap(
+            &req.token_in,
+            &req.token_out,
+            req.amount_in,
+            req.min_amount_out,
+            deadline,
+        )
+        .await
+        .map_err(venue_error)?;
+
+    Ok(Json(BestSwap { route, tx_hash }))
+}
+
+// ============== Sub-Accounts ==============
+
+async fn user_sub_accounts(
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> Json<Vec<SubAccountInfo>> {
+    Json(state.router.sub_accounts(user_id))
+}
+
+#[derive(Debug, serde::Serialize)]
+struct SubAccountBalances {
+    #[serde(flatten)]
+    account: SubAccountInfo,
+    balances: Vec<ExchangeBalance>,
+    error: Option<String>,
+}
+
+/// Balances of each of a user's sub-accounts, queried with its own credentials
+async fn user_balances(
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<Vec<SubAccountBalances>> {
+    let accounts = state.router.sub_accounts(user_id);
+    if accounts.is_empty() {
+        return Err(api_error(StatusCode::NOT_FOUND, "No sub-accounts for user"));
+    }
+
+    let mut result = Vec::with_capacity(accounts.len());
+    for account in accounts {
+        let balances = match state
+            .router
+            .sub_account_exchange(user_id, &account.exchange)
+        {
+            Some(exchange) => exchange.get_balances().await,
+            None => continue,
+        };
+
+        result.push(match balances {
+            Ok(balances) => SubAccountBalances {
+                account,
+                balances,
+                error: None,
+            },
+            Err(e) => SubAccountBalances {
+                account,
+                balances: Vec::new(),
+                error: Some(e.to_string()),
+            },
+        });
+    }
+
+    Ok(Json(result))
+}
+
+// ============== Rebalancing ==============
+
+/// Trade a user's portfolio toward target weights, or preview the trades
+async fn rebalance(
+    State(state): State<AppState>,
+    Json(mut req): Json<RebalanceRequest>,
+) -> ApiResult<RebalanceReport> {
+    req.normalize();
+    req.validate()
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?;
+
+    state
+        .rebalancer
+        .run(&req)
+        .await
+        .map(Json)
+        .map_err(venue_error)
+}
+
+// ============== Funding & Borrow Rates ==============
+
+#[derive(Debug, Deserialize)]
+struct RateQuery {
+    venue: Option<String>,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+}
+
+async fn latest_rates(State(state): State<AppState>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "funding": state.rates.latest_funding(),
+        "borrow": state.rates.latest_borrow(),
     }))
 }
+
+async fn funding_rates(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+    Query(query): Query<RateQuery>,
+) -> ApiResult<Vec<RateSeries<FundingRate>>> {
+    let (base, quote) = symbol
+        .split_once('-')
+        .ok_or_else(|| api_error(StatusCode::BAD_REQUEST, "Invalid symbol format"))?;
+
+    Ok(Json(state.rates.funding(
+        &Symbol::new(base, quote),
+        query.venue.as_deref(),
+        query.start,
+        query.end,
+    )))
+}
+
+async fn borrow_rates(
+    State(state): State<AppState>,
+    Path(asset): Path<String>,
+    Query(query): Query<RateQuery>,
+) -> Json<Vec<RateSeries<BorrowRate>>> {
+    Json(state.rates.borrow(
+        &asset.to_uppercase(),
+        query.venue.as_deref(),
+        query.start,
+        query.end,
+    ))
+}
+
+// ============== Execution Algos ==============
+
+#[derive(Debug, Deserialize)]
+struct SubmitAlgoOrderRequest {
+    #[serde(flatten)]
+    order: OrderRequest,
+    algo: Algo,
+}
+
+async fn submit_algo_order(
+    State(state): State<AppState>,
+    Json(req): Json<SubmitAlgoOrderRequest>,
+) -> ApiResult<serde_json::Value> {
+    let order = req.order.into_routed_order(&state.router)?;
+
+    let parent_id = state
+        .algos
+        .submit(order, req.algo)
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?;
+
+    Ok(Json(serde_json::json!({ "parent_id": parent_id })))
+}
+
+async fn list_algo_orders(State(state): State<AppState>) -> Json<Vec<ParentOrder>> {
+    Json(state.algos.list())
+}
+
+async fn get_algo_order(
+    State(state): State<AppState>,
+    Path(AnyId(id)): Path<AnyId>,
+) -> ApiResult<ParentOrder> {
+    state
+        .algos
+        .get(id)
+        .map(Json)
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Parent order not found"))
+}
+
+async fn pause_algo_order(
+    State(state): State<AppState>,
+    Path(AnyId(id)): Path<AnyId>,
+) -> ApiResult<ParentOrder> {
+    state.algos.pause(id).map_err(parent_error)?;
+    get_algo_order(State(state), Path(id)).await
+}
+
+async fn resume_algo_order(
+    State(state): State<AppState>,
+    Path(AnyId(id)): Path<AnyId>,
+) -> ApiResult<ParentOrder> {
+    state.algos.resume(id).map_err(parent_error)?;
+    get_algo_order(State(state), Path(id)).await
+}
+
+async fn cancel_algo_order(
+    State(state): State<AppState>,
+    Path(AnyId(id)): Path<AnyId>,
+) -> ApiResult<ParentOrder> {
+    state.algos.cancel(id).await.map_err(parent_error)?;
+    get_algo_order(State(state), Path(id)).await
+}
+
+fn parent_error(e: ExchangeError) -> (StatusCode, Json<serde_json::Value>) {
+    api_error(StatusCode::CONFLICT, e)
+}
@@ -0,0 +1,220 @@
+//! Historical Kline Backfill
+//!
+//! Newly listed internal symbols have no trades yet, so their charts would
+//! start empty. On startup, closed klines for the configured symbols and
+//! intervals are pulled from a venue page by page, starting
+//! `kline_backfill_days` back, and published as candle events on
+//! `market.candles` for the pipeline's candle store. Consumers key
+//! candles by symbol, interval and open time, so rerunning a backfill is
+//! harmless.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use tracing::{info, warn};
+
+use crate::adapters::{ExchangeAdapter, ExchangeResult};
+use crate::config::Config;
+use crate::events::EventPublisher;
+use crate::router::ExchangeRouter;
+use common::{Candle, ExchangeError, Symbol};
+
+/// Klines requested per page; Binance's maximum
+const PAGE_LIMIT: u32 = 1000;
+
+pub struct KlineBackfill {
+    router: Arc<ExchangeRouter>,
+    publisher: Arc<EventPublisher>,
+    exchange: String,
+    symbols: Vec<Symbol>,
+    intervals: Vec<String>,
+    lookback: chrono::Duration,
+}
+
+impl KlineBackfill {
+    pub fn new(
+        router: Arc<ExchangeRouter>,
+        publisher: Arc<EventPublisher>,
+        config: &Config,
+    ) -> Self {
+        let symbols = config
+            .kline_backfill_symbols
+            .split(',')
+            .filter_map(|s| s.trim().split_once('-'))
+            .map(|(base, quote)| Symbol::new(base, quote))
+            .collect();
+        let intervals = config
+            .kline_backfill_intervals
+            .split(',')
+            .map(|i| i.trim().to_string())
+            .filter(|i| !i.is_empty())
+            .collect();
+
+        Self {
+            router,
+            publisher,
+            exchange: config.kline_backfill_exchange.clone(),
+            symbols,
+            intervals,
+            lookback: chrono::Duration::days(config.kline_backfill_days as i64),
+        }
+    }
+
+    /// Backfill every configured symbol and interval once
+    pub async fn run(self) -> Result<()> {
+        let Some(exchange) = self.router.get_exchange(&self.exchange).cloned() else {
+            warn!(exchange = %self.exchange, "Kline backfill venue not configured");
+            return Ok(());
+        };
+
+        info!(
+            exchange = %self.exchange,
+            symbols = self.symbols.len(),
+            intervals = ?self.intervals,
+            "Kline backfill started"
+        );
+
+        let now = Utc::now();
+        let start = now - self.lookback;
+        for symbol in &self.symbols {
+            for interval in &self.intervals {
+                let candles = match closed_klines(exchange.as_ref(), symbol, interval, start, now)
+                    .await
+                {
+                    Ok(candles) => candles,
+                    Err(ExchangeError::UnsupportedOperation(_)) => {
+                        warn!(exchange = %self.exchange, "Venue does not serve klines");
+                        return Ok(());
+                    }
+                    Err(e) => {
+                        warn!(symbol = %symbol, interval = %interval, "Kline backfill failed: {}", e);
+                        continue;
+                    }
+                };
+
+                let published = self.publish(&candles).await;
+                metrics::counter!("klines_backfilled", "interval" => interval.clone())
+                    .increment(published as u64);
+                info!(symbol = %symbol, interval = %interval, published, "Klines backfilled");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Publish candles, returning how many were sent
+    async fn publish(&self, candles: &[Candle]) -> usize {
+        let mut published = 0;
+        for candle in candles {
+            match self.publisher.publish(candle.clone()).await {
+                Ok(()) => published += 1,
+                Err(e) => warn!(symbol = %candle.symbol, "Failed to publish candle: {}", e),
+            }
+        }
+        published
+    }
+}
+
+/// Klines of `symbol` from `start` that closed before `now`, paging forward
+/// until the venue returns a short page or the open kline
+async fn closed_klines(
+    adapter: &dyn ExchangeAdapter,
+    symbol: &Symbol,
+    interval: &str,
+    mut start: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> ExchangeResult<Vec<Candle>> {
+    let mut candles = Vec::new();
+    loop {
+        let page = adapter
+            .get_klines(symbol, interval, start, PAGE_LIMIT)
+            .await?;
+        let full = page.len() >= PAGE_LIMIT as usize;
+        let Some(last) = page.last() else {
+            break;
+        };
+        let done = !full || last.close_time >= now;
+        start = last.close_time + chrono::Duration::milliseconds(1);
+
+        candles.extend(page.into_iter().filter(|c| c.close_time < now));
+        if done {
+            break;
+        }
+    }
+    Ok(candles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::conformance::MockVenue;
+    use crate::adapters::BinanceAdapter;
+    use axum::http::{Method, StatusCode};
+    use common::SymbolRegistry;
+    use serde_json::json;
+
+    fn kline(open_ms: i64) -> serde_json::Value {
+        json!([
+            open_ms,
+            "100.0",
+            "110.0",
+            "90.0",
+            "105.0",
+            "12.5",
+            open_ms + 59_999,
+            "1300.0",
+            42,
+            "6.0",
+            "630.0",
+            "0"
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_closed_klines_paginated() {
+        let venue = MockVenue::start().await;
+        let adapter = BinanceAdapter::new(
+            "key".to_string(),
+            "secret".to_string(),
+            Arc::new(SymbolRegistry::new()),
+        )
+        .with_base_url(venue.url());
+
+        let now = Utc::now();
+        let minute = 60_000;
+        let first = now.timestamp_millis() / minute * minute - (PAGE_LIMIT as i64 + 2) * minute;
+
+        // A full page of closed klines, then the rest up to the open one
+        let page: Vec<_> = (0..PAGE_LIMIT as i64)
+            .map(|i| kline(first + i * minute))
+            .collect();
+        venue
+            .mock_once(Method::GET, "/api/v3/klines", StatusCode::OK, json!(page))
+            .await;
+        let rest: Vec<_> = (PAGE_LIMIT as i64..PAGE_LIMIT as i64 + 3)
+            .map(|i| kline(first + i * minute))
+            .collect();
+        venue
+            .mock(Method::GET, "/api/v3/klines", StatusCode::OK, json!(rest))
+            .await;
+
+        let start = DateTime::from_timestamp_millis(first).unwrap();
+        let candles = closed_klines(&adapter, &Symbol::new("BTC", "USDT"), "1m", start, now)
+            .await
+            .unwrap();
+
+        assert_eq!(candles.len(), PAGE_LIMIT as usize + 2);
+        assert!(candles.iter().all(|c| c.close_time < now));
+        assert_eq!(candles[0].trade_count, 42);
+
+        // The second page starts just after the first page's last kline
+        let second = venue
+            .last_request(&Method::GET, "/api/v3/klines")
+            .await
+            .unwrap();
+        let expected = first + (PAGE_LIMIT as i64 - 1) * minute + 59_999 + 1;
+        assert_eq!(second.params()["startTime"], expected.to_string());
+        assert_eq!(second.params()["symbol"], "BTCUSDT");
+    }
+}
@@ -2,10 +2,14 @@
 
 #![allow(dead_code)]
 
-use anyhow::Result;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize)]
+use common::config::{schemes, Checks, Validate};
+
+use crate::adapters::{ApprovalPolicy, LimitMode};
+use crate::smart::RoutingMode;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     #[serde(default = "default_host")]
     pub host: String,
@@ -19,19 +23,241 @@ pub struct Config {
     pub redis_url: String,
     pub kafka_brokers: String,
 
+    /// Reference-data service for venue symbol mapping
+    #[serde(default)]
+    pub reference_data_url: Option<String>,
+
     // Ethereum
     pub eth_rpc_url: String,
 
     #[serde(default = "default_chain_id")]
     pub chain_id: u64,
 
+    /// Hex private key of the wallet DEX swaps are sent from
+    pub wallet_private_key: Option<String>,
+
+    /// Encrypted JSON keystore, used when no private key is set
+    pub wallet_keystore_path: Option<String>,
+    pub wallet_keystore_password: Option<String>,
+
+    /// Router approvals for swaps: `exact` per swap, or `max` once
+    #[serde(default)]
+    pub wallet_approval_policy: ApprovalPolicy,
+
+    /// JSON file listing hot and cold wallets per chain; its first hot
+    /// wallet for `chain_id` signs swaps when no key is set above
+    #[serde(default)]
+    pub wallets_file: Option<String>,
+
+    /// Comma-separated `TOKEN=amount` hot wallet balances above which the
+    /// excess is swept to the chain's cold wallet; no sweeps when empty
+    #[serde(default)]
+    pub wallet_sweep_thresholds: String,
+
+    #[serde(default = "default_wallet_sweep_interval")]
+    pub wallet_sweep_interval_secs: u64,
+
     // Exchange API Keys (encrypted in production)
     pub binance_api_key: Option<String>,
     pub binance_api_secret: Option<String>,
 
+    /// Kraken API key and base64-encoded private key
+    pub kraken_api_key: Option<String>,
+    pub kraken_api_secret: Option<String>,
+
+    pub bybit_api_key: Option<String>,
+    pub bybit_api_secret: Option<String>,
+
+    pub okx_api_key: Option<String>,
+    pub okx_api_secret: Option<String>,
+    pub okx_passphrase: Option<String>,
+
     pub coinbase_api_key: Option<String>,
     pub coinbase_api_secret: Option<String>,
     pub coinbase_passphrase: Option<String>,
+
+    /// JSON file mapping users to venue sub-accounts and their credentials
+    #[serde(default)]
+    pub subaccounts_file: Option<String>,
+
+    /// Reject external orders from users without a sub-account on the venue
+    #[serde(default)]
+    pub require_sub_accounts: bool,
+
+    // Order routing
+    /// `static` sends orders to the symbol's venue, `smart` to the best price
+    #[serde(default)]
+    pub routing_mode: RoutingMode,
+
+    /// Let smart routing split an order across venues
+    #[serde(default = "default_smart_routing_split")]
+    pub smart_routing_split: bool,
+
+    /// Book levels per side read from each venue when smart routing
+    #[serde(default = "default_smart_routing_depth")]
+    pub smart_routing_depth: u32,
+
+    // Portfolio rebalancing
+    /// Slippage allowance for rebalance trades when a request sets none
+    #[serde(default = "default_rebalance_max_slippage_bps")]
+    pub rebalance_max_slippage_bps: u32,
+
+    /// Smallest rebalance trade, in the quote asset, when a request sets none
+    #[serde(default = "default_rebalance_min_trade_value")]
+    pub rebalance_min_trade_value: u64,
+
+    /// Attempts per venue request on transient errors; 1 disables retries
+    #[serde(default = "default_venue_max_attempts")]
+    pub venue_max_attempts: u32,
+
+    /// Backoff before the first retry, doubled on each further retry
+    #[serde(default = "default_venue_retry_base_ms")]
+    pub venue_retry_base_ms: u64,
+
+    /// Request budgets as `venue.class=requests/window`, class being
+    /// `order`, `account` or `market`
+    #[serde(default = "default_venue_rate_limits")]
+    pub venue_rate_limits: String,
+
+    /// Request weights as `venue.operation=weight`; operations default to 1
+    #[serde(default = "default_venue_request_weights")]
+    pub venue_request_weights: String,
+
+    /// `queue` requests over budget until it refills, or `reject` them
+    #[serde(default)]
+    pub venue_rate_limit_mode: LimitMode,
+
+    /// Longest a queued request waits before it is rejected
+    #[serde(default = "default_venue_rate_limit_max_wait_ms")]
+    pub venue_rate_limit_max_wait_ms: u64,
+
+    /// Consecutive connection or API failures that open a venue's breaker
+    #[serde(default = "default_venue_breaker_threshold")]
+    pub venue_breaker_threshold: u32,
+
+    /// Seconds an open breaker waits before probing the venue again
+    #[serde(default = "default_venue_breaker_cooldown_secs")]
+    pub venue_breaker_cooldown_secs: u64,
+
+    // Venue clock synchronization
+    /// Seconds between venue server time checks; 0 disables
+    #[serde(default = "default_clock_sync_interval")]
+    pub clock_sync_interval_secs: u64,
+
+    /// Clock offset from a venue beyond which a risk alert is raised
+    #[serde(default = "default_clock_max_drift_ms")]
+    pub clock_max_drift_ms: u64,
+
+    // Stablecoin depeg monitor
+    #[serde(default = "default_depeg_monitor_enabled")]
+    pub depeg_monitor_enabled: bool,
+
+    /// Maximum tolerated deviation from parity in basis points
+    #[serde(default = "default_depeg_band_bps")]
+    pub depeg_band_bps: u32,
+
+    #[serde(default = "default_depeg_check_interval")]
+    pub depeg_check_interval_secs: u64,
+
+    /// Route away from depegged stablecoins while they are off-peg
+    #[serde(default)]
+    pub depeg_reroute: bool,
+
+    // Fill price check
+    /// Deviation from the internal midprice that flags a fill; 0 disables
+    #[serde(default = "default_fill_price_max_deviation_bps")]
+    pub fill_price_max_deviation_bps: u32,
+
+    /// Pause the venue's route for the symbol after an abnormal fill
+    #[serde(default)]
+    pub fill_price_pause_routes: bool,
+
+    // Observability
+    #[serde(default = "default_metrics_port")]
+    pub metrics_port: u16,
+
+    // Funding and borrow rate collection
+    #[serde(default = "default_rates_collector_enabled")]
+    pub rates_collector_enabled: bool,
+
+    #[serde(default = "default_rates_collect_interval")]
+    pub rates_collect_interval_secs: u64,
+
+    /// How long collected rate series are kept
+    #[serde(default = "default_rates_retention_days")]
+    pub rates_retention_days: u32,
+
+    /// Comma-separated perpetuals to collect funding for, e.g. `BTC-USDT,ETH-USDT`
+    #[serde(default = "default_funding_symbols")]
+    pub funding_symbols: String,
+
+    /// Comma-separated assets to collect borrow rates for
+    #[serde(default = "default_borrow_assets")]
+    pub borrow_assets: String,
+
+    // Historical kline backfill
+    /// Comma-separated symbols to backfill on startup; none when empty
+    #[serde(default)]
+    pub kline_backfill_symbols: String,
+
+    /// Comma-separated kline intervals to backfill
+    #[serde(default = "default_kline_backfill_intervals")]
+    pub kline_backfill_intervals: String,
+
+    /// How far back to backfill
+    #[serde(default = "default_kline_backfill_days")]
+    pub kline_backfill_days: u32,
+
+    /// Venue klines are pulled from
+    #[serde(default = "default_kline_backfill_exchange")]
+    pub kline_backfill_exchange: String,
+
+    // Market data proxy
+    /// How long proxied venue responses are served from cache
+    #[serde(default = "default_market_proxy_cache_ms")]
+    pub market_proxy_cache_ms: u64,
+
+    /// Upstream market data requests per second per venue; 0 is unlimited
+    #[serde(default = "default_market_proxy_requests_per_sec")]
+    pub market_proxy_requests_per_sec: u32,
+
+    // Binance WebSocket streams
+    /// Stream fills from the Binance user-data stream when an API key is set
+    #[serde(default = "default_binance_user_stream_enabled")]
+    pub binance_user_stream_enabled: bool,
+
+    /// Comma-separated symbols to stream Binance tickers for; none when empty
+    #[serde(default)]
+    pub binance_stream_symbols: String,
+
+    // On-chain transfer reconciliation
+    /// Comma-separated wallet addresses whose token transfers are reconciled
+    /// against the wallet service's records; disabled when empty
+    #[serde(default)]
+    pub transfer_recon_addresses: String,
+
+    /// Comma-separated tokens to reconcile, by symbol
+    #[serde(default = "default_transfer_recon_tokens")]
+    pub transfer_recon_tokens: String,
+
+    /// Postgres database holding the wallet service's `transactions` table
+    #[serde(default)]
+    pub transfer_recon_database_url: Option<String>,
+
+    /// First block to scan when no cursor has been stored yet
+    #[serde(default)]
+    pub transfer_recon_start_block: u64,
+
+    /// Blocks behind the head a transfer must be before it is reconciled
+    #[serde(default = "default_transfer_recon_confirmations")]
+    pub transfer_recon_confirmations: u64,
+
+    /// Blocks covered by each log query
+    #[serde(default = "default_transfer_recon_block_range")]
+    pub transfer_recon_block_range: u64,
+
+    #[serde(default = "default_transfer_recon_interval")]
+    pub transfer_recon_interval_secs: u64,
 }
 
 fn default_host() -> String {
@@ -46,12 +272,196 @@ fn default_log_level() -> String {
 fn default_chain_id() -> u64 {
     1
 }
+fn default_wallet_sweep_interval() -> u64 {
+    300
+}
+fn default_metrics_port() -> u16 {
+    9090
+}
+fn default_venue_max_attempts() -> u32 {
+    3
+}
+fn default_venue_retry_base_ms() -> u64 {
+    200
+}
+fn default_venue_rate_limits() -> String {
+    [
+        "binance.order=50/10s",
+        "binance.account=1200/60s",
+        "binance.market=1200/60s",
+        "kraken.order=60/60s",
+        "kraken.account=15/45s",
+        "kraken.market=1/1s",
+        "bybit.order=10/1s",
+        "bybit.account=10/1s",
+        "bybit.market=600/5s",
+        "okx.order=60/2s",
+        "okx.account=10/2s",
+        "okx.market=20/2s",
+    ]
+    .join(",")
+}
+fn default_venue_request_weights() -> String {
+    "binance.get_symbols=20,binance.get_symbol_info=20,binance.get_balances=20,\
+     binance.get_depth=5,binance.get_klines=2"
+        .to_string()
+}
+fn default_venue_rate_limit_max_wait_ms() -> u64 {
+    2000
+}
+fn default_venue_breaker_threshold() -> u32 {
+    5
+}
+fn default_venue_breaker_cooldown_secs() -> u64 {
+    30
+}
+fn default_smart_routing_split() -> bool {
+    true
+}
+fn default_smart_routing_depth() -> u32 {
+    20
+}
+fn default_rebalance_max_slippage_bps() -> u32 {
+    50
+}
+fn default_rebalance_min_trade_value() -> u64 {
+    10
+}
+fn default_clock_sync_interval() -> u64 {
+    60
+}
+fn default_clock_max_drift_ms() -> u64 {
+    1000
+}
+
+fn default_depeg_monitor_enabled() -> bool {
+    true
+}
+fn default_depeg_band_bps() -> u32 {
+    50
+}
+fn default_depeg_check_interval() -> u64 {
+    30
+}
+
+fn default_fill_price_max_deviation_bps() -> u32 {
+    500
+}
+
+fn default_rates_collector_enabled() -> bool {
+    true
+}
+fn default_rates_collect_interval() -> u64 {
+    900
+}
+fn default_rates_retention_days() -> u32 {
+    90
+}
+fn default_funding_symbols() -> String {
+    "BTC-USDT,ETH-USDT,SOL-USDT".to_string()
+}
+fn default_borrow_assets() -> String {
+    "USDT,USDC,BTC,ETH".to_string()
+}
+fn default_kline_backfill_intervals() -> String {
+    "1m,1h,1d".to_string()
+}
+fn default_kline_backfill_days() -> u32 {
+    30
+}
+fn default_kline_backfill_exchange() -> String {
+    "binance".to_string()
+}
+fn default_market_proxy_cache_ms() -> u64 {
+    1000
+}
+fn default_market_proxy_requests_per_sec() -> u32 {
+    5
+}
+fn default_binance_user_stream_enabled() -> bool {
+    true
+}
+
+fn default_transfer_recon_tokens() -> String {
+    "USDC,USDT,DAI,WETH".to_string()
+}
+fn default_transfer_recon_confirmations() -> u64 {
+    12
+}
+fn default_transfer_recon_block_range() -> u64 {
+    2000
+}
+fn default_transfer_recon_interval() -> u64 {
+    300
+}
+
+impl Validate for Config {
+    fn validate(&self, checks: &mut Checks) {
+        checks.ports(&[("port", self.port), ("metrics_port", self.metrics_port)]);
+        checks.url("redis_url", &self.redis_url, schemes::REDIS);
+        checks.brokers("kafka_brokers", &self.kafka_brokers);
+        checks.optional_url(
+            "reference_data_url",
+            self.reference_data_url.as_deref(),
+            schemes::HTTP,
+        );
+        checks.url(
+            "eth_rpc_url",
+            &self.eth_rpc_url,
+            &["http", "https", "ws", "wss"],
+        );
+
+        if !self.wallet_sweep_thresholds.trim().is_empty() {
+            if self.wallets_file.is_none() {
+                checks.fail("wallets_file", "required with wallet_sweep_thresholds");
+            }
+            // An unlimited router allowance would expose all proceeds
+            if self.wallet_approval_policy == ApprovalPolicy::Max {
+                checks.fail(
+                    "wallet_approval_policy",
+                    "must be exact while proceeds are swept",
+                );
+            }
+            checks.duration(
+                "wallet_sweep_interval_secs",
+                self.wallet_sweep_interval_secs,
+            );
+        }
 
-impl Config {
-    pub fn load() -> Result<Self> {
-        let config = config::Config::builder()
-            .add_source(config::Environment::default().separator("__"))
-            .build()?;
-        Ok(config.try_deserialize()?)
+        checks.non_zero("venue_max_attempts", self.venue_max_attempts as u64);
+        checks.non_zero(
+            "rebalance_max_slippage_bps",
+            self.rebalance_max_slippage_bps as u64,
+        );
+        checks.duration(
+            "venue_breaker_cooldown_secs",
+            self.venue_breaker_cooldown_secs,
+        );
+        if self.depeg_monitor_enabled {
+            checks.duration("depeg_check_interval_secs", self.depeg_check_interval_secs);
+        }
+        if self.rates_collector_enabled {
+            checks.duration(
+                "rates_collect_interval_secs",
+                self.rates_collect_interval_secs,
+            );
+        }
+        if !self.transfer_recon_addresses.trim().is_empty() {
+            match self.transfer_recon_database_url.as_deref() {
+                Some(url) => checks.url("transfer_recon_database_url", url, schemes::POSTGRES),
+                None => checks.fail(
+                    "transfer_recon_database_url",
+                    "required with transfer_recon_addresses",
+                ),
+            }
+            checks.non_zero(
+                "transfer_recon_block_range",
+                self.transfer_recon_block_range,
+            );
+            checks.duration(
+                "transfer_recon_interval_secs",
+                self.transfer_recon_interval_secs,
+            );
+        }
     }
 }
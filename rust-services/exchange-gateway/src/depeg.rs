@@ -0,0 +1,306 @@
+//! Stablecoin Depeg Monitor
+//!
+//! Watches stablecoin pairs across CEX tickers and on-chain pools. When a
+//! stablecoin trades outside the configured band against every other
+//! stablecoin it is flagged as depegged: a Critical risk alert is published
+//! and, if enabled, the router avoids it as a quote asset. Orders quoted in
+//! an avoided asset are refused unless the caller opts into trading
+//! another stablecoin, repriced at the rates observed here.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::ClientConfig;
+use rust_decimal::Decimal;
+use tokio::time;
+use tracing::{info, warn};
+
+use crate::config::Config;
+use crate::router::ExchangeRouter;
+use common::{
+    events::{topics, AlertSeverity, Event, RiskAlert},
+    DepegObservation, StablecoinDepegMetadata, Symbol,
+};
+
+/// Monitored stablecoins
+const STABLECOINS: [&str; 3] = ["USDT", "USDC", "DAI"];
+
+/// Observed price of `base` in units of `quote` on a venue
+#[derive(Debug, Clone)]
+pub struct StableQuote {
+    pub venue: String,
+    pub base: String,
+    pub quote: String,
+    pub price: Decimal,
+}
+
+/// Find stablecoins trading outside `band` against every counterpart
+///
+/// Returns each depegged asset with its smallest observed deviation from
+/// parity. Requiring deviation against all counterparts attributes the
+/// depeg to the right side of a pair.
+pub fn find_depegged(quotes: &[StableQuote], band: Decimal) -> Vec<(String, Decimal)> {
+    // asset -> counterpart -> deviations of asset priced in counterpart
+    let mut deviations: HashMap<&str, HashMap<&str, Vec<Decimal>>> = HashMap::new();
+
+    for q in quotes.iter().filter(|q| q.price > Decimal::ZERO) {
+        deviations
+            .entry(&q.base)
+            .or_default()
+            .entry(&q.quote)
+            .or_default()
+            .push(q.price - Decimal::ONE);
+        deviations
+            .entry(&q.quote)
+            .or_default()
+            .entry(&q.base)
+            .or_default()
+            .push(Decimal::ONE / q.price - Decimal::ONE);
+    }
+
+    let mut depegged: Vec<(String, Decimal)> = deviations
+        .into_iter()
+        .filter_map(|(asset, counterparts)| {
+            // Smallest absolute deviation across all observations
+            let min = counterparts
+                .values()
+                .flatten()
+                .min_by_key(|d| d.abs())
+                .copied()?;
+            (min.abs() > band).then(|| (asset.to_string(), min))
+        })
+        .collect();
+
+    depegged.sort_by(|a, b| a.0.cmp(&b.0));
+    depegged
+}
+
+/// Mean observed price of each stablecoin pair, keyed by (base, quote)
+pub fn mean_rates(quotes: &[StableQuote]) -> HashMap<(String, String), Decimal> {
+    let mut sums: HashMap<(String, String), (Decimal, u32)> = HashMap::new();
+    for q in quotes.iter().filter(|q| q.price > Decimal::ZERO) {
+        let sum = sums.entry((q.base.clone(), q.quote.clone())).or_default();
+        sum.0 += q.price;
+        sum.1 += 1;
+    }
+    sums.into_iter()
+        .map(|(pair, (total, n))| (pair, total / Decimal::from(n)))
+        .collect()
+}
+
+pub struct DepegMonitor {
+    router: Arc<ExchangeRouter>,
+    producer: FutureProducer,
+    band: Decimal,
+    interval: Duration,
+    reroute: bool,
+
+    /// Currently depegged assets and their deviation
+    depegged: HashMap<String, Decimal>,
+}
+
+impl DepegMonitor {
+    pub fn new(router: Arc<ExchangeRouter>, config: &Config) -> Result<Self> {
+        let producer: FutureProducer = ClientConfig::new()
+            .set("bootstrap.servers", &config.kafka_brokers)
+            .set("message.timeout.ms", "5000")
+            .create()?;
+
+        Ok(Self {
+            router,
+            producer,
+            band: Decimal::new(config.depeg_band_bps as i64, 4),
+            interval: Duration::from_secs(config.depeg_check_interval_secs),
+            reroute: config.depeg_reroute,
+            depegged: HashMap::new(),
+        })
+    }
+
+    /// Run the monitor loop
+    pub async fn run(mut self) -> Result<()> {
+        let mut interval = time::interval(self.interval);
+
+        info!(
+            band = %self.band,
+            reroute = self.reroute,
+            "Stablecoin depeg monitor started"
+        );
+
+        loop {
+            interval.tick().await;
+
+            let quotes = self.collect_quotes().await;
+            self.router.set_stable_rates(mean_rates(&quotes));
+            let current: HashMap<String, Decimal> =
+                find_depegged(&quotes, self.band).into_iter().collect();
+
+            for (asset, deviation) in &current {
+                if !self.depegged.contains_key(asset) {
+                    warn!(asset = %asset, deviation = %deviation, "Stablecoin depeg detected");
+                    self.publish_alert(asset, *deviation, &quotes).await;
+                    if self.reroute {
+                        self.router.set_asset_avoided(asset, true);
+                    }
+                }
+            }
+
+            for asset in self.depegged.keys() {
+                if !current.contains_key(asset) {
+                    info!(asset = %asset, "Stablecoin back within peg band");
+                    if self.reroute {
+                        self.router.set_asset_avoided(asset, false);
+                    }
+                }
+            }
+
+            self.depegged = current;
+        }
+    }
+
+    /// Gather stablecoin pair prices from all venues
+    async fn collect_quotes(&self) -> Vec<StableQuote> {
+        let mut quotes = Vec::new();
+
+        for (i, base) in STABLECOINS.iter().enumerate() {
+            for quote in &STABLECOINS[i + 1..] {
+                let symbol = Symbol::new(base, quote);
+
+                for (venue, exchange) in self.router.exchanges() {
+                    if self.router.dexes().contains_key(venue) {
+                        continue;
+                    }
+                    if let Ok(data) = exchange.get_market_data(&symbol).await {
+                        quotes.push(StableQuote {
+                            venue: venue.clone(),
+                            base: base.to_string(),
+                            quote: quote.to_string(),
+                            price: data.last,
+                        });
+                    }
+                }
+
+                for (venue, dex) in self.router.dexes() {
+                    if let Ok(out) = dex.get_quote(base, quote, Decimal::ONE).await {
+                        quotes.push(StableQuote {
+                            venue: venue.clone(),
+                            base: base.to_string(),
+                            quote: quote.to_string(),
+                            price: out,
+                        });
+                    }
+                }
+            }
+        }
+
+        quotes
+    }
+
+    /// Publish a Critical risk alert for a depegged asset
+    async fn publish_alert(&self, asset: &str, deviation: Decimal, quotes: &[StableQuote]) {
+        let observations = quotes
+            .iter()
+            .filter(|q| q.base == asset || q.quote == asset)
+            .map(|q| DepegObservation {
+                venue: q.venue.clone(),
+                pair: format!("{}-{}", q.base, q.quote),
+                price: q.price,
+            })
+            .collect();
+
+        let alert = RiskAlert::builder(
+            StablecoinDepegMetadata {
+                asset: asset.to_string(),
+                deviation,
+                band: self.band,
+                rerouted: self.reroute,
+                observations,
+            },
+            format!("{asset} deviates {deviation} from parity"),
+        )
+        .severity(AlertSeverity::Critical)
+        .build();
+
+        let event = Event::new("risk_alert", "exchange-gateway", alert);
+        let payload = match serde_json::to_string(&event) {
+            Ok(p) => p,
+            Err(e) => {
+                warn!("Failed to serialize depeg alert: {}", e);
+                return;
+            }
+        };
+
+        if let Err((e, _)) = self
+            .producer
+            .send(
+                FutureRecord::to(topics::ALERTS)
+                    .key(asset)
+                    .payload(&payload),
+                Duration::from_secs(5),
+            )
+            .await
+        {
+            warn!("Failed to publish depeg alert: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(base: &str, quote: &str, price: Decimal) -> StableQuote {
+        StableQuote {
+            venue: "test".to_string(),
+            base: base.to_string(),
+            quote: quote.to_string(),
+            price,
+        }
+    }
+
+    #[test]
+    fn test_depeg_attributed_to_deviating_asset() {
+        let band = Decimal::new(50, 4);
+        let quotes = vec![
+            quote("USDT", "USDC", Decimal::new(97, 2)),
+            quote("USDT", "DAI", Decimal::new(97, 2)),
+            quote("USDC", "DAI", Decimal::new(10001, 4)),
+        ];
+
+        let depegged = find_depegged(&quotes, band);
+
+        assert_eq!(depegged.len(), 1);
+        assert_eq!(depegged[0].0, "USDT");
+        assert_eq!(depegged[0].1, Decimal::new(-3, 2));
+    }
+
+    #[test]
+    fn test_no_depeg_within_band() {
+        let band = Decimal::new(50, 4);
+        let quotes = vec![
+            quote("USDT", "USDC", Decimal::new(9998, 4)),
+            quote("USDC", "DAI", Decimal::new(10003, 4)),
+        ];
+
+        assert!(find_depegged(&quotes, band).is_empty());
+    }
+
+    #[test]
+    fn test_mean_rates_average_venues() {
+        let quotes = vec![
+            quote("USDT", "USDC", Decimal::new(96, 2)),
+            quote("USDT", "USDC", Decimal::new(98, 2)),
+            quote("USDC", "DAI", Decimal::ZERO),
+        ];
+
+        let rates = mean_rates(&quotes);
+
+        assert_eq!(rates.len(), 1);
+        assert_eq!(
+            rates[&("USDT".to_string(), "USDC".to_string())],
+            Decimal::new(97, 2)
+        );
+    }
+}
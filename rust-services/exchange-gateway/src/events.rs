@@ -0,0 +1,45 @@
+//! Gateway Event Publisher
+//!
+//! Produces typed events from gateway workers to their Kafka topics.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::ClientConfig;
+
+use crate::config::Config;
+use common::events::{Event, TypedEvent};
+
+pub struct EventPublisher {
+    producer: FutureProducer,
+}
+
+impl EventPublisher {
+    pub fn new(config: &Config) -> Result<Self> {
+        let producer: FutureProducer = ClientConfig::new()
+            .set("bootstrap.servers", &config.kafka_brokers)
+            .set("message.timeout.ms", "5000")
+            .create()?;
+
+        Ok(Self { producer })
+    }
+
+    /// Publish an event to its payload's topic and partition key
+    pub async fn publish<T: TypedEvent>(&self, payload: T) -> Result<()> {
+        let outbound = Event::builder(payload).build();
+        let payload = outbound.to_json()?;
+
+        self.producer
+            .send(
+                FutureRecord::to(outbound.topic)
+                    .key(&outbound.key)
+                    .payload(&payload),
+                Duration::from_secs(5),
+            )
+            .await
+            .map_err(|(e, _)| anyhow::anyhow!("Kafka send error: {e}"))?;
+
+        Ok(())
+    }
+}
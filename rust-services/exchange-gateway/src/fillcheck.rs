@@ -0,0 +1,182 @@
+//! Fill Price Check
+//!
+//! Compares each external fill with the internal midprice the data
+//! pipeline keeps in Redis under `mid:{symbol}`. A fill further than
+//! `fill_price_max_deviation_bps` from it usually means a wrong symbol
+//! mapping or a decimal error rather than a real market move, so a
+//! Critical risk alert is published and, when `fill_price_pause_routes` is
+//! set, the venue stops receiving orders for the symbol until an operator
+//! resumes it. Fills without a fresh midprice are not checked.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::ClientConfig;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use rust_decimal::Decimal;
+use tracing::{debug, warn};
+
+use crate::config::Config;
+use crate::router::ExchangeRouter;
+use common::events::{topics, AlertSeverity, Event, RiskAlert, VenueFill};
+use common::AbnormalFillPriceMetadata;
+
+const BPS: Decimal = Decimal::from_parts(10_000, 0, 0, false, 0);
+
+/// Distance of `price` from `reference` in basis points of the reference
+pub fn deviation_bps(price: Decimal, reference: Decimal) -> Decimal {
+    (price - reference).abs() / reference * BPS
+}
+
+pub struct FillPriceCheck {
+    router: Arc<ExchangeRouter>,
+    redis: ConnectionManager,
+    producer: FutureProducer,
+    max_deviation_bps: Decimal,
+    pause_routes: bool,
+}
+
+impl FillPriceCheck {
+    pub async fn new(router: Arc<ExchangeRouter>, config: &Config) -> Result<Self> {
+        let client = redis::Client::open(config.redis_url.as_str())?;
+        let producer: FutureProducer = ClientConfig::new()
+            .set("bootstrap.servers", &config.kafka_brokers)
+            .set("message.timeout.ms", "5000")
+            .create()?;
+
+        Ok(Self {
+            router,
+            redis: ConnectionManager::new(client).await?,
+            producer,
+            max_deviation_bps: Decimal::from(config.fill_price_max_deviation_bps),
+            pause_routes: config.fill_price_pause_routes,
+        })
+    }
+
+    /// Internal midprice of the fill's symbol, if one is fresh
+    async fn reference(&self, fill: &VenueFill) -> Option<Decimal> {
+        let mut conn = self.redis.clone();
+        match conn
+            .get::<_, Option<String>>(format!("mid:{}", fill.symbol))
+            .await
+        {
+            Ok(mid) => mid?.parse().ok().filter(|mid| *mid > Decimal::ZERO),
+            Err(e) => {
+                warn!(symbol = %fill.symbol, "Midprice lookup failed: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Check a fill against the internal midprice
+    ///
+    /// Returns whether the fill was flagged. Lookup and publishing failures
+    /// are logged; the fill itself is never held back.
+    pub async fn check(&self, fill: &VenueFill) -> bool {
+        let Some(reference) = self.reference(fill).await else {
+            metrics::counter!("fill_price_checks", "outcome" => "no_reference").increment(1);
+            debug!(symbol = %fill.symbol, "No midprice to check fill against");
+            return false;
+        };
+
+        let deviation = deviation_bps(fill.price, reference);
+        if deviation <= self.max_deviation_bps {
+            metrics::counter!("fill_price_checks", "outcome" => "ok").increment(1);
+            return false;
+        }
+
+        metrics::counter!("fill_price_checks", "outcome" => "abnormal").increment(1);
+        warn!(
+            exchange = %fill.exchange,
+            symbol = %fill.symbol,
+            order_id = %fill.exchange_order_id,
+            price = %fill.price,
+            reference = %reference,
+            deviation_bps = %deviation.round_dp(1),
+            "Abnormal fill price"
+        );
+        if self.pause_routes {
+            self.router
+                .set_route_paused(&fill.exchange, &fill.symbol, true);
+        }
+        self.publish_alert(fill, reference, deviation).await;
+        true
+    }
+
+    /// Publish a Critical risk alert for an abnormal fill
+    async fn publish_alert(&self, fill: &VenueFill, reference: Decimal, deviation: Decimal) {
+        let alert = RiskAlert::builder(
+            AbnormalFillPriceMetadata {
+                exchange: fill.exchange.clone(),
+                symbol: fill.symbol.clone(),
+                exchange_order_id: fill.exchange_order_id.clone(),
+                trade_id: fill.trade_id.clone(),
+                price: fill.price,
+                reference,
+                deviation_bps: deviation.round_dp(1),
+                max_deviation_bps: self.max_deviation_bps,
+                route_paused: self.pause_routes,
+            },
+            format!(
+                "{} fill on {} at {} is {} bps from the internal midprice {}",
+                fill.symbol,
+                fill.exchange,
+                fill.price,
+                deviation.round_dp(1),
+                reference
+            ),
+        )
+        .severity(AlertSeverity::Critical)
+        .build();
+
+        let event = Event::new("risk_alert", "exchange-gateway", alert);
+        let payload = match serde_json::to_string(&event) {
+            Ok(p) => p,
+            Err(e) => {
+                warn!("Failed to serialize fill price alert: {}", e);
+                return;
+            }
+        };
+
+        if let Err((e, _)) = self
+            .producer
+            .send(
+                FutureRecord::to(topics::ALERTS)
+                    .key(&fill.exchange)
+                    .payload(&payload),
+                Duration::from_secs(5),
+            )
+            .await
+        {
+            warn!("Failed to publish fill price alert: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deviation_is_symmetric_in_bps_of_reference() {
+        let reference = Decimal::from(2000);
+        assert_eq!(
+            deviation_bps(Decimal::from(2010), reference),
+            Decimal::from(50)
+        );
+        assert_eq!(
+            deviation_bps(Decimal::from(1990), reference),
+            Decimal::from(50)
+        );
+        assert_eq!(deviation_bps(reference, reference), Decimal::ZERO);
+
+        // A price off by a decimal place is far outside any sane threshold
+        assert_eq!(
+            deviation_bps(Decimal::from(200), reference),
+            Decimal::from(9000)
+        );
+    }
+}
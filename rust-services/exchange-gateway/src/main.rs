@@ -11,16 +11,32 @@ use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 mod adapters;
+mod algo;
 mod api;
+mod backfill;
 mod config;
+mod depeg;
+mod events;
+mod fillcheck;
+mod market;
+mod metrics;
+mod rates;
+mod rebalance;
+mod replace;
 mod router;
+mod smart;
+mod subaccounts;
+mod sweep;
+mod timesync;
+mod transfers;
+mod wallet;
 
 use config::Config;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenvy::dotenv().ok();
-    let config = Config::load()?;
+    let config: Config = common::config::load()?;
 
     tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::new(&config.log_level))
@@ -32,11 +48,157 @@ async fn main() -> Result<()> {
         env!("CARGO_PKG_VERSION")
     );
 
+    common::events::init_service("exchange-gateway");
+
+    // Initialize metrics
+    metrics::init_metrics(&config)?;
+
     // Initialize exchange adapters
     let exchange_router = Arc::new(router::ExchangeRouter::new(&config).await?);
 
+    // Keep venue symbol mapping in sync with the reference-data service
+    if let Some(url) = config.reference_data_url.clone() {
+        let registry = exchange_router.symbols();
+        let brokers = config.kafka_brokers.clone();
+        tokio::spawn(async move {
+            let group = "exchange-gateway".to_string();
+            if let Err(e) = common::refdata::client::run_sync(registry, url, brokers, group).await {
+                tracing::error!("Reference data sync error: {}", e);
+            }
+        });
+    }
+
+    // Start stablecoin depeg monitor
+    if config.depeg_monitor_enabled {
+        let monitor = depeg::DepegMonitor::new(exchange_router.clone(), &config)?;
+        tokio::spawn(async move {
+            if let Err(e) = monitor.run().await {
+                tracing::error!("Depeg monitor error: {}", e);
+            }
+        });
+    }
+
+    // Keep request timestamps within venue receive windows
+    if config.clock_sync_interval_secs > 0 {
+        let clock_sync = timesync::ClockSync::new(exchange_router.clone(), &config)?;
+        tokio::spawn(async move {
+            if let Err(e) = clock_sync.run().await {
+                tracing::error!("Clock sync error: {}", e);
+            }
+        });
+    }
+
+    // Start funding and borrow rate collection
+    let rate_store = Arc::new(rates::RateStore::new(chrono::Duration::days(
+        config.rates_retention_days as i64,
+    )));
+    if config.rates_collector_enabled {
+        let collector =
+            rates::RateCollector::new(exchange_router.clone(), rate_store.clone(), &config);
+        tokio::spawn(async move {
+            if let Err(e) = collector.run().await {
+                tracing::error!("Rate collector error: {}", e);
+            }
+        });
+    }
+
+    let publisher = Arc::new(events::EventPublisher::new(&config)?);
+
+    // Backfill chart history for configured symbols
+    if !config.kline_backfill_symbols.is_empty() {
+        let backfill =
+            backfill::KlineBackfill::new(exchange_router.clone(), publisher.clone(), &config);
+        tokio::spawn(async move {
+            if let Err(e) = backfill.run().await {
+                tracing::error!("Kline backfill error: {}", e);
+            }
+        });
+    }
+
+    // Check wallet transfer records against on-chain Transfer logs
+    if let Some(reconciler) = transfers::TransferReconciler::new(&config, publisher.clone()).await?
+    {
+        tokio::spawn(async move {
+            if let Err(e) = reconciler.run().await {
+                tracing::error!("Transfer reconciliation error: {}", e);
+            }
+        });
+    }
+
+    // Sweep hot wallet proceeds above their thresholds to cold storage
+    if let Some(sweeper) = sweep::Sweeper::new(&config, publisher.clone())? {
+        tokio::spawn(async move {
+            if let Err(e) = sweeper.run().await {
+                tracing::error!("Wallet sweep error: {}", e);
+            }
+        });
+    }
+
+    // Push Binance fills and tickers instead of polling for them
+    if let Some(api_key) = config.binance_api_key.clone() {
+        let mut streams =
+            adapters::BinanceStreams::new(api_key, exchange_router.symbols(), publisher.clone());
+        if config.fill_price_max_deviation_bps > 0 {
+            let fill_check =
+                fillcheck::FillPriceCheck::new(exchange_router.clone(), &config).await?;
+            streams = streams.with_fill_check(Arc::new(fill_check));
+        }
+        let streams = Arc::new(streams);
+        if config.binance_user_stream_enabled {
+            let streams = streams.clone();
+            tokio::spawn(async move {
+                if let Err(e) = streams.run_user_data().await {
+                    tracing::error!("Binance user-data stream error: {}", e);
+                }
+            });
+        }
+        let markets: Vec<common::Symbol> = config
+            .binance_stream_symbols
+            .split(',')
+            .filter_map(|s| s.trim().split_once('-'))
+            .map(|(base, quote)| common::Symbol::new(base, quote))
+            .collect();
+        tokio::spawn(async move {
+            if let Err(e) = streams.run_market(markets).await {
+                tracing::error!("Binance market stream error: {}", e);
+            }
+        });
+    }
+
+    // Execution algos slice parent orders through the same router
+    let algo_engine = Arc::new(algo::AlgoEngine::new(exchange_router.clone()));
+
+    // Cached, rate-limited passthrough of venue market data
+    let market_proxy = Arc::new(market::MarketDataProxy::new(
+        exchange_router.clone(),
+        &config,
+    ));
+
+    // External orders routed by best price or configured venue
+    let smart_router = Arc::new(smart::SmartRouter::new(
+        exchange_router.clone(),
+        publisher.clone(),
+        &config,
+    ));
+
+    // Target-weight rebalancing through the smart router and DEX aggregator
+    let rebalancer = Arc::new(rebalance::Rebalancer::new(
+        exchange_router.clone(),
+        smart_router.clone(),
+        &config,
+    ));
+
     // Start API server
-    api::run_server(exchange_router, &config).await?;
+    api::run_server(
+        exchange_router,
+        algo_engine,
+        rate_store,
+        market_proxy,
+        smart_router,
+        rebalancer,
+        &config,
+    )
+    .await?;
 
     Ok(())
 }
@@ -0,0 +1,233 @@
+//! Public Market Data Proxy
+//!
+//! One egress point for venue tickers, depth and trades, so internal
+//! services and UIs do not each hit exchange APIs. Responses are cached
+//! briefly per venue, symbol and query, and upstream requests are rate
+//! limited per venue. When a venue's budget is spent the last cached
+//! response is served, however old; without one the request is refused.
+//! Every upstream fetch is logged and counted.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use tracing::info;
+
+use crate::adapters::ExchangeResult;
+use crate::config::Config;
+use crate::router::ExchangeRouter;
+use common::{ExchangeError, Symbol};
+
+/// Upper bound on depth levels and trades per request
+const MAX_LIMIT: u32 = 500;
+
+/// Market data kinds served by the proxy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MarketQuery {
+    Ticker,
+    Depth(u32),
+    Trades(u32),
+}
+
+impl MarketQuery {
+    fn kind(&self) -> &'static str {
+        match self {
+            MarketQuery::Ticker => "ticker",
+            MarketQuery::Depth(_) => "depth",
+            MarketQuery::Trades(_) => "trades",
+        }
+    }
+}
+
+/// Where a proxied response came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CacheStatus {
+    /// Fetched from the venue
+    Miss,
+    /// Served from the cache within its TTL
+    Hit,
+    /// Served from an expired entry because the venue budget is spent
+    Stale,
+}
+
+impl CacheStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CacheStatus::Miss => "miss",
+            CacheStatus::Hit => "hit",
+            CacheStatus::Stale => "stale",
+        }
+    }
+}
+
+/// Token bucket refilled at `rate` tokens per second up to `rate`
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    fn new(rate: u32, now: Instant) -> Self {
+        Self {
+            tokens: rate as f64,
+            refilled_at: now,
+        }
+    }
+
+    fn try_take(&mut self, rate: u32, now: Instant) -> bool {
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate as f64).min(rate as f64);
+        self.refilled_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+type CacheKey = (String, Symbol, MarketQuery);
+
+pub struct MarketDataProxy {
+    router: Arc<ExchangeRouter>,
+    ttl: Duration,
+    /// Upstream requests per second per venue; 0 is unlimited
+    requests_per_sec: u32,
+    cache: DashMap<CacheKey, (Instant, serde_json::Value)>,
+    budgets: DashMap<String, Mutex<Bucket>>,
+}
+
+impl MarketDataProxy {
+    pub fn new(router: Arc<ExchangeRouter>, config: &Config) -> Self {
+        Self {
+            router,
+            ttl: Duration::from_millis(config.market_proxy_cache_ms),
+            requests_per_sec: config.market_proxy_requests_per_sec,
+            cache: DashMap::new(),
+            budgets: DashMap::new(),
+        }
+    }
+
+    /// Clamp a requested depth or trade count to what the proxy serves
+    pub fn clamp_limit(limit: Option<u32>) -> u32 {
+        limit.unwrap_or(100).clamp(1, MAX_LIMIT)
+    }
+
+    /// Market data of `symbol` on `exchange`, from the cache or the venue
+    pub async fn get(
+        &self,
+        exchange: &str,
+        symbol: &Symbol,
+        query: MarketQuery,
+    ) -> ExchangeResult<(serde_json::Value, CacheStatus)> {
+        let adapter = self.router.get_exchange(exchange).ok_or_else(|| {
+            ExchangeError::UnsupportedOperation(format!("Unknown exchange: {exchange}"))
+        })?;
+        let key = (exchange.to_string(), symbol.clone(), query);
+        let now = Instant::now();
+
+        let cached = self.cache.get(&key).map(|entry| entry.value().clone());
+        if let Some((fetched_at, value)) = &cached {
+            if now.duration_since(*fetched_at) < self.ttl {
+                self.count(exchange, query, CacheStatus::Hit.as_str());
+                return Ok((value.clone(), CacheStatus::Hit));
+            }
+        }
+
+        if !self.take_budget(exchange, now) {
+            return match cached {
+                Some((_, value)) => {
+                    self.count(exchange, query, CacheStatus::Stale.as_str());
+                    Ok((value, CacheStatus::Stale))
+                }
+                None => {
+                    self.count(exchange, query, "limited");
+                    Err(ExchangeError::RateLimited)
+                }
+            };
+        }
+
+        info!(
+            exchange = exchange,
+            symbol = %symbol,
+            kind = query.kind(),
+            "Proxying market data request"
+        );
+        let fetched = match query {
+            MarketQuery::Ticker => adapter.get_market_data(symbol).await.map(to_json),
+            MarketQuery::Depth(limit) => adapter.get_depth(symbol, limit).await.map(to_json),
+            MarketQuery::Trades(limit) => adapter.get_trades(symbol, limit).await.map(to_json),
+        };
+        let value = match fetched {
+            Ok(value) => value,
+            Err(e) => {
+                self.count(exchange, query, "error");
+                return Err(e);
+            }
+        };
+
+        self.cache.insert(key, (now, value.clone()));
+        self.count(exchange, query, CacheStatus::Miss.as_str());
+        Ok((value, CacheStatus::Miss))
+    }
+
+    fn take_budget(&self, exchange: &str, now: Instant) -> bool {
+        if self.requests_per_sec == 0 {
+            return true;
+        }
+        self.budgets
+            .entry(exchange.to_string())
+            .or_insert_with(|| Mutex::new(Bucket::new(self.requests_per_sec, now)))
+            .lock()
+            .try_take(self.requests_per_sec, now)
+    }
+
+    fn count(&self, exchange: &str, query: MarketQuery, outcome: &'static str) {
+        metrics::counter!(
+            "market_proxy_requests",
+            "exchange" => exchange.to_string(),
+            "kind" => query.kind(),
+            "outcome" => outcome
+        )
+        .increment(1);
+    }
+}
+
+fn to_json<T: serde::Serialize>(data: T) -> serde_json::Value {
+    serde_json::to_value(data).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_refills_at_rate() {
+        let start = Instant::now();
+        let mut bucket = Bucket::new(2, start);
+
+        assert!(bucket.try_take(2, start));
+        assert!(bucket.try_take(2, start));
+        assert!(!bucket.try_take(2, start));
+
+        // Half a second buys one more request at 2/s
+        assert!(bucket.try_take(2, start + Duration::from_millis(500)));
+        assert!(!bucket.try_take(2, start + Duration::from_millis(500)));
+
+        // Idle time does not bank more than one second of requests
+        let later = start + Duration::from_secs(60);
+        assert!(bucket.try_take(2, later));
+        assert!(bucket.try_take(2, later));
+        assert!(!bucket.try_take(2, later));
+    }
+
+    #[test]
+    fn test_limits_are_clamped() {
+        assert_eq!(MarketDataProxy::clamp_limit(None), 100);
+        assert_eq!(MarketDataProxy::clamp_limit(Some(0)), 1);
+        assert_eq!(MarketDataProxy::clamp_limit(Some(5000)), MAX_LIMIT);
+    }
+}
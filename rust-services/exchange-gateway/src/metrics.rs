@@ -0,0 +1,110 @@
+//! Prometheus metrics for observability
+//!
+//! Exposes metrics for:
+//! - Venue request counts and latency
+//! - Routing decisions
+//! - Order placements and swap executions
+//! - Portfolio rebalances
+//! - Venue health checks
+
+use anyhow::Result;
+use metrics_exporter_prometheus::PrometheusBuilder;
+use std::net::SocketAddr;
+
+use crate::config::Config;
+
+/// Initialize metrics exporter
+pub fn init_metrics(config: &Config) -> Result<()> {
+    let addr: SocketAddr = format!("0.0.0.0:{}", config.metrics_port).parse()?;
+
+    PrometheusBuilder::new()
+        .with_http_listener(addr)
+        .install()?;
+
+    // Register standard metrics
+    metrics::describe_counter!("exchange_requests", "Venue API requests by outcome");
+
+    metrics::describe_histogram!(
+        "exchange_request_latency_us",
+        "Venue API request latency in microseconds"
+    );
+
+    metrics::describe_counter!(
+        "venue_requests_throttled",
+        "Venue requests queued or rejected by the local rate limiter"
+    );
+
+    metrics::describe_counter!(
+        "exchange_breaker_trips",
+        "Venue circuit breakers opened after consecutive failures"
+    );
+
+    metrics::describe_gauge!(
+        "exchange_breaker_state",
+        "Venue circuit breaker state: 0 closed, 1 half-open, 2 open"
+    );
+
+    metrics::describe_counter!("route_decisions", "Symbols routed to a venue");
+
+    metrics::describe_counter!(
+        "route_reroutes",
+        "Orders moved off an avoided stablecoin quote asset"
+    );
+
+    metrics::describe_counter!(
+        "route_breaker_reroutes",
+        "Orders routed around a venue with an open circuit breaker"
+    );
+
+    metrics::describe_counter!(
+        "fill_price_checks",
+        "Venue fills checked against the internal midprice, by outcome"
+    );
+
+    metrics::describe_counter!("orders_placed", "Orders placed on external venues");
+
+    metrics::describe_counter!("swaps_executed", "On-chain swaps submitted");
+
+    metrics::describe_counter!("rebalance_runs", "Portfolio rebalances run, by mode");
+
+    metrics::describe_counter!(
+        "rebalance_legs",
+        "Rebalance trades by venue type and outcome"
+    );
+
+    metrics::describe_counter!(
+        "market_proxy_requests",
+        "Proxied market data requests by cache outcome"
+    );
+
+    metrics::describe_counter!("exchange_health_checks", "Venue availability checks");
+
+    metrics::describe_gauge!(
+        "exchange_available",
+        "Whether the venue passed its last health check"
+    );
+
+    metrics::describe_counter!(
+        "transfer_recon_transfers",
+        "On-chain token transfers checked against wallet records"
+    );
+
+    metrics::describe_counter!(
+        "transfer_recon_discrepancies",
+        "Transfers and records that failed reconciliation, by kind"
+    );
+
+    metrics::describe_gauge!(
+        "transfer_recon_block",
+        "Last block reconciled against on-chain Transfer logs"
+    );
+
+    metrics::describe_counter!(
+        "wallet_sweeps",
+        "Hot wallet sweeps to the cold wallet, by token and status"
+    );
+
+    tracing::info!("Metrics server started on port {}", config.metrics_port);
+
+    Ok(())
+}
@@ -0,0 +1,358 @@
+//! Funding and Borrow Rate Collection
+//!
+//! Periodically pulls perpetual funding rates and margin/lending borrow
+//! rates from every venue that publishes them and keeps a time series per
+//! venue and instrument for carry calculations. Venues with history
+//! endpoints are fetched incrementally from the last stored point; others
+//! are sampled at the collection interval.
+
+#![allow(dead_code)]
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use tokio::time;
+use tracing::{debug, info, warn};
+
+use crate::adapters::{BorrowRate, FundingRate};
+use crate::config::Config;
+use crate::router::ExchangeRouter;
+use common::{ExchangeError, Symbol};
+
+/// Point in a rate series
+trait Timestamped {
+    fn time(&self) -> DateTime<Utc>;
+}
+
+impl Timestamped for FundingRate {
+    fn time(&self) -> DateTime<Utc> {
+        self.funding_time
+    }
+}
+
+impl Timestamped for BorrowRate {
+    fn time(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+}
+
+/// Time-ordered points for one venue and instrument
+type Series<T> = HashMap<(String, String), VecDeque<T>>;
+
+/// Series of one venue and instrument, as returned by the API
+#[derive(Debug, Clone, Serialize)]
+pub struct RateSeries<T> {
+    pub venue: String,
+    pub instrument: String,
+    pub rates: Vec<T>,
+}
+
+/// In-memory rate history with a retention window
+pub struct RateStore {
+    funding: RwLock<Series<FundingRate>>,
+    borrow: RwLock<Series<BorrowRate>>,
+    retention: chrono::Duration,
+}
+
+impl RateStore {
+    pub fn new(retention: chrono::Duration) -> Self {
+        Self {
+            funding: RwLock::new(HashMap::new()),
+            borrow: RwLock::new(HashMap::new()),
+            retention,
+        }
+    }
+
+    /// Append points newer than the series' last point and drop expired ones
+    ///
+    /// Returns the number of points added.
+    fn append<T: Timestamped>(
+        &self,
+        series: &RwLock<Series<T>>,
+        venue: &str,
+        instrument: &str,
+        mut points: Vec<T>,
+        now: DateTime<Utc>,
+    ) -> usize {
+        points.sort_by_key(|p| p.time());
+
+        let mut series = series.write();
+        let entries = series
+            .entry((venue.to_string(), instrument.to_string()))
+            .or_default();
+
+        let mut added = 0;
+        for point in points {
+            if entries
+                .back()
+                .is_some_and(|last| point.time() <= last.time())
+            {
+                continue;
+            }
+            entries.push_back(point);
+            added += 1;
+        }
+
+        let cutoff = now - self.retention;
+        while entries.front().is_some_and(|p| p.time() < cutoff) {
+            entries.pop_front();
+        }
+
+        added
+    }
+
+    fn range<T: Timestamped + Clone>(
+        series: &RwLock<Series<T>>,
+        instrument: &str,
+        venue: Option<&str>,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Vec<RateSeries<T>> {
+        series
+            .read()
+            .iter()
+            .filter(|((v, i), _)| i == instrument && venue.is_none_or(|venue| v == venue))
+            .map(|((venue, instrument), points)| RateSeries {
+                venue: venue.clone(),
+                instrument: instrument.clone(),
+                rates: points
+                    .iter()
+                    .filter(|p| start.is_none_or(|s| p.time() >= s))
+                    .filter(|p| end.is_none_or(|e| p.time() <= e))
+                    .cloned()
+                    .collect(),
+            })
+            .collect()
+    }
+
+    fn latest<T: Clone>(series: &RwLock<Series<T>>) -> Vec<RateSeries<T>> {
+        series
+            .read()
+            .iter()
+            .filter_map(|((venue, instrument), points)| {
+                Some(RateSeries {
+                    venue: venue.clone(),
+                    instrument: instrument.clone(),
+                    rates: vec![points.back()?.clone()],
+                })
+            })
+            .collect()
+    }
+
+    fn last_time<T: Timestamped>(
+        series: &RwLock<Series<T>>,
+        venue: &str,
+        instrument: &str,
+    ) -> Option<DateTime<Utc>> {
+        series
+            .read()
+            .get(&(venue.to_string(), instrument.to_string()))
+            .and_then(|points| points.back())
+            .map(Timestamped::time)
+    }
+
+    pub fn record_funding(&self, venue: &str, symbol: &Symbol, points: Vec<FundingRate>) -> usize {
+        self.append(
+            &self.funding,
+            venue,
+            &symbol.to_string(),
+            points,
+            Utc::now(),
+        )
+    }
+
+    pub fn record_borrow(&self, venue: &str, asset: &str, points: Vec<BorrowRate>) -> usize {
+        self.append(&self.borrow, venue, asset, points, Utc::now())
+    }
+
+    /// Funding history of `symbol`, per venue
+    pub fn funding(
+        &self,
+        symbol: &Symbol,
+        venue: Option<&str>,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Vec<RateSeries<FundingRate>> {
+        Self::range(&self.funding, &symbol.to_string(), venue, start, end)
+    }
+
+    /// Borrow rate history of `asset`, per venue
+    pub fn borrow(
+        &self,
+        asset: &str,
+        venue: Option<&str>,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Vec<RateSeries<BorrowRate>> {
+        Self::range(&self.borrow, asset, venue, start, end)
+    }
+
+    /// Most recent funding rate of every series
+    pub fn latest_funding(&self) -> Vec<RateSeries<FundingRate>> {
+        Self::latest(&self.funding)
+    }
+
+    /// Most recent borrow rate of every series
+    pub fn latest_borrow(&self) -> Vec<RateSeries<BorrowRate>> {
+        Self::latest(&self.borrow)
+    }
+
+    /// Start of the next fetch: just after the last point, or the retention window
+    fn fetch_from<T: Timestamped>(
+        &self,
+        series: &RwLock<Series<T>>,
+        venue: &str,
+        instrument: &str,
+        now: DateTime<Utc>,
+    ) -> DateTime<Utc> {
+        Self::last_time(series, venue, instrument)
+            .map(|t| t + chrono::Duration::milliseconds(1))
+            .unwrap_or(now - self.retention)
+    }
+}
+
+/// Scheduled collector feeding a [`RateStore`]
+pub struct RateCollector {
+    router: Arc<ExchangeRouter>,
+    store: Arc<RateStore>,
+    symbols: Vec<Symbol>,
+    assets: Vec<String>,
+    interval: Duration,
+}
+
+impl RateCollector {
+    pub fn new(router: Arc<ExchangeRouter>, store: Arc<RateStore>, config: &Config) -> Self {
+        let symbols = config
+            .funding_symbols
+            .split(',')
+            .filter_map(|s| s.trim().split_once('-'))
+            .map(|(base, quote)| Symbol::new(base, quote))
+            .collect();
+        let assets = config
+            .borrow_assets
+            .split(',')
+            .map(|a| a.trim().to_uppercase())
+            .filter(|a| !a.is_empty())
+            .collect();
+
+        Self {
+            router,
+            store,
+            symbols,
+            assets,
+            interval: Duration::from_secs(config.rates_collect_interval_secs),
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let mut interval = time::interval(self.interval);
+
+        info!(
+            symbols = self.symbols.len(),
+            assets = self.assets.len(),
+            venues = self.router.rate_sources().len(),
+            "Rate collector started"
+        );
+
+        loop {
+            interval.tick().await;
+            self.collect().await;
+        }
+    }
+
+    async fn collect(&self) {
+        let now = Utc::now();
+
+        for (venue, source) in self.router.rate_sources() {
+            for symbol in &self.symbols {
+                let since =
+                    self.store
+                        .fetch_from(&self.store.funding, venue, &symbol.to_string(), now);
+                match source.get_funding_rates(symbol, since).await {
+                    Ok(points) => {
+                        let added = self.store.record_funding(venue, symbol, points);
+                        debug!(venue = %venue, symbol = %symbol, added, "Funding rates collected");
+                    }
+                    Err(ExchangeError::UnsupportedOperation(_)) => break,
+                    Err(e) => {
+                        warn!(venue = %venue, symbol = %symbol, "Failed to collect funding rates: {}", e);
+                    }
+                }
+            }
+
+            for asset in &self.assets {
+                let since = self.store.fetch_from(&self.store.borrow, venue, asset, now);
+                match source.get_borrow_rates(asset, since).await {
+                    Ok(points) => {
+                        let added = self.store.record_borrow(venue, asset, points);
+                        debug!(venue = %venue, asset = %asset, added, "Borrow rates collected");
+                    }
+                    Err(ExchangeError::UnsupportedOperation(_)) => break,
+                    Err(e) => {
+                        warn!(venue = %venue, asset = %asset, "Failed to collect borrow rates: {}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    fn borrow(rate: i64, at: DateTime<Utc>) -> BorrowRate {
+        BorrowRate {
+            annual_rate: Decimal::new(rate, 2),
+            timestamp: at,
+        }
+    }
+
+    #[test]
+    fn test_store_appends_new_points_within_retention() {
+        let store = RateStore::new(chrono::Duration::days(1));
+        let now = Utc::now();
+        let hour = chrono::Duration::hours(1);
+
+        let added = store.append(
+            &store.borrow,
+            "aave",
+            "USDC",
+            vec![
+                borrow(5, now - hour),
+                borrow(4, now - hour * 30),
+                borrow(6, now),
+            ],
+            now,
+        );
+        assert_eq!(added, 3);
+
+        // Overlapping fetch only adds the newer point
+        let added = store.append(
+            &store.borrow,
+            "aave",
+            "USDC",
+            vec![borrow(6, now), borrow(7, now + hour)],
+            now + hour,
+        );
+        assert_eq!(added, 1);
+
+        // The 30h-old point is past retention
+        let series = store.borrow("USDC", Some("aave"), None, None);
+        let rates: Vec<Decimal> = series[0].rates.iter().map(|r| r.annual_rate).collect();
+        assert_eq!(
+            rates,
+            vec![Decimal::new(5, 2), Decimal::new(6, 2), Decimal::new(7, 2)]
+        );
+        assert_eq!(
+            store.fetch_from(&store.borrow, "aave", "USDC", now),
+            now + hour + chrono::Duration::milliseconds(1)
+        );
+    }
+}
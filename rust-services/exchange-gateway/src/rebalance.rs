@@ -0,0 +1,880 @@
+//! Portfolio Rebalancer
+//!
+//! Trades a portfolio toward target asset weights. Balances are summed
+//! across every venue account the user trades through, and each asset is
+//! valued in the quote asset at the first venue midprice available. Assets
+//! without a target are left alone and not counted in the portfolio value.
+//!
+//! Each trade is an IOC limit order against the quote asset, priced at the
+//! midprice moved by the slippage allowance, and placed through the smart
+//! order router. With `use_dex` the trade is also quoted on the DEX
+//! aggregator and swapped there when that executes better; swaps spend the
+//! gateway wallet, whose balances are then counted too. Trades expected to
+//! fill beyond the allowance are skipped, and sells run before buys so
+//! their proceeds fund the buys.
+//!
+//! A dry run returns the plan with each trade's route and expected price.
+//! A live run adds each trade's fills and the weights balances were left at.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::adapters::ExchangeResult;
+use crate::config::Config;
+use crate::router::{AggregatedQuote, ExchangeRouter};
+use crate::smart::SmartRouter;
+use common::{
+    ExchangeError, Order, OrderStatus, OrderType, SelfTradePrevention, Side, Symbol, TimeInForce,
+    VenueAllocation,
+};
+
+const BPS: Decimal = Decimal::from_parts(10_000, 0, 0, false, 0);
+
+/// Largest gap between the target weights' sum and 1
+const WEIGHT_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 4);
+
+/// Decimal places used when the symbol has no reference data
+const DEFAULT_PRECISION: u32 = 8;
+
+/// Seconds a swap may wait to be mined before it reverts
+const SWAP_DEADLINE_SECS: i64 = 120;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetWeight {
+    pub asset: String,
+    #[serde(with = "common::decimal::flex")]
+    pub weight: Decimal,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RebalanceRequest {
+    pub user_id: Uuid,
+    /// Target weight of each asset, summing to 1
+    pub targets: Vec<TargetWeight>,
+    /// Asset the portfolio is valued in and traded against
+    pub quote: String,
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Worst expected fill accepted, in basis points from the midprice
+    pub max_slippage_bps: Option<u32>,
+    /// Smallest trade placed, in the quote asset
+    #[serde(default, with = "common::decimal::flex_option")]
+    pub min_trade_value: Option<Decimal>,
+    /// Quote trades on DEXes too and swap from the gateway wallet
+    #[serde(default)]
+    pub use_dex: bool,
+}
+
+impl RebalanceRequest {
+    /// Upper-case asset names, as venues report balances
+    pub fn normalize(&mut self) {
+        self.quote = self.quote.to_uppercase();
+        for target in &mut self.targets {
+            target.asset = target.asset.to_uppercase();
+        }
+    }
+
+    /// Reject targets that cannot describe a portfolio
+    pub fn validate(&self) -> Result<(), String> {
+        if self.targets.is_empty() {
+            return Err("At least one target is required".to_string());
+        }
+        let mut sum = Decimal::ZERO;
+        for (i, target) in self.targets.iter().enumerate() {
+            if target.weight < Decimal::ZERO {
+                return Err(format!("Negative weight for {}", target.asset));
+            }
+            if self.targets[..i].iter().any(|t| t.asset == target.asset) {
+                return Err(format!("Duplicate target for {}", target.asset));
+            }
+            sum += target.weight;
+        }
+        if (sum - Decimal::ONE).abs() > WEIGHT_TOLERANCE {
+            return Err(format!("Target weights sum to {}, not 1", sum));
+        }
+        Ok(())
+    }
+}
+
+/// One asset's holding, valued in the quote asset
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Position {
+    pub asset: String,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub balance: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub price: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub value: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub weight: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub target_weight: Decimal,
+}
+
+/// Trade moving one asset to its target weight
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedTrade {
+    pub asset: String,
+    pub side: Side,
+    pub quantity: Decimal,
+    pub reference_price: Decimal,
+    pub value: Decimal,
+}
+
+/// Portfolio value, positions and the trades reaching the targets
+///
+/// `prices` holds each target asset's price in the quote asset; the quote
+/// asset itself is priced at 1. Trades worth less than `min_trade_value`
+/// are left out. Sells come first, each side largest first.
+pub fn plan(
+    holdings: &HashMap<String, Decimal>,
+    prices: &HashMap<String, Decimal>,
+    targets: &[TargetWeight],
+    quote: &str,
+    min_trade_value: Decimal,
+) -> (Decimal, Vec<Position>, Vec<PlannedTrade>) {
+    let mut positions: Vec<Position> = targets
+        .iter()
+        .map(|target| {
+            let balance = holdings.get(&target.asset).copied().unwrap_or_default();
+            let price = if target.asset == quote {
+                Decimal::ONE
+            } else {
+                prices.get(&target.asset).copied().unwrap_or_default()
+            };
+            Position {
+                asset: target.asset.clone(),
+                balance,
+                price,
+                value: balance * price,
+                weight: Decimal::ZERO,
+                target_weight: target.weight,
+            }
+        })
+        .collect();
+
+    let total: Decimal = positions.iter().map(|p| p.value).sum();
+    if total <= Decimal::ZERO {
+        return (total, positions, Vec::new());
+    }
+
+    let mut trades = Vec::new();
+    for position in &mut positions {
+        position.weight = position.value / total;
+        if position.asset == quote || position.price <= Decimal::ZERO {
+            continue;
+        }
+        let gap = total * position.target_weight - position.value;
+        if gap.abs() < min_trade_value || gap.is_zero() {
+            continue;
+        }
+        trades.push(PlannedTrade {
+            asset: position.asset.clone(),
+            side: if gap > Decimal::ZERO {
+                Side::Buy
+            } else {
+                Side::Sell
+            },
+            quantity: gap.abs() / position.price,
+            reference_price: position.price,
+            value: gap.abs(),
+        });
+    }
+    trades.sort_by(|a, b| {
+        (a.side == Side::Buy)
+            .cmp(&(b.side == Side::Buy))
+            .then_with(|| b.value.cmp(&a.value))
+    });
+
+    (total, positions, trades)
+}
+
+/// Worst acceptable price for `side`, `bps` away from `reference`
+pub fn limit_price(side: Side, reference: Decimal, bps: u32) -> Decimal {
+    let offset = reference * Decimal::from(bps) / BPS;
+    match side {
+        Side::Buy => reference + offset,
+        Side::Sell => reference - offset,
+    }
+}
+
+/// How far `expected` is from `reference` against `side`, in basis points
+pub fn slippage_bps(side: Side, reference: Decimal, expected: Decimal) -> Decimal {
+    let adverse = match side {
+        Side::Buy => expected - reference,
+        Side::Sell => reference - expected,
+    };
+    adverse / reference * BPS
+}
+
+/// Where a trade was routed
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum LegRoute {
+    Cex { allocations: Vec<VenueAllocation> },
+    Dex { quote: AggregatedQuote },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LegStatus {
+    /// Dry run: routed but not placed
+    Planned,
+    /// Not placed: no route, or the expected fill breaches the allowance
+    Skipped,
+    Filled,
+    PartiallyFilled,
+    /// IOC orders placed but nothing filled
+    Unfilled,
+    /// Swap transaction sent; fills are the quoted amounts
+    Submitted,
+    Failed,
+}
+
+impl LegStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LegStatus::Planned => "planned",
+            LegStatus::Skipped => "skipped",
+            LegStatus::Filled => "filled",
+            LegStatus::PartiallyFilled => "partially_filled",
+            LegStatus::Unfilled => "unfilled",
+            LegStatus::Submitted => "submitted",
+            LegStatus::Failed => "failed",
+        }
+    }
+}
+
+/// One trade of a rebalance
+#[derive(Debug, Clone, Serialize)]
+pub struct RebalanceLeg {
+    pub asset: String,
+    pub symbol: Symbol,
+    pub side: Side,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub quantity: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub reference_price: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub limit_price: Decimal,
+    pub route: Option<LegRoute>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub expected_price: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub expected_slippage_bps: Option<Decimal>,
+    pub status: LegStatus,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub filled_quantity: Decimal,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub avg_price: Option<Decimal>,
+    pub tx_hash: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RebalanceReport {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub quote: String,
+    pub dry_run: bool,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub max_slippage_bps: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub total_value: Decimal,
+    pub positions: Vec<Position>,
+    pub legs: Vec<RebalanceLeg>,
+    /// Positions once the trades ran; absent on a dry run
+    pub after: Option<Vec<Position>>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+}
+
+pub struct Rebalancer {
+    router: Arc<ExchangeRouter>,
+    smart: Arc<SmartRouter>,
+    max_slippage_bps: u32,
+    min_trade_value: Decimal,
+}
+
+impl Rebalancer {
+    pub fn new(router: Arc<ExchangeRouter>, smart: Arc<SmartRouter>, config: &Config) -> Self {
+        Self {
+            router,
+            smart,
+            max_slippage_bps: config.rebalance_max_slippage_bps,
+            min_trade_value: Decimal::from(config.rebalance_min_trade_value),
+        }
+    }
+
+    /// Plan the trades reaching `request`'s targets, and place them unless
+    /// it is a dry run
+    pub async fn run(&self, request: &RebalanceRequest) -> ExchangeResult<RebalanceReport> {
+        let started_at = Utc::now();
+        let id = Uuid::new_v4();
+        let max_slippage_bps = request.max_slippage_bps.unwrap_or(self.max_slippage_bps);
+        let min_trade_value = request.min_trade_value.unwrap_or(self.min_trade_value);
+
+        let holdings = self.balances(request.user_id, request.use_dex).await?;
+        let prices = self.prices(request).await?;
+        let (total_value, positions, trades) = plan(
+            &holdings,
+            &prices,
+            &request.targets,
+            &request.quote,
+            min_trade_value,
+        );
+        if total_value <= Decimal::ZERO {
+            return Err(ExchangeError::OrderRejected(
+                "Nothing held in the target assets".to_string(),
+            ));
+        }
+
+        let mut legs = Vec::with_capacity(trades.len());
+        for trade in &trades {
+            let leg = self.leg(id, request, trade, max_slippage_bps).await;
+            metrics::counter!(
+                "rebalance_legs",
+                "venue" => match leg.route {
+                    Some(LegRoute::Dex { .. }) => "dex",
+                    _ => "cex",
+                },
+                "status" => leg.status.as_str()
+            )
+            .increment(1);
+            legs.push(leg);
+        }
+
+        let after = if request.dry_run {
+            None
+        } else {
+            let holdings = self.balances(request.user_id, request.use_dex).await?;
+            let prices = self.prices(request).await?;
+            let (_, after, _) = plan(
+                &holdings,
+                &prices,
+                &request.targets,
+                &request.quote,
+                min_trade_value,
+            );
+            Some(after)
+        };
+
+        metrics::counter!(
+            "rebalance_runs",
+            "mode" => if request.dry_run { "dry_run" } else { "live" }
+        )
+        .increment(1);
+        info!(
+            rebalance_id = %id,
+            user_id = %request.user_id,
+            dry_run = request.dry_run,
+            total_value = %total_value,
+            legs = legs.len(),
+            "Rebalance complete"
+        );
+
+        Ok(RebalanceReport {
+            id,
+            user_id: request.user_id,
+            quote: request.quote.clone(),
+            dry_run: request.dry_run,
+            max_slippage_bps: Decimal::from(max_slippage_bps),
+            total_value,
+            positions,
+            legs,
+            after,
+            started_at,
+            completed_at: Utc::now(),
+        })
+    }
+
+    /// Centralized venues the user trades through, by name
+    fn venues(&self, user_id: Uuid) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .router
+            .list_exchanges()
+            .into_iter()
+            .filter(|name| !self.router.dexes().contains_key(name))
+            .filter(|name| self.router.get_user_exchange(user_id, name).is_some())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Free balance of each asset summed across the user's venue accounts,
+    /// and the gateway wallet with `use_dex`
+    async fn balances(
+        &self,
+        user_id: Uuid,
+        use_dex: bool,
+    ) -> ExchangeResult<HashMap<String, Decimal>> {
+        let mut adapters: Vec<_> = self
+            .venues(user_id)
+            .into_iter()
+            .filter_map(|name| {
+                let adapter = self.router.get_user_exchange(user_id, &name)?.clone();
+                Some((name, adapter))
+            })
+            .collect();
+        if use_dex {
+            // Every DEX adapter signs with the same wallet
+            let mut dexes: Vec<_> = self.router.dexes().keys().cloned().collect();
+            dexes.sort();
+            if let Some(name) = dexes.into_iter().next() {
+                if let Some(adapter) = self.router.get_exchange(&name) {
+                    adapters.push((name, adapter.clone()));
+                }
+            }
+        }
+
+        let mut holdings: HashMap<String, Decimal> = HashMap::new();
+        for (name, adapter) in adapters {
+            let balances = adapter.get_balances().await.inspect_err(|e| {
+                warn!(exchange = %name, "Balances unavailable for rebalance: {}", e);
+            })?;
+            for balance in balances {
+                *holdings.entry(balance.asset.to_uppercase()).or_default() += balance.free;
+            }
+        }
+        Ok(holdings)
+    }
+
+    /// Midprice of each target asset in the quote asset
+    async fn prices(&self, request: &RebalanceRequest) -> ExchangeResult<HashMap<String, Decimal>> {
+        let venues = self.venues(request.user_id);
+        let mut prices = HashMap::new();
+
+        for target in &request.targets {
+            if target.asset == request.quote {
+                continue;
+            }
+            let symbol = Symbol::new(&target.asset, &request.quote);
+            let mut price = None;
+            for name in &venues {
+                let Some(adapter) = self.router.get_user_exchange(request.user_id, name) else {
+                    continue;
+                };
+                match adapter.get_market_data(&symbol).await {
+                    Ok(data) if data.bid > Decimal::ZERO && data.ask > Decimal::ZERO => {
+                        price = Some((data.bid + data.ask) / Decimal::TWO);
+                    }
+                    Ok(data) if data.last > Decimal::ZERO => price = Some(data.last),
+                    Ok(_) => continue,
+                    Err(e) => {
+                        tracing::debug!(exchange = %name, symbol = %symbol, "No price: {}", e);
+                        continue;
+                    }
+                }
+                break;
+            }
+            let price = price.ok_or_else(|| {
+                ExchangeError::UnsupportedOperation(format!("No venue prices {}", symbol))
+            })?;
+            prices.insert(target.asset.clone(), price);
+        }
+        Ok(prices)
+    }
+
+    /// Quantity and limit price rounded to the symbol's precision, the
+    /// limit rounded toward the reference price
+    fn round(
+        &self,
+        symbol: &Symbol,
+        side: Side,
+        quantity: Decimal,
+        limit: Decimal,
+    ) -> (Decimal, Decimal) {
+        let (price_dp, quantity_dp) = self
+            .router
+            .symbols()
+            .get(symbol)
+            .map(|info| (info.price_precision, info.quantity_precision))
+            .unwrap_or((DEFAULT_PRECISION, DEFAULT_PRECISION));
+        let price_rounding = match side {
+            Side::Buy => RoundingStrategy::ToZero,
+            Side::Sell => RoundingStrategy::AwayFromZero,
+        };
+        (
+            quantity.round_dp_with_strategy(quantity_dp, RoundingStrategy::ToZero),
+            limit.round_dp_with_strategy(price_dp, price_rounding),
+        )
+    }
+
+    /// Route one trade, and place it unless this is a dry run
+    async fn leg(
+        &self,
+        rebalance_id: Uuid,
+        request: &RebalanceRequest,
+        trade: &PlannedTrade,
+        max_slippage_bps: u32,
+    ) -> RebalanceLeg {
+        let symbol = Symbol::new(&trade.asset, &request.quote);
+        let (quantity, limit) = self.round(
+            &symbol,
+            trade.side,
+            trade.quantity,
+            limit_price(trade.side, trade.reference_price, max_slippage_bps),
+        );
+        let mut leg = RebalanceLeg {
+            asset: trade.asset.clone(),
+            symbol: symbol.clone(),
+            side: trade.side,
+            quantity,
+            reference_price: trade.reference_price,
+            limit_price: limit,
+            route: None,
+            expected_price: None,
+            expected_slippage_bps: None,
+            status: LegStatus::Skipped,
+            filled_quantity: Decimal::ZERO,
+            avg_price: None,
+            tx_hash: None,
+            error: None,
+        };
+        if quantity <= Decimal::ZERO {
+            leg.error = Some("Quantity rounds to zero".to_string());
+            return leg;
+        }
+
+        let now = Utc::now();
+        let order = Order {
+            id: Uuid::new_v4(),
+            client_order_id: format!(
+                "rebal-{}-{}",
+                &rebalance_id.simple().to_string()[..8],
+                trade.asset
+            ),
+            user_id: request.user_id,
+            symbol: symbol.clone(),
+            side: trade.side,
+            order_type: OrderType::Limit,
+            time_in_force: TimeInForce::IOC,
+            status: OrderStatus::Pending,
+            price: Some(limit),
+            stop_price: None,
+            trigger_source: None,
+            quantity,
+            filled_quantity: Decimal::ZERO,
+            remaining_quantity: quantity,
+            avg_fill_price: None,
+            sequence: 0,
+            created_at: now,
+            updated_at: now,
+            expire_at: None,
+            display_quantity: None,
+            self_trade_prevention: SelfTradePrevention::default(),
+        };
+
+        // Candidate routes with their expected average price
+        let cex = match self.smart.route(&order).await {
+            Ok(decision) => {
+                let expected =
+                    average_price(&decision.allocations).unwrap_or(trade.reference_price);
+                Some((expected, decision.allocations))
+            }
+            Err(e) => {
+                leg.error = Some(e.to_string());
+                None
+            }
+        };
+        let dex = if request.use_dex {
+            self.dex_quote(request, trade, quantity).await
+        } else {
+            None
+        };
+
+        let dex_better = match (&cex, &dex) {
+            (Some((cex_price, _)), Some((dex_price, _))) => match trade.side {
+                Side::Buy => dex_price < cex_price,
+                Side::Sell => dex_price > cex_price,
+            },
+            (None, Some(_)) => true,
+            _ => false,
+        };
+        let (expected, route) = match (cex, dex) {
+            (_, Some((price, quote))) if dex_better => (price, LegRoute::Dex { quote }),
+            (Some((price, allocations)), _) => (price, LegRoute::Cex { allocations }),
+            _ => return leg,
+        };
+        leg.error = None;
+        leg.expected_price = Some(expected);
+        leg.expected_slippage_bps = Some(slippage_bps(trade.side, trade.reference_price, expected));
+        leg.route = Some(route.clone());
+
+        if leg.expected_slippage_bps > Some(Decimal::from(max_slippage_bps)) {
+            leg.error = Some(format!(
+                "Expected fill is beyond the {} bps slippage allowance",
+                max_slippage_bps
+            ));
+            return leg;
+        }
+        if request.dry_run {
+            leg.status = LegStatus::Planned;
+            return leg;
+        }
+
+        match route {
+            LegRoute::Cex { .. } => self.place(&order, &mut leg).await,
+            LegRoute::Dex { quote } => self.swap(request, trade, quote, &mut leg).await,
+        }
+        info!(
+            rebalance_id = %rebalance_id,
+            symbol = %symbol,
+            side = ?trade.side,
+            quantity = %quantity,
+            status = ?leg.status,
+            filled = %leg.filled_quantity,
+            "Rebalance trade placed"
+        );
+        leg
+    }
+
+    /// Best DEX quote for a trade and its price in the quote asset
+    async fn dex_quote(
+        &self,
+        request: &RebalanceRequest,
+        trade: &PlannedTrade,
+        quantity: Decimal,
+    ) -> Option<(Decimal, AggregatedQuote)> {
+        // Swaps are exact-in: sells spend the asset, buys the quote asset
+        let (token_in, token_out, amount_in) = match trade.side {
+            Side::Sell => (&trade.asset, &request.quote, quantity),
+            Side::Buy => (
+                &request.quote,
+                &trade.asset,
+                quantity * trade.reference_price,
+            ),
+        };
+        let quote = match self
+            .router
+            .aggregator()
+            .best_quote(token_in, token_out, amount_in)
+            .await
+        {
+            Ok(quote) if quote.effective_amount_out > Decimal::ZERO => quote,
+            Ok(_) => return None,
+            Err(e) => {
+                tracing::debug!(asset = %trade.asset, "No DEX quote: {}", e);
+                return None;
+            }
+        };
+        let price = match trade.side {
+            Side::Sell => quote.effective_amount_out / amount_in,
+            Side::Buy => amount_in / quote.effective_amount_out,
+        };
+        Some((price, quote))
+    }
+
+    /// Place an IOC order through the smart router and record its fills
+    async fn place(&self, order: &Order, leg: &mut RebalanceLeg) {
+        let children = match self.smart.execute(order).await {
+            Ok(children) => children,
+            Err(e) => {
+                warn!(symbol = %order.symbol, "Rebalance order failed: {}", e);
+                leg.status = LegStatus::Failed;
+                leg.error = Some(e.to_string());
+                return;
+            }
+        };
+
+        let mut notional = Decimal::ZERO;
+        let mut errors = Vec::new();
+        for child in children {
+            if let Some(placed) = child.order {
+                leg.filled_quantity += placed.filled_quantity;
+                notional += placed.filled_quantity * placed.avg_price.unwrap_or_default();
+            }
+            if let Some(e) = child.error {
+                errors.push(format!("{}: {}", child.allocation.exchange, e));
+            }
+        }
+        if leg.filled_quantity > Decimal::ZERO {
+            leg.avg_price = Some(notional / leg.filled_quantity);
+        }
+        if !errors.is_empty() {
+            leg.error = Some(errors.join("; "));
+        }
+        leg.status = if leg.filled_quantity >= leg.quantity {
+            LegStatus::Filled
+        } else if leg.filled_quantity > Decimal::ZERO {
+            LegStatus::PartiallyFilled
+        } else if errors.is_empty() {
+            LegStatus::Unfilled
+        } else {
+            LegStatus::Failed
+        };
+    }
+
+    /// Swap on the quoted DEX route, reverting below the limit price
+    async fn swap(
+        &self,
+        request: &RebalanceRequest,
+        trade: &PlannedTrade,
+        quote: AggregatedQuote,
+        leg: &mut RebalanceLeg,
+    ) {
+        let amount_in = quote.quote.amount_in;
+        let (token_in, token_out, min_amount_out) = match trade.side {
+            Side::Sell => (&trade.asset, &request.quote, amount_in * leg.limit_price),
+            Side::Buy => (&request.quote, &trade.asset, amount_in / leg.limit_price),
+        };
+        let deadline = (Utc::now().timestamp() + SWAP_DEADLINE_SECS) as u64;
+
+        match self
+            .router
+            .aggregator()
+            .swap(token_in, token_out, amount_in, min_amount_out, deadline)
+            .await
+        {
+            Ok((route, tx_hash)) => {
+                let amount_out = route.quote.amount_out;
+                leg.filled_quantity = match trade.side {
+                    Side::Sell => amount_in,
+                    Side::Buy => amount_out,
+                };
+                if leg.filled_quantity > Decimal::ZERO && amount_out > Decimal::ZERO {
+                    leg.avg_price = Some(match trade.side {
+                        Side::Sell => amount_out / amount_in,
+                        Side::Buy => amount_in / amount_out,
+                    });
+                }
+                leg.route = Some(LegRoute::Dex { quote: route });
+                leg.tx_hash = Some(tx_hash);
+                leg.status = LegStatus::Submitted;
+            }
+            Err(e) => {
+                warn!(asset = %trade.asset, "Rebalance swap failed: {}", e);
+                leg.status = LegStatus::Failed;
+                leg.error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+/// Quantity-weighted expected price of the allocations that have one
+fn average_price(allocations: &[VenueAllocation]) -> Option<Decimal> {
+    let (quantity, notional) = allocations
+        .iter()
+        .filter_map(|a| {
+            a.expected_price
+                .map(|price| (a.quantity, a.quantity * price))
+        })
+        .fold((Decimal::ZERO, Decimal::ZERO), |(q, n), (aq, an)| {
+            (q + aq, n + an)
+        });
+    (quantity > Decimal::ZERO).then(|| notional / quantity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn targets(weights: &[(&str, i64)]) -> Vec<TargetWeight> {
+        weights
+            .iter()
+            .map(|(asset, pct)| TargetWeight {
+                asset: asset.to_string(),
+                weight: Decimal::new(*pct, 2),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_sells_before_buys() {
+        let holdings = HashMap::from([
+            ("BTC".to_string(), Decimal::from(1)),
+            ("ETH".to_string(), Decimal::from(5)),
+            ("USDT".to_string(), Decimal::from(10_000)),
+        ]);
+        let prices = HashMap::from([
+            ("BTC".to_string(), Decimal::from(50_000)),
+            ("ETH".to_string(), Decimal::from(2_000)),
+        ]);
+        // Worth 50k BTC, 10k ETH and 10k USDT of 70k
+        let (total, positions, trades) = plan(
+            &holdings,
+            &prices,
+            &targets(&[("BTC", 50), ("ETH", 30), ("USDT", 20)]),
+            "USDT",
+            Decimal::from(10),
+        );
+
+        assert_eq!(total, Decimal::from(70_000));
+        assert_eq!(positions[0].weight, Decimal::from(50) / Decimal::from(70));
+        assert_eq!(
+            trades,
+            vec![
+                PlannedTrade {
+                    asset: "BTC".to_string(),
+                    side: Side::Sell,
+                    quantity: Decimal::new(3, 1),
+                    reference_price: Decimal::from(50_000),
+                    value: Decimal::from(15_000),
+                },
+                PlannedTrade {
+                    asset: "ETH".to_string(),
+                    side: Side::Buy,
+                    quantity: Decimal::new(55, 1),
+                    reference_price: Decimal::from(2_000),
+                    value: Decimal::from(11_000),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_skips_trades_below_the_minimum() {
+        let holdings = HashMap::from([
+            ("ETH".to_string(), Decimal::from(1)),
+            ("USDT".to_string(), Decimal::from(1_990)),
+        ]);
+        let prices = HashMap::from([("ETH".to_string(), Decimal::from(2_000))]);
+        let (_, _, trades) = plan(
+            &holdings,
+            &prices,
+            &targets(&[("ETH", 50), ("USDT", 50)]),
+            "USDT",
+            Decimal::from(10),
+        );
+        assert!(trades.is_empty());
+    }
+
+    #[test]
+    fn test_limit_and_slippage_are_against_the_trade() {
+        let reference = Decimal::from(100);
+        assert_eq!(limit_price(Side::Buy, reference, 50), Decimal::new(1005, 1));
+        assert_eq!(limit_price(Side::Sell, reference, 50), Decimal::new(995, 1));
+        assert_eq!(
+            slippage_bps(Side::Buy, reference, Decimal::from(101)),
+            Decimal::from(100)
+        );
+        assert_eq!(
+            slippage_bps(Side::Sell, reference, Decimal::from(101)),
+            Decimal::from(-100)
+        );
+    }
+
+    #[test]
+    fn test_targets_must_sum_to_one() {
+        let request = |weights: &[(&str, i64)]| RebalanceRequest {
+            user_id: Uuid::nil(),
+            targets: targets(weights),
+            quote: "USDT".to_string(),
+            dry_run: true,
+            max_slippage_bps: None,
+            min_trade_value: None,
+            use_dex: false,
+        };
+        assert!(request(&[("BTC", 60), ("USDT", 40)]).validate().is_ok());
+        assert!(request(&[("BTC", 60), ("USDT", 30)]).validate().is_err());
+        assert!(request(&[("BTC", 60), ("BTC", 40)]).validate().is_err());
+        assert!(request(&[]).validate().is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! Order Replace Ledger
+//!
+//! Remembers which venue orders have been replaced through the gateway,
+//! so a client retrying `PUT /orders/:id` after a timeout gets the
+//! replacement back instead of a second cancel and place. Only one replace
+//! of an order may be in flight; a failed replace is forgotten so it can
+//! be retried.
+
+use std::time::{Duration, Instant};
+
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+
+use crate::adapters::{ExchangeOrder, ExchangeResult};
+
+/// How long a completed replace is remembered
+const RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Outcome of starting a replace
+#[derive(Debug)]
+pub enum ReplaceStart {
+    /// Go ahead and replace the order
+    Proceed,
+    /// This replace already completed
+    Done(ExchangeOrder),
+    /// Another replace of the order is in flight
+    InFlight,
+    /// The order was already replaced by a different request
+    Replaced,
+}
+
+struct Replacement {
+    client_order_id: Option<String>,
+    result: Option<ExchangeOrder>,
+    at: Instant,
+}
+
+#[derive(Default)]
+pub struct ReplaceLedger {
+    /// (exchange, venue order id) -> replacement
+    replacements: DashMap<(String, String), Replacement>,
+}
+
+impl ReplaceLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Claim the replace of `order_id` on `exchange`
+    ///
+    /// Requests are told apart by their client order id.
+    pub fn start(
+        &self,
+        exchange: &str,
+        order_id: &str,
+        client_order_id: Option<&str>,
+    ) -> ReplaceStart {
+        let now = Instant::now();
+        self.replacements
+            .retain(|_, r| r.result.is_none() || now.duration_since(r.at) < RETENTION);
+
+        match self
+            .replacements
+            .entry((exchange.to_string(), order_id.to_string()))
+        {
+            Entry::Vacant(slot) => {
+                slot.insert(Replacement {
+                    client_order_id: client_order_id.map(str::to_string),
+                    result: None,
+                    at: now,
+                });
+                ReplaceStart::Proceed
+            }
+            Entry::Occupied(existing) => {
+                let existing = existing.get();
+                if existing.client_order_id.as_deref() != client_order_id {
+                    return ReplaceStart::Replaced;
+                }
+                match &existing.result {
+                    Some(order) => ReplaceStart::Done(order.clone()),
+                    None => ReplaceStart::InFlight,
+                }
+            }
+        }
+    }
+
+    /// Record the outcome of a replace claimed with `start`
+    pub fn finish(&self, exchange: &str, order_id: &str, result: &ExchangeResult<ExchangeOrder>) {
+        let key = (exchange.to_string(), order_id.to_string());
+        match result {
+            Ok(order) => {
+                if let Some(mut replacement) = self.replacements.get_mut(&key) {
+                    replacement.result = Some(order.clone());
+                    replacement.at = Instant::now();
+                }
+            }
+            Err(_) => {
+                self.replacements.remove(&key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{ExchangeError, OrderStatus, Symbol};
+    use rust_decimal::Decimal;
+
+    fn replacement() -> ExchangeOrder {
+        ExchangeOrder {
+            exchange_order_id: "2".to_string(),
+            client_order_id: "r1".to_string(),
+            symbol: Symbol::new("BTC", "USDT"),
+            status: OrderStatus::Open,
+            filled_quantity: Decimal::ZERO,
+            avg_price: None,
+        }
+    }
+
+    #[test]
+    fn test_retries_get_the_replacement() {
+        let ledger = ReplaceLedger::new();
+        assert!(matches!(
+            ledger.start("binance", "1", Some("r1")),
+            ReplaceStart::Proceed
+        ));
+        assert!(matches!(
+            ledger.start("binance", "1", Some("r1")),
+            ReplaceStart::InFlight
+        ));
+
+        ledger.finish("binance", "1", &Ok(replacement()));
+        match ledger.start("binance", "1", Some("r1")) {
+            ReplaceStart::Done(order) => assert_eq!(order.exchange_order_id, "2"),
+            other => panic!("expected the replacement, got {other:?}"),
+        }
+        assert!(matches!(
+            ledger.start("binance", "1", Some("r2")),
+            ReplaceStart::Replaced
+        ));
+        // Other venues keep their own order ids
+        assert!(matches!(
+            ledger.start("kraken", "1", Some("r2")),
+            ReplaceStart::Proceed
+        ));
+    }
+
+    #[test]
+    fn test_failed_replace_can_be_retried() {
+        let ledger = ReplaceLedger::new();
+        ledger.start("binance", "1", None);
+        ledger.finish("binance", "1", &Err(ExchangeError::RateLimited));
+        assert!(matches!(
+            ledger.start("binance", "1", Some("r2")),
+            ReplaceStart::Proceed
+        ));
+    }
+}
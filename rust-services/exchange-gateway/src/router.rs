@@ -1,40 +1,204 @@
 //! Exchange Router
 //!
-//! Routes orders to appropriate exchanges based on configuration
+//! Routes orders to appropriate exchanges based on configuration, and
+//! swaps to the DEX paying the most after gas
 
 #![allow(dead_code)]
 
 use anyhow::Result;
-use std::collections::HashMap;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::signers::Signer;
+use futures_util::future::join_all;
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Instant;
 
-use crate::adapters::{BinanceAdapter, ExchangeAdapter, UniswapAdapter};
+use crate::adapters::{
+    AaveAdapter, BinanceAdapter, BreakerStatus, BybitAdapter, CircuitBreaker, DexAdapter,
+    ExchangeAdapter, ExchangeResult, Guarded, KrakenAdapter, Metered, OkxAdapter, PoolInfo,
+    QuoteLimits, RateLimits, RateSource, RetryPolicy, RouteQuote, SushiswapAdapter, Throttled,
+    UniswapAdapter, VenueClock,
+};
 use crate::config::Config;
-use common::Symbol;
+use crate::subaccounts::{self, SubAccountInfo};
+use crate::wallet;
+use common::{ExchangeError, Order, Symbol, SymbolRegistry};
+use uuid::Uuid;
+
+/// Stablecoins interchangeable as quote assets when one is avoided
+const STABLECOINS: [&str; 3] = ["USDT", "USDC", "DAI"];
 
 pub struct ExchangeRouter {
     exchanges: HashMap<String, Arc<dyn ExchangeAdapter>>,
+    dexes: HashMap<String, Arc<dyn DexAdapter>>,
+    rate_sources: HashMap<String, Arc<dyn RateSource>>,
     symbol_routing: HashMap<String, String>, // symbol -> exchange name
+
+    /// Assets to route away from (e.g. depegged stablecoins)
+    avoided_assets: RwLock<HashSet<String>>,
+
+    /// Observed price of one stablecoin in another, keyed by (base, quote)
+    stable_rates: RwLock<HashMap<(String, String), Decimal>>,
+
+    /// (exchange, symbol) routes paused after abnormal fills
+    paused_routes: RwLock<HashSet<(String, String)>>,
+
+    /// Circuit breakers shared by each venue's house and sub-account adapters
+    breakers: HashMap<String, Arc<CircuitBreaker>>,
+
+    /// Reference data shared with the adapters
+    symbols: Arc<SymbolRegistry>,
+
+    /// Per-user sub-account adapters keyed by (user, exchange)
+    user_exchanges: HashMap<(Uuid, String), Arc<dyn ExchangeAdapter>>,
+    sub_accounts: HashMap<Uuid, Vec<SubAccountInfo>>,
+
+    /// Refuse to route users without a sub-account on the venue
+    require_sub_accounts: bool,
+
+    /// Best-output routing across `dexes`
+    aggregator: DexAggregator,
 }
 
 impl ExchangeRouter {
     pub async fn new(config: &Config) -> Result<Self> {
         let mut exchanges: HashMap<String, Arc<dyn ExchangeAdapter>> = HashMap::new();
+        let mut dexes: HashMap<String, Arc<dyn DexAdapter>> = HashMap::new();
+        let mut rate_sources: HashMap<String, Arc<dyn RateSource>> = HashMap::new();
+        let symbols = Arc::new(SymbolRegistry::new());
+        let retry = RetryPolicy::new(
+            config.venue_max_attempts,
+            std::time::Duration::from_millis(config.venue_retry_base_ms),
+        );
+        // Shared by the house and sub-account adapters of each venue
+        let binance_clock = Arc::new(VenueClock::new());
+        let okx_clock = Arc::new(VenueClock::new());
+        let bybit_clock = Arc::new(VenueClock::new());
+        let limits = RateLimits::parse(&config.venue_rate_limits, &config.venue_request_weights)
+            .map_err(anyhow::Error::msg)?;
+        let max_wait = std::time::Duration::from_millis(config.venue_rate_limit_max_wait_ms);
+        let limiter = |venue| limits.limiter(venue, config.venue_rate_limit_mode, max_wait);
+        let binance_limiter = limiter("binance");
+        let kraken_limiter = limiter("kraken");
+        let bybit_limiter = limiter("bybit");
+        let okx_limiter = limiter("okx");
+        let breaker = |venue| {
+            Arc::new(CircuitBreaker::new(
+                venue,
+                config.venue_breaker_threshold,
+                std::time::Duration::from_secs(config.venue_breaker_cooldown_secs),
+            ))
+        };
+        let binance_breaker = breaker("binance");
+        let kraken_breaker = breaker("kraken");
+        let bybit_breaker = breaker("bybit");
+        let okx_breaker = breaker("okx");
 
         // Initialize Binance if configured
         if let (Some(key), Some(secret)) = (&config.binance_api_key, &config.binance_api_secret) {
-            let binance = BinanceAdapter::new(key.clone(), secret.clone());
+            let binance = Metered::new(
+                "binance",
+                Guarded::new(
+                    binance_breaker.clone(),
+                    Throttled::new(
+                        binance_limiter.clone(),
+                        BinanceAdapter::new(key.clone(), secret.clone(), symbols.clone())
+                            .with_retry_policy(retry)
+                            .with_clock(binance_clock.clone()),
+                    ),
+                ),
+            );
             if binance.is_available().await {
-                exchanges.insert("binance".to_string(), Arc::new(binance));
+                let binance = Arc::new(binance);
+                exchanges.insert("binance".to_string(), binance.clone());
+                rate_sources.insert("binance".to_string(), binance);
                 tracing::info!("Binance adapter initialized");
             }
         }
 
+        // Initialize Kraken if configured
+        if let (Some(key), Some(secret)) = (&config.kraken_api_key, &config.kraken_api_secret) {
+            let kraken = Metered::new(
+                "kraken",
+                Guarded::new(
+                    kraken_breaker.clone(),
+                    Throttled::new(
+                        kraken_limiter.clone(),
+                        KrakenAdapter::new(key.clone(), secret.clone(), symbols.clone())
+                            .with_retry_policy(retry),
+                    ),
+                ),
+            );
+            if kraken.is_available().await {
+                exchanges.insert("kraken".to_string(), Arc::new(kraken));
+                tracing::info!("Kraken adapter initialized");
+            }
+        }
+
+        // Initialize Bybit if configured
+        if let (Some(key), Some(secret)) = (&config.bybit_api_key, &config.bybit_api_secret) {
+            let bybit = Metered::new(
+                "bybit",
+                Guarded::new(
+                    bybit_breaker.clone(),
+                    Throttled::new(
+                        bybit_limiter.clone(),
+                        BybitAdapter::new(key.clone(), secret.clone(), symbols.clone())
+                            .with_retry_policy(retry)
+                            .with_clock(bybit_clock.clone()),
+                    ),
+                ),
+            );
+            if bybit.is_available().await {
+                exchanges.insert("bybit".to_string(), Arc::new(bybit));
+                tracing::info!("Bybit adapter initialized");
+            }
+        }
+
+        // Initialize OKX if configured
+        if let (Some(key), Some(secret), Some(passphrase)) = (
+            &config.okx_api_key,
+            &config.okx_api_secret,
+            &config.okx_passphrase,
+        ) {
+            let okx = Metered::new(
+                "okx",
+                Guarded::new(
+                    okx_breaker.clone(),
+                    Throttled::new(
+                        okx_limiter.clone(),
+                        OkxAdapter::new(
+                            key.clone(),
+                            secret.clone(),
+                            passphrase.clone(),
+                            symbols.clone(),
+                        )
+                        .with_retry_policy(retry)
+                        .with_clock(okx_clock.clone()),
+                    ),
+                ),
+            );
+            if okx.is_available().await {
+                exchanges.insert("okx".to_string(), Arc::new(okx));
+                tracing::info!("OKX adapter initialized");
+            }
+        }
+
         // Initialize Uniswap
         match UniswapAdapter::new(&config.eth_rpc_url, config.chain_id) {
-            Ok(uniswap) => {
+            Ok(mut uniswap) => {
+                if let Some(wallet) = wallet::load_wallet(config)? {
+                    tracing::info!(address = ?wallet.address(), "Uniswap swaps enabled");
+                    uniswap = uniswap.with_wallet(wallet, config.wallet_approval_policy);
+                }
+                let uniswap = Metered::new("uniswap", uniswap);
                 if uniswap.is_available().await {
-                    exchanges.insert("uniswap".to_string(), Arc::new(uniswap));
+                    let uniswap = Arc::new(uniswap);
+                    exchanges.insert("uniswap".to_string(), uniswap.clone());
+                    dexes.insert("uniswap".to_string(), uniswap);
                     tracing::info!("Uniswap adapter initialized");
                 }
             }
@@ -43,29 +207,458 @@ impl ExchangeRouter {
             }
         }
 
+        // Initialize SushiSwap
+        match SushiswapAdapter::new(&config.eth_rpc_url, config.chain_id) {
+            Ok(mut sushiswap) => {
+                if let Some(wallet) = wallet::load_wallet(config)? {
+                    sushiswap = sushiswap.with_wallet(wallet, config.wallet_approval_policy);
+                }
+                let sushiswap = Metered::new("sushiswap", sushiswap);
+                if sushiswap.is_available().await {
+                    let sushiswap = Arc::new(sushiswap);
+                    exchanges.insert("sushiswap".to_string(), sushiswap.clone());
+                    dexes.insert("sushiswap".to_string(), sushiswap);
+                    tracing::info!("SushiSwap adapter initialized");
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Failed to initialize SushiSwap: {}", e);
+            }
+        }
+
+        // Initialize Aave borrow rates
+        match AaveAdapter::new(&config.eth_rpc_url) {
+            Ok(aave) => {
+                rate_sources.insert("aave".to_string(), Arc::new(Metered::new("aave", aave)));
+            }
+            Err(e) => {
+                tracing::warn!("Failed to initialize Aave: {}", e);
+            }
+        }
+
+        // Initialize per-user sub-accounts
+        let mut user_exchanges: HashMap<(Uuid, String), Arc<dyn ExchangeAdapter>> = HashMap::new();
+        let mut sub_accounts: HashMap<Uuid, Vec<SubAccountInfo>> = HashMap::new();
+        if let Some(path) = &config.subaccounts_file {
+            for sub in subaccounts::load(path)? {
+                let exchange = sub.exchange.to_lowercase();
+                let adapter: Arc<dyn ExchangeAdapter> = match exchange.as_str() {
+                    "binance" => Arc::new(Metered::new(
+                        "binance",
+                        Guarded::new(
+                            binance_breaker.clone(),
+                            Throttled::new(
+                                binance_limiter.clone(),
+                                BinanceAdapter::new(
+                                    sub.api_key.clone(),
+                                    sub.api_secret.clone(),
+                                    symbols.clone(),
+                                )
+                                .with_retry_policy(retry)
+                                .with_clock(binance_clock.clone()),
+                            ),
+                        ),
+                    )),
+                    "kraken" => Arc::new(Metered::new(
+                        "kraken",
+                        Guarded::new(
+                            kraken_breaker.clone(),
+                            Throttled::new(
+                                kraken_limiter.clone(),
+                                KrakenAdapter::new(
+                                    sub.api_key.clone(),
+                                    sub.api_secret.clone(),
+                                    symbols.clone(),
+                                )
+                                .with_retry_policy(retry),
+                            ),
+                        ),
+                    )),
+                    "bybit" => Arc::new(Metered::new(
+                        "bybit",
+                        Guarded::new(
+                            bybit_breaker.clone(),
+                            Throttled::new(
+                                bybit_limiter.clone(),
+                                BybitAdapter::new(
+                                    sub.api_key.clone(),
+                                    sub.api_secret.clone(),
+                                    symbols.clone(),
+                                )
+                                .with_retry_policy(retry)
+                                .with_clock(bybit_clock.clone()),
+                            ),
+                        ),
+                    )),
+                    "okx" => {
+                        let Some(passphrase) = sub.passphrase.clone() else {
+                            tracing::warn!(
+                                user_id = %sub.user_id,
+                                "OKX sub-account has no passphrase, skipping"
+                            );
+                            continue;
+                        };
+                        Arc::new(Metered::new(
+                            "okx",
+                            Guarded::new(
+                                okx_breaker.clone(),
+                                Throttled::new(
+                                    okx_limiter.clone(),
+                                    OkxAdapter::new(
+                                        sub.api_key.clone(),
+                                        sub.api_secret.clone(),
+                                        passphrase,
+                                        symbols.clone(),
+                                    )
+                                    .with_retry_policy(retry)
+                                    .with_clock(okx_clock.clone()),
+                                ),
+                            ),
+                        ))
+                    }
+                    _ => {
+                        tracing::warn!(
+                            user_id = %sub.user_id,
+                            exchange = %exchange,
+                            "No adapter for sub-account venue, skipping"
+                        );
+                        continue;
+                    }
+                };
+                sub_accounts
+                    .entry(sub.user_id)
+                    .or_default()
+                    .push(SubAccountInfo::from(&sub));
+                user_exchanges.insert((sub.user_id, exchange), adapter);
+            }
+            tracing::info!(users = sub_accounts.len(), "Sub-accounts loaded");
+        }
+
         // Default routing (can be configured)
         let symbol_routing = HashMap::new();
 
+        let gas_oracle = Provider::<Http>::try_from(config.eth_rpc_url.as_str())
+            .ok()
+            .map(Arc::new);
+        let aggregator = DexAggregator::new(dexes.clone(), gas_oracle);
+
         Ok(Self {
             exchanges,
+            dexes,
+            rate_sources,
             symbol_routing,
+            avoided_assets: RwLock::new(HashSet::new()),
+            stable_rates: RwLock::new(HashMap::new()),
+            paused_routes: RwLock::new(HashSet::new()),
+            breakers: HashMap::from([
+                ("binance".to_string(), binance_breaker),
+                ("kraken".to_string(), kraken_breaker),
+                ("bybit".to_string(), bybit_breaker),
+                ("okx".to_string(), okx_breaker),
+            ]),
+            symbols,
+            user_exchanges,
+            sub_accounts,
+            require_sub_accounts: config.require_sub_accounts,
+            aggregator,
         })
     }
 
+    /// Reference data registry, synced by `main` when configured
+    pub fn symbols(&self) -> Arc<SymbolRegistry> {
+        self.symbols.clone()
+    }
+
     /// Get exchange adapter by name
     pub fn get_exchange(&self, name: &str) -> Option<&Arc<dyn ExchangeAdapter>> {
         self.exchanges.get(name)
     }
 
-    /// Get exchange for a symbol
-    pub fn get_exchange_for_symbol(&self, symbol: &Symbol) -> Option<&Arc<dyn ExchangeAdapter>> {
-        let exchange_name = self
-            .symbol_routing
+    /// Venue a symbol routes to
+    fn venue_for_symbol(&self, symbol: &Symbol) -> String {
+        self.symbol_routing
             .get(&symbol.to_string())
             .cloned()
-            .unwrap_or_else(|| "binance".to_string()); // Default to Binance
+            .unwrap_or_else(|| "binance".to_string()) // Default to Binance
+    }
+
+    pub(crate) fn record_route(exchange_name: String, routed: bool) {
+        metrics::counter!(
+            "route_decisions",
+            "exchange" => exchange_name,
+            "outcome" => if routed { "routed" } else { "unavailable" }
+        )
+        .increment(1);
+    }
+
+    /// `venue`, or another venue taking its orders while its breaker is open
+    ///
+    /// `available` says whether a venue has an adapter for the order. With
+    /// no healthy alternative the venue is kept and its requests fail fast.
+    fn healthy_venue(
+        &self,
+        venue: String,
+        symbol: &Symbol,
+        available: impl Fn(&str) -> bool,
+    ) -> String {
+        if !self.is_breaker_open(&venue) {
+            return venue;
+        }
+
+        let mut candidates: Vec<_> = self.breakers.keys().collect();
+        candidates.sort();
+        let Some(fallback) = candidates.into_iter().find(|name| {
+            **name != venue
+                && available(name)
+                && !self.is_breaker_open(name)
+                && !self.is_route_paused(name, symbol)
+        }) else {
+            return venue;
+        };
+
+        tracing::info!(
+            from = %venue,
+            to = %fallback,
+            symbol = %symbol,
+            "Routing around open circuit breaker"
+        );
+        metrics::counter!(
+            "route_breaker_reroutes",
+            "from" => venue,
+            "to" => fallback.clone()
+        )
+        .increment(1);
+        fallback.clone()
+    }
+
+    /// Get exchange for a symbol
+    pub fn get_exchange_for_symbol(&self, symbol: &Symbol) -> Option<&Arc<dyn ExchangeAdapter>> {
+        let exchange_name = self.healthy_venue(self.venue_for_symbol(symbol), symbol, |name| {
+            self.exchanges.contains_key(name)
+        });
+        let exchange = self
+            .exchanges
+            .get(&exchange_name)
+            .filter(|_| !self.is_route_paused(&exchange_name, symbol));
+        Self::record_route(exchange_name, exchange.is_some());
+        exchange
+    }
+
+    /// Route a user's order for `symbol` to a venue
+    ///
+    /// Uses the user's sub-account on the venue when one is mapped, and
+    /// the gateway's own account otherwise unless sub-accounts are
+    /// required. Orders go to another venue while the venue's circuit
+    /// breaker is open. Returns the venue name with the adapter.
+    pub fn route_for_user(
+        &self,
+        user_id: Uuid,
+        symbol: &Symbol,
+    ) -> Option<(String, Arc<dyn ExchangeAdapter>)> {
+        let exchange_name = self.healthy_venue(self.venue_for_symbol(symbol), symbol, |name| {
+            self.get_user_exchange(user_id, name).is_some()
+        });
+        let exchange = self
+            .get_user_exchange(user_id, &exchange_name)
+            .filter(|_| !self.is_route_paused(&exchange_name, symbol))
+            .cloned();
+        Self::record_route(exchange_name.clone(), exchange.is_some());
+        exchange.map(|e| (exchange_name, e))
+    }
+
+    /// Get the adapter for a user's account on `name`
+    pub fn get_user_exchange(
+        &self,
+        user_id: Uuid,
+        name: &str,
+    ) -> Option<&Arc<dyn ExchangeAdapter>> {
+        if let Some(adapter) = self.user_exchanges.get(&(user_id, name.to_string())) {
+            return Some(adapter);
+        }
+        if self.require_sub_accounts {
+            return None;
+        }
+        self.exchanges.get(name)
+    }
+
+    /// Sub-accounts mapped to a user
+    pub fn sub_accounts(&self, user_id: Uuid) -> Vec<SubAccountInfo> {
+        self.sub_accounts.get(&user_id).cloned().unwrap_or_default()
+    }
+
+    /// Adapter bound to a user's sub-account on `exchange`, without fallback
+    pub fn sub_account_exchange(
+        &self,
+        user_id: Uuid,
+        exchange: &str,
+    ) -> Option<&Arc<dyn ExchangeAdapter>> {
+        self.user_exchanges.get(&(user_id, exchange.to_lowercase()))
+    }
+
+    /// Get all exchange adapters by name
+    pub fn exchanges(&self) -> &HashMap<String, Arc<dyn ExchangeAdapter>> {
+        &self.exchanges
+    }
+
+    /// Get all DEX adapters by name
+    pub fn dexes(&self) -> &HashMap<String, Arc<dyn DexAdapter>> {
+        &self.dexes
+    }
+
+    /// Best-output swap routing across every DEX
+    pub fn aggregator(&self) -> &DexAggregator {
+        &self.aggregator
+    }
+
+    /// Pool depth for a pair on every DEX, by DEX name
+    pub async fn dex_pools(
+        &self,
+        token_a: &str,
+        token_b: &str,
+    ) -> Vec<(String, ExchangeResult<PoolInfo>)> {
+        let mut pools = Vec::with_capacity(self.dexes.len());
+        for (name, dex) in &self.dexes {
+            pools.push((name.clone(), dex.get_pool_info(token_a, token_b).await));
+        }
+        pools.sort_by(|a, b| a.0.cmp(&b.0));
+        pools
+    }
+
+    /// Swap quote for a pair on every DEX, by DEX name
+    pub async fn dex_quotes(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_in: Decimal,
+        limits: &QuoteLimits,
+    ) -> Vec<(String, ExchangeResult<RouteQuote>)> {
+        let mut quotes = Vec::with_capacity(self.dexes.len());
+        for (name, dex) in &self.dexes {
+            quotes.push((
+                name.clone(),
+                dex.get_limited_quote(token_in, token_out, amount_in, limits)
+                    .await,
+            ));
+        }
+        quotes.sort_by(|a, b| a.0.cmp(&b.0));
+        quotes
+    }
+
+    /// DEXes whose pool holds at least `amount_in / max_pool_share` of `token_in`
+    ///
+    /// Larger trades would move the pool price too far to be worth quoting.
+    pub async fn dexes_with_depth(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_in: Decimal,
+        max_pool_share: Decimal,
+    ) -> Vec<(String, Arc<dyn DexAdapter>)> {
+        let mut deep = Vec::new();
+        for (name, result) in self.dex_pools(token_in, token_out).await {
+            match result {
+                Ok(pool) if amount_in <= pool.reserve_a * max_pool_share => {
+                    deep.push((name.clone(), self.dexes[&name].clone()));
+                }
+                Ok(pool) => {
+                    tracing::debug!(
+                        dex = %name,
+                        amount_in = %amount_in,
+                        reserve = %pool.reserve_a,
+                        "Pool too shallow for trade size"
+                    );
+                }
+                Err(e) => tracing::debug!(dex = %name, "No pool depth: {}", e),
+            }
+        }
+        deep
+    }
 
-        self.exchanges.get(&exchange_name)
+    /// Get all funding/borrow rate sources by name
+    pub fn rate_sources(&self) -> &HashMap<String, Arc<dyn RateSource>> {
+        &self.rate_sources
+    }
+
+    /// Mark an asset as avoided (or no longer avoided) for routing
+    pub fn set_asset_avoided(&self, asset: &str, avoided: bool) {
+        let mut assets = self.avoided_assets.write();
+        if avoided {
+            assets.insert(asset.to_uppercase());
+        } else {
+            assets.remove(&asset.to_uppercase());
+        }
+    }
+
+    /// Check if an asset is currently avoided
+    pub fn is_asset_avoided(&self, asset: &str) -> bool {
+        self.avoided_assets.read().contains(&asset.to_uppercase())
+    }
+
+    /// Stop (or resume) sending orders for `symbol` to `exchange`
+    pub fn set_route_paused(&self, exchange: &str, symbol: &Symbol, paused: bool) {
+        let route = (exchange.to_lowercase(), symbol.to_string());
+        let mut routes = self.paused_routes.write();
+        if paused {
+            routes.insert(route);
+        } else {
+            routes.remove(&route);
+        }
+    }
+
+    /// Check if orders for `symbol` are kept off `exchange`
+    pub fn is_route_paused(&self, exchange: &str, symbol: &Symbol) -> bool {
+        self.paused_routes
+            .read()
+            .contains(&(exchange.to_lowercase(), symbol.to_string()))
+    }
+
+    /// Paused routes as (exchange, symbol)
+    pub fn paused_routes(&self) -> Vec<(String, String)> {
+        let mut routes: Vec<_> = self.paused_routes.read().iter().cloned().collect();
+        routes.sort();
+        routes
+    }
+
+    /// Check if `exchange`'s circuit breaker is keeping orders off it
+    pub fn is_breaker_open(&self, exchange: &str) -> bool {
+        self.breakers
+            .get(exchange)
+            .is_some_and(|breaker| breaker.is_open(Instant::now()))
+    }
+
+    /// Circuit breaker state of `exchange`, if it has one
+    pub fn breaker_status(&self, exchange: &str) -> Option<BreakerStatus> {
+        self.breakers
+            .get(exchange)
+            .map(|breaker| breaker.status(Instant::now()))
+    }
+
+    /// Replace the observed stablecoin rates, keyed by (base, quote)
+    pub fn set_stable_rates(&self, rates: HashMap<(String, String), Decimal>) {
+        *self.stable_rates.write() = rates;
+    }
+
+    /// `order` ready to trade while its quote asset may be avoided
+    ///
+    /// An order quoted in an avoided stablecoin is refused unless the
+    /// caller opted into `reroute`; it is then moved to a healthy
+    /// stablecoin and repriced at the observed rate between the two.
+    pub fn reroute(&self, order: &Order, reroute: bool) -> ExchangeResult<Order> {
+        let rerouted = reroute_order(
+            order,
+            &self.avoided_assets.read(),
+            &self.stable_rates.read(),
+            reroute,
+        )?;
+        if rerouted.symbol != order.symbol {
+            metrics::counter!(
+                "route_reroutes",
+                "from" => order.symbol.quote().to_string(),
+                "to" => rerouted.symbol.quote().to_string()
+            )
+            .increment(1);
+        }
+        Ok(rerouted)
     }
 
     /// List all available exchanges
@@ -82,3 +675,319 @@ impl ExchangeRouter {
         }
     }
 }
+
+/// `symbol` with an avoided stablecoin quote swapped for the first healthy
+/// stablecoin that is not its base asset
+fn preferred_quote(symbol: &Symbol, avoided: &HashSet<String>) -> Symbol {
+    if !avoided.contains(symbol.quote()) || !STABLECOINS.contains(&symbol.quote()) {
+        return symbol.clone();
+    }
+    STABLECOINS
+        .iter()
+        .find(|s| !avoided.contains(**s) && **s != symbol.base())
+        .map(|s| Symbol::new(symbol.base(), s))
+        .unwrap_or_else(|| symbol.clone())
+}
+
+/// Price of one `from` in `to`, directly or from the inverse pair
+fn stable_rate(
+    rates: &HashMap<(String, String), Decimal>,
+    from: &str,
+    to: &str,
+) -> Option<Decimal> {
+    let direct = rates.get(&(from.to_string(), to.to_string())).copied();
+    let inverse = || {
+        rates
+            .get(&(to.to_string(), from.to_string()))
+            .filter(|r| !r.is_zero())
+            .map(|r| Decimal::ONE / r)
+    };
+    direct.filter(|r| !r.is_zero()).or_else(inverse)
+}
+
+/// `order` off an avoided quote asset, see [`ExchangeRouter::reroute`]
+fn reroute_order(
+    order: &Order,
+    avoided: &HashSet<String>,
+    rates: &HashMap<(String, String), Decimal>,
+    reroute: bool,
+) -> ExchangeResult<Order> {
+    let quote = order.symbol.quote();
+    if !avoided.contains(quote) {
+        return Ok(order.clone());
+    }
+    let symbol = preferred_quote(&order.symbol, avoided);
+    if symbol == order.symbol {
+        return Err(ExchangeError::OrderRejected(format!(
+            "Quote asset {quote} is avoided and has no healthy replacement"
+        )));
+    }
+    if !reroute {
+        return Err(ExchangeError::OrderRejected(format!(
+            "Quote asset {quote} is avoided; opt into rerouting to trade {symbol} instead"
+        )));
+    }
+
+    let rate = stable_rate(rates, quote, symbol.quote()).ok_or_else(|| {
+        ExchangeError::OrderRejected(format!(
+            "No observed {quote}/{} rate to reprice at",
+            symbol.quote()
+        ))
+    })?;
+    Ok(Order {
+        symbol,
+        price: order.price.map(|p| p * rate),
+        stop_price: order.stop_price.map(|p| p * rate),
+        ..order.clone()
+    })
+}
+
+/// Swap quote from the DEX a swap would be routed to
+#[derive(Debug, Clone, Serialize)]
+pub struct AggregatedQuote {
+    pub dex: String,
+    #[serde(flatten)]
+    pub quote: RouteQuote,
+    /// Estimated gas cost in units of the output token, if it could be priced
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub gas_cost: Option<Decimal>,
+    /// Output less the gas cost
+    #[serde(with = "rust_decimal::serde::str")]
+    pub effective_amount_out: Decimal,
+}
+
+/// Routes swaps to the DEX with the best output after gas
+///
+/// Every DEX is quoted in parallel. Gas is priced at the current gas price
+/// and the best ETH rate into the output token; when either is unavailable
+/// quotes are ranked on output alone.
+pub struct DexAggregator {
+    dexes: HashMap<String, Arc<dyn DexAdapter>>,
+    gas_oracle: Option<Arc<Provider<Http>>>,
+}
+
+impl DexAggregator {
+    pub fn new(
+        dexes: HashMap<String, Arc<dyn DexAdapter>>,
+        gas_oracle: Option<Arc<Provider<Http>>>,
+    ) -> Self {
+        Self { dexes, gas_oracle }
+    }
+
+    /// Best quote across every DEX, net of gas
+    pub async fn best_quote(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_in: Decimal,
+    ) -> ExchangeResult<AggregatedQuote> {
+        let quotes = join_all(self.dexes.iter().map(|(name, dex)| async move {
+            (
+                name.clone(),
+                dex.get_route_quote(token_in, token_out, amount_in).await,
+            )
+        }));
+        let (quotes, gas_unit_cost) = tokio::join!(quotes, self.gas_unit_cost(token_out));
+
+        let mut last_error = None;
+        let quotes = quotes
+            .into_iter()
+            .filter_map(|(name, result)| match result {
+                Ok(quote) => Some((name, quote)),
+                Err(e) => {
+                    tracing::debug!(dex = %name, "No swap quote: {}", e);
+                    last_error = Some(e);
+                    None
+                }
+            })
+            .collect();
+
+        select_best(quotes, gas_unit_cost).ok_or_else(|| {
+            last_error.unwrap_or_else(|| {
+                ExchangeError::UnsupportedOperation("No DEX configured".to_string())
+            })
+        })
+    }
+
+    /// Swap on the DEX with the best output after gas
+    ///
+    /// Returns the quote the swap was routed on and the transaction hash.
+    pub async fn swap(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount_in: Decimal,
+        min_amount_out: Decimal,
+        deadline: u64,
+    ) -> ExchangeResult<(AggregatedQuote, String)> {
+        let best = self.best_quote(token_in, token_out, amount_in).await?;
+        tracing::info!(
+            dex = %best.dex,
+            amount_out = %best.quote.amount_out,
+            effective_amount_out = %best.effective_amount_out,
+            "Routing swap to best DEX"
+        );
+
+        let tx_hash = self.dexes[&best.dex]
+            .swap(token_in, token_out, amount_in, min_amount_out, deadline)
+            .await?;
+        Ok((best, tx_hash))
+    }
+
+    /// Cost of one unit of gas in `token_out`
+    async fn gas_unit_cost(&self, token_out: &str) -> Option<Decimal> {
+        let gas_price = self.gas_oracle.as_ref()?.get_gas_price().await.ok()?;
+        let gas_price_eth =
+            Decimal::try_from_i128_with_scale(gas_price.as_u128() as i128, 18).ok()?;
+
+        if matches!(token_out.to_uppercase().as_str(), "ETH" | "WETH") {
+            return Some(gas_price_eth);
+        }
+        let eth_price = join_all(
+            self.dexes
+                .values()
+                .map(|dex| dex.get_quote("WETH", token_out, Decimal::ONE)),
+        )
+        .await
+        .into_iter()
+        .filter_map(Result::ok)
+        .max()?;
+        Some(gas_price_eth * eth_price)
+    }
+}
+
+/// Quote with the highest output net of gas, if any
+fn select_best(
+    quotes: Vec<(String, RouteQuote)>,
+    gas_unit_cost: Option<Decimal>,
+) -> Option<AggregatedQuote> {
+    quotes
+        .into_iter()
+        .map(|(dex, quote)| {
+            let gas_cost = gas_unit_cost
+                .zip(quote.gas_estimate)
+                .map(|(unit, gas)| unit * Decimal::from(gas));
+            AggregatedQuote {
+                effective_amount_out: quote.amount_out - gas_cost.unwrap_or_default(),
+                dex,
+                quote,
+                gas_cost,
+            }
+        })
+        .max_by(|a, b| a.effective_amount_out.cmp(&b.effective_amount_out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(amount_out: i64, gas_estimate: Option<u64>) -> RouteQuote {
+        RouteQuote {
+            tokens: vec!["WETH".to_string(), "USDC".to_string()],
+            fees: vec![3000],
+            encoded_path: None,
+            amount_in: Decimal::ONE,
+            amount_out: Decimal::from(amount_out),
+            gas_estimate,
+        }
+    }
+
+    #[test]
+    fn test_avoided_quote_is_rerouted() {
+        let mut avoided = HashSet::new();
+        let symbol = Symbol::new("BTC", "USDT");
+        assert_eq!(preferred_quote(&symbol, &avoided), symbol);
+
+        avoided.insert("USDT".to_string());
+        assert_eq!(
+            preferred_quote(&symbol, &avoided),
+            Symbol::new("BTC", "USDC")
+        );
+        // Never the base asset, nor another avoided stablecoin
+        assert_eq!(
+            preferred_quote(&Symbol::new("USDC", "USDT"), &avoided),
+            Symbol::new("USDC", "DAI")
+        );
+        avoided.insert("USDC".to_string());
+        avoided.insert("DAI".to_string());
+        assert_eq!(preferred_quote(&symbol, &avoided), symbol);
+
+        // Only stablecoin quotes are swapped
+        avoided.insert("ETH".to_string());
+        let symbol = Symbol::new("BTC", "ETH");
+        assert_eq!(preferred_quote(&symbol, &avoided), symbol);
+    }
+
+    #[test]
+    fn test_depegged_quote_needs_opt_in_and_reprices() {
+        let avoided = HashSet::from(["USDT".to_string()]);
+        let mut rates = HashMap::new();
+        let order = Order::builder()
+            .symbol(Symbol::new("BTC", "USDT"))
+            .price(60_000)
+            .build();
+
+        // Other quote assets are untouched
+        let healthy = Order::builder().symbol(Symbol::new("BTC", "USDC")).build();
+        let routed = reroute_order(&healthy, &avoided, &rates, false).unwrap();
+        assert_eq!(
+            (routed.symbol, routed.price),
+            (healthy.symbol, healthy.price)
+        );
+
+        // Refused without opt-in, and without a rate to reprice at
+        assert!(matches!(
+            reroute_order(&order, &avoided, &rates, false),
+            Err(ExchangeError::OrderRejected(_))
+        ));
+        assert!(matches!(
+            reroute_order(&order, &avoided, &rates, true),
+            Err(ExchangeError::OrderRejected(_))
+        ));
+
+        // USDT at 0.97 USDC: the limit moves to 58200 USDC, not 60000
+        rates.insert(
+            ("USDT".to_string(), "USDC".to_string()),
+            Decimal::new(97, 2),
+        );
+        let routed = reroute_order(&order, &avoided, &rates, true).unwrap();
+        assert_eq!(routed.symbol, Symbol::new("BTC", "USDC"));
+        assert_eq!(routed.price, Some(Decimal::new(58_200, 0)));
+        assert_eq!(routed.quantity, order.quantity);
+
+        // The inverse pair serves too
+        rates.clear();
+        rates.insert(
+            ("USDC".to_string(), "USDT".to_string()),
+            Decimal::new(125, 2),
+        );
+        let routed = reroute_order(&order, &avoided, &rates, true).unwrap();
+        assert_eq!(routed.price, Some(Decimal::new(48_000, 0)));
+    }
+
+    #[test]
+    fn test_best_route_accounts_for_gas() {
+        // 0.01 USDC per gas unit
+        let unit = Some(Decimal::new(1, 2));
+        let quotes = vec![
+            ("uniswap".to_string(), quote(2000, Some(300_000))),
+            ("sushiswap".to_string(), quote(1999, Some(110_000))),
+        ];
+
+        // Uniswap pays 1 USDC more but costs 1900 USDC more in gas
+        let best = select_best(quotes.clone(), unit).unwrap();
+        assert_eq!(best.dex, "sushiswap");
+        assert_eq!(best.gas_cost, Some(Decimal::from(1100)));
+        assert_eq!(best.effective_amount_out, Decimal::from(899));
+
+        // Without a gas price the larger output wins
+        let best = select_best(quotes, None).unwrap();
+        assert_eq!(best.dex, "uniswap");
+        assert_eq!(best.effective_amount_out, Decimal::from(2000));
+    }
+
+    #[test]
+    fn test_no_quotes_no_route() {
+        assert!(select_best(vec![], Some(Decimal::ONE)).is_none());
+    }
+}
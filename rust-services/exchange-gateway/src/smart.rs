@@ -0,0 +1,471 @@
+//! Smart Order Routing
+//!
+//! In `smart` mode an external order goes to the venue with the best
+//! executable price rather than the symbol's configured venue. The books
+//! of every venue the user can trade on are read, and the order is priced
+//! by walking each book for its full quantity. With splitting enabled the
+//! order is instead divided across venues level by level, taking the best
+//! price wherever it is, and any quantity beyond the visible liquidity is
+//! added to the best venue's share.
+//!
+//! Each decision is logged and published as an `OrderRouted` event.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use futures_util::future::join_all;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::adapters::{ExchangeAdapter, ExchangeOrder, ExchangeResult};
+use crate::config::Config;
+use crate::events::EventPublisher;
+use crate::router::ExchangeRouter;
+use common::{ExchangeError, Order, OrderRouted, OrderType, PriceLevel, Side, VenueAllocation};
+
+/// How external orders pick a venue
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoutingMode {
+    /// The symbol's configured venue
+    #[default]
+    Static,
+    /// The venue or venues with the best executable price
+    Smart,
+}
+
+/// Child order placed on one venue for a routed order
+#[derive(Debug, serde::Serialize)]
+pub struct RoutedOrder {
+    #[serde(flatten)]
+    pub allocation: VenueAllocation,
+    pub order: Option<ExchangeOrder>,
+    pub error: Option<String>,
+}
+
+/// Book side an order of `side` executes against, best price first
+fn executable_levels(
+    side: Side,
+    mut bids: Vec<PriceLevel>,
+    mut asks: Vec<PriceLevel>,
+) -> Vec<PriceLevel> {
+    match side {
+        Side::Buy => {
+            asks.sort_by(|a, b| a.price.cmp(&b.price));
+            asks
+        }
+        Side::Sell => {
+            bids.sort_by(|a, b| b.price.cmp(&a.price));
+            bids
+        }
+    }
+}
+
+/// Whether `price` is at least as good as `other` for `side`
+fn better_or_equal(side: Side, price: Decimal, other: Decimal) -> bool {
+    match side {
+        Side::Buy => price <= other,
+        Side::Sell => price >= other,
+    }
+}
+
+/// Quantity and average price of the levels an order of `quantity` takes
+fn walk(
+    side: Side,
+    quantity: Decimal,
+    limit: Option<Decimal>,
+    levels: &[PriceLevel],
+) -> (Decimal, Option<Decimal>) {
+    let mut filled = Decimal::ZERO;
+    let mut notional = Decimal::ZERO;
+    for level in levels {
+        if filled >= quantity || limit.is_some_and(|l| !better_or_equal(side, level.price, l)) {
+            break;
+        }
+        let take = level.quantity.min(quantity - filled);
+        filled += take;
+        notional += take * level.price;
+    }
+    (filled, (filled > Decimal::ZERO).then(|| notional / filled))
+}
+
+/// Divide `quantity` across venues, best price first
+///
+/// `books` holds each venue's executable levels, best first. Without
+/// `split` the whole order goes to the venue filling the most of it within
+/// `limit`, at the best average price on ties.
+pub fn allocate(
+    side: Side,
+    quantity: Decimal,
+    limit: Option<Decimal>,
+    books: &[(String, Vec<PriceLevel>)],
+    split: bool,
+) -> Vec<VenueAllocation> {
+    if !split {
+        let best = books
+            .iter()
+            .map(|(exchange, levels)| {
+                let (filled, price) = walk(side, quantity, limit, levels);
+                (exchange, filled, price)
+            })
+            .max_by(|a, b| {
+                a.1.cmp(&b.1).then_with(|| match (a.2, b.2) {
+                    (Some(pa), Some(pb)) if pa != pb => {
+                        if better_or_equal(side, pa, pb) {
+                            std::cmp::Ordering::Greater
+                        } else {
+                            std::cmp::Ordering::Less
+                        }
+                    }
+                    // Earlier venues win exact ties
+                    _ => b.0.cmp(a.0),
+                })
+            });
+        return best
+            .map(|(exchange, _, expected_price)| {
+                vec![VenueAllocation {
+                    exchange: exchange.clone(),
+                    quantity,
+                    expected_price,
+                }]
+            })
+            .unwrap_or_default();
+    }
+
+    // Every venue's levels in one queue, best price first
+    let mut levels: Vec<(&String, &PriceLevel)> = books
+        .iter()
+        .flat_map(|(exchange, levels)| levels.iter().map(move |level| (exchange, level)))
+        .collect();
+    levels.sort_by(|a, b| match side {
+        Side::Buy => a.1.price.cmp(&b.1.price),
+        Side::Sell => b.1.price.cmp(&a.1.price),
+    });
+
+    let mut taken: Vec<(String, Decimal, Decimal)> = Vec::new();
+    let mut remaining = quantity;
+    for (exchange, level) in levels {
+        if remaining <= Decimal::ZERO
+            || limit.is_some_and(|l| !better_or_equal(side, level.price, l))
+        {
+            break;
+        }
+        let take = level.quantity.min(remaining);
+        if take <= Decimal::ZERO {
+            continue;
+        }
+        remaining -= take;
+        match taken.iter_mut().find(|(e, _, _)| e == exchange) {
+            Some((_, qty, notional)) => {
+                *qty += take;
+                *notional += take * level.price;
+            }
+            None => taken.push((exchange.clone(), take, take * level.price)),
+        }
+    }
+
+    let mut allocations: Vec<VenueAllocation> = taken
+        .into_iter()
+        .map(|(exchange, qty, notional)| VenueAllocation {
+            exchange,
+            quantity: qty,
+            expected_price: Some(notional / qty),
+        })
+        .collect();
+
+    // Liquidity beyond the visible books rests with the best venue
+    if remaining > Decimal::ZERO {
+        match allocations.first_mut() {
+            Some(best) => best.quantity += remaining,
+            None => return allocate(side, quantity, limit, books, false),
+        }
+    }
+    allocations
+}
+
+pub struct SmartRouter {
+    router: Arc<ExchangeRouter>,
+    publisher: Arc<EventPublisher>,
+    mode: RoutingMode,
+    split: bool,
+    depth: u32,
+}
+
+impl SmartRouter {
+    pub fn new(
+        router: Arc<ExchangeRouter>,
+        publisher: Arc<EventPublisher>,
+        config: &Config,
+    ) -> Self {
+        Self {
+            router,
+            publisher,
+            mode: config.routing_mode,
+            split: config.smart_routing_split,
+            depth: config.smart_routing_depth,
+        }
+    }
+
+    /// Venues the order's user can trade the symbol on, by name, less paused
+    /// routes and venues with an open circuit breaker
+    fn venues(&self, order: &Order) -> Vec<(String, Arc<dyn ExchangeAdapter>)> {
+        let mut names = self.router.list_exchanges();
+        names.sort();
+        names
+            .into_iter()
+            .filter(|name| {
+                !self.router.is_route_paused(name, &order.symbol)
+                    && !self.router.is_breaker_open(name)
+            })
+            .filter_map(|name| {
+                let adapter = self.router.get_user_exchange(order.user_id, &name)?.clone();
+                Some((name, adapter))
+            })
+            .collect()
+    }
+
+    /// Choose the venues for `order` without placing it
+    pub async fn route(&self, order: &Order) -> ExchangeResult<OrderRouted> {
+        let no_route =
+            || ExchangeError::UnsupportedOperation(format!("No exchange routes {}", order.symbol));
+
+        let (allocations, skipped_venues) = match self.mode {
+            RoutingMode::Static => {
+                let (exchange, _) = self
+                    .router
+                    .route_for_user(order.user_id, &order.symbol)
+                    .ok_or_else(no_route)?;
+                let allocation = VenueAllocation {
+                    exchange,
+                    quantity: order.quantity,
+                    expected_price: None,
+                };
+                (vec![allocation], Vec::new())
+            }
+            RoutingMode::Smart => {
+                let venues = self.venues(order);
+                let depths = join_all(
+                    venues
+                        .iter()
+                        .map(|(_, adapter)| adapter.get_depth(&order.symbol, self.depth)),
+                )
+                .await;
+
+                let mut books = Vec::new();
+                let mut skipped = Vec::new();
+                for ((name, _), depth) in venues.into_iter().zip(depths) {
+                    match depth {
+                        Ok(depth) => {
+                            let levels = executable_levels(order.side, depth.bids, depth.asks);
+                            books.push((name, levels));
+                        }
+                        Err(e) => {
+                            warn!(
+                                exchange = %name,
+                                symbol = %order.symbol,
+                                "Venue skipped by smart routing: {}",
+                                e
+                            );
+                            skipped.push(name);
+                        }
+                    }
+                }
+
+                let limit = match order.order_type {
+                    OrderType::Limit => order.price,
+                    _ => None,
+                };
+                let allocations = allocate(order.side, order.quantity, limit, &books, self.split);
+                if allocations.is_empty() {
+                    return Err(no_route());
+                }
+                (allocations, skipped)
+            }
+        };
+
+        for allocation in &allocations {
+            ExchangeRouter::record_route(allocation.exchange.clone(), true);
+        }
+        info!(
+            order_id = %order.id,
+            symbol = %order.symbol,
+            side = ?order.side,
+            quantity = %order.quantity,
+            ?allocations,
+            ?skipped_venues,
+            "Order routed"
+        );
+
+        Ok(OrderRouted {
+            order_id: order.id,
+            client_order_id: order.client_order_id.clone(),
+            symbol: order.symbol.clone(),
+            side: order.side,
+            quantity: order.quantity,
+            allocations,
+            skipped_venues,
+            timestamp: Utc::now(),
+        })
+    }
+
+    /// Route `order`, publish the decision and place a child on each venue
+    pub async fn execute(&self, order: &Order) -> ExchangeResult<Vec<RoutedOrder>> {
+        let decision = self.route(order).await?;
+        if let Err(e) = self.publisher.publish(decision.clone()).await {
+            warn!(order_id = %order.id, "Failed to publish routing decision: {}", e);
+        }
+
+        let split = decision.allocations.len() > 1;
+        let children: Vec<(VenueAllocation, Order)> = decision
+            .allocations
+            .into_iter()
+            .enumerate()
+            .map(|(n, allocation)| {
+                let mut child = order.clone();
+                if split {
+                    child.id = Uuid::new_v4();
+                    child.client_order_id = format!("{}-{}", order.client_order_id, n + 1);
+                }
+                child.quantity = allocation.quantity;
+                child.remaining_quantity = allocation.quantity;
+                (allocation, child)
+            })
+            .collect();
+
+        let placed = join_all(children.iter().map(|(allocation, child)| async move {
+            let adapter = self
+                .router
+                .get_user_exchange(order.user_id, &allocation.exchange)
+                .ok_or_else(|| {
+                    ExchangeError::UnsupportedOperation(format!(
+                        "No account on {}",
+                        allocation.exchange
+                    ))
+                })?;
+            adapter.place_order(child).await
+        }))
+        .await;
+
+        Ok(children
+            .into_iter()
+            .zip(placed)
+            .map(|((allocation, _), result)| match result {
+                Ok(order) => RoutedOrder {
+                    allocation,
+                    order: Some(order),
+                    error: None,
+                },
+                Err(e) => {
+                    warn!(exchange = %allocation.exchange, "Routed child order failed: {}", e);
+                    RoutedOrder {
+                        allocation,
+                        order: None,
+                        error: Some(e.to_string()),
+                    }
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(price: Decimal, quantity: Decimal) -> PriceLevel {
+        PriceLevel {
+            price,
+            quantity,
+            order_count: 0,
+        }
+    }
+
+    fn books() -> Vec<(String, Vec<PriceLevel>)> {
+        vec![
+            (
+                "binance".to_string(),
+                vec![
+                    level(Decimal::from(100), Decimal::from(1)),
+                    level(Decimal::from(102), Decimal::from(5)),
+                ],
+            ),
+            (
+                "kraken".to_string(),
+                vec![
+                    level(Decimal::from(101), Decimal::from(2)),
+                    level(Decimal::from(103), Decimal::from(5)),
+                ],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_split_takes_the_best_levels_across_venues() {
+        let allocations = allocate(Side::Buy, Decimal::from(4), None, &books(), true);
+        assert_eq!(
+            allocations,
+            vec![
+                VenueAllocation {
+                    exchange: "binance".to_string(),
+                    quantity: Decimal::from(2),
+                    expected_price: Some(Decimal::from(101)),
+                },
+                VenueAllocation {
+                    exchange: "kraken".to_string(),
+                    quantity: Decimal::from(2),
+                    expected_price: Some(Decimal::from(101)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsplit_order_goes_to_the_best_average_price() {
+        // binance: 1 @ 100 + 2 @ 102 = 101.33; kraken: 2 @ 101 + 1 @ 103 = 101.67
+        let allocations = allocate(Side::Buy, Decimal::from(3), None, &books(), false);
+        assert_eq!(allocations.len(), 1);
+        assert_eq!(allocations[0].exchange, "binance");
+        assert_eq!(allocations[0].quantity, Decimal::from(3));
+    }
+
+    #[test]
+    fn test_limit_bounds_the_split_and_remainder_rests_on_the_best_venue() {
+        let allocations = allocate(
+            Side::Buy,
+            Decimal::from(5),
+            Some(Decimal::from(101)),
+            &books(),
+            true,
+        );
+        assert_eq!(allocations[0].exchange, "binance");
+        assert_eq!(allocations[0].quantity, Decimal::from(3));
+        assert_eq!(allocations[0].expected_price, Some(Decimal::from(100)));
+        assert_eq!(allocations[1].exchange, "kraken");
+        assert_eq!(allocations[1].quantity, Decimal::from(2));
+    }
+
+    #[test]
+    fn test_sells_take_the_highest_bids() {
+        let bids =
+            |prices: &[Decimal]| prices.iter().map(|p| level(*p, Decimal::from(1))).collect();
+        let books = vec![
+            (
+                "binance".to_string(),
+                executable_levels(
+                    Side::Sell,
+                    bids(&[Decimal::from(98), Decimal::from(99)]),
+                    vec![],
+                ),
+            ),
+            (
+                "okx".to_string(),
+                executable_levels(Side::Sell, bids(&[Decimal::from(100)]), vec![]),
+            ),
+        ];
+        let allocations = allocate(Side::Sell, Decimal::from(1), None, &books, false);
+        assert_eq!(allocations[0].exchange, "okx");
+        assert_eq!(allocations[0].expected_price, Some(Decimal::from(100)));
+    }
+}
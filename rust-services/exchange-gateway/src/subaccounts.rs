@@ -0,0 +1,99 @@
+//! Venue Sub-Accounts
+//!
+//! Maps internal users to venue sub-accounts (Binance sub-accounts,
+//! Coinbase portfolios) so their external orders and balances are
+//! segregated per client. Each mapping carries the sub-account's own API
+//! credentials and is loaded from a JSON file at startup.
+
+#![allow(dead_code)]
+
+use std::fmt;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Sub-account of one user on one venue
+#[derive(Clone, Deserialize)]
+pub struct SubAccount {
+    pub user_id: Uuid,
+    pub exchange: String,
+    /// Venue identifier: Binance sub-account email or Coinbase portfolio id
+    pub account: String,
+    pub api_key: String,
+    pub api_secret: String,
+    #[serde(default)]
+    pub passphrase: Option<String>,
+}
+
+// Keep credentials out of logs
+impl fmt::Debug for SubAccount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubAccount")
+            .field("user_id", &self.user_id)
+            .field("exchange", &self.exchange)
+            .field("account", &self.account)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Sub-account as exposed over the API, without credentials
+#[derive(Debug, Clone, Serialize)]
+pub struct SubAccountInfo {
+    pub exchange: String,
+    pub account: String,
+}
+
+impl From<&SubAccount> for SubAccountInfo {
+    fn from(sub: &SubAccount) -> Self {
+        Self {
+            exchange: sub.exchange.clone(),
+            account: sub.account.clone(),
+        }
+    }
+}
+
+/// Read sub-account mappings from a JSON array
+pub fn load(path: &str) -> Result<Vec<SubAccount>> {
+    let subs: Vec<SubAccount> = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+
+    let mut seen = std::collections::HashSet::new();
+    for sub in &subs {
+        if !seen.insert((sub.user_id, sub.exchange.to_lowercase())) {
+            anyhow::bail!(
+                "Duplicate {} sub-account for user {}",
+                sub.exchange,
+                sub.user_id
+            );
+        }
+    }
+
+    Ok(subs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_rejects_duplicates_and_redacts_debug() {
+        let user = Uuid::new_v4();
+        let entry = serde_json::json!({
+            "user_id": user,
+            "exchange": "binance",
+            "account": "mm-desk@example.com",
+            "api_key": "key",
+            "api_secret": "very-secret",
+        });
+
+        let path = std::env::temp_dir().join(format!("subaccounts-{user}.json"));
+        std::fs::write(&path, serde_json::json!([entry]).to_string()).unwrap();
+        let subs = load(path.to_str().unwrap()).unwrap();
+        assert_eq!(subs.len(), 1);
+        assert!(!format!("{:?}", subs[0]).contains("very-secret"));
+
+        std::fs::write(&path, serde_json::json!([entry, entry]).to_string()).unwrap();
+        assert!(load(path.to_str().unwrap()).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
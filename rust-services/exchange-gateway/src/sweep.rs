@@ -0,0 +1,300 @@
+//! Hot Wallet Sweeps
+//!
+//! DEX proceeds accumulate in the hot wallets swaps are sent from. Every
+//! interval each hot wallet's balance of each configured token is read,
+//! and whatever is above the token's threshold is moved to the chain's
+//! cold wallet with a plain ERC-20 `transfer`. The threshold stays behind
+//! as working balance for swaps, and a transfer from the holder needs no
+//! approval. Sweeps run on the gateway's own chain, whose RPC endpoint
+//! and token list it has.
+//!
+//! Every sweep, failed or not, is published to the audit topic and raised
+//! as a risk alert: Info once mined, Critical when it fails.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use ethers::prelude::*;
+use rust_decimal::Decimal;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::adapters::uniswap::{from_base_units, resolve_token, to_base_units, Token, IERC20};
+use crate::config::Config;
+use crate::events::EventPublisher;
+use crate::wallet::{ManagedWallet, SignerClient, Wallets};
+use common::events::{AlertSeverity, RiskAlert, WalletMovement};
+use common::WalletMovementMetadata;
+
+/// Balance of `token` a hot wallet keeps; the excess is swept
+#[derive(Debug, Clone)]
+pub struct SweepRule {
+    pub token: Token,
+    pub threshold: Decimal,
+}
+
+/// Parse `TOKEN=threshold` pairs, comma-separated
+pub fn parse_rules(spec: &str) -> Result<Vec<SweepRule>> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|rule| !rule.is_empty())
+        .map(|rule| {
+            let (token, threshold) = rule
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Sweep rule {rule} is not TOKEN=threshold"))?;
+            let threshold: Decimal = threshold
+                .trim()
+                .parse()
+                .map_err(|e| anyhow!("Sweep threshold in {rule}: {e}"))?;
+            if threshold < Decimal::ZERO {
+                anyhow::bail!("Sweep threshold in {rule} is negative");
+            }
+            Ok(SweepRule {
+                token: resolve_token(token.trim())?,
+                threshold,
+            })
+        })
+        .collect()
+}
+
+/// Base units to sweep from `balance`, if it is above `threshold`
+pub fn sweep_amount(balance: U256, threshold: U256) -> Option<U256> {
+    (balance > threshold).then(|| balance - threshold)
+}
+
+struct HotWallet {
+    name: String,
+    client: Arc<SignerClient>,
+}
+
+pub struct Sweeper {
+    chain_id: u64,
+    hot: Vec<HotWallet>,
+    cold: ManagedWallet,
+    rules: Vec<SweepRule>,
+    publisher: Arc<EventPublisher>,
+    interval: Duration,
+}
+
+impl Sweeper {
+    /// Sweeper for the gateway's chain, `None` when sweeps are not set up
+    pub fn new(config: &Config, publisher: Arc<EventPublisher>) -> Result<Option<Self>> {
+        let rules = parse_rules(&config.wallet_sweep_thresholds)?;
+        if rules.is_empty() {
+            return Ok(None);
+        }
+        let path = config
+            .wallets_file
+            .as_deref()
+            .ok_or_else(|| anyhow!("wallet_sweep_thresholds requires wallets_file"))?;
+        let wallets = Wallets::load(path)?;
+        let cold = wallets
+            .cold_wallet(config.chain_id)
+            .ok_or_else(|| anyhow!("No cold wallet for chain {}", config.chain_id))?
+            .clone();
+
+        let provider = Arc::new(Provider::<Http>::try_from(config.eth_rpc_url.as_str())?);
+        let hot: Vec<HotWallet> = wallets
+            .hot_wallets(config.chain_id)
+            .filter_map(|wallet| {
+                Some(HotWallet {
+                    name: wallet.name.clone(),
+                    client: Arc::new(SignerMiddleware::new(provider.clone(), wallet.signer()?)),
+                })
+            })
+            .collect();
+        if hot.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            chain_id: config.chain_id,
+            hot,
+            cold,
+            rules,
+            publisher,
+            interval: Duration::from_secs(config.wallet_sweep_interval_secs),
+        }))
+    }
+
+    /// Sweep every hot wallet every interval
+    pub async fn run(self) -> Result<()> {
+        let mut interval = tokio::time::interval(self.interval);
+
+        info!(
+            chain_id = self.chain_id,
+            hot_wallets = self.hot.len(),
+            cold_wallet = %self.cold.name,
+            tokens = self.rules.len(),
+            "Hot wallet sweeps started"
+        );
+
+        loop {
+            interval.tick().await;
+            for wallet in &self.hot {
+                for rule in &self.rules {
+                    if let Err(e) = self.sweep(wallet, rule).await {
+                        warn!(
+                            wallet = %wallet.name,
+                            token = rule.token.symbol,
+                            "Sweep check failed: {}",
+                            e
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sweep one token's excess from one wallet
+    async fn sweep(&self, wallet: &HotWallet, rule: &SweepRule) -> Result<()> {
+        let token = IERC20::new(rule.token.address, wallet.client.clone());
+        let balance = token.balance_of(wallet.client.address()).call().await?;
+        let threshold = to_base_units(rule.threshold, rule.token.decimals)?;
+        let Some(units) = sweep_amount(balance, threshold) else {
+            return Ok(());
+        };
+        let amount = from_base_units(units, rule.token.decimals)?;
+
+        let sent = self.transfer(&token, units).await;
+        let (tx_hash, error) = match &sent {
+            Ok(tx_hash) => (Some(*tx_hash), None),
+            Err((tx_hash, e)) => (*tx_hash, Some(e.clone())),
+        };
+        let movement = WalletMovement {
+            movement_id: Uuid::new_v4(),
+            chain_id: self.chain_id,
+            kind: "sweep".to_string(),
+            token: rule.token.symbol.to_string(),
+            amount,
+            from_wallet: wallet.name.clone(),
+            from: format!("{:?}", wallet.client.address()),
+            to_wallet: self.cold.name.clone(),
+            to: format!("{:?}", self.cold.address),
+            tx_hash: tx_hash.map(|h| format!("{h:?}")),
+            status: if sent.is_ok() { "confirmed" } else { "failed" }.to_string(),
+            error,
+            timestamp: Utc::now(),
+        };
+
+        metrics::counter!(
+            "wallet_sweeps",
+            "token" => rule.token.symbol,
+            "status" => movement.status.clone()
+        )
+        .increment(1);
+        match &movement.error {
+            None => info!(
+                wallet = %wallet.name,
+                token = rule.token.symbol,
+                %amount,
+                tx_hash = ?tx_hash,
+                "Swept to cold wallet"
+            ),
+            Some(e) => warn!(
+                wallet = %wallet.name,
+                token = rule.token.symbol,
+                %amount,
+                "Sweep failed: {}",
+                e
+            ),
+        }
+        self.audit(movement).await;
+        Ok(())
+    }
+
+    /// Send `units` to the cold wallet and wait for it to be mined
+    async fn transfer(
+        &self,
+        token: &IERC20<SignerClient>,
+        units: U256,
+    ) -> Result<TxHash, (Option<TxHash>, String)> {
+        let call = token.transfer(self.cold.address, units);
+        let pending = call
+            .send()
+            .await
+            .map_err(|e| (None, format!("not sent: {e}")))?;
+        let tx_hash = pending.tx_hash();
+
+        let receipt = pending
+            .await
+            .map_err(|e| (Some(tx_hash), e.to_string()))?
+            .ok_or_else(|| (Some(tx_hash), "dropped from the mempool".to_string()))?;
+        if receipt.status != Some(1u64.into()) {
+            return Err((Some(tx_hash), "reverted".to_string()));
+        }
+        Ok(tx_hash)
+    }
+
+    /// Publish the movement to the audit topic and as a risk alert
+    async fn audit(&self, movement: WalletMovement) {
+        let metadata = WalletMovementMetadata {
+            movement_id: movement.movement_id,
+            chain_id: movement.chain_id,
+            kind: movement.kind.clone(),
+            token: movement.token.clone(),
+            amount: movement.amount,
+            from: movement.from.clone(),
+            to: movement.to.clone(),
+            tx_hash: movement.tx_hash.clone(),
+            status: movement.status.clone(),
+        };
+        let (severity, message) = match &movement.error {
+            None => (
+                AlertSeverity::Info,
+                format!(
+                    "Swept {} {} from {} to {}",
+                    movement.amount, movement.token, movement.from_wallet, movement.to_wallet
+                ),
+            ),
+            Some(e) => (
+                AlertSeverity::Critical,
+                format!(
+                    "Sweep of {} {} from {} to {} failed: {}",
+                    movement.amount, movement.token, movement.from_wallet, movement.to_wallet, e
+                ),
+            ),
+        };
+
+        if let Err(e) = self.publisher.publish(movement).await {
+            warn!("Failed to publish wallet movement: {}", e);
+        }
+        let alert = RiskAlert::builder(metadata, message)
+            .severity(severity)
+            .build();
+        if let Err(e) = self.publisher.publish(alert).await {
+            warn!("Failed to publish wallet movement alert: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rules() {
+        let rules = parse_rules("USDC=50000, eth=2.5").unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[1].token.symbol, "WETH");
+        assert_eq!(rules[1].threshold, Decimal::new(25, 1));
+
+        assert!(parse_rules("").unwrap().is_empty());
+        assert!(parse_rules("USDC").is_err());
+        assert!(parse_rules("USDC=-1").is_err());
+        assert!(parse_rules("NOPE=1").is_err());
+    }
+
+    #[test]
+    fn test_only_the_excess_is_swept() {
+        let threshold = U256::from(1_000u64);
+        assert_eq!(sweep_amount(U256::from(999u64), threshold), None);
+        assert_eq!(sweep_amount(threshold, threshold), None);
+        assert_eq!(
+            sweep_amount(U256::from(1_250u64), threshold),
+            Some(U256::from(250u64))
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! Venue Clock Synchronization
+//!
+//! Venues such as Binance reject signed requests whose timestamp is
+//! outside their receive window. Every venue's server time is fetched
+//! periodically and the measured offset applied to request timestamps, so
+//! a drifting local clock never causes rejections. An offset beyond
+//! `clock_max_drift_ms` means the host clock needs attention: a Warning
+//! risk alert is published once per drift episode.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::ClientConfig;
+use tokio::time;
+use tracing::{info, warn};
+
+use crate::config::Config;
+use crate::router::ExchangeRouter;
+use common::events::{topics, AlertSeverity, Event, RiskAlert};
+use common::ClockDriftMetadata;
+
+pub struct ClockSync {
+    router: Arc<ExchangeRouter>,
+    producer: FutureProducer,
+    interval: Duration,
+    max_drift_ms: i64,
+
+    /// Venues currently beyond the drift threshold
+    drifting: HashSet<String>,
+}
+
+impl ClockSync {
+    pub fn new(router: Arc<ExchangeRouter>, config: &Config) -> Result<Self> {
+        let producer: FutureProducer = ClientConfig::new()
+            .set("bootstrap.servers", &config.kafka_brokers)
+            .set("message.timeout.ms", "5000")
+            .create()?;
+
+        Ok(Self {
+            router,
+            producer,
+            interval: Duration::from_secs(config.clock_sync_interval_secs),
+            max_drift_ms: config.clock_max_drift_ms as i64,
+            drifting: HashSet::new(),
+        })
+    }
+
+    /// Run the sync loop
+    pub async fn run(mut self) -> Result<()> {
+        let mut interval = time::interval(self.interval);
+
+        info!(
+            interval_secs = self.interval.as_secs(),
+            max_drift_ms = self.max_drift_ms,
+            "Venue clock sync started"
+        );
+
+        loop {
+            interval.tick().await;
+
+            for (venue, exchange) in self.router.exchanges() {
+                let offset = match exchange.sync_clock().await {
+                    Ok(Some(offset)) => offset,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        warn!(venue = %venue, "Venue clock sync failed: {}", e);
+                        continue;
+                    }
+                };
+
+                metrics::gauge!("exchange_clock_offset_ms", "exchange" => venue.clone())
+                    .set(offset as f64);
+
+                if !self.is_drift(offset) {
+                    if self.drifting.remove(venue) {
+                        info!(venue = %venue, offset_ms = offset, "Venue clock drift resolved");
+                    }
+                } else if self.drifting.insert(venue.clone()) {
+                    warn!(venue = %venue, offset_ms = offset, "Venue clock drift detected");
+                    self.publish_alert(venue, offset).await;
+                }
+            }
+        }
+    }
+
+    fn is_drift(&self, offset_ms: i64) -> bool {
+        offset_ms.abs() > self.max_drift_ms
+    }
+
+    /// Publish a Warning risk alert for a drifting venue clock
+    async fn publish_alert(&self, venue: &str, offset_ms: i64) {
+        let alert = RiskAlert::builder(
+            ClockDriftMetadata {
+                venue: venue.to_string(),
+                offset_ms,
+                max_drift_ms: self.max_drift_ms,
+            },
+            format!("Local clock is {offset_ms}ms off {venue} server time"),
+        )
+        .severity(AlertSeverity::Warning)
+        .build();
+
+        let event = Event::new("risk_alert", "exchange-gateway", alert);
+        let payload = match serde_json::to_string(&event) {
+            Ok(p) => p,
+            Err(e) => {
+                warn!("Failed to serialize clock drift alert: {}", e);
+                return;
+            }
+        };
+
+        if let Err((e, _)) = self
+            .producer
+            .send(
+                FutureRecord::to(topics::ALERTS)
+                    .key(venue)
+                    .payload(&payload),
+                Duration::from_secs(5),
+            )
+            .await
+        {
+            warn!("Failed to publish clock drift alert: {}", e);
+        }
+    }
+}
@@ -0,0 +1,635 @@
+//! On-chain Transfer Reconciliation
+//!
+//! Independent check on wallet activity for treasury: ERC-20 `Transfer`
+//! logs touching each tracked address are compared with the deposit and
+//! withdrawal records the wallet service keeps in its `transactions`
+//! table. Confirmed blocks are scanned in fixed ranges from a cursor kept
+//! in Redis, so history is covered once from `transfer_recon_start_block`
+//! and a restart resumes where the last scan stopped.
+//!
+//! Each movement without a record, each record whose transaction moved
+//! something else, and each confirmed record whose transaction moved
+//! nothing is published as a risk alert. Unrecorded outflows from a
+//! tracked address are Critical; everything else is a Warning. Sweeps
+//! from hot wallets to the cold wallet are audited by the sweeper and
+//! not reported here.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use ethers::prelude::*;
+use ethers::types::ValueOrArray;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use rust_decimal::Decimal;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::Row;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::adapters::uniswap::{from_base_units, resolve_token, Token};
+use crate::config::Config;
+use crate::events::EventPublisher;
+use crate::wallet::Wallets;
+use common::events::{AlertSeverity, RiskAlert};
+use common::{ChainTransferInfo, TransferRecordInfo, UnreconciledTransferMetadata};
+
+/// `Transfer(address,address,uint256)` event signature
+fn transfer_topic() -> H256 {
+    H256::from(ethers::utils::keccak256(
+        "Transfer(address,address,uint256)",
+    ))
+}
+
+/// A token movement read from a `Transfer` log
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainTransfer {
+    pub tx_hash: H256,
+    pub log_index: u64,
+    pub block_number: u64,
+    pub token: &'static str,
+    pub from: Address,
+    pub to: Address,
+    pub amount: Decimal,
+}
+
+/// A transfer as recorded by the wallet service
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransferRecord {
+    pub id: Uuid,
+    pub tx_hash: H256,
+    pub tx_type: String,
+    pub status: String,
+    /// `None` when the stored address does not parse
+    pub from: Option<Address>,
+    pub to: Option<Address>,
+    pub currency: String,
+    pub amount: Decimal,
+}
+
+impl TransferRecord {
+    fn is_confirmed(&self) -> bool {
+        self.status.eq_ignore_ascii_case("confirmed")
+    }
+
+    fn matches(&self, transfer: &ChainTransfer) -> bool {
+        self.currency.eq_ignore_ascii_case(transfer.token)
+            && self.from == Some(transfer.from)
+            && self.to == Some(transfer.to)
+            && self.amount == transfer.amount
+    }
+}
+
+/// A difference between the chain and the wallet service's records
+#[derive(Debug, Clone, PartialEq)]
+pub enum Discrepancy {
+    /// Tokens moved with no record of the transaction
+    Unrecorded {
+        transfer: ChainTransfer,
+        /// Whether the tokens left a tracked address
+        outflow: bool,
+    },
+    /// The recorded transaction moved tokens, but not the recorded ones
+    Mismatched {
+        record: TransferRecord,
+        observed: Vec<ChainTransfer>,
+    },
+    /// A confirmed record whose transaction moved nothing in its block
+    NotOnChain { record: TransferRecord },
+}
+
+impl Discrepancy {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Unrecorded { .. } => "unrecorded",
+            Self::Mismatched { .. } => "mismatched",
+            Self::NotOnChain { .. } => "not_on_chain",
+        }
+    }
+
+    fn severity(&self) -> AlertSeverity {
+        match self {
+            Self::Unrecorded { outflow: true, .. } => AlertSeverity::Critical,
+            _ => AlertSeverity::Warning,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            Self::Unrecorded { transfer, outflow } => format!(
+                "Unrecorded {} of {} {} from {:?} to {:?} in {:?}",
+                if *outflow { "outflow" } else { "inflow" },
+                transfer.amount,
+                transfer.token,
+                transfer.from,
+                transfer.to,
+                transfer.tx_hash
+            ),
+            Self::Mismatched { record, .. } => format!(
+                "{} {} of {} {} does not match the transfers in {:?}",
+                record.tx_type, record.id, record.amount, record.currency, record.tx_hash
+            ),
+            Self::NotOnChain { record } => format!(
+                "Confirmed {} {} of {} {} moved nothing in {:?}",
+                record.tx_type, record.id, record.amount, record.currency, record.tx_hash
+            ),
+        }
+    }
+
+    fn metadata(&self) -> UnreconciledTransferMetadata {
+        let transfer = |t: &ChainTransfer| ChainTransferInfo {
+            tx_hash: format!("{:?}", t.tx_hash),
+            log_index: t.log_index,
+            block_number: t.block_number,
+            token: t.token.to_string(),
+            from: format!("{:?}", t.from),
+            to: format!("{:?}", t.to),
+            amount: t.amount,
+        };
+        let record = |r: &TransferRecord| TransferRecordInfo {
+            id: r.id,
+            tx_hash: format!("{:?}", r.tx_hash),
+            tx_type: r.tx_type.clone(),
+            status: r.status.clone(),
+            currency: r.currency.clone(),
+            amount: r.amount,
+        };
+
+        let mut metadata = UnreconciledTransferMetadata {
+            kind: self.kind().to_string(),
+            transfer: None,
+            record: None,
+            observed: Vec::new(),
+        };
+        match self {
+            Self::Unrecorded { transfer: t, .. } => metadata.transfer = Some(transfer(t)),
+            Self::Mismatched {
+                record: r,
+                observed,
+            } => {
+                metadata.record = Some(record(r));
+                metadata.observed = observed.iter().map(transfer).collect();
+            }
+            Self::NotOnChain { record: r } => metadata.record = Some(record(r)),
+        }
+        metadata
+    }
+}
+
+/// Match on-chain transfers with records of the same transaction
+///
+/// Each record claims one transfer with the same token, addresses and
+/// amount. Records and transfers left over are discrepancies, except
+/// unconfirmed records with nothing on chain, which may still be pending
+/// or have failed.
+pub fn reconcile(
+    tracked: &HashSet<Address>,
+    transfers: &[ChainTransfer],
+    records: &[TransferRecord],
+) -> Vec<Discrepancy> {
+    let mut by_tx: HashMap<H256, Vec<usize>> = HashMap::new();
+    for (i, transfer) in transfers.iter().enumerate() {
+        by_tx.entry(transfer.tx_hash).or_default().push(i);
+    }
+
+    let mut claimed = vec![false; transfers.len()];
+    let mut mismatched = HashSet::new();
+    let mut found = Vec::new();
+
+    for record in records {
+        let in_tx = by_tx
+            .get(&record.tx_hash)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let matched = in_tx
+            .iter()
+            .copied()
+            .find(|&i| !claimed[i] && record.matches(&transfers[i]));
+
+        match matched {
+            Some(i) => claimed[i] = true,
+            None if !in_tx.is_empty() => {
+                mismatched.insert(record.tx_hash);
+                found.push(Discrepancy::Mismatched {
+                    record: record.clone(),
+                    observed: in_tx.iter().map(|&i| transfers[i].clone()).collect(),
+                });
+            }
+            None if record.is_confirmed() => found.push(Discrepancy::NotOnChain {
+                record: record.clone(),
+            }),
+            None => {}
+        }
+    }
+
+    // Transfers of a mismatched transaction were reported with its record
+    for (transfer, _) in transfers
+        .iter()
+        .zip(claimed)
+        .filter(|(t, claimed)| !claimed && !mismatched.contains(&t.tx_hash))
+    {
+        found.push(Discrepancy::Unrecorded {
+            transfer: transfer.clone(),
+            outflow: tracked.contains(&transfer.from),
+        });
+    }
+
+    found
+}
+
+fn parse_address(address: Option<String>) -> Option<Address> {
+    address?.parse().ok()
+}
+
+/// Transfer from a `Transfer` log of `token`, if the log is one
+fn parse_log(token: &Token, log: &Log) -> Option<ChainTransfer> {
+    if log.removed == Some(true) || log.topics.len() != 3 || log.topics[0] != transfer_topic() {
+        return None;
+    }
+    let amount = U256::from_big_endian(log.data.as_ref());
+
+    Some(ChainTransfer {
+        tx_hash: log.transaction_hash?,
+        log_index: log.log_index?.as_u64(),
+        block_number: log.block_number?.as_u64(),
+        token: token.symbol,
+        from: Address::from(log.topics[1]),
+        to: Address::from(log.topics[2]),
+        amount: from_base_units(amount, token.decimals).ok()?,
+    })
+}
+
+pub struct TransferReconciler {
+    provider: Provider<Http>,
+    pool: PgPool,
+    redis: ConnectionManager,
+    publisher: Arc<EventPublisher>,
+    addresses: Vec<Address>,
+    tokens: Vec<Token>,
+    /// Sweep sources and destination, when sweeps are on
+    sweep_from: HashSet<Address>,
+    sweep_to: Option<Address>,
+    cursor_key: String,
+    start_block: u64,
+    confirmations: u64,
+    block_range: u64,
+    interval: Duration,
+}
+
+impl TransferReconciler {
+    /// Reconciler for the configured addresses, `None` when there are none
+    pub async fn new(config: &Config, publisher: Arc<EventPublisher>) -> Result<Option<Self>> {
+        let addresses = config
+            .transfer_recon_addresses
+            .split(',')
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+            .map(|a| {
+                a.parse::<Address>()
+                    .map_err(|e| anyhow::anyhow!("Invalid tracked address {a}: {e}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if addresses.is_empty() {
+            return Ok(None);
+        }
+        let tokens = config
+            .transfer_recon_tokens
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(|t| Ok(resolve_token(t)?))
+            .collect::<Result<Vec<_>>>()?;
+
+        let database_url = config
+            .transfer_recon_database_url
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("transfer_recon_database_url is not set"))?;
+        let pool = PgPoolOptions::new()
+            .max_connections(2)
+            .connect(database_url)
+            .await?;
+        let redis = redis::Client::open(config.redis_url.as_str())?;
+
+        let wallets = match &config.wallets_file {
+            Some(path) if !config.wallet_sweep_thresholds.trim().is_empty() => Wallets::load(path)?,
+            _ => Wallets::default(),
+        };
+
+        Ok(Some(Self {
+            provider: Provider::<Http>::try_from(config.eth_rpc_url.as_str())?,
+            pool,
+            redis: ConnectionManager::new(redis).await?,
+            publisher,
+            addresses,
+            tokens,
+            sweep_from: wallets
+                .hot_wallets(config.chain_id)
+                .map(|w| w.address)
+                .collect(),
+            sweep_to: wallets.cold_wallet(config.chain_id).map(|w| w.address),
+            cursor_key: format!("transfer_recon:{}:next_block", config.chain_id),
+            start_block: config.transfer_recon_start_block,
+            confirmations: config.transfer_recon_confirmations,
+            block_range: config.transfer_recon_block_range,
+            interval: Duration::from_secs(config.transfer_recon_interval_secs),
+        }))
+    }
+
+    /// Reconcile newly confirmed blocks every interval
+    pub async fn run(self) -> Result<()> {
+        let mut interval = tokio::time::interval(self.interval);
+
+        info!(
+            addresses = self.addresses.len(),
+            tokens = self.tokens.len(),
+            "Transfer reconciliation started"
+        );
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.catch_up().await {
+                warn!("Transfer reconciliation failed: {}", e);
+            }
+        }
+    }
+
+    /// Reconcile every confirmed block after the cursor, one range at a time
+    async fn catch_up(&self) -> Result<()> {
+        let head = self
+            .provider
+            .get_block_number()
+            .await?
+            .as_u64()
+            .saturating_sub(self.confirmations);
+
+        let mut next = self.cursor().await?;
+        while next <= head {
+            let last = (next + self.block_range - 1).min(head);
+            self.reconcile_range(next, last).await?;
+            next = last + 1;
+            self.store_cursor(next).await?;
+        }
+        Ok(())
+    }
+
+    async fn reconcile_range(&self, from_block: u64, to_block: u64) -> Result<()> {
+        let transfers = self.transfers(from_block, to_block).await?;
+        let records = self.records(from_block, to_block, &transfers).await?;
+
+        let tracked: HashSet<Address> = self.addresses.iter().copied().collect();
+        let mut found = reconcile(&tracked, &transfers, &records);
+        found.retain(|d| !self.is_sweep(d));
+
+        metrics::counter!("transfer_recon_transfers").increment(transfers.len() as u64);
+        metrics::gauge!("transfer_recon_block").set(to_block as f64);
+        for discrepancy in &found {
+            metrics::counter!("transfer_recon_discrepancies", "kind" => discrepancy.kind())
+                .increment(1);
+            warn!(
+                kind = discrepancy.kind(),
+                from_block,
+                to_block,
+                "{}",
+                discrepancy.message()
+            );
+            self.publish_alert(discrepancy).await;
+        }
+        Ok(())
+    }
+
+    fn is_sweep(&self, discrepancy: &Discrepancy) -> bool {
+        match discrepancy {
+            Discrepancy::Unrecorded { transfer, .. } => {
+                self.sweep_from.contains(&transfer.from) && self.sweep_to == Some(transfer.to)
+            }
+            _ => false,
+        }
+    }
+
+    /// Transfers of tracked tokens into or out of tracked addresses
+    async fn transfers(&self, from_block: u64, to_block: u64) -> Result<Vec<ChainTransfer>> {
+        let tracked: ValueOrArray<Option<H256>> = ValueOrArray::Array(
+            self.addresses
+                .iter()
+                .map(|address| Some(H256::from(*address)))
+                .collect(),
+        );
+
+        let mut transfers = Vec::new();
+        for token in &self.tokens {
+            let filter = Filter::new()
+                .address(token.address)
+                .topic0(transfer_topic())
+                .from_block(from_block)
+                .to_block(to_block);
+
+            // Transfers between two tracked addresses come back from both
+            let outgoing = self
+                .provider
+                .get_logs(&filter.clone().topic1(tracked.clone()))
+                .await?;
+            let incoming = self
+                .provider
+                .get_logs(&filter.topic2(tracked.clone()))
+                .await?;
+            transfers.extend(
+                outgoing
+                    .iter()
+                    .chain(&incoming)
+                    .filter_map(|log| parse_log(token, log)),
+            );
+        }
+
+        transfers.sort_by_key(|t| (t.block_number, t.log_index));
+        transfers.dedup_by_key(|t| (t.tx_hash, t.log_index));
+        Ok(transfers)
+    }
+
+    /// Records of the transactions behind `transfers`, and of any other
+    /// tracked-token transfer the wallet service placed in the range
+    async fn records(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        transfers: &[ChainTransfer],
+    ) -> Result<Vec<TransferRecord>> {
+        let hashes: Vec<String> = transfers
+            .iter()
+            .map(|t| format!("{:?}", t.tx_hash))
+            .collect();
+        let currencies: Vec<String> = self.tokens.iter().map(|t| t.symbol.to_string()).collect();
+        let addresses: Vec<String> = self.addresses.iter().map(|a| format!("{a:?}")).collect();
+
+        let rows = sqlx::query(
+            "SELECT id, tx_hash, tx_type, status, from_address, to_address, currency, amount
+             FROM transactions
+             WHERE tx_hash IS NOT NULL
+               AND (lower(tx_hash) = ANY($1)
+                    OR (block_number BETWEEN $2 AND $3
+                        AND upper(currency) = ANY($4)
+                        AND (lower(from_address) = ANY($5) OR lower(to_address) = ANY($5))))",
+        )
+        .bind(&hashes)
+        .bind(from_block as i64)
+        .bind(to_block as i64)
+        .bind(&currencies)
+        .bind(&addresses)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut records = Vec::with_capacity(rows.len());
+        for row in rows {
+            let id: Uuid = row.try_get("id")?;
+            let tx_hash: String = row.try_get("tx_hash")?;
+            let Ok(tx_hash) = tx_hash.parse::<H256>() else {
+                warn!(%id, %tx_hash, "Skipping transfer record with an invalid hash");
+                continue;
+            };
+            records.push(TransferRecord {
+                id,
+                tx_hash,
+                tx_type: row.try_get("tx_type")?,
+                status: row.try_get("status")?,
+                from: parse_address(row.try_get("from_address")?),
+                to: parse_address(row.try_get("to_address")?),
+                currency: row.try_get("currency")?,
+                amount: row.try_get("amount")?,
+            });
+        }
+        Ok(records)
+    }
+
+    /// First block not yet reconciled
+    async fn cursor(&self) -> Result<u64> {
+        let mut conn = self.redis.clone();
+        let stored: Option<u64> = conn.get(&self.cursor_key).await?;
+        Ok(stored.unwrap_or(self.start_block))
+    }
+
+    async fn store_cursor(&self, next_block: u64) -> Result<()> {
+        let mut conn = self.redis.clone();
+        conn.set::<_, _, ()>(&self.cursor_key, next_block).await?;
+        Ok(())
+    }
+
+    async fn publish_alert(&self, discrepancy: &Discrepancy) {
+        let alert = RiskAlert::builder(discrepancy.metadata(), discrepancy.message())
+            .severity(discrepancy.severity())
+            .build();
+        if let Err(e) = self.publisher.publish(alert).await {
+            warn!("Failed to publish transfer reconciliation alert: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(byte: u8) -> Address {
+        Address::repeat_byte(byte)
+    }
+
+    fn transfer(tx: u8, log_index: u64, from: u8, to: u8, amount: i64) -> ChainTransfer {
+        ChainTransfer {
+            tx_hash: H256::repeat_byte(tx),
+            log_index,
+            block_number: 100,
+            token: "USDC",
+            from: address(from),
+            to: address(to),
+            amount: Decimal::from(amount),
+        }
+    }
+
+    fn record(tx: u8, from: u8, to: u8, amount: i64, status: &str) -> TransferRecord {
+        TransferRecord {
+            id: Uuid::new_v4(),
+            tx_hash: H256::repeat_byte(tx),
+            tx_type: "withdrawal".to_string(),
+            status: status.to_string(),
+            from: Some(address(from)),
+            to: Some(address(to)),
+            currency: "usdc".to_string(),
+            amount: Decimal::from(amount),
+        }
+    }
+
+    #[test]
+    fn test_recorded_transfers_reconcile() {
+        let tracked = HashSet::from([address(1)]);
+        let transfers = [transfer(0xa, 0, 1, 2, 50), transfer(0xb, 3, 3, 1, 20)];
+        let records = [
+            record(0xa, 1, 2, 50, "confirmed"),
+            record(0xb, 3, 1, 20, "confirmed"),
+        ];
+
+        assert!(reconcile(&tracked, &transfers, &records).is_empty());
+    }
+
+    #[test]
+    fn test_unrecorded_outflow_is_critical() {
+        let tracked = HashSet::from([address(1)]);
+        let transfers = [transfer(0xa, 0, 1, 9, 50), transfer(0xb, 0, 9, 1, 5)];
+
+        let found = reconcile(&tracked, &transfers, &[]);
+        assert_eq!(found.len(), 2);
+        assert!(matches!(found[0].severity(), AlertSeverity::Critical));
+        assert!(matches!(
+            found[1],
+            Discrepancy::Unrecorded { outflow: false, .. }
+        ));
+    }
+
+    #[test]
+    fn test_wrong_amount_is_mismatched_not_unrecorded() {
+        let tracked = HashSet::from([address(1)]);
+        let transfers = [transfer(0xa, 0, 1, 2, 49)];
+        let records = [record(0xa, 1, 2, 50, "confirmed")];
+
+        let found = reconcile(&tracked, &transfers, &records);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind(), "mismatched");
+    }
+
+    #[test]
+    fn test_only_confirmed_records_must_be_on_chain() {
+        let tracked = HashSet::from([address(1)]);
+        let records = [
+            record(0xa, 1, 2, 50, "confirmed"),
+            record(0xb, 1, 2, 50, "failed"),
+        ];
+
+        let found = reconcile(&tracked, &[], &records);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind(), "not_on_chain");
+    }
+
+    #[test]
+    fn test_parse_transfer_log() {
+        let usdc = resolve_token("USDC").unwrap();
+        let mut data = [0u8; 32];
+        U256::from(1_500_000u64).to_big_endian(&mut data);
+        let log = Log {
+            address: usdc.address,
+            topics: vec![
+                transfer_topic(),
+                H256::from(address(1)),
+                H256::from(address(2)),
+            ],
+            data: data.to_vec().into(),
+            transaction_hash: Some(H256::repeat_byte(0xa)),
+            log_index: Some(U256::from(4)),
+            block_number: Some(U64::from(100)),
+            ..Default::default()
+        };
+
+        let transfer = parse_log(&usdc, &log).unwrap();
+        assert_eq!(transfer.amount, Decimal::new(15, 1));
+        assert_eq!(transfer.from, address(1));
+        assert_eq!(transfer.to, address(2));
+        assert_eq!(transfer.log_index, 4);
+    }
+}
@@ -0,0 +1,295 @@
+//! DEX Wallets
+//!
+//! Signing key for on-chain swaps, loaded from a raw private key or an
+//! encrypted JSON keystore. DEX adapters wrap their provider in a
+//! `SignerMiddleware` with it; without a wallet, swaps are refused.
+//!
+//! A wallets file can instead list several wallets per chain. Hot wallets
+//! hold a key and sign swaps and sweeps; cold wallets are addresses only,
+//! the destination for swept proceeds, and their keys never reach the
+//! gateway. Each chain has at most one cold wallet.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use anyhow::{anyhow, bail, Result};
+use ethers::{
+    middleware::SignerMiddleware,
+    providers::{Http, Provider},
+    signers::{LocalWallet, Signer},
+    types::Address,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::config::Config;
+
+/// Provider that signs and sends transactions from the gateway wallet
+pub type SignerClient = SignerMiddleware<Arc<Provider<Http>>, LocalWallet>;
+
+/// Wallet configured for on-chain execution, if any
+///
+/// A private key takes precedence over a keystore, and either over the
+/// first hot wallet for the chain in the wallets file.
+pub fn load_wallet(config: &Config) -> Result<Option<LocalWallet>> {
+    let wallet = if config.wallet_private_key.is_some() || config.wallet_keystore_path.is_some() {
+        decrypt(
+            config.wallet_private_key.as_deref(),
+            config.wallet_keystore_path.as_deref(),
+            config.wallet_keystore_password.as_deref(),
+        )?
+    } else if let Some(path) = &config.wallets_file {
+        match Wallets::load(path)?.swap_wallet(config.chain_id) {
+            Some(wallet) => wallet,
+            None => return Ok(None),
+        }
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(wallet.with_chain_id(config.chain_id)))
+}
+
+fn decrypt(
+    private_key: Option<&str>,
+    keystore_path: Option<&str>,
+    keystore_password: Option<&str>,
+) -> Result<LocalWallet> {
+    match (private_key, keystore_path) {
+        (Some(key), _) => Ok(key.trim_start_matches("0x").parse::<LocalWallet>()?),
+        (None, Some(path)) => {
+            let password = keystore_password
+                .ok_or_else(|| anyhow!("A keystore password is required with a keystore"))?;
+            Ok(LocalWallet::decrypt_keystore(path, password)?)
+        }
+        (None, None) => bail!("No private key or keystore"),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WalletRole {
+    /// Holds a key; signs swaps and sweeps
+    Hot,
+    /// Address only; receives sweeps
+    Cold,
+}
+
+/// Wallet as listed in the wallets file
+#[derive(Clone, Deserialize)]
+struct WalletEntry {
+    name: String,
+    chain_id: u64,
+    role: WalletRole,
+    /// Required for cold wallets; checked against the key for hot ones
+    #[serde(default)]
+    address: Option<Address>,
+    #[serde(default)]
+    private_key: Option<String>,
+    #[serde(default)]
+    keystore_path: Option<String>,
+    #[serde(default)]
+    keystore_password: Option<String>,
+}
+
+/// A loaded wallet
+#[derive(Clone)]
+pub struct ManagedWallet {
+    pub name: String,
+    pub chain_id: u64,
+    pub role: WalletRole,
+    pub address: Address,
+    /// Present for hot wallets only
+    signer: Option<LocalWallet>,
+}
+
+impl ManagedWallet {
+    /// Signing key, bound to the wallet's chain
+    pub fn signer(&self) -> Option<LocalWallet> {
+        self.signer
+            .clone()
+            .map(|wallet| wallet.with_chain_id(self.chain_id))
+    }
+}
+
+// Keep keys out of logs
+impl fmt::Debug for ManagedWallet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ManagedWallet")
+            .field("name", &self.name)
+            .field("chain_id", &self.chain_id)
+            .field("role", &self.role)
+            .field("address", &self.address)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Hot and cold wallets by chain
+#[derive(Debug, Clone, Default)]
+pub struct Wallets {
+    wallets: Vec<ManagedWallet>,
+}
+
+impl Wallets {
+    /// Read wallets from a JSON array, decrypting hot wallet keys
+    pub fn load(path: &str) -> Result<Self> {
+        let entries: Vec<WalletEntry> = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+
+        let mut names = HashSet::new();
+        let mut cold_chains = HashSet::new();
+        let mut wallets = Vec::with_capacity(entries.len());
+        for entry in entries {
+            if !names.insert(entry.name.clone()) {
+                bail!("Duplicate wallet {}", entry.name);
+            }
+            wallets.push(match entry.role {
+                WalletRole::Hot => Self::hot(entry)?,
+                WalletRole::Cold => {
+                    if !cold_chains.insert(entry.chain_id) {
+                        bail!("More than one cold wallet for chain {}", entry.chain_id);
+                    }
+                    Self::cold(entry)?
+                }
+            });
+        }
+
+        Ok(Self { wallets })
+    }
+
+    fn hot(entry: WalletEntry) -> Result<ManagedWallet> {
+        let signer = decrypt(
+            entry.private_key.as_deref(),
+            entry.keystore_path.as_deref(),
+            entry.keystore_password.as_deref(),
+        )
+        .map_err(|e| anyhow!("Hot wallet {}: {e}", entry.name))?;
+        if entry.address.is_some_and(|a| a != signer.address()) {
+            bail!("Hot wallet {} address does not match its key", entry.name);
+        }
+
+        Ok(ManagedWallet {
+            name: entry.name,
+            chain_id: entry.chain_id,
+            role: WalletRole::Hot,
+            address: signer.address(),
+            signer: Some(signer),
+        })
+    }
+
+    fn cold(entry: WalletEntry) -> Result<ManagedWallet> {
+        if entry.private_key.is_some() || entry.keystore_path.is_some() {
+            bail!("Cold wallet {} must not carry a key", entry.name);
+        }
+        let address = entry
+            .address
+            .ok_or_else(|| anyhow!("Cold wallet {} has no address", entry.name))?;
+
+        Ok(ManagedWallet {
+            name: entry.name,
+            chain_id: entry.chain_id,
+            role: WalletRole::Cold,
+            address,
+            signer: None,
+        })
+    }
+
+    /// Hot wallets on `chain_id`, in file order
+    pub fn hot_wallets(&self, chain_id: u64) -> impl Iterator<Item = &ManagedWallet> {
+        self.wallets
+            .iter()
+            .filter(move |w| w.chain_id == chain_id && w.role == WalletRole::Hot)
+    }
+
+    pub fn cold_wallet(&self, chain_id: u64) -> Option<&ManagedWallet> {
+        self.wallets
+            .iter()
+            .find(|w| w.chain_id == chain_id && w.role == WalletRole::Cold)
+    }
+
+    /// Key DEX swaps on `chain_id` are signed with: the first hot wallet
+    pub fn swap_wallet(&self, chain_id: u64) -> Option<LocalWallet> {
+        self.hot_wallets(chain_id).next()?.signer()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config(extra: serde_json::Value) -> Config {
+        let mut base = json!({
+            "eth_rpc_url": "http://localhost:8545",
+            "kafka_brokers": "localhost:9092",
+            "chain_id": 5,
+        });
+        base.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(base).unwrap()
+    }
+
+    #[test]
+    fn test_load_wallet_from_private_key() {
+        // Well-known development key
+        let wallet = load_wallet(&config(json!({
+            "wallet_private_key":
+                "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        })))
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(
+            wallet.address(),
+            "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+                .parse()
+                .unwrap()
+        );
+        assert_eq!(wallet.chain_id(), 5);
+    }
+
+    #[test]
+    fn test_wallets_file_roles() {
+        let cold = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+        let hot = json!({
+            "name": "hot-1",
+            "chain_id": 1,
+            "role": "hot",
+            "private_key": "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        });
+        let vault = json!({ "name": "vault", "chain_id": 1, "role": "cold", "address": cold });
+
+        let path = std::env::temp_dir().join(format!("wallets-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, json!([hot, vault]).to_string()).unwrap();
+        let wallets = Wallets::load(path.to_str().unwrap()).unwrap();
+        assert_eq!(wallets.hot_wallets(1).count(), 1);
+        assert_eq!(
+            wallets.cold_wallet(1).unwrap().address,
+            cold.parse().unwrap()
+        );
+        assert!(wallets.cold_wallet(5).is_none());
+        assert_eq!(wallets.swap_wallet(1).unwrap().chain_id(), 1);
+        assert!(!format!("{:?}", wallets).contains("ac0974"));
+
+        // Cold keys stay off the gateway
+        let keyed = json!({
+            "name": "vault",
+            "chain_id": 1,
+            "role": "cold",
+            "address": cold,
+            "private_key": "0x01",
+        });
+        std::fs::write(&path, json!([hot, keyed]).to_string()).unwrap();
+        assert!(Wallets::load(path.to_str().unwrap()).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_no_wallet_or_missing_password() {
+        assert!(load_wallet(&config(json!({}))).unwrap().is_none());
+        assert!(load_wallet(&config(json!({
+            "wallet_keystore_path": "/tmp/keystore.json",
+        })))
+        .is_err());
+    }
+}
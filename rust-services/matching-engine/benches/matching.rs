@@ -2,12 +2,18 @@
 //!
 //! Run with: cargo bench --package matching-engine
 
+use std::sync::Arc;
+
 use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
 use rust_decimal::Decimal;
 use uuid::Uuid;
 
-use common::{Order, OrderStatus, OrderType, Side, Symbol, TimeInForce};
-use matching_engine::orderbook::OrderBook;
+use common::{Order, OrderStatus, OrderType, SelfTradePrevention, Side, Symbol, TimeInForce};
+use matching_engine::orderbook::{BookSnapshot, OrderBook, RestingOrder};
+use matching_engine::snapshot::restore_books;
+
+/// Resting orders in the cold-start restore benchmarks
+const RESTORE_ORDERS: usize = 1_000_000;
 
 fn create_order(side: Side, price: Decimal, quantity: Decimal) -> Order {
     Order {
@@ -21,6 +27,7 @@ fn create_order(side: Side, price: Decimal, quantity: Decimal) -> Order {
         status: OrderStatus::Pending,
         price: Some(price),
         stop_price: None,
+        trigger_source: None,
         quantity,
         filled_quantity: Decimal::ZERO,
         remaining_quantity: quantity,
@@ -28,6 +35,9 @@ fn create_order(side: Side, price: Decimal, quantity: Decimal) -> Order {
         sequence: 0,
         created_at: chrono::Utc::now(),
         updated_at: chrono::Utc::now(),
+        expire_at: None,
+        display_quantity: None,
+        self_trade_prevention: SelfTradePrevention::default(),
     }
 }
 
@@ -126,11 +136,75 @@ fn bench_depth_retrieval(c: &mut Criterion) {
     group.finish();
 }
 
+/// Snapshot of `orders` resting orders spread over 1000 levels per side
+fn book_snapshot(symbol: Symbol, orders: usize) -> BookSnapshot {
+    let resting = |price: i64, sequence: usize| RestingOrder {
+        order_id: Uuid::new_v4(),
+        client_order_id: Uuid::new_v4().to_string(),
+        user_id: Uuid::new_v4(),
+        price: Decimal::new(price, 0),
+        remaining_quantity: Decimal::new(1, 0),
+        hidden_quantity: Decimal::ZERO,
+        display_quantity: None,
+        sequence: sequence as u64,
+    };
+    let per_side = orders / 2;
+
+    BookSnapshot {
+        symbol,
+        bids: (0..per_side)
+            .map(|i| resting(49999 - (i * 1000 / per_side) as i64, i))
+            .collect(),
+        asks: (0..per_side)
+            .map(|i| resting(50001 + (i * 1000 / per_side) as i64, per_side + i))
+            .collect(),
+        next_order_sequence: orders as u64,
+        next_trade_id: 1,
+        book_sequence: 1,
+    }
+}
+
+fn bench_snapshot_restore(c: &mut Criterion) {
+    let mut group = c.benchmark_group("snapshot_restore");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(RESTORE_ORDERS as u64));
+
+    let snapshot = book_snapshot(Symbol::new("BTC", "USDT"), RESTORE_ORDERS);
+    group.bench_function("restore_1m_orders_one_book", |b| {
+        b.iter_batched(
+            || OrderBook::new(Symbol::new("BTC", "USDT")),
+            |book| book.restore(black_box(&snapshot)),
+            criterion::BatchSize::LargeInput,
+        );
+    });
+
+    // The same orders across 16 symbols, restored in parallel
+    let snapshots: Vec<BookSnapshot> = (0..16)
+        .map(|i| book_snapshot(Symbol::new(&format!("C{i}"), "USDT"), RESTORE_ORDERS / 16))
+        .collect();
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    group.bench_function("restore_1m_orders_16_books", |b| {
+        b.iter_batched(
+            || {
+                snapshots
+                    .iter()
+                    .map(|s| (Arc::new(OrderBook::new(s.symbol.clone())), s))
+                    .collect::<Vec<_>>()
+            },
+            |books| restore_books(black_box(&books), threads),
+            criterion::BatchSize::LargeInput,
+        );
+    });
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_order_insertion,
     bench_order_matching,
     bench_order_cancellation,
     bench_depth_retrieval,
+    bench_snapshot_restore,
 );
 criterion_main!(benches);
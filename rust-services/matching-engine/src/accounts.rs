@@ -0,0 +1,350 @@
+//! Account Balances
+//!
+//! Engine bookkeeping over the common [`Ledger`]: what each open order has
+//! locked, so its fills are paid from its own funds and the rest is
+//! released once it fills, is cancelled or expires.
+//!
+//! Sells lock their base quantity. Buys lock the quote cost at their limit
+//! price plus the taker fee, whether or not they end up taking. A market
+//! buy locks the cost of walking the asks for its quantity, which the book
+//! cannot change before it matches; a stop-market buy locks at its stop
+//! price, and once triggered tops its lock up to the cost of walking the
+//! asks. A fill is paid out of its own order's lock, any excess out of the
+//! available balance, so other orders' locks are never touched. Balances
+//! and locks are part of engine snapshots.
+
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use common::{
+    AccountBalance, Balance, Ledger, Order, Side, Trade, TradeFees, TradeLocks, TradingError,
+};
+
+/// Funds an open order still holds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reservation {
+    pub order_id: Uuid,
+    pub user_id: Uuid,
+    pub asset: String,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+
+    /// Order quantity not yet filled
+    #[serde(with = "rust_decimal::serde::str")]
+    pub quantity: Decimal,
+}
+
+/// User balances and the funds open orders lock
+pub struct Accounts {
+    ledger: Mutex<Ledger>,
+    reservations: DashMap<Uuid, Reservation>,
+    taker_fee_rate: Decimal,
+}
+
+impl Accounts {
+    pub fn new(taker_fee_bps: u32) -> Self {
+        Self {
+            ledger: Mutex::new(Ledger::new()),
+            reservations: DashMap::new(),
+            taker_fee_rate: Decimal::new(taker_fee_bps as i64, 4),
+        }
+    }
+
+    /// Lock the funds `order` needs; `cost` is a buy's quote cost before
+    /// fees, `None` when there is nothing it could buy
+    pub fn reserve(&self, order: &Order, cost: Option<Decimal>) -> Result<(), TradingError> {
+        let (asset, amount) = match order.side {
+            Side::Sell => (order.symbol.base(), order.remaining_quantity),
+            Side::Buy => match cost {
+                Some(cost) => (
+                    order.symbol.quote(),
+                    cost * (Decimal::ONE + self.taker_fee_rate),
+                ),
+                None => return Ok(()),
+            },
+        };
+
+        self.ledger.lock().reserve(order.user_id, asset, amount)?;
+        self.reservations.insert(
+            order.id,
+            Reservation {
+                order_id: order.id,
+                user_id: order.user_id,
+                asset: asset.to_string(),
+                amount,
+                quantity: order.remaining_quantity,
+            },
+        );
+        Ok(())
+    }
+
+    /// Raise a triggered order's lock to `cost` plus the taker fee, if it
+    /// holds less
+    pub fn top_up(&self, order: &Order, cost: Decimal) -> Result<(), TradingError> {
+        let Some(mut reservation) = self.reservations.get_mut(&order.id) else {
+            return Ok(());
+        };
+        let amount = cost * (Decimal::ONE + self.taker_fee_rate);
+        if amount <= reservation.amount {
+            return Ok(());
+        }
+
+        self.ledger.lock().reserve(
+            reservation.user_id,
+            &reservation.asset,
+            amount - reservation.amount,
+        )?;
+        reservation.amount = amount;
+        Ok(())
+    }
+
+    /// Settle a trade, each side paying out of its own order's lock
+    pub fn settle(&self, trade: &Trade, taker_fee: Decimal) {
+        let fees = TradeFees::taker(trade, taker_fee);
+        let (buy_order, sell_order) = match trade.taker_side {
+            Side::Buy => (trade.taker_order_id, trade.maker_order_id),
+            Side::Sell => (trade.maker_order_id, trade.taker_order_id),
+        };
+        let locks = TradeLocks {
+            buyer: self.draw(buy_order, trade.quote_quantity + fees.buyer, trade.quantity),
+            seller: self.draw(sell_order, trade.quantity, trade.quantity),
+        };
+
+        let mut ledger = self.ledger.lock();
+        ledger.settle(trade, fees, locks);
+        for order_id in [buy_order, sell_order] {
+            self.release_filled(&mut ledger, order_id);
+        }
+    }
+
+    /// Count a fill against an order's lock, returning the part of
+    /// `amount` the lock covers
+    fn draw(&self, order_id: Uuid, amount: Decimal, quantity: Decimal) -> Decimal {
+        let Some(mut reservation) = self.reservations.get_mut(&order_id) else {
+            return Decimal::ZERO;
+        };
+        let drawn = amount.min(reservation.amount);
+        reservation.amount -= drawn;
+        reservation.quantity -= quantity.min(reservation.quantity);
+        drawn
+    }
+
+    /// Release what is left of a filled order's lock
+    fn release_filled(&self, ledger: &mut Ledger, order_id: Uuid) {
+        if let Some((_, reservation)) = self
+            .reservations
+            .remove_if(&order_id, |_, r| r.quantity.is_zero())
+        {
+            ledger.release(reservation.user_id, &reservation.asset, reservation.amount);
+        }
+    }
+
+    /// Release whatever an order that left the book still holds
+    pub fn release(&self, order_id: Uuid) {
+        if let Some((_, reservation)) = self.reservations.remove(&order_id) {
+            self.ledger
+                .lock()
+                .release(reservation.user_id, &reservation.asset, reservation.amount);
+        }
+    }
+
+    /// Hand an order's lock to its in-place replacement, releasing the
+    /// share of the quantity it no longer needs
+    pub fn replace(&self, order_id: Uuid, replacement: &Order) {
+        let Some((_, mut reservation)) = self.reservations.remove(&order_id) else {
+            return;
+        };
+
+        let quantity = replacement.remaining_quantity.min(reservation.quantity);
+        if !reservation.quantity.is_zero() {
+            let kept = reservation.amount * quantity / reservation.quantity;
+            self.ledger.lock().release(
+                reservation.user_id,
+                &reservation.asset,
+                reservation.amount - kept,
+            );
+            reservation.amount = kept;
+        }
+        reservation.order_id = replacement.id;
+        reservation.quantity = quantity;
+        self.reservations.insert(replacement.id, reservation);
+    }
+
+    pub fn deposit(&self, user_id: Uuid, asset: &str, amount: Decimal) -> Balance {
+        self.ledger.lock().deposit(user_id, asset, amount)
+    }
+
+    pub fn withdraw(
+        &self,
+        user_id: Uuid,
+        asset: &str,
+        amount: Decimal,
+    ) -> Result<Balance, TradingError> {
+        self.ledger.lock().withdraw(user_id, asset, amount)
+    }
+
+    pub fn user_balances(&self, user_id: Uuid) -> Vec<AccountBalance> {
+        self.ledger.lock().user_balances(user_id)
+    }
+
+    pub fn balances(&self) -> Vec<AccountBalance> {
+        self.ledger.lock().balances()
+    }
+
+    pub fn reservations(&self) -> Vec<Reservation> {
+        let mut reservations: Vec<Reservation> =
+            self.reservations.iter().map(|r| r.clone()).collect();
+        reservations.sort_by_key(|r| r.order_id);
+        reservations
+    }
+
+    /// Replace balances and locks with those from a snapshot
+    pub fn restore(&self, balances: &[AccountBalance], reservations: &[Reservation]) {
+        *self.ledger.lock() = Ledger::restore(balances);
+        self.reservations.clear();
+        for reservation in reservations {
+            self.reservations
+                .insert(reservation.order_id, reservation.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use common::{OrderStatus, Symbol, TriggerSource};
+
+    fn order(user_id: Uuid, side: Side, price: i64, quantity: i64) -> Order {
+        Order::builder()
+            .user(user_id)
+            .symbol(Symbol::new("BTC", "USDT"))
+            .side(side)
+            .status(OrderStatus::Open)
+            .price(price)
+            .quantity(quantity)
+            .build()
+    }
+
+    fn stop_buy(user_id: Uuid, stop_price: i64) -> Order {
+        Order::builder()
+            .user(user_id)
+            .symbol(Symbol::new("BTC", "USDT"))
+            .stop_market(stop_price, TriggerSource::LastTrade)
+            .build()
+    }
+
+    fn trade(maker: &Order, taker: &Order, price: i64, quantity: i64) -> Trade {
+        Trade {
+            id: Uuid::new_v4(),
+            trade_id: 1,
+            symbol: maker.symbol.clone(),
+            maker_order_id: maker.id,
+            maker_user_id: maker.user_id,
+            taker_order_id: taker.id,
+            taker_user_id: taker.user_id,
+            price: Decimal::from(price),
+            quantity: Decimal::from(quantity),
+            quote_quantity: Decimal::from(price * quantity),
+            taker_side: taker.side,
+            executed_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_locks_follow_orders_through_fills_and_cancels() {
+        // 1% taker fee
+        let accounts = Accounts::new(100);
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        accounts.deposit(alice, "USDT", Decimal::from(1010));
+        accounts.deposit(bob, "BTC", Decimal::from(3));
+
+        let buy = order(alice, Side::Buy, 100, 10);
+        let cost = buy.price.map(|p| p * buy.quantity);
+        accounts.reserve(&buy, cost).unwrap();
+        let second = order(alice, Side::Buy, 100, 1);
+        assert!(matches!(
+            accounts.reserve(&second, second.price),
+            Err(TradingError::InsufficientBalance { .. })
+        ));
+
+        // Bob sells 3 into the resting bid; the maker pays no fee
+        let sell = order(bob, Side::Sell, 100, 3);
+        accounts.reserve(&sell, None).unwrap();
+        accounts.settle(&trade(&buy, &sell, 100, 3), Decimal::from(3));
+
+        let alice_balances = accounts.user_balances(alice);
+        assert_eq!(alice_balances[0].asset, "BTC");
+        assert_eq!(alice_balances[0].balance.available, Decimal::from(3));
+        assert_eq!(alice_balances[1].balance.locked, Decimal::from(710));
+        let bob_balances = accounts.user_balances(bob);
+        assert_eq!(bob_balances[0].balance.total(), Decimal::ZERO);
+        assert_eq!(bob_balances[1].balance.available, Decimal::from(297));
+        // The filled sell no longer holds anything
+        assert_eq!(accounts.reservations().len(), 1);
+
+        // Reducing the bid to 5 keeps the share of the lock for 2 of the 7
+        // left
+        let mut reduced = order(alice, Side::Buy, 100, 5);
+        reduced.filled_quantity = Decimal::from(3);
+        reduced.remaining_quantity = Decimal::from(2);
+        accounts.replace(buy.id, &reduced);
+        assert_eq!(
+            accounts.reservations()[0].amount,
+            Decimal::from(710) * Decimal::from(2) / Decimal::from(7)
+        );
+
+        accounts.release(reduced.id);
+        let usdt = &accounts.user_balances(alice)[1].balance;
+        assert_eq!(usdt.locked, Decimal::ZERO);
+        assert_eq!(usdt.available, Decimal::from(710));
+        assert!(accounts.reservations().is_empty());
+    }
+
+    #[test]
+    fn test_stop_filling_above_its_stop_leaves_other_locks_alone() {
+        let accounts = Accounts::new(0);
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        accounts.deposit(alice, "USDT", Decimal::from(1000));
+        accounts.deposit(bob, "BTC", Decimal::ONE);
+
+        for resting in [
+            order(alice, Side::Buy, 100, 2),
+            order(alice, Side::Buy, 90, 3),
+        ] {
+            let cost = resting.price.map(|p| p * resting.quantity);
+            accounts.reserve(&resting, cost).unwrap();
+        }
+        let stop = stop_buy(alice, 100);
+        accounts.reserve(&stop, stop.stop_price).unwrap();
+
+        // Triggered with the asks at 150
+        accounts.top_up(&stop, Decimal::from(150)).unwrap();
+        let sell = order(bob, Side::Sell, 150, 1);
+        accounts.reserve(&sell, None).unwrap();
+        accounts.settle(&trade(&sell, &stop, 150, 1), Decimal::ZERO);
+
+        let usdt = &accounts.user_balances(alice)[1].balance;
+        assert_eq!(usdt.locked, Decimal::from(470));
+        assert_eq!(usdt.available, Decimal::from(380));
+        let amounts: Vec<Decimal> = accounts.reservations().iter().map(|r| r.amount).collect();
+        assert_eq!(amounts.len(), 2);
+        assert_eq!(amounts.iter().sum::<Decimal>(), Decimal::from(470));
+
+        // A top-up the available balance cannot cover is refused
+        let second = stop_buy(alice, 100);
+        accounts.reserve(&second, second.stop_price).unwrap();
+        assert!(matches!(
+            accounts.top_up(&second, Decimal::from(500)),
+            Err(TradingError::InsufficientBalance { .. })
+        ));
+        accounts.release(second.id);
+        let usdt = &accounts.user_balances(alice)[1].balance;
+        assert_eq!(usdt.locked, Decimal::from(470));
+        assert_eq!(usdt.available, Decimal::from(380));
+    }
+}
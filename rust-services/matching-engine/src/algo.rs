@@ -0,0 +1,449 @@
+//! Native Execution Algorithms
+//!
+//! TWAP and iceberg parent orders executed directly against the internal
+//! books. The engine schedules child slices through its normal command
+//! path and rolls child fills up into a consolidated parent order, so
+//! basic algos need no separate OMS.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use common::{Order, OrderStatus, Trade, TradingError};
+
+use crate::engine::MatchingEngine;
+use crate::latency::Ingress;
+
+/// How often iceberg parents check whether their visible clip has filled
+const ICEBERG_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Execution algorithm parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Algo {
+    /// Equal slices spread evenly over `duration_secs`
+    Twap { duration_secs: u64, slices: u32 },
+
+    /// Show at most `clip_size` in the book at a time
+    Iceberg {
+        #[serde(with = "common::decimal::flex")]
+        clip_size: Decimal,
+    },
+}
+
+impl Algo {
+    /// Validate algo parameters
+    pub fn validate(&self) -> Result<(), TradingError> {
+        match self {
+            Algo::Twap {
+                duration_secs,
+                slices,
+            } if *duration_secs == 0 || *slices == 0 => Err(TradingError::InvalidOrder(
+                "TWAP requires a positive duration and slice count".to_string(),
+            )),
+            Algo::Iceberg { clip_size } if *clip_size <= Decimal::ZERO => Err(
+                TradingError::InvalidOrder("Iceberg clip size must be positive".to_string()),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Delay between scheduling attempts
+    fn interval(&self) -> Duration {
+        match self {
+            Algo::Twap {
+                duration_secs,
+                slices,
+            } => Duration::from_secs(*duration_secs) / (*slices).max(1),
+            Algo::Iceberg { .. } => ICEBERG_POLL_INTERVAL,
+        }
+    }
+}
+
+/// Parent order lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlgoState {
+    Running,
+    Paused,
+    Cancelled,
+    Completed,
+}
+
+impl AlgoState {
+    pub fn is_final(&self) -> bool {
+        matches!(self, AlgoState::Cancelled | AlgoState::Completed)
+    }
+}
+
+/// Child slice of a parent order
+#[derive(Debug, Clone, Serialize)]
+pub struct ChildSlice {
+    pub order_id: Uuid,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub quantity: Decimal,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub filled_quantity: Decimal,
+
+    /// Still working in the book
+    pub open: bool,
+}
+
+/// Parent order with consolidated fills
+///
+/// `order` carries the parent id and the aggregate filled/remaining
+/// quantity and average price across all children.
+#[derive(Debug, Clone, Serialize)]
+pub struct ParentOrder {
+    pub order: Order,
+    pub algo: Algo,
+    pub state: AlgoState,
+
+    /// Quantity released to the book as children
+    #[serde(with = "rust_decimal::serde::str")]
+    pub released_quantity: Decimal,
+
+    pub children: Vec<ChildSlice>,
+
+    #[serde(skip)]
+    notional: Decimal,
+}
+
+impl ParentOrder {
+    fn has_open_child(&self) -> bool {
+        self.children.iter().any(|c| c.open)
+    }
+
+    /// Size of the next child slice
+    fn next_slice_quantity(&self) -> Decimal {
+        let unreleased = self.order.quantity - self.released_quantity;
+        let size = match &self.algo {
+            Algo::Twap { slices, .. } => self.order.quantity / Decimal::from((*slices).max(1)),
+            Algo::Iceberg { clip_size } => *clip_size,
+        };
+        size.min(unreleased)
+    }
+
+    fn apply_fill(
+        &mut self,
+        child_id: Uuid,
+        price: Decimal,
+        quantity: Decimal,
+        now: DateTime<Utc>,
+    ) {
+        if let Some(child) = self.children.iter_mut().find(|c| c.order_id == child_id) {
+            child.filled_quantity += quantity;
+            if child.filled_quantity >= child.quantity {
+                child.open = false;
+            }
+        }
+
+        self.notional += price * quantity;
+        self.order.filled_quantity += quantity;
+        self.order.remaining_quantity = self.order.quantity - self.order.filled_quantity;
+        self.order.avg_fill_price = Some(self.notional / self.order.filled_quantity);
+        self.order.updated_at = now;
+        self.refresh_status(now);
+    }
+
+    fn close_child(&mut self, child_id: Uuid, now: DateTime<Utc>) {
+        if let Some(child) = self.children.iter_mut().find(|c| c.order_id == child_id) {
+            child.open = false;
+        }
+        self.refresh_status(now);
+    }
+
+    /// Derive the parent status from its fills and children
+    fn refresh_status(&mut self, now: DateTime<Utc>) {
+        if self.state == AlgoState::Cancelled {
+            self.order.status = OrderStatus::Cancelled;
+            return;
+        }
+
+        let exhausted = self.released_quantity >= self.order.quantity && !self.has_open_child();
+
+        self.order.status = if self.order.remaining_quantity == Decimal::ZERO {
+            OrderStatus::Filled
+        } else if exhausted {
+            // Everything was released but some children left quantity unfilled
+            OrderStatus::Cancelled
+        } else if self.order.filled_quantity > Decimal::ZERO {
+            OrderStatus::PartiallyFilled
+        } else {
+            OrderStatus::Open
+        };
+
+        if self.order.remaining_quantity == Decimal::ZERO || exhausted {
+            self.state = AlgoState::Completed;
+        }
+        self.order.updated_at = now;
+    }
+}
+
+/// Parent orders and the child -> parent index
+#[derive(Default)]
+pub struct AlgoBook {
+    parents: DashMap<Uuid, ParentOrder>,
+    child_parent: DashMap<Uuid, Uuid>,
+}
+
+impl AlgoBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new parent order
+    pub fn insert(&self, mut order: Order, algo: Algo) -> Uuid {
+        order.status = OrderStatus::Open;
+        let id = order.id;
+        self.parents.insert(
+            id,
+            ParentOrder {
+                order,
+                algo,
+                state: AlgoState::Running,
+                released_quantity: Decimal::ZERO,
+                children: Vec::new(),
+                notional: Decimal::ZERO,
+            },
+        );
+        id
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<ParentOrder> {
+        self.parents.get(&id).map(|p| p.clone())
+    }
+
+    pub fn list(&self) -> Vec<ParentOrder> {
+        self.parents.iter().map(|p| p.clone()).collect()
+    }
+
+    /// Move a parent between running and paused
+    pub fn transition(&self, id: Uuid, from: AlgoState, to: AlgoState) -> Result<(), TradingError> {
+        let mut parent = self
+            .parents
+            .get_mut(&id)
+            .ok_or_else(|| TradingError::OrderNotFound(id.to_string()))?;
+
+        if parent.state != from {
+            return Err(TradingError::InvalidOrder(format!(
+                "Parent order is {:?}, expected {:?}",
+                parent.state, from
+            )));
+        }
+        parent.state = to;
+        Ok(())
+    }
+
+    /// Cancel a parent, returning its open children
+    pub fn cancel(&self, id: Uuid, now: DateTime<Utc>) -> Result<Vec<Uuid>, TradingError> {
+        let mut parent = self
+            .parents
+            .get_mut(&id)
+            .ok_or_else(|| TradingError::OrderNotFound(id.to_string()))?;
+
+        if parent.state.is_final() {
+            return Err(TradingError::InvalidOrder(format!(
+                "Parent order is already {:?}",
+                parent.state
+            )));
+        }
+
+        parent.state = AlgoState::Cancelled;
+        parent.refresh_status(now);
+
+        Ok(parent
+            .children
+            .iter()
+            .filter(|c| c.open)
+            .map(|c| c.order_id)
+            .collect())
+    }
+
+    /// Build the next child order for a running parent
+    ///
+    /// Returns `None` while paused, finished, or (for icebergs) while the
+    /// previous clip is still working.
+    pub fn next_child(&self, id: Uuid, now: DateTime<Utc>) -> Option<Order> {
+        let mut parent = self.parents.get_mut(&id)?;
+
+        if parent.state != AlgoState::Running {
+            return None;
+        }
+        if matches!(parent.algo, Algo::Iceberg { .. }) && parent.has_open_child() {
+            return None;
+        }
+
+        let quantity = parent.next_slice_quantity();
+        if quantity <= Decimal::ZERO {
+            return None;
+        }
+
+        let mut child = parent.order.clone();
+        child.id = Uuid::new_v4();
+        child.client_order_id =
+            format!("{}-{}", parent.order.client_order_id, parent.children.len());
+        child.status = OrderStatus::Pending;
+        child.quantity = quantity;
+        child.filled_quantity = Decimal::ZERO;
+        child.remaining_quantity = quantity;
+        child.avg_fill_price = None;
+        child.created_at = now;
+        child.updated_at = now;
+
+        parent.released_quantity += quantity;
+        parent.children.push(ChildSlice {
+            order_id: child.id,
+            quantity,
+            filled_quantity: Decimal::ZERO,
+            open: true,
+        });
+        self.child_parent.insert(child.id, id);
+
+        Some(child)
+    }
+
+    /// Roll trade fills up into their parents, returning the parents touched
+    pub fn on_trade(&self, trade: &Trade) -> Vec<Uuid> {
+        let mut touched = Vec::new();
+
+        for child_id in [trade.maker_order_id, trade.taker_order_id] {
+            let Some(parent_id) = self.child_parent.get(&child_id).map(|p| *p) else {
+                continue;
+            };
+            if let Some(mut parent) = self.parents.get_mut(&parent_id) {
+                parent.apply_fill(child_id, trade.price, trade.quantity, trade.executed_at);
+                touched.push(parent_id);
+            }
+        }
+
+        touched
+    }
+
+    /// Record the final state of a child after matching
+    ///
+    /// Children that no longer rest in the book are closed. Returns the
+    /// parent id if `order` is a child.
+    pub fn on_child_processed(&self, order: &Order) -> Option<Uuid> {
+        let parent_id = *self.child_parent.get(&order.id)?;
+
+        let resting = matches!(
+            order.status,
+            OrderStatus::Open | OrderStatus::PartiallyFilled
+        );
+        if !resting {
+            if let Some(mut parent) = self.parents.get_mut(&parent_id) {
+                parent.close_child(order.id, order.updated_at);
+            }
+        }
+
+        Some(parent_id)
+    }
+
+    /// Close a child that left the book without filling
+    pub fn on_child_removed(&self, child_id: Uuid, now: DateTime<Utc>) -> Option<Uuid> {
+        let parent_id = *self.child_parent.get(&child_id)?;
+        if let Some(mut parent) = self.parents.get_mut(&parent_id) {
+            parent.close_child(child_id, now);
+        }
+        Some(parent_id)
+    }
+}
+
+/// Schedule child slices for a parent until it completes or is cancelled
+pub async fn run_parent(engine: Arc<MatchingEngine>, parent_id: Uuid) {
+    let Some(parent) = engine.algos().get(parent_id) else {
+        return;
+    };
+
+    let mut ticker = tokio::time::interval(parent.algo.interval());
+
+    loop {
+        ticker.tick().await;
+
+        let Some(parent) = engine.algos().get(parent_id) else {
+            return;
+        };
+        if parent.state.is_final() {
+            info!(parent_id = %parent_id, state = ?parent.state, "Algo parent finished");
+            return;
+        }
+
+        let Some(child) = engine.algos().next_child(parent_id, engine.now()) else {
+            continue;
+        };
+
+        let child_id = child.id;
+        if let Err(e) = engine.submit_order(child, Ingress::Algo).await {
+            warn!(parent_id = %parent_id, "Failed to submit child slice: {}", e);
+            engine.algos().on_child_removed(child_id, engine.now());
+        } else {
+            metrics::counter!("algo_child_orders").increment(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parent_order(quantity: i64) -> Order {
+        Order::builder()
+            .client_order_id("parent")
+            .quantity(quantity)
+            .build()
+    }
+
+    fn fill(child: &Order, price: i64, quantity: i64) -> Trade {
+        Trade {
+            id: Uuid::new_v4(),
+            trade_id: 1,
+            symbol: child.symbol.clone(),
+            maker_order_id: Uuid::new_v4(),
+            maker_user_id: Uuid::new_v4(),
+            taker_order_id: child.id,
+            taker_user_id: child.user_id,
+            price: Decimal::new(price, 0),
+            quantity: Decimal::new(quantity, 0),
+            quote_quantity: Decimal::new(price * quantity, 0),
+            taker_side: child.side,
+            executed_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_iceberg_releases_one_clip_and_rolls_up_fills() {
+        let book = AlgoBook::new();
+        let id = book.insert(
+            parent_order(5),
+            Algo::Iceberg {
+                clip_size: Decimal::new(3, 0),
+            },
+        );
+        let now = Utc::now();
+
+        let first = book.next_child(id, now).unwrap();
+        assert_eq!(first.quantity, Decimal::new(3, 0));
+        // Previous clip still working
+        assert!(book.next_child(id, now).is_none());
+
+        book.on_trade(&fill(&first, 2000, 3));
+        let second = book.next_child(id, now).unwrap();
+        assert_eq!(second.quantity, Decimal::new(2, 0));
+
+        book.on_trade(&fill(&second, 2010, 2));
+        let parent = book.get(id).unwrap();
+        assert_eq!(parent.state, AlgoState::Completed);
+        assert_eq!(parent.order.status, OrderStatus::Filled);
+        assert_eq!(parent.order.avg_fill_price, Some(Decimal::new(2004, 0)));
+        assert!(book.next_child(id, now).is_none());
+    }
+}
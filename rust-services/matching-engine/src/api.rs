@@ -2,16 +2,24 @@
 //!
 //! Exposes REST endpoints for order management and market data
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, Query, State},
+    extract::{ws::WebSocketUpgrade, Path, Query, Request, State},
     http::StatusCode,
-    response::IntoResponse,
+    middleware::{self, Next},
+    response::{
+        sse::{Event as SseEvent, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     routing::{delete, get, post},
     Json, Router,
 };
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use tokio_stream::{wrappers::ReceiverStream, Stream, StreamExt};
 use tower_http::{
     compression::CompressionLayer,
     cors::{Any, CorsLayer},
@@ -19,9 +27,28 @@ use tower_http::{
 };
 use uuid::Uuid;
 
+use crate::algo::{Algo, ParentOrder};
 use crate::config::Config;
-use crate::engine::MatchingEngine;
-use common::{Order, OrderStatus, OrderType, PriceLevel, Side, Symbol, TimeInForce};
+use crate::display::DisplayPrecision;
+use crate::engine::{EngineState, MatchingEngine};
+use crate::fees::FeesSummary;
+use crate::history::HistoryGap;
+use crate::journal;
+use crate::latency::Ingress;
+use crate::orderbook::BookMemory;
+use crate::orders::StatusFilter;
+use crate::protection::Halt;
+use crate::quality::{QualityReport, QualityTracker};
+use crate::risk::RiskLimits;
+use crate::sessions::MarketState;
+use crate::stats::SymbolStats;
+use crate::stream;
+use crate::supervisor::TaskStatus;
+use crate::throttle::ThrottleLimits;
+use common::{
+    AccountBalance, AnyId, IndicativePrice, Order, OrderStatus, OrderType, PriceLevel,
+    SelfTradePrevention, Side, Symbol, SymbolConfig, TimeInForce, TradingError, TriggerSource,
+};
 
 type AppState = Arc<MatchingEngine>;
 
@@ -33,10 +60,36 @@ pub async fn run_server(engine: Arc<MatchingEngine>, config: &Config) -> anyhow:
         .route("/info", get(info))
         // Orders
         .route("/orders", post(submit_order))
-        .route("/orders/:order_id", delete(cancel_order))
+        .route("/orders/batch", post(submit_orders).delete(cancel_orders))
+        .route(
+            "/orders/:order_id",
+            get(get_order).put(replace_order).delete(cancel_order),
+        )
+        .route("/users/:user_id/orders", get(list_user_orders))
+        .route("/users/:user_id/balances", get(list_user_balances))
+        // Execution Algos
+        .route(
+            "/algo-orders",
+            post(submit_algo_order).get(list_algo_orders),
+        )
+        .route(
+            "/algo-orders/:parent_id",
+            get(get_algo_order).delete(cancel_algo_order),
+        )
+        .route("/algo-orders/:parent_id/pause", post(pause_algo_order))
+        .route("/algo-orders/:parent_id/resume", post(resume_algo_order))
         // Market Data
         .route("/orderbook/:symbol", get(get_orderbook))
         .route("/symbols", get(get_symbols))
+        .route("/trades/:symbol", get(get_trade_history))
+        .route("/trades/:symbol/replay", get(replay_trades))
+        .route("/stats/:symbol/quality", get(get_market_quality))
+        .route("/auction/:symbol", get(get_indicative_price))
+        .route("/market-state", get(get_market_states))
+        .route("/fees", get(get_fees))
+        .route("/ws", get(market_data_stream))
+        // Admin
+        .nest("/admin", admin_routes(config.admin_token.clone()))
         // State
         .with_state(engine)
         // Middleware
@@ -66,12 +119,32 @@ pub struct SubmitOrderRequest {
     pub symbol: String,
     pub side: Side,
     pub order_type: OrderType,
-    pub quantity: String,
-    pub price: Option<String>,
+    #[serde(with = "common::decimal::flex")]
+    pub quantity: Decimal,
+    #[serde(default, with = "common::decimal::flex_option")]
+    pub price: Option<Decimal>,
+    /// Trigger price of stop orders
+    #[serde(default, with = "common::decimal::flex_option")]
+    pub stop_price: Option<Decimal>,
+    /// Stop trigger reference; defaults to the symbol's
+    pub trigger_source: Option<TriggerSource>,
     pub time_in_force: Option<TimeInForce>,
+    pub expire_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Iceberg slice shown on the book
+    #[serde(default, with = "common::decimal::flex_option")]
+    pub display_quantity: Option<Decimal>,
+    /// Defaults to cancelling the resting order
+    pub self_trade_prevention: Option<SelfTradePrevention>,
     pub user_id: Uuid,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SubmitAlgoOrderRequest {
+    #[serde(flatten)]
+    pub order: SubmitOrderRequest,
+    pub algo: Algo,
+}
+
 #[derive(Debug, Serialize)]
 pub struct OrderResponse {
     pub id: Uuid,
@@ -82,27 +155,71 @@ pub struct OrderResponse {
     pub status: OrderStatus,
     pub quantity: String,
     pub price: Option<String>,
+    pub stop_price: Option<String>,
+    pub trigger_source: Option<TriggerSource>,
     pub filled_quantity: String,
     pub remaining_quantity: String,
 }
 
+impl OrderResponse {
+    fn from_order(order: Order, precision: &DisplayPrecision) -> Self {
+        Self {
+            id: order.id,
+            client_order_id: order.client_order_id,
+            symbol: order.symbol.to_string(),
+            side: order.side,
+            order_type: order.order_type,
+            status: order.status,
+            quantity: precision.quantity(order.quantity).to_string(),
+            price: order.price.map(|p| precision.price(p).to_string()),
+            stop_price: order.stop_price.map(|p| precision.price(p).to_string()),
+            trigger_source: order.trigger_source,
+            filled_quantity: precision.quantity(order.filled_quantity).to_string(),
+            remaining_quantity: precision.quantity(order.remaining_quantity).to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UserOrdersQuery {
+    #[serde(default)]
+    pub status: StatusFilter,
+    /// Skip rounding to the symbol's display precision
+    #[serde(default)]
+    pub raw: bool,
+}
+
 #[derive(Debug, Serialize)]
 pub struct OrderBookResponse {
     pub symbol: String,
     pub bids: Vec<PriceLevel>,
     pub asks: Vec<PriceLevel>,
+    /// Book sequence the depth is as of, for syncing with the delta streams
     pub sequence: u64,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct OrderBookQuery {
     pub levels: Option<usize>,
+    /// Skip rounding to the symbol's display precision
+    #[serde(default)]
+    pub raw: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DisplayQuery {
+    /// Skip rounding to the symbol's display precision
+    #[serde(default)]
+    pub raw: bool,
 }
 
 #[derive(Debug, Serialize)]
 pub struct HealthResponse {
     pub status: &'static str,
+    pub state: EngineState,
     pub version: &'static str,
+    /// Supervised background tasks, by name
+    pub tasks: BTreeMap<&'static str, TaskStatus>,
 }
 
 #[derive(Debug, Serialize)]
@@ -110,6 +227,38 @@ pub struct InfoResponse {
     pub name: &'static str,
     pub version: &'static str,
     pub symbols: Vec<String>,
+    /// Order and trade counts by symbol since the engine started
+    pub stats: BTreeMap<String, SymbolStats>,
+    /// New orders are rejected until resting orders drain
+    pub cancel_only: bool,
+    /// Approximate memory held by each in-memory book
+    pub memory: BTreeMap<String, BookMemory>,
+}
+
+/// Outcome of one item of a batch request, in request order
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BatchItemResult<T> {
+    Accepted(T),
+    Rejected(ApiError),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchCancelRequest {
+    #[serde(with = "common::ids::flex")]
+    pub order_id: Uuid,
+    pub symbol: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchCancelQuery {
+    /// Requesting user, for cancel throttling
+    pub user_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CancelAccepted {
+    pub order_id: Uuid,
 }
 
 #[derive(Debug, Serialize)]
@@ -118,18 +267,47 @@ pub struct ApiError {
     pub code: String,
 }
 
+impl ApiError {
+    fn status(&self) -> StatusCode {
+        match self.code.as_str() {
+            "ENGINE_NOT_READY" | "CANCEL_ONLY" | "HISTORY_INCOMPLETE" => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            "ORDER_NOT_FOUND" | "OVERRIDE_NOT_FOUND" | "REPLAY_DISABLED" | "HISTORY_DISABLED"
+            | "ACCOUNTS_DISABLED" | "SYMBOL_NOT_FOUND" | "SYMBOL_NOT_HALTED" => {
+                StatusCode::NOT_FOUND
+            }
+            "SYMBOL_EXISTS" | "SYMBOL_HALTED" | "MARKET_CLOSED" => StatusCode::CONFLICT,
+            "RATE_LIMITED" => StatusCode::TOO_MANY_REQUESTS,
+            "UNAUTHORIZED" => StatusCode::UNAUTHORIZED,
+            "ADMIN_DISABLED" => StatusCode::FORBIDDEN,
+            "PERSISTENCE_FAILED" | "HISTORY_UNAVAILABLE" => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
 impl IntoResponse for ApiError {
     fn into_response(self) -> axum::response::Response {
-        (StatusCode::BAD_REQUEST, Json(self)).into_response()
+        (self.status(), Json(self)).into_response()
     }
 }
 
 // ============== Handlers ==============
 
-async fn health_check() -> Json<HealthResponse> {
+async fn health_check(State(engine): State<AppState>) -> Json<HealthResponse> {
+    let state = engine.state();
+    let supervisor = engine.supervisor();
+
     Json(HealthResponse {
-        status: "healthy",
+        status: match state {
+            EngineState::Recovering => "recovering",
+            EngineState::Ready if supervisor.is_degraded() => "degraded",
+            EngineState::Ready => "healthy",
+        },
+        state,
         version: env!("CARGO_PKG_VERSION"),
+        tasks: supervisor.tasks(),
     })
 }
 
@@ -138,42 +316,289 @@ async fn info(State(engine): State<AppState>) -> Json<InfoResponse> {
         name: "FastTrading Matching Engine",
         version: env!("CARGO_PKG_VERSION"),
         symbols: engine.symbols().iter().map(|s| s.to_string()).collect(),
+        stats: engine.matching_stats(),
+        cancel_only: engine.is_cancel_only(),
+        memory: engine.book_memory(),
     })
 }
 
 async fn submit_order(
     State(engine): State<AppState>,
+    Query(display): Query<DisplayQuery>,
     Json(req): Json<SubmitOrderRequest>,
 ) -> Result<Json<OrderResponse>, ApiError> {
-    use chrono::Utc;
-    use rust_decimal::Decimal;
-    use std::str::FromStr;
+    if !engine.is_ready() {
+        return Err(ApiError {
+            error: "Engine is recovering and not accepting orders".to_string(),
+            code: "ENGINE_NOT_READY".to_string(),
+        });
+    }
+
+    if engine.is_cancel_only() {
+        return Err(ApiError {
+            error: "Engine is cancel-only until resting orders drain".to_string(),
+            code: "CANCEL_ONLY".to_string(),
+        });
+    }
+
+    let order = build_order(req)?;
+    engine.validate_order(&order).map_err(validation_error)?;
+
+    // Submit to engine
+    engine
+        .submit_order(order.clone(), Ingress::Api)
+        .await
+        .map_err(|e| submit_error(e, "SUBMIT_FAILED"))?;
+
+    let precision = engine.display_precision(&order.symbol, display.raw);
+    Ok(Json(OrderResponse::from_order(order, &precision)))
+}
+
+/// Submit up to `max_batch_size` orders as one engine command
+///
+/// Each order is validated and throttled on its own and rejected in
+/// place; the rest are applied back to back.
+async fn submit_orders(
+    State(engine): State<AppState>,
+    Query(display): Query<DisplayQuery>,
+    Json(reqs): Json<Vec<SubmitOrderRequest>>,
+) -> Result<Json<Vec<BatchItemResult<OrderResponse>>>, ApiError> {
+    if !engine.is_ready() {
+        return Err(ApiError {
+            error: "Engine is recovering and not accepting orders".to_string(),
+            code: "ENGINE_NOT_READY".to_string(),
+        });
+    }
+
+    if engine.is_cancel_only() {
+        return Err(ApiError {
+            error: "Engine is cancel-only until resting orders drain".to_string(),
+            code: "CANCEL_ONLY".to_string(),
+        });
+    }
+    check_batch_size(&engine, reqs.len())?;
+
+    let built: Vec<Result<Order, ApiError>> = reqs
+        .into_iter()
+        .map(|req| {
+            let order = build_order(req)?;
+            engine.validate_order(&order).map_err(validation_error)?;
+            Ok(order)
+        })
+        .collect();
+    let orders = built
+        .iter()
+        .filter_map(|b| b.as_ref().ok().cloned())
+        .collect();
+    let mut admitted = engine
+        .submit_orders(orders, Ingress::Api)
+        .await
+        .map_err(|e| submit_error(e, "SUBMIT_FAILED"))?
+        .into_iter();
+
+    let mut results = Vec::with_capacity(built.len());
+    for built in built {
+        results.push(match built {
+            Err(e) => BatchItemResult::Rejected(e),
+            Ok(order) => match admitted.next().expect("one admission per submitted order") {
+                Ok(()) => {
+                    let precision = engine.display_precision(&order.symbol, display.raw);
+                    BatchItemResult::Accepted(OrderResponse::from_order(order, &precision))
+                }
+                Err(e) => BatchItemResult::Rejected(submit_error(e, "SUBMIT_FAILED")),
+            },
+        });
+    }
+    Ok(Json(results))
+}
+
+/// Cancel up to `max_batch_size` orders as one engine command
+async fn cancel_orders(
+    State(engine): State<AppState>,
+    Query(params): Query<BatchCancelQuery>,
+    Json(reqs): Json<Vec<BatchCancelRequest>>,
+) -> Result<Json<Vec<BatchItemResult<CancelAccepted>>>, ApiError> {
+    check_batch_size(&engine, reqs.len())?;
+
+    let parsed: Vec<Result<(Uuid, Symbol), ApiError>> = reqs
+        .into_iter()
+        .map(|req| Ok((req.order_id, parse_symbol(&req.symbol)?)))
+        .collect();
+    let cancels = parsed
+        .iter()
+        .filter_map(|p| p.as_ref().ok().cloned())
+        .collect();
+    let mut admitted = engine
+        .cancel_orders(cancels, params.user_id)
+        .await
+        .map_err(|e| submit_error(e, "CANCEL_FAILED"))?
+        .into_iter();
+
+    let mut results = Vec::with_capacity(parsed.len());
+    for parsed in parsed {
+        results.push(match parsed {
+            Err(e) => BatchItemResult::Rejected(e),
+            Ok((order_id, _)) => match admitted.next().expect("one admission per cancel") {
+                Ok(()) => BatchItemResult::Accepted(CancelAccepted { order_id }),
+                Err(e) => BatchItemResult::Rejected(submit_error(e, "CANCEL_FAILED")),
+            },
+        });
+    }
+    Ok(Json(results))
+}
+
+fn check_batch_size(engine: &MatchingEngine, len: usize) -> Result<(), ApiError> {
+    let max = engine.max_batch_size();
+    if len == 0 || len > max {
+        return Err(ApiError {
+            error: format!("A batch holds 1 to {max} items, got {len}"),
+            code: "INVALID_BATCH".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Cancel/replace a resting order
+///
+/// The body describes the replacement in full. Reducing only the quantity
+/// keeps the original's time priority; the replacement gets a new id.
+async fn replace_order(
+    State(engine): State<AppState>,
+    Path(AnyId(order_id)): Path<AnyId>,
+    Query(display): Query<DisplayQuery>,
+    Json(req): Json<SubmitOrderRequest>,
+) -> Result<Json<OrderResponse>, ApiError> {
+    if !engine.is_ready() {
+        return Err(ApiError {
+            error: "Engine is recovering and not accepting orders".to_string(),
+            code: "ENGINE_NOT_READY".to_string(),
+        });
+    }
 
-    // Parse quantity
-    let quantity = Decimal::from_str(&req.quantity).map_err(|_| ApiError {
-        error: "Invalid quantity".to_string(),
-        code: "INVALID_QUANTITY".to_string(),
+    let replacement = build_order(req)?;
+    if replacement.order_type != OrderType::Limit {
+        return Err(ApiError {
+            error: "Only limit orders can be replaced".to_string(),
+            code: "INVALID_ORDER".to_string(),
+        });
+    }
+    engine
+        .validate_order(&replacement)
+        .map_err(validation_error)?;
+
+    let resting = engine
+        .order_book(&replacement.symbol.to_string())
+        .and_then(|book| book.resting_owner(order_id));
+    let Some((user_id, side)) = resting else {
+        return Err(ApiError {
+            error: format!("Order {order_id} is not resting on the book"),
+            code: "ORDER_NOT_FOUND".to_string(),
+        });
+    };
+    if user_id != replacement.user_id || side != replacement.side {
+        return Err(ApiError {
+            error: format!("Replacement must keep the user and side of order {order_id}"),
+            code: "INVALID_ORDER".to_string(),
+        });
+    }
+
+    engine
+        .replace_order(order_id, replacement.clone(), Ingress::Api)
+        .await
+        .map_err(|e| submit_error(e, "REPLACE_FAILED"))?;
+
+    let precision = engine.display_precision(&replacement.symbol, display.raw);
+    Ok(Json(OrderResponse::from_order(replacement, &precision)))
+}
+
+/// Latest known state of an order
+async fn get_order(
+    State(engine): State<AppState>,
+    Path(AnyId(order_id)): Path<AnyId>,
+    Query(display): Query<DisplayQuery>,
+) -> Result<Json<OrderResponse>, ApiError> {
+    let order = engine.order(order_id).ok_or_else(|| ApiError {
+        error: format!("Order {order_id} not found"),
+        code: "ORDER_NOT_FOUND".to_string(),
     })?;
 
-    // Parse price
-    let price = req
-        .price
-        .as_ref()
-        .map(|p| Decimal::from_str(p))
-        .transpose()
-        .map_err(|_| ApiError {
-            error: "Invalid price".to_string(),
-            code: "INVALID_PRICE".to_string(),
-        })?;
+    let precision = engine.display_precision(&order.symbol, display.raw);
+    Ok(Json(OrderResponse::from_order(order, &precision)))
+}
+
+/// A user's orders, newest first, optionally only open or closed ones
+async fn list_user_orders(
+    State(engine): State<AppState>,
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<UserOrdersQuery>,
+) -> Json<Vec<OrderResponse>> {
+    Json(
+        engine
+            .user_orders(user_id, query.status)
+            .into_iter()
+            .map(|order| {
+                let precision = engine.display_precision(&order.symbol, query.raw);
+                OrderResponse::from_order(order, &precision)
+            })
+            .collect(),
+    )
+}
+
+/// A user's balances by asset
+async fn list_user_balances(
+    State(engine): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<Vec<AccountBalance>>, ApiError> {
+    if !engine.accounts_enabled() {
+        return Err(accounts_disabled());
+    }
+    Ok(Json(engine.user_balances(user_id)))
+}
+
+fn accounts_disabled() -> ApiError {
+    ApiError {
+        error: "Accounts are disabled".to_string(),
+        code: "ACCOUNTS_DISABLED".to_string(),
+    }
+}
+
+/// Validate a submit request and build the engine order
+fn build_order(req: SubmitOrderRequest) -> Result<Order, ApiError> {
+    use chrono::Utc;
+
+    let SubmitOrderRequest {
+        quantity,
+        price,
+        stop_price,
+        display_quantity,
+        ..
+    } = req;
 
     // Validate limit order has price
-    if req.order_type == OrderType::Limit && price.is_none() {
+    if matches!(req.order_type, OrderType::Limit | OrderType::StopLimit) && price.is_none() {
         return Err(ApiError {
             error: "Limit order requires price".to_string(),
             code: "PRICE_REQUIRED".to_string(),
         });
     }
 
+    // Validate stop order has trigger price
+    let is_stop = matches!(req.order_type, OrderType::StopLimit | OrderType::StopMarket);
+    if is_stop && stop_price.is_none() {
+        return Err(ApiError {
+            error: "Stop order requires stop_price".to_string(),
+            code: "STOP_PRICE_REQUIRED".to_string(),
+        });
+    }
+
+    // Validate GTD order has expiry
+    if req.time_in_force == Some(TimeInForce::GTD) && req.expire_at.is_none() {
+        return Err(ApiError {
+            error: "GTD order requires expire_at".to_string(),
+            code: "EXPIRY_REQUIRED".to_string(),
+        });
+    }
+
     // Parse symbol
     let parts: Vec<&str> = req.symbol.split('-').collect();
     if parts.len() != 2 {
@@ -197,7 +622,8 @@ async fn submit_order(
         time_in_force: req.time_in_force.unwrap_or(TimeInForce::GTC),
         status: OrderStatus::Pending,
         price,
-        stop_price: None,
+        stop_price: stop_price.filter(|_| is_stop),
+        trigger_source: req.trigger_source.filter(|_| is_stop),
         quantity,
         filled_quantity: Decimal::ZERO,
         remaining_quantity: quantity,
@@ -205,34 +631,17 @@ async fn submit_order(
         sequence: 0,
         created_at: Utc::now(),
         updated_at: Utc::now(),
+        expire_at: req.expire_at,
+        display_quantity,
+        self_trade_prevention: req.self_trade_prevention.unwrap_or_default(),
     };
 
-    // Submit to engine
-    engine
-        .submit_order(order.clone())
-        .await
-        .map_err(|e| ApiError {
-            error: e.to_string(),
-            code: "SUBMIT_FAILED".to_string(),
-        })?;
-
-    Ok(Json(OrderResponse {
-        id: order.id,
-        client_order_id: order.client_order_id,
-        symbol: order.symbol.to_string(),
-        side: order.side,
-        order_type: order.order_type,
-        status: OrderStatus::Pending,
-        quantity: order.quantity.to_string(),
-        price: order.price.map(|p| p.to_string()),
-        filled_quantity: "0".to_string(),
-        remaining_quantity: order.quantity.to_string(),
-    }))
+    Ok(order)
 }
 
 async fn cancel_order(
     State(engine): State<AppState>,
-    Path(order_id): Path<Uuid>,
+    Path(AnyId(order_id)): Path<AnyId>,
     Query(params): Query<CancelQuery>,
 ) -> Result<StatusCode, ApiError> {
     let symbol = Symbol::new(
@@ -241,20 +650,39 @@ async fn cancel_order(
     );
 
     engine
-        .cancel_order(order_id, symbol)
+        .cancel_order(order_id, symbol, params.user_id)
         .await
-        .map_err(|e| ApiError {
-            error: e.to_string(),
-            code: "CANCEL_FAILED".to_string(),
-        })?;
+        .map_err(|e| submit_error(e, "CANCEL_FAILED"))?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Map an engine submission error, surfacing throttling as its own code
+fn submit_error(e: anyhow::Error, code: &str) -> ApiError {
+    let code = match e.downcast_ref::<TradingError>() {
+        Some(TradingError::RateLimitExceeded) => "RATE_LIMITED",
+        _ => code,
+    };
+    ApiError {
+        error: e.to_string(),
+        code: code.to_string(),
+    }
+}
+
+/// Rejection of an order failing validation, under the error's own code
+fn validation_error(e: TradingError) -> ApiError {
+    ApiError {
+        error: e.to_string(),
+        code: e.code().to_string(),
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CancelQuery {
     pub base: Option<String>,
     pub quote: Option<String>,
+    /// Requesting user, for cancel throttling
+    pub user_id: Option<Uuid>,
 }
 
 async fn get_orderbook(
@@ -273,19 +701,756 @@ async fn get_orderbook(
     let sym = Symbol::new(parts[0], parts[1]);
     let levels = query.levels.unwrap_or(20);
 
-    let (bids, asks) = engine.get_depth(&sym, levels).map_err(|e| ApiError {
+    let (bids, asks, sequence) = engine.get_depth(&sym, levels).map_err(|e| ApiError {
         error: e.to_string(),
         code: "SYMBOL_NOT_FOUND".to_string(),
     })?;
 
+    let precision = engine.display_precision(&sym, query.raw);
     Ok(Json(OrderBookResponse {
         symbol,
-        bids,
-        asks,
-        sequence: 0, // TODO: get from order book
+        bids: bids.into_iter().map(|l| precision.level(l)).collect(),
+        asks: asks.into_iter().map(|l| precision.level(l)).collect(),
+        sequence,
     }))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct StreamQuery {
+    /// Comma-separated symbols to subscribe to on connect
+    pub symbols: Option<String>,
+}
+
+/// WebSocket stream of book updates, trades and BBO changes
+async fn market_data_stream(
+    State(engine): State<AppState>,
+    Query(query): Query<StreamQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let symbols: Vec<String> = query
+        .symbols
+        .map(|s| s.split(',').map(|s| s.trim().to_string()).collect())
+        .unwrap_or_default();
+
+    ws.on_upgrade(move |socket| async move {
+        stream::serve_client(socket, &engine, symbols).await;
+    })
+}
+
 async fn get_symbols(State(engine): State<AppState>) -> Json<Vec<String>> {
     Json(engine.symbols().iter().map(|s| s.to_string()).collect())
 }
+
+/// Price improvement, spreads and fill rates of the current and last windows
+async fn get_market_quality(
+    State(engine): State<AppState>,
+    Path(symbol): Path<String>,
+) -> Result<Json<QualityReport>, ApiError> {
+    let (base, quote) = symbol.split_once('-').ok_or_else(|| ApiError {
+        error: "Invalid symbol format".to_string(),
+        code: "INVALID_SYMBOL".to_string(),
+    })?;
+
+    let sym = Symbol::new(base, quote);
+    if !engine.symbols().contains(&sym) {
+        return Err(ApiError {
+            error: format!("Unknown symbol: {symbol}"),
+            code: "SYMBOL_NOT_FOUND".to_string(),
+        });
+    }
+
+    let report = engine
+        .market_quality(&sym)
+        .unwrap_or_else(|| QualityReport {
+            current: QualityTracker::empty(&sym, engine.now()),
+            previous: None,
+        });
+    Ok(Json(report))
+}
+
+/// Indicative uncross price and imbalance of a halted or pre-open
+/// symbol's auction
+async fn get_indicative_price(
+    State(engine): State<AppState>,
+    Path(symbol): Path<String>,
+) -> Result<Json<IndicativePrice>, ApiError> {
+    let (base, quote) = symbol.split_once('-').ok_or_else(|| ApiError {
+        error: "Invalid symbol format".to_string(),
+        code: "INVALID_SYMBOL".to_string(),
+    })?;
+
+    let sym = Symbol::new(base, quote);
+    if !engine.symbols().contains(&sym) {
+        return Err(ApiError {
+            error: format!("Unknown symbol: {symbol}"),
+            code: "SYMBOL_NOT_FOUND".to_string(),
+        });
+    }
+
+    engine
+        .indicative_price(&sym)
+        .map(Json)
+        .ok_or_else(|| ApiError {
+            error: format!("{symbol} has no auction in progress"),
+            code: "SYMBOL_NOT_HALTED".to_string(),
+        })
+}
+
+/// Market state of every listed symbol: pre-open, open, halted or closed
+async fn get_market_states(State(engine): State<AppState>) -> Json<BTreeMap<String, MarketState>> {
+    Json(engine.market_states())
+}
+
+/// Fee house account balances and the current accrual window
+async fn get_fees(State(engine): State<AppState>) -> Json<FeesSummary> {
+    Json(engine.fees_summary())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Defaults to and is capped at `MAX_HISTORY_TRADES`
+    pub limit: Option<usize>,
+    /// Skip rounding to the symbol's display precision
+    #[serde(default)]
+    pub raw: bool,
+}
+
+/// Upper bound on trades returned by one history query
+const MAX_HISTORY_TRADES: usize = 1000;
+
+/// Persisted trades executed in `[from, to)`, oldest first
+async fn get_trade_history(
+    State(engine): State<AppState>,
+    Path(symbol): Path<String>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<Vec<common::Trade>>, ApiError> {
+    let history = engine.history().ok_or_else(|| ApiError {
+        error: "Trade history is not enabled".to_string(),
+        code: "HISTORY_DISABLED".to_string(),
+    })?;
+
+    let (base, quote) = symbol.split_once('-').ok_or_else(|| ApiError {
+        error: "Invalid symbol format".to_string(),
+        code: "INVALID_SYMBOL".to_string(),
+    })?;
+
+    let sym = Symbol::new(base, quote);
+    let limit = query
+        .limit
+        .unwrap_or(MAX_HISTORY_TRADES)
+        .min(MAX_HISTORY_TRADES);
+    let trades = history
+        .trades(&sym, query.from, query.to, limit)
+        .await
+        .map_err(|e| match e.downcast_ref::<HistoryGap>() {
+            Some(gap) => ApiError {
+                error: format!("Trade history {gap}"),
+                code: "HISTORY_INCOMPLETE".to_string(),
+            },
+            None => {
+                tracing::error!(symbol = %sym, error = %e, "Trade history query failed");
+                ApiError {
+                    error: "Trade history is unavailable".to_string(),
+                    code: "HISTORY_UNAVAILABLE".to_string(),
+                }
+            }
+        })?;
+
+    let precision = engine.display_precision(&sym, query.raw);
+    let trades = trades.into_iter().map(|t| precision.trade(t)).collect();
+    Ok(Json(trades))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplayQuery {
+    /// Defaults to the start of the journal (today)
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Playback rate, e.g. `10x`
+    pub speed: Option<String>,
+    /// Skip rounding to the symbol's display precision
+    #[serde(default)]
+    pub raw: bool,
+}
+
+/// Stream today's journaled trades as server-sent events
+async fn replay_trades(
+    State(engine): State<AppState>,
+    Path(symbol): Path<String>,
+    Query(query): Query<ReplayQuery>,
+) -> Result<Sse<impl Stream<Item = Result<SseEvent, axum::Error>>>, ApiError> {
+    let journal = engine.journal().ok_or_else(|| ApiError {
+        error: "Trade replay is not enabled".to_string(),
+        code: "REPLAY_DISABLED".to_string(),
+    })?;
+
+    let (base, quote) = symbol.split_once('-').ok_or_else(|| ApiError {
+        error: "Invalid symbol format".to_string(),
+        code: "INVALID_SYMBOL".to_string(),
+    })?;
+
+    let speed = match query.speed.as_deref() {
+        Some(speed) => journal::parse_speed(speed).ok_or_else(|| ApiError {
+            error: format!("Invalid replay speed: {speed}"),
+            code: "INVALID_SPEED".to_string(),
+        })?,
+        None => 1.0,
+    };
+
+    let sym = Symbol::new(base, quote);
+    let precision = engine.display_precision(&sym, query.raw);
+    let trades = journal.range(&sym, query.from, query.to);
+    let (tx, rx) = tokio::sync::mpsc::channel(64);
+    tokio::spawn(journal::replay(trades, speed, tx));
+
+    let events = ReceiverStream::new(rx)
+        .map(move |trade| {
+            SseEvent::default()
+                .event("trade")
+                .json_data(precision.trade(trade))
+        })
+        .chain(tokio_stream::once(Ok(SseEvent::default()
+            .event("end")
+            .data("replay complete"))));
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+async fn submit_algo_order(
+    State(engine): State<AppState>,
+    Json(req): Json<SubmitAlgoOrderRequest>,
+) -> Result<Json<ParentOrder>, ApiError> {
+    if !engine.is_ready() {
+        return Err(ApiError {
+            error: "Engine is recovering and not accepting orders".to_string(),
+            code: "ENGINE_NOT_READY".to_string(),
+        });
+    }
+
+    let order = build_order(req.order)?;
+    engine.validate_order(&order).map_err(validation_error)?;
+
+    let parent_id = engine.submit_algo(order, req.algo).map_err(|e| ApiError {
+        error: e.to_string(),
+        code: "SUBMIT_FAILED".to_string(),
+    })?;
+
+    get_algo_order(State(engine), Path(parent_id)).await
+}
+
+async fn list_algo_orders(State(engine): State<AppState>) -> Json<Vec<ParentOrder>> {
+    Json(engine.algos().list())
+}
+
+async fn get_algo_order(
+    State(engine): State<AppState>,
+    Path(AnyId(parent_id)): Path<AnyId>,
+) -> Result<Json<ParentOrder>, ApiError> {
+    engine
+        .algos()
+        .get(parent_id)
+        .map(Json)
+        .ok_or_else(|| ApiError {
+            error: "Parent order not found".to_string(),
+            code: "ORDER_NOT_FOUND".to_string(),
+        })
+}
+
+async fn pause_algo_order(
+    State(engine): State<AppState>,
+    Path(AnyId(parent_id)): Path<AnyId>,
+) -> Result<Json<ParentOrder>, ApiError> {
+    engine.pause_algo(parent_id).map_err(|e| ApiError {
+        error: e.to_string(),
+        code: "PAUSE_FAILED".to_string(),
+    })?;
+    get_algo_order(State(engine), Path(parent_id)).await
+}
+
+async fn resume_algo_order(
+    State(engine): State<AppState>,
+    Path(AnyId(parent_id)): Path<AnyId>,
+) -> Result<Json<ParentOrder>, ApiError> {
+    engine.resume_algo(parent_id).map_err(|e| ApiError {
+        error: e.to_string(),
+        code: "RESUME_FAILED".to_string(),
+    })?;
+    get_algo_order(State(engine), Path(parent_id)).await
+}
+
+async fn cancel_algo_order(
+    State(engine): State<AppState>,
+    Path(AnyId(parent_id)): Path<AnyId>,
+) -> Result<Json<ParentOrder>, ApiError> {
+    engine.cancel_algo(parent_id).await.map_err(|e| ApiError {
+        error: e.to_string(),
+        code: "CANCEL_FAILED".to_string(),
+    })?;
+    get_algo_order(State(engine), Path(parent_id)).await
+}
+
+// ============== Admin ==============
+
+fn admin_routes(admin_token: Option<String>) -> Router<AppState> {
+    Router::new()
+        .route("/throttles", get(list_throttles))
+        .route(
+            "/throttles/:user_id",
+            get(get_throttle).put(set_throttle).delete(delete_throttle),
+        )
+        .route("/users/:user_id/deposit", post(deposit))
+        .route("/users/:user_id/withdraw", post(withdraw))
+        .route("/risk-limits", get(list_risk_limits))
+        .route(
+            "/risk-limits/:user_id",
+            get(get_risk_limits)
+                .put(set_risk_limits)
+                .delete(delete_risk_limits),
+        )
+        .route("/symbols", post(add_symbol))
+        .route("/symbols/:symbol", delete(delist_symbol))
+        .route("/symbols/:symbol/halt", post(halt_symbol))
+        .route("/symbols/:symbol/resume", post(resume_symbol))
+        .route("/halts", get(list_halts))
+        .route_layer(middleware::from_fn_with_state(admin_token, require_admin))
+}
+
+async fn require_admin(
+    State(admin_token): State<Option<String>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let Some(expected) = admin_token else {
+        return Err(ApiError {
+            error: "Admin API is disabled".to_string(),
+            code: "ADMIN_DISABLED".to_string(),
+        });
+    };
+
+    let provided = request
+        .headers()
+        .get("x-admin-token")
+        .and_then(|v| v.to_str().ok());
+    if provided != Some(expected.as_str()) {
+        return Err(ApiError {
+            error: "Invalid admin token".to_string(),
+            code: "UNAUTHORIZED".to_string(),
+        });
+    }
+
+    Ok(next.run(request).await)
+}
+
+#[derive(Debug, Serialize)]
+pub struct ThrottleOverride {
+    pub user_id: Uuid,
+    pub limits: ThrottleLimits,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ThrottlesResponse {
+    pub defaults: ThrottleLimits,
+    pub overrides: Vec<ThrottleOverride>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserThrottleResponse {
+    pub user_id: Uuid,
+    pub limits: ThrottleLimits,
+    /// Whether `limits` is a per-user override rather than the defaults
+    pub overridden: bool,
+}
+
+async fn list_throttles(State(engine): State<AppState>) -> Json<ThrottlesResponse> {
+    let throttles = engine.throttles();
+    Json(ThrottlesResponse {
+        defaults: throttles.defaults(),
+        overrides: throttles
+            .overrides()
+            .into_iter()
+            .map(|(user_id, limits)| ThrottleOverride { user_id, limits })
+            .collect(),
+    })
+}
+
+async fn get_throttle(
+    State(engine): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> Json<UserThrottleResponse> {
+    let throttles = engine.throttles();
+    Json(UserThrottleResponse {
+        user_id,
+        limits: throttles.limits(user_id),
+        overridden: throttles.is_overridden(user_id),
+    })
+}
+
+async fn set_throttle(
+    State(engine): State<AppState>,
+    Path(user_id): Path<Uuid>,
+    Json(limits): Json<ThrottleLimits>,
+) -> Result<Json<UserThrottleResponse>, ApiError> {
+    engine
+        .throttles()
+        .set_override(user_id, limits)
+        .await
+        .map_err(|e| ApiError {
+            error: e.to_string(),
+            code: "PERSISTENCE_FAILED".to_string(),
+        })?;
+
+    tracing::info!(user_id = %user_id, ?limits, "User throttle override set");
+
+    Ok(Json(UserThrottleResponse {
+        user_id,
+        limits,
+        overridden: true,
+    }))
+}
+
+async fn delete_throttle(
+    State(engine): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let removed = engine
+        .throttles()
+        .remove_override(user_id)
+        .await
+        .map_err(|e| ApiError {
+            error: e.to_string(),
+            code: "PERSISTENCE_FAILED".to_string(),
+        })?;
+
+    if !removed {
+        return Err(ApiError {
+            error: "No throttle override for user".to_string(),
+            code: "OVERRIDE_NOT_FOUND".to_string(),
+        });
+    }
+
+    tracing::info!(user_id = %user_id, "User throttle override removed");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Serialize)]
+pub struct RiskLimitsOverride {
+    pub user_id: Uuid,
+    pub limits: RiskLimits,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RiskLimitsResponse {
+    pub defaults: RiskLimits,
+    pub overrides: Vec<RiskLimitsOverride>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserRiskLimitsResponse {
+    pub user_id: Uuid,
+    pub limits: RiskLimits,
+    /// Whether `limits` is a per-user override rather than the defaults
+    pub overridden: bool,
+}
+
+async fn list_risk_limits(State(engine): State<AppState>) -> Json<RiskLimitsResponse> {
+    let risk_limits = engine.risk_limits();
+    Json(RiskLimitsResponse {
+        defaults: risk_limits.defaults(),
+        overrides: risk_limits
+            .overrides()
+            .into_iter()
+            .map(|(user_id, limits)| RiskLimitsOverride { user_id, limits })
+            .collect(),
+    })
+}
+
+async fn get_risk_limits(
+    State(engine): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> Json<UserRiskLimitsResponse> {
+    let risk_limits = engine.risk_limits();
+    Json(UserRiskLimitsResponse {
+        user_id,
+        limits: risk_limits.limits(user_id),
+        overridden: risk_limits.is_overridden(user_id),
+    })
+}
+
+async fn set_risk_limits(
+    State(engine): State<AppState>,
+    Path(user_id): Path<Uuid>,
+    Json(limits): Json<RiskLimits>,
+) -> Result<Json<UserRiskLimitsResponse>, ApiError> {
+    if limits.max_order_quantity < Decimal::ZERO || limits.max_notional_exposure < Decimal::ZERO {
+        return Err(ApiError {
+            error: "Risk limits must not be negative".to_string(),
+            code: "INVALID_LIMITS".to_string(),
+        });
+    }
+
+    engine
+        .risk_limits()
+        .set_override(user_id, limits)
+        .await
+        .map_err(|e| ApiError {
+            error: e.to_string(),
+            code: "PERSISTENCE_FAILED".to_string(),
+        })?;
+
+    tracing::info!(user_id = %user_id, ?limits, "User risk limit override set");
+
+    Ok(Json(UserRiskLimitsResponse {
+        user_id,
+        limits,
+        overridden: true,
+    }))
+}
+
+async fn delete_risk_limits(
+    State(engine): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let removed = engine
+        .risk_limits()
+        .remove_override(user_id)
+        .await
+        .map_err(|e| ApiError {
+            error: e.to_string(),
+            code: "PERSISTENCE_FAILED".to_string(),
+        })?;
+
+    if !removed {
+        return Err(ApiError {
+            error: "No risk limit override for user".to_string(),
+            code: "OVERRIDE_NOT_FOUND".to_string(),
+        });
+    }
+
+    tracing::info!(user_id = %user_id, "User risk limit override removed");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransferRequest {
+    pub asset: String,
+    #[serde(with = "common::decimal::flex")]
+    pub amount: Decimal,
+}
+
+async fn deposit(
+    State(engine): State<AppState>,
+    Path(user_id): Path<Uuid>,
+    Json(req): Json<TransferRequest>,
+) -> Result<Json<AccountBalance>, ApiError> {
+    transfer(engine, user_id, req, Decimal::ONE).await
+}
+
+async fn withdraw(
+    State(engine): State<AppState>,
+    Path(user_id): Path<Uuid>,
+    Json(req): Json<TransferRequest>,
+) -> Result<Json<AccountBalance>, ApiError> {
+    transfer(engine, user_id, req, Decimal::NEGATIVE_ONE).await
+}
+
+/// Apply a deposit (`sign` 1) or withdrawal (`sign` -1) in the matching
+/// loop, so it is journaled and ordered with the user's orders
+async fn transfer(
+    engine: AppState,
+    user_id: Uuid,
+    req: TransferRequest,
+    sign: Decimal,
+) -> Result<Json<AccountBalance>, ApiError> {
+    if !engine.accounts_enabled() {
+        return Err(accounts_disabled());
+    }
+    let asset = req.asset.trim().to_uppercase();
+    if asset.is_empty() || req.amount <= Decimal::ZERO {
+        return Err(ApiError {
+            error: "Transfers need an asset and a positive amount".to_string(),
+            code: "INVALID_TRANSFER".to_string(),
+        });
+    }
+
+    let balance = engine
+        .transfer(user_id, asset.clone(), req.amount * sign)
+        .await
+        .map_err(|e| submit_error(e, "TRANSFER_FAILED"))?
+        .map_err(|e| ApiError {
+            error: e.to_string(),
+            code: e.code().to_string(),
+        })?;
+
+    tracing::info!(user_id = %user_id, asset, amount = %(req.amount * sign), "Balance transfer");
+    Ok(Json(AccountBalance {
+        user_id,
+        asset,
+        balance,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddSymbolRequest {
+    /// e.g. `DOGE-USDT`
+    pub symbol: String,
+    /// Tick size, lot size and min notional; unconstrained when omitted
+    pub config: Option<SymbolConfig>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SymbolResponse {
+    pub symbol: String,
+    pub config: Option<SymbolConfig>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DelistResponse {
+    pub symbol: String,
+    /// Resting orders cancelled by the delisting
+    pub cancelled_orders: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HaltResponse {
+    pub symbol: String,
+    pub halted: bool,
+}
+
+fn parse_symbol(symbol: &str) -> Result<Symbol, ApiError> {
+    let (base, quote) = symbol
+        .split_once('-')
+        .filter(|(base, quote)| !base.is_empty() && !quote.is_empty())
+        .ok_or_else(|| ApiError {
+            error: "Invalid symbol format".to_string(),
+            code: "INVALID_SYMBOL".to_string(),
+        })?;
+    Ok(Symbol::new(base, quote))
+}
+
+/// List a new symbol and open an empty book for it
+async fn add_symbol(
+    State(engine): State<AppState>,
+    Json(req): Json<AddSymbolRequest>,
+) -> Result<(StatusCode, Json<SymbolResponse>), ApiError> {
+    let symbol = parse_symbol(&req.symbol)?;
+    let added = engine
+        .add_symbol(symbol.clone(), req.config.clone())
+        .await
+        .map_err(|e| submit_error(e, "SYMBOL_ADD_FAILED"))?;
+
+    if !added {
+        return Err(ApiError {
+            error: format!("Symbol already listed: {symbol}"),
+            code: "SYMBOL_EXISTS".to_string(),
+        });
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        Json(SymbolResponse {
+            symbol: symbol.to_string(),
+            config: req.config,
+        }),
+    ))
+}
+
+/// Delist a symbol, cancelling its resting orders
+async fn delist_symbol(
+    State(engine): State<AppState>,
+    Path(symbol): Path<String>,
+) -> Result<Json<DelistResponse>, ApiError> {
+    let symbol = parse_symbol(&symbol)?;
+    let cancelled = engine
+        .delist_symbol(symbol.clone())
+        .await
+        .map_err(|e| submit_error(e, "SYMBOL_DELIST_FAILED"))?
+        .ok_or_else(|| ApiError {
+            error: format!("Unknown symbol: {symbol}"),
+            code: "SYMBOL_NOT_FOUND".to_string(),
+        })?;
+
+    tracing::info!(symbol = %symbol, cancelled, "Symbol delisted via admin API");
+    Ok(Json(DelistResponse {
+        symbol: symbol.to_string(),
+        cancelled_orders: cancelled,
+    }))
+}
+
+/// Halt matching on a symbol; its book collects orders for an auction
+async fn halt_symbol(
+    State(engine): State<AppState>,
+    Path(symbol): Path<String>,
+) -> Result<Json<HaltResponse>, ApiError> {
+    let symbol = parse_symbol(&symbol)?;
+    if !engine.symbols().contains(&symbol) {
+        return Err(ApiError {
+            error: format!("Unknown symbol: {symbol}"),
+            code: "SYMBOL_NOT_FOUND".to_string(),
+        });
+    }
+    let halted = engine
+        .halt_symbol(symbol.clone())
+        .await
+        .map_err(|e| submit_error(e, "SYMBOL_HALT_FAILED"))?;
+
+    if !halted {
+        return Err(ApiError {
+            error: format!("{symbol} is already halted"),
+            code: "SYMBOL_HALTED".to_string(),
+        });
+    }
+
+    tracing::info!(symbol = %symbol, "Symbol halted via admin API");
+    Ok(Json(HaltResponse {
+        symbol: symbol.to_string(),
+        halted: true,
+    }))
+}
+
+/// Lift an admin or circuit breaker halt and uncross the symbol's book
+async fn resume_symbol(
+    State(engine): State<AppState>,
+    Path(symbol): Path<String>,
+) -> Result<Json<HaltResponse>, ApiError> {
+    let symbol = parse_symbol(&symbol)?;
+    let resumed = engine
+        .resume_symbol(symbol.clone())
+        .await
+        .map_err(|e| submit_error(e, "SYMBOL_RESUME_FAILED"))?;
+
+    if !resumed {
+        return Err(ApiError {
+            error: format!("{symbol} is not halted"),
+            code: "SYMBOL_NOT_HALTED".to_string(),
+        });
+    }
+
+    tracing::info!(symbol = %symbol, "Symbol resumed via admin API");
+    Ok(Json(HaltResponse {
+        symbol: symbol.to_string(),
+        halted: false,
+    }))
+}
+
+/// Symbols halted by an admin or a circuit breaker
+async fn list_halts(State(engine): State<AppState>) -> Json<BTreeMap<String, Halt>> {
+    Json(engine.halts())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deposit_accepts_numeric_amount() {
+        for body in [
+            r#"{"asset": "usdt", "amount": 250.5}"#,
+            r#"{"asset": "usdt", "amount": "250.5"}"#,
+        ] {
+            let req: TransferRequest = serde_json::from_str(body).unwrap();
+            assert_eq!(req.amount, Decimal::new(2505, 1));
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Halt Auctions
+//!
+//! While a symbol is halted its book stops matching. Limit orders that
+//! can rest are collected where they land, even through the other side,
+//! and the book becomes a call auction. Participants follow it through
+//! the indicative price: the single price at which the most quantity
+//! would trade if the book uncrossed now, with the quantity left over at
+//! that price. It is published periodically and served over REST.
+//!
+//! When the halt lifts the book uncrosses at the indicative price before
+//! anything else trades on it. Auction trades have no aggressor; the bid
+//! is reported as the taker.
+
+use rust_decimal::Decimal;
+
+use common::{Order, Side, TimeInForce};
+
+/// Auction outcome at a single uncross price
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Equilibrium {
+    pub price: Decimal,
+    /// Quantity that trades at `price`
+    pub paired: Decimal,
+    /// Bid minus ask interest at `price`; positive when buyers are left
+    pub surplus: Decimal,
+}
+
+impl Equilibrium {
+    /// Side left with unmatched quantity, if any
+    pub fn imbalance_side(&self) -> Option<Side> {
+        if self.surplus > Decimal::ZERO {
+            Some(Side::Buy)
+        } else if self.surplus < Decimal::ZERO {
+            Some(Side::Sell)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether an order may rest in a halted book
+///
+/// Market and immediate orders have nothing to execute against until the
+/// auction uncrosses.
+pub fn accepts(order: &Order) -> bool {
+    order.price.is_some() && matches!(order.time_in_force, TimeInForce::GTC | TimeInForce::GTD)
+}
+
+/// Uncross price of `bids` (best first) and `asks` (best first), given as
+/// (price, quantity) levels
+///
+/// The price executes the most quantity, then leaves the smallest
+/// surplus. Remaining ties go to the highest price when every candidate
+/// leaves buyers over, the lowest when every one leaves sellers over, and
+/// otherwise to the price nearest the middle of the tied range, the lower
+/// of two equally near. Returns `None` when the book does not cross.
+pub fn equilibrium(
+    bids: &[(Decimal, Decimal)],
+    asks: &[(Decimal, Decimal)],
+) -> Option<Equilibrium> {
+    let (&(best_bid, _), &(best_ask, _)) = (bids.first()?, asks.first()?);
+    if best_bid < best_ask {
+        return None;
+    }
+
+    // Only prices inside the crossed range can pair anything
+    let mut prices: Vec<Decimal> = bids
+        .iter()
+        .map(|&(price, _)| price)
+        .filter(|&price| price >= best_ask)
+        .chain(
+            asks.iter()
+                .map(|&(price, _)| price)
+                .filter(|&price| price <= best_bid),
+        )
+        .collect();
+    prices.sort();
+    prices.dedup();
+
+    let candidates: Vec<Equilibrium> = prices
+        .into_iter()
+        .map(|price| {
+            let demand: Decimal = bids
+                .iter()
+                .take_while(|&&(bid, _)| bid >= price)
+                .map(|&(_, quantity)| quantity)
+                .sum();
+            let supply: Decimal = asks
+                .iter()
+                .take_while(|&&(ask, _)| ask <= price)
+                .map(|&(_, quantity)| quantity)
+                .sum();
+            Equilibrium {
+                price,
+                paired: demand.min(supply),
+                surplus: demand - supply,
+            }
+        })
+        .collect();
+
+    let paired = candidates.iter().map(|c| c.paired).max()?;
+    let least_surplus = candidates
+        .iter()
+        .filter(|c| c.paired == paired)
+        .map(|c| c.surplus.abs())
+        .min()?;
+    let tied: Vec<Equilibrium> = candidates
+        .into_iter()
+        .filter(|c| c.paired == paired && c.surplus.abs() == least_surplus)
+        .collect();
+
+    // Candidates are in ascending price order
+    if tied.iter().all(|c| c.surplus > Decimal::ZERO) {
+        return tied.last().copied();
+    }
+    if tied.iter().all(|c| c.surplus < Decimal::ZERO) {
+        return tied.first().copied();
+    }
+    let middle = (tied.first()?.price + tied.last()?.price) / Decimal::TWO;
+    tied.into_iter().min_by_key(|c| (c.price - middle).abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels(levels: &[(i64, i64)]) -> Vec<(Decimal, Decimal)> {
+        levels
+            .iter()
+            .map(|&(price, quantity)| (Decimal::from(price), Decimal::from(quantity)))
+            .collect()
+    }
+
+    #[test]
+    fn test_uncrossed_book_has_no_equilibrium() {
+        let bids = levels(&[(99, 1)]);
+        let asks = levels(&[(100, 1)]);
+        assert_eq!(equilibrium(&bids, &asks), None);
+        assert_eq!(equilibrium(&bids, &levels(&[])), None);
+    }
+
+    #[test]
+    fn test_equilibrium_maximizes_paired_quantity() {
+        let bids = levels(&[(102, 3), (101, 2), (100, 4)]);
+        let asks = levels(&[(99, 2), (100, 3), (101, 1)]);
+
+        // At 101: 5 bid vs 6 offered pairs 5; at 100: 9 bid vs 5 offered pairs 5;
+        // 101 leaves the smaller surplus
+        let found = equilibrium(&bids, &asks).unwrap();
+        assert_eq!(found.price, Decimal::from(101));
+        assert_eq!(found.paired, Decimal::from(5));
+        assert_eq!(found.surplus, Decimal::from(-1));
+        assert_eq!(found.imbalance_side(), Some(Side::Sell));
+    }
+
+    #[test]
+    fn test_buy_pressure_takes_the_highest_tied_price() {
+        let bids = levels(&[(105, 10)]);
+        let asks = levels(&[(100, 4)]);
+
+        let found = equilibrium(&bids, &asks).unwrap();
+        assert_eq!(found.price, Decimal::from(105));
+        assert_eq!(found.paired, Decimal::from(4));
+        assert_eq!(found.imbalance_side(), Some(Side::Buy));
+    }
+
+    #[test]
+    fn test_mixed_pressure_takes_the_middle_price() {
+        let bids = levels(&[(104, 5), (102, 1)]);
+        let asks = levels(&[(100, 5), (103, 1)]);
+
+        let found = equilibrium(&bids, &asks).unwrap();
+        assert_eq!(found.price, Decimal::from(102));
+        assert_eq!(found.paired, Decimal::from(5));
+        assert_eq!(found.imbalance_side(), Some(Side::Buy));
+    }
+}
@@ -0,0 +1,67 @@
+//! Redis BBO Fast-Path
+//!
+//! Publishes best bid/ask per symbol directly to Redis so latency-sensitive
+//! internal consumers (risk pre-checks, market makers) can read top of book
+//! without Kafka consumer lag. Writes only happen when the BBO changes.
+
+use anyhow::Result;
+use chrono::Utc;
+use dashmap::DashMap;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use rust_decimal::Decimal;
+use tracing::warn;
+
+use common::Symbol;
+
+type Bbo = (Option<Decimal>, Option<Decimal>);
+
+/// Publishes BBO changes to Redis hashes keyed `bbo:{symbol}`
+pub struct BboPublisher {
+    conn: ConnectionManager,
+
+    /// Last published BBO per symbol
+    last: DashMap<String, Bbo>,
+}
+
+impl BboPublisher {
+    pub async fn new(redis_url: &str) -> Result<Self> {
+        let client = redis::Client::open(redis_url)?;
+        let conn = ConnectionManager::new(client).await?;
+        Ok(Self {
+            conn,
+            last: DashMap::new(),
+        })
+    }
+
+    /// Publish the BBO for `symbol` if it differs from the last published value
+    ///
+    /// Failures are logged and swallowed; Redis is a fast-path, not the
+    /// source of truth.
+    pub async fn publish(&self, symbol: &Symbol, bbo: Bbo, sequence: u64) {
+        let key = symbol.to_string();
+
+        if self.last.get(&key).is_some_and(|last| *last == bbo) {
+            return;
+        }
+        self.last.insert(key, bbo);
+
+        let (bid, ask) = bbo;
+        let fields = [
+            ("bid", bid.map(|p| p.to_string()).unwrap_or_default()),
+            ("ask", ask.map(|p| p.to_string()).unwrap_or_default()),
+            ("sequence", sequence.to_string()),
+            ("timestamp", Utc::now().timestamp_micros().to_string()),
+        ];
+
+        let mut conn = self.conn.clone();
+        if let Err(e) = conn
+            .hset_multiple::<_, _, _, ()>(format!("bbo:{symbol}"), &fields)
+            .await
+        {
+            warn!(symbol = %symbol, "Failed to publish BBO to Redis: {}", e);
+            // Force a rewrite on the next change attempt
+            self.last.remove(&symbol.to_string());
+        }
+    }
+}
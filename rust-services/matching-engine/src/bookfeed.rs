@@ -0,0 +1,250 @@
+//! Order Book Feed
+//!
+//! Publication policy for the `market.orderbook` topic. Trades go to their
+//! own topic one event per trade; book changes are published as level
+//! deltas, conflated per symbol to at most one message per interval. A
+//! change inside the interval only marks the symbol dirty, and the next
+//! flush publishes every level that moved since the last message, so
+//! bursts cost one message per interval however many changes they make.
+//! Each symbol also gets a periodic full snapshot to start from.
+//!
+//! Messages carry the book sequence they reflect, and deltas also the
+//! sequence of the message before them, so a consumer detects a lost
+//! message without every book change being published.
+//!
+//! Depth is tracked to a fixed number of levels per side, as for the
+//! WebSocket stream: a level pushed below it is reported as removed.
+
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+
+use crate::config::Config;
+use crate::orderbook::OrderBook;
+use crate::stream::{diff_levels, to_pairs};
+use common::{
+    events::{OrderBookDeltas, OrderBookUpdate},
+    PriceLevel,
+};
+
+type Depth = (Vec<PriceLevel>, Vec<PriceLevel>);
+
+#[derive(Debug, Default)]
+struct SymbolFeed {
+    /// Depth as of the last message
+    published: Depth,
+    /// Book changes not yet published
+    pending: u32,
+    /// Book sequence of the last message
+    sequence: u64,
+    delta_sent_at: Option<Instant>,
+    snapshot_sent_at: Option<Instant>,
+}
+
+/// Messages a flush produced
+#[derive(Debug, Default)]
+pub struct FeedMessages {
+    pub snapshots: Vec<OrderBookUpdate>,
+    pub deltas: Vec<OrderBookDeltas>,
+}
+
+/// Conflating publisher state for every book
+pub struct BookFeed {
+    levels: usize,
+    conflation: Duration,
+    snapshot_interval: Duration,
+    symbols: DashMap<String, SymbolFeed>,
+}
+
+impl BookFeed {
+    pub fn new(levels: usize, conflation: Duration, snapshot_interval: Duration) -> Self {
+        Self {
+            levels,
+            conflation,
+            snapshot_interval,
+            symbols: DashMap::new(),
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            config.book_feed_levels,
+            Duration::from_millis(config.book_feed_conflation_ms),
+            Duration::from_secs(config.book_feed_snapshot_interval_secs),
+        )
+    }
+
+    /// How often pending changes and due snapshots should be flushed
+    pub fn flush_interval(&self) -> Duration {
+        if self.conflation.is_zero() {
+            self.snapshot_interval
+        } else {
+            self.conflation.min(self.snapshot_interval)
+        }
+    }
+
+    /// Record a change to `book`, returning its deltas if the symbol is
+    /// outside its conflation interval
+    pub fn on_change(
+        &self,
+        book: &OrderBook,
+        now: Instant,
+        at: DateTime<Utc>,
+    ) -> Option<OrderBookDeltas> {
+        let mut feed = self.symbols.entry(book.symbol().to_string()).or_default();
+        feed.pending += 1;
+        if feed
+            .delta_sent_at
+            .is_some_and(|sent| now.duration_since(sent) < self.conflation)
+        {
+            metrics::counter!("book_changes_conflated").increment(1);
+            return None;
+        }
+        self.deltas(book, &mut feed, now, at)
+    }
+
+    /// Publish what is due: snapshots of books whose interval elapsed,
+    /// then deltas of symbols whose conflation interval elapsed
+    pub fn flush<'a>(
+        &self,
+        books: impl IntoIterator<Item = &'a OrderBook>,
+        now: Instant,
+        at: DateTime<Utc>,
+    ) -> FeedMessages {
+        let mut messages = FeedMessages::default();
+        for book in books {
+            let mut feed = self.symbols.entry(book.symbol().to_string()).or_default();
+
+            let snapshot_due = feed
+                .snapshot_sent_at
+                .is_none_or(|sent| now.duration_since(sent) >= self.snapshot_interval);
+            if snapshot_due {
+                messages
+                    .snapshots
+                    .push(self.snapshot(book, &mut feed, now, at));
+                continue;
+            }
+
+            let delta_due = feed
+                .delta_sent_at
+                .is_none_or(|sent| now.duration_since(sent) >= self.conflation);
+            if feed.pending > 0 && delta_due {
+                messages
+                    .deltas
+                    .extend(self.deltas(book, &mut feed, now, at));
+            }
+        }
+        messages
+    }
+
+    /// Forget a delisted symbol
+    pub fn remove(&self, symbol: &str) {
+        self.symbols.remove(symbol);
+    }
+
+    /// Full depth; it covers any pending changes, and later deltas
+    /// follow its sequence
+    fn snapshot(
+        &self,
+        book: &OrderBook,
+        feed: &mut SymbolFeed,
+        now: Instant,
+        at: DateTime<Utc>,
+    ) -> OrderBookUpdate {
+        feed.published = book.get_depth(self.levels);
+        feed.pending = 0;
+        feed.sequence = book.book_sequence();
+        feed.snapshot_sent_at = Some(now);
+        OrderBookUpdate {
+            symbol: book.symbol().clone(),
+            bids: to_pairs(&feed.published.0),
+            asks: to_pairs(&feed.published.1),
+            sequence: feed.sequence,
+            timestamp: at,
+        }
+    }
+
+    fn deltas(
+        &self,
+        book: &OrderBook,
+        feed: &mut SymbolFeed,
+        now: Instant,
+        at: DateTime<Utc>,
+    ) -> Option<OrderBookDeltas> {
+        let depth = book.get_depth(self.levels);
+        let bids = diff_levels(&feed.published.0, &depth.0);
+        let asks = diff_levels(&feed.published.1, &depth.1);
+        let changes = std::mem::take(&mut feed.pending);
+        feed.published = depth;
+        if bids.is_empty() && asks.is_empty() {
+            return None;
+        }
+
+        let prev_sequence = feed.sequence;
+        feed.sequence = book.book_sequence();
+        feed.delta_sent_at = Some(now);
+        Some(OrderBookDeltas {
+            symbol: book.symbol().clone(),
+            bids,
+            asks,
+            sequence: feed.sequence,
+            prev_sequence,
+            changes,
+            timestamp: at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{Order, Side, Symbol};
+    use rust_decimal::Decimal;
+
+    fn order(side: Side, price: i64) -> Order {
+        Order::builder().side(side).price(price).build()
+    }
+
+    #[test]
+    fn test_conflates_changes_within_interval() {
+        let feed = BookFeed::new(10, Duration::from_millis(100), Duration::from_secs(30));
+        let book = OrderBook::new(Symbol::new("ETH", "USDT"));
+        let start = Instant::now();
+        let at = Utc::now();
+
+        let messages = feed.flush([&book], start, at);
+        assert_eq!(messages.snapshots.len(), 1);
+        assert_eq!(messages.snapshots[0].sequence, 0);
+
+        book.process_order(order(Side::Buy, 1999));
+        let first = feed.on_change(&book, start, at).unwrap();
+        assert_eq!((first.prev_sequence, first.sequence), (0, 1));
+        assert_eq!(first.bids, vec![(Decimal::new(1999, 0), Decimal::ONE)]);
+
+        // A burst inside the interval is held back
+        let soon = start + Duration::from_millis(10);
+        book.process_order(order(Side::Buy, 1999));
+        assert!(feed.on_change(&book, soon, at).is_none());
+        book.process_order(order(Side::Sell, 2001));
+        assert!(feed.on_change(&book, soon, at).is_none());
+        assert!(feed.flush([&book], soon, at).deltas.is_empty());
+
+        // ...and published as one message once it elapses
+        let later = start + Duration::from_millis(100);
+        let messages = feed.flush([&book], later, at);
+        assert!(messages.snapshots.is_empty());
+        let merged = &messages.deltas[0];
+        assert_eq!((merged.prev_sequence, merged.sequence), (1, 3));
+        assert_eq!(merged.changes, 2);
+        assert_eq!(
+            merged.bids,
+            vec![(Decimal::new(1999, 0), Decimal::new(2, 0))]
+        );
+        assert_eq!(merged.asks, vec![(Decimal::new(2001, 0), Decimal::ONE)]);
+
+        // Nothing pending, nothing sent
+        let idle = later + Duration::from_secs(1);
+        assert!(feed.flush([&book], idle, at).deltas.is_empty());
+    }
+}
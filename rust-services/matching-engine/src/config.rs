@@ -3,10 +3,12 @@
 //! Loads configuration from environment variables and config files
 //! with sensible defaults for development.
 
-use anyhow::Result;
-use serde::Deserialize;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize)]
+use common::config::{schemes, Checks, Validate};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     // Server
     #[serde(default = "default_host")]
@@ -20,23 +22,58 @@ pub struct Config {
     pub log_level: String,
 
     // Database
-    #[allow(dead_code)]
     pub database_url: String,
 
     #[serde(default = "default_pool_size")]
-    #[allow(dead_code)]
     pub database_pool_size: u32,
 
+    /// Persist orders and trades to Postgres for historical queries
+    #[serde(default)]
+    pub history_enabled: bool,
+
+    /// Records queued for the history writer before new ones are dropped
+    #[serde(default = "default_history_queue_size")]
+    pub history_queue_size: usize,
+
     // Redis
-    #[allow(dead_code)]
     pub redis_url: String,
 
+    /// Publish BBO changes to Redis directly from the engine
+    #[serde(default)]
+    pub bbo_redis_enabled: bool,
+
     // Kafka
     pub kafka_brokers: String,
 
     #[serde(default = "default_kafka_group")]
     pub kafka_group_id: String,
 
+    /// How long the producer waits to fill a batch before sending
+    #[serde(default = "default_kafka_linger_ms")]
+    pub kafka_linger_ms: u64,
+
+    /// Upper bound on a producer batch per partition, in bytes
+    #[serde(default = "default_kafka_batch_size")]
+    pub kafka_batch_size: usize,
+
+    /// Events queued for the publisher before the matching loop waits
+    #[serde(default = "default_event_queue_size")]
+    pub event_queue_size: usize,
+
+    /// Store events in a Postgres outbox before the matching loop moves
+    /// on, and publish them from there
+    #[serde(default)]
+    pub event_outbox_enabled: bool,
+
+    /// Interval at which the outbox relay checks for events it was not
+    /// woken for
+    #[serde(default = "default_event_outbox_poll_ms")]
+    pub event_outbox_poll_ms: u64,
+
+    /// How long delivered events are kept in the outbox
+    #[serde(default = "default_event_outbox_retention_secs")]
+    pub event_outbox_retention_secs: u64,
+
     // Matching Engine
     #[serde(default = "default_matching_interval")]
     #[allow(dead_code)]
@@ -46,6 +83,211 @@ pub struct Config {
     #[allow(dead_code)]
     pub max_orders_per_symbol: usize,
 
+    /// Resting orders across all books past which the engine turns
+    /// cancel-only; 0 disables the cap
+    #[serde(default = "default_max_resting_orders")]
+    pub max_resting_orders: usize,
+
+    /// Interval at which per-book memory gauges are updated
+    #[serde(default = "default_memory_accounting_interval_secs")]
+    pub memory_accounting_interval_secs: u64,
+
+    /// Upper bound on depth levels returned by the API
+    #[serde(default = "default_max_depth_levels")]
+    pub max_depth_levels: usize,
+
+    /// Upper bound on orders or cancels in one batch request
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: usize,
+
+    /// Levels per side in WebSocket book snapshots and updates
+    #[serde(default = "default_ws_depth_levels")]
+    pub ws_depth_levels: usize,
+
+    /// Messages buffered per WebSocket client before it is resynced
+    #[serde(default = "default_ws_buffer_size")]
+    pub ws_buffer_size: usize,
+
+    /// Publish book deltas and periodic snapshots to the order book topic
+    #[serde(default = "default_book_feed_enabled")]
+    pub book_feed_enabled: bool,
+
+    /// Minimum interval between delta messages per symbol; changes inside
+    /// it are merged into the next one. 0 publishes every change
+    #[serde(default = "default_book_feed_conflation_ms")]
+    pub book_feed_conflation_ms: u64,
+
+    #[serde(default = "default_book_feed_snapshot_interval_secs")]
+    pub book_feed_snapshot_interval_secs: u64,
+
+    /// Levels per side in published deltas and snapshots
+    #[serde(default = "default_book_feed_levels")]
+    pub book_feed_levels: usize,
+
+    /// Reference-data service for tick/lot validation; unconstrained when unset
+    #[serde(default)]
+    pub reference_data_url: Option<String>,
+
+    /// JSON file of per-symbol tick size, lot size and min notional,
+    /// keyed by symbol; built-in defaults are used when unset
+    #[serde(default)]
+    pub symbol_config_path: Option<String>,
+
+    // Per-user throttles (0 = unlimited); admin API overrides per user
+    #[serde(default)]
+    pub throttle_orders_per_sec: u32,
+
+    #[serde(default)]
+    pub throttle_cancels_per_sec: u32,
+
+    /// Token bucket capacity; 0 uses the per-second rate
+    #[serde(default)]
+    pub throttle_burst: u32,
+
+    // Per-user pre-trade risk limits (0 = unlimited); admin API overrides
+    // per user
+    #[serde(default)]
+    pub risk_max_order_quantity: Decimal,
+
+    #[serde(default)]
+    pub risk_max_open_orders: u32,
+
+    /// Notional of a user's open orders, including a new one
+    #[serde(default)]
+    pub risk_max_notional_exposure: Decimal,
+
+    /// Furthest a limit price may cross the midprice, in basis points
+    #[serde(default)]
+    pub risk_price_band_bps: u32,
+
+    // Per-symbol price protections (0 = off); symbol configs override
+    // per symbol
+    /// Furthest a limit price may be from the last trade, in basis points
+    #[serde(default)]
+    pub price_band_bps: u32,
+
+    /// Price move within the breaker window that halts a symbol, in
+    /// basis points
+    #[serde(default)]
+    pub circuit_breaker_bps: u32,
+
+    #[serde(default = "default_circuit_breaker_window_secs")]
+    pub circuit_breaker_window_secs: u64,
+
+    /// Scheduled trading sessions in UTC, as `SYMBOL=session` pairs such
+    /// as `*=Mon-Fri 13:30-20:00`; unscheduled symbols are always open
+    #[serde(default)]
+    pub trading_sessions: String,
+
+    /// How long before a session opens its book collects orders for the
+    /// opening auction
+    #[serde(default = "default_pre_open_secs")]
+    pub pre_open_secs: u64,
+
+    /// Token for the admin API (`X-Admin-Token`); admin API disabled when unset
+    #[serde(default)]
+    pub admin_token: Option<String>,
+
+    /// Keep today's trades in memory for the replay endpoint
+    #[serde(default)]
+    pub trade_replay_enabled: bool,
+
+    #[serde(default = "default_trade_replay_max_per_symbol")]
+    pub trade_replay_max_per_symbol: usize,
+
+    /// Directory of the write-ahead command journal; journaling is
+    /// disabled when unset
+    #[serde(default)]
+    pub command_journal_dir: Option<String>,
+
+    /// Size at which a new journal segment is started
+    #[serde(default = "default_command_journal_segment_bytes")]
+    pub command_journal_segment_bytes: u64,
+
+    /// Rebuild the books from the whole journal, print them and exit
+    #[serde(default)]
+    pub command_journal_audit: bool,
+
+    /// Seconds between order book snapshots; 0 disables snapshots
+    #[serde(default)]
+    pub snapshot_interval_secs: u64,
+
+    /// Evict books with no resting orders after this many idle seconds;
+    /// unset or 0 keeps every book in memory
+    #[serde(default)]
+    pub idle_book_evict_secs: Option<u64>,
+
+    /// Snapshot file; snapshots are kept in Redis when unset
+    #[serde(default)]
+    pub snapshot_path: Option<String>,
+
+    /// Target for loading and restoring the snapshot on startup; overruns
+    /// are logged and counted. 0 disables the check
+    #[serde(default = "default_snapshot_restore_budget_ms")]
+    pub snapshot_restore_budget_ms: u64,
+
+    /// Threads books are restored on; 0 uses every available core
+    #[serde(default)]
+    pub snapshot_restore_threads: usize,
+
+    /// Interval at which market quality windows are closed and published
+    #[serde(default = "default_market_quality_interval_secs")]
+    pub market_quality_interval_secs: u64,
+
+    /// Interval at which indicative auction prices of halted symbols are
+    /// published
+    #[serde(default = "default_auction_publish_interval_ms")]
+    pub auction_publish_interval_ms: u64,
+
+    /// Interval at which mark and index prices for stop orders are read
+    /// from the data pipeline's Redis cache; 0 disables those triggers
+    #[serde(default = "default_reference_price_poll_ms")]
+    pub reference_price_poll_ms: u64,
+
+    /// Reject incoming orders created longer ago than this
+    #[serde(default)]
+    pub max_order_age_ms: Option<u64>,
+
+    // Order events
+    /// Omit the fills list from order events
+    #[serde(default)]
+    pub lean_order_events: bool,
+
+    #[serde(default = "default_max_fills_per_event")]
+    pub max_fills_per_event: usize,
+
+    /// Taker fee in basis points of quote notional
+    #[serde(default)]
+    pub taker_fee_bps: u32,
+
+    /// Lock user balances for orders and settle trades between accounts;
+    /// orders are not balance-checked when disabled
+    #[serde(default)]
+    pub accounts_enabled: bool,
+
+    /// Closed orders kept for order queries, oldest evicted first
+    #[serde(default = "default_order_store_max_closed")]
+    pub order_store_max_closed: usize,
+
+    /// Interval at which fees accrued into house accounts are published
+    #[serde(default = "default_fee_accrual_interval_secs")]
+    pub fee_accrual_interval_secs: u64,
+
+    // Supervised background tasks
+    /// Task failures tolerated within the restart window before exiting
+    #[serde(default = "default_task_max_restarts")]
+    pub task_max_restarts: u32,
+
+    #[serde(default = "default_task_restart_window_secs")]
+    pub task_restart_window_secs: u64,
+
+    /// Delay before the first restart, doubled on each further failure
+    #[serde(default = "default_task_restart_backoff_ms")]
+    pub task_restart_backoff_ms: u64,
+
+    #[serde(default = "default_task_restart_max_backoff_ms")]
+    pub task_restart_max_backoff_ms: u64,
+
     // Observability
     #[serde(default)]
     #[allow(dead_code)]
@@ -53,6 +295,14 @@ pub struct Config {
 
     #[serde(default = "default_metrics_port")]
     pub metrics_port: u16,
+
+    /// Ingress-to-last-event latency an order should stay within
+    #[serde(default = "default_golden_path_slo_target_us")]
+    pub golden_path_slo_target_us: u64,
+
+    /// Share of orders that must meet the latency target
+    #[serde(default = "default_golden_path_slo_objective")]
+    pub golden_path_slo_objective: f64,
 }
 
 fn default_host() -> String {
@@ -71,10 +321,38 @@ fn default_pool_size() -> u32 {
     20
 }
 
+fn default_history_queue_size() -> usize {
+    100_000
+}
+
 fn default_kafka_group() -> String {
     "matching-engine".to_string()
 }
 
+fn default_kafka_linger_ms() -> u64 {
+    5
+}
+
+fn default_kafka_batch_size() -> usize {
+    64 * 1024
+}
+
+fn default_event_queue_size() -> usize {
+    100_000
+}
+
+fn default_event_outbox_poll_ms() -> u64 {
+    500
+}
+
+fn default_event_outbox_retention_secs() -> u64 {
+    24 * 60 * 60
+}
+
+fn default_snapshot_restore_budget_ms() -> u64 {
+    10_000
+}
+
 fn default_matching_interval() -> u64 {
     100 // 100 microseconds
 }
@@ -83,16 +361,177 @@ fn default_max_orders_per_symbol() -> usize {
     100_000
 }
 
+fn default_max_resting_orders() -> usize {
+    2_000_000
+}
+
+fn default_memory_accounting_interval_secs() -> u64 {
+    10
+}
+
+fn default_max_depth_levels() -> usize {
+    100
+}
+
+fn default_max_batch_size() -> usize {
+    100
+}
+
+fn default_circuit_breaker_window_secs() -> u64 {
+    300
+}
+
+fn default_pre_open_secs() -> u64 {
+    300
+}
+
+fn default_ws_depth_levels() -> usize {
+    50
+}
+
+fn default_ws_buffer_size() -> usize {
+    4096
+}
+
+fn default_book_feed_enabled() -> bool {
+    true
+}
+
+fn default_book_feed_conflation_ms() -> u64 {
+    100
+}
+
+fn default_book_feed_snapshot_interval_secs() -> u64 {
+    30
+}
+
+fn default_book_feed_levels() -> usize {
+    50
+}
+
+fn default_trade_replay_max_per_symbol() -> usize {
+    100_000
+}
+
+fn default_command_journal_segment_bytes() -> u64 {
+    64 * 1024 * 1024
+}
+
+fn default_market_quality_interval_secs() -> u64 {
+    60
+}
+
+fn default_auction_publish_interval_ms() -> u64 {
+    1000
+}
+
+fn default_reference_price_poll_ms() -> u64 {
+    1000
+}
+
+fn default_order_store_max_closed() -> usize {
+    100_000
+}
+
+fn default_fee_accrual_interval_secs() -> u64 {
+    60
+}
+
+fn default_max_fills_per_event() -> usize {
+    100
+}
+
+fn default_task_max_restarts() -> u32 {
+    5
+}
+
+fn default_task_restart_window_secs() -> u64 {
+    300
+}
+
+fn default_task_restart_backoff_ms() -> u64 {
+    500
+}
+
+fn default_task_restart_max_backoff_ms() -> u64 {
+    30_000
+}
+
 fn default_metrics_port() -> u16 {
     9090
 }
 
-impl Config {
-    pub fn load() -> Result<Self> {
-        let config = config::Config::builder()
-            .add_source(config::Environment::default().separator("__"))
-            .build()?;
+fn default_golden_path_slo_target_us() -> u64 {
+    5_000
+}
+
+fn default_golden_path_slo_objective() -> f64 {
+    0.999
+}
+
+impl Validate for Config {
+    fn validate(&self, checks: &mut Checks) {
+        checks.ports(&[("port", self.port), ("metrics_port", self.metrics_port)]);
+        checks.url("database_url", &self.database_url, schemes::POSTGRES);
+        checks.url("redis_url", &self.redis_url, schemes::REDIS);
+        checks.brokers("kafka_brokers", &self.kafka_brokers);
+        checks.optional_url(
+            "reference_data_url",
+            self.reference_data_url.as_deref(),
+            schemes::HTTP,
+        );
 
-        Ok(config.try_deserialize()?)
+        checks.duration(
+            "memory_accounting_interval_secs",
+            self.memory_accounting_interval_secs,
+        );
+        checks.duration(
+            "market_quality_interval_secs",
+            self.market_quality_interval_secs,
+        );
+        checks.duration("fee_accrual_interval_secs", self.fee_accrual_interval_secs);
+        checks.duration(
+            "auction_publish_interval_ms",
+            self.auction_publish_interval_ms,
+        );
+        checks.non_zero("event_queue_size", self.event_queue_size as u64);
+        checks.non_zero("kafka_batch_size", self.kafka_batch_size as u64);
+        if self.event_outbox_enabled {
+            checks.duration("event_outbox_poll_ms", self.event_outbox_poll_ms);
+        }
+        checks.non_zero("max_batch_size", self.max_batch_size as u64);
+        checks.non_zero("history_queue_size", self.history_queue_size as u64);
+        checks.non_zero("ws_buffer_size", self.ws_buffer_size as u64);
+        if self.book_feed_enabled {
+            checks.duration(
+                "book_feed_snapshot_interval_secs",
+                self.book_feed_snapshot_interval_secs,
+            );
+            checks.non_zero("book_feed_levels", self.book_feed_levels as u64);
+        }
+        checks.non_zero(
+            "command_journal_segment_bytes",
+            self.command_journal_segment_bytes,
+        );
+        if self.risk_max_order_quantity < Decimal::ZERO {
+            checks.fail("risk_max_order_quantity", "must not be negative");
+        }
+        if self.risk_max_notional_exposure < Decimal::ZERO {
+            checks.fail("risk_max_notional_exposure", "must not be negative");
+        }
+        checks.duration(
+            "circuit_breaker_window_secs",
+            self.circuit_breaker_window_secs,
+        );
+        if let Err(e) = crate::sessions::parse_sessions(&self.trading_sessions) {
+            checks.fail("trading_sessions", e);
+        }
+        checks.non_zero("golden_path_slo_target_us", self.golden_path_slo_target_us);
+        if !(self.golden_path_slo_objective > 0.0 && self.golden_path_slo_objective < 1.0) {
+            checks.fail("golden_path_slo_objective", "must be between 0 and 1");
+        }
+        if self.command_journal_audit && self.command_journal_dir.is_none() {
+            checks.fail("command_journal_audit", "requires command_journal_dir");
+        }
     }
 }
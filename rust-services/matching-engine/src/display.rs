@@ -0,0 +1,100 @@
+//! API Display Precision
+//!
+//! Decimals carry whatever scale arithmetic left them with, so API
+//! responses round prices and quantities to the symbol's display
+//! precision from the instrument registry. Clients that need the exact
+//! values ask for `raw=true`.
+
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use common::{PriceLevel, SymbolInfo, Trade};
+
+/// Decimal places for a symbol's prices and quantities; `None` is raw
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayPrecision {
+    price: Option<u32>,
+    quantity: Option<u32>,
+}
+
+impl DisplayPrecision {
+    pub const RAW: Self = Self {
+        price: None,
+        quantity: None,
+    };
+
+    pub fn from_info(info: &SymbolInfo) -> Self {
+        Self {
+            price: Some(info.price_precision),
+            quantity: Some(info.quantity_precision),
+        }
+    }
+
+    pub fn price(&self, value: Decimal) -> Decimal {
+        fixed(value, self.price)
+    }
+
+    pub fn quantity(&self, value: Decimal) -> Decimal {
+        fixed(value, self.quantity)
+    }
+
+    pub fn level(&self, level: PriceLevel) -> PriceLevel {
+        PriceLevel {
+            price: self.price(level.price),
+            quantity: self.quantity(level.quantity),
+            order_count: level.order_count,
+        }
+    }
+
+    /// Quote quantities are in the quote asset and shown at price precision
+    pub fn trade(&self, trade: Trade) -> Trade {
+        Trade {
+            price: self.price(trade.price),
+            quantity: self.quantity(trade.quantity),
+            quote_quantity: self.price(trade.quote_quantity),
+            ..trade
+        }
+    }
+}
+
+/// Round half away from zero to exactly `dp` places
+fn fixed(value: Decimal, dp: Option<u32>) -> Decimal {
+    let Some(dp) = dp else {
+        return value;
+    };
+    let mut rounded = value.round_dp_with_strategy(dp, RoundingStrategy::MidpointAwayFromZero);
+    rounded.rescale(dp);
+    rounded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::Symbol;
+
+    #[test]
+    fn test_rounds_to_symbol_precision() {
+        let mut info = SymbolInfo::from_symbol(&Symbol::new("BTC", "USDT"));
+        info.price_precision = 2;
+        info.quantity_precision = 4;
+        let precision = DisplayPrecision::from_info(&info);
+
+        assert_eq!(
+            precision.price(Decimal::new(2000_000000000, 9)).to_string(),
+            "2000.00"
+        );
+        assert_eq!(
+            precision.price(Decimal::new(19995, 1)).to_string(),
+            "1999.50"
+        );
+        assert_eq!(
+            precision.quantity(Decimal::new(123456, 5)).to_string(),
+            "1.2346"
+        );
+        assert_eq!(
+            DisplayPrecision::RAW
+                .price(Decimal::new(2000_000000000, 9))
+                .to_string(),
+            "2000.000000000"
+        );
+    }
+}
@@ -2,24 +2,59 @@
 //!
 //! Manages multiple order books and coordinates order processing
 
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Result;
 use dashmap::DashMap;
 use parking_lot::RwLock;
-use rdkafka::producer::{FutureProducer, FutureRecord};
-use rdkafka::ClientConfig;
-use tokio::sync::mpsc;
-use tracing::{info, instrument, warn};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use tokio::sync::{mpsc, oneshot, watch};
+use tracing::{error, info, instrument, warn};
 
 use common::{
-    events::{topics, Event, OrderUpdated, TradeExecuted},
-    Order, Symbol, Trade, TradingError,
+    events::{
+        AlertSeverity, FillSummary, IndicativePrice, OrderBookDeltas, OrderCancelled,
+        OrderRejected, OrderUpdated, RiskAlert, SymbolAdded, SymbolDelisted, SymbolHalted,
+        SymbolResumed, TradeExecuted, TypedEvent,
+    },
+    AccountBalance, Balance, Clock, EngineCapacityMetadata, HybridClock, Order, OrderStatus,
+    PreTradeLimitMetadata, SharedClock, Side, Symbol, SymbolConfig, SymbolInfo, SymbolRegistry,
+    SymbolStatus, Trade, TradingError, TriggerSource,
 };
+use uuid::Uuid;
 
+use crate::accounts::Accounts;
+use crate::algo::{self, Algo, AlgoBook, AlgoState};
+use crate::auction;
+use crate::bbo::BboPublisher;
+use crate::bookfeed::BookFeed;
 use crate::config::Config;
-use crate::orderbook::OrderBook;
+use crate::display::DisplayPrecision;
+use crate::fees::{FeeLedger, FeesSummary};
+use crate::history::{HistoryRecord, HistoryStore};
+use crate::idle::ColdBooks;
+use crate::journal::TradeJournal;
+use crate::latency::{GoldenPath, Ingress};
+use crate::memory::{CapTransition, RestingOrderCap};
+use crate::orderbook::{BookMemory, OrderBook};
+use crate::orders::{OrderStore, StatusFilter};
+use crate::protection::{Halt, HaltReason, PriceProtection};
+use crate::publisher::EventPublisher;
+use crate::quality::{QualityReport, QualityTracker};
+use crate::risk::{self, RiskLimits, RiskLimitsRegistry, RiskStore};
+use crate::sessions::{MarketState, TradingCalendar};
+use crate::snapshot::{restore_books, EngineSnapshot, SnapshotStore};
+use crate::stats::{MatchingStats, SymbolStats};
+use crate::stops::{ReferencePrices, StopBook};
+use crate::stream::MarketStream;
+use crate::supervisor::{RestartPolicy, Supervisor};
+use crate::throttle::{MessageKind, ThrottleLimits, ThrottleStore, Throttles};
+use crate::wal::{CommandJournal, JournalClock, JournalEntry, JournalReader, JournaledCommand};
 
 /// Order command for the matching engine
 pub enum OrderCommand {
@@ -28,33 +63,285 @@ pub enum OrderCommand {
         order_id: uuid::Uuid,
         symbol: Symbol,
     },
+    /// Cancel a resting order and submit its replacement
+    ReplaceOrder {
+        order_id: uuid::Uuid,
+        replacement: Order,
+    },
+    /// Order-log message fully applied; sent after the commands it produced
+    LogPosition {
+        partition: i32,
+        offset: i64,
+    },
+    /// Mark or index price update from the data pipeline
+    ReferencePrice {
+        symbol: Symbol,
+        source: TriggerSource,
+        price: Decimal,
+    },
+    /// Capture all books between commands
+    Snapshot(oneshot::Sender<EngineSnapshot>),
+    /// Evict books that are empty and idle
+    EvictIdle,
+    /// Publish conflated book deltas and due book snapshots
+    FlushBookFeed,
+    /// List a symbol; replies `false` if it is already listed
+    AddSymbol {
+        symbol: Symbol,
+        config: Option<SymbolConfig>,
+        reply: oneshot::Sender<bool>,
+    },
+    /// Cancel a symbol's resting orders and retire its book; replies with
+    /// the number of cancelled orders, `None` if it is not listed
+    DelistSymbol {
+        symbol: Symbol,
+        reply: oneshot::Sender<Option<usize>>,
+    },
+    /// Uncross a book whose halt has lifted
+    Uncross {
+        symbol: Symbol,
+    },
+    /// Halt matching on a symbol; replies `false` if it is not listed or
+    /// already halted by the engine
+    Halt {
+        symbol: Symbol,
+        reply: oneshot::Sender<bool>,
+    },
+    /// Lift the engine's halt of a symbol and uncross its book; replies
+    /// `false` if the engine has not halted it
+    Resume {
+        symbol: Symbol,
+        reply: oneshot::Sender<bool>,
+    },
+    /// Credit a user's available balance, or debit it for a negative
+    /// amount; replies with the new balance
+    Transfer {
+        user_id: Uuid,
+        asset: String,
+        amount: Decimal,
+        reply: oneshot::Sender<std::result::Result<Balance, TradingError>>,
+    },
+    /// Orders and cancels applied back to back, each journaled on its
+    /// own, with their events committed together
+    Batch(Vec<OrderCommand>),
+    /// Re-apply journaled commands in order; replies with the last
+    /// sequence number applied
+    Replay {
+        entries: Vec<JournalEntry>,
+        reply: oneshot::Sender<u64>,
+    },
+}
+
+/// Engine lifecycle state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EngineState {
+    /// Books are being rebuilt; new orders are rejected
+    Recovering,
+    /// Accepting orders
+    Ready,
 }
 
 /// Matching Engine
 pub struct MatchingEngine {
+    /// Lifecycle state
+    state: watch::Sender<EngineState>,
+
+    /// Time source shared by all order books
+    clock: SharedClock,
+
+    /// Underlies `clock`; pinned to a command's journal time while it is
+    /// applied
+    journal_clock: Arc<JournalClock>,
+
+    /// Write-ahead command journal, when enabled
+    command_journal: Option<parking_lot::Mutex<CommandJournal>>,
+    command_journal_dir: Option<PathBuf>,
+
+    /// Sequence of the last journaled command applied to the books
+    journal_sequence: AtomicU64,
+
+    /// Rebuilding books for an audit; nothing is published
+    audit: bool,
+
     /// Order books per symbol
     order_books: DashMap<String, Arc<OrderBook>>,
 
-    /// Kafka producer for events
-    producer: FutureProducer,
+    /// Evicted idle books, restored on their next command
+    cold_books: ColdBooks,
+
+    /// Queues events for the Kafka publisher task
+    events: Arc<EventPublisher>,
 
     /// Command channel
     command_tx: mpsc::Sender<OrderCommand>,
     command_rx: RwLock<Option<mpsc::Receiver<OrderCommand>>>,
 
-    /// Supported symbols
-    symbols: Vec<Symbol>,
+    /// Listed symbols; changed only from the matching loop
+    symbols: RwLock<Vec<Symbol>>,
+
+    /// Optional Redis BBO fast-path
+    bbo_publisher: Option<BboPublisher>,
+
+    /// Conflated book deltas for the order book topic, when enabled
+    book_feed: Option<BookFeed>,
+
+    /// Maximum fills attached to an order event (0 in lean mode)
+    max_fills_per_event: usize,
+
+    /// Taker fee in basis points of quote notional
+    taker_fee_bps: u32,
+
+    /// Maximum age of incoming orders
+    max_order_age: Option<chrono::Duration>,
+
+    /// Upper bound on depth levels per request
+    max_depth_levels: usize,
+
+    /// Upper bound on commands per batch request
+    max_batch_size: usize,
+
+    /// Threads books are restored on from a snapshot
+    restore_threads: usize,
+
+    /// Native execution algo parents
+    algos: AlgoBook,
+
+    /// Untriggered stop orders
+    stops: StopBook,
+
+    /// Instrument metadata from the reference-data service
+    instruments: Arc<SymbolRegistry>,
+
+    /// Tick size, lot size and min notional per symbol
+    symbol_configs: RwLock<HashMap<String, SymbolConfig>>,
+
+    /// Price bands and circuit breaker halts per symbol
+    protection: PriceProtection,
+
+    /// Defaults for symbols whose config sets no price band or breaker
+    price_band_bps: u32,
+    circuit_breaker_bps: u32,
+
+    /// Scheduled trading sessions per symbol
+    calendar: TradingCalendar,
+
+    /// Per-user order/cancel rate limits
+    throttles: Throttles,
+
+    /// Ingress-to-last-event latency of orders in flight
+    golden_path: GoldenPath,
+
+    /// Per-user pre-trade risk limits
+    risk_limits: RiskLimitsRegistry,
+
+    /// Today's trades for replay, when enabled
+    journal: Option<TradeJournal>,
+
+    /// Persistent order and trade history, when enabled
+    history: Option<Arc<HistoryStore>>,
+
+    /// Execution quality statistics per symbol
+    quality: QualityTracker,
+
+    /// Order and trade counts per symbol
+    stats: MatchingStats,
+
+    /// Cancel-only backstop against unbounded book growth
+    resting_cap: RestingOrderCap,
+
+    /// House accounts for collected fees
+    fees: FeeLedger,
+
+    /// User balances and the funds open orders lock, when enabled
+    accounts: Option<Accounts>,
+
+    /// Latest state of processed orders, for HTTP queries
+    orders: OrderStore,
+
+    market_stream: MarketStream,
+
+    /// Track order-log positions for snapshots and the command journal
+    log_positions_tracked: bool,
+
+    /// Last applied order-log offset per partition
+    log_offsets: parking_lot::Mutex<BTreeMap<i32, i64>>,
+
+    /// Restarts failed background tasks and tracks their health
+    supervisor: Arc<Supervisor>,
+}
+
+/// Command receiver taken by a running matching loop
+///
+/// Hands the receiver back when the loop stops, even by panicking, so a
+/// supervised restart picks up the same channel.
+struct LoopReceiver<'a> {
+    rx: Option<mpsc::Receiver<OrderCommand>>,
+    slot: &'a RwLock<Option<mpsc::Receiver<OrderCommand>>>,
+}
+
+impl LoopReceiver<'_> {
+    async fn recv(&mut self) -> Option<OrderCommand> {
+        self.rx.as_mut()?.recv().await
+    }
+}
+
+impl Drop for LoopReceiver<'_> {
+    fn drop(&mut self) {
+        *self.slot.write() = self.rx.take();
+    }
 }
 
 impl MatchingEngine {
     pub async fn new(config: &Config) -> Result<Self> {
-        // Initialize Kafka producer
-        let producer: FutureProducer = ClientConfig::new()
-            .set("bootstrap.servers", &config.kafka_brokers)
-            .set("message.timeout.ms", "5000")
-            .set("acks", "all")
-            .set("enable.idempotence", "true")
-            .create()?;
+        // Events are produced off the matching path
+        let events = Arc::new(EventPublisher::new(config).await?);
+
+        // Initialize Redis BBO fast-path
+        let bbo_publisher = if config.bbo_redis_enabled && !config.command_journal_audit {
+            info!("Redis BBO publication enabled");
+            Some(BboPublisher::new(&config.redis_url).await?)
+        } else {
+            None
+        };
+
+        // Throttle overrides are only editable, and so only persisted,
+        // when the admin API is enabled
+        let throttle_store = if config.admin_token.is_some() {
+            Some(ThrottleStore::new(&config.redis_url).await?)
+        } else {
+            None
+        };
+        let throttles = Throttles::new(ThrottleLimits::from_config(config), throttle_store);
+        let restored = throttles.restore().await?;
+        if restored > 0 {
+            info!(users = restored, "Restored user throttle overrides");
+        }
+
+        let risk_store = if config.admin_token.is_some() {
+            Some(RiskStore::new(&config.redis_url).await?)
+        } else {
+            None
+        };
+        let risk_limits = RiskLimitsRegistry::new(RiskLimits::from_config(config), risk_store);
+        let restored = risk_limits.restore().await?;
+        if restored > 0 {
+            info!(users = restored, "Restored user risk limit overrides");
+        }
+
+        let history = if config.command_journal_audit {
+            None
+        } else {
+            HistoryStore::from_config(config).await?.map(Arc::new)
+        };
+
+        let command_journal_dir = config.command_journal_dir.as_ref().map(PathBuf::from);
+        let command_journal = match &command_journal_dir {
+            Some(dir) if !config.command_journal_audit => Some(parking_lot::Mutex::new(
+                CommandJournal::open(dir, config.command_journal_segment_bytes)?,
+            )),
+            _ => None,
+        };
 
         // Create command channel
         let (tx, rx) = mpsc::channel(100_000);
@@ -66,25 +353,133 @@ impl MatchingEngine {
             Symbol::new("SOL", "USDT"),
             Symbol::new("AVAX", "USDT"),
         ];
+        let symbol_configs = match &config.symbol_config_path {
+            Some(path) => {
+                let configs: HashMap<String, SymbolConfig> =
+                    serde_json::from_slice(&tokio::fs::read(path).await?)?;
+                info!(path = %path, symbols = configs.len(), "Loaded symbol configs");
+                configs
+            }
+            None => default_symbol_configs(),
+        };
 
+        let journal_clock = Arc::new(JournalClock::new());
+        let clock: SharedClock = Arc::new(HybridClock::new(journal_clock.clone()));
         let engine = Self {
+            state: watch::Sender::new(EngineState::Recovering),
+            clock: clock.clone(),
+            journal_clock,
+            command_journal,
+            command_journal_dir,
+            journal_sequence: AtomicU64::new(0),
+            audit: config.command_journal_audit,
             order_books: DashMap::new(),
-            producer,
+            cold_books: ColdBooks::new(
+                config
+                    .idle_book_evict_secs
+                    .filter(|secs| *secs > 0)
+                    .map(Duration::from_secs),
+            ),
+            events,
             command_tx: tx,
             command_rx: RwLock::new(Some(rx)),
-            symbols: symbols.clone(),
+            symbols: RwLock::new(symbols.clone()),
+            bbo_publisher,
+            book_feed: config
+                .book_feed_enabled
+                .then(|| BookFeed::from_config(config)),
+            max_fills_per_event: if config.lean_order_events {
+                0
+            } else {
+                config.max_fills_per_event
+            },
+            taker_fee_bps: config.taker_fee_bps,
+            max_order_age: config
+                .max_order_age_ms
+                .map(|ms| chrono::Duration::milliseconds(ms as i64)),
+            max_depth_levels: config.max_depth_levels,
+            max_batch_size: config.max_batch_size,
+            restore_threads: match config.snapshot_restore_threads {
+                0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+                threads => threads,
+            },
+            algos: AlgoBook::new(),
+            stops: StopBook::new(),
+            instruments: Arc::new(SymbolRegistry::new()),
+            symbol_configs: RwLock::new(symbol_configs),
+            protection: PriceProtection::from_config(config),
+            price_band_bps: config.price_band_bps,
+            circuit_breaker_bps: config.circuit_breaker_bps,
+            calendar: TradingCalendar::from_config(config)?,
+            throttles,
+            golden_path: GoldenPath::from_config(config),
+            risk_limits,
+            journal: config
+                .trade_replay_enabled
+                .then(|| TradeJournal::new(config.trade_replay_max_per_symbol)),
+            history,
+            quality: QualityTracker::new(),
+            stats: MatchingStats::new(),
+            resting_cap: RestingOrderCap::new(config.max_resting_orders),
+            fees: FeeLedger::new(clock.now()),
+            accounts: config
+                .accounts_enabled
+                .then(|| Accounts::new(config.taker_fee_bps)),
+            orders: OrderStore::new(config.order_store_max_closed),
+            market_stream: MarketStream::new(config.ws_buffer_size, config.ws_depth_levels),
+            log_positions_tracked: config.snapshot_interval_secs > 0
+                || config.command_journal_dir.is_some(),
+            log_offsets: parking_lot::Mutex::new(BTreeMap::new()),
+            supervisor: Arc::new(Supervisor::new(RestartPolicy::from_config(config))),
         };
 
         // Initialize order books
         for symbol in symbols {
             engine
-                .order_books
-                .insert(symbol.to_string(), Arc::new(OrderBook::new(symbol)));
+                .cold_books
+                .touch(&symbol.to_string(), std::time::Instant::now());
+            engine.order_books.insert(
+                symbol.to_string(),
+                Arc::new(OrderBook::with_clock(symbol, engine.clock.clone())),
+            );
         }
 
         Ok(engine)
     }
 
+    /// Publisher draining the engine's event queue to Kafka
+    pub fn events(&self) -> &Arc<EventPublisher> {
+        &self.events
+    }
+
+    /// Supervisor of the engine's background tasks
+    pub fn supervisor(&self) -> &Arc<Supervisor> {
+        &self.supervisor
+    }
+
+    /// Get current lifecycle state
+    pub fn state(&self) -> EngineState {
+        *self.state.borrow()
+    }
+
+    /// Check if the engine accepts orders
+    pub fn is_ready(&self) -> bool {
+        self.state() == EngineState::Ready
+    }
+
+    /// Mark recovery complete and start accepting orders
+    pub fn mark_ready(&self) {
+        self.state.send_replace(EngineState::Ready);
+        info!("Matching engine ready");
+    }
+
+    /// Wait until the engine is ready
+    pub async fn wait_ready(&self) {
+        let mut rx = self.state.subscribe();
+        // The sender lives as long as the engine, so this cannot fail
+        let _ = rx.wait_for(|s| *s == EngineState::Ready).await;
+    }
+
     /// Get command sender
     #[allow(dead_code)]
     pub fn command_sender(&self) -> mpsc::Sender<OrderCommand> {
@@ -93,183 +488,2324 @@ impl MatchingEngine {
 
     /// Run the main matching loop
     pub async fn run_matching_loop(&self) -> Result<()> {
-        let mut rx = self
+        let rx = self
             .command_rx
             .write()
             .take()
             .expect("Matching loop already started");
+        let mut rx = LoopReceiver {
+            rx: Some(rx),
+            slot: &self.command_rx,
+        };
 
         info!("Starting matching engine loop");
 
         while let Some(command) = rx.recv().await {
+            let mut measured = Vec::new();
+
             match command {
-                OrderCommand::NewOrder(order) => {
-                    self.process_new_order(order).await?;
+                OrderCommand::Replay { entries, reply } => {
+                    let last = self.apply_replayed(entries).await;
+                    self.events.commit().await;
+                    let _ = reply.send(last);
                 }
-                OrderCommand::CancelOrder { order_id, symbol } => {
-                    self.process_cancel(order_id, symbol).await?;
+                OrderCommand::Batch(commands) => {
+                    for command in commands {
+                        measured.extend(Self::measured(&command));
+                        self.apply(command).await;
+                    }
                 }
+                command => {
+                    measured.extend(Self::measured(&command));
+                    self.apply(command).await;
+                }
+            }
+
+            // A command's events are stored before the next command
+            self.events.commit().await;
+            let now = std::time::Instant::now();
+            for order_id in measured {
+                self.golden_path.completed(order_id, now);
             }
         }
 
         Ok(())
     }
 
-    /// Process a new order
-    #[instrument(skip(self), fields(order_id = %order.id, symbol = %order.symbol))]
-    async fn process_new_order(&self, order: Order) -> Result<()> {
-        let start = std::time::Instant::now();
+    /// Order whose golden-path latency `command` completes
+    fn measured(command: &OrderCommand) -> Option<Uuid> {
+        match command {
+            OrderCommand::NewOrder(order) => Some(order.id),
+            OrderCommand::ReplaceOrder { replacement, .. } => Some(replacement.id),
+            _ => None,
+        }
+    }
 
-        // Get order book
-        let book = self.get_order_book(&order.symbol)?;
+    /// Journal and apply one command
+    async fn apply(&self, command: OrderCommand) {
+        match self.journal_command(&command) {
+            Ok(at) => {
+                // Apply at the journaled time, as a replay will
+                if let Some(at) = at {
+                    self.journal_clock.pin(at);
+                }
+                self.dispatch(command).await;
+                self.journal_clock.unpin();
+            }
+            Err(e) => self.fail_unjournaled(command, &e).await,
+        }
+    }
 
-        // Process through matching engine
-        let (updated_order, trades) = book.process_order(order.clone());
+    /// Apply one command to the books
+    async fn dispatch(&self, command: OrderCommand) {
+        match command {
+            OrderCommand::NewOrder(order) => {
+                let rejected = order.clone();
+                if let Err(e) = self.process_new_order(order).await {
+                    self.reject_failed(rejected, &e).await;
+                }
+            }
+            OrderCommand::CancelOrder { order_id, symbol } => {
+                if let Err(e) = self.process_cancel(order_id, symbol).await {
+                    metrics::counter!("commands_failed", "command" => "cancel").increment(1);
+                    error!(order_id = %order_id, "Cancel failed: {}", e);
+                }
+            }
+            OrderCommand::ReplaceOrder {
+                order_id,
+                replacement,
+            } => {
+                let rejected = replacement.clone();
+                if let Err(e) = self.process_replace(order_id, replacement).await {
+                    self.reject_failed(rejected, &e).await;
+                }
+            }
+            OrderCommand::ReferencePrice {
+                symbol,
+                source,
+                price,
+            } => {
+                if self.is_listed(&symbol) {
+                    if source == TriggerSource::MarkPrice {
+                        self.protection.on_mark_price(&symbol.0, price);
+                    }
+                    if let Err(e) = self.trigger_stops(&symbol, source, price).await {
+                        metrics::counter!("commands_failed", "command" => "trigger_stops")
+                            .increment(1);
+                        error!(symbol = %symbol, "Stop triggering failed: {}", e);
+                    }
+                }
+            }
+            OrderCommand::Uncross { symbol } => {
+                if let Err(e) = self.reopen(&symbol).await {
+                    metrics::counter!("commands_failed", "command" => "uncross").increment(1);
+                    error!(symbol = %symbol, "Uncross failed: {}", e);
+                }
+            }
+            OrderCommand::Halt { symbol, reply } => match self.process_halt(&symbol).await {
+                Ok(halted) => {
+                    let _ = reply.send(halted);
+                }
+                Err(e) => error!(symbol = %symbol, "Halting symbol failed: {}", e),
+            },
+            OrderCommand::Resume { symbol, reply } => match self.process_resume(&symbol).await {
+                Ok(resumed) => {
+                    let _ = reply.send(resumed);
+                }
+                Err(e) => error!(symbol = %symbol, "Resuming symbol failed: {}", e),
+            },
+            OrderCommand::LogPosition { partition, offset } => {
+                self.log_offsets.lock().insert(partition, offset);
+            }
+            OrderCommand::Snapshot(reply) => {
+                let _ = reply.send(self.snapshot());
+            }
+            OrderCommand::EvictIdle => {
+                self.evict_idle_books(std::time::Instant::now());
+            }
+            OrderCommand::FlushBookFeed => self.flush_book_feed().await,
+            OrderCommand::AddSymbol {
+                symbol,
+                config,
+                reply,
+            } => match self.process_add_symbol(symbol.clone(), config).await {
+                Ok(added) => {
+                    let _ = reply.send(added);
+                }
+                Err(e) => error!(symbol = %symbol, "Adding symbol failed: {}", e),
+            },
+            OrderCommand::DelistSymbol { symbol, reply } => {
+                match self.process_delist_symbol(symbol.clone()).await {
+                    Ok(cancelled) => {
+                        let _ = reply.send(cancelled);
+                    }
+                    Err(e) => error!(symbol = %symbol, "Delisting symbol failed: {}", e),
+                }
+            }
+            OrderCommand::Transfer {
+                user_id,
+                asset,
+                amount,
+                reply,
+            } => {
+                let _ = reply.send(self.process_transfer(user_id, &asset, amount));
+            }
+            OrderCommand::Batch(_) | OrderCommand::Replay { .. } => {
+                unreachable!("batches and replays are applied by the matching loop")
+            }
+        }
+    }
 
-        // Record latency
-        let latency = start.elapsed();
-        metrics::histogram!("matching_latency_us").record(latency.as_micros() as f64);
+    /// Journal a state-changing command before it is applied, returning
+    /// the time it is journaled at
+    fn journal_command(
+        &self,
+        command: &OrderCommand,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        let Some(journal) = &self.command_journal else {
+            return Ok(None);
+        };
+        let Some(journaled) = JournaledCommand::of(command) else {
+            return Ok(None);
+        };
 
-        // Publish order accepted event
-        self.publish_order_event(&updated_order).await?;
+        let at = self.journal_clock.now();
+        let sequence = journal.lock().append(at, journaled)?;
+        self.journal_sequence.store(sequence, Ordering::Release);
+        Ok(Some(at))
+    }
 
-        // Publish trade events
-        for trade in &trades {
-            self.publish_trade_event(trade).await?;
-            metrics::counter!("trades_executed").increment(1);
+    /// Fail a command that could not be journaled, as if it had failed
+    /// to apply
+    async fn fail_unjournaled(&self, command: OrderCommand, e: &anyhow::Error) {
+        match command {
+            OrderCommand::NewOrder(order)
+            | OrderCommand::ReplaceOrder {
+                replacement: order, ..
+            } => self.reject_failed(order, e).await,
+            _ => {
+                metrics::counter!("commands_failed", "command" => "journal").increment(1);
+                error!("Command dropped, journaling failed: {}", e);
+            }
+        }
+    }
+
+    /// Apply journaled commands at their journal times
+    async fn apply_replayed(&self, entries: Vec<JournalEntry>) -> u64 {
+        for entry in entries {
+            self.journal_clock.pin(entry.at);
+            self.dispatch(entry.command.into_command()).await;
+            self.journal_sequence
+                .store(entry.sequence, Ordering::Release);
+            metrics::counter!("journal_entries_replayed").increment(1);
+        }
+        // An audit reports the books as of the last command
+        if !self.audit {
+            self.journal_clock.unpin();
+        }
+        self.journal_sequence.load(Ordering::Acquire)
+    }
+
+    /// Re-apply the commands journaled after `after` through the matching
+    /// loop; call before `mark_ready`. Returns the number replayed.
+    pub async fn replay_journal(&self, after: u64) -> Result<usize> {
+        let Some(dir) = &self.command_journal_dir else {
+            return Ok(0);
+        };
+
+        let mut reader = JournalReader::open(dir, after)?;
+        let mut replayed = 0;
+        while let Some(entries) = reader.next_segment()? {
+            if entries.is_empty() {
+                continue;
+            }
+            replayed += entries.len();
+
+            let (reply, rx) = oneshot::channel();
+            self.command_tx
+                .send(OrderCommand::Replay { entries, reply })
+                .await
+                .map_err(|_| anyhow::anyhow!("Matching engine channel closed"))?;
+            rx.await?;
         }
 
         info!(
-            order_id = %updated_order.id,
-            status = ?updated_order.status,
-            trades = trades.len(),
-            latency_us = latency.as_micros(),
-            "Order processed"
+            after,
+            replayed,
+            sequence = self.journal_sequence.load(Ordering::Acquire),
+            "Command journal replayed"
         );
+        Ok(replayed)
+    }
 
-        Ok(())
+    /// Whether this engine only rebuilds its books from the journal
+    pub fn is_audit(&self) -> bool {
+        self.audit
     }
 
-    /// Process order cancellation
-    #[instrument(skip(self), fields(order_id = %order_id, symbol = %symbol))]
-    async fn process_cancel(&self, order_id: uuid::Uuid, symbol: Symbol) -> Result<()> {
-        let book = self.get_order_book(&symbol)?;
+    /// Reject an order whose processing failed, keeping the loop alive
+    ///
+    /// Kafka submitters get no synchronous reply, so without the rejection
+    /// the order would silently vanish.
+    async fn reject_failed(&self, mut order: Order, e: &anyhow::Error) {
+        let trading_error = e.downcast_ref::<TradingError>();
+        let reason = trading_error.map_or("internal", TradingError::reason);
+        let code = trading_error.map_or("INTERNAL", TradingError::code);
+        error!(order_id = %order.id, reason, "Order processing failed: {}", e);
 
-        if book.cancel_order(order_id) {
-            metrics::counter!("orders_cancelled").increment(1);
-            info!("Order cancelled");
-        } else {
-            warn!("Order not found for cancellation");
+        let now = self.clock.now();
+        order.status = OrderStatus::Rejected;
+        order.updated_at = now;
+        metrics::counter!("orders_rejected", "reason" => reason).increment(1);
+        let published = match self.publish_order_event(&order, &[]).await {
+            Ok(()) => {
+                self.publish(OrderRejected {
+                    order_id: order.id,
+                    client_order_id: order.client_order_id.clone(),
+                    symbol: order.symbol.clone(),
+                    reason: e.to_string(),
+                    code: code.to_string(),
+                    timestamp: now,
+                })
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = published {
+            error!(order_id = %order.id, "Failed to publish rejection: {}", e);
+        }
+    }
+
+    /// Capture every book and the order-log position it reflects
+    ///
+    /// Only consistent when called from the matching loop.
+    fn snapshot(&self) -> EngineSnapshot {
+        let mut books: Vec<_> = self
+            .order_books
+            .iter()
+            .map(|book| book.snapshot())
+            .collect();
+        books.extend(self.cold_books.snapshots());
+        books.sort_by_key(|b| b.symbol.to_string());
+
+        EngineSnapshot {
+            taken_at: self.clock.now(),
+            books,
+            log_offsets: self.log_offsets.lock().clone(),
+            fee_accounts: self.fees.accounts(),
+            balances: self
+                .accounts
+                .as_ref()
+                .map(Accounts::balances)
+                .unwrap_or_default(),
+            reservations: self
+                .accounts
+                .as_ref()
+                .map(Accounts::reservations)
+                .unwrap_or_default(),
+            symbols: self.symbols(),
+            symbol_configs: self.symbol_configs.read().clone(),
+            stop_orders: self.stops.orders(),
+            halts: self.protection.halts(),
+            journal_sequence: self
+                .command_journal_dir
+                .is_some()
+                .then(|| self.journal_sequence.load(Ordering::Acquire)),
+        }
+    }
+
+    /// Rebuild the books from a snapshot; call before `mark_ready`
+    pub fn restore(&self, snapshot: &EngineSnapshot) -> Result<()> {
+        let start = std::time::Instant::now();
+
+        // Symbols listed or delisted at runtime; older snapshots only
+        // carry the books
+        if !snapshot.symbols.is_empty() {
+            let listed: Vec<String> = snapshot.symbols.iter().map(|s| s.to_string()).collect();
+            self.order_books.retain(|key, _| listed.contains(key));
+            self.symbols.write().clear();
+        }
+        for symbol in snapshot
+            .symbols
+            .iter()
+            .chain(snapshot.books.iter().map(|b| &b.symbol))
+        {
+            self.list_symbol(symbol);
+        }
+        {
+            let mut configs = self.symbol_configs.write();
+            for (symbol, config) in &snapshot.symbol_configs {
+                configs
+                    .entry(symbol.clone())
+                    .or_insert_with(|| config.clone());
+            }
+        }
+
+        let books = snapshot
+            .books
+            .iter()
+            .map(|book| Ok((self.get_order_book(&book.symbol)?, book)))
+            .collect::<Result<Vec<_>>>()?;
+        restore_books(&books, self.restore_threads);
+        self.stops.restore(&snapshot.stop_orders);
+        self.protection.restore(&snapshot.halts);
+        *self.log_offsets.lock() = snapshot.log_offsets.clone();
+        self.journal_sequence
+            .store(snapshot.journal_sequence.unwrap_or(0), Ordering::Release);
+        self.fees.restore(&snapshot.fee_accounts);
+        if let Some(accounts) = &self.accounts {
+            accounts.restore(&snapshot.balances, &snapshot.reservations);
         }
 
+        info!(
+            taken_at = %snapshot.taken_at,
+            books = snapshot.books.len(),
+            orders = snapshot
+                .books
+                .iter()
+                .map(|b| b.bids.len() + b.asks.len())
+                .sum::<usize>(),
+            stop_orders = snapshot.stop_orders.len(),
+            threads = self.restore_threads,
+            elapsed_ms = start.elapsed().as_millis() as u64,
+            "Order books restored from snapshot"
+        );
+        metrics::histogram!("snapshot_restore_ms").record(start.elapsed().as_millis() as f64);
         Ok(())
     }
 
-    /// Get order book for symbol
-    fn get_order_book(&self, symbol: &Symbol) -> Result<Arc<OrderBook>> {
-        self.order_books
-            .get(&symbol.to_string())
-            .map(|r| r.clone())
-            .ok_or_else(|| TradingError::SymbolNotFound(symbol.to_string()).into())
+    /// Order-log offsets the books reflect, empty without a snapshot
+    pub fn log_offsets(&self) -> BTreeMap<i32, i64> {
+        self.log_offsets.lock().clone()
     }
 
-    /// Submit order to matching engine
-    pub async fn submit_order(&self, order: Order) -> Result<()> {
+    /// Record that an order-log message has been applied
+    pub async fn mark_log_position(&self, partition: i32, offset: i64) -> Result<()> {
+        if !self.log_positions_tracked {
+            return Ok(());
+        }
         self.command_tx
-            .send(OrderCommand::NewOrder(order))
+            .send(OrderCommand::LogPosition { partition, offset })
             .await
-            .map_err(|_| anyhow::anyhow!("Matching engine channel closed"))?;
-        Ok(())
+            .map_err(|_| anyhow::anyhow!("Matching engine channel closed"))
     }
 
-    /// Cancel order
-    pub async fn cancel_order(&self, order_id: uuid::Uuid, symbol: Symbol) -> Result<()> {
+    /// Snapshot all books between commands of the matching loop
+    pub async fn capture_snapshot(&self) -> Result<EngineSnapshot> {
+        let (tx, rx) = oneshot::channel();
         self.command_tx
-            .send(OrderCommand::CancelOrder { order_id, symbol })
+            .send(OrderCommand::Snapshot(tx))
             .await
             .map_err(|_| anyhow::anyhow!("Matching engine channel closed"))?;
-        Ok(())
+        Ok(rx.await?)
     }
 
-    /// Get order book depth
-    pub fn get_depth(
-        &self,
-        symbol: &Symbol,
-        levels: usize,
-    ) -> Result<(Vec<common::PriceLevel>, Vec<common::PriceLevel>)> {
-        let book = self.get_order_book(symbol)?;
-        Ok(book.get_depth(levels))
+    /// Save a snapshot of all books every `interval`
+    pub async fn run_snapshotter(&self, store: SnapshotStore, interval: Duration) -> Result<()> {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick completes immediately
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.save_snapshot(&store).await {
+                warn!("Failed to save order book snapshot: {}", e);
+            }
+        }
     }
 
-    /// Get best bid/offer
-    #[allow(dead_code)]
-    pub fn get_bbo(
-        &self,
-        symbol: &Symbol,
-    ) -> Result<(Option<rust_decimal::Decimal>, Option<rust_decimal::Decimal>)> {
-        let book = self.get_order_book(symbol)?;
-        Ok(book.get_bbo())
+    /// Capture all books and save them to `store`
+    pub async fn save_snapshot(&self, store: &SnapshotStore) -> Result<()> {
+        let snapshot = self.capture_snapshot().await?;
+
+        let start = std::time::Instant::now();
+        store.save(&snapshot).await?;
+        metrics::histogram!("snapshot_save_ms").record(start.elapsed().as_millis() as f64);
+        metrics::counter!("snapshots_saved").increment(1);
+        Ok(())
     }
 
-    /// Publish order event to Kafka
-    async fn publish_order_event(&self, order: &Order) -> Result<()> {
-        let event = Event::new(
-            "order_updated",
-            "matching-engine",
-            OrderUpdated {
-                order_id: order.id,
-                client_order_id: order.client_order_id.clone(),
-                symbol: order.symbol.clone(),
-                status: order.status,
-                filled_quantity: order.filled_quantity,
-                remaining_quantity: order.remaining_quantity,
-                avg_fill_price: order.avg_fill_price,
-                timestamp: order.updated_at,
-            },
-        );
+    /// Close and publish market quality windows every `interval`
+    pub async fn run_quality_publisher(&self, interval: Duration) -> Result<()> {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick completes immediately
+        ticker.tick().await;
 
-        let payload = serde_json::to_string(&event)?;
+        loop {
+            ticker.tick().await;
+            let now = self.clock.now();
+            for symbol in &self.symbols() {
+                let quality = self.quality.close_window(symbol, now);
+                if let Err(e) = self.publish(quality).await {
+                    warn!(symbol = %symbol, "Failed to publish market quality: {}", e);
+                }
+            }
+        }
+    }
 
-        self.producer
-            .send(
-                FutureRecord::to(topics::ORDERS)
-                    .key(&order.id.to_string())
-                    .payload(&payload),
-                Duration::from_secs(5),
-            )
-            .await
-            .map_err(|(e, _)| anyhow::anyhow!("Kafka send error: {e}"))?;
+    /// Publish fees accrued into house accounts every `interval`
+    pub async fn run_fee_publisher(&self, interval: Duration) -> Result<()> {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick completes immediately
+        ticker.tick().await;
 
-        Ok(())
+        loop {
+            ticker.tick().await;
+            for accrued in self.fees.close_window(self.clock.now()) {
+                let asset = accrued.asset.clone();
+                if let Err(e) = self.publish(accrued).await {
+                    warn!(asset = %asset, "Failed to publish fee accrual: {}", e);
+                }
+            }
+        }
     }
 
-    /// Publish trade event to Kafka
-    async fn publish_trade_event(&self, trade: &Trade) -> Result<()> {
-        let event = Event::new(
-            "trade_executed",
-            "matching-engine",
-            TradeExecuted {
-                trade: trade.clone(),
-            },
-        );
+    /// Publish the indicative prices of halted and pre-open symbols every
+    /// `interval`, and uncross books that opened with no order arriving
+    /// since
+    pub async fn run_auction_publisher(&self, interval: Duration) -> Result<()> {
+        let mut ticker = tokio::time::interval(interval);
 
-        let payload = serde_json::to_string(&event)?;
+        loop {
+            ticker.tick().await;
+            for symbol in self.symbols() {
+                if let Some(indicative) = self.indicative_price(&symbol) {
+                    if let Err(e) = self.publish(indicative).await {
+                        warn!(symbol = %symbol, "Failed to publish indicative price: {}", e);
+                    }
+                } else if self.market_state(&symbol) == MarketState::Open
+                    && self
+                        .order_books
+                        .get(&symbol.0)
+                        .is_some_and(|book| book.is_crossed())
+                {
+                    self.command_tx
+                        .send(OrderCommand::Uncross { symbol })
+                        .await
+                        .map_err(|_| anyhow::anyhow!("Matching engine channel closed"))?;
+                }
+            }
+        }
+    }
 
-        self.producer
-            .send(
-                FutureRecord::to(topics::TRADES)
-                    .key(&trade.id.to_string())
-                    .payload(&payload),
-                Duration::from_secs(5),
-            )
-            .await
-            .map_err(|(e, _)| anyhow::anyhow!("Kafka send error: {e}"))?;
+    /// Feed mark and index prices to stops waiting on them every `interval`
+    pub async fn run_reference_prices(
+        &self,
+        prices: ReferencePrices,
+        interval: Duration,
+    ) -> Result<()> {
+        let mut ticker = tokio::time::interval(interval);
 
-        Ok(())
+        loop {
+            ticker.tick().await;
+            for source in [TriggerSource::MarkPrice, TriggerSource::IndexPrice] {
+                let symbols = self.stops.symbols_watching(source);
+                let updates = match prices.fetch(source, &symbols).await {
+                    Ok(updates) => updates,
+                    Err(e) => {
+                        warn!(
+                            source = source.as_str(),
+                            "Failed to fetch reference prices: {}", e
+                        );
+                        continue;
+                    }
+                };
+                for (symbol, price) in updates {
+                    self.command_tx
+                        .send(OrderCommand::ReferencePrice {
+                            symbol,
+                            source,
+                            price,
+                        })
+                        .await
+                        .map_err(|_| anyhow::anyhow!("Matching engine channel closed"))?;
+                }
+            }
+        }
+    }
+
+    /// Latest known state of an order
+    pub fn order(&self, order_id: Uuid) -> Option<Order> {
+        self.orders.get(order_id)
+    }
+
+    /// A user's orders matching `filter`, newest first
+    pub fn user_orders(&self, user_id: Uuid, filter: StatusFilter) -> Vec<Order> {
+        self.orders.list_for_user(user_id, filter)
+    }
+
+    /// House account balances and fees accrued since the last publish
+    pub fn fees_summary(&self) -> FeesSummary {
+        self.fees.summary()
+    }
+
+    /// Instrument metadata kept in sync with the reference-data service
+    pub fn instruments(&self) -> Arc<SymbolRegistry> {
+        self.instruments.clone()
+    }
+
+    /// Precision of API prices and quantities for `symbol`
+    ///
+    /// Symbols without reference data use the defaults derived from the
+    /// symbol alone.
+    pub fn display_precision(&self, symbol: &Symbol, raw: bool) -> DisplayPrecision {
+        if raw {
+            return DisplayPrecision::RAW;
+        }
+        let info = self
+            .instruments
+            .get(symbol)
+            .unwrap_or_else(|| SymbolInfo::from_symbol(symbol));
+        DisplayPrecision::from_info(&info)
+    }
+
+    /// Check an order against the symbol's market state, tick/lot sizes,
+    /// minimum notional and price band
+    ///
+    /// Symbols with neither a symbol config nor reference data are not
+    /// constrained. Halted and pre-open symbols take the orders their
+    /// auction can collect, whatever their price; closed symbols take none.
+    pub fn validate_order(&self, order: &Order) -> std::result::Result<(), TradingError> {
+        order.validate_display_quantity()?;
+        if !self.is_listed(&order.symbol) {
+            return Err(TradingError::SymbolNotFound(order.symbol.to_string()));
+        }
+        if let Some(config) = self.symbol_configs.read().get(&order.symbol.0) {
+            config.validate_order(order.price, order.quantity)?;
+            if let Some(price) = self.notional_price(order) {
+                config.validate_notional(price * order.quantity, self.taker_fee_bps)?;
+            }
+        }
+        if order.is_stop() {
+            self.stop_trigger(order)?;
+        }
+        match self.market_state(&order.symbol) {
+            MarketState::Open => {
+                let (band_bps, _) = self.protection_bps(&order.symbol);
+                self.protection.check_band(order, band_bps)?;
+            }
+            MarketState::PreOpen | MarketState::Halted if auction::accepts(order) => {}
+            _ => return Err(TradingError::MarketClosed),
+        }
+        match self.instruments.get(&order.symbol) {
+            Some(info) if info.status == SymbolStatus::Halted && auction::accepts(order) => {
+                info.validate_increments(order.price, order.quantity)
+            }
+            Some(info) => info.validate_order(order.price, order.quantity),
+            None => Ok(()),
+        }
+    }
+
+    /// Price an order's notional is judged at: its limit price, the stop
+    /// price of a stop-market order, or for a market order the best
+    /// opposite price, if the book has one
+    fn notional_price(&self, order: &Order) -> Option<Decimal> {
+        if order.price.is_some() {
+            return order.price;
+        }
+        if order.is_stop() {
+            return order.stop_price;
+        }
+        let (bid, ask) = self.order_books.get(&order.symbol.0)?.get_bbo();
+        match order.side {
+            Side::Buy => ask,
+            Side::Sell => bid,
+        }
+    }
+
+    /// State of `symbol` at the engine clock: Halted if reference data or
+    /// the engine has halted it, else as its session schedule has it
+    pub fn market_state(&self, symbol: &Symbol) -> MarketState {
+        if self.is_halted(symbol) {
+            return MarketState::Halted;
+        }
+        self.calendar.state(&symbol.0, self.clock.now())
+    }
+
+    /// Market states of the listed symbols
+    pub fn market_states(&self) -> BTreeMap<String, MarketState> {
+        self.symbols()
+            .into_iter()
+            .map(|symbol| {
+                let state = self.market_state(&symbol);
+                (symbol.0, state)
+            })
+            .collect()
+    }
+
+    /// Whether reference data or the engine has `symbol` halted
+    fn is_halted(&self, symbol: &Symbol) -> bool {
+        self.protection.is_halted(&symbol.0)
+            || self
+                .instruments
+                .get(symbol)
+                .is_some_and(|info| info.status == SymbolStatus::Halted)
+    }
+
+    /// Price band and circuit breaker threshold of `symbol`, in basis points
+    fn protection_bps(&self, symbol: &Symbol) -> (u32, u32) {
+        let configs = self.symbol_configs.read();
+        let config = configs.get(&symbol.0);
+        (
+            config
+                .and_then(|c| c.price_band_bps)
+                .unwrap_or(self.price_band_bps),
+            config
+                .and_then(|c| c.circuit_breaker_bps)
+                .unwrap_or(self.circuit_breaker_bps),
+        )
+    }
+
+    /// Trigger reference of a stop order, checked against the sources its
+    /// symbol allows
+    fn stop_trigger(&self, order: &Order) -> std::result::Result<TriggerSource, TradingError> {
+        if order.stop_price.is_none_or(|p| p <= Decimal::ZERO) {
+            return Err(TradingError::InvalidOrder(
+                "Stop order requires a positive stop price".to_string(),
+            ));
+        }
+        match self.symbol_configs.read().get(&order.symbol.0) {
+            Some(config) => config.resolve_trigger(order.trigger_source),
+            None => Ok(order.trigger_source.unwrap_or_default()),
+        }
+    }
+
+    /// Check whether an incoming order is stale and must not match
+    fn is_stale(&self, order: &Order, now: chrono::DateTime<chrono::Utc>) -> bool {
+        order.is_expired(now)
+            || self
+                .max_order_age
+                .is_some_and(|max_age| now - order.created_at > max_age)
+    }
+
+    /// Process a new order
+    #[instrument(skip(self), fields(order_id = %order.id, symbol = %order.symbol))]
+    async fn process_new_order(&self, mut order: Order) -> Result<()> {
+        let start = std::time::Instant::now();
+        self.stats.received(&order.symbol);
+
+        // Reject stale intent before it reaches the book
+        let now = self.clock.now();
+        if self.is_stale(&order, now) {
+            order.status = OrderStatus::Expired;
+            order.updated_at = now;
+            self.publish_order_event(&order, &[]).await?;
+            if let Some(parent_id) = self.algos.on_child_processed(&order) {
+                self.publish_parent_event(parent_id).await?;
+            }
+            metrics::counter!("orders_expired").increment(1);
+            warn!(
+                created_at = %order.created_at,
+                expire_at = ?order.expire_at,
+                "Order expired before matching"
+            );
+            return Ok(());
+        }
+
+        // Reject orders off the symbol's tick/lot grid, below min notional,
+        // for closed markets, over the user's risk limits, while the engine
+        // is cancel-only or without the balance to cover them
+        if let Err(e) = self.admit(&order).await {
+            order.status = OrderStatus::Rejected;
+            order.updated_at = now;
+            self.publish_order_event(&order, &[]).await?;
+            self.publish(OrderRejected {
+                order_id: order.id,
+                client_order_id: order.client_order_id.clone(),
+                symbol: order.symbol.clone(),
+                reason: e.to_string(),
+                code: e.code().to_string(),
+                timestamp: now,
+            })
+            .await?;
+            if let Some(parent_id) = self.algos.on_child_processed(&order) {
+                self.publish_parent_event(parent_id).await?;
+            }
+            metrics::counter!("orders_rejected", "reason" => e.reason()).increment(1);
+            warn!(reason = %e, "Order rejected");
+            return Ok(());
+        }
+
+        let symbol = order.symbol.clone();
+        let last_price = if order.is_stop() {
+            order.trigger_source = self.stop_trigger(&order).ok();
+            self.accept_stop(order).await?
+        } else {
+            self.match_order(order, start).await?
+        };
+
+        match last_price {
+            Some(price) => {
+                self.trigger_stops(&symbol, TriggerSource::LastTrade, price)
+                    .await
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Run a new order through every check, locking its funds last so a
+    /// rejection never leaves them held
+    async fn admit(&self, order: &Order) -> std::result::Result<(), TradingError> {
+        self.validate_order(order)?;
+        self.check_risk(order).await?;
+        self.check_resting_cap().await?;
+        self.reserve_funds(order)
+    }
+
+    /// Park a stop order until its reference crosses the stop price
+    ///
+    /// Returns the last trade price if the stop triggered on arrival.
+    async fn accept_stop(&self, mut order: Order) -> Result<Option<Decimal>> {
+        order.status = OrderStatus::Pending;
+        order.updated_at = self.clock.now();
+        self.publish_order_event(&order, &[]).await?;
+
+        let order_id = order.id;
+        let stop_price = order.stop_price;
+        let source = order.trigger_source.unwrap_or_default();
+        match self.stops.insert(order) {
+            Some(order) => {
+                metrics::counter!("stop_orders_triggered", "source" => source.as_str())
+                    .increment(1);
+                info!(order_id = %order_id, ?stop_price, source = source.as_str(), "Stop order triggered on arrival");
+                self.match_triggered(order).await
+            }
+            None => {
+                metrics::gauge!("stop_orders_pending").set(self.stops.count() as f64);
+                info!(order_id = %order_id, ?stop_price, source = source.as_str(), "Stop order accepted");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Match a triggered stop, first locking what a stop-market buy pays
+    /// walking the asks beyond what its stop price locked; rejected if
+    /// the available balance cannot cover it
+    async fn match_triggered(&self, order: Order) -> Result<Option<Decimal>> {
+        let accounts = self
+            .accounts
+            .as_ref()
+            .filter(|_| order.side == Side::Buy && order.price.is_none());
+        let cost = accounts.and_then(|_| {
+            self.order_books
+                .get(&order.symbol.0)?
+                .cost_to_buy(order.remaining_quantity)
+        });
+        if let (Some(accounts), Some(cost)) = (accounts, cost) {
+            if let Err(e) = accounts.top_up(&order, cost) {
+                warn!(order_id = %order.id, reason = %e, "Triggered stop rejected");
+                self.reject_order(order, e.reason()).await?;
+                return Ok(None);
+            }
+        }
+        self.match_order(order, std::time::Instant::now()).await
+    }
+
+    /// Match the stops triggered by `source` reaching `price`, and any
+    /// stops their trades trigger in turn
+    async fn trigger_stops(
+        &self,
+        symbol: &Symbol,
+        source: TriggerSource,
+        price: Decimal,
+    ) -> Result<()> {
+        let mut queue: VecDeque<(Order, TriggerSource, Decimal)> = self
+            .stops
+            .trigger(symbol, source, price)
+            .into_iter()
+            .map(|order| (order, source, price))
+            .collect();
+
+        while let Some((mut order, source, price)) = queue.pop_front() {
+            metrics::counter!("stop_orders_triggered", "source" => source.as_str()).increment(1);
+            metrics::gauge!("stop_orders_pending").set(self.stops.count() as f64);
+            info!(
+                order_id = %order.id,
+                stop_price = ?order.stop_price,
+                %price,
+                source = source.as_str(),
+                "Stop order triggered"
+            );
+
+            let now = self.clock.now();
+            if order.is_expired(now) {
+                order.status = OrderStatus::Expired;
+                order.updated_at = now;
+                self.publish_order_event(&order, &[]).await?;
+                metrics::counter!("orders_expired").increment(1);
+                continue;
+            }
+
+            if let Some(last) = self.match_triggered(order).await? {
+                queue.extend(
+                    self.stops
+                        .trigger(symbol, TriggerSource::LastTrade, last)
+                        .into_iter()
+                        .map(|order| (order, TriggerSource::LastTrade, last)),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Match a validated order against its book and publish the results
+    ///
+    /// Returns the price of the last trade, if any.
+    async fn match_order(
+        &self,
+        order: Order,
+        start: std::time::Instant,
+    ) -> Result<Option<Decimal>> {
+        let now = self.clock.now();
+
+        // Get order book
+        let book = self.get_order_book(&order.symbol)?;
+
+        if self.market_state(&order.symbol) != MarketState::Open {
+            return self.collect_for_auction(&book, order).await;
+        }
+        // The halt lifted or the session opened since the book was last
+        // touched
+        let uncrossed_at = if book.is_crossed() {
+            self.uncross(&book).await?
+        } else {
+            None
+        };
+
+        // Process through matching engine
+        let arrival_bbo = book.get_bbo();
+        let result = book.process_order(order.clone());
+        let updated_order = result.order;
+        let trades = result.trades;
+
+        // Settle before the orders' events release what they locked
+        if let Some(accounts) = &self.accounts {
+            for trade in &trades {
+                accounts.settle(trade, self.taker_fee(trade));
+            }
+        }
+
+        // Record latency
+        let latency = start.elapsed();
+        metrics::histogram!("matching_latency_us").record(latency.as_micros() as f64);
+
+        self.publish_bbo(&book).await;
+        self.quality
+            .record(&updated_order, &trades, arrival_bbo, self.clock.now());
+        if !trades.is_empty() {
+            self.stats.matched(&updated_order.symbol);
+            self.stats.trades(&updated_order.symbol, trades.len());
+        }
+        self.stats
+            .sequence(&updated_order.symbol, book.book_sequence());
+
+        // Publish order accepted event
+        self.publish_order_event(&updated_order, &trades).await?;
+
+        // Publish trade events
+        for trade in &trades {
+            if let Some(journal) = &self.journal {
+                journal.record(trade);
+            }
+            self.fees.accrue(trade, self.taker_fee(trade));
+            self.orders.record_fill(trade);
+            self.market_stream.publish_trade(trade);
+            self.publish_trade_event(trade).await?;
+            metrics::counter!("trades_executed").increment(1);
+        }
+        self.check_breaker(&updated_order.symbol, &trades).await?;
+
+        // Publish resting orders removed by self-trade prevention
+        for cancelled in &result.self_trade_cancels {
+            warn!(
+                maker_order_id = %cancelled.order_id,
+                taker_order_id = %updated_order.id,
+                user_id = %cancelled.user_id,
+                quantity = %cancelled.remaining_quantity,
+                "Self-trade prevented, resting order cancelled"
+            );
+            self.publish_cancel_event(
+                cancelled.order_id,
+                &cancelled.client_order_id,
+                &updated_order.symbol,
+                "self_trade_prevention",
+            )
+            .await?;
+            metrics::counter!("self_trades_prevented").increment(1);
+            if let Some(parent_id) = self.algos.on_child_removed(cancelled.order_id, now) {
+                self.publish_parent_event(parent_id).await?;
+            }
+        }
+
+        // The incoming order's own remainder, under cancel-taker policies
+        if result.taker_self_trade_cancelled {
+            warn!(
+                order_id = %updated_order.id,
+                policy = ?updated_order.self_trade_prevention,
+                quantity = %updated_order.remaining_quantity,
+                "Self-trade prevented, incoming order cancelled"
+            );
+            self.publish_cancel_event(
+                updated_order.id,
+                &updated_order.client_order_id,
+                &updated_order.symbol,
+                "self_trade_prevention",
+            )
+            .await?;
+            metrics::counter!("self_trades_prevented").increment(1);
+        }
+
+        // Roll child fills up into their algo parents
+        let mut parents: Vec<Uuid> = trades.iter().flat_map(|t| self.algos.on_trade(t)).collect();
+        parents.extend(self.algos.on_child_processed(&updated_order));
+        parents.sort();
+        parents.dedup();
+        for parent_id in parents {
+            self.publish_parent_event(parent_id).await?;
+        }
+
+        info!(
+            order_id = %updated_order.id,
+            status = ?updated_order.status,
+            trades = trades.len(),
+            latency_us = latency.as_micros(),
+            "Order processed"
+        );
+
+        Ok(trades.last().map(|t| t.price).or(uncrossed_at))
+    }
+
+    /// Rest an order in a halted book's auction without matching it
+    async fn collect_for_auction(&self, book: &OrderBook, order: Order) -> Result<Option<Decimal>> {
+        if !auction::accepts(&order) {
+            // A stop triggered during the halt, with nothing to execute against
+            self.reject_order(order, TradingError::MarketClosed.reason())
+                .await?;
+            return Ok(None);
+        }
+
+        let order = book.rest_order(order);
+        self.stats.sequence(&order.symbol, book.book_sequence());
+        self.publish_order_event(&order, &[]).await?;
+        if let Some(parent_id) = self.algos.on_child_processed(&order) {
+            self.publish_parent_event(parent_id).await?;
+        }
+        metrics::counter!("auction_orders_collected").increment(1);
+        info!(order_id = %order.id, "Order collected for auction");
+        Ok(None)
+    }
+
+    /// Uncross `symbol`'s book if it is open again, then trigger the stops
+    /// the uncross price reaches
+    async fn reopen(&self, symbol: &Symbol) -> Result<()> {
+        let Some(book) = self.order_books.get(&symbol.0).map(|b| b.clone()) else {
+            return Ok(());
+        };
+        if !book.is_crossed() || self.market_state(symbol) != MarketState::Open {
+            return Ok(());
+        }
+        match self.uncross(&book).await? {
+            Some(price) => {
+                self.trigger_stops(symbol, TriggerSource::LastTrade, price)
+                    .await
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Feed trade prices to the circuit breaker, halting the symbol when
+    /// they move too far within its window
+    async fn check_breaker(&self, symbol: &Symbol, trades: &[Trade]) -> Result<()> {
+        let (_, breaker_bps) = self.protection_bps(symbol);
+        let now = self.clock.now();
+        for trade in trades {
+            let Some(trip) = self
+                .protection
+                .on_trade(&symbol.0, trade.price, now, breaker_bps)
+            else {
+                continue;
+            };
+            metrics::counter!("symbol_halts", "reason" => HaltReason::CircuitBreaker.as_str())
+                .increment(1);
+            warn!(
+                symbol = %symbol,
+                from = %trip.from,
+                to = %trip.to,
+                move_bps = %trip.move_bps,
+                "Circuit breaker tripped, symbol halted"
+            );
+            self.publish(SymbolHalted {
+                symbol: symbol.clone(),
+                reason: HaltReason::CircuitBreaker.as_str().to_string(),
+                reference_price: Some(trip.from),
+                price: Some(trip.to),
+                timestamp: now,
+            })
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Trade a crossed book at its equilibrium price and publish the results
+    ///
+    /// Returns the uncross price if anything traded.
+    async fn uncross(&self, book: &OrderBook) -> Result<Option<Decimal>> {
+        let (bids, asks) = book.crossing_interest();
+        let Some(equilibrium) = auction::equilibrium(&bids, &asks) else {
+            return Ok(None);
+        };
+        let symbol = book.symbol().clone();
+        let result = book.uncross(equilibrium.price);
+
+        for trade in &result.trades {
+            if let Some(journal) = &self.journal {
+                journal.record(trade);
+            }
+            self.fees.accrue(trade, self.taker_fee(trade));
+            if let Some(accounts) = &self.accounts {
+                accounts.settle(trade, self.taker_fee(trade));
+            }
+            self.orders.record_fill(trade);
+            self.orders.record_taker_fill(trade);
+            self.market_stream.publish_trade(trade);
+        }
+
+        // Each bid fills completely before the next, so a taker's trades
+        // are consecutive
+        let mut takers: Vec<Uuid> = result.trades.iter().map(|t| t.taker_order_id).collect();
+        takers.dedup();
+        for taker in takers {
+            let Some(order) = self.orders.get(taker) else {
+                continue;
+            };
+            let trades: Vec<Trade> = result
+                .trades
+                .iter()
+                .filter(|t| t.taker_order_id == taker)
+                .cloned()
+                .collect();
+            self.publish_order_event(&order, &trades).await?;
+        }
+
+        for trade in &result.trades {
+            self.publish_trade_event(trade).await?;
+            metrics::counter!("trades_executed").increment(1);
+        }
+        self.check_breaker(&symbol, &result.trades).await?;
+
+        let now = self.clock.now();
+        for cancelled in &result.self_trade_cancels {
+            self.publish_cancel_event(
+                cancelled.order_id,
+                &cancelled.client_order_id,
+                &symbol,
+                "self_trade_prevention",
+            )
+            .await?;
+            metrics::counter!("self_trades_prevented").increment(1);
+            if let Some(parent_id) = self.algos.on_child_removed(cancelled.order_id, now) {
+                self.publish_parent_event(parent_id).await?;
+            }
+        }
+
+        let mut parents: Vec<Uuid> = result
+            .trades
+            .iter()
+            .flat_map(|t| self.algos.on_trade(t))
+            .collect();
+        parents.sort();
+        parents.dedup();
+        for parent_id in parents {
+            self.publish_parent_event(parent_id).await?;
+        }
+
+        self.stats.trades(&symbol, result.trades.len());
+        self.stats.sequence(&symbol, book.book_sequence());
+        self.publish_bbo(book).await;
+        metrics::counter!("auction_uncrosses").increment(1);
+        info!(
+            symbol = %symbol,
+            price = %equilibrium.price,
+            quantity = %equilibrium.paired,
+            trades = result.trades.len(),
+            "Auction uncrossed"
+        );
+
+        Ok(result.trades.last().map(|t| t.price))
+    }
+
+    /// Indicative auction outcome of `symbol`, `None` unless it is halted
+    /// or pre-open
+    pub fn indicative_price(&self, symbol: &Symbol) -> Option<IndicativePrice> {
+        if !matches!(
+            self.market_state(symbol),
+            MarketState::Halted | MarketState::PreOpen
+        ) {
+            return None;
+        }
+        let (bids, asks) = self
+            .order_books
+            .get(&symbol.0)
+            .map(|book| book.crossing_interest())
+            .unwrap_or_default();
+        let equilibrium = auction::equilibrium(&bids, &asks);
+
+        Some(IndicativePrice {
+            symbol: symbol.clone(),
+            price: equilibrium.map(|e| e.price),
+            paired_quantity: equilibrium.map_or(Decimal::ZERO, |e| e.paired),
+            imbalance_quantity: equilibrium.map_or(Decimal::ZERO, |e| e.surplus.abs()),
+            imbalance_side: equilibrium.and_then(|e| e.imbalance_side()),
+            timestamp: self.clock.now(),
+        })
+    }
+
+    /// Process order cancellation
+    #[instrument(skip(self), fields(order_id = %order_id, symbol = %symbol))]
+    async fn process_cancel(&self, order_id: uuid::Uuid, symbol: Symbol) -> Result<()> {
+        if !self.is_listed(&symbol) {
+            warn!("Cancel for unlisted symbol");
+            return Ok(());
+        }
+        let book = self.get_order_book(&symbol)?;
+
+        if let Some(cancelled) = book.cancel_order(order_id) {
+            metrics::counter!("orders_cancelled").increment(1);
+            self.stats.sequence(&symbol, book.book_sequence());
+            self.publish_bbo(&book).await;
+            self.publish_cancel_event(
+                cancelled.order_id,
+                &cancelled.client_order_id,
+                &symbol,
+                "user_requested",
+            )
+            .await?;
+            if let Some(parent_id) = self.algos.on_child_removed(order_id, self.clock.now()) {
+                self.publish_parent_event(parent_id).await?;
+            }
+            info!("Order cancelled");
+        } else if let Some(stop) = self.stops.cancel(&symbol, order_id) {
+            metrics::counter!("orders_cancelled").increment(1);
+            metrics::gauge!("stop_orders_pending").set(self.stops.count() as f64);
+            self.publish_cancel_event(stop.id, &stop.client_order_id, &symbol, "user_requested")
+                .await?;
+            info!("Stop order cancelled");
+        } else {
+            warn!("Order not found for cancellation");
+        }
+
+        Ok(())
+    }
+
+    /// Process cancel/replace
+    ///
+    /// A replacement that only reduces the quantity takes over the
+    /// original's place in the queue; anything else is a cancel followed by
+    /// a new order. The replacement is rejected if the original already
+    /// left the book or belongs to another user or side.
+    #[instrument(skip(self, replacement), fields(order_id = %order_id, replacement_id = %replacement.id))]
+    async fn process_replace(&self, order_id: uuid::Uuid, mut replacement: Order) -> Result<()> {
+        if !self.is_listed(&replacement.symbol) {
+            // Rejected by validation; delisting already cancelled the original
+            return self.process_new_order(replacement).await;
+        }
+        let book = self.get_order_book(&replacement.symbol)?;
+
+        if let Some((user_id, side)) = book.resting_owner(order_id) {
+            if user_id != replacement.user_id || side != replacement.side {
+                warn!("Replacement does not match the resting order's owner or side");
+                return self.reject_order(replacement, "replace_mismatch").await;
+            }
+        }
+
+        let now = self.clock.now();
+        if !self.is_stale(&replacement, now) && self.validate_order(&replacement).is_ok() {
+            if let Some(amended) = book.amend_order(order_id, &mut replacement) {
+                if let Some(accounts) = &self.accounts {
+                    accounts.replace(order_id, &replacement);
+                }
+                metrics::counter!("orders_replaced").increment(1);
+                metrics::counter!("orders_amended_in_place").increment(1);
+                self.publish_bbo(&book).await;
+                self.publish_cancel_event(
+                    amended.order_id,
+                    &amended.client_order_id,
+                    &replacement.symbol,
+                    "replaced",
+                )
+                .await?;
+                if let Some(parent_id) = self.algos.on_child_removed(order_id, now) {
+                    self.publish_parent_event(parent_id).await?;
+                }
+                self.publish_order_event(&replacement, &[]).await?;
+                info!(
+                    quantity = %replacement.remaining_quantity,
+                    "Order reduced in place"
+                );
+                return Ok(());
+            }
+        }
+
+        let Some(cancelled) = book.cancel_order(order_id) else {
+            warn!("Order not found for replacement");
+            replacement.status = OrderStatus::Rejected;
+            replacement.updated_at = self.clock.now();
+            self.publish_order_event(&replacement, &[]).await?;
+            metrics::counter!("orders_rejected", "reason" => "order_not_found").increment(1);
+            return Ok(());
+        };
+
+        metrics::counter!("orders_replaced").increment(1);
+        self.publish_cancel_event(
+            cancelled.order_id,
+            &cancelled.client_order_id,
+            &replacement.symbol,
+            "replaced",
+        )
+        .await?;
+        if let Some(parent_id) = self.algos.on_child_removed(order_id, self.clock.now()) {
+            self.publish_parent_event(parent_id).await?;
+        }
+
+        self.process_new_order(replacement).await
+    }
+
+    /// Order book by symbol string, e.g. `BTC-USDT`
+    ///
+    /// A cold book is returned as a detached copy and stays evicted.
+    pub fn order_book(&self, symbol: &str) -> Option<Arc<OrderBook>> {
+        if let Some(book) = self.order_books.get(symbol) {
+            return Some(book.clone());
+        }
+        let snapshot = self.cold_books.get(symbol)?;
+        let book = OrderBook::with_clock(snapshot.symbol.clone(), self.clock.clone());
+        book.restore(&snapshot);
+        Some(Arc::new(book))
+    }
+
+    /// Evict books that are empty and idle; called from the matching loop
+    fn evict_idle_books(&self, now: std::time::Instant) {
+        let idle: Vec<String> = self
+            .order_books
+            .iter()
+            .filter(|book| self.cold_books.is_idle(book, now))
+            .map(|book| book.key().clone())
+            .collect();
+
+        for key in idle {
+            if let Some((_, book)) = self
+                .order_books
+                .remove_if(&key, |_, book| self.cold_books.is_idle(book, now))
+            {
+                self.cold_books.insert(&book);
+                metrics::counter!("order_books_evicted").increment(1);
+                info!(symbol = %key, "Idle order book evicted");
+            }
+        }
+        metrics::gauge!("order_books_cold").set(self.cold_books.cold_count() as f64);
+    }
+
+    /// Evict idle books every `interval`
+    pub async fn run_idle_evictor(&self, interval: Duration) -> Result<()> {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick completes immediately
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+            self.command_tx
+                .send(OrderCommand::EvictIdle)
+                .await
+                .map_err(|_| anyhow::anyhow!("Matching engine channel closed"))?;
+        }
+    }
+
+    /// Whether idle books are evicted
+    pub fn evicts_idle_books(&self) -> bool {
+        self.cold_books.enabled()
+    }
+
+    /// WebSocket market data fan-out
+    pub fn market_stream(&self) -> &MarketStream {
+        &self.market_stream
+    }
+
+    /// Get order book for symbol, restoring it if it was evicted
+    ///
+    /// Counts as activity on the symbol; only call from the matching loop.
+    fn get_order_book(&self, symbol: &Symbol) -> Result<Arc<OrderBook>> {
+        let key = symbol.to_string();
+        self.cold_books.touch(&key, std::time::Instant::now());
+
+        if let Some(book) = self.order_books.get(&key) {
+            return Ok(book.clone());
+        }
+
+        let snapshot = self
+            .cold_books
+            .take(&key)
+            .ok_or_else(|| TradingError::SymbolNotFound(key.clone()))?;
+        let book = Arc::new(OrderBook::with_clock(symbol.clone(), self.clock.clone()));
+        book.restore(&snapshot);
+        self.order_books.insert(key.clone(), book.clone());
+
+        metrics::counter!("order_books_restored").increment(1);
+        metrics::gauge!("order_books_cold").set(self.cold_books.cold_count() as f64);
+        info!(symbol = %key, "Idle order book restored");
+        Ok(book)
+    }
+
+    /// Order book for read-only use; does not restore evicted books
+    fn read_order_book(&self, symbol: &Symbol) -> Result<Arc<OrderBook>> {
+        self.order_book(&symbol.to_string())
+            .ok_or_else(|| TradingError::SymbolNotFound(symbol.to_string()).into())
+    }
+
+    /// Submit order to matching engine
+    pub async fn submit_order(&self, order: Order, ingress: Ingress) -> Result<()> {
+        if !self.is_ready() {
+            return Err(TradingError::EngineNotReady("recovery in progress".to_string()).into());
+        }
+        let received = std::time::Instant::now();
+        self.throttles
+            .check(order.user_id, MessageKind::Order, received)?;
+
+        let order_id = order.id;
+        self.golden_path.received(order_id, ingress, received);
+        self.command_tx
+            .send(OrderCommand::NewOrder(order))
+            .await
+            .map_err(|_| {
+                self.golden_path.forget(order_id);
+                anyhow::anyhow!("Matching engine channel closed")
+            })?;
+        Ok(())
+    }
+
+    /// Cancel order
+    ///
+    /// Cancels are throttled when the requesting user is known.
+    pub async fn cancel_order(
+        &self,
+        order_id: uuid::Uuid,
+        symbol: Symbol,
+        user_id: Option<Uuid>,
+    ) -> Result<()> {
+        if let Some(user_id) = user_id {
+            self.throttles
+                .check(user_id, MessageKind::Cancel, std::time::Instant::now())?;
+        }
+
+        self.command_tx
+            .send(OrderCommand::CancelOrder { order_id, symbol })
+            .await
+            .map_err(|_| anyhow::anyhow!("Matching engine channel closed"))?;
+        Ok(())
+    }
+
+    /// Cancel a resting order and submit `replacement` in its place
+    pub async fn replace_order(
+        &self,
+        order_id: uuid::Uuid,
+        replacement: Order,
+        ingress: Ingress,
+    ) -> Result<()> {
+        if !self.is_ready() {
+            return Err(TradingError::EngineNotReady("recovery in progress".to_string()).into());
+        }
+        let received = std::time::Instant::now();
+        self.throttles
+            .check(replacement.user_id, MessageKind::Order, received)?;
+
+        let replacement_id = replacement.id;
+        self.golden_path.received(replacement_id, ingress, received);
+        self.command_tx
+            .send(OrderCommand::ReplaceOrder {
+                order_id,
+                replacement,
+            })
+            .await
+            .map_err(|_| {
+                self.golden_path.forget(replacement_id);
+                anyhow::anyhow!("Matching engine channel closed")
+            })?;
+        Ok(())
+    }
+
+    /// Submit orders as one command batch; returns each order's admission,
+    /// in order, or an error if none could be sent
+    pub async fn submit_orders(
+        &self,
+        orders: Vec<Order>,
+        ingress: Ingress,
+    ) -> Result<Vec<Result<()>>> {
+        if !self.is_ready() {
+            return Err(TradingError::EngineNotReady("recovery in progress".to_string()).into());
+        }
+        let received = std::time::Instant::now();
+
+        let mut commands = Vec::with_capacity(orders.len());
+        let admitted: Vec<Result<()>> = orders
+            .into_iter()
+            .map(|order| {
+                self.throttles
+                    .check(order.user_id, MessageKind::Order, received)?;
+                self.golden_path.received(order.id, ingress, received);
+                commands.push(OrderCommand::NewOrder(order));
+                Ok(())
+            })
+            .collect();
+
+        self.send_batch(commands).await?;
+        Ok(admitted)
+    }
+
+    /// Cancel orders as one command batch; returns each cancel's
+    /// admission, in order, or an error if none could be sent
+    ///
+    /// Cancels are throttled when the requesting user is known.
+    pub async fn cancel_orders(
+        &self,
+        cancels: Vec<(Uuid, Symbol)>,
+        user_id: Option<Uuid>,
+    ) -> Result<Vec<Result<()>>> {
+        let received = std::time::Instant::now();
+
+        let mut commands = Vec::with_capacity(cancels.len());
+        let admitted: Vec<Result<()>> = cancels
+            .into_iter()
+            .map(|(order_id, symbol)| {
+                if let Some(user_id) = user_id {
+                    self.throttles
+                        .check(user_id, MessageKind::Cancel, received)?;
+                }
+                commands.push(OrderCommand::CancelOrder { order_id, symbol });
+                Ok(())
+            })
+            .collect();
+
+        self.send_batch(commands).await?;
+        Ok(admitted)
+    }
+
+    /// Upper bound on orders or cancels in one batch
+    pub fn max_batch_size(&self) -> usize {
+        self.max_batch_size
+    }
+
+    async fn send_batch(&self, commands: Vec<OrderCommand>) -> Result<()> {
+        if commands.is_empty() {
+            return Ok(());
+        }
+        metrics::histogram!("command_batch_size").record(commands.len() as f64);
+
+        if let Err(mpsc::error::SendError(OrderCommand::Batch(commands))) =
+            self.command_tx.send(OrderCommand::Batch(commands)).await
+        {
+            for order_id in commands.iter().filter_map(Self::measured) {
+                self.golden_path.forget(order_id);
+            }
+            anyhow::bail!("Matching engine channel closed");
+        }
+        Ok(())
+    }
+
+    /// Market quality of `symbol`'s current and last published windows
+    pub fn market_quality(&self, symbol: &Symbol) -> Option<QualityReport> {
+        self.quality.report(symbol, self.clock.now())
+    }
+
+    /// Approximate memory held by each in-memory book, by symbol
+    pub fn book_memory(&self) -> BTreeMap<String, BookMemory> {
+        self.order_books
+            .iter()
+            .map(|book| (book.key().clone(), book.memory()))
+            .collect()
+    }
+
+    /// Orders resting on the books or waiting on a stop trigger
+    fn resting_orders(&self) -> usize {
+        self.order_books
+            .iter()
+            .map(|book| book.resting_orders())
+            .sum::<usize>()
+            + self.stops.count()
+    }
+
+    /// Whether new orders are refused until resting orders drain
+    pub fn is_cancel_only(&self) -> bool {
+        self.resting_cap.is_cancel_only()
+    }
+
+    /// Check an order against its user's pre-trade risk limits, alerting
+    /// on a violation
+    async fn check_risk(&self, order: &Order) -> std::result::Result<(), TradingError> {
+        let limits = self.risk_limits.limits(order.user_id);
+        if limits == RiskLimits::default() {
+            return Ok(());
+        }
+
+        let open_orders = self.orders.list_for_user(order.user_id, StatusFilter::Open);
+        let mid = match self.order_books.get(&order.symbol.0).map(|b| b.get_bbo()) {
+            Some((Some(bid), Some(ask))) => Some((bid + ask) / Decimal::TWO),
+            _ => None,
+        };
+        let Err(violation) = risk::check_order(
+            &limits,
+            order,
+            &open_orders,
+            self.notional_price(order),
+            mid,
+        ) else {
+            return Ok(());
+        };
+
+        metrics::counter!("risk_checks_failed", "check" => violation.check.as_str()).increment(1);
+        let alert = RiskAlert::builder(
+            PreTradeLimitMetadata {
+                order_id: order.id,
+                symbol: order.symbol.clone(),
+                check: violation.check.as_str().to_string(),
+                value: violation.value,
+                limit: violation.limit,
+            },
+            format!("Order {} rejected: {}", order.id, violation),
+        )
+        .user_id(order.user_id)
+        .timestamp(self.clock.now())
+        .build();
+        if let Err(e) = self.publish(alert).await {
+            warn!("Failed to publish pre-trade limit alert: {}", e);
+        }
+
+        Err(TradingError::from(&violation))
+    }
+
+    /// Lock the balance an order needs, when accounts are enabled
+    fn reserve_funds(&self, order: &Order) -> std::result::Result<(), TradingError> {
+        let Some(accounts) = &self.accounts else {
+            return Ok(());
+        };
+        let cost = match (order.side, order.price.or(order.stop_price)) {
+            (Side::Sell, _) => None,
+            (Side::Buy, Some(price)) => Some(price * order.remaining_quantity),
+            (Side::Buy, None) => self
+                .order_books
+                .get(&order.symbol.0)
+                .and_then(|book| book.cost_to_buy(order.remaining_quantity)),
+        };
+        accounts.reserve(order, cost)
+    }
+
+    /// Refuse new orders while the resting-order cap is exceeded
+    async fn check_resting_cap(&self) -> std::result::Result<(), TradingError> {
+        if !self.resting_cap.enabled() {
+            return Ok(());
+        }
+        self.update_resting_cap(self.resting_orders()).await;
+        if self.resting_cap.is_cancel_only() {
+            return Err(TradingError::OrderRejected(format!(
+                "Engine is cancel-only with over {} resting orders",
+                self.resting_cap.max()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Switch cancel-only mode for `resting` orders, alerting on a change
+    async fn update_resting_cap(&self, resting: usize) {
+        let Some(transition) = self.resting_cap.update(resting) else {
+            return;
+        };
+
+        let (severity, message) = match transition {
+            CapTransition::CancelOnly => {
+                error!(
+                    resting,
+                    max = self.resting_cap.max(),
+                    "Resting order cap exceeded, engine is cancel-only"
+                );
+                (
+                    AlertSeverity::Critical,
+                    format!(
+                        "{resting} resting orders exceed the cap of {}; new orders are rejected",
+                        self.resting_cap.max()
+                    ),
+                )
+            }
+            CapTransition::Resumed => {
+                info!(
+                    resting,
+                    "Resting orders drained, engine accepts orders again"
+                );
+                (
+                    AlertSeverity::Info,
+                    format!("{resting} resting orders; new orders are accepted again"),
+                )
+            }
+        };
+
+        let alert = RiskAlert::builder(
+            EngineCapacityMetadata {
+                resting_orders: resting,
+                max_resting_orders: self.resting_cap.max(),
+                cancel_only: transition == CapTransition::CancelOnly,
+            },
+            message,
+        )
+        .severity(severity)
+        .timestamp(self.clock.now())
+        .build();
+        if let Err(e) = self.publish(alert).await {
+            warn!("Failed to publish engine capacity alert: {}", e);
+        }
+    }
+
+    /// Update book memory gauges and re-check the resting-order cap every
+    /// `interval`, so cancel-only mode ends once cancels drain the books
+    pub async fn run_memory_accounting(&self, interval: Duration) -> Result<()> {
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+            let books = self.book_memory();
+            for (symbol, memory) in &books {
+                metrics::gauge!("book_resting_orders", "symbol" => symbol.clone())
+                    .set(memory.resting_orders as f64);
+                metrics::gauge!("book_price_levels", "symbol" => symbol.clone())
+                    .set(memory.price_levels as f64);
+                metrics::gauge!("book_memory_bytes", "symbol" => symbol.clone())
+                    .set(memory.bytes as f64);
+            }
+
+            let resting =
+                books.values().map(|m| m.resting_orders).sum::<usize>() + self.stops.count();
+            metrics::gauge!("engine_resting_orders").set(resting as f64);
+            metrics::gauge!("engine_book_memory_bytes")
+                .set(books.values().map(|m| m.bytes).sum::<usize>() as f64);
+            self.update_resting_cap(resting).await;
+        }
+    }
+
+    /// Order and trade counts for every symbol the engine has processed
+    pub fn matching_stats(&self) -> BTreeMap<String, SymbolStats> {
+        self.stats.all()
+    }
+
+    /// Trade journal for replay, if enabled
+    pub fn journal(&self) -> Option<&TradeJournal> {
+        self.journal.as_ref()
+    }
+
+    /// Persistent order and trade history, when enabled
+    pub fn history(&self) -> Option<&Arc<HistoryStore>> {
+        self.history.as_ref()
+    }
+
+    /// Per-user message throttles
+    pub fn throttles(&self) -> &Throttles {
+        &self.throttles
+    }
+
+    /// Per-user pre-trade risk limits
+    pub fn risk_limits(&self) -> &RiskLimitsRegistry {
+        &self.risk_limits
+    }
+
+    pub fn accounts_enabled(&self) -> bool {
+        self.accounts.is_some()
+    }
+
+    /// A user's balances by asset, empty when accounts are disabled
+    pub fn user_balances(&self, user_id: Uuid) -> Vec<AccountBalance> {
+        self.accounts
+            .as_ref()
+            .map(|accounts| accounts.user_balances(user_id))
+            .unwrap_or_default()
+    }
+
+    /// Deposit into, or for a negative amount withdraw from, a user's
+    /// available balance through the matching loop
+    pub async fn transfer(
+        &self,
+        user_id: Uuid,
+        asset: String,
+        amount: Decimal,
+    ) -> Result<std::result::Result<Balance, TradingError>> {
+        let (reply, rx) = oneshot::channel();
+        self.command_tx
+            .send(OrderCommand::Transfer {
+                user_id,
+                asset,
+                amount,
+                reply,
+            })
+            .await
+            .map_err(|_| anyhow::anyhow!("Matching engine channel closed"))?;
+        Ok(rx.await?)
+    }
+
+    fn process_transfer(
+        &self,
+        user_id: Uuid,
+        asset: &str,
+        amount: Decimal,
+    ) -> std::result::Result<Balance, TradingError> {
+        let Some(accounts) = &self.accounts else {
+            return Err(TradingError::OrderRejected(
+                "Accounts are disabled".to_string(),
+            ));
+        };
+        let balance = if amount.is_sign_negative() {
+            accounts.withdraw(user_id, asset, -amount)?
+        } else {
+            accounts.deposit(user_id, asset, amount)
+        };
+        info!(user_id = %user_id, asset, %amount, "Balance transfer applied");
+        Ok(balance)
+    }
+
+    /// Publish a rejection for an order refused before reaching the book
+    pub async fn reject_order(&self, mut order: Order, reason: &'static str) -> Result<()> {
+        order.status = OrderStatus::Rejected;
+        order.updated_at = self.clock.now();
+        metrics::counter!("orders_rejected", "reason" => reason).increment(1);
+        self.publish_order_event(&order, &[]).await
+    }
+
+    /// Current engine time
+    pub fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        self.clock.now()
+    }
+
+    /// Native execution algo parents
+    pub fn algos(&self) -> &AlgoBook {
+        &self.algos
+    }
+
+    /// Accept an algo parent order and start scheduling its children
+    pub fn submit_algo(self: &Arc<Self>, order: Order, algo: Algo) -> Result<Uuid> {
+        if !self.is_ready() {
+            return Err(TradingError::EngineNotReady("recovery in progress".to_string()).into());
+        }
+        algo.validate()?;
+        self.read_order_book(&order.symbol)?;
+
+        let parent_id = self.algos.insert(order, algo);
+        info!(parent_id = %parent_id, "Algo parent order accepted");
+
+        tokio::spawn(algo::run_parent(self.clone(), parent_id));
+        Ok(parent_id)
+    }
+
+    /// Pause a running algo parent
+    pub fn pause_algo(&self, parent_id: Uuid) -> Result<()> {
+        Ok(self
+            .algos
+            .transition(parent_id, AlgoState::Running, AlgoState::Paused)?)
+    }
+
+    /// Resume a paused algo parent
+    pub fn resume_algo(&self, parent_id: Uuid) -> Result<()> {
+        Ok(self
+            .algos
+            .transition(parent_id, AlgoState::Paused, AlgoState::Running)?)
+    }
+
+    /// Cancel an algo parent and its working children
+    pub async fn cancel_algo(&self, parent_id: Uuid) -> Result<()> {
+        let open_children = self.algos.cancel(parent_id, self.clock.now())?;
+        let symbol = self
+            .algos
+            .get(parent_id)
+            .map(|p| p.order.symbol)
+            .ok_or_else(|| TradingError::OrderNotFound(parent_id.to_string()))?;
+
+        for child_id in open_children {
+            self.cancel_order(child_id, symbol.clone(), None).await?;
+        }
+
+        self.publish_parent_event(parent_id).await
+    }
+
+    /// Publish the consolidated state of an algo parent
+    async fn publish_parent_event(&self, parent_id: Uuid) -> Result<()> {
+        match self.algos.get(parent_id) {
+            Some(parent) => self.publish_order_event(&parent.order, &[]).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Get order book depth and its book sequence, capped at the
+    /// configured maximum levels
+    pub fn get_depth(
+        &self,
+        symbol: &Symbol,
+        levels: usize,
+    ) -> Result<(Vec<common::PriceLevel>, Vec<common::PriceLevel>, u64)> {
+        let book = self.read_order_book(symbol)?;
+        Ok(book.get_sequenced_depth(levels.min(self.max_depth_levels)))
+    }
+
+    /// Get best bid/offer
+    #[allow(dead_code)]
+    pub fn get_bbo(
+        &self,
+        symbol: &Symbol,
+    ) -> Result<(Option<rust_decimal::Decimal>, Option<rust_decimal::Decimal>)> {
+        let book = self.read_order_book(symbol)?;
+        Ok(book.get_bbo())
+    }
+
+    /// Publish BBO to Redis if the fast-path is enabled, and book changes
+    /// to WebSocket clients
+    async fn publish_bbo(&self, book: &OrderBook) {
+        self.market_stream.publish_book(book);
+        if let Some(publisher) = &self.bbo_publisher {
+            publisher
+                .publish(book.symbol(), book.get_bbo(), book.book_sequence())
+                .await;
+        }
+        let deltas = self
+            .book_feed
+            .as_ref()
+            .and_then(|feed| feed.on_change(book, std::time::Instant::now(), self.clock.now()));
+        if let Some(deltas) = deltas {
+            self.publish_book_deltas(deltas).await;
+        }
+    }
+
+    async fn publish_book_deltas(&self, deltas: OrderBookDeltas) {
+        let symbol = deltas.symbol.clone();
+        match self.publish(deltas).await {
+            Ok(()) => metrics::counter!("book_deltas_published").increment(1),
+            Err(e) => warn!(symbol = %symbol, "Failed to publish book deltas: {}", e),
+        }
+    }
+
+    /// Publish conflated deltas whose interval elapsed and due snapshots
+    async fn flush_book_feed(&self) {
+        let Some(feed) = &self.book_feed else {
+            return;
+        };
+        let books: Vec<Arc<OrderBook>> = self
+            .order_books
+            .iter()
+            .map(|book| book.value().clone())
+            .collect();
+        let messages = feed.flush(
+            books.iter().map(|b| b.as_ref()),
+            std::time::Instant::now(),
+            self.clock.now(),
+        );
+
+        for snapshot in messages.snapshots {
+            let symbol = snapshot.symbol.clone();
+            match self.publish(snapshot).await {
+                Ok(()) => metrics::counter!("book_snapshots_published").increment(1),
+                Err(e) => warn!(symbol = %symbol, "Failed to publish book snapshot: {}", e),
+            }
+        }
+        for deltas in messages.deltas {
+            self.publish_book_deltas(deltas).await;
+        }
+    }
+
+    /// How often the book feed needs flushing; `None` when it is disabled
+    pub fn book_feed_interval(&self) -> Option<Duration> {
+        self.book_feed.as_ref().map(BookFeed::flush_interval)
+    }
+
+    /// Flush the book feed every `interval`
+    pub async fn run_book_feed_flusher(&self, interval: Duration) -> Result<()> {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            self.command_tx
+                .send(OrderCommand::FlushBookFeed)
+                .await
+                .map_err(|_| anyhow::anyhow!("Matching engine channel closed"))?;
+        }
+    }
+
+    /// Fee the taker pays on a trade, in the quote asset
+    fn taker_fee(&self, trade: &Trade) -> Decimal {
+        trade.quote_quantity * Decimal::new(self.taker_fee_bps as i64, 4)
+    }
+
+    /// Build the capped fills list for an order event
+    fn fill_summaries(&self, trades: &[Trade]) -> (Vec<FillSummary>, bool) {
+        fill_summaries(trades, self.max_fills_per_event, |t| self.taker_fee(t))
+    }
+
+    /// Publish order event to Kafka
+    async fn publish_order_event(&self, order: &Order, trades: &[Trade]) -> Result<()> {
+        self.orders.upsert(order);
+        if let Some(accounts) = self.accounts.as_ref().filter(|_| order.is_complete()) {
+            accounts.release(order.id);
+        }
+        if let Some(history) = &self.history {
+            history.record(HistoryRecord::Order {
+                order: order.clone(),
+                trades: trades.to_vec(),
+            });
+        }
+        if order.status == OrderStatus::Rejected {
+            self.stats.rejected(&order.symbol);
+        }
+        let (fills, fills_truncated) = self.fill_summaries(trades);
+
+        self.publish(OrderUpdated {
+            order_id: order.id,
+            client_order_id: order.client_order_id.clone(),
+            symbol: order.symbol.clone(),
+            status: order.status,
+            filled_quantity: order.filled_quantity,
+            remaining_quantity: order.remaining_quantity,
+            avg_fill_price: order.avg_fill_price,
+            fills,
+            fills_truncated,
+            timestamp: order.updated_at,
+        })
+        .await
+    }
+
+    /// Publish order cancellation event to Kafka
+    async fn publish_cancel_event(
+        &self,
+        order_id: uuid::Uuid,
+        client_order_id: &str,
+        symbol: &Symbol,
+        reason: &str,
+    ) -> Result<()> {
+        let now = self.clock.now();
+        self.orders.cancel(order_id, now);
+        if let Some(accounts) = &self.accounts {
+            accounts.release(order_id);
+        }
+        if let Some(history) = &self.history {
+            history.record(HistoryRecord::Cancel { order_id, at: now });
+        }
+        self.stats.cancelled(symbol);
+        self.publish(OrderCancelled {
+            order_id,
+            client_order_id: client_order_id.to_string(),
+            symbol: symbol.clone(),
+            reason: reason.to_string(),
+            timestamp: now,
+        })
+        .await
+    }
+
+    /// Publish trade event to Kafka
+    async fn publish_trade_event(&self, trade: &Trade) -> Result<()> {
+        self.publish(TradeExecuted {
+            trade: trade.clone(),
+        })
+        .await
+    }
+
+    /// Publish an event to its payload's topic and partition key
+    async fn publish<T: TypedEvent>(&self, payload: T) -> Result<()> {
+        if self.audit {
+            return Ok(());
+        }
+        self.events.publish(payload).await
+    }
+
+    /// Listed symbols
+    pub fn symbols(&self) -> Vec<Symbol> {
+        self.symbols.read().clone()
+    }
+
+    pub fn is_listed(&self, symbol: &Symbol) -> bool {
+        self.symbols.read().contains(symbol)
+    }
+
+    /// List a symbol at runtime; `false` if it is already listed
+    pub async fn add_symbol(&self, symbol: Symbol, config: Option<SymbolConfig>) -> Result<bool> {
+        let (reply, rx) = oneshot::channel();
+        self.command_tx
+            .send(OrderCommand::AddSymbol {
+                symbol,
+                config,
+                reply,
+            })
+            .await
+            .map_err(|_| anyhow::anyhow!("Matching engine channel closed"))?;
+        Ok(rx.await?)
+    }
+
+    /// Delist a symbol, cancelling its resting orders
+    ///
+    /// Commands already queued for the symbol are processed first; later
+    /// orders are rejected. Returns the number of cancelled orders, or
+    /// `None` if the symbol is not listed.
+    pub async fn delist_symbol(&self, symbol: Symbol) -> Result<Option<usize>> {
+        let (reply, rx) = oneshot::channel();
+        self.command_tx
+            .send(OrderCommand::DelistSymbol { symbol, reply })
+            .await
+            .map_err(|_| anyhow::anyhow!("Matching engine channel closed"))?;
+        Ok(rx.await?)
+    }
+
+    /// Halt matching on a symbol until `resume_symbol`
+    ///
+    /// Its book collects orders for an auction meanwhile. Returns `false`
+    /// if the symbol is not listed or already halted by the engine.
+    pub async fn halt_symbol(&self, symbol: Symbol) -> Result<bool> {
+        let (reply, rx) = oneshot::channel();
+        self.command_tx
+            .send(OrderCommand::Halt { symbol, reply })
+            .await
+            .map_err(|_| anyhow::anyhow!("Matching engine channel closed"))?;
+        Ok(rx.await?)
+    }
+
+    /// Lift an admin or circuit breaker halt and uncross the book
+    ///
+    /// A reference-data halt stays in force. Returns `false` if the
+    /// engine has not halted the symbol.
+    pub async fn resume_symbol(&self, symbol: Symbol) -> Result<bool> {
+        let (reply, rx) = oneshot::channel();
+        self.command_tx
+            .send(OrderCommand::Resume { symbol, reply })
+            .await
+            .map_err(|_| anyhow::anyhow!("Matching engine channel closed"))?;
+        Ok(rx.await?)
+    }
+
+    /// Symbols halted by an admin or a circuit breaker
+    pub fn halts(&self) -> BTreeMap<String, Halt> {
+        self.protection.halts()
+    }
+
+    /// Add a symbol and an empty book for it, if not already listed
+    fn list_symbol(&self, symbol: &Symbol) -> bool {
+        let mut symbols = self.symbols.write();
+        if symbols.contains(symbol) {
+            return false;
+        }
+        symbols.push(symbol.clone());
+
+        let key = symbol.to_string();
+        self.cold_books.touch(&key, std::time::Instant::now());
+        if self.cold_books.get(&key).is_none() {
+            self.order_books.entry(key).or_insert_with(|| {
+                Arc::new(OrderBook::with_clock(symbol.clone(), self.clock.clone()))
+            });
+        }
+        true
+    }
+
+    async fn process_add_symbol(
+        &self,
+        symbol: Symbol,
+        config: Option<SymbolConfig>,
+    ) -> Result<bool> {
+        if !self.list_symbol(&symbol) {
+            return Ok(false);
+        }
+        if let Some(config) = &config {
+            self.symbol_configs
+                .write()
+                .insert(symbol.to_string(), config.clone());
+        }
+
+        info!(symbol = %symbol, ?config, "Symbol listed");
+        self.publish(SymbolAdded {
+            symbol,
+            config,
+            timestamp: self.clock.now(),
+        })
+        .await?;
+        Ok(true)
+    }
+
+    async fn process_halt(&self, symbol: &Symbol) -> Result<bool> {
+        let now = self.clock.now();
+        if !self.is_listed(symbol) || !self.protection.halt(&symbol.0, HaltReason::Admin, now) {
+            return Ok(false);
+        }
+
+        metrics::counter!("symbol_halts", "reason" => HaltReason::Admin.as_str()).increment(1);
+        info!(symbol = %symbol, "Symbol halted");
+        self.publish(SymbolHalted {
+            symbol: symbol.clone(),
+            reason: HaltReason::Admin.as_str().to_string(),
+            reference_price: None,
+            price: None,
+            timestamp: now,
+        })
+        .await?;
+        Ok(true)
+    }
+
+    async fn process_resume(&self, symbol: &Symbol) -> Result<bool> {
+        if !self.protection.resume(&symbol.0) {
+            return Ok(false);
+        }
+
+        info!(symbol = %symbol, "Symbol resumed");
+        self.publish(SymbolResumed {
+            symbol: symbol.clone(),
+            timestamp: self.clock.now(),
+        })
+        .await?;
+        self.reopen(symbol).await?;
+        Ok(true)
+    }
+
+    async fn process_delist_symbol(&self, symbol: Symbol) -> Result<Option<usize>> {
+        let key = symbol.to_string();
+        {
+            let mut symbols = self.symbols.write();
+            let Some(index) = symbols.iter().position(|s| *s == symbol) else {
+                return Ok(None);
+            };
+            symbols.remove(index);
+        }
+        self.symbol_configs.write().remove(&key);
+        self.protection.remove(&key);
+        if let Some(feed) = &self.book_feed {
+            feed.remove(&key);
+        }
+
+        // Cold books have no resting orders
+        self.cold_books.take(&key);
+        let mut cancelled = 0;
+        if let Some((_, book)) = self.order_books.remove(&key) {
+            let snapshot = book.snapshot();
+            for resting in snapshot.bids.iter().chain(&snapshot.asks) {
+                let Some(order) = book.cancel_order(resting.order_id) else {
+                    continue;
+                };
+                cancelled += 1;
+                self.publish_cancel_event(
+                    order.order_id,
+                    &order.client_order_id,
+                    &symbol,
+                    "delisted",
+                )
+                .await?;
+                if let Some(parent_id) = self
+                    .algos
+                    .on_child_removed(order.order_id, self.clock.now())
+                {
+                    self.publish_parent_event(parent_id).await?;
+                }
+            }
+            self.publish_bbo(&book).await;
+        }
+        for stop in self.stops.remove_symbol(&symbol) {
+            cancelled += 1;
+            self.publish_cancel_event(stop.id, &stop.client_order_id, &symbol, "delisted")
+                .await?;
+        }
+        metrics::gauge!("stop_orders_pending").set(self.stops.count() as f64);
+        metrics::counter!("orders_cancelled").increment(cancelled as u64);
+        metrics::gauge!("order_books_cold").set(self.cold_books.cold_count() as f64);
+
+        info!(symbol = %symbol, cancelled, "Symbol delisted");
+        self.publish(SymbolDelisted {
+            symbol,
+            cancelled_orders: cancelled,
+            timestamp: self.clock.now(),
+        })
+        .await?;
+        Ok(Some(cancelled))
+    }
+}
+
+/// Trading rules for the built-in symbols
+fn default_symbol_configs() -> HashMap<String, SymbolConfig> {
+    let min_notional = Decimal::new(5, 0);
+    [
+        ("BTC-USDT", Decimal::new(1, 2), Decimal::new(1, 5)),
+        ("ETH-USDT", Decimal::new(1, 2), Decimal::new(1, 4)),
+        ("SOL-USDT", Decimal::new(1, 3), Decimal::new(1, 2)),
+        ("AVAX-USDT", Decimal::new(1, 3), Decimal::new(1, 2)),
+    ]
+    .into_iter()
+    .map(|(symbol, tick, lot)| {
+        (
+            symbol.to_string(),
+            SymbolConfig::new(tick, lot, min_notional),
+        )
+    })
+    .collect()
+}
+
+/// Fills list of at most `max_fills` trades, and whether it omits any; a
+/// cap of 0 (lean order events) leaves fills out rather than truncating
+fn fill_summaries(
+    trades: &[Trade],
+    max_fills: usize,
+    fee: impl Fn(&Trade) -> Decimal,
+) -> (Vec<FillSummary>, bool) {
+    if max_fills == 0 {
+        return (Vec::new(), false);
+    }
+
+    let fills = trades
+        .iter()
+        .take(max_fills)
+        .map(|t| FillSummary {
+            trade_id: t.trade_id,
+            price: t.price,
+            quantity: t.quantity,
+            fee: fee(t),
+        })
+        .collect();
+
+    (fills, trades.len() > max_fills)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trades(count: u64) -> Vec<Trade> {
+        (1..=count)
+            .map(|trade_id| Trade {
+                id: Uuid::new_v4(),
+                trade_id,
+                symbol: Symbol::new("ETH", "USDT"),
+                maker_order_id: Uuid::new_v4(),
+                maker_user_id: Uuid::new_v4(),
+                taker_order_id: Uuid::new_v4(),
+                taker_user_id: Uuid::new_v4(),
+                price: Decimal::new(2000, 0),
+                quantity: Decimal::ONE,
+                quote_quantity: Decimal::new(2000, 0),
+                taker_side: Side::Buy,
+                executed_at: chrono::Utc::now(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fill_summaries_are_capped() {
+        let fee = |t: &Trade| t.quote_quantity / Decimal::new(1000, 0);
+
+        let (fills, truncated) = fill_summaries(&trades(3), 3, fee);
+        assert_eq!(fills.len(), 3);
+        assert!(!truncated);
+        assert_eq!(fills[0].fee, Decimal::new(2, 0));
+
+        let (fills, truncated) = fill_summaries(&trades(5), 3, fee);
+        assert_eq!(
+            fills.iter().map(|f| f.trade_id).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert!(truncated);
     }
 
-    /// Get supported symbols
-    pub fn symbols(&self) -> &[Symbol] {
-        &self.symbols
+    #[test]
+    fn test_lean_fill_summaries_are_not_truncated() {
+        let (fills, truncated) = fill_summaries(&trades(5), 0, |_| Decimal::ZERO);
+        assert!(fills.is_empty());
+        assert!(!truncated);
     }
 }
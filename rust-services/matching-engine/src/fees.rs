@@ -0,0 +1,211 @@
+//! Fee Accrual
+//!
+//! Taker fees are collected into one house account per asset, the quote
+//! asset of the trades that paid them. Balances are part of engine
+//! snapshots so they survive restarts alongside the books. Accruals are
+//! published as `FeesAccrued` events per window, so revenue reporting
+//! reads the events instead of re-aggregating every trade.
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use common::{FeesAccrued, Trade};
+
+/// House account balance for one asset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HouseAccount {
+    pub asset: String,
+
+    #[serde(with = "rust_decimal::serde::str")]
+    pub balance: Decimal,
+
+    /// Fee-paying fills since the account was opened
+    pub fills: u64,
+}
+
+/// House accounts plus fees accrued in the current window
+#[derive(Debug, Clone, Serialize)]
+pub struct FeesSummary {
+    pub window_start: DateTime<Utc>,
+    pub accounts: Vec<AccountSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountSummary {
+    #[serde(flatten)]
+    pub account: HouseAccount,
+
+    /// Accrued since `window_start`, not yet published
+    #[serde(with = "rust_decimal::serde::str")]
+    pub window_amount: Decimal,
+
+    pub window_fills: u64,
+}
+
+#[derive(Debug, Default)]
+struct Account {
+    balance: Decimal,
+    fills: u64,
+    window_amount: Decimal,
+    window_fills: u64,
+}
+
+/// Per-asset house accounts for collected fees
+pub struct FeeLedger {
+    accounts: DashMap<String, Account>,
+    window_start: Mutex<DateTime<Utc>>,
+}
+
+impl FeeLedger {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            accounts: DashMap::new(),
+            window_start: Mutex::new(now),
+        }
+    }
+
+    /// Credit the fee a trade's taker paid to the quote asset's account
+    pub fn accrue(&self, trade: &Trade, fee: Decimal) {
+        if fee.is_zero() {
+            return;
+        }
+        let mut account = self
+            .accounts
+            .entry(trade.symbol.quote().to_string())
+            .or_default();
+        account.balance += fee;
+        account.fills += 1;
+        account.window_amount += fee;
+        account.window_fills += 1;
+    }
+
+    /// Close the window, returning accruals for accounts that collected fees
+    pub fn close_window(&self, now: DateTime<Utc>) -> Vec<FeesAccrued> {
+        let start = std::mem::replace(&mut *self.window_start.lock(), now);
+
+        let mut accrued: Vec<FeesAccrued> = self
+            .accounts
+            .iter_mut()
+            .filter(|account| account.window_fills > 0)
+            .map(|mut account| {
+                let event = FeesAccrued {
+                    asset: account.key().clone(),
+                    window_start: start,
+                    window_end: now,
+                    amount: account.window_amount,
+                    balance: account.balance,
+                    fills: account.window_fills,
+                };
+                account.window_amount = Decimal::ZERO;
+                account.window_fills = 0;
+                event
+            })
+            .collect();
+        accrued.sort_by(|a, b| a.asset.cmp(&b.asset));
+        accrued
+    }
+
+    /// Balances of every house account, by asset
+    pub fn accounts(&self) -> Vec<HouseAccount> {
+        let mut accounts: Vec<HouseAccount> = self
+            .accounts
+            .iter()
+            .map(|account| HouseAccount {
+                asset: account.key().clone(),
+                balance: account.balance,
+                fills: account.fills,
+            })
+            .collect();
+        accounts.sort_by(|a, b| a.asset.cmp(&b.asset));
+        accounts
+    }
+
+    pub fn summary(&self) -> FeesSummary {
+        let window_start = *self.window_start.lock();
+        let accounts = self
+            .accounts()
+            .into_iter()
+            .map(|account| {
+                let window = self.accounts.get(&account.asset);
+                AccountSummary {
+                    window_amount: window.as_ref().map_or(Decimal::ZERO, |w| w.window_amount),
+                    window_fills: window.as_ref().map_or(0, |w| w.window_fills),
+                    account,
+                }
+            })
+            .collect();
+        FeesSummary {
+            window_start,
+            accounts,
+        }
+    }
+
+    /// Replace balances with those from a snapshot
+    pub fn restore(&self, accounts: &[HouseAccount]) {
+        self.accounts.clear();
+        for account in accounts {
+            self.accounts.insert(
+                account.asset.clone(),
+                Account {
+                    balance: account.balance,
+                    fills: account.fills,
+                    ..Account::default()
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{Side, Symbol};
+    use uuid::Uuid;
+
+    fn trade(base: &str, quote: &str) -> Trade {
+        Trade {
+            id: Uuid::new_v4(),
+            trade_id: 1,
+            symbol: Symbol::new(base, quote),
+            maker_order_id: Uuid::new_v4(),
+            maker_user_id: Uuid::new_v4(),
+            taker_order_id: Uuid::new_v4(),
+            taker_user_id: Uuid::new_v4(),
+            price: Decimal::new(100, 0),
+            quantity: Decimal::ONE,
+            quote_quantity: Decimal::new(100, 0),
+            taker_side: Side::Buy,
+            executed_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_accrues_per_quote_asset_and_windows() {
+        let start = Utc::now();
+        let ledger = FeeLedger::new(start);
+
+        ledger.accrue(&trade("BTC", "USDT"), Decimal::new(5, 2));
+        ledger.accrue(&trade("ETH", "USDT"), Decimal::new(3, 2));
+        ledger.accrue(&trade("ETH", "BTC"), Decimal::new(1, 6));
+        ledger.accrue(&trade("SOL", "USDC"), Decimal::ZERO);
+
+        let end = start + chrono::Duration::minutes(1);
+        let accrued = ledger.close_window(end);
+        assert_eq!(accrued.len(), 2);
+        assert_eq!(accrued[0].asset, "BTC");
+        assert_eq!(accrued[1].asset, "USDT");
+        assert_eq!(accrued[1].amount, Decimal::new(8, 2));
+        assert_eq!(accrued[1].fills, 2);
+        assert_eq!(accrued[1].window_start, start);
+
+        // Balances carry over; quiet accounts publish nothing
+        ledger.accrue(&trade("BTC", "USDT"), Decimal::new(2, 2));
+        let accrued = ledger.close_window(end + chrono::Duration::minutes(1));
+        assert_eq!(accrued.len(), 1);
+        assert_eq!(accrued[0].amount, Decimal::new(2, 2));
+        assert_eq!(accrued[0].balance, Decimal::new(10, 2));
+    }
+}
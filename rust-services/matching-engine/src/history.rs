@@ -0,0 +1,448 @@
+//! Order and Trade History
+//!
+//! Persists every order state and trade to Postgres so history outlives
+//! Kafka retention and can be queried by time range. The matching loop
+//! only queues records; a background writer drains the queue and writes
+//! each batch in one transaction, so an order and the trades it produced
+//! are stored together or not at all.
+//!
+//! Maker fills are applied in SQL from the trades, mirroring the order
+//! store. Trades are keyed by id, so replaying the order log after a
+//! snapshot restore does not store a trade, or apply its fill, twice.
+//!
+//! Records dropped because the writer fell behind leave a gap, stored in
+//! `engine_history_gaps` with the next batch. Queries over a time range
+//! touching a gap fail rather than return incomplete history; deleting the
+//! gap's row once it is backfilled from the order log makes the range
+//! queryable again.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::postgres::{PgPool, PgPoolOptions, PgRow};
+use sqlx::{Postgres, Row, Transaction};
+use thiserror::Error;
+use tokio::sync::{mpsc, Mutex};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use common::{Order, Side, Symbol, Trade};
+
+use crate::config::Config;
+
+/// Records written per transaction
+const MAX_BATCH: usize = 500;
+
+/// Delay before a failed batch is retried
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS engine_orders (
+        id UUID PRIMARY KEY,
+        client_order_id TEXT NOT NULL,
+        user_id UUID NOT NULL,
+        symbol TEXT NOT NULL,
+        side TEXT NOT NULL,
+        order_type TEXT NOT NULL,
+        time_in_force TEXT NOT NULL,
+        status TEXT NOT NULL,
+        price NUMERIC,
+        stop_price NUMERIC,
+        quantity NUMERIC NOT NULL,
+        filled_quantity NUMERIC NOT NULL,
+        remaining_quantity NUMERIC NOT NULL,
+        avg_fill_price NUMERIC,
+        created_at TIMESTAMPTZ NOT NULL,
+        updated_at TIMESTAMPTZ NOT NULL
+    )",
+    "CREATE INDEX IF NOT EXISTS engine_orders_user_created
+        ON engine_orders (user_id, created_at)",
+    "CREATE TABLE IF NOT EXISTS engine_trades (
+        id UUID PRIMARY KEY,
+        trade_id BIGINT NOT NULL,
+        symbol TEXT NOT NULL,
+        maker_order_id UUID NOT NULL,
+        maker_user_id UUID NOT NULL,
+        taker_order_id UUID NOT NULL,
+        taker_user_id UUID NOT NULL,
+        price NUMERIC NOT NULL,
+        quantity NUMERIC NOT NULL,
+        quote_quantity NUMERIC NOT NULL,
+        taker_side TEXT NOT NULL,
+        executed_at TIMESTAMPTZ NOT NULL
+    )",
+    "CREATE INDEX IF NOT EXISTS engine_trades_symbol_executed
+        ON engine_trades (symbol, executed_at)",
+    "CREATE TABLE IF NOT EXISTS engine_history_gaps (
+        id BIGSERIAL PRIMARY KEY,
+        since TIMESTAMPTZ NOT NULL,
+        until TIMESTAMPTZ NOT NULL
+    )",
+];
+
+/// A change to persist
+#[derive(Debug, Clone)]
+pub enum HistoryRecord {
+    /// Latest state of an order and the trades it just produced
+    Order { order: Order, trades: Vec<Trade> },
+    /// A resting order was cancelled
+    Cancel { order_id: Uuid, at: DateTime<Utc> },
+}
+
+impl HistoryRecord {
+    fn at(&self) -> DateTime<Utc> {
+        match self {
+            HistoryRecord::Order { order, .. } => order.updated_at,
+            HistoryRecord::Cancel { at, .. } => *at,
+        }
+    }
+}
+
+/// Period over which records were dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("history is incomplete from {since} to {until}")]
+pub struct HistoryGap {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+}
+
+impl HistoryGap {
+    fn widen(gap: Option<Self>, at: DateTime<Utc>) -> Self {
+        match gap {
+            Some(gap) => Self {
+                since: gap.since.min(at),
+                until: gap.until.max(at),
+            },
+            None => Self {
+                since: at,
+                until: at,
+            },
+        }
+    }
+
+    fn overlaps(&self, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> bool {
+        !from.is_some_and(|from| self.until < from) && !to.is_some_and(|to| self.since >= to)
+    }
+}
+
+pub struct HistoryStore {
+    pool: PgPool,
+    tx: mpsc::Sender<HistoryRecord>,
+    /// Held by the running writer; a supervised restart picks it up again
+    rx: Mutex<mpsc::Receiver<HistoryRecord>>,
+    /// Records dropped since the last batch written
+    dropped: parking_lot::Mutex<Option<HistoryGap>>,
+}
+
+impl HistoryStore {
+    /// Connect and create the history tables, or `None` when disabled
+    pub async fn from_config(config: &Config) -> Result<Option<Self>> {
+        if !config.history_enabled {
+            return Ok(None);
+        }
+
+        let pool = PgPoolOptions::new()
+            .max_connections(config.database_pool_size)
+            .connect(&config.database_url)
+            .await?;
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&pool).await?;
+        }
+        info!("Order and trade history enabled");
+
+        let (tx, rx) = mpsc::channel(config.history_queue_size);
+        Ok(Some(Self {
+            pool,
+            tx,
+            rx: Mutex::new(rx),
+            dropped: parking_lot::Mutex::new(None),
+        }))
+    }
+
+    /// Queue a record for the writer
+    ///
+    /// Never blocks the matching loop; when the writer has fallen this far
+    /// behind the record is dropped and counted, and its time joins the
+    /// gap stored with the next batch.
+    pub fn record(&self, record: HistoryRecord) {
+        let at = record.at();
+        if let Err(e) = self.tx.try_send(record) {
+            let mut dropped = self.dropped.lock();
+            *dropped = Some(HistoryGap::widen(*dropped, at));
+            metrics::counter!("history_records_dropped").increment(1);
+            warn!(error = %e, %at, "History queue full, record dropped");
+        }
+    }
+
+    /// Drain queued records into Postgres until the queue closes
+    pub async fn run_writer(&self) -> Result<()> {
+        let mut rx = self.rx.lock().await;
+        let mut batch = Vec::with_capacity(MAX_BATCH);
+
+        while rx.recv_many(&mut batch, MAX_BATCH).await > 0 {
+            let gap = self.dropped.lock().take();
+            while let Err(e) = self.write(&batch, gap).await {
+                metrics::counter!("history_write_failures").increment(1);
+                warn!(error = %e, records = batch.len(), "History write failed, retrying");
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+            metrics::counter!("history_records_written").increment(batch.len() as u64);
+            metrics::gauge!("history_queue_depth").set(rx.len() as f64);
+            batch.clear();
+        }
+
+        Ok(())
+    }
+
+    async fn write(&self, batch: &[HistoryRecord], gap: Option<HistoryGap>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        if let Some(gap) = gap {
+            sqlx::query("INSERT INTO engine_history_gaps (since, until) VALUES ($1, $2)")
+                .bind(gap.since)
+                .bind(gap.until)
+                .execute(&mut *tx)
+                .await?;
+        }
+        for record in batch {
+            match record {
+                HistoryRecord::Order { order, trades } => {
+                    upsert_order(&mut tx, order).await?;
+                    for trade in trades {
+                        insert_trade(&mut tx, trade).await?;
+                    }
+                }
+                HistoryRecord::Cancel { order_id, at } => {
+                    sqlx::query(
+                        "UPDATE engine_orders SET status = 'cancelled', updated_at = $2
+                         WHERE id = $1
+                           AND status NOT IN ('filled', 'cancelled', 'rejected', 'expired')",
+                    )
+                    .bind(order_id)
+                    .bind(at)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Gap, stored or not yet written, overlapping `[from, to)`
+    pub async fn gap(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Option<HistoryGap>> {
+        if let Some(gap) = *self.dropped.lock() {
+            if gap.overlaps(from, to) {
+                return Ok(Some(gap));
+            }
+        }
+
+        let row = sqlx::query(
+            "SELECT since, until FROM engine_history_gaps
+             WHERE ($1::timestamptz IS NULL OR until >= $1)
+               AND ($2::timestamptz IS NULL OR since < $2)
+             ORDER BY since
+             LIMIT 1",
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| {
+            Ok(HistoryGap {
+                since: row.try_get("since")?,
+                until: row.try_get("until")?,
+            })
+        })
+        .transpose()
+    }
+
+    /// Trades on `symbol` executed in `[from, to)`, oldest first
+    ///
+    /// Fails with a [`HistoryGap`] when records in the range were dropped.
+    pub async fn trades(
+        &self,
+        symbol: &Symbol,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Result<Vec<Trade>> {
+        if let Some(gap) = self.gap(from, to).await? {
+            return Err(gap.into());
+        }
+
+        let rows = sqlx::query(
+            "SELECT id, trade_id, symbol, maker_order_id, maker_user_id, taker_order_id,
+                    taker_user_id, price, quantity, quote_quantity, taker_side, executed_at
+             FROM engine_trades
+             WHERE symbol = $1
+               AND ($2::timestamptz IS NULL OR executed_at >= $2)
+               AND ($3::timestamptz IS NULL OR executed_at < $3)
+             ORDER BY executed_at, trade_id
+             LIMIT $4",
+        )
+        .bind(&symbol.0)
+        .bind(from)
+        .bind(to)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(trade_from_row).collect()
+    }
+}
+
+async fn upsert_order(tx: &mut Transaction<'_, Postgres>, order: &Order) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO engine_orders (
+            id, client_order_id, user_id, symbol, side, order_type, time_in_force, status,
+            price, stop_price, quantity, filled_quantity, remaining_quantity, avg_fill_price,
+            created_at, updated_at
+         ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+         ON CONFLICT (id) DO UPDATE SET
+            status = EXCLUDED.status,
+            filled_quantity = EXCLUDED.filled_quantity,
+            remaining_quantity = EXCLUDED.remaining_quantity,
+            avg_fill_price = EXCLUDED.avg_fill_price,
+            updated_at = EXCLUDED.updated_at",
+    )
+    .bind(order.id)
+    .bind(&order.client_order_id)
+    .bind(order.user_id)
+    .bind(&order.symbol.0)
+    .bind(label(&order.side)?)
+    .bind(label(&order.order_type)?)
+    .bind(label(&order.time_in_force)?)
+    .bind(label(&order.status)?)
+    .bind(order.price)
+    .bind(order.stop_price)
+    .bind(order.quantity)
+    .bind(order.filled_quantity)
+    .bind(order.remaining_quantity)
+    .bind(order.avg_fill_price)
+    .bind(order.created_at)
+    .bind(order.updated_at)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Store a trade and apply it to its maker order, once per trade id
+async fn insert_trade(tx: &mut Transaction<'_, Postgres>, trade: &Trade) -> Result<()> {
+    let inserted = sqlx::query(
+        "INSERT INTO engine_trades (
+            id, trade_id, symbol, maker_order_id, maker_user_id, taker_order_id,
+            taker_user_id, price, quantity, quote_quantity, taker_side, executed_at
+         ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+         ON CONFLICT (id) DO NOTHING",
+    )
+    .bind(trade.id)
+    .bind(trade.trade_id as i64)
+    .bind(&trade.symbol.0)
+    .bind(trade.maker_order_id)
+    .bind(trade.maker_user_id)
+    .bind(trade.taker_order_id)
+    .bind(trade.taker_user_id)
+    .bind(trade.price)
+    .bind(trade.quantity)
+    .bind(trade.quote_quantity)
+    .bind(label(&trade.taker_side)?)
+    .bind(trade.executed_at)
+    .execute(&mut **tx)
+    .await?
+    .rows_affected();
+
+    if inserted == 0 {
+        return Ok(());
+    }
+
+    sqlx::query(
+        "UPDATE engine_orders SET
+            avg_fill_price = (COALESCE(avg_fill_price, 0) * filled_quantity + $2)
+                / (filled_quantity + $3),
+            filled_quantity = filled_quantity + $3,
+            remaining_quantity = GREATEST(remaining_quantity - $3, 0),
+            status = CASE WHEN remaining_quantity - $3 <= 0
+                          THEN 'filled' ELSE 'partially_filled' END,
+            updated_at = $4
+         WHERE id = $1",
+    )
+    .bind(trade.maker_order_id)
+    .bind(trade.quote_quantity)
+    .bind(trade.quantity)
+    .bind(trade.executed_at)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+fn trade_from_row(row: &PgRow) -> Result<Trade> {
+    let taker_side: String = row.try_get("taker_side")?;
+    Ok(Trade {
+        id: row.try_get("id")?,
+        trade_id: row.try_get::<i64, _>("trade_id")? as u64,
+        symbol: Symbol(row.try_get("symbol")?),
+        maker_order_id: row.try_get("maker_order_id")?,
+        maker_user_id: row.try_get("maker_user_id")?,
+        taker_order_id: row.try_get("taker_order_id")?,
+        taker_user_id: row.try_get("taker_user_id")?,
+        price: row.try_get("price")?,
+        quantity: row.try_get("quantity")?,
+        quote_quantity: row.try_get("quote_quantity")?,
+        taker_side: serde_json::from_value::<Side>(serde_json::Value::String(taker_side))?,
+        executed_at: row.try_get("executed_at")?,
+    })
+}
+
+/// Column value of an enum: its serde name, as in events
+fn label<T: Serialize>(value: &T) -> Result<String> {
+    match serde_json::to_value(value)? {
+        serde_json::Value::String(label) => Ok(label),
+        other => anyhow::bail!("Expected a string label, got {other}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{OrderStatus, TimeInForce};
+
+    #[test]
+    fn test_labels_match_event_names() {
+        assert_eq!(
+            label(&OrderStatus::PartiallyFilled).unwrap(),
+            "partially_filled"
+        );
+        assert_eq!(label(&OrderStatus::Cancelled).unwrap(), "cancelled");
+        assert_eq!(label(&TimeInForce::GTC).unwrap(), "gtc");
+
+        let side = label(&Side::Sell).unwrap();
+        assert_eq!(
+            serde_json::from_value::<Side>(serde_json::Value::String(side)).unwrap(),
+            Side::Sell
+        );
+    }
+
+    #[test]
+    fn test_gap_covers_dropped_records() {
+        let t0 = Utc::now();
+        let minute = chrono::Duration::minutes(1);
+        let gap = HistoryGap::widen(None, t0);
+        let gap = HistoryGap::widen(Some(gap), t0 + minute);
+        let gap = HistoryGap::widen(Some(gap), t0 - minute);
+        assert_eq!(gap.since, t0 - minute);
+        assert_eq!(gap.until, t0 + minute);
+
+        assert!(gap.overlaps(None, None));
+        assert!(gap.overlaps(Some(t0 + minute), None));
+        assert!(!gap.overlaps(Some(t0 + minute * 2), None));
+        assert!(!gap.overlaps(None, Some(t0 - minute)));
+        assert!(gap.overlaps(Some(t0 - minute * 5), Some(t0)));
+    }
+}
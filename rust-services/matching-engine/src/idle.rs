@@ -0,0 +1,122 @@
+//! Idle Book Eviction
+//!
+//! Books for symbols that stopped trading would otherwise stay in memory
+//! forever. A book with no resting orders and no commands for
+//! `idle_book_evict_secs` is evicted from the matching loop, leaving only
+//! its snapshot (sequence and trade id counters) behind. The next command
+//! for the symbol restores it, so eviction is invisible to clients.
+//!
+//! Reads of a cold book are served from a detached copy of the snapshot
+//! and do not count as activity.
+
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+
+use crate::orderbook::{BookSnapshot, OrderBook};
+
+pub struct ColdBooks {
+    /// Inactivity after which an empty book is evicted; `None` disables
+    idle_after: Option<Duration>,
+
+    /// Last command per symbol
+    last_active: DashMap<String, Instant>,
+
+    /// Snapshots of evicted books
+    cold: DashMap<String, BookSnapshot>,
+}
+
+impl ColdBooks {
+    pub fn new(idle_after: Option<Duration>) -> Self {
+        Self {
+            idle_after,
+            last_active: DashMap::new(),
+            cold: DashMap::new(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.idle_after.is_some()
+    }
+
+    /// Record activity on a symbol
+    pub fn touch(&self, symbol: &str, now: Instant) {
+        if self.enabled() {
+            self.last_active.insert(symbol.to_string(), now);
+        }
+    }
+
+    /// Whether `book` is empty and has been inactive long enough to evict
+    pub fn is_idle(&self, book: &OrderBook, now: Instant) -> bool {
+        let Some(idle_after) = self.idle_after else {
+            return false;
+        };
+        book.is_empty()
+            && self
+                .last_active
+                .get(&book.symbol().to_string())
+                .is_none_or(|at| now.duration_since(*at) >= idle_after)
+    }
+
+    /// Keep an evicted book's snapshot
+    pub fn insert(&self, book: &OrderBook) {
+        let key = book.symbol().to_string();
+        self.last_active.remove(&key);
+        self.cold.insert(key, book.snapshot());
+    }
+
+    /// Remove a cold book's snapshot to restore it
+    pub fn take(&self, symbol: &str) -> Option<BookSnapshot> {
+        self.cold.remove(symbol).map(|(_, snapshot)| snapshot)
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<BookSnapshot> {
+        self.cold.get(symbol).map(|s| s.clone())
+    }
+
+    /// Snapshots of every cold book
+    pub fn snapshots(&self) -> Vec<BookSnapshot> {
+        self.cold.iter().map(|s| s.clone()).collect()
+    }
+
+    /// Number of evicted books
+    pub fn cold_count(&self) -> usize {
+        self.cold.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{Order, Side, Symbol};
+
+    fn order(side: Side) -> Order {
+        Order::builder().side(side).build()
+    }
+
+    #[test]
+    fn test_evicts_empty_idle_books_and_keeps_counters() {
+        let cold = ColdBooks::new(Some(Duration::from_secs(60)));
+        let book = OrderBook::new(Symbol::new("ETH", "USDT"));
+        let start = Instant::now();
+        let later = start + Duration::from_secs(61);
+
+        cold.touch("ETH-USDT", start);
+        book.process_order(order(Side::Sell));
+        assert!(!cold.is_idle(&book, later));
+
+        let first = book.process_order(order(Side::Buy)).trades[0].trade_id;
+        assert!(!cold.is_idle(&book, start + Duration::from_secs(30)));
+        assert!(cold.is_idle(&book, later));
+
+        cold.insert(&book);
+        let restored = OrderBook::new(Symbol::new("ETH", "USDT"));
+        restored.restore(&cold.take("ETH-USDT").unwrap());
+        assert_eq!(cold.cold_count(), 0);
+
+        // Trade ids continue where the evicted book left off
+        restored.process_order(order(Side::Sell));
+        let result = restored.process_order(order(Side::Buy));
+        assert_eq!(result.trades[0].trade_id, first + 1);
+    }
+}
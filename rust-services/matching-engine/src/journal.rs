@@ -0,0 +1,161 @@
+//! In-Memory Trade Journal
+//!
+//! Keeps the current UTC day's trades per symbol so they can be replayed
+//! at an accelerated rate for demos, UI testing and training environments
+//! without the archive service. Each symbol's journal is cleared when the
+//! first trade of a new day arrives.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use dashmap::DashMap;
+use tokio::sync::mpsc;
+
+use common::{Symbol, Trade};
+
+/// Longest real-time pause between replayed trades, whatever the speed
+const MAX_REPLAY_GAP: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Default)]
+struct DayJournal {
+    day: Option<NaiveDate>,
+    trades: VecDeque<Trade>,
+}
+
+/// Today's trades per symbol, oldest first
+pub struct TradeJournal {
+    max_trades_per_symbol: usize,
+    journals: DashMap<String, DayJournal>,
+}
+
+impl TradeJournal {
+    pub fn new(max_trades_per_symbol: usize) -> Self {
+        Self {
+            max_trades_per_symbol,
+            journals: DashMap::new(),
+        }
+    }
+
+    /// Append a trade, starting a new journal on a new day
+    pub fn record(&self, trade: &Trade) {
+        let day = trade.executed_at.date_naive();
+        let mut journal = self.journals.entry(trade.symbol.to_string()).or_default();
+
+        if journal.day != Some(day) {
+            journal.day = Some(day);
+            journal.trades.clear();
+        }
+
+        journal.trades.push_back(trade.clone());
+        if journal.trades.len() > self.max_trades_per_symbol {
+            journal.trades.pop_front();
+        }
+    }
+
+    /// Journaled trades executed within `[from, to]`
+    pub fn range(
+        &self,
+        symbol: &Symbol,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Vec<Trade> {
+        self.journals
+            .get(&symbol.to_string())
+            .map(|journal| {
+                journal
+                    .trades
+                    .iter()
+                    .filter(|t| from.is_none_or(|from| t.executed_at >= from))
+                    .filter(|t| to.is_none_or(|to| t.executed_at <= to))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Parse a replay speed such as `10x` or `2.5`
+pub fn parse_speed(speed: &str) -> Option<f64> {
+    speed
+        .trim()
+        .trim_end_matches(['x', 'X'])
+        .parse::<f64>()
+        .ok()
+        .filter(|s| s.is_finite() && *s > 0.0)
+}
+
+/// Send `trades` to `tx`, spacing them by their original gaps divided by `speed`
+///
+/// Stops early once the receiver is dropped.
+pub async fn replay(trades: Vec<Trade>, speed: f64, tx: mpsc::Sender<Trade>) {
+    let mut previous: Option<DateTime<Utc>> = None;
+
+    for trade in trades {
+        if let Some(previous) = previous {
+            let gap = (trade.executed_at - previous)
+                .to_std()
+                .unwrap_or_default()
+                .div_f64(speed)
+                .min(MAX_REPLAY_GAP);
+            tokio::time::sleep(gap).await;
+        }
+        previous = Some(trade.executed_at);
+
+        if tx.send(trade).await.is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::Side;
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    fn trade(symbol: &Symbol, trade_id: u64, executed_at: DateTime<Utc>) -> Trade {
+        Trade {
+            id: Uuid::new_v4(),
+            trade_id,
+            symbol: symbol.clone(),
+            maker_order_id: Uuid::new_v4(),
+            maker_user_id: Uuid::new_v4(),
+            taker_order_id: Uuid::new_v4(),
+            taker_user_id: Uuid::new_v4(),
+            price: Decimal::ONE,
+            quantity: Decimal::ONE,
+            quote_quantity: Decimal::ONE,
+            taker_side: Side::Buy,
+            executed_at,
+        }
+    }
+
+    #[test]
+    fn test_journal_keeps_current_day_only() {
+        let journal = TradeJournal::new(2);
+        let symbol = Symbol::new("ETH", "USDT");
+        let day1 = "2024-01-01T23:59:00Z".parse::<DateTime<Utc>>().unwrap();
+        let day2 = "2024-01-02T00:01:00Z".parse::<DateTime<Utc>>().unwrap();
+
+        journal.record(&trade(&symbol, 1, day1));
+        journal.record(&trade(&symbol, 2, day2));
+        journal.record(&trade(&symbol, 3, day2 + chrono::Duration::seconds(1)));
+        journal.record(&trade(&symbol, 4, day2 + chrono::Duration::seconds(2)));
+
+        // Day 1 was dropped at rollover and the cap keeps the last two
+        let ids: Vec<u64> = journal
+            .range(&symbol, None, None)
+            .iter()
+            .map(|t| t.trade_id)
+            .collect();
+        assert_eq!(ids, vec![3, 4]);
+
+        let from = day2 + chrono::Duration::seconds(2);
+        assert_eq!(journal.range(&symbol, Some(from), None).len(), 1);
+
+        assert_eq!(parse_speed("10x"), Some(10.0));
+        assert_eq!(parse_speed("0x"), None);
+    }
+}
@@ -1,19 +1,25 @@
 //! Kafka consumer for order events
 //!
-//! Consumes orders from Kafka topics and forwards to matching engine
+//! Consumes order, cancel and replace commands from Kafka topics and
+//! forwards them to the matching engine
 
 use anyhow::Result;
 use rdkafka::{
     consumer::{Consumer, StreamConsumer},
-    ClientConfig, Message,
+    ClientConfig, Message, Offset, TopicPartitionList,
 };
 use std::sync::Arc;
+use std::time::Duration;
 use tokio_stream::StreamExt;
 use tracing::{error, info, warn};
 
 use crate::config::Config;
 use crate::engine::MatchingEngine;
-use common::{events::topics, Order};
+use crate::latency::Ingress;
+use common::{
+    events::{topics, Event, OrderCancelRequested, OrderCommandEnvelope, OrderSubmitted},
+    Order, TradingError,
+};
 
 /// Run Kafka consumer
 pub async fn run_consumer(engine: Arc<MatchingEngine>, config: &Config) -> Result<()> {
@@ -25,9 +31,29 @@ pub async fn run_consumer(engine: Arc<MatchingEngine>, config: &Config) -> Resul
         .set("session.timeout.ms", "10000")
         .create()?;
 
-    consumer.subscribe(&[topics::ORDERS])?;
+    // Don't consume into half-built books
+    engine.wait_ready().await;
 
-    info!("Kafka consumer started, subscribed to {}", topics::ORDERS);
+    let offsets = engine.log_offsets();
+    if offsets.is_empty() {
+        consumer.subscribe(&[topics::ORDERS])?;
+        info!("Kafka consumer started, subscribed to {}", topics::ORDERS);
+    } else {
+        // Replay the order log from just after the restored snapshot
+        let metadata = consumer.fetch_metadata(Some(topics::ORDERS), Duration::from_secs(10))?;
+        let mut assignment = TopicPartitionList::new();
+        for topic in metadata.topics() {
+            for partition in topic.partitions() {
+                let offset = offsets
+                    .get(&partition.id())
+                    .map(|o| Offset::Offset(o + 1))
+                    .unwrap_or(Offset::Stored);
+                assignment.add_partition_offset(topics::ORDERS, partition.id(), offset)?;
+            }
+        }
+        consumer.assign(&assignment)?;
+        info!(offsets = ?offsets, "Kafka consumer replaying {} from snapshot", topics::ORDERS);
+    }
 
     let mut stream = consumer.stream();
 
@@ -39,6 +65,9 @@ pub async fn run_consumer(engine: Arc<MatchingEngine>, config: &Config) -> Resul
                         error!("Failed to process message: {}", e);
                     }
                 }
+                engine
+                    .mark_log_position(msg.partition(), msg.offset())
+                    .await?;
             }
             Err(e) => {
                 warn!("Kafka error: {}", e);
@@ -49,13 +78,111 @@ pub async fn run_consumer(engine: Arc<MatchingEngine>, config: &Config) -> Resul
     Ok(())
 }
 
+/// Submit an order, publishing a rejection if the user is throttled
+///
+/// Kafka submitters get no synchronous reply, so the rejection event is
+/// how they learn the order will never reach the book.
+async fn submit(engine: &MatchingEngine, order: Order) -> Result<()> {
+    let rejected = order.clone();
+    match engine.submit_order(order, Ingress::Kafka).await {
+        Err(e)
+            if matches!(
+                e.downcast_ref::<TradingError>(),
+                Some(TradingError::RateLimitExceeded)
+            ) =>
+        {
+            warn!(order_id = %rejected.id, user_id = %rejected.user_id, "Order throttled");
+            engine.reject_order(rejected, "rate_limited").await
+        }
+        result => result,
+    }
+}
+
 async fn process_message(engine: &MatchingEngine, payload: &[u8]) -> Result<()> {
-    // Try to parse as an order
-    let order: Order = serde_json::from_slice(payload)?;
+    let value: serde_json::Value = serde_json::from_slice(payload)?;
 
-    info!(order_id = %order.id, "Received order from Kafka");
+    // Bare orders predate the OMS envelope
+    if value.get("event_type").is_none() {
+        let order: Order = serde_json::from_value(value)?;
+        info!(order_id = %order.id, "Received order from Kafka");
+        return submit(engine, order).await;
+    }
 
-    engine.submit_order(order).await?;
+    let event: Event<serde_json::Value> = serde_json::from_value(value)?;
+
+    match event.event_type.as_str() {
+        "order_command" => {
+            let command: OrderCommandEnvelope = serde_json::from_value(event.payload)?;
+            dispatch(engine, command, event.correlation_id).await?;
+        }
+        // Single-purpose events from OMS versions before the command envelope
+        "order_submitted" => {
+            let submitted: OrderSubmitted = serde_json::from_value(event.payload)?;
+            dispatch(
+                engine,
+                OrderCommandEnvelope::Submit(submitted),
+                event.correlation_id,
+            )
+            .await?;
+        }
+        "order_cancel_requested" => {
+            let request: OrderCancelRequested = serde_json::from_value(event.payload)?;
+            dispatch(
+                engine,
+                OrderCommandEnvelope::Cancel(request),
+                event.correlation_id,
+            )
+            .await?;
+        }
+        // Our own order updates share the topic
+        _ => {}
+    }
 
     Ok(())
 }
+
+async fn dispatch(
+    engine: &MatchingEngine,
+    command: OrderCommandEnvelope,
+    correlation_id: Option<uuid::Uuid>,
+) -> Result<()> {
+    match command {
+        OrderCommandEnvelope::Submit(submitted) => {
+            info!(
+                order_id = %submitted.order.id,
+                correlation_id = ?correlation_id,
+                "Received order from Kafka"
+            );
+            submit(engine, submitted.order).await
+        }
+        OrderCommandEnvelope::Cancel(request) => {
+            info!(order_id = %request.order_id, "Received cancel from Kafka");
+            engine
+                .cancel_order(request.order_id, request.symbol, Some(request.user_id))
+                .await
+        }
+        OrderCommandEnvelope::Replace(request) => {
+            info!(
+                order_id = %request.order_id,
+                replacement_id = %request.replacement.id,
+                "Received replace from Kafka"
+            );
+            let replacement = request.replacement.clone();
+            match engine
+                .replace_order(request.order_id, request.replacement, Ingress::Kafka)
+                .await
+            {
+                Err(e)
+                    if matches!(
+                        e.downcast_ref::<TradingError>(),
+                        Some(TradingError::RateLimitExceeded)
+                    ) =>
+                {
+                    warn!(order_id = %replacement.id, user_id = %replacement.user_id, "Replace throttled");
+                    engine.reject_order(replacement, "rate_limited").await
+                }
+                result => result,
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Golden-Path Latency
+//!
+//! `matching_latency_us` times the book alone. This times what a client
+//! waits for: from an order reaching the engine, accepted by the API or
+//! read off Kafka, to the last event it produced being handed to the
+//! publisher after its command. Queueing for the matching loop,
+//! journaling, admission checks, matching and event publication all
+//! count; delivery to the broker after that is `event_publish_ms`.
+//!
+//! Each order is checked against a latency target. With
+//! `golden_path_slo_requests` and `golden_path_slo_breaches` counting
+//! orders and orders over target, the SLO burn rate over a window is
+//!
+//! ```text
+//! rate(golden_path_slo_breaches[1h]) / rate(golden_path_slo_requests[1h])
+//!   / (1 - golden_path_slo_objective)
+//! ```
+//!
+//! 1 spends the error budget exactly over the SLO period.
+
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+use uuid::Uuid;
+
+use crate::config::Config;
+
+/// Where an order entered the engine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ingress {
+    Api,
+    Kafka,
+    /// Child slice of a native execution algo
+    Algo,
+}
+
+impl Ingress {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Ingress::Api => "api",
+            Ingress::Kafka => "kafka",
+            Ingress::Algo => "algo",
+        }
+    }
+}
+
+/// Orders in flight between ingress and their last event
+pub struct GoldenPath {
+    target: Duration,
+    received: DashMap<Uuid, (Instant, Ingress)>,
+}
+
+impl GoldenPath {
+    pub fn new(target: Duration, objective: f64) -> Self {
+        metrics::gauge!("golden_path_slo_objective").set(objective);
+        metrics::gauge!("golden_path_slo_target_us").set(target.as_micros() as f64);
+        Self {
+            target,
+            received: DashMap::new(),
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            Duration::from_micros(config.golden_path_slo_target_us),
+            config.golden_path_slo_objective,
+        )
+    }
+
+    /// An order reached the engine at `at`
+    pub fn received(&self, order_id: Uuid, ingress: Ingress, at: Instant) {
+        self.received.insert(order_id, (at, ingress));
+    }
+
+    /// The order never reached the matching loop
+    pub fn forget(&self, order_id: Uuid) {
+        self.received.remove(&order_id);
+    }
+
+    /// The order's events were handed to the publisher at `at`; records
+    /// and returns its latency if its ingress was seen
+    pub fn completed(&self, order_id: Uuid, at: Instant) -> Option<Duration> {
+        let (_, (received, ingress)) = self.received.remove(&order_id)?;
+        let latency = at.saturating_duration_since(received);
+
+        let ingress = ingress.as_str();
+        metrics::histogram!("golden_path_latency_us", "ingress" => ingress)
+            .record(latency.as_micros() as f64);
+        metrics::counter!("golden_path_slo_requests", "ingress" => ingress).increment(1);
+        if latency > self.target {
+            metrics::counter!("golden_path_slo_breaches", "ingress" => ingress).increment(1);
+        }
+        Some(latency)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_runs_from_ingress_to_completion() {
+        let path = GoldenPath::new(Duration::from_millis(5), 0.999);
+        let order_id = Uuid::new_v4();
+        let start = Instant::now();
+
+        path.received(order_id, Ingress::Api, start);
+        assert_eq!(
+            path.completed(order_id, start + Duration::from_micros(750)),
+            Some(Duration::from_micros(750))
+        );
+
+        // Completed once; replayed and unknown orders are not measured
+        assert_eq!(path.completed(order_id, start), None);
+
+        path.received(order_id, Ingress::Kafka, start);
+        path.forget(order_id);
+        assert_eq!(path.completed(order_id, start), None);
+    }
+}
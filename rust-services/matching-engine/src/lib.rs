@@ -10,9 +10,36 @@
 //! - Event sourcing for audit trail
 //! - Kafka for event distribution
 
+pub mod accounts;
+pub mod algo;
 pub mod api;
+pub mod auction;
+pub mod bbo;
+pub mod bookfeed;
 pub mod config;
+pub mod display;
 pub mod engine;
+pub mod fees;
+pub mod history;
+pub mod idle;
+pub mod journal;
 pub mod kafka;
+pub mod latency;
+pub mod memory;
 pub mod metrics;
 pub mod orderbook;
+pub mod orders;
+pub mod outbox;
+pub mod protection;
+pub mod publisher;
+pub mod quality;
+pub mod reconstruction;
+pub mod risk;
+pub mod sessions;
+pub mod snapshot;
+pub mod stats;
+pub mod stops;
+pub mod stream;
+pub mod supervisor;
+pub mod throttle;
+pub mod wal;
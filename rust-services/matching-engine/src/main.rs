@@ -15,12 +15,38 @@ use std::sync::Arc;
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+mod accounts;
+mod algo;
 mod api;
+mod auction;
+mod bbo;
+mod bookfeed;
 mod config;
+mod display;
 mod engine;
+mod fees;
+mod history;
+mod idle;
+mod journal;
 mod kafka;
+mod latency;
+mod memory;
 mod metrics;
 mod orderbook;
+mod orders;
+mod outbox;
+mod protection;
+mod publisher;
+mod quality;
+mod risk;
+mod sessions;
+mod snapshot;
+mod stats;
+mod stops;
+mod stream;
+mod supervisor;
+mod throttle;
+mod wal;
 
 use config::Config;
 use engine::MatchingEngine;
@@ -29,7 +55,7 @@ use engine::MatchingEngine;
 async fn main() -> Result<()> {
     // Load configuration
     dotenvy::dotenv().ok();
-    let config = Config::load()?;
+    let config: Config = common::config::load()?;
 
     // Initialize tracing
     init_tracing(&config)?;
@@ -39,35 +65,227 @@ async fn main() -> Result<()> {
         env!("CARGO_PKG_VERSION")
     );
 
+    common::events::init_service("matching-engine");
+
     // Initialize metrics
     metrics::init_metrics(&config)?;
 
     // Create matching engine
     let engine = Arc::new(MatchingEngine::new(&config).await?);
 
-    // Start background workers
+    // Start background workers, restarted by the supervisor if they fail
+    let supervisor = engine.supervisor().clone();
     let engine_clone = engine.clone();
-    tokio::spawn(async move {
-        if let Err(e) = engine_clone.run_matching_loop().await {
-            tracing::error!("Matching loop error: {}", e);
-        }
+    supervisor.spawn("matching_loop", move || {
+        let engine = engine_clone.clone();
+        async move { engine.run_matching_loop().await }
+    });
+
+    let events = engine.events().clone();
+    supervisor.spawn("event_publisher", move || {
+        let events = events.clone();
+        async move { events.run().await }
+    });
+
+    // Audit: rebuild the books from the whole journal, print them and exit
+    if engine.is_audit() {
+        let replayed = engine.replay_journal(0).await?;
+        let snapshot = engine.capture_snapshot().await?;
+        println!("{}", serde_json::to_string_pretty(&snapshot)?);
+        info!(replayed, "Command journal audit complete");
+        return Ok(());
+    }
+
+    // Publish market quality windows
+    let engine_clone = engine.clone();
+    let interval = std::time::Duration::from_secs(config.market_quality_interval_secs);
+    supervisor.spawn("quality_publisher", move || {
+        let engine = engine_clone.clone();
+        async move { engine.run_quality_publisher(interval).await }
+    });
+
+    // Publish indicative prices of halted symbols and reopen their books
+    let engine_clone = engine.clone();
+    let interval = std::time::Duration::from_millis(config.auction_publish_interval_ms);
+    supervisor.spawn("auction_publisher", move || {
+        let engine = engine_clone.clone();
+        async move { engine.run_auction_publisher(interval).await }
+    });
+
+    // Track book memory and lift cancel-only mode once books drain
+    let engine_clone = engine.clone();
+    let interval = std::time::Duration::from_secs(config.memory_accounting_interval_secs);
+    supervisor.spawn("memory_accounting", move || {
+        let engine = engine_clone.clone();
+        async move { engine.run_memory_accounting(interval).await }
+    });
+
+    // Evict idle books
+    if engine.evicts_idle_books() {
+        let engine_clone = engine.clone();
+        let interval = std::time::Duration::from_secs(10);
+        supervisor.spawn("idle_evictor", move || {
+            let engine = engine_clone.clone();
+            async move { engine.run_idle_evictor(interval).await }
+        });
+    }
+
+    // Publish conflated book deltas and periodic book snapshots
+    if let Some(interval) = engine.book_feed_interval() {
+        let engine_clone = engine.clone();
+        supervisor.spawn("book_feed", move || {
+            let engine = engine_clone.clone();
+            async move { engine.run_book_feed_flusher(interval).await }
+        });
+    }
+
+    // Publish fees accrued into house accounts
+    let engine_clone = engine.clone();
+    let interval = std::time::Duration::from_secs(config.fee_accrual_interval_secs);
+    supervisor.spawn("fee_publisher", move || {
+        let engine = engine_clone.clone();
+        async move { engine.run_fee_publisher(interval).await }
     });
 
+    // Feed mark and index prices to stop orders waiting on them
+    if config.reference_price_poll_ms > 0 {
+        let prices = stops::ReferencePrices::new(&config.redis_url).await?;
+        let engine_clone = engine.clone();
+        let interval = std::time::Duration::from_millis(config.reference_price_poll_ms);
+        supervisor.spawn("reference_prices", move || {
+            let engine = engine_clone.clone();
+            let prices = prices.clone();
+            async move { engine.run_reference_prices(prices, interval).await }
+        });
+    }
+
+    // Keep instrument metadata in sync with the reference-data service
+    if let Some(url) = config.reference_data_url.clone() {
+        let registry = engine.instruments();
+        let brokers = config.kafka_brokers.clone();
+        let group = config.kafka_group_id.clone();
+        supervisor.spawn("reference_data_sync", move || {
+            common::refdata::client::run_sync(
+                registry.clone(),
+                url.clone(),
+                brokers.clone(),
+                group.clone(),
+            )
+        });
+    }
+
+    // Write order and trade history to Postgres
+    if let Some(history) = engine.history().cloned() {
+        supervisor.spawn("history_writer", move || {
+            let history = history.clone();
+            async move { history.run_writer().await }
+        });
+    }
+
+    // Restore books from the last snapshot and keep snapshotting
+    let recovery = std::time::Instant::now();
+    let mut journal_after = Some(0);
+    let snapshot_store = snapshot::SnapshotStore::from_config(&config).await?;
+    if let Some(store) = snapshot_store.clone() {
+        if let Some(snapshot) = store.load().await? {
+            let loaded_ms = recovery.elapsed().as_millis() as u64;
+            ::metrics::histogram!("snapshot_load_ms").record(loaded_ms as f64);
+            engine.restore(&snapshot)?;
+            journal_after = snapshot.journal_sequence;
+
+            let elapsed_ms = recovery.elapsed().as_millis() as u64;
+            let budget_ms = config.snapshot_restore_budget_ms;
+            if budget_ms > 0 && elapsed_ms > budget_ms {
+                ::metrics::counter!("snapshot_restore_over_budget").increment(1);
+                tracing::warn!(
+                    loaded_ms,
+                    elapsed_ms,
+                    budget_ms,
+                    "Snapshot restore exceeded its budget"
+                );
+            }
+        }
+
+        let engine_clone = engine.clone();
+        let interval = std::time::Duration::from_secs(config.snapshot_interval_secs);
+        supervisor.spawn("snapshotter", move || {
+            let engine = engine_clone.clone();
+            let store = store.clone();
+            async move { engine.run_snapshotter(store, interval).await }
+        });
+    }
+
+    // Re-apply commands journaled since the snapshot
+    match journal_after {
+        Some(after) => {
+            engine.replay_journal(after).await?;
+        }
+        None => tracing::warn!("Snapshot predates the command journal, journal not replayed"),
+    }
+
+    // Recovery complete - start accepting orders
+    ::metrics::gauge!("recovery_ms").set(recovery.elapsed().as_millis() as f64);
+    info!(
+        elapsed_ms = recovery.elapsed().as_millis() as u64,
+        "Recovery complete"
+    );
+    engine.mark_ready();
+
     // Start Kafka consumer
     let engine_clone = engine.clone();
     let config_clone = config.clone();
-    tokio::spawn(async move {
-        if let Err(e) = kafka::run_consumer(engine_clone, &config_clone).await {
-            tracing::error!("Kafka consumer error: {}", e);
-        }
+    supervisor.spawn("kafka_consumer", move || {
+        let engine = engine_clone.clone();
+        let config = config_clone.clone();
+        async move { kafka::run_consumer(engine, &config).await }
     });
 
-    // Start HTTP API server
-    api::run_server(engine, &config).await?;
+    // Serve the HTTP API until shutdown, then save a final snapshot
+    tokio::select! {
+        result = api::run_server(engine.clone(), &config) => result?,
+        () = shutdown_signal() => {
+            info!("Shutdown requested");
+            if let Some(store) = &snapshot_store {
+                engine.save_snapshot(store).await?;
+                info!("Final order book snapshot saved");
+            }
+        }
+    }
 
     Ok(())
 }
 
+/// Resolves on Ctrl-C or, on Unix, SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {}
+        () = terminate => {}
+    }
+}
+
 fn init_tracing(config: &Config) -> Result<()> {
     let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(&config.log_level));
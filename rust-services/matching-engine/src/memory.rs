@@ -0,0 +1,108 @@
+//! Memory Backstop
+//!
+//! Every resting order holds memory until it trades or is cancelled, so an
+//! order-spam attack can grow the books until the matcher is OOM-killed.
+//! Past `max_resting_orders` the engine turns cancel-only: new orders are
+//! rejected while cancels drain the books, and orders are accepted again
+//! once fewer than `RESUME_PERCENT` of the cap rest.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Share of the cap resting orders must fall below to resume, so the
+/// engine does not flap at the limit
+const RESUME_PERCENT: usize = 90;
+
+/// Change of mode caused by a resting-order count
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapTransition {
+    /// The cap was exceeded; only cancels are accepted
+    CancelOnly,
+    /// Resting orders drained below the resume level
+    Resumed,
+}
+
+pub struct RestingOrderCap {
+    /// Maximum resting orders across all books; 0 disables the cap
+    max: usize,
+    cancel_only: AtomicBool,
+}
+
+impl RestingOrderCap {
+    pub fn new(max: usize) -> Self {
+        Self {
+            max,
+            cancel_only: AtomicBool::new(false),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.max > 0
+    }
+
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    /// Whether new orders are being refused
+    pub fn is_cancel_only(&self) -> bool {
+        self.cancel_only.load(Ordering::Acquire)
+    }
+
+    /// Update the mode for `resting` orders across all books
+    ///
+    /// Returns the transition, if any; only one caller sees each.
+    pub fn update(&self, resting: usize) -> Option<CapTransition> {
+        if !self.enabled() {
+            return None;
+        }
+
+        let transition = if resting >= self.max {
+            self.cancel_only
+                .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+                .ok()
+                .map(|_| CapTransition::CancelOnly)
+        } else if resting < self.max * RESUME_PERCENT / 100 {
+            self.cancel_only
+                .compare_exchange(true, false, Ordering::AcqRel, Ordering::Acquire)
+                .ok()
+                .map(|_| CapTransition::Resumed)
+        } else {
+            None
+        };
+
+        metrics::gauge!("engine_cancel_only").set(if self.is_cancel_only() { 1.0 } else { 0.0 });
+        transition
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_only_until_drained_below_resume_level() {
+        let cap = RestingOrderCap::new(100);
+        assert_eq!(cap.update(99), None);
+        assert!(!cap.is_cancel_only());
+
+        assert_eq!(cap.update(100), Some(CapTransition::CancelOnly));
+        assert!(cap.is_cancel_only());
+        // Reported once
+        assert_eq!(cap.update(120), None);
+
+        // Between the resume level and the cap the mode holds
+        assert_eq!(cap.update(95), None);
+        assert!(cap.is_cancel_only());
+
+        assert_eq!(cap.update(89), Some(CapTransition::Resumed));
+        assert!(!cap.is_cancel_only());
+        assert_eq!(cap.update(89), None);
+    }
+
+    #[test]
+    fn test_disabled_cap_never_trips() {
+        let cap = RestingOrderCap::new(0);
+        assert_eq!(cap.update(usize::MAX), None);
+        assert!(!cap.is_cancel_only());
+    }
+}
@@ -26,18 +26,241 @@ pub fn init_metrics(config: &Config) -> Result<()> {
         "Order matching latency in microseconds"
     );
 
+    metrics::describe_histogram!(
+        "golden_path_latency_us",
+        "Order ingress to its last event handed to the publisher, in microseconds, by ingress"
+    );
+
+    metrics::describe_histogram!(
+        "command_batch_size",
+        "Orders or cancels sent to the matching loop per batch request"
+    );
+
+    metrics::describe_counter!(
+        "golden_path_slo_requests",
+        "Orders measured against the golden-path latency SLO, by ingress"
+    );
+
+    metrics::describe_counter!(
+        "golden_path_slo_breaches",
+        "Orders over the golden-path latency target, by ingress"
+    );
+
+    metrics::describe_gauge!(
+        "golden_path_slo_objective",
+        "Share of orders that must meet the golden-path latency target"
+    );
+
+    metrics::describe_gauge!(
+        "golden_path_slo_target_us",
+        "Golden-path latency target in microseconds"
+    );
+
     metrics::describe_counter!("orders_received", "Total orders received");
 
     metrics::describe_counter!("orders_matched", "Total orders matched");
 
     metrics::describe_counter!("orders_cancelled", "Total orders cancelled");
 
+    metrics::describe_counter!(
+        "orders_expired",
+        "Orders rejected as expired before matching"
+    );
+
+    metrics::describe_counter!("orders_rejected", "Orders rejected, by reason");
+
+    metrics::describe_counter!(
+        "risk_checks_failed",
+        "Orders rejected by pre-trade risk limits, by check"
+    );
+
+    metrics::describe_counter!(
+        "commands_failed",
+        "Engine commands that failed without an order to reject"
+    );
+
     metrics::describe_counter!("trades_executed", "Total trades executed");
 
+    metrics::describe_counter!("symbol_orders_received", "Orders received, by symbol");
+
+    metrics::describe_counter!(
+        "symbol_orders_matched",
+        "Incoming orders that traded, by symbol"
+    );
+
+    metrics::describe_counter!("symbol_orders_cancelled", "Orders cancelled, by symbol");
+
+    metrics::describe_counter!("symbol_orders_rejected", "Orders rejected, by symbol");
+
+    metrics::describe_counter!("symbol_trades", "Trades executed, by symbol");
+
+    metrics::describe_gauge!(
+        "symbol_last_sequence",
+        "Book sequence after the last match or cancel, by symbol"
+    );
+
+    metrics::describe_counter!(
+        "self_trades_prevented",
+        "Resting orders cancelled by self-trade prevention"
+    );
+
+    metrics::describe_counter!(
+        "auction_orders_collected",
+        "Orders rested without matching while their symbol was halted"
+    );
+
+    metrics::describe_counter!("auction_uncrosses", "Halted books uncrossed on reopening");
+
+    metrics::describe_counter!(
+        "symbol_halts",
+        "Symbols halted by an admin or a circuit breaker, by reason"
+    );
+
+    metrics::describe_counter!("task_failures", "Supervised background task failures");
+
+    metrics::describe_counter!("task_restarts", "Supervised background task restarts");
+
     metrics::describe_gauge!("orderbook_depth_bids", "Number of bid levels in order book");
 
     metrics::describe_gauge!("orderbook_depth_asks", "Number of ask levels in order book");
 
+    metrics::describe_gauge!("book_resting_orders", "Resting orders, by symbol");
+
+    metrics::describe_gauge!("book_price_levels", "Price levels on both sides, by symbol");
+
+    metrics::describe_counter!(
+        "book_deltas_published",
+        "Conflated book delta messages published to the order book topic"
+    );
+
+    metrics::describe_counter!(
+        "book_changes_conflated",
+        "Book changes held back for the next delta message"
+    );
+
+    metrics::describe_counter!(
+        "book_snapshots_published",
+        "Periodic book snapshots published to the order book topic"
+    );
+
+    metrics::describe_gauge!(
+        "book_memory_bytes",
+        "Approximate memory held by the order book, by symbol"
+    );
+
+    metrics::describe_gauge!(
+        "engine_resting_orders",
+        "Resting and pending stop orders across all books"
+    );
+
+    metrics::describe_gauge!(
+        "engine_book_memory_bytes",
+        "Approximate memory held by all order books"
+    );
+
+    metrics::describe_gauge!(
+        "engine_cancel_only",
+        "1 while the resting order cap is exceeded and new orders are rejected"
+    );
+
+    metrics::describe_counter!(
+        "history_records_written",
+        "Order and trade records written to Postgres"
+    );
+
+    metrics::describe_counter!(
+        "history_records_dropped",
+        "History records dropped because the writer queue was full"
+    );
+
+    metrics::describe_counter!(
+        "history_write_failures",
+        "Failed history transactions, retried"
+    );
+
+    metrics::describe_gauge!(
+        "history_queue_depth",
+        "Records waiting for the history writer"
+    );
+
+    metrics::describe_counter!(
+        "journal_entries_written",
+        "Commands appended to the command journal"
+    );
+
+    metrics::describe_counter!(
+        "journal_entries_replayed",
+        "Journaled commands re-applied on recovery or audit"
+    );
+
+    metrics::describe_counter!(
+        "journal_segments_created",
+        "Command journal segments started"
+    );
+
+    metrics::describe_counter!("events_published", "Events delivered to Kafka, by topic");
+
+    metrics::describe_counter!(
+        "events_failed",
+        "Events Kafka rejected or failed to deliver, by topic"
+    );
+
+    metrics::describe_histogram!(
+        "event_batch_size",
+        "Events handed to the producer per batch"
+    );
+
+    metrics::describe_histogram!(
+        "event_publish_ms",
+        "Time to produce a batch and collect its delivery reports in milliseconds"
+    );
+
+    metrics::describe_gauge!("event_queue_depth", "Events waiting for the publisher");
+
+    metrics::describe_counter!("outbox_events_written", "Events stored in the event outbox");
+
+    metrics::describe_counter!(
+        "outbox_write_failures",
+        "Failed event outbox writes, retried"
+    );
+
+    metrics::describe_gauge!(
+        "outbox_backlog",
+        "Undelivered outbox events found by the last relay pass"
+    );
+
+    metrics::describe_counter!(
+        "outbox_events_purged",
+        "Delivered events deleted from the outbox after retention"
+    );
+
+    metrics::describe_counter!("snapshots_saved", "Order book snapshots saved");
+
+    metrics::describe_histogram!(
+        "snapshot_save_ms",
+        "Time to write an order book snapshot in milliseconds"
+    );
+
+    metrics::describe_histogram!(
+        "snapshot_load_ms",
+        "Time to read and decode the startup snapshot in milliseconds"
+    );
+
+    metrics::describe_histogram!(
+        "snapshot_restore_ms",
+        "Time to rebuild the order books from a snapshot in milliseconds"
+    );
+
+    metrics::describe_counter!(
+        "snapshot_restore_over_budget",
+        "Startup snapshot restores that exceeded snapshot_restore_budget_ms"
+    );
+
+    metrics::describe_gauge!(
+        "recovery_ms",
+        "Time from snapshot load to ready, including journal replay, in milliseconds"
+    );
+
     tracing::info!("Metrics server started on port {}", config.metrics_port);
 
     Ok(())
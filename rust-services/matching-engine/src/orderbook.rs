@@ -10,27 +10,59 @@
 //! - Match: O(1) for best price lookup
 //! - Cancel: O(log n) + O(m) where m is orders at that price
 
-use chrono::Utc;
 use parking_lot::RwLock;
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use uuid::Uuid;
 
-use common::{Order, OrderStatus, PriceLevel, Side, Symbol, Trade};
+use common::{
+    HybridClock, Order, OrderStatus, OrderType, PriceLevel, SelfTradePrevention, SequenceGenerator,
+    SharedClock, Side, Symbol, TimeInForce, Trade,
+};
 
 /// Order entry in the book
+///
+/// `remaining_quantity` is the visible quantity; iceberg orders keep the
+/// rest in `hidden_quantity` and refill up to `display_quantity`.
 #[derive(Debug, Clone)]
 struct OrderEntry {
     order_id: Uuid,
+    client_order_id: String,
     user_id: Uuid,
-    #[allow(dead_code)]
     price: Decimal,
     remaining_quantity: Decimal,
-    #[allow(dead_code)]
+    hidden_quantity: Decimal,
+    display_quantity: Option<Decimal>,
     sequence: u64,
 }
 
+impl OrderEntry {
+    /// Visible plus hidden quantity
+    fn total_quantity(&self) -> Decimal {
+        self.remaining_quantity + self.hidden_quantity
+    }
+
+    /// Show the next slice from the hidden reserve
+    ///
+    /// Returns false once the reserve is exhausted.
+    fn refill(&mut self, sequence: u64) -> bool {
+        let Some(display) = self.display_quantity else {
+            return false;
+        };
+        if self.hidden_quantity.is_zero() {
+            return false;
+        }
+        let slice = display.min(self.hidden_quantity);
+        self.hidden_quantity -= slice;
+        self.remaining_quantity = slice;
+        self.sequence = sequence;
+        true
+    }
+}
+
 /// Price level containing orders at the same price
 #[derive(Debug, Default)]
 struct Level {
@@ -72,6 +104,98 @@ impl Level {
     }
 }
 
+/// Resting order removed from the book without trading
+#[derive(Debug, Clone)]
+pub struct CancelledOrder {
+    pub order_id: Uuid,
+    pub client_order_id: String,
+    pub user_id: Uuid,
+    pub remaining_quantity: Decimal,
+}
+
+/// Outcome of processing an incoming order
+#[derive(Debug)]
+pub struct MatchResult {
+    /// Updated incoming order
+    pub order: Order,
+
+    /// Trades executed against resting orders
+    pub trades: Vec<Trade>,
+
+    /// Resting orders cancelled by self-trade prevention
+    pub self_trade_cancels: Vec<CancelledOrder>,
+
+    /// Incoming order's remainder cancelled by self-trade prevention
+    pub taker_self_trade_cancelled: bool,
+}
+
+/// Outcome of uncrossing a book collected by a halt auction
+#[derive(Debug, Default)]
+pub struct UncrossResult {
+    /// Trades at the uncross price, with the bid as taker
+    pub trades: Vec<Trade>,
+
+    /// Newer of two orders from one owner that met in the uncross
+    pub self_trade_cancels: Vec<CancelledOrder>,
+}
+
+/// Outcome of matching at a single price level
+struct LevelMatch {
+    trades: Vec<Trade>,
+    matched: Decimal,
+    /// Taker quantity removed by self-trade decrement, without trading
+    decremented: Decimal,
+    taker_cancelled: bool,
+}
+
+/// Resting order as captured in a book snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestingOrder {
+    pub order_id: Uuid,
+    pub client_order_id: String,
+    pub user_id: Uuid,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub price: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub remaining_quantity: Decimal,
+    #[serde(default, with = "rust_decimal::serde::str")]
+    pub hidden_quantity: Decimal,
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub display_quantity: Option<Decimal>,
+    pub sequence: u64,
+}
+
+/// Full state of an order book, enough to rebuild it exactly
+///
+/// Orders on each side are listed best price first, in time priority
+/// within a price.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookSnapshot {
+    pub symbol: Symbol,
+    pub bids: Vec<RestingOrder>,
+    pub asks: Vec<RestingOrder>,
+    pub next_order_sequence: u64,
+    pub next_trade_id: u64,
+    pub book_sequence: u64,
+}
+
+/// Approximate memory held by an order book
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct BookMemory {
+    pub resting_orders: usize,
+    pub price_levels: usize,
+    /// Order entries, price levels and the order index, in bytes
+    pub bytes: usize,
+}
+
+/// Depth snapshot valid for a single book sequence
+struct DepthCache {
+    sequence: u64,
+    levels: usize,
+    bids: Vec<PriceLevel>,
+    asks: Vec<PriceLevel>,
+}
+
 /// Order book for a single trading pair
 pub struct OrderBook {
     symbol: Symbol,
@@ -86,86 +210,142 @@ pub struct OrderBook {
     order_prices: RwLock<HashMap<Uuid, (Side, Decimal)>>,
 
     /// Sequence counter for FIFO ordering
-    sequence: AtomicU64,
+    sequence: SequenceGenerator,
 
     /// Trade ID counter
-    trade_counter: AtomicU64,
+    trade_counter: SequenceGenerator,
 
     /// Book sequence for snapshot versioning
     book_sequence: AtomicU64,
+
+    /// Time source for trade and order timestamps
+    clock: SharedClock,
+
+    /// Last computed depth, reused until the book changes
+    depth_cache: RwLock<Option<DepthCache>>,
 }
 
 impl OrderBook {
+    #[allow(dead_code)]
     pub fn new(symbol: Symbol) -> Self {
+        Self::with_clock(symbol, Arc::new(HybridClock::system()))
+    }
+
+    /// Create an order book driven by the given clock
+    pub fn with_clock(symbol: Symbol, clock: SharedClock) -> Self {
         Self {
             symbol,
             bids: RwLock::new(BTreeMap::new()),
             asks: RwLock::new(BTreeMap::new()),
             order_prices: RwLock::new(HashMap::new()),
-            sequence: AtomicU64::new(0),
-            trade_counter: AtomicU64::new(0),
+            sequence: SequenceGenerator::new(0),
+            trade_counter: SequenceGenerator::new(0),
             book_sequence: AtomicU64::new(0),
+            clock,
+            depth_cache: RwLock::new(None),
         }
     }
 
+    /// Get the book's symbol
+    pub fn symbol(&self) -> &Symbol {
+        &self.symbol
+    }
+
     /// Get next sequence number
     fn next_sequence(&self) -> u64 {
-        self.sequence.fetch_add(1, Ordering::SeqCst)
+        self.sequence.next()
     }
 
     /// Get next trade ID
     fn next_trade_id(&self) -> u64 {
-        self.trade_counter.fetch_add(1, Ordering::SeqCst)
+        self.trade_counter.next()
     }
 
     /// Get current book sequence
-    #[allow(dead_code)]
     pub fn book_sequence(&self) -> u64 {
         self.book_sequence.load(Ordering::SeqCst)
     }
 
     /// Process an incoming order
-    /// Returns (updated order, list of trades)
-    pub fn process_order(&self, mut order: Order) -> (Order, Vec<Trade>) {
+    ///
+    /// IOC orders never rest: any unfilled remainder is cancelled. FOK
+    /// orders that cannot fill completely are expired without trading.
+    /// Meeting the owner's own resting order is resolved by the order's
+    /// self-trade prevention policy.
+    pub fn process_order(&self, mut order: Order) -> MatchResult {
         order.sequence = self.next_sequence();
         order.status = OrderStatus::Open;
 
         let mut trades = Vec::new();
+        let mut self_trade_cancels = Vec::new();
+
+        if order.time_in_force == TimeInForce::FOK
+            && self.available_liquidity(&order) < order.remaining_quantity
+        {
+            order.status = OrderStatus::Expired;
+            order.updated_at = self.clock.now();
+            return MatchResult {
+                order,
+                trades,
+                self_trade_cancels,
+                taker_self_trade_cancelled: false,
+            };
+        }
 
         // Try to match against opposite side
-        let remaining = self.match_order(&mut order, &mut trades);
+        let (remaining, taker_self_trade_cancelled) =
+            self.match_order(&mut order, &mut trades, &mut self_trade_cancels);
+
+        order.remaining_quantity = remaining;
 
         // Update order status
-        if remaining == Decimal::ZERO {
+        if taker_self_trade_cancelled {
+            order.status = OrderStatus::Cancelled;
+        } else if remaining == Decimal::ZERO {
             order.status = OrderStatus::Filled;
-        } else if order.remaining_quantity < order.quantity {
-            order.status = OrderStatus::PartiallyFilled;
-
-            // Add remaining to book (for limit orders)
-            if order.price.is_some() {
-                self.add_to_book(&order);
-            }
         } else {
-            // No fills - add to book (for limit orders)
-            if order.price.is_some() {
+            if order.filled_quantity > Decimal::ZERO {
+                order.status = OrderStatus::PartiallyFilled;
+            }
+
+            if order.time_in_force == TimeInForce::IOC {
+                order.status = OrderStatus::Cancelled;
+            } else if order.price.is_some() {
+                // Add remaining to book (for limit orders)
                 self.add_to_book(&order);
             }
         }
 
-        order.remaining_quantity = remaining;
-        order.updated_at = Utc::now();
+        order.updated_at = self.clock.now();
 
         // Update book sequence
-        if !trades.is_empty() {
+        if !trades.is_empty() || !self_trade_cancels.is_empty() {
             self.book_sequence.fetch_add(1, Ordering::SeqCst);
         }
 
-        (order, trades)
+        #[cfg(feature = "invariant-checks")]
+        self.check_invariants();
+
+        MatchResult {
+            order,
+            trades,
+            self_trade_cancels,
+            taker_self_trade_cancelled,
+        }
     }
 
     /// Match order against the book
-    fn match_order(&self, order: &mut Order, trades: &mut Vec<Trade>) -> Decimal {
+    ///
+    /// Returns the unfilled quantity and whether self-trade prevention
+    /// cancelled it.
+    fn match_order(
+        &self,
+        order: &mut Order,
+        trades: &mut Vec<Trade>,
+        self_trade_cancels: &mut Vec<CancelledOrder>,
+    ) -> (Decimal, bool) {
         let mut remaining = order.remaining_quantity;
+        let mut taker_cancelled = false;
 
         // Determine which side to match against
         let is_buy = order.side == Side::Buy;
@@ -187,11 +367,17 @@ impl OrderBook {
             }
 
             // Match at this price level
-            let (matched, level_trades) = self.match_at_price(order, best_price, remaining, is_buy);
+            let level =
+                self.match_at_price(order, best_price, remaining, is_buy, self_trade_cancels);
+
+            remaining -= level.matched + level.decremented;
+            order.filled_quantity += level.matched;
+            trades.extend(level.trades);
 
-            remaining -= matched;
-            order.filled_quantity += matched;
-            trades.extend(level_trades);
+            if level.taker_cancelled || (remaining.is_zero() && !level.decremented.is_zero()) {
+                taker_cancelled = true;
+                break;
+            }
         }
 
         // Calculate average fill price
@@ -201,7 +387,70 @@ impl OrderBook {
             order.avg_fill_price = Some(total_value / total_qty);
         }
 
-        remaining
+        (remaining, taker_cancelled)
+    }
+
+    /// Quote cost of buying `quantity` from the asks, best price first;
+    /// `None` while there are no asks
+    pub fn cost_to_buy(&self, quantity: Decimal) -> Option<Decimal> {
+        let mut remaining = quantity;
+        let mut cost = Decimal::ZERO;
+        for (&price, level) in self.asks.read().iter() {
+            if remaining.is_zero() {
+                break;
+            }
+            let taken = level.total_quantity.min(remaining);
+            cost += price * taken;
+            remaining -= taken;
+        }
+        (!cost.is_zero()).then_some(cost)
+    }
+
+    /// Resting quantity an order could fill against, up to its own quantity
+    ///
+    /// The order's own resting orders follow its self-trade prevention
+    /// policy: skipped under `CancelMaker`, the end of the count under
+    /// `CancelTaker` and `CancelBoth`, and counted under
+    /// `DecrementAndCancel` since the overlap comes off the order itself.
+    fn available_liquidity(&self, order: &Order) -> Decimal {
+        let crosses = |price: Decimal| match (order.side, order.price) {
+            (_, None) => true,
+            (Side::Buy, Some(limit)) => price <= limit,
+            (Side::Sell, Some(limit)) => price >= limit,
+        };
+        let mut available = Decimal::ZERO;
+
+        let book = match order.side {
+            Side::Buy => self.asks.read(),
+            Side::Sell => self.bids.read(),
+        };
+        let levels: Box<dyn Iterator<Item = (&Decimal, &Level)>> = match order.side {
+            Side::Buy => Box::new(book.iter()),
+            Side::Sell => Box::new(book.iter().rev()),
+        };
+
+        for (&price, level) in levels {
+            if !crosses(price) {
+                break;
+            }
+            for entry in level.orders.iter() {
+                if entry.user_id == order.user_id {
+                    match order.self_trade_prevention {
+                        SelfTradePrevention::CancelMaker => continue,
+                        SelfTradePrevention::CancelTaker | SelfTradePrevention::CancelBoth => {
+                            return available;
+                        }
+                        SelfTradePrevention::DecrementAndCancel => {}
+                    }
+                }
+                available += entry.total_quantity();
+                if available >= order.remaining_quantity {
+                    return available;
+                }
+            }
+        }
+
+        available
     }
 
     /// Get best ask price that matches our buy order
@@ -241,9 +490,14 @@ impl OrderBook {
         price: Decimal,
         mut quantity: Decimal,
         is_buy: bool,
-    ) -> (Decimal, Vec<Trade>) {
-        let mut trades = Vec::new();
-        let mut matched = Decimal::ZERO;
+        self_trade_cancels: &mut Vec<CancelledOrder>,
+    ) -> LevelMatch {
+        let mut result = LevelMatch {
+            trades: Vec::new(),
+            matched: Decimal::ZERO,
+            decremented: Decimal::ZERO,
+            taker_cancelled: false,
+        };
 
         let mut book = if is_buy {
             self.asks.write()
@@ -253,7 +507,7 @@ impl OrderBook {
 
         let level = match book.get_mut(&price) {
             Some(level) => level,
-            None => return (Decimal::ZERO, trades),
+            None => return result,
         };
 
         while quantity > Decimal::ZERO {
@@ -262,9 +516,34 @@ impl OrderBook {
                 None => break,
             };
 
-            // Self-trade prevention
             if maker.user_id == taker_order.user_id {
+                let policy = taker_order.self_trade_prevention;
+                if policy == SelfTradePrevention::DecrementAndCancel {
+                    let overlap = quantity.min(maker.total_quantity());
+                    result.decremented += overlap;
+                    quantity -= overlap;
+                    if overlap < maker.total_quantity() {
+                        self.decrement_front(level, overlap);
+                        // The taker is used up before trading with anyone else
+                        break;
+                    }
+                } else if policy == SelfTradePrevention::CancelTaker {
+                    result.taker_cancelled = true;
+                    break;
+                }
+
                 level.pop();
+                self.order_prices.write().remove(&maker.order_id);
+                self_trade_cancels.push(CancelledOrder {
+                    remaining_quantity: maker.total_quantity(),
+                    order_id: maker.order_id,
+                    client_order_id: maker.client_order_id,
+                    user_id: maker.user_id,
+                });
+                if policy == SelfTradePrevention::CancelBoth {
+                    result.taker_cancelled = true;
+                    break;
+                }
                 continue;
             }
 
@@ -284,17 +563,22 @@ impl OrderBook {
                 quantity: fill_qty,
                 quote_quantity: quote_qty,
                 taker_side: taker_order.side,
-                executed_at: Utc::now(),
+                executed_at: self.clock.now(),
             };
 
-            trades.push(trade);
-            matched += fill_qty;
+            result.trades.push(trade);
+            result.matched += fill_qty;
             quantity -= fill_qty;
 
-            // Update or remove maker order
+            // Update, refill or remove maker order
             if fill_qty >= maker.remaining_quantity {
-                level.pop();
-                self.order_prices.write().remove(&maker.order_id);
+                let mut filled = level.pop().expect("maker was at the front");
+                if filled.refill(self.next_sequence()) {
+                    // The refreshed slice queues behind the level
+                    level.add(filled);
+                } else {
+                    self.order_prices.write().remove(&maker.order_id);
+                }
             } else {
                 // Update remaining quantity in place
                 if let Some(entry) = level.orders.front_mut() {
@@ -309,18 +593,47 @@ impl OrderBook {
             book.remove(&price);
         }
 
-        (matched, trades)
+        result
+    }
+
+    /// Reduce the order at the front of `level` by less than its total
+    ///
+    /// Visible quantity goes first; an iceberg whose slice is used up
+    /// refills from its reserve and queues behind the level.
+    fn decrement_front(&self, level: &mut Level, quantity: Decimal) {
+        let Some(entry) = level.orders.front_mut() else {
+            return;
+        };
+        let visible = quantity.min(entry.remaining_quantity);
+        entry.remaining_quantity -= visible;
+        entry.hidden_quantity -= quantity - visible;
+        level.total_quantity -= visible;
+
+        if entry.remaining_quantity.is_zero() {
+            let mut entry = level.pop().expect("entry was at the front");
+            entry.refill(self.next_sequence());
+            level.add(entry);
+        }
+        self.book_sequence.fetch_add(1, Ordering::SeqCst);
     }
 
     /// Add order to the book
     fn add_to_book(&self, order: &Order) {
         let price = order.price.expect("Limit order must have price");
+        let visible = order
+            .display_quantity
+            .map_or(order.remaining_quantity, |d| {
+                d.min(order.remaining_quantity)
+            });
 
         let entry = OrderEntry {
             order_id: order.id,
+            client_order_id: order.client_order_id.clone(),
             user_id: order.user_id,
             price,
-            remaining_quantity: order.remaining_quantity,
+            remaining_quantity: visible,
+            hidden_quantity: order.remaining_quantity - visible,
+            display_quantity: order.display_quantity,
             sequence: order.sequence,
         };
 
@@ -343,32 +656,347 @@ impl OrderBook {
     }
 
     /// Cancel an order
-    pub fn cancel_order(&self, order_id: Uuid) -> bool {
-        let location = self.order_prices.write().remove(&order_id);
+    pub fn cancel_order(&self, order_id: Uuid) -> Option<CancelledOrder> {
+        let (side, price) = self.order_prices.write().remove(&order_id)?;
 
-        if let Some((side, price)) = location {
+        let entry = {
             let mut book = match side {
                 Side::Buy => self.bids.write(),
                 Side::Sell => self.asks.write(),
             };
 
-            if let Some(level) = book.get_mut(&price) {
-                level.remove(order_id);
+            let level = book.get_mut(&price)?;
+            let entry = level.remove(order_id);
+
+            if level.is_empty() {
+                book.remove(&price);
+            }
+            entry?
+        };
 
-                if level.is_empty() {
-                    book.remove(&price);
+        self.book_sequence.fetch_add(1, Ordering::SeqCst);
+
+        #[cfg(feature = "invariant-checks")]
+        self.check_invariants();
+
+        Some(CancelledOrder {
+            remaining_quantity: entry.total_quantity(),
+            order_id: entry.order_id,
+            client_order_id: entry.client_order_id,
+            user_id: entry.user_id,
+        })
+    }
+
+    /// Whether no orders rest on either side
+    pub fn is_empty(&self) -> bool {
+        self.bids.read().is_empty() && self.asks.read().is_empty()
+    }
+
+    /// Number of orders resting on either side
+    pub fn resting_orders(&self) -> usize {
+        self.order_prices.read().len()
+    }
+
+    /// Approximate memory held by the book
+    ///
+    /// Counts allocated queue slots, client order ids, level entries and
+    /// index slots; allocator overhead is not included. Each structure is
+    /// locked on its own, so the parts may be from adjacent book states.
+    pub fn memory(&self) -> BookMemory {
+        use std::mem::size_of;
+
+        let mut memory = BookMemory::default();
+        for side in [&self.bids, &self.asks] {
+            let levels = side.read();
+            memory.price_levels += levels.len();
+            memory.bytes += levels.len() * (size_of::<Decimal>() + size_of::<Level>());
+            for level in levels.values() {
+                memory.bytes += level.orders.capacity() * size_of::<OrderEntry>()
+                    + level
+                        .orders
+                        .iter()
+                        .map(|o| o.client_order_id.capacity())
+                        .sum::<usize>();
+            }
+        }
+
+        let index = self.order_prices.read();
+        memory.resting_orders = index.len();
+        memory.bytes += index.capacity() * size_of::<(Uuid, (Side, Decimal))>();
+        memory
+    }
+
+    /// Whether `order_id` is resting on the book
+    pub fn contains_order(&self, order_id: Uuid) -> bool {
+        self.order_prices.read().contains_key(&order_id)
+    }
+
+    /// Owner and side of a resting order
+    pub fn resting_owner(&self, order_id: Uuid) -> Option<(Uuid, Side)> {
+        let (side, price) = *self.order_prices.read().get(&order_id)?;
+        let book = match side {
+            Side::Buy => self.bids.read(),
+            Side::Sell => self.asks.read(),
+        };
+        let entry = book
+            .get(&price)?
+            .orders
+            .iter()
+            .find(|o| o.order_id == order_id)?;
+        Some((entry.user_id, side))
+    }
+
+    /// Hand a resting order's queue position to its replacement
+    ///
+    /// Only applies when the replacement belongs to the same user and rests
+    /// at the same side and price with no more than the original's open
+    /// quantity and the same display quantity; the entry is then reduced in
+    /// place and keeps its time priority. Returns `None` and leaves the book
+    /// untouched otherwise.
+    pub fn amend_order(&self, order_id: Uuid, replacement: &mut Order) -> Option<CancelledOrder> {
+        let (side, price) = *self.order_prices.read().get(&order_id)?;
+        if replacement.side != side
+            || replacement.price != Some(price)
+            || replacement.order_type != OrderType::Limit
+            || matches!(
+                replacement.time_in_force,
+                TimeInForce::IOC | TimeInForce::FOK
+            )
+        {
+            return None;
+        }
+
+        let cancelled = {
+            let mut book = match side {
+                Side::Buy => self.bids.write(),
+                Side::Sell => self.asks.write(),
+            };
+            let level = book.get_mut(&price)?;
+            let entry = level.orders.iter_mut().find(|o| o.order_id == order_id)?;
+
+            let quantity = replacement.remaining_quantity;
+            if replacement.user_id != entry.user_id
+                || quantity > entry.total_quantity()
+                || replacement.display_quantity != entry.display_quantity
+            {
+                return None;
+            }
+
+            let cancelled = CancelledOrder {
+                remaining_quantity: entry.total_quantity(),
+                order_id: entry.order_id,
+                client_order_id: entry.client_order_id.clone(),
+                user_id: entry.user_id,
+            };
+
+            let visible = entry.remaining_quantity.min(quantity);
+            level.total_quantity -= entry.remaining_quantity - visible;
+            entry.order_id = replacement.id;
+            entry.client_order_id = replacement.client_order_id.clone();
+            entry.remaining_quantity = visible;
+            entry.hidden_quantity = quantity - visible;
+
+            replacement.sequence = entry.sequence;
+            cancelled
+        };
+
+        {
+            let mut order_prices = self.order_prices.write();
+            order_prices.remove(&order_id);
+            order_prices.insert(replacement.id, (side, price));
+        }
+
+        replacement.status = OrderStatus::Open;
+        replacement.updated_at = self.clock.now();
+        self.book_sequence.fetch_add(1, Ordering::SeqCst);
+
+        #[cfg(feature = "invariant-checks")]
+        self.check_invariants();
+
+        Some(cancelled)
+    }
+
+    /// Rest a limit order without matching it, as a halted book collects
+    /// its auction
+    pub fn rest_order(&self, mut order: Order) -> Order {
+        order.sequence = self.next_sequence();
+        order.status = OrderStatus::Open;
+        order.updated_at = self.clock.now();
+        self.add_to_book(&order);
+
+        #[cfg(feature = "invariant-checks")]
+        self.check_invariants();
+
+        order
+    }
+
+    /// Whether the best bid is at or through the best ask
+    pub fn is_crossed(&self) -> bool {
+        matches!(self.get_bbo(), (Some(bid), Some(ask)) if bid >= ask)
+    }
+
+    /// Levels at or through the other side's best price, best first, as
+    /// (price, quantity)
+    ///
+    /// Quantities include iceberg reserves, which trade in an uncross.
+    pub fn crossing_interest(&self) -> (Vec<(Decimal, Decimal)>, Vec<(Decimal, Decimal)>) {
+        let bids = self.bids.read();
+        let asks = self.asks.read();
+        let (Some((&best_bid, _)), Some((&best_ask, _))) =
+            (bids.last_key_value(), asks.first_key_value())
+        else {
+            return (Vec::new(), Vec::new());
+        };
+
+        let total = |(&price, level): (&Decimal, &Level)| {
+            let quantity: Decimal = level.orders.iter().map(OrderEntry::total_quantity).sum();
+            (price, quantity)
+        };
+        (
+            bids.range(best_ask..).rev().map(total).collect(),
+            asks.range(..=best_bid).map(total).collect(),
+        )
+    }
+
+    /// Trade the crossed part of the book at a single `price`
+    ///
+    /// Bids at or above and asks at or below `price` fill each other in
+    /// time priority until one side runs out. When both fronts belong to
+    /// the same owner the newer order is cancelled instead.
+    pub fn uncross(&self, price: Decimal) -> UncrossResult {
+        let mut result = UncrossResult::default();
+
+        {
+            let mut bids = self.bids.write();
+            let mut asks = self.asks.write();
+
+            loop {
+                let Some(mut bid_entry) = bids.last_entry().filter(|e| *e.key() >= price) else {
+                    break;
+                };
+                let Some(mut ask_entry) = asks.first_entry().filter(|e| *e.key() <= price) else {
+                    break;
+                };
+                let bid_level = bid_entry.get_mut();
+                let ask_level = ask_entry.get_mut();
+                let bid = bid_level.peek().expect("levels are never empty").clone();
+                let ask = ask_level.peek().expect("levels are never empty").clone();
+
+                if bid.user_id == ask.user_id {
+                    let level = if bid.sequence > ask.sequence {
+                        bid_level
+                    } else {
+                        ask_level
+                    };
+                    let newer = level.pop().expect("entry was at the front");
+                    self.order_prices.write().remove(&newer.order_id);
+                    result.self_trade_cancels.push(CancelledOrder {
+                        remaining_quantity: newer.total_quantity(),
+                        order_id: newer.order_id,
+                        client_order_id: newer.client_order_id,
+                        user_id: newer.user_id,
+                    });
+                } else {
+                    let quantity = bid.remaining_quantity.min(ask.remaining_quantity);
+                    result.trades.push(Trade {
+                        id: Uuid::new_v4(),
+                        trade_id: self.next_trade_id(),
+                        symbol: self.symbol.clone(),
+                        maker_order_id: ask.order_id,
+                        maker_user_id: ask.user_id,
+                        taker_order_id: bid.order_id,
+                        taker_user_id: bid.user_id,
+                        price,
+                        quantity,
+                        quote_quantity: quantity * price,
+                        taker_side: Side::Buy,
+                        executed_at: self.clock.now(),
+                    });
+                    self.fill_front(bid_level, quantity);
+                    self.fill_front(ask_level, quantity);
+                }
+
+                if bid_entry.get().is_empty() {
+                    bid_entry.remove();
+                }
+                if ask_entry.get().is_empty() {
+                    ask_entry.remove();
                 }
             }
+        }
 
+        if !result.trades.is_empty() || !result.self_trade_cancels.is_empty() {
             self.book_sequence.fetch_add(1, Ordering::SeqCst);
-            true
-        } else {
-            false
+        }
+
+        #[cfg(feature = "invariant-checks")]
+        self.check_invariants();
+
+        result
+    }
+
+    /// Fill part of the visible quantity of the order at the front of
+    /// `level`, refilling or removing it once its slice is used up
+    fn fill_front(&self, level: &mut Level, quantity: Decimal) {
+        let Some(entry) = level.orders.front_mut() else {
+            return;
+        };
+        entry.remaining_quantity -= quantity;
+        level.total_quantity -= quantity;
+
+        if entry.remaining_quantity.is_zero() {
+            let mut filled = level.pop().expect("entry was at the front");
+            if filled.refill(self.next_sequence()) {
+                level.add(filled);
+            } else {
+                self.order_prices.write().remove(&filled.order_id);
+            }
         }
     }
 
     /// Get order book depth
+    ///
+    /// Served from cache while `book_sequence` is unchanged, so bursts of
+    /// depth polling do not take the side locks away from matching.
     pub fn get_depth(&self, levels: usize) -> (Vec<PriceLevel>, Vec<PriceLevel>) {
+        let (bids, asks, _) = self.get_sequenced_depth(levels);
+        (bids, asks)
+    }
+
+    /// Order book depth with the book sequence it reflects
+    ///
+    /// A change racing the walk may already be included, so the depth is
+    /// as of at least the returned sequence; deltas are absolute levels
+    /// and re-applying that change is harmless.
+    pub fn get_sequenced_depth(&self, levels: usize) -> (Vec<PriceLevel>, Vec<PriceLevel>, u64) {
+        // Read before walking: a concurrent change bumps the sequence
+        // afterwards and invalidates whatever we cache here
+        let sequence = self.book_sequence();
+
+        if let Some(cache) = self.depth_cache.read().as_ref() {
+            if cache.sequence == sequence && cache.levels >= levels {
+                return (
+                    cache.bids.iter().take(levels).cloned().collect(),
+                    cache.asks.iter().take(levels).cloned().collect(),
+                    sequence,
+                );
+            }
+        }
+
+        let (bids, asks) = self.walk_depth(levels);
+
+        *self.depth_cache.write() = Some(DepthCache {
+            sequence,
+            levels,
+            bids: bids.clone(),
+            asks: asks.clone(),
+        });
+
+        (bids, asks, sequence)
+    }
+
+    /// Aggregate the top `levels` of each side under the side locks
+    fn walk_depth(&self, levels: usize) -> (Vec<PriceLevel>, Vec<PriceLevel>) {
         let bids: Vec<PriceLevel> = self
             .bids
             .read()
@@ -398,18 +1026,144 @@ impl OrderBook {
     }
 
     /// Get best bid/ask
-    #[allow(dead_code)]
     pub fn get_bbo(&self) -> (Option<Decimal>, Option<Decimal>) {
         let best_bid = self.bids.read().last_key_value().map(|(&p, _)| p);
         let best_ask = self.asks.read().first_key_value().map(|(&p, _)| p);
         (best_bid, best_ask)
     }
+
+    /// Capture every resting order and the book's counters
+    pub fn snapshot(&self) -> BookSnapshot {
+        let resting = |entry: &OrderEntry| RestingOrder {
+            order_id: entry.order_id,
+            client_order_id: entry.client_order_id.clone(),
+            user_id: entry.user_id,
+            price: entry.price,
+            remaining_quantity: entry.remaining_quantity,
+            hidden_quantity: entry.hidden_quantity,
+            display_quantity: entry.display_quantity,
+            sequence: entry.sequence,
+        };
+
+        let bids = self.bids.read();
+        let asks = self.asks.read();
+
+        BookSnapshot {
+            symbol: self.symbol.clone(),
+            bids: bids
+                .values()
+                .rev()
+                .flat_map(|level| level.orders.iter().map(resting))
+                .collect(),
+            asks: asks
+                .values()
+                .flat_map(|level| level.orders.iter().map(resting))
+                .collect(),
+            next_order_sequence: self.sequence.peek(),
+            next_trade_id: self.trade_counter.peek(),
+            book_sequence: self.book_sequence(),
+        }
+    }
+
+    /// Replace the book's contents with a snapshot
+    pub fn restore(&self, snapshot: &BookSnapshot) {
+        let mut bids = self.bids.write();
+        let mut asks = self.asks.write();
+        let mut order_prices = self.order_prices.write();
+
+        bids.clear();
+        asks.clear();
+        order_prices.clear();
+        order_prices.reserve(snapshot.bids.len() + snapshot.asks.len());
+
+        for (side, orders, book) in [
+            (Side::Buy, &snapshot.bids, &mut *bids),
+            (Side::Sell, &snapshot.asks, &mut *asks),
+        ] {
+            for order in orders {
+                order_prices.insert(order.order_id, (side, order.price));
+                book.entry(order.price).or_default().add(OrderEntry {
+                    order_id: order.order_id,
+                    client_order_id: order.client_order_id.clone(),
+                    user_id: order.user_id,
+                    price: order.price,
+                    remaining_quantity: order.remaining_quantity,
+                    hidden_quantity: order.hidden_quantity,
+                    display_quantity: order.display_quantity,
+                    sequence: order.sequence,
+                });
+            }
+        }
+
+        self.sequence.reset(snapshot.next_order_sequence);
+        self.trade_counter.reset(snapshot.next_trade_id);
+        self.book_sequence
+            .store(snapshot.book_sequence, Ordering::SeqCst);
+        *self.depth_cache.write() = None;
+    }
+
+    /// Validate level totals and order index consistency
+    ///
+    /// Panics on the first violation. Runs after every mutating operation
+    /// when the `invariant-checks` feature is enabled.
+    #[cfg(any(test, feature = "invariant-checks"))]
+    pub fn check_invariants(&self) {
+        let bids = self.bids.read();
+        let asks = self.asks.read();
+        let order_prices = self.order_prices.read();
+
+        let mut indexed = 0;
+
+        for (side, book) in [(Side::Buy, &*bids), (Side::Sell, &*asks)] {
+            for (&price, level) in book.iter() {
+                assert!(
+                    !level.is_empty(),
+                    "{}: empty {:?} level left at {}",
+                    self.symbol,
+                    side,
+                    price
+                );
+
+                let sum: Decimal = level.orders.iter().map(|o| o.remaining_quantity).sum();
+                assert_eq!(
+                    level.total_quantity, sum,
+                    "{}: {:?} level {} total drifted from order sum",
+                    self.symbol, side, price
+                );
+
+                for entry in &level.orders {
+                    assert!(
+                        entry.remaining_quantity > Decimal::ZERO,
+                        "{}: order {} rests with non-positive quantity",
+                        self.symbol,
+                        entry.order_id
+                    );
+                    assert_eq!(
+                        order_prices.get(&entry.order_id),
+                        Some(&(side, price)),
+                        "{}: order {} missing or misplaced in index",
+                        self.symbol,
+                        entry.order_id
+                    );
+                    indexed += 1;
+                }
+            }
+        }
+
+        assert_eq!(
+            order_prices.len(),
+            indexed,
+            "{}: order index has entries for orders not in the book",
+            self.symbol
+        );
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use common::{OrderType, TimeInForce};
+    use chrono::Utc;
+    use common::OrderType;
 
     fn create_order(side: Side, price: Decimal, quantity: Decimal) -> Order {
         Order {
@@ -423,6 +1177,7 @@ mod tests {
             status: OrderStatus::Pending,
             price: Some(price),
             stop_price: None,
+            trigger_source: None,
             quantity,
             filled_quantity: Decimal::ZERO,
             remaining_quantity: quantity,
@@ -430,6 +1185,9 @@ mod tests {
             sequence: 0,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            expire_at: None,
+            display_quantity: None,
+            self_trade_prevention: SelfTradePrevention::default(),
         }
     }
 
@@ -439,15 +1197,15 @@ mod tests {
 
         // Add sell order
         let sell = create_order(Side::Sell, Decimal::new(2000, 0), Decimal::new(1, 0));
-        let (sell_result, trades) = book.process_order(sell);
-        assert!(trades.is_empty());
-        assert_eq!(sell_result.status, OrderStatus::Open);
+        let sell_result = book.process_order(sell);
+        assert!(sell_result.trades.is_empty());
+        assert_eq!(sell_result.order.status, OrderStatus::Open);
 
         // Add matching buy order
         let buy = create_order(Side::Buy, Decimal::new(2000, 0), Decimal::new(1, 0));
-        let (buy_result, trades) = book.process_order(buy);
-        assert_eq!(trades.len(), 1);
-        assert_eq!(buy_result.status, OrderStatus::Filled);
+        let buy_result = book.process_order(buy);
+        assert_eq!(buy_result.trades.len(), 1);
+        assert_eq!(buy_result.order.status, OrderStatus::Filled);
     }
 
     #[test]
@@ -460,7 +1218,7 @@ mod tests {
 
         // Buy only 1 ETH
         let buy = create_order(Side::Buy, Decimal::new(2000, 0), Decimal::new(1, 0));
-        let (_, trades) = book.process_order(buy);
+        let trades = book.process_order(buy).trades;
 
         assert_eq!(trades.len(), 1);
         assert_eq!(trades[0].quantity, Decimal::new(1, 0));
@@ -470,4 +1228,434 @@ mod tests {
         assert_eq!(asks.len(), 1);
         assert_eq!(asks[0].quantity, Decimal::new(1, 0));
     }
+
+    #[test]
+    fn test_ioc_and_fok_never_rest() {
+        let book = OrderBook::new(Symbol::new("ETH", "USDT"));
+        book.process_order(create_order(
+            Side::Sell,
+            Decimal::new(2000, 0),
+            Decimal::ONE,
+        ));
+
+        // FOK for more than the book holds is killed without trading
+        let mut fok = create_order(Side::Buy, Decimal::new(2000, 0), Decimal::new(2, 0));
+        fok.time_in_force = TimeInForce::FOK;
+        let result = book.process_order(fok);
+        assert_eq!(result.order.status, OrderStatus::Expired);
+        assert!(result.trades.is_empty());
+        assert_eq!(book.get_bbo().1, Some(Decimal::new(2000, 0)));
+
+        // IOC fills what it can and cancels the remainder
+        let mut ioc = create_order(Side::Buy, Decimal::new(2000, 0), Decimal::new(2, 0));
+        ioc.time_in_force = TimeInForce::IOC;
+        let result = book.process_order(ioc);
+        assert_eq!(result.order.status, OrderStatus::Cancelled);
+        assert_eq!(result.order.filled_quantity, Decimal::ONE);
+        assert_eq!(result.order.remaining_quantity, Decimal::ONE);
+        assert_eq!(book.get_bbo(), (None, None));
+    }
+
+    #[test]
+    fn test_iceberg_shows_slice_and_refills_behind_level() {
+        let book = OrderBook::new(Symbol::new("ETH", "USDT"));
+        let price = Decimal::new(2000, 0);
+
+        let mut iceberg = create_order(Side::Sell, price, Decimal::new(10, 0));
+        iceberg.display_quantity = Some(Decimal::new(2, 0));
+        let iceberg_id = iceberg.id;
+        book.process_order(iceberg);
+        let plain = create_order(Side::Sell, price, Decimal::ONE);
+        let plain_id = plain.id;
+        book.process_order(plain);
+
+        let (_, asks) = book.get_depth(1);
+        assert_eq!(asks[0].quantity, Decimal::new(3, 0));
+
+        // The first slice fills, and its refill queues behind the plain order
+        let result = book.process_order(create_order(Side::Buy, price, Decimal::new(3, 0)));
+        let makers: Vec<Uuid> = result.trades.iter().map(|t| t.maker_order_id).collect();
+        assert_eq!(makers, vec![iceberg_id, plain_id]);
+        book.check_invariants();
+
+        let (_, asks) = book.get_depth(1);
+        assert_eq!(asks[0].quantity, Decimal::new(2, 0));
+
+        // Hidden reserve counts for cancellation
+        let cancelled = book.cancel_order(iceberg_id).unwrap();
+        assert_eq!(cancelled.remaining_quantity, Decimal::new(8, 0));
+        assert_eq!(book.get_bbo(), (None, None));
+    }
+
+    #[test]
+    fn test_amend_keeps_priority_only_when_reducing() {
+        let book = OrderBook::new(Symbol::new("ETH", "USDT"));
+        let price = Decimal::new(2000, 0);
+
+        let first = create_order(Side::Sell, price, Decimal::new(5, 0));
+        let first_id = first.id;
+        book.process_order(first);
+        book.process_order(create_order(Side::Sell, price, Decimal::ONE));
+
+        // Increasing the quantity or moving the price loses the queue position
+        let mut larger = create_order(Side::Sell, price, Decimal::new(6, 0));
+        assert!(book.amend_order(first_id, &mut larger).is_none());
+        let mut moved = create_order(Side::Sell, Decimal::new(2001, 0), Decimal::ONE);
+        assert!(book.amend_order(first_id, &mut moved).is_none());
+
+        let mut reduced = create_order(Side::Sell, price, Decimal::new(2, 0));
+        let reduced_id = reduced.id;
+        let cancelled = book.amend_order(first_id, &mut reduced).unwrap();
+        assert_eq!(cancelled.remaining_quantity, Decimal::new(5, 0));
+        assert_eq!(reduced.status, OrderStatus::Open);
+        assert!(!book.contains_order(first_id));
+        book.check_invariants();
+
+        let (_, asks) = book.get_depth(1);
+        assert_eq!(asks[0].quantity, Decimal::new(3, 0));
+
+        let result = book.process_order(create_order(Side::Buy, price, Decimal::ONE));
+        assert_eq!(result.trades[0].maker_order_id, reduced_id);
+    }
+
+    #[test]
+    fn test_amend_rejects_another_users_replacement() {
+        let book = OrderBook::new(Symbol::new("ETH", "USDT"));
+        let price = Decimal::new(2000, 0);
+
+        let original = create_order(Side::Sell, price, Decimal::new(5, 0));
+        let (original_id, owner) = (original.id, original.user_id);
+        book.process_order(original);
+        assert_eq!(book.resting_owner(original_id), Some((owner, Side::Sell)));
+
+        // Same side, price and a smaller quantity, but another user
+        let mut foreign = create_order(Side::Sell, price, Decimal::new(2, 0));
+        assert!(book.amend_order(original_id, &mut foreign).is_none());
+        assert_eq!(foreign.status, OrderStatus::Pending);
+        assert_eq!(book.resting_owner(original_id), Some((owner, Side::Sell)));
+
+        let (_, asks) = book.get_depth(1);
+        assert_eq!(asks[0].quantity, Decimal::new(5, 0));
+    }
+
+    #[test]
+    fn test_snapshot_restores_identical_book() {
+        let book = OrderBook::new(Symbol::new("ETH", "USDT"));
+        book.process_order(create_order(Side::Buy, Decimal::new(1990, 0), Decimal::ONE));
+        book.process_order(create_order(Side::Buy, Decimal::new(1995, 0), Decimal::ONE));
+        book.process_order(create_order(
+            Side::Sell,
+            Decimal::new(2005, 0),
+            Decimal::TWO,
+        ));
+        book.process_order(create_order(Side::Buy, Decimal::new(2005, 0), Decimal::ONE));
+
+        let snapshot = book.snapshot();
+        let json = serde_json::to_string(&snapshot).unwrap();
+
+        let restored = OrderBook::new(Symbol::new("ETH", "USDT"));
+        restored.restore(&serde_json::from_str(&json).unwrap());
+        restored.check_invariants();
+
+        assert_eq!(restored.snapshot().bids, snapshot.bids);
+        assert_eq!(restored.snapshot().asks, snapshot.asks);
+        assert_eq!(restored.get_bbo(), book.get_bbo());
+        assert_eq!(restored.book_sequence(), book.book_sequence());
+
+        // Counters continue where the original left off
+        let buy = create_order(Side::Buy, Decimal::new(2005, 0), Decimal::ONE);
+        let result = restored.process_order(buy);
+        assert_eq!(result.trades[0].trade_id, snapshot.next_trade_id);
+        assert_eq!(result.order.sequence, snapshot.next_order_sequence);
+    }
+
+    #[test]
+    fn test_self_trade_prevention_cancels_resting_order() {
+        let book = OrderBook::new(Symbol::new("ETH", "USDT"));
+        let user = Uuid::new_v4();
+
+        // Own resting order ahead of another user's order at the same level
+        let mut own_sell = create_order(Side::Sell, Decimal::new(2000, 0), Decimal::new(2, 0));
+        own_sell.user_id = user;
+        let own_sell_id = own_sell.id;
+        book.process_order(own_sell);
+
+        let other_sell = create_order(Side::Sell, Decimal::new(2000, 0), Decimal::new(3, 0));
+        book.process_order(other_sell);
+
+        let mut buy = create_order(Side::Buy, Decimal::new(2000, 0), Decimal::new(4, 0));
+        buy.user_id = user;
+        let result = book.process_order(buy);
+
+        assert_eq!(result.self_trade_cancels.len(), 1);
+        assert_eq!(result.self_trade_cancels[0].order_id, own_sell_id);
+        assert_eq!(
+            result.self_trade_cancels[0].remaining_quantity,
+            Decimal::new(2, 0)
+        );
+
+        // Only the other user's 3 traded; remaining 1 rests as a bid
+        let traded: Decimal = result.trades.iter().map(|t| t.quantity).sum();
+        assert_eq!(traded, Decimal::new(3, 0));
+        assert_eq!(result.order.status, OrderStatus::PartiallyFilled);
+
+        let (bids, asks) = book.get_depth(10);
+        assert!(asks.is_empty());
+        assert_eq!(bids.len(), 1);
+        assert_eq!(bids[0].quantity, Decimal::new(1, 0));
+
+        // The cancelled order is gone from the index too
+        assert!(book.cancel_order(own_sell_id).is_none());
+        book.check_invariants();
+    }
+
+    #[test]
+    fn test_self_trade_prevention_policies() {
+        let user = Uuid::new_v4();
+        let price = Decimal::new(2000, 0);
+        let run = |policy, quantity: i64| {
+            let book = OrderBook::new(Symbol::new("ETH", "USDT"));
+            let mut own_sell = create_order(Side::Sell, price, Decimal::new(2, 0));
+            own_sell.user_id = user;
+            book.process_order(own_sell);
+            // Warm the depth cache so a change the sequence misses shows up
+            assert_eq!(book.get_depth(1).1[0].quantity, Decimal::new(2, 0));
+
+            let mut buy = create_order(Side::Buy, price, Decimal::new(quantity, 0));
+            buy.user_id = user;
+            buy.self_trade_prevention = policy;
+            let result = book.process_order(buy);
+            book.check_invariants();
+            let resting: Decimal = book.get_depth(1).1.iter().map(|l| l.quantity).sum();
+            (result, resting, book.get_bbo().0)
+        };
+
+        // The taker goes, the maker stays
+        let (result, resting, bid) = run(SelfTradePrevention::CancelTaker, 3);
+        assert!(result.taker_self_trade_cancelled);
+        assert!(result.self_trade_cancels.is_empty());
+        assert_eq!(result.order.status, OrderStatus::Cancelled);
+        assert_eq!((resting, bid), (Decimal::new(2, 0), None));
+
+        // Both go
+        let (result, resting, bid) = run(SelfTradePrevention::CancelBoth, 3);
+        assert!(result.taker_self_trade_cancelled);
+        assert_eq!(result.self_trade_cancels.len(), 1);
+        assert_eq!((resting, bid), (Decimal::ZERO, None));
+
+        // Smaller taker: the maker is reduced and the taker cancelled
+        let (result, resting, _) = run(SelfTradePrevention::DecrementAndCancel, 1);
+        assert!(result.taker_self_trade_cancelled);
+        assert!(result.self_trade_cancels.is_empty());
+        assert_eq!(resting, Decimal::ONE);
+
+        // Larger taker: the maker is cancelled and the rest of the taker rests
+        let (result, resting, bid) = run(SelfTradePrevention::DecrementAndCancel, 3);
+        assert!(!result.taker_self_trade_cancelled);
+        assert_eq!(result.self_trade_cancels.len(), 1);
+        assert!(result.trades.is_empty());
+        assert_eq!(result.order.remaining_quantity, Decimal::ONE);
+        assert_eq!(result.order.status, OrderStatus::Open);
+        assert_eq!((resting, bid), (Decimal::ZERO, Some(price)));
+    }
+
+    #[test]
+    fn test_fok_liquidity_follows_self_trade_policy() {
+        let user = Uuid::new_v4();
+        let price = Decimal::new(2000, 0);
+        // The owner's 1 ahead of another user's 3 at the same level
+        let run = |policy, quantity: i64| {
+            let book = OrderBook::new(Symbol::new("ETH", "USDT"));
+            let mut own_sell = create_order(Side::Sell, price, Decimal::ONE);
+            own_sell.user_id = user;
+            book.process_order(own_sell);
+            book.process_order(create_order(Side::Sell, price, Decimal::new(3, 0)));
+
+            let mut fok = create_order(Side::Buy, price, Decimal::new(quantity, 0));
+            fok.user_id = user;
+            fok.time_in_force = TimeInForce::FOK;
+            fok.self_trade_prevention = policy;
+            let result = book.process_order(fok);
+            book.check_invariants();
+            let traded: Decimal = result.trades.iter().map(|t| t.quantity).sum();
+            (result.order.status, traded)
+        };
+
+        // The own order is cancelled and skipped
+        assert_eq!(
+            run(SelfTradePrevention::CancelMaker, 3),
+            (OrderStatus::Filled, Decimal::new(3, 0))
+        );
+
+        // Nothing behind the own order is reachable
+        for policy in [
+            SelfTradePrevention::CancelTaker,
+            SelfTradePrevention::CancelBoth,
+        ] {
+            assert_eq!(run(policy, 1), (OrderStatus::Expired, Decimal::ZERO));
+        }
+
+        // The own 1 comes off the order, leaving 3 for the other user's 3...
+        assert_eq!(
+            run(SelfTradePrevention::DecrementAndCancel, 4),
+            (OrderStatus::Cancelled, Decimal::new(3, 0))
+        );
+        // ...but not 4
+        assert_eq!(
+            run(SelfTradePrevention::DecrementAndCancel, 5),
+            (OrderStatus::Expired, Decimal::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_self_trade_prevention_preserves_level_totals() {
+        let book = OrderBook::new(Symbol::new("ETH", "USDT"));
+        let user = Uuid::new_v4();
+
+        let first = create_order(Side::Sell, Decimal::new(2000, 0), Decimal::new(1, 0));
+        book.process_order(first);
+
+        let mut own_sell = create_order(Side::Sell, Decimal::new(2000, 0), Decimal::new(2, 0));
+        own_sell.user_id = user;
+        book.process_order(own_sell);
+
+        let last = create_order(Side::Sell, Decimal::new(2000, 0), Decimal::new(5, 0));
+        book.process_order(last);
+
+        // Fill the first order, cancel own order, partially fill the last
+        let mut buy = create_order(Side::Buy, Decimal::new(2000, 0), Decimal::new(3, 0));
+        buy.user_id = user;
+        let result = book.process_order(buy);
+
+        let traded: Decimal = result.trades.iter().map(|t| t.quantity).sum();
+        let cancelled: Decimal = result
+            .self_trade_cancels
+            .iter()
+            .map(|c| c.remaining_quantity)
+            .sum();
+        assert_eq!(traded, Decimal::new(3, 0));
+        assert_eq!(cancelled, Decimal::new(2, 0));
+
+        // 8 resting - 3 traded - 2 cancelled
+        let (_, asks) = book.get_depth(10);
+        assert_eq!(asks.len(), 1);
+        assert_eq!(asks[0].quantity, Decimal::new(3, 0));
+        assert_eq!(asks[0].order_count, 1);
+        book.check_invariants();
+    }
+
+    #[test]
+    fn test_timestamps_follow_injected_clock() {
+        let start = Utc::now();
+        let clock = Arc::new(common::MockClock::new(start));
+        let book = OrderBook::with_clock(Symbol::new("ETH", "USDT"), clock.clone());
+
+        book.process_order(create_order(
+            Side::Sell,
+            Decimal::new(2000, 0),
+            Decimal::new(1, 0),
+        ));
+
+        clock.advance(chrono::Duration::seconds(10));
+        let result = book.process_order(create_order(
+            Side::Buy,
+            Decimal::new(2000, 0),
+            Decimal::new(1, 0),
+        ));
+
+        assert_eq!(
+            result.trades[0].executed_at,
+            start + chrono::Duration::seconds(10)
+        );
+        assert_eq!(
+            result.order.updated_at,
+            start + chrono::Duration::seconds(10)
+        );
+    }
+
+    #[test]
+    fn test_depth_cache_invalidated_by_book_changes() {
+        let book = OrderBook::new(Symbol::new("ETH", "USDT"));
+
+        book.process_order(create_order(Side::Buy, Decimal::new(2000, 0), Decimal::ONE));
+        let (bids, _) = book.get_depth(10);
+        assert_eq!(bids.len(), 1);
+
+        // Cached result, truncated to the smaller request
+        let (bids, asks) = book.get_depth(1);
+        assert_eq!(bids.len(), 1);
+        assert!(asks.is_empty());
+
+        book.process_order(create_order(Side::Buy, Decimal::new(1999, 0), Decimal::ONE));
+        let (bids, _) = book.get_depth(10);
+        assert_eq!(bids.len(), 2);
+        assert_eq!(bids[1].price, Decimal::new(1999, 0));
+    }
+
+    #[test]
+    fn test_memory_tracks_resting_orders() {
+        let book = OrderBook::new(Symbol::new("ETH", "USDT"));
+        assert_eq!(book.memory().resting_orders, 0);
+
+        book.process_order(create_order(Side::Buy, Decimal::new(2000, 0), Decimal::ONE));
+        book.process_order(create_order(Side::Buy, Decimal::new(2000, 0), Decimal::ONE));
+        book.process_order(create_order(
+            Side::Sell,
+            Decimal::new(2001, 0),
+            Decimal::ONE,
+        ));
+        let memory = book.memory();
+        assert_eq!(memory.resting_orders, 3);
+        assert_eq!(book.resting_orders(), 3);
+        assert_eq!(memory.price_levels, 2);
+        assert!(memory.bytes >= 3 * std::mem::size_of::<OrderEntry>());
+
+        // Matching the ask frees its level
+        book.process_order(create_order(Side::Buy, Decimal::new(2001, 0), Decimal::ONE));
+        let memory = book.memory();
+        assert_eq!(memory.resting_orders, 2);
+        assert_eq!(memory.price_levels, 1);
+    }
+
+    #[test]
+    fn test_halted_book_rests_crossed_orders_and_uncrosses() {
+        let book = OrderBook::new(Symbol::new("ETH", "USDT"));
+
+        let bid = book.rest_order(create_order(
+            Side::Buy,
+            Decimal::new(2010, 0),
+            Decimal::new(3, 0),
+        ));
+        let ask = book.rest_order(create_order(
+            Side::Sell,
+            Decimal::new(2000, 0),
+            Decimal::new(2, 0),
+        ));
+        book.rest_order(create_order(
+            Side::Sell,
+            Decimal::new(2005, 0),
+            Decimal::new(2, 0),
+        ));
+        assert_eq!(bid.status, OrderStatus::Open);
+        assert!(book.is_crossed());
+
+        let (bids, asks) = book.crossing_interest();
+        assert_eq!(bids, vec![(Decimal::new(2010, 0), Decimal::new(3, 0))]);
+        assert_eq!(asks.len(), 2);
+
+        let result = book.uncross(Decimal::new(2005, 0));
+        assert_eq!(result.trades.len(), 2);
+        assert!(result
+            .trades
+            .iter()
+            .all(|t| t.price == Decimal::new(2005, 0) && t.taker_order_id == bid.id));
+        assert_eq!(result.trades[0].maker_order_id, ask.id);
+        assert_eq!(result.trades[1].quantity, Decimal::ONE);
+
+        assert!(!book.is_crossed());
+        let (bids, asks) = book.get_depth(10);
+        assert!(bids.is_empty());
+        assert_eq!(asks[0].quantity, Decimal::ONE);
+        book.check_invariants();
+    }
 }
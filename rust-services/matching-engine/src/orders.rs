@@ -0,0 +1,234 @@
+//! Order State Store
+//!
+//! Latest state of every order the engine has processed, indexed by order
+//! id and by user, so clients can reconcile over HTTP without consuming
+//! the order event stream. Updated from the same places that publish
+//! order events; resting orders pick up maker fills from trades.
+//!
+//! Open orders are kept until they close. Closed orders are retained up
+//! to `order_store_max_closed`, oldest evicted first.
+
+use std::collections::{HashSet, VecDeque};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use common::{Order, OrderStatus, Trade};
+
+/// Which orders a listing returns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusFilter {
+    /// Pending, open and partially filled
+    Open,
+    /// Filled, cancelled, rejected and expired
+    Closed,
+    #[default]
+    All,
+}
+
+impl StatusFilter {
+    fn matches(self, status: OrderStatus) -> bool {
+        match self {
+            Self::Open => !is_closed(status),
+            Self::Closed => is_closed(status),
+            Self::All => true,
+        }
+    }
+}
+
+fn is_closed(status: OrderStatus) -> bool {
+    matches!(
+        status,
+        OrderStatus::Filled | OrderStatus::Cancelled | OrderStatus::Rejected | OrderStatus::Expired
+    )
+}
+
+pub struct OrderStore {
+    orders: DashMap<Uuid, Order>,
+
+    /// User -> their order ids
+    by_user: DashMap<Uuid, HashSet<Uuid>>,
+
+    /// Closed order ids, oldest first
+    closed: Mutex<VecDeque<Uuid>>,
+    max_closed: usize,
+}
+
+impl OrderStore {
+    pub fn new(max_closed: usize) -> Self {
+        Self {
+            orders: DashMap::new(),
+            by_user: DashMap::new(),
+            closed: Mutex::new(VecDeque::new()),
+            max_closed,
+        }
+    }
+
+    /// Record the latest state of an order
+    pub fn upsert(&self, order: &Order) {
+        let was_closed = self
+            .orders
+            .insert(order.id, order.clone())
+            .is_some_and(|previous| is_closed(previous.status));
+        self.by_user
+            .entry(order.user_id)
+            .or_default()
+            .insert(order.id);
+
+        if is_closed(order.status) && !was_closed {
+            self.on_closed(order.id);
+        }
+    }
+
+    /// Apply a trade to its resting maker order
+    pub fn record_fill(&self, trade: &Trade) {
+        self.apply_fill(trade.maker_order_id, trade);
+    }
+
+    /// Apply an auction trade to its taker, which was resting too
+    pub fn record_taker_fill(&self, trade: &Trade) {
+        self.apply_fill(trade.taker_order_id, trade);
+    }
+
+    fn apply_fill(&self, order_id: Uuid, trade: &Trade) {
+        let closed = {
+            let Some(mut order) = self.orders.get_mut(&order_id) else {
+                return;
+            };
+            let filled = order.filled_quantity + trade.quantity;
+            let notional = order.avg_fill_price.unwrap_or_default() * order.filled_quantity
+                + trade.quote_quantity;
+            order.avg_fill_price = Some(notional / filled);
+            order.filled_quantity = filled;
+            order.remaining_quantity =
+                (order.remaining_quantity - trade.quantity).max(Decimal::ZERO);
+            order.status = if order.remaining_quantity.is_zero() {
+                OrderStatus::Filled
+            } else {
+                OrderStatus::PartiallyFilled
+            };
+            order.updated_at = trade.executed_at;
+            order.status == OrderStatus::Filled
+        };
+
+        if closed {
+            self.on_closed(order_id);
+        }
+    }
+
+    /// Mark a resting order cancelled
+    pub fn cancel(&self, order_id: Uuid, at: DateTime<Utc>) {
+        let cancelled = self
+            .orders
+            .get_mut(&order_id)
+            .filter(|order| !is_closed(order.status))
+            .map(|mut order| {
+                order.status = OrderStatus::Cancelled;
+                order.updated_at = at;
+            })
+            .is_some();
+
+        if cancelled {
+            self.on_closed(order_id);
+        }
+    }
+
+    pub fn get(&self, order_id: Uuid) -> Option<Order> {
+        self.orders.get(&order_id).map(|o| o.clone())
+    }
+
+    /// A user's orders matching `filter`, newest first
+    pub fn list_for_user(&self, user_id: Uuid, filter: StatusFilter) -> Vec<Order> {
+        let Some(ids) = self.by_user.get(&user_id).map(|ids| ids.clone()) else {
+            return Vec::new();
+        };
+
+        let mut orders: Vec<Order> = ids
+            .iter()
+            .filter_map(|id| self.get(*id))
+            .filter(|order| filter.matches(order.status))
+            .collect();
+        orders.sort_by_key(|o| std::cmp::Reverse(o.created_at));
+        orders
+    }
+
+    /// Queue a newly closed order for eviction
+    fn on_closed(&self, order_id: Uuid) {
+        let evicted: Vec<Uuid> = {
+            let mut closed = self.closed.lock();
+            closed.push_back(order_id);
+            let excess = closed.len().saturating_sub(self.max_closed);
+            closed.drain(..excess).collect()
+        };
+
+        for id in evicted {
+            if let Some((_, order)) = self.orders.remove(&id) {
+                if let Some(mut ids) = self.by_user.get_mut(&order.user_id) {
+                    ids.remove(&id);
+                }
+                self.by_user
+                    .remove_if(&order.user_id, |_, ids| ids.is_empty());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::Side;
+
+    fn order(user_id: Uuid, status: OrderStatus) -> Order {
+        Order::builder()
+            .user(user_id)
+            .side(Side::Sell)
+            .status(status)
+            .quantity(2)
+            .build()
+    }
+
+    #[test]
+    fn test_tracks_maker_fills_and_evicts_closed() {
+        let store = OrderStore::new(1);
+        let user = Uuid::new_v4();
+
+        let resting = order(user, OrderStatus::Open);
+        store.upsert(&resting);
+        let rejected = order(user, OrderStatus::Rejected);
+        store.upsert(&rejected);
+
+        let trade = Trade {
+            id: Uuid::new_v4(),
+            trade_id: 1,
+            symbol: resting.symbol.clone(),
+            maker_order_id: resting.id,
+            maker_user_id: user,
+            taker_order_id: Uuid::new_v4(),
+            taker_user_id: Uuid::new_v4(),
+            price: Decimal::new(2000, 0),
+            quantity: Decimal::ONE,
+            quote_quantity: Decimal::new(2000, 0),
+            taker_side: Side::Buy,
+            executed_at: Utc::now(),
+        };
+        store.record_fill(&trade);
+
+        let open = store.list_for_user(user, StatusFilter::Open);
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].status, OrderStatus::PartiallyFilled);
+        assert_eq!(open[0].remaining_quantity, Decimal::ONE);
+        assert_eq!(open[0].avg_fill_price, Some(Decimal::new(2000, 0)));
+
+        // Filling the rest closes it and evicts the older rejected order
+        store.record_fill(&trade);
+        assert_eq!(store.get(resting.id).unwrap().status, OrderStatus::Filled);
+        assert!(store.get(rejected.id).is_none());
+        assert!(store.list_for_user(user, StatusFilter::Open).is_empty());
+        assert_eq!(store.list_for_user(user, StatusFilter::All).len(), 1);
+    }
+}
@@ -0,0 +1,175 @@
+//! Event Outbox
+//!
+//! Postgres table of events waiting for Kafka. With the outbox enabled the
+//! matching loop stores every event a command produced before it takes the
+//! next command, and the publisher relays stored events to Kafka, marking
+//! each one delivered once the broker acknowledges it. A Kafka outage then
+//! delays events instead of losing them.
+//!
+//! Delivery is at least once: an event produced just before a crash or a
+//! failed mark is produced again, and commands replayed from the journal
+//! store their events again. Consumers deduplicate on the event `id`.
+
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Duration;
+
+use anyhow::Result;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::Row;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::config::Config;
+
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS engine_event_outbox (
+        id BIGINT PRIMARY KEY,
+        event_id UUID NOT NULL,
+        topic TEXT NOT NULL,
+        key TEXT NOT NULL,
+        payload TEXT NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+        delivered_at TIMESTAMPTZ
+    )",
+    "CREATE INDEX IF NOT EXISTS engine_event_outbox_undelivered
+        ON engine_event_outbox (id) WHERE delivered_at IS NULL",
+    "CREATE INDEX IF NOT EXISTS engine_event_outbox_delivered
+        ON engine_event_outbox (delivered_at)",
+];
+
+/// An event to store
+#[derive(Debug, Clone)]
+pub struct OutboxEvent {
+    pub event_id: Uuid,
+    pub topic: String,
+    pub key: String,
+    pub payload: String,
+}
+
+/// A stored event not yet delivered
+#[derive(Debug, Clone)]
+pub struct PendingEvent {
+    /// Position in the outbox; events are relayed in this order
+    pub id: i64,
+    pub event: OutboxEvent,
+}
+
+pub struct EventOutbox {
+    pool: PgPool,
+    /// Id of the next stored event; the matching loop is the only writer
+    next_id: AtomicI64,
+    retention: Duration,
+}
+
+impl EventOutbox {
+    /// Connect and create the outbox table, or `None` when disabled
+    pub async fn from_config(config: &Config) -> Result<Option<Self>> {
+        if !config.event_outbox_enabled || config.command_journal_audit {
+            return Ok(None);
+        }
+
+        let pool = PgPoolOptions::new()
+            .max_connections(config.database_pool_size)
+            .connect(&config.database_url)
+            .await?;
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&pool).await?;
+        }
+        let last: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(id), 0) FROM engine_event_outbox")
+            .fetch_one(&pool)
+            .await?;
+        info!(last_id = last, "Event outbox enabled");
+
+        Ok(Some(Self {
+            pool,
+            next_id: AtomicI64::new(last + 1),
+            retention: Duration::from_secs(config.event_outbox_retention_secs),
+        }))
+    }
+
+    /// Store events in order, in one transaction
+    ///
+    /// Ids are only consumed once the events are stored, so a failed call
+    /// can be retried with the same events.
+    pub async fn append(&self, events: &[OutboxEvent]) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        let first = self.next_id.load(Ordering::Acquire);
+
+        let ids: Vec<i64> = (first..).take(events.len()).collect();
+        let event_ids: Vec<Uuid> = events.iter().map(|e| e.event_id).collect();
+        let topics: Vec<&str> = events.iter().map(|e| e.topic.as_str()).collect();
+        let keys: Vec<&str> = events.iter().map(|e| e.key.as_str()).collect();
+        let payloads: Vec<&str> = events.iter().map(|e| e.payload.as_str()).collect();
+
+        // A retry after an unacknowledged commit finds its rows stored
+        sqlx::query(
+            "INSERT INTO engine_event_outbox (id, event_id, topic, key, payload)
+             SELECT * FROM UNNEST($1::BIGINT[], $2::UUID[], $3::TEXT[], $4::TEXT[], $5::TEXT[])
+             ON CONFLICT (id) DO NOTHING",
+        )
+        .bind(&ids)
+        .bind(&event_ids)
+        .bind(&topics)
+        .bind(&keys)
+        .bind(&payloads)
+        .execute(&self.pool)
+        .await?;
+
+        self.next_id
+            .store(first + events.len() as i64, Ordering::Release);
+        Ok(())
+    }
+
+    /// Oldest undelivered events, up to `limit`
+    pub async fn pending(&self, limit: usize) -> Result<Vec<PendingEvent>> {
+        let rows = sqlx::query(
+            "SELECT id, event_id, topic, key, payload FROM engine_event_outbox
+             WHERE delivered_at IS NULL
+             ORDER BY id
+             LIMIT $1",
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(PendingEvent {
+                    id: row.try_get("id")?,
+                    event: OutboxEvent {
+                        event_id: row.try_get("event_id")?,
+                        topic: row.try_get("topic")?,
+                        key: row.try_get("key")?,
+                        payload: row.try_get("payload")?,
+                    },
+                })
+            })
+            .collect()
+    }
+
+    pub async fn mark_delivered(&self, ids: &[i64]) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        sqlx::query("UPDATE engine_event_outbox SET delivered_at = now() WHERE id = ANY($1)")
+            .bind(ids)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Delete events delivered longer ago than the retention period,
+    /// returning how many were deleted
+    pub async fn purge(&self) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM engine_event_outbox
+             WHERE delivered_at < now() - make_interval(secs => $1)",
+        )
+        .bind(self.retention.as_secs_f64())
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
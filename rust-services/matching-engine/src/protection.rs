@@ -0,0 +1,296 @@
+//! Price Protections
+//!
+//! Per-symbol guards against erroneous prices. A limit order priced more
+//! than the price band away from its symbol's reference price, the last
+//! trade or before the first trade the last mark price, is rejected. A
+//! circuit breaker halts the symbol when trades move the price more than
+//! its threshold within the window; the book then collects orders for an
+//! auction, as under a reference-data halt, until an admin resumes it.
+//! Admins may also halt a symbol by hand.
+//!
+//! Trips are decided at the journaled time of the command that traded,
+//! and admin halts are journaled, so replaying the journal halts the same
+//! symbols. Snapshots carry the halts in force; a restored symbol's
+//! breaker window starts empty.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use common::{Order, TradingError};
+
+const BPS: Decimal = Decimal::from_parts(10_000, 0, 0, false, 0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HaltReason {
+    CircuitBreaker,
+    Admin,
+}
+
+impl HaltReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HaltReason::CircuitBreaker => "circuit_breaker",
+            HaltReason::Admin => "admin",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Halt {
+    pub reason: HaltReason,
+    pub since: DateTime<Utc>,
+}
+
+/// Price move that tripped a circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trip {
+    /// Window extreme the price moved away from
+    pub from: Decimal,
+    pub to: Decimal,
+    pub move_bps: Decimal,
+}
+
+#[derive(Debug, Default)]
+struct SymbolGuard {
+    last_trade: Option<Decimal>,
+    mark: Option<Decimal>,
+    /// Trades in the window that may still be its low or high, oldest
+    /// first; prices increase through `lows` and decrease through `highs`
+    lows: VecDeque<(DateTime<Utc>, Decimal)>,
+    highs: VecDeque<(DateTime<Utc>, Decimal)>,
+    halt: Option<Halt>,
+}
+
+impl SymbolGuard {
+    fn reference(&self) -> Option<Decimal> {
+        self.last_trade.or(self.mark)
+    }
+
+    /// Add a trade to the window ending at `at`
+    fn record(&mut self, price: Decimal, at: DateTime<Utc>, window: chrono::Duration) {
+        let cutoff = at - window;
+        for side in [&mut self.lows, &mut self.highs] {
+            while side.front().is_some_and(|(t, _)| *t < cutoff) {
+                side.pop_front();
+            }
+        }
+        while self.lows.back().is_some_and(|(_, p)| *p >= price) {
+            self.lows.pop_back();
+        }
+        while self.highs.back().is_some_and(|(_, p)| *p <= price) {
+            self.highs.pop_back();
+        }
+        self.lows.push_back((at, price));
+        self.highs.push_back((at, price));
+    }
+}
+
+/// Price bands and circuit breakers of every symbol
+pub struct PriceProtection {
+    window: chrono::Duration,
+    guards: DashMap<String, SymbolGuard>,
+}
+
+impl PriceProtection {
+    pub fn new(window: chrono::Duration) -> Self {
+        Self {
+            window,
+            guards: DashMap::new(),
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(chrono::Duration::seconds(
+            config.circuit_breaker_window_secs as i64,
+        ))
+    }
+
+    /// Price the band is centred on
+    pub fn reference(&self, symbol: &str) -> Option<Decimal> {
+        self.guards.get(symbol)?.reference()
+    }
+
+    /// Check a limit price against the band of `band_bps` around the
+    /// reference; market orders, stops and unreferenced symbols pass
+    pub fn check_band(&self, order: &Order, band_bps: u32) -> Result<(), TradingError> {
+        if band_bps == 0 || order.is_stop() {
+            return Ok(());
+        }
+        let (Some(price), Some(reference)) = (order.price, self.reference(&order.symbol.0)) else {
+            return Ok(());
+        };
+
+        let band = reference * Decimal::from(band_bps) / BPS;
+        if (price - reference).abs() > band {
+            return Err(TradingError::InvalidOrder(format!(
+                "Price {price} is more than {band_bps} bps from the reference price {reference}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Latest mark price; the reference until the symbol first trades
+    pub fn on_mark_price(&self, symbol: &str, price: Decimal) {
+        self.guards.entry(symbol.to_string()).or_default().mark = Some(price);
+    }
+
+    /// Record a trade, halting the symbol if the price moved more than
+    /// `breaker_bps` within the window; returns the move that tripped it
+    pub fn on_trade(
+        &self,
+        symbol: &str,
+        price: Decimal,
+        at: DateTime<Utc>,
+        breaker_bps: u32,
+    ) -> Option<Trip> {
+        let mut guard = self.guards.entry(symbol.to_string()).or_default();
+        guard.last_trade = Some(price);
+        guard.record(price, at, self.window);
+        if breaker_bps == 0 || guard.halt.is_some() {
+            return None;
+        }
+
+        let (_, low) = *guard.lows.front()?;
+        let (_, high) = *guard.highs.front()?;
+        let (from, moved) = if (high - price) / high >= (price - low) / low {
+            (high, (high - price) / high)
+        } else {
+            (low, (price - low) / low)
+        };
+        let move_bps = (moved * BPS).round_dp(2);
+        if move_bps <= Decimal::from(breaker_bps) {
+            return None;
+        }
+
+        guard.halt = Some(Halt {
+            reason: HaltReason::CircuitBreaker,
+            since: at,
+        });
+        Some(Trip {
+            from,
+            to: price,
+            move_bps,
+        })
+    }
+
+    /// Halt a symbol; false if it already is
+    pub fn halt(&self, symbol: &str, reason: HaltReason, at: DateTime<Utc>) -> bool {
+        let mut guard = self.guards.entry(symbol.to_string()).or_default();
+        if guard.halt.is_some() {
+            return false;
+        }
+        guard.halt = Some(Halt { reason, since: at });
+        true
+    }
+
+    /// Lift a symbol's halt, restarting its breaker window; false if it
+    /// was not halted
+    pub fn resume(&self, symbol: &str) -> bool {
+        let Some(mut guard) = self.guards.get_mut(symbol) else {
+            return false;
+        };
+        if guard.halt.take().is_none() {
+            return false;
+        }
+        guard.lows.clear();
+        guard.highs.clear();
+        true
+    }
+
+    pub fn is_halted(&self, symbol: &str) -> bool {
+        self.guards
+            .get(symbol)
+            .is_some_and(|guard| guard.halt.is_some())
+    }
+
+    /// Halted symbols
+    pub fn halts(&self) -> BTreeMap<String, Halt> {
+        self.guards
+            .iter()
+            .filter_map(|guard| Some((guard.key().clone(), guard.halt?)))
+            .collect()
+    }
+
+    /// Reinstate the halts of a snapshot
+    pub fn restore(&self, halts: &BTreeMap<String, Halt>) {
+        for (symbol, halt) in halts {
+            self.guards.entry(symbol.clone()).or_default().halt = Some(*halt);
+        }
+    }
+
+    /// Forget a delisted symbol
+    pub fn remove(&self, symbol: &str) {
+        self.guards.remove(symbol);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::Side;
+
+    fn order(side: Side, price: i64) -> Order {
+        Order::builder().side(side).price(price).build()
+    }
+
+    #[test]
+    fn test_band_around_reference_price() {
+        let protection = PriceProtection::new(chrono::Duration::minutes(5));
+
+        // Nothing to band against yet
+        assert!(protection.check_band(&order(Side::Buy, 5000), 500).is_ok());
+
+        protection.on_mark_price("ETH-USDT", Decimal::new(2000, 0));
+        assert!(protection.check_band(&order(Side::Buy, 2100), 500).is_ok());
+        assert!(protection.check_band(&order(Side::Buy, 2101), 500).is_err());
+
+        // The last trade takes over from the mark price
+        protection.on_trade("ETH-USDT", Decimal::new(1000, 0), Utc::now(), 0);
+        assert!(protection.check_band(&order(Side::Sell, 949), 500).is_err());
+        assert!(protection.check_band(&order(Side::Sell, 950), 500).is_ok());
+        assert!(protection.check_band(&order(Side::Sell, 949), 0).is_ok());
+    }
+
+    #[test]
+    fn test_breaker_trips_on_move_within_window() {
+        let protection = PriceProtection::new(chrono::Duration::minutes(5));
+        let t0 = Utc::now();
+
+        assert!(protection
+            .on_trade("ETH-USDT", Decimal::new(2000, 0), t0, 1000)
+            .is_none());
+        assert!(protection
+            .on_trade("ETH-USDT", Decimal::new(2150, 0), t0, 1000)
+            .is_none());
+
+        // 10% off the window's high, but the high has aged out
+        let later = t0 + chrono::Duration::minutes(6);
+        assert!(protection
+            .on_trade("ETH-USDT", Decimal::new(1935, 0), later, 1000)
+            .is_none());
+        assert!(!protection.is_halted("ETH-USDT"));
+
+        let trip = protection
+            .on_trade("ETH-USDT", Decimal::new(2150, 0), later, 1000)
+            .unwrap();
+        assert_eq!(trip.from, Decimal::new(1935, 0));
+        assert_eq!(trip.move_bps, Decimal::new(111111, 2));
+        assert_eq!(
+            protection.halts()["ETH-USDT"].reason,
+            HaltReason::CircuitBreaker
+        );
+
+        // Resuming restarts the window
+        assert!(protection.resume("ETH-USDT"));
+        assert!(!protection.resume("ETH-USDT"));
+        assert!(protection
+            .on_trade("ETH-USDT", Decimal::new(2000, 0), later, 1000)
+            .is_none());
+    }
+}
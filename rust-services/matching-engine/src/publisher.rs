@@ -0,0 +1,282 @@
+//! Event Publisher
+//!
+//! The matching path only serializes each event and queues it. A
+//! dedicated task hands queued events to the Kafka producer in batches and
+//! then collects their delivery reports, so matching latency no longer
+//! includes broker round trips. How the producer groups messages into
+//! requests is tuned with `kafka_linger_ms` and `kafka_batch_size`.
+//!
+//! Events reach the producer in queue order and the idempotent producer
+//! keeps that order within each partition. When the queue is full the
+//! matching loop waits for room rather than dropping events; events still
+//! queued when the process dies are lost.
+//!
+//! With the event outbox enabled, events are staged instead and the
+//! matching loop commits them to the outbox after each command, waiting
+//! out a Postgres outage rather than moving on. The task then relays the
+//! outbox to Kafka. An event whose delivery fails is retried on a later
+//! pass, after events produced behind it.
+
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use rdkafka::error::KafkaError;
+use rdkafka::producer::{DeliveryFuture, FutureProducer, FutureRecord};
+use rdkafka::types::RDKafkaErrorCode;
+use rdkafka::ClientConfig;
+use tokio::sync::{mpsc, Mutex, Notify};
+use tracing::{error, warn};
+
+use common::events::{Event, TypedEvent};
+
+use crate::config::Config;
+use crate::outbox::{EventOutbox, OutboxEvent};
+
+/// Events handed to the producer before delivery reports are collected
+const MAX_BATCH: usize = 1000;
+
+/// Wait before retrying when the producer's own queue is full
+const PRODUCER_QUEUE_FULL_BACKOFF: Duration = Duration::from_millis(10);
+
+/// Delay before a failed outbox write or read is retried
+const OUTBOX_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// How often delivered events past their retention are deleted
+const OUTBOX_PURGE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A serialized event waiting to be produced
+#[derive(Debug)]
+struct Outbound {
+    topic: &'static str,
+    key: String,
+    payload: String,
+}
+
+/// Outbox mode: events wait in `staged` until committed to the outbox
+struct Relay {
+    outbox: EventOutbox,
+    staged: parking_lot::Mutex<Vec<OutboxEvent>>,
+    /// Held across a commit so staged events are stored in order
+    committing: Mutex<()>,
+    committed: Notify,
+    poll_interval: Duration,
+}
+
+pub struct EventPublisher {
+    producer: FutureProducer,
+    tx: mpsc::Sender<Outbound>,
+    /// Held by the running publisher; a supervised restart picks it up again
+    rx: Mutex<mpsc::Receiver<Outbound>>,
+    relay: Option<Relay>,
+}
+
+impl EventPublisher {
+    pub async fn new(config: &Config) -> Result<Self> {
+        let producer: FutureProducer = ClientConfig::new()
+            .set("bootstrap.servers", &config.kafka_brokers)
+            .set("message.timeout.ms", "5000")
+            .set("acks", "all")
+            .set("enable.idempotence", "true")
+            .set("linger.ms", config.kafka_linger_ms.to_string())
+            .set("batch.size", config.kafka_batch_size.to_string())
+            .create()?;
+
+        let relay = EventOutbox::from_config(config).await?.map(|outbox| Relay {
+            outbox,
+            staged: parking_lot::Mutex::new(Vec::new()),
+            committing: Mutex::new(()),
+            committed: Notify::new(),
+            poll_interval: Duration::from_millis(config.event_outbox_poll_ms),
+        });
+
+        let (tx, rx) = mpsc::channel(config.event_queue_size);
+        Ok(Self {
+            producer,
+            tx,
+            rx: Mutex::new(rx),
+            relay,
+        })
+    }
+
+    /// Queue an event for its payload's topic and partition key
+    pub async fn publish<T: TypedEvent>(&self, payload: T) -> Result<()> {
+        let event = Event::builder(payload).build();
+
+        if let Some(relay) = &self.relay {
+            relay.staged.lock().push(OutboxEvent {
+                event_id: event.event.id,
+                topic: event.topic.to_string(),
+                payload: event.to_json()?,
+                key: event.key,
+            });
+            return Ok(());
+        }
+
+        let outbound = Outbound {
+            topic: event.topic,
+            payload: event.to_json()?,
+            key: event.key,
+        };
+
+        self.tx
+            .send(outbound)
+            .await
+            .map_err(|_| anyhow::anyhow!("Event publisher channel closed"))
+    }
+
+    /// Store staged events in the outbox, retrying until Postgres takes
+    /// them; a no-op without the outbox
+    pub async fn commit(&self) {
+        let Some(relay) = &self.relay else {
+            return;
+        };
+        let _committing = relay.committing.lock().await;
+        let events = std::mem::take(&mut *relay.staged.lock());
+        if events.is_empty() {
+            return;
+        }
+
+        while let Err(e) = relay.outbox.append(&events).await {
+            metrics::counter!("outbox_write_failures").increment(1);
+            warn!(error = %e, events = events.len(), "Event outbox write failed, retrying");
+            tokio::time::sleep(OUTBOX_RETRY_DELAY).await;
+        }
+        metrics::counter!("outbox_events_written").increment(events.len() as u64);
+        relay.committed.notify_one();
+    }
+
+    /// Produce queued events until the queue closes, or relay the outbox
+    /// when it is enabled
+    pub async fn run(&self) -> Result<()> {
+        if let Some(relay) = &self.relay {
+            return self.run_relay(relay).await;
+        }
+
+        let mut rx = self.rx.lock().await;
+        let mut batch = Vec::with_capacity(MAX_BATCH);
+
+        while rx.recv_many(&mut batch, MAX_BATCH).await > 0 {
+            let start = Instant::now();
+            metrics::histogram!("event_batch_size").record(batch.len() as f64);
+
+            let mut deliveries = Vec::with_capacity(batch.len());
+            for event in batch.drain(..) {
+                if let Some(delivery) = self.produce(event.topic, &event.key, &event.payload).await
+                {
+                    deliveries.push((event.topic, delivery));
+                }
+            }
+
+            for (topic, delivery) in deliveries {
+                self.delivered(topic, delivery).await;
+            }
+
+            metrics::histogram!("event_publish_ms").record(start.elapsed().as_millis() as f64);
+            metrics::gauge!("event_queue_depth").set(rx.len() as f64);
+        }
+
+        Ok(())
+    }
+
+    /// Relay undelivered outbox events to Kafka, oldest first
+    async fn run_relay(&self, relay: &Relay) -> Result<()> {
+        let mut last_purge = Instant::now();
+
+        loop {
+            // Events published outside the matching loop have no command
+            // to commit them
+            self.commit().await;
+
+            let pending = match relay.outbox.pending(MAX_BATCH).await {
+                Ok(pending) => pending,
+                Err(e) => {
+                    warn!(error = %e, "Event outbox read failed, retrying");
+                    tokio::time::sleep(OUTBOX_RETRY_DELAY).await;
+                    continue;
+                }
+            };
+            metrics::gauge!("outbox_backlog").set(pending.len() as f64);
+
+            if pending.is_empty() {
+                if last_purge.elapsed() >= OUTBOX_PURGE_INTERVAL {
+                    match relay.outbox.purge().await {
+                        Ok(purged) => {
+                            metrics::counter!("outbox_events_purged").increment(purged);
+                        }
+                        Err(e) => warn!(error = %e, "Event outbox purge failed"),
+                    }
+                    last_purge = Instant::now();
+                }
+                let _ = tokio::time::timeout(relay.poll_interval, relay.committed.notified()).await;
+                continue;
+            }
+
+            let start = Instant::now();
+            metrics::histogram!("event_batch_size").record(pending.len() as f64);
+
+            let mut deliveries = Vec::with_capacity(pending.len());
+            for pending in &pending {
+                let event = &pending.event;
+                if let Some(delivery) = self.produce(&event.topic, &event.key, &event.payload).await
+                {
+                    deliveries.push((pending.id, event.topic.as_str(), delivery));
+                }
+            }
+
+            let mut delivered = Vec::with_capacity(deliveries.len());
+            for (id, topic, delivery) in deliveries {
+                if self.delivered(topic, delivery).await {
+                    delivered.push(id);
+                }
+            }
+
+            // Unmarked events are produced again, and deduplicated downstream
+            if let Err(e) = relay.outbox.mark_delivered(&delivered).await {
+                warn!(error = %e, events = delivered.len(), "Marking outbox events delivered failed");
+            }
+            metrics::histogram!("event_publish_ms").record(start.elapsed().as_millis() as f64);
+
+            if delivered.len() < pending.len() {
+                tokio::time::sleep(OUTBOX_RETRY_DELAY).await;
+            }
+        }
+    }
+
+    /// Hand an event to the producer, waiting while its queue is full
+    async fn produce(&self, topic: &str, key: &str, payload: &str) -> Option<DeliveryFuture> {
+        loop {
+            let record = FutureRecord::to(topic).key(key).payload(payload);
+            match self.producer.send_result(record) {
+                Ok(delivery) => return Some(delivery),
+                Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), _)) => {
+                    tokio::time::sleep(PRODUCER_QUEUE_FULL_BACKOFF).await;
+                }
+                Err((e, _)) => {
+                    metrics::counter!("events_failed", "topic" => topic.to_string()).increment(1);
+                    error!(topic, "Event rejected by producer: {}", e);
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Wait for a delivery report, returning whether the event was delivered
+    async fn delivered(&self, topic: &str, delivery: DeliveryFuture) -> bool {
+        match delivery.await {
+            Ok(Ok(_)) => {
+                metrics::counter!("events_published", "topic" => topic.to_string()).increment(1);
+                true
+            }
+            Ok(Err((e, _))) => {
+                metrics::counter!("events_failed", "topic" => topic.to_string()).increment(1);
+                error!(topic, "Event delivery failed: {}", e);
+                false
+            }
+            Err(_) => {
+                metrics::counter!("events_failed", "topic" => topic.to_string()).increment(1);
+                error!(topic, "Event delivery report lost");
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! Market Quality Statistics
+//!
+//! Per-symbol execution quality of incoming orders: price improvement
+//! over limit prices, effective vs quoted spread and fill rates on
+//! arrival. Statistics accumulate over a window that is closed and
+//! published as a `MarketQuality` event at a fixed interval.
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use common::{MarketQuality, Order, Side, Symbol, Trade};
+
+const BPS: Decimal = Decimal::from_parts(10_000, 0, 0, false, 0);
+
+#[derive(Debug)]
+struct Window {
+    start: DateTime<Utc>,
+    orders: u64,
+    filled_orders: u64,
+    fully_filled_orders: u64,
+    submitted_quantity: Decimal,
+    filled_quantity: Decimal,
+    limit_fills: u64,
+    price_improved_fills: u64,
+    price_improvement: Decimal,
+    quoted_spread_sum_bps: Decimal,
+    quoted_samples: u64,
+    effective_spread_sum_bps: Decimal,
+    effective_quantity: Decimal,
+}
+
+impl Window {
+    fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            start,
+            orders: 0,
+            filled_orders: 0,
+            fully_filled_orders: 0,
+            submitted_quantity: Decimal::ZERO,
+            filled_quantity: Decimal::ZERO,
+            limit_fills: 0,
+            price_improved_fills: 0,
+            price_improvement: Decimal::ZERO,
+            quoted_spread_sum_bps: Decimal::ZERO,
+            quoted_samples: 0,
+            effective_spread_sum_bps: Decimal::ZERO,
+            effective_quantity: Decimal::ZERO,
+        }
+    }
+
+    fn snapshot(&self, symbol: &Symbol, end: DateTime<Utc>) -> MarketQuality {
+        let ratio = |num: Decimal, den: Decimal| (!den.is_zero()).then(|| num / den);
+
+        MarketQuality {
+            symbol: symbol.clone(),
+            window_start: self.start,
+            window_end: end,
+            orders: self.orders,
+            filled_orders: self.filled_orders,
+            fully_filled_orders: self.fully_filled_orders,
+            fill_rate: ratio(self.filled_quantity, self.submitted_quantity),
+            limit_fills: self.limit_fills,
+            price_improved_fills: self.price_improved_fills,
+            price_improvement: self.price_improvement,
+            quoted_spread_bps: ratio(
+                self.quoted_spread_sum_bps,
+                Decimal::from(self.quoted_samples),
+            ),
+            effective_spread_bps: ratio(self.effective_spread_sum_bps, self.effective_quantity),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SymbolQuality {
+    current: Window,
+    previous: Option<MarketQuality>,
+}
+
+/// Current and last completed window of a symbol, as returned by the API
+#[derive(Debug, Clone, Serialize)]
+pub struct QualityReport {
+    pub current: MarketQuality,
+    pub previous: Option<MarketQuality>,
+}
+
+/// Per-symbol market quality accumulators
+pub struct QualityTracker {
+    symbols: DashMap<String, SymbolQuality>,
+}
+
+impl QualityTracker {
+    pub fn new() -> Self {
+        Self {
+            symbols: DashMap::new(),
+        }
+    }
+
+    /// Record an incoming order after matching
+    ///
+    /// `bbo` is the top of book the order arrived at, before it matched.
+    pub fn record(
+        &self,
+        order: &Order,
+        trades: &[Trade],
+        bbo: (Option<Decimal>, Option<Decimal>),
+        now: DateTime<Utc>,
+    ) {
+        let mut entry = self
+            .symbols
+            .entry(order.symbol.to_string())
+            .or_insert_with(|| SymbolQuality {
+                current: Window::new(now),
+                previous: None,
+            });
+        let window = &mut entry.current;
+
+        window.orders += 1;
+        window.submitted_quantity += order.quantity;
+        window.filled_quantity += order.filled_quantity;
+        if !order.filled_quantity.is_zero() {
+            window.filled_orders += 1;
+        }
+        if order.remaining_quantity.is_zero() {
+            window.fully_filled_orders += 1;
+        }
+
+        let mid = match bbo {
+            (Some(bid), Some(ask)) if bid > Decimal::ZERO && ask >= bid => {
+                let mid = (bid + ask) / Decimal::TWO;
+                window.quoted_spread_sum_bps += (ask - bid) / mid * BPS;
+                window.quoted_samples += 1;
+                Some(mid)
+            }
+            _ => None,
+        };
+
+        for trade in trades {
+            if let Some(mid) = mid {
+                window.effective_spread_sum_bps +=
+                    Decimal::TWO * (trade.price - mid).abs() / mid * BPS * trade.quantity;
+                window.effective_quantity += trade.quantity;
+            }
+
+            if let Some(limit) = order.price {
+                let improvement = match order.side {
+                    Side::Buy => limit - trade.price,
+                    Side::Sell => trade.price - limit,
+                };
+                window.limit_fills += 1;
+                if improvement > Decimal::ZERO {
+                    window.price_improved_fills += 1;
+                    window.price_improvement += improvement * trade.quantity;
+                }
+            }
+        }
+    }
+
+    /// Current and previous windows of `symbol`
+    pub fn report(&self, symbol: &Symbol, now: DateTime<Utc>) -> Option<QualityReport> {
+        self.symbols
+            .get(&symbol.to_string())
+            .map(|q| QualityReport {
+                current: q.current.snapshot(symbol, now),
+                previous: q.previous.clone(),
+            })
+    }
+
+    /// Statistics of a window in which nothing happened
+    pub fn empty(symbol: &Symbol, now: DateTime<Utc>) -> MarketQuality {
+        Window::new(now).snapshot(symbol, now)
+    }
+
+    /// Close the current window of `symbol` and start a new one
+    pub fn close_window(&self, symbol: &Symbol, now: DateTime<Utc>) -> MarketQuality {
+        let mut entry = self
+            .symbols
+            .entry(symbol.to_string())
+            .or_insert_with(|| SymbolQuality {
+                current: Window::new(now),
+                previous: None,
+            });
+
+        let closed = entry.current.snapshot(symbol, now);
+        entry.current = Window::new(now);
+        entry.previous = Some(closed.clone());
+        closed
+    }
+}
+
+impl Default for QualityTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::OrderStatus;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_improvement_and_spreads() {
+        let tracker = QualityTracker::new();
+        let symbol = Symbol::new("BTC", "USDT");
+        let now = Utc::now();
+
+        // Buy 2 @ 102, half filled at 101
+        let order = Order::builder()
+            .symbol(symbol.clone())
+            .status(OrderStatus::PartiallyFilled)
+            .price(102)
+            .quantity(2)
+            .filled(1, 101)
+            .build();
+        let trade = Trade {
+            id: Uuid::new_v4(),
+            trade_id: 1,
+            symbol: symbol.clone(),
+            maker_order_id: Uuid::new_v4(),
+            maker_user_id: Uuid::new_v4(),
+            taker_order_id: order.id,
+            taker_user_id: order.user_id,
+            price: Decimal::new(101, 0),
+            quantity: Decimal::ONE,
+            quote_quantity: Decimal::new(101, 0),
+            taker_side: Side::Buy,
+            executed_at: now,
+        };
+
+        // Arrived at 99 / 101, mid 100
+        let bbo = (Some(Decimal::new(99, 0)), Some(Decimal::new(101, 0)));
+        tracker.record(&order, &[trade], bbo, now);
+
+        let quality = tracker.close_window(&symbol, now);
+        assert_eq!(quality.orders, 1);
+        assert_eq!(quality.filled_orders, 1);
+        assert_eq!(quality.fully_filled_orders, 0);
+        assert_eq!(quality.fill_rate, Some(Decimal::new(5, 1)));
+        assert_eq!(quality.price_improved_fills, 1);
+        assert_eq!(quality.price_improvement, Decimal::ONE);
+        assert_eq!(quality.quoted_spread_bps, Some(Decimal::new(200, 0)));
+        assert_eq!(quality.effective_spread_bps, Some(Decimal::new(200, 0)));
+
+        // The closed window becomes the previous one
+        let report = tracker.report(&symbol, now).unwrap();
+        assert_eq!(report.current.orders, 0);
+        assert_eq!(report.previous.unwrap().orders, 1);
+    }
+}
@@ -0,0 +1,368 @@
+//! Pre-Trade Risk Checks
+//!
+//! Per-user limits a new order must pass before it reaches the book: order
+//! size, open order count, notional exposure of open orders and a price
+//! band around the book's midprice. As with throttles, every user gets the
+//! configured defaults unless an override has been set through the admin
+//! API, and overrides are persisted to Redis.
+//!
+//! Exposure is gross: buy and sell orders both count at their limit (or
+//! stop) price times the quantity still open. The price band only applies
+//! to limit orders priced through the midprice; passive orders and orders
+//! on a one-sided book are not banded.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::Result;
+use dashmap::DashMap;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::config::Config;
+use common::{Order, Side, TradingError};
+
+/// Redis hash of user id -> JSON limits
+const OVERRIDES_KEY: &str = "engine:risk_limits";
+
+/// Pre-trade limits for one user; a zero limit is unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskLimits {
+    pub max_order_quantity: Decimal,
+    pub max_open_orders: u32,
+    /// Notional of open orders, including the new one
+    pub max_notional_exposure: Decimal,
+    /// Furthest a limit price may cross the midprice, in basis points
+    pub price_band_bps: u32,
+}
+
+impl RiskLimits {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_order_quantity: config.risk_max_order_quantity,
+            max_open_orders: config.risk_max_open_orders,
+            max_notional_exposure: config.risk_max_notional_exposure,
+            price_band_bps: config.risk_price_band_bps,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskCheck {
+    OrderSize,
+    OpenOrders,
+    NotionalExposure,
+    PriceBand,
+}
+
+impl RiskCheck {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiskCheck::OrderSize => "order_size",
+            RiskCheck::OpenOrders => "open_orders",
+            RiskCheck::NotionalExposure => "notional_exposure",
+            RiskCheck::PriceBand => "price_band",
+        }
+    }
+}
+
+/// A limit an order would breach
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskViolation {
+    pub check: RiskCheck,
+    /// The order's value for the check: quantity, open orders, exposure or
+    /// limit price
+    pub value: Decimal,
+    /// Bound the value breached; for the price band, the banded price
+    pub limit: Decimal,
+}
+
+impl fmt::Display for RiskViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.check {
+            RiskCheck::OrderSize => write!(
+                f,
+                "order quantity {} exceeds the maximum of {}",
+                self.value, self.limit
+            ),
+            RiskCheck::OpenOrders => write!(
+                f,
+                "{} open orders would exceed the maximum of {}",
+                self.value, self.limit
+            ),
+            RiskCheck::NotionalExposure => write!(
+                f,
+                "open order notional {} would exceed the maximum of {}",
+                self.value, self.limit
+            ),
+            RiskCheck::PriceBand => write!(
+                f,
+                "limit price {} is outside the price band at {}",
+                self.value, self.limit
+            ),
+        }
+    }
+}
+
+impl From<&RiskViolation> for TradingError {
+    fn from(violation: &RiskViolation) -> Self {
+        TradingError::RiskLimitExceeded(violation.to_string())
+    }
+}
+
+/// Check `order` against `limits`
+///
+/// `open_orders` are the user's other open orders and `mid` the book's
+/// midprice, if it has both sides. `notional_price` prices the new order's
+/// exposure; a market order on an empty book adds none.
+pub fn check_order(
+    limits: &RiskLimits,
+    order: &Order,
+    open_orders: &[Order],
+    notional_price: Option<Decimal>,
+    mid: Option<Decimal>,
+) -> std::result::Result<(), RiskViolation> {
+    if !limits.max_order_quantity.is_zero() && order.quantity > limits.max_order_quantity {
+        return Err(RiskViolation {
+            check: RiskCheck::OrderSize,
+            value: order.quantity,
+            limit: limits.max_order_quantity,
+        });
+    }
+
+    let count = open_orders.len() + 1;
+    if limits.max_open_orders > 0 && count > limits.max_open_orders as usize {
+        return Err(RiskViolation {
+            check: RiskCheck::OpenOrders,
+            value: Decimal::from(count),
+            limit: Decimal::from(limits.max_open_orders),
+        });
+    }
+
+    if !limits.max_notional_exposure.is_zero() {
+        let exposure = open_orders
+            .iter()
+            .filter_map(|o| Some(o.price.or(o.stop_price)? * o.remaining_quantity))
+            .sum::<Decimal>()
+            + notional_price.map_or(Decimal::ZERO, |p| p * order.quantity);
+        if exposure > limits.max_notional_exposure {
+            return Err(RiskViolation {
+                check: RiskCheck::NotionalExposure,
+                value: exposure,
+                limit: limits.max_notional_exposure,
+            });
+        }
+    }
+
+    if limits.price_band_bps > 0 && !order.is_stop() {
+        if let (Some(price), Some(mid)) = (order.price, mid) {
+            let band = mid * Decimal::from(limits.price_band_bps) / Decimal::from(10_000);
+            let (outside, bound) = match order.side {
+                Side::Buy => (price > mid + band, mid + band),
+                Side::Sell => (price < mid - band, mid - band),
+            };
+            if outside {
+                return Err(RiskViolation {
+                    check: RiskCheck::PriceBand,
+                    value: price,
+                    limit: bound,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Persists limit overrides in Redis
+pub struct RiskStore {
+    conn: ConnectionManager,
+}
+
+impl RiskStore {
+    pub async fn new(redis_url: &str) -> Result<Self> {
+        let client = redis::Client::open(redis_url)?;
+        let conn = ConnectionManager::new(client).await?;
+        Ok(Self { conn })
+    }
+
+    pub async fn load(&self) -> Result<HashMap<Uuid, RiskLimits>> {
+        let mut conn = self.conn.clone();
+        let raw: HashMap<String, String> = conn.hgetall(OVERRIDES_KEY).await?;
+
+        Ok(raw
+            .into_iter()
+            .filter_map(|(user, limits)| {
+                Some((user.parse().ok()?, serde_json::from_str(&limits).ok()?))
+            })
+            .collect())
+    }
+
+    async fn save(&self, user_id: Uuid, limits: &RiskLimits) -> Result<()> {
+        let mut conn = self.conn.clone();
+        conn.hset::<_, _, _, ()>(
+            OVERRIDES_KEY,
+            user_id.to_string(),
+            serde_json::to_string(limits)?,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, user_id: Uuid) -> Result<()> {
+        let mut conn = self.conn.clone();
+        conn.hdel::<_, _, ()>(OVERRIDES_KEY, user_id.to_string())
+            .await?;
+        Ok(())
+    }
+}
+
+/// Per-user pre-trade limits with runtime-editable overrides
+pub struct RiskLimitsRegistry {
+    defaults: RiskLimits,
+    overrides: DashMap<Uuid, RiskLimits>,
+    store: Option<RiskStore>,
+}
+
+impl RiskLimitsRegistry {
+    pub fn new(defaults: RiskLimits, store: Option<RiskStore>) -> Self {
+        Self {
+            defaults,
+            overrides: DashMap::new(),
+            store,
+        }
+    }
+
+    /// Load persisted overrides
+    pub async fn restore(&self) -> Result<usize> {
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+
+        let overrides = store.load().await?;
+        let count = overrides.len();
+        for (user_id, limits) in overrides {
+            self.overrides.insert(user_id, limits);
+        }
+        Ok(count)
+    }
+
+    pub fn defaults(&self) -> RiskLimits {
+        self.defaults
+    }
+
+    /// Limits in force for `user_id`
+    pub fn limits(&self, user_id: Uuid) -> RiskLimits {
+        self.overrides
+            .get(&user_id)
+            .map(|l| *l)
+            .unwrap_or(self.defaults)
+    }
+
+    pub fn is_overridden(&self, user_id: Uuid) -> bool {
+        self.overrides.contains_key(&user_id)
+    }
+
+    pub fn overrides(&self) -> Vec<(Uuid, RiskLimits)> {
+        self.overrides
+            .iter()
+            .map(|e| (*e.key(), *e.value()))
+            .collect()
+    }
+
+    /// Set an override, persisting it first
+    pub async fn set_override(&self, user_id: Uuid, limits: RiskLimits) -> Result<()> {
+        if let Some(store) = &self.store {
+            store.save(user_id, &limits).await?;
+        }
+        self.overrides.insert(user_id, limits);
+        Ok(())
+    }
+
+    /// Drop an override so the user falls back to the defaults
+    pub async fn remove_override(&self, user_id: Uuid) -> Result<bool> {
+        if let Some(store) = &self.store {
+            store.delete(user_id).await?;
+        }
+        Ok(self.overrides.remove(&user_id).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{OrderStatus, Symbol};
+
+    fn order(side: Side, price: i64, quantity: i64) -> Order {
+        Order::builder()
+            .symbol(Symbol::new("BTC", "USDT"))
+            .side(side)
+            .status(OrderStatus::Open)
+            .price(price)
+            .quantity(quantity)
+            .build()
+    }
+
+    #[test]
+    fn test_limits_checked_in_order() {
+        let limits = RiskLimits {
+            max_order_quantity: Decimal::from(10),
+            max_open_orders: 2,
+            max_notional_exposure: Decimal::from(5000),
+            price_band_bps: 500,
+        };
+        let mid = Some(Decimal::from(100));
+
+        let ok = order(Side::Buy, 100, 10);
+        assert!(check_order(&limits, &ok, &[], ok.price, mid).is_ok());
+
+        let large = order(Side::Buy, 100, 11);
+        let violation = check_order(&limits, &large, &[], large.price, mid).unwrap_err();
+        assert_eq!(violation.check, RiskCheck::OrderSize);
+
+        let open = vec![order(Side::Sell, 200, 10), order(Side::Buy, 90, 5)];
+        let violation = check_order(&limits, &ok, &open, ok.price, mid).unwrap_err();
+        assert_eq!(violation.check, RiskCheck::OpenOrders);
+        assert_eq!(violation.value, Decimal::from(3));
+
+        // 2000 open plus 1000 new is within 5000; 4000 plus 1000 is not
+        let open = vec![order(Side::Sell, 200, 10)];
+        assert!(check_order(&limits, &ok, &open, ok.price, mid).is_ok());
+        let open = vec![order(Side::Sell, 200, 20)];
+        let violation = check_order(&limits, &ok, &open, ok.price, mid).unwrap_err();
+        assert_eq!(violation.check, RiskCheck::NotionalExposure);
+        assert_eq!(violation.value, Decimal::from(5000));
+
+        let unlimited = RiskLimits::default();
+        assert!(check_order(&unlimited, &large, &open, large.price, mid).is_ok());
+    }
+
+    #[test]
+    fn test_price_band_only_bounds_aggressive_prices() {
+        let limits = RiskLimits {
+            price_band_bps: 500,
+            ..RiskLimits::default()
+        };
+        let mid = Some(Decimal::from(100));
+
+        let buy = order(Side::Buy, 106, 1);
+        let violation = check_order(&limits, &buy, &[], buy.price, mid).unwrap_err();
+        assert_eq!(violation.check, RiskCheck::PriceBand);
+        assert_eq!(violation.limit, Decimal::from(105));
+        assert!(TradingError::from(&violation)
+            .to_string()
+            .contains("price band"));
+
+        let sell = order(Side::Sell, 94, 1);
+        assert!(check_order(&limits, &sell, &[], sell.price, mid).is_err());
+
+        // Passive prices and one-sided books are not banded
+        let passive = order(Side::Buy, 50, 1);
+        assert!(check_order(&limits, &passive, &[], passive.price, mid).is_ok());
+        assert!(check_order(&limits, &buy, &[], buy.price, None).is_ok());
+    }
+}
@@ -0,0 +1,251 @@
+//! Trading Sessions
+//!
+//! Every symbol is in one market state. A symbol with a scheduled session
+//! is Open while the session is, in PreOpen for `pre_open_secs` before it
+//! opens and Closed otherwise; symbols without one are always Open. A
+//! halt, by reference data, an admin or a circuit breaker, makes it Halted
+//! whatever the schedule.
+//!
+//! Only an Open book matches. PreOpen and Halted books collect orders for
+//! an auction, uncrossed once the symbol is Open again. A Closed symbol
+//! rejects new orders; resting orders stay, and stops triggering meanwhile
+//! wait in the book for the next open.
+//!
+//! Sessions are in UTC and states follow the engine clock, so replaying
+//! the journal sees the states seen live.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, Utc, Weekday};
+use serde::Serialize;
+
+use crate::config::Config;
+
+/// Key of the session for symbols without their own
+const ALL_SYMBOLS: &str = "*";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketState {
+    PreOpen,
+    Open,
+    Halted,
+    Closed,
+}
+
+/// Daily trading hours, on some weekdays or every day
+///
+/// A session closing at or before its open time runs overnight; its days
+/// are the days it opens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    days: Option<(Weekday, Weekday)>,
+    open: NaiveTime,
+    close: NaiveTime,
+}
+
+impl Session {
+    /// Parse `HH:MM-HH:MM`, optionally after a day or day range such as
+    /// `Mon-Fri`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (days, hours) = match spec.split_once(' ') {
+            Some((days, hours)) => (Some(parse_days(days.trim())?), hours.trim()),
+            None => (None, spec),
+        };
+        let (open, close) = hours
+            .split_once('-')
+            .ok_or_else(|| anyhow!("Session hours `{hours}` are not HH:MM-HH:MM"))?;
+        let open = parse_time(open)?;
+        let close = parse_time(close)?;
+        if open == close {
+            bail!("Session `{spec}` opens and closes at the same time");
+        }
+        Ok(Self { days, open, close })
+    }
+
+    fn opens_on(&self, day: Weekday) -> bool {
+        let Some((first, last)) = self.days else {
+            return true;
+        };
+        let (first, last, day) = (
+            first.num_days_from_monday(),
+            last.num_days_from_monday(),
+            day.num_days_from_monday(),
+        );
+        if first <= last {
+            (first..=last).contains(&day)
+        } else {
+            day >= first || day <= last
+        }
+    }
+
+    /// State at `at`, ignoring halts
+    fn state(&self, at: DateTime<Utc>, pre_open: chrono::Duration) -> MarketState {
+        let today = at.date_naive();
+        // Yesterday's session may run overnight, and tomorrow's pre-open
+        // may start today
+        for date in [today.pred_opt(), Some(today), today.succ_opt()]
+            .into_iter()
+            .flatten()
+            .filter(|date| self.opens_on(date.weekday()))
+        {
+            let (open, close) = self.hours_from(date);
+            if open <= at && at < close {
+                return MarketState::Open;
+            }
+            if open - pre_open <= at && at < open {
+                return MarketState::PreOpen;
+            }
+        }
+        MarketState::Closed
+    }
+
+    /// Open and close of the session opening on `date`
+    fn hours_from(&self, date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        let close_date = if self.close > self.open {
+            date
+        } else {
+            date + Days::new(1)
+        };
+        (
+            date.and_time(self.open).and_utc(),
+            close_date.and_time(self.close).and_utc(),
+        )
+    }
+}
+
+fn parse_days(spec: &str) -> Result<(Weekday, Weekday)> {
+    let day = |day: &str| {
+        day.trim()
+            .parse::<Weekday>()
+            .map_err(|_| anyhow!("Invalid weekday `{day}`"))
+    };
+    match spec.split_once('-') {
+        Some((first, last)) => Ok((day(first)?, day(last)?)),
+        None => day(spec).map(|day| (day, day)),
+    }
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M")
+        .map_err(|_| anyhow!("Invalid session time `{time}`: use HH:MM"))
+}
+
+/// Parse `SYMBOL=session` pairs, comma-separated; `*` schedules every
+/// symbol without a session of its own
+pub fn parse_sessions(spec: &str) -> Result<HashMap<String, Session>> {
+    let mut sessions = HashMap::new();
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (symbol, session) = entry
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Trading session `{entry}` is not SYMBOL=session"))?;
+        let symbol = symbol.trim().to_uppercase();
+        if sessions
+            .insert(symbol.clone(), Session::parse(session.trim())?)
+            .is_some()
+        {
+            bail!("Trading session for {symbol} given twice");
+        }
+    }
+    Ok(sessions)
+}
+
+/// Scheduled sessions of every symbol
+pub struct TradingCalendar {
+    pre_open: chrono::Duration,
+    sessions: HashMap<String, Session>,
+}
+
+impl TradingCalendar {
+    pub fn new(sessions: HashMap<String, Session>, pre_open: chrono::Duration) -> Self {
+        Self { pre_open, sessions }
+    }
+
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Ok(Self::new(
+            parse_sessions(&config.trading_sessions)?,
+            chrono::Duration::seconds(config.pre_open_secs as i64),
+        ))
+    }
+
+    /// Scheduled state of `symbol` at `at`; never Halted
+    pub fn state(&self, symbol: &str, at: DateTime<Utc>) -> MarketState {
+        match self
+            .sessions
+            .get(symbol)
+            .or_else(|| self.sessions.get(ALL_SYMBOLS))
+        {
+            Some(session) => session.state(at, self.pre_open),
+            None => MarketState::Open,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        // 2024-01-01 is a Monday
+        Utc.with_ymd_and_hms(2024, 1, day, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_sessions() {
+        let sessions = parse_sessions("*=Mon-Fri 13:30-20:00, btc-usdt=22:00-06:00").unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions["BTC-USDT"].days, None);
+        assert_eq!(sessions["*"].days, Some((Weekday::Mon, Weekday::Fri)));
+
+        assert!(parse_sessions("").unwrap().is_empty());
+        assert!(parse_sessions("BTC-USDT").is_err());
+        assert!(parse_sessions("BTC-USDT=9:30").is_err());
+        assert!(parse_sessions("BTC-USDT=Someday 09:30-16:00").is_err());
+        assert!(parse_sessions("BTC-USDT=09:30-09:30").is_err());
+        assert!(parse_sessions("BTC-USDT=09:30-16:00,BTC-USDT=10:00-11:00").is_err());
+    }
+
+    #[test]
+    fn test_states_follow_the_schedule() {
+        let calendar = TradingCalendar::new(
+            parse_sessions("*=Mon-Fri 13:30-20:00, BTC-USDT=Fri 22:00-06:00").unwrap(),
+            chrono::Duration::minutes(15),
+        );
+
+        assert_eq!(
+            calendar.state("ETH-USDT", at(1, 13, 0)),
+            MarketState::Closed
+        );
+        assert_eq!(
+            calendar.state("ETH-USDT", at(1, 13, 15)),
+            MarketState::PreOpen
+        );
+        assert_eq!(calendar.state("ETH-USDT", at(1, 13, 30)), MarketState::Open);
+        assert_eq!(
+            calendar.state("ETH-USDT", at(1, 20, 0)),
+            MarketState::Closed
+        );
+        // Saturday
+        assert_eq!(
+            calendar.state("ETH-USDT", at(6, 14, 0)),
+            MarketState::Closed
+        );
+
+        // Overnight from Friday into Saturday
+        assert_eq!(
+            calendar.state("BTC-USDT", at(5, 21, 50)),
+            MarketState::PreOpen
+        );
+        assert_eq!(calendar.state("BTC-USDT", at(6, 5, 59)), MarketState::Open);
+        assert_eq!(calendar.state("BTC-USDT", at(6, 6, 0)), MarketState::Closed);
+        assert_eq!(calendar.state("BTC-USDT", at(7, 1, 0)), MarketState::Closed);
+
+        let unscheduled = TradingCalendar::new(HashMap::new(), chrono::Duration::zero());
+        assert_eq!(
+            unscheduled.state("ETH-USDT", at(6, 3, 0)),
+            MarketState::Open
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Order Book Snapshots
+//!
+//! Periodic snapshots of every order book for crash recovery. Snapshots
+//! are taken between commands in the matching loop together with the
+//! Kafka order-log offsets applied so far, so on startup the books are
+//! restored and the log is replayed from just after those offsets.
+//!
+//! Orders submitted over HTTP are not in the order log and are only
+//! recovered up to the last snapshot. Replayed orders publish their
+//! events again; trade ids are restored, so consumers can deduplicate.
+//!
+//! A final snapshot is saved on shutdown, so a clean restart has nothing
+//! to replay. Books are independent and are restored in parallel; load
+//! and restore times are reported against `snapshot_restore_budget_ms`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+
+use common::{AccountBalance, Order, Symbol, SymbolConfig};
+
+use crate::accounts::Reservation;
+use crate::config::Config;
+use crate::fees::HouseAccount;
+use crate::orderbook::{BookSnapshot, OrderBook};
+use crate::protection::Halt;
+
+/// Redis key holding the latest snapshot
+const SNAPSHOT_KEY: &str = "engine:snapshot";
+
+/// Every order book plus the order-log position it reflects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineSnapshot {
+    pub taken_at: DateTime<Utc>,
+    pub books: Vec<BookSnapshot>,
+    /// Last applied offset per partition of the orders topic
+    pub log_offsets: BTreeMap<i32, i64>,
+    /// Fee house account balances
+    #[serde(default)]
+    pub fee_accounts: Vec<HouseAccount>,
+    /// Listed symbols, including those added at runtime
+    #[serde(default)]
+    pub symbols: Vec<Symbol>,
+    /// Trading rules per symbol
+    #[serde(default)]
+    pub symbol_configs: HashMap<String, SymbolConfig>,
+    /// Stop orders waiting for their trigger
+    #[serde(default)]
+    pub stop_orders: Vec<Order>,
+    /// User balances, when accounts are enabled
+    #[serde(default)]
+    pub balances: Vec<AccountBalance>,
+    /// Funds locked by open orders
+    #[serde(default)]
+    pub reservations: Vec<Reservation>,
+    /// Admin and circuit breaker halts in force
+    #[serde(default)]
+    pub halts: BTreeMap<String, Halt>,
+    /// Last command journal entry the books reflect; unset when the
+    /// journal was disabled
+    #[serde(default)]
+    pub journal_sequence: Option<u64>,
+}
+
+/// Where snapshots are kept
+#[derive(Clone)]
+pub enum SnapshotStore {
+    Redis(ConnectionManager),
+    Disk(PathBuf),
+}
+
+impl SnapshotStore {
+    /// Store configured for this engine, if snapshots are enabled
+    ///
+    /// Snapshots go to `snapshot_path` when set and to Redis otherwise.
+    pub async fn from_config(config: &Config) -> Result<Option<Self>> {
+        if config.snapshot_interval_secs == 0 {
+            return Ok(None);
+        }
+
+        let store = match &config.snapshot_path {
+            Some(path) => Self::Disk(PathBuf::from(path)),
+            None => {
+                let client = redis::Client::open(config.redis_url.as_str())?;
+                Self::Redis(ConnectionManager::new(client).await?)
+            }
+        };
+        Ok(Some(store))
+    }
+
+    pub async fn save(&self, snapshot: &EngineSnapshot) -> Result<()> {
+        let payload = serde_json::to_vec(snapshot)?;
+
+        match self {
+            Self::Redis(conn) => {
+                let mut conn = conn.clone();
+                conn.set::<_, _, ()>(SNAPSHOT_KEY, payload).await?;
+            }
+            Self::Disk(path) => {
+                // Write then rename so a crash never leaves a torn snapshot
+                let tmp = path.with_extension("tmp");
+                tokio::fs::write(&tmp, payload)
+                    .await
+                    .with_context(|| format!("writing {}", tmp.display()))?;
+                tokio::fs::rename(&tmp, path).await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn load(&self) -> Result<Option<EngineSnapshot>> {
+        let payload: Option<Vec<u8>> = match self {
+            Self::Redis(conn) => {
+                let mut conn = conn.clone();
+                conn.get(SNAPSHOT_KEY).await?
+            }
+            Self::Disk(path) => match tokio::fs::read(path).await {
+                Ok(payload) => Some(payload),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+            },
+        };
+
+        payload
+            .map(|p| serde_json::from_slice(&p).context("decoding snapshot"))
+            .transpose()
+    }
+}
+
+/// Restore each book from its snapshot on up to `threads` threads
+///
+/// Workers take the largest remaining book first so one big book does not
+/// leave the others waiting behind it.
+pub fn restore_books(books: &[(Arc<OrderBook>, &BookSnapshot)], threads: usize) {
+    let mut order: Vec<usize> = (0..books.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(books[i].1.bids.len() + books[i].1.asks.len()));
+
+    let next = AtomicUsize::new(0);
+    let worker = || {
+        while let Some(&i) = order.get(next.fetch_add(1, Ordering::Relaxed)) {
+            let (book, snapshot) = &books[i];
+            book.restore(snapshot);
+        }
+    };
+
+    let threads = threads.clamp(1, books.len().max(1));
+    std::thread::scope(|scope| {
+        for _ in 1..threads {
+            scope.spawn(worker);
+        }
+        worker();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::Symbol;
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    use crate::orderbook::RestingOrder;
+
+    fn book_snapshot(symbol: Symbol, orders: i64) -> BookSnapshot {
+        let resting = |price: i64| RestingOrder {
+            order_id: Uuid::new_v4(),
+            client_order_id: String::new(),
+            user_id: Uuid::new_v4(),
+            price: Decimal::from(price),
+            remaining_quantity: Decimal::ONE,
+            hidden_quantity: Decimal::ZERO,
+            display_quantity: None,
+            sequence: price as u64,
+        };
+        BookSnapshot {
+            symbol,
+            bids: (0..orders).map(|i| resting(100 - i % 10)).collect(),
+            asks: (0..orders).map(|i| resting(101 + i % 10)).collect(),
+            next_order_sequence: 1000,
+            next_trade_id: 7,
+            book_sequence: 42,
+        }
+    }
+
+    #[test]
+    fn test_parallel_restore_matches_each_snapshot() {
+        let snapshots: Vec<BookSnapshot> = ["BTC", "ETH", "SOL"]
+            .iter()
+            .zip([50, 5, 0])
+            .map(|(base, orders)| book_snapshot(Symbol::new(base, "USDT"), orders))
+            .collect();
+        let books: Vec<(Arc<OrderBook>, &BookSnapshot)> = snapshots
+            .iter()
+            .map(|s| (Arc::new(OrderBook::new(s.symbol.clone())), s))
+            .collect();
+
+        restore_books(&books, 4);
+
+        for (book, snapshot) in &books {
+            let restored = book.snapshot();
+            assert_eq!(restored.bids.len(), snapshot.bids.len());
+            assert_eq!(restored.asks.len(), snapshot.asks.len());
+            assert_eq!(restored.next_trade_id, 7);
+            assert_eq!(restored.book_sequence, 42);
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Per-Symbol Matching Statistics
+//!
+//! Running counts of the orders and trades the engine processed for each
+//! symbol since it started. They are served from `/info` and exported as
+//! `symbol`-labelled series, so a dashboard can chart one symbol from one
+//! metric rather than joining the engine-wide counters.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use dashmap::DashMap;
+use serde::Serialize;
+
+use common::Symbol;
+
+#[derive(Debug, Default)]
+struct Counters {
+    received: AtomicU64,
+    matched: AtomicU64,
+    cancelled: AtomicU64,
+    rejected: AtomicU64,
+    trades: AtomicU64,
+    last_sequence: AtomicU64,
+}
+
+/// Counts for one symbol
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SymbolStats {
+    pub orders_received: u64,
+    /// Incoming orders that traded at least once
+    pub orders_matched: u64,
+    pub orders_cancelled: u64,
+    pub orders_rejected: u64,
+    pub trades: u64,
+    /// Book sequence after the symbol's last match or cancel
+    pub last_sequence: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct MatchingStats {
+    symbols: DashMap<String, Counters>,
+}
+
+impl MatchingStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn add(
+        &self,
+        symbol: &Symbol,
+        metric: &'static str,
+        n: u64,
+        field: fn(&Counters) -> &AtomicU64,
+    ) {
+        if n == 0 {
+            return;
+        }
+        let counters = self.symbols.entry(symbol.to_string()).or_default();
+        field(&counters).fetch_add(n, Ordering::Relaxed);
+        metrics::counter!(metric, "symbol" => symbol.to_string()).increment(n);
+    }
+
+    pub fn received(&self, symbol: &Symbol) {
+        self.add(symbol, "symbol_orders_received", 1, |c| &c.received);
+    }
+
+    pub fn matched(&self, symbol: &Symbol) {
+        self.add(symbol, "symbol_orders_matched", 1, |c| &c.matched);
+    }
+
+    pub fn cancelled(&self, symbol: &Symbol) {
+        self.add(symbol, "symbol_orders_cancelled", 1, |c| &c.cancelled);
+    }
+
+    pub fn rejected(&self, symbol: &Symbol) {
+        self.add(symbol, "symbol_orders_rejected", 1, |c| &c.rejected);
+    }
+
+    pub fn trades(&self, symbol: &Symbol, trades: usize) {
+        self.add(symbol, "symbol_trades", trades as u64, |c| &c.trades);
+    }
+
+    /// Record the symbol's book sequence after it changed
+    pub fn sequence(&self, symbol: &Symbol, sequence: u64) {
+        let last = self
+            .symbols
+            .entry(symbol.to_string())
+            .or_default()
+            .last_sequence
+            .fetch_max(sequence, Ordering::Relaxed)
+            .max(sequence);
+        metrics::gauge!("symbol_last_sequence", "symbol" => symbol.to_string()).set(last as f64);
+    }
+
+    pub fn get(&self, symbol: &Symbol) -> Option<SymbolStats> {
+        self.symbols
+            .get(&symbol.to_string())
+            .map(|c| Self::read(&c))
+    }
+
+    /// Counts for every symbol seen, by symbol
+    pub fn all(&self) -> BTreeMap<String, SymbolStats> {
+        self.symbols
+            .iter()
+            .map(|entry| (entry.key().clone(), Self::read(entry.value())))
+            .collect()
+    }
+
+    fn read(counters: &Counters) -> SymbolStats {
+        SymbolStats {
+            orders_received: counters.received.load(Ordering::Relaxed),
+            orders_matched: counters.matched.load(Ordering::Relaxed),
+            orders_cancelled: counters.cancelled.load(Ordering::Relaxed),
+            orders_rejected: counters.rejected.load(Ordering::Relaxed),
+            trades: counters.trades.load(Ordering::Relaxed),
+            last_sequence: counters.last_sequence.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_are_kept_per_symbol() {
+        let stats = MatchingStats::new();
+        let btc = Symbol::new("BTC", "USDT");
+        let eth = Symbol::new("ETH", "USDT");
+
+        stats.received(&btc);
+        stats.received(&btc);
+        stats.matched(&btc);
+        stats.trades(&btc, 3);
+        stats.sequence(&btc, 7);
+        stats.received(&eth);
+        stats.rejected(&eth);
+        stats.cancelled(&btc);
+
+        assert_eq!(
+            stats.get(&btc).unwrap(),
+            SymbolStats {
+                orders_received: 2,
+                orders_matched: 1,
+                orders_cancelled: 1,
+                orders_rejected: 0,
+                trades: 3,
+                last_sequence: 7,
+            }
+        );
+        assert_eq!(stats.get(&eth).unwrap().orders_rejected, 1);
+        assert_eq!(
+            stats.all().keys().collect::<Vec<_>>(),
+            vec!["BTC-USDT", "ETH-USDT"]
+        );
+    }
+
+    #[test]
+    fn test_unseen_symbol_has_no_stats() {
+        let stats = MatchingStats::new();
+        stats.trades(&Symbol::new("BTC", "USDT"), 0);
+        assert_eq!(stats.get(&Symbol::new("BTC", "USDT")), None);
+    }
+}
@@ -0,0 +1,213 @@
+//! Stop Orders
+//!
+//! Stop orders wait off the book until their trigger reference crosses
+//! the stop price, then enter the book as the limit or market order they
+//! carry. Each stop watches one reference: the symbol's last trade on this
+//! engine, or the mark or index price published by the data pipeline.
+//! Which references a symbol's stops may use, and the default, come from
+//! its symbol config.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use parking_lot::Mutex;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use common::{Order, Symbol, TriggerSource};
+
+/// Untriggered stop orders and the latest reference prices per symbol
+pub struct StopBook {
+    /// Stops per symbol, in arrival order
+    pending: Mutex<HashMap<String, Vec<Order>>>,
+
+    /// Latest price per symbol and trigger reference
+    references: Mutex<HashMap<(String, TriggerSource), Decimal>>,
+}
+
+impl StopBook {
+    pub fn new() -> Self {
+        Self {
+            pending: Mutex::new(HashMap::new()),
+            references: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Add a stop whose trigger source is resolved
+    ///
+    /// Returns the order instead if its reference has already crossed the
+    /// stop price.
+    pub fn insert(&self, order: Order) -> Option<Order> {
+        let source = order.trigger_source.unwrap_or_default();
+        let key = order.symbol.to_string();
+        let reference = self.references.lock().get(&(key.clone(), source)).copied();
+        if reference.is_some_and(|price| order.stop_triggered_at(price)) {
+            return Some(order);
+        }
+
+        self.pending.lock().entry(key).or_default().push(order);
+        None
+    }
+
+    /// Remove an untriggered stop
+    pub fn cancel(&self, symbol: &Symbol, order_id: Uuid) -> Option<Order> {
+        let mut pending = self.pending.lock();
+        let stops = pending.get_mut(&symbol.to_string())?;
+        let index = stops.iter().position(|o| o.id == order_id)?;
+        Some(stops.remove(index))
+    }
+
+    /// Record a reference price and take the stops it triggers, oldest first
+    pub fn trigger(&self, symbol: &Symbol, source: TriggerSource, price: Decimal) -> Vec<Order> {
+        let key = symbol.to_string();
+        self.references.lock().insert((key.clone(), source), price);
+
+        let mut pending = self.pending.lock();
+        let Some(stops) = pending.get_mut(&key) else {
+            return Vec::new();
+        };
+        let (triggered, waiting) = std::mem::take(stops).into_iter().partition(|o| {
+            o.trigger_source.unwrap_or_default() == source && o.stop_triggered_at(price)
+        });
+        *stops = waiting;
+        triggered
+    }
+
+    /// Take every stop for a symbol
+    pub fn remove_symbol(&self, symbol: &Symbol) -> Vec<Order> {
+        let key = symbol.to_string();
+        self.references.lock().retain(|(s, _), _| *s != key);
+        self.pending.lock().remove(&key).unwrap_or_default()
+    }
+
+    /// Symbols with stops waiting on `source`
+    pub fn symbols_watching(&self, source: TriggerSource) -> Vec<Symbol> {
+        self.pending
+            .lock()
+            .values()
+            .filter_map(|stops| {
+                stops
+                    .iter()
+                    .find(|o| o.trigger_source.unwrap_or_default() == source)
+                    .map(|o| o.symbol.clone())
+            })
+            .collect()
+    }
+
+    /// Every untriggered stop, for snapshots
+    pub fn orders(&self) -> Vec<Order> {
+        self.pending.lock().values().flatten().cloned().collect()
+    }
+
+    /// Replace the untriggered stops with those from a snapshot
+    pub fn restore(&self, orders: &[Order]) {
+        let mut pending = self.pending.lock();
+        pending.clear();
+        for order in orders {
+            pending
+                .entry(order.symbol.to_string())
+                .or_default()
+                .push(order.clone());
+        }
+    }
+
+    /// Number of untriggered stops
+    pub fn count(&self) -> usize {
+        self.pending.lock().values().map(Vec::len).sum()
+    }
+}
+
+impl Default for StopBook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Mark and index prices the data pipeline keeps in Redis
+#[derive(Clone)]
+pub struct ReferencePrices {
+    conn: ConnectionManager,
+}
+
+impl ReferencePrices {
+    pub async fn new(redis_url: &str) -> Result<Self> {
+        let client = redis::Client::open(redis_url)?;
+        let conn = ConnectionManager::new(client).await?;
+        Ok(Self { conn })
+    }
+
+    /// Current `source` prices for `symbols`; symbols without a fresh
+    /// price are skipped
+    pub async fn fetch(
+        &self,
+        source: TriggerSource,
+        symbols: &[Symbol],
+    ) -> Result<Vec<(Symbol, Decimal)>> {
+        let prefix = match source {
+            TriggerSource::MarkPrice => "mid",
+            TriggerSource::IndexPrice => "price",
+            TriggerSource::LastTrade => return Ok(Vec::new()),
+        };
+        if symbols.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let keys: Vec<String> = symbols.iter().map(|s| format!("{prefix}:{s}")).collect();
+        let mut conn = self.conn.clone();
+        let values: Vec<Option<String>> = conn.mget(&keys).await?;
+
+        Ok(symbols
+            .iter()
+            .zip(values)
+            .filter_map(|(symbol, value)| Some((symbol.clone(), value?.parse().ok()?)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::Side;
+
+    fn stop(side: Side, stop_price: i64, source: TriggerSource) -> Order {
+        Order::builder()
+            .side(side)
+            .stop_market(stop_price, source)
+            .build()
+    }
+
+    #[test]
+    fn test_stops_trigger_on_their_own_reference() {
+        let stops = StopBook::new();
+        let symbol = Symbol::new("ETH", "USDT");
+        let buy = stop(Side::Buy, 2100, TriggerSource::MarkPrice);
+        let sell = stop(Side::Sell, 1900, TriggerSource::LastTrade);
+        assert!(stops.insert(buy.clone()).is_none());
+        assert!(stops.insert(sell.clone()).is_none());
+        assert_eq!(
+            stops.symbols_watching(TriggerSource::MarkPrice),
+            vec![symbol.clone()]
+        );
+
+        // A last-trade print through the buy stop does not trigger it
+        let last = TriggerSource::LastTrade;
+        assert!(stops
+            .trigger(&symbol, last, Decimal::new(2200, 0))
+            .is_empty());
+
+        let mark = TriggerSource::MarkPrice;
+        let triggered = stops.trigger(&symbol, mark, Decimal::new(2100, 0));
+        assert_eq!(triggered.len(), 1);
+        assert_eq!(triggered[0].id, buy.id);
+
+        // Once the reference is known, a stop already through it triggers
+        // on arrival
+        let late = stop(Side::Buy, 2000, TriggerSource::MarkPrice);
+        assert!(stops.insert(late).is_some());
+
+        assert_eq!(stops.cancel(&symbol, sell.id).map(|o| o.id), Some(sell.id));
+        assert_eq!(stops.count(), 0);
+    }
+}
@@ -0,0 +1,405 @@
+//! WebSocket Market Data Stream
+//!
+//! Streams order book, trade and BBO messages for the symbols a client
+//! subscribes to. A subscription starts with a depth snapshot; after that
+//! the client receives incremental updates carrying the new aggregate
+//! quantity of each changed level, zero meaning the level is gone.
+//!
+//! Updates are diffed against the last depth sent for the symbol and only
+//! computed while at least one client is connected. Clients that fall
+//! behind the broadcast buffer are resent snapshots of their symbols.
+//!
+//! Every update carries the book sequence it brings the book to and that
+//! of the update before it. A client keeping a local book drops updates
+//! at or below its snapshot's sequence, and from then on expects each
+//! update's `prev_sequence` to be the last sequence it applied; anything
+//! else is a gap, recovered from a fresh snapshot over REST or by
+//! resubscribing. The Kafka order book feed follows the same rule.
+
+use std::collections::HashSet;
+
+use axum::extract::ws::{Message, WebSocket};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{debug, warn};
+
+use crate::engine::MatchingEngine;
+use crate::orderbook::OrderBook;
+use common::{
+    events::{OrderBookDeltas, OrderBookUpdate},
+    PriceLevel, Symbol, Trade,
+};
+
+type Depth = (Vec<PriceLevel>, Vec<PriceLevel>);
+
+/// Best bid/ask change
+#[derive(Debug, Clone, Serialize)]
+pub struct BboUpdate {
+    pub symbol: Symbol,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub bid: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub ask: Option<Decimal>,
+    pub sequence: u64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Message sent to WebSocket clients
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MarketMessage {
+    /// Full depth; replaces whatever the client holds for the symbol
+    Snapshot(OrderBookUpdate),
+    /// Changed levels only
+    Update(OrderBookDeltas),
+    Trade(Trade),
+    Bbo(BboUpdate),
+    Subscribed {
+        symbols: Vec<String>,
+    },
+    Unsubscribed {
+        symbols: Vec<String>,
+    },
+    Error {
+        error: String,
+    },
+}
+
+impl MarketMessage {
+    fn symbol(&self) -> Option<String> {
+        match self {
+            Self::Snapshot(snapshot) => Some(snapshot.symbol.to_string()),
+            Self::Update(update) => Some(update.symbol.to_string()),
+            Self::Trade(trade) => Some(trade.symbol.to_string()),
+            Self::Bbo(bbo) => Some(bbo.symbol.to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// Subscription request from a client
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum ClientRequest {
+    Subscribe { symbols: Vec<String> },
+    Unsubscribe { symbols: Vec<String> },
+}
+
+/// Fan-out of market data to WebSocket clients
+pub struct MarketStream {
+    tx: broadcast::Sender<MarketMessage>,
+
+    /// Levels per side in snapshots and tracked for updates
+    depth_levels: usize,
+
+    /// Last depth sent per symbol and the book sequence of its update
+    last_depth: DashMap<String, (Depth, u64)>,
+    /// Last BBO sent per symbol
+    last_bbo: DashMap<String, (Option<Decimal>, Option<Decimal>)>,
+}
+
+impl MarketStream {
+    pub fn new(capacity: usize, depth_levels: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity.max(1));
+        Self {
+            tx,
+            depth_levels,
+            last_depth: DashMap::new(),
+            last_bbo: DashMap::new(),
+        }
+    }
+
+    fn has_clients(&self) -> bool {
+        self.tx.receiver_count() > 0
+    }
+
+    /// Publish level and BBO changes of `book` since the last call
+    pub fn publish_book(&self, book: &OrderBook) {
+        if !self.has_clients() {
+            return;
+        }
+
+        let symbol = book.symbol();
+        let key = symbol.to_string();
+        let sequence = book.book_sequence();
+        let now = Utc::now();
+
+        let depth = book.get_depth(self.depth_levels);
+        let (bids, asks, prev_sequence) = match self.last_depth.get(&key).as_deref() {
+            Some(((last_bids, last_asks), last_sequence)) => (
+                diff_levels(last_bids, &depth.0),
+                diff_levels(last_asks, &depth.1),
+                *last_sequence,
+            ),
+            None => (to_pairs(&depth.0), to_pairs(&depth.1), 0),
+        };
+
+        // Changes below the tracked depth send nothing and leave the
+        // sequence to the last update, so they are not reported as gaps
+        if !bids.is_empty() || !asks.is_empty() {
+            self.last_depth.insert(key.clone(), (depth, sequence));
+            let _ = self.tx.send(MarketMessage::Update(OrderBookDeltas {
+                symbol: symbol.clone(),
+                bids,
+                asks,
+                sequence,
+                prev_sequence,
+                changes: 1,
+                timestamp: now,
+            }));
+        }
+
+        let bbo = book.get_bbo();
+        if self.last_bbo.get(&key).is_some_and(|last| *last == bbo) {
+            return;
+        }
+        self.last_bbo.insert(key, bbo);
+        let _ = self.tx.send(MarketMessage::Bbo(BboUpdate {
+            symbol: symbol.clone(),
+            bid: bbo.0,
+            ask: bbo.1,
+            sequence,
+            timestamp: now,
+        }));
+    }
+
+    pub fn publish_trade(&self, trade: &Trade) {
+        if self.has_clients() {
+            let _ = self.tx.send(MarketMessage::Trade(trade.clone()));
+        }
+    }
+
+    /// Current depth of `book` as a snapshot message
+    fn snapshot(&self, book: &OrderBook) -> MarketMessage {
+        let sequence = book.book_sequence();
+        let (bids, asks) = book.get_depth(self.depth_levels);
+        MarketMessage::Snapshot(OrderBookUpdate {
+            symbol: book.symbol().clone(),
+            bids: to_pairs(&bids),
+            asks: to_pairs(&asks),
+            sequence,
+            timestamp: Utc::now(),
+        })
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<MarketMessage> {
+        self.tx.subscribe()
+    }
+}
+
+pub(crate) fn to_pairs(levels: &[PriceLevel]) -> Vec<(Decimal, Decimal)> {
+    levels.iter().map(|l| (l.price, l.quantity)).collect()
+}
+
+/// Levels whose quantity changed from `old` to `new`, removed ones at zero
+pub(crate) fn diff_levels(old: &[PriceLevel], new: &[PriceLevel]) -> Vec<(Decimal, Decimal)> {
+    let mut changes: Vec<(Decimal, Decimal)> = new
+        .iter()
+        .filter(|level| {
+            !old.iter()
+                .any(|o| o.price == level.price && o.quantity == level.quantity)
+        })
+        .map(|level| (level.price, level.quantity))
+        .collect();
+
+    changes.extend(
+        old.iter()
+            .filter(|o| !new.iter().any(|level| level.price == o.price))
+            .map(|o| (o.price, Decimal::ZERO)),
+    );
+
+    changes
+}
+
+/// Serve one client until it disconnects
+pub async fn serve_client(mut socket: WebSocket, engine: &MatchingEngine, initial: Vec<String>) {
+    let stream = engine.market_stream();
+    let mut rx = stream.subscribe();
+    let mut subscribed = HashSet::new();
+
+    if !initial.is_empty() && !subscribe(&mut socket, engine, &mut subscribed, initial).await {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            received = socket.recv() => {
+                let text = match received {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None | Some(Err(_)) => return,
+                    Some(Ok(_)) => continue,
+                };
+
+                let ok = match serde_json::from_str::<ClientRequest>(&text) {
+                    Ok(ClientRequest::Subscribe { symbols }) => {
+                        subscribe(&mut socket, engine, &mut subscribed, symbols).await
+                    }
+                    Ok(ClientRequest::Unsubscribe { symbols }) => {
+                        for symbol in &symbols {
+                            subscribed.remove(symbol);
+                        }
+                        send(&mut socket, &MarketMessage::Unsubscribed { symbols }).await
+                    }
+                    Err(e) => {
+                        send(&mut socket, &MarketMessage::Error { error: e.to_string() }).await
+                    }
+                };
+                if !ok {
+                    return;
+                }
+            }
+            message = rx.recv() => {
+                let ok = match message {
+                    Ok(message) => match message.symbol() {
+                        Some(symbol) if subscribed.contains(&symbol) => {
+                            send(&mut socket, &message).await
+                        }
+                        _ => true,
+                    },
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(skipped, "WebSocket client lagged, resending snapshots");
+                        metrics::counter!("ws_client_lagged").increment(1);
+                        resync(&mut socket, engine, &subscribed).await
+                    }
+                    Err(RecvError::Closed) => return,
+                };
+                if !ok {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Add symbols to the client's subscriptions and send their snapshots
+async fn subscribe(
+    socket: &mut WebSocket,
+    engine: &MatchingEngine,
+    subscribed: &mut HashSet<String>,
+    symbols: Vec<String>,
+) -> bool {
+    let mut books = Vec::with_capacity(symbols.len());
+    for symbol in &symbols {
+        match engine.order_book(symbol) {
+            Some(book) => books.push(book),
+            None => {
+                let error = format!("Unknown symbol {symbol}");
+                return send(socket, &MarketMessage::Error { error }).await;
+            }
+        }
+    }
+
+    subscribed.extend(symbols.iter().cloned());
+    if !send(socket, &MarketMessage::Subscribed { symbols }).await {
+        return false;
+    }
+    for book in books {
+        if !send(socket, &engine.market_stream().snapshot(&book)).await {
+            return false;
+        }
+    }
+    true
+}
+
+async fn resync(
+    socket: &mut WebSocket,
+    engine: &MatchingEngine,
+    subscribed: &HashSet<String>,
+) -> bool {
+    for symbol in subscribed {
+        if let Some(book) = engine.order_book(symbol) {
+            if !send(socket, &engine.market_stream().snapshot(&book)).await {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Send a message, returning false once the client is gone
+async fn send(socket: &mut WebSocket, message: &MarketMessage) -> bool {
+    let text = match serde_json::to_string(message) {
+        Ok(text) => text,
+        Err(e) => {
+            warn!("Failed to encode market message: {}", e);
+            return true;
+        }
+    };
+    let sent = socket.send(Message::Text(text)).await.is_ok();
+    if !sent {
+        debug!("WebSocket client disconnected");
+    }
+    sent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{Order, Side};
+
+    fn order(side: Side, price: i64) -> Order {
+        Order::builder().side(side).price(price).build()
+    }
+
+    fn next_update(rx: &mut broadcast::Receiver<MarketMessage>) -> OrderBookDeltas {
+        loop {
+            if let MarketMessage::Update(update) = rx.try_recv().unwrap() {
+                return update;
+            }
+        }
+    }
+
+    fn level(price: i64, quantity: i64) -> PriceLevel {
+        PriceLevel {
+            price: Decimal::new(price, 0),
+            quantity: Decimal::new(quantity, 0),
+            order_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_diff_levels_reports_changes_and_removals() {
+        let old = vec![level(100, 1), level(99, 2), level(98, 3)];
+        let new = vec![level(101, 5), level(100, 1), level(99, 1)];
+
+        let mut changes = diff_levels(&old, &new);
+        changes.sort();
+
+        assert_eq!(
+            changes,
+            vec![
+                (Decimal::new(98, 0), Decimal::ZERO),
+                (Decimal::new(99, 0), Decimal::ONE),
+                (Decimal::new(101, 0), Decimal::new(5, 0)),
+            ]
+        );
+        assert!(diff_levels(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_updates_chain_book_sequences() {
+        let stream = MarketStream::new(16, 1);
+        let mut rx = stream.subscribe();
+        let book = OrderBook::new(Symbol::new("ETH", "USDT"));
+
+        book.process_order(order(Side::Buy, 1999));
+        stream.publish_book(&book);
+        let first = next_update(&mut rx);
+        assert_eq!((first.prev_sequence, first.sequence), (0, 1));
+        assert!(matches!(rx.try_recv(), Ok(MarketMessage::Bbo(_))));
+
+        // Below the tracked depth: nothing sent, and no gap either
+        book.process_order(order(Side::Buy, 1998));
+        stream.publish_book(&book);
+        assert!(rx.try_recv().is_err());
+
+        book.process_order(order(Side::Buy, 2000));
+        stream.publish_book(&book);
+        let next = next_update(&mut rx);
+        assert_eq!((next.prev_sequence, next.sequence), (1, 3));
+        assert!(next.bids.contains(&(Decimal::new(2000, 0), Decimal::ONE)));
+    }
+}
@@ -0,0 +1,259 @@
+//! Supervised Background Tasks
+//!
+//! Background workers that error, panic or return are restarted with
+//! exponential backoff. The engine reports itself degraded while a task is
+//! down or has failed within the restart window. A task failing more often
+//! than the policy allows exits the process, so the orchestrator restarts
+//! it instead of leaving the API up with nothing matching behind it.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use parking_lot::Mutex;
+use serde::Serialize;
+use tracing::{error, info, warn};
+
+use crate::config::Config;
+
+/// When and how often failed tasks are restarted
+#[derive(Debug, Clone, Copy)]
+pub struct RestartPolicy {
+    /// Failures tolerated within `window` before the process exits
+    pub max_restarts: u32,
+    pub window: Duration,
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RestartPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_restarts: config.task_max_restarts,
+            window: Duration::from_secs(config.task_restart_window_secs),
+            base_backoff: Duration::from_millis(config.task_restart_backoff_ms),
+            max_backoff: Duration::from_millis(config.task_restart_max_backoff_ms),
+        }
+    }
+
+    /// Delay before the restart following `failures` recent failures
+    fn backoff(&self, failures: u32) -> Duration {
+        let factor = 2u32.saturating_pow(failures.saturating_sub(1));
+        self.base_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// State of one supervised task
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TaskStatus {
+    pub running: bool,
+    pub restarts: u64,
+    pub last_error: Option<String>,
+
+    /// Failure times within the restart window
+    #[serde(skip)]
+    failures: VecDeque<Instant>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Decision {
+    Restart(Duration),
+    Exit,
+}
+
+pub struct Supervisor {
+    policy: RestartPolicy,
+    tasks: Mutex<BTreeMap<&'static str, TaskStatus>>,
+}
+
+impl Supervisor {
+    pub fn new(policy: RestartPolicy) -> Self {
+        Self {
+            policy,
+            tasks: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Run `task` for the life of the process, restarting it whenever it stops
+    pub fn spawn<F, Fut>(self: &Arc<Self>, name: &'static str, task: F)
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let supervisor = self.clone();
+        tokio::spawn(async move {
+            loop {
+                supervisor.started(name);
+
+                // Spawned separately so panics surface as join errors
+                let error = match tokio::spawn(task()).await {
+                    Ok(Ok(())) => "task exited".to_string(),
+                    Ok(Err(e)) => e.to_string(),
+                    Err(e) => e.to_string(),
+                };
+
+                match supervisor.failed(name, error.clone(), Instant::now()) {
+                    Decision::Restart(delay) => {
+                        warn!(
+                            task = name,
+                            delay_ms = delay.as_millis() as u64,
+                            "Supervised task failed, restarting: {}",
+                            error
+                        );
+                        tokio::time::sleep(delay).await;
+                    }
+                    Decision::Exit => {
+                        error!(
+                            task = name,
+                            "Supervised task failed too often, exiting: {}", error
+                        );
+                        std::process::exit(1);
+                    }
+                }
+            }
+        });
+    }
+
+    fn started(&self, name: &'static str) {
+        let mut tasks = self.tasks.lock();
+        let status = tasks.entry(name).or_default();
+        if status.last_error.is_some() {
+            status.restarts += 1;
+            metrics::counter!("task_restarts", "task" => name).increment(1);
+            info!(
+                task = name,
+                restarts = status.restarts,
+                "Supervised task restarted"
+            );
+        }
+        status.running = true;
+    }
+
+    fn failed(&self, name: &'static str, error: String, now: Instant) -> Decision {
+        metrics::counter!("task_failures", "task" => name).increment(1);
+
+        let mut tasks = self.tasks.lock();
+        let status = tasks.entry(name).or_default();
+        status.running = false;
+        status.last_error = Some(error);
+        status.failures.push_back(now);
+        while status
+            .failures
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > self.policy.window)
+        {
+            status.failures.pop_front();
+        }
+
+        let recent = status.failures.len() as u32;
+        if recent > self.policy.max_restarts {
+            Decision::Exit
+        } else {
+            Decision::Restart(self.policy.backoff(recent))
+        }
+    }
+
+    /// Whether any task is down or failed within the restart window
+    pub fn is_degraded(&self) -> bool {
+        self.degraded_at(Instant::now())
+    }
+
+    fn degraded_at(&self, now: Instant) -> bool {
+        self.tasks.lock().values().any(|status| {
+            !status.running
+                || status
+                    .failures
+                    .back()
+                    .is_some_and(|t| now.duration_since(*t) <= self.policy.window)
+        })
+    }
+
+    /// Status of every supervised task, by name
+    pub fn tasks(&self) -> BTreeMap<&'static str, TaskStatus> {
+        self.tasks.lock().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn supervisor() -> Supervisor {
+        Supervisor::new(RestartPolicy {
+            max_restarts: 3,
+            window: Duration::from_secs(60),
+            base_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(250),
+        })
+    }
+
+    #[test]
+    fn test_backoff_grows_until_exit() {
+        let supervisor = supervisor();
+        let start = Instant::now();
+        supervisor.started("matching_loop");
+
+        let delays: Vec<_> = (0..4)
+            .map(|i| {
+                supervisor.failed(
+                    "matching_loop",
+                    "boom".into(),
+                    start + Duration::from_secs(i),
+                )
+            })
+            .collect();
+        assert_eq!(
+            delays,
+            vec![
+                Decision::Restart(Duration::from_millis(100)),
+                Decision::Restart(Duration::from_millis(200)),
+                Decision::Restart(Duration::from_millis(250)),
+                Decision::Exit,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failures_age_out_of_window() {
+        let supervisor = supervisor();
+        let start = Instant::now();
+        for i in 0..3 {
+            supervisor.failed(
+                "kafka_consumer",
+                "boom".into(),
+                start + Duration::from_secs(i),
+            );
+        }
+
+        // Old failures no longer count towards the limit
+        let later = start + Duration::from_secs(120);
+        assert_eq!(
+            supervisor.failed("kafka_consumer", "boom".into(), later),
+            Decision::Restart(Duration::from_millis(100))
+        );
+    }
+
+    #[test]
+    fn test_degraded_until_window_passes() {
+        let supervisor = supervisor();
+        let start = Instant::now();
+        supervisor.started("matching_loop");
+        assert!(!supervisor.degraded_at(start));
+
+        supervisor.failed("matching_loop", "boom".into(), start);
+        assert!(supervisor.degraded_at(start));
+
+        // Restarted but failed recently
+        supervisor.started("matching_loop");
+        assert!(supervisor.degraded_at(start + Duration::from_secs(30)));
+        assert!(!supervisor.degraded_at(start + Duration::from_secs(61)));
+
+        let status = &supervisor.tasks()["matching_loop"];
+        assert_eq!(status.restarts, 1);
+        assert_eq!(status.last_error.as_deref(), Some("boom"));
+    }
+}
@@ -0,0 +1,267 @@
+//! Per-User Message Throttles
+//!
+//! Token-bucket limits on order and cancel messages per user. Every user
+//! gets the configured defaults unless an override has been set through
+//! the admin API; overrides are persisted to Redis so they survive
+//! restarts.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use anyhow::Result;
+use dashmap::DashMap;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::config::Config;
+use common::TradingError;
+
+/// Redis hash of user id -> JSON limits
+const OVERRIDES_KEY: &str = "engine:throttles";
+
+/// Message rate limits for one user; a zero rate is unlimited
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThrottleLimits {
+    pub orders_per_sec: u32,
+    pub cancels_per_sec: u32,
+    /// Bucket capacity; 0 uses the per-second rate
+    #[serde(default)]
+    pub burst: u32,
+}
+
+impl ThrottleLimits {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            orders_per_sec: config.throttle_orders_per_sec,
+            cancels_per_sec: config.throttle_cancels_per_sec,
+            burst: config.throttle_burst,
+        }
+    }
+
+    fn rate(&self, kind: MessageKind) -> u32 {
+        match kind {
+            MessageKind::Order => self.orders_per_sec,
+            MessageKind::Cancel => self.cancels_per_sec,
+        }
+    }
+
+    fn capacity(&self, kind: MessageKind) -> f64 {
+        if self.burst > 0 {
+            self.burst as f64
+        } else {
+            self.rate(kind) as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageKind {
+    Order,
+    Cancel,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Persists limit overrides in Redis
+pub struct ThrottleStore {
+    conn: ConnectionManager,
+}
+
+impl ThrottleStore {
+    pub async fn new(redis_url: &str) -> Result<Self> {
+        let client = redis::Client::open(redis_url)?;
+        let conn = ConnectionManager::new(client).await?;
+        Ok(Self { conn })
+    }
+
+    pub async fn load(&self) -> Result<HashMap<Uuid, ThrottleLimits>> {
+        let mut conn = self.conn.clone();
+        let raw: HashMap<String, String> = conn.hgetall(OVERRIDES_KEY).await?;
+
+        Ok(raw
+            .into_iter()
+            .filter_map(|(user, limits)| {
+                Some((user.parse().ok()?, serde_json::from_str(&limits).ok()?))
+            })
+            .collect())
+    }
+
+    async fn save(&self, user_id: Uuid, limits: &ThrottleLimits) -> Result<()> {
+        let mut conn = self.conn.clone();
+        conn.hset::<_, _, _, ()>(
+            OVERRIDES_KEY,
+            user_id.to_string(),
+            serde_json::to_string(limits)?,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, user_id: Uuid) -> Result<()> {
+        let mut conn = self.conn.clone();
+        conn.hdel::<_, _, ()>(OVERRIDES_KEY, user_id.to_string())
+            .await?;
+        Ok(())
+    }
+}
+
+/// Per-user token buckets with runtime-editable limits
+pub struct Throttles {
+    defaults: ThrottleLimits,
+    overrides: DashMap<Uuid, ThrottleLimits>,
+    buckets: DashMap<(Uuid, MessageKind), Bucket>,
+    store: Option<ThrottleStore>,
+}
+
+impl Throttles {
+    pub fn new(defaults: ThrottleLimits, store: Option<ThrottleStore>) -> Self {
+        Self {
+            defaults,
+            overrides: DashMap::new(),
+            buckets: DashMap::new(),
+            store,
+        }
+    }
+
+    /// Load persisted overrides
+    pub async fn restore(&self) -> Result<usize> {
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+
+        let overrides = store.load().await?;
+        let count = overrides.len();
+        for (user_id, limits) in overrides {
+            self.overrides.insert(user_id, limits);
+        }
+        Ok(count)
+    }
+
+    pub fn defaults(&self) -> ThrottleLimits {
+        self.defaults
+    }
+
+    /// Limits in force for `user_id`
+    pub fn limits(&self, user_id: Uuid) -> ThrottleLimits {
+        self.overrides
+            .get(&user_id)
+            .map(|l| *l)
+            .unwrap_or(self.defaults)
+    }
+
+    pub fn is_overridden(&self, user_id: Uuid) -> bool {
+        self.overrides.contains_key(&user_id)
+    }
+
+    pub fn overrides(&self) -> Vec<(Uuid, ThrottleLimits)> {
+        self.overrides
+            .iter()
+            .map(|e| (*e.key(), *e.value()))
+            .collect()
+    }
+
+    /// Set an override, persisting it first
+    pub async fn set_override(&self, user_id: Uuid, limits: ThrottleLimits) -> Result<()> {
+        if let Some(store) = &self.store {
+            store.save(user_id, &limits).await?;
+        }
+        self.overrides.insert(user_id, limits);
+        self.reset(user_id);
+        Ok(())
+    }
+
+    /// Drop an override so the user falls back to the defaults
+    pub async fn remove_override(&self, user_id: Uuid) -> Result<bool> {
+        if let Some(store) = &self.store {
+            store.delete(user_id).await?;
+        }
+        let removed = self.overrides.remove(&user_id).is_some();
+        self.reset(user_id);
+        Ok(removed)
+    }
+
+    /// Start fresh buckets under the new limits
+    fn reset(&self, user_id: Uuid) {
+        self.buckets.remove(&(user_id, MessageKind::Order));
+        self.buckets.remove(&(user_id, MessageKind::Cancel));
+    }
+
+    /// Take one token for a message, failing if the user is over the limit
+    pub fn check(
+        &self,
+        user_id: Uuid,
+        kind: MessageKind,
+        now: Instant,
+    ) -> Result<(), TradingError> {
+        let limits = self.limits(user_id);
+        let rate = limits.rate(kind);
+        if rate == 0 {
+            return Ok(());
+        }
+        let capacity = limits.capacity(kind);
+
+        let mut bucket = self.buckets.entry((user_id, kind)).or_insert(Bucket {
+            tokens: capacity,
+            refilled_at: now,
+        });
+
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * rate as f64).min(capacity);
+        bucket.refilled_at = now;
+
+        if bucket.tokens < 1.0 {
+            return Err(TradingError::RateLimitExceeded);
+        }
+        bucket.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_override_replaces_defaults() {
+        let defaults = ThrottleLimits {
+            orders_per_sec: 2,
+            cancels_per_sec: 0,
+            burst: 0,
+        };
+        let throttles = Throttles::new(defaults, None);
+        let user = Uuid::new_v4();
+        let start = Instant::now();
+
+        assert!(throttles.check(user, MessageKind::Order, start).is_ok());
+        assert!(throttles.check(user, MessageKind::Order, start).is_ok());
+        assert!(throttles.check(user, MessageKind::Order, start).is_err());
+        // Half a second refills one token at 2/s
+        let later = start + Duration::from_millis(500);
+        assert!(throttles.check(user, MessageKind::Order, later).is_ok());
+        // Cancels are unlimited
+        for _ in 0..100 {
+            assert!(throttles.check(user, MessageKind::Cancel, start).is_ok());
+        }
+
+        let vip = ThrottleLimits {
+            orders_per_sec: 100,
+            cancels_per_sec: 100,
+            burst: 5,
+        };
+        throttles.set_override(user, vip).await.unwrap();
+        for _ in 0..5 {
+            assert!(throttles.check(user, MessageKind::Order, later).is_ok());
+        }
+        assert!(throttles.check(user, MessageKind::Order, later).is_err());
+
+        assert!(throttles.remove_override(user).await.unwrap());
+        assert_eq!(throttles.limits(user), defaults);
+    }
+}
@@ -0,0 +1,513 @@
+//! Command Journal
+//!
+//! Append-only log of every command that changes engine state, written by
+//! the matching loop before the command is applied. Entries are numbered
+//! from 1 and stored as JSON lines in segment files named after their
+//! first sequence number; a new segment is started on open and whenever
+//! the current one reaches its size limit.
+//!
+//! Replaying the journal through the matching loop rebuilds every book:
+//! on startup after the last snapshot, and from empty books in audit
+//! mode. The engine clock is pinned to each command's journal time while
+//! it is applied, so expiry and order-age checks decide as they did live
+//! and the same journal always yields the same books, order sequences and
+//! trade ids.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use common::{Clock, Order, Symbol, SymbolConfig, TriggerSource};
+
+use crate::engine::OrderCommand;
+
+const SEGMENT_EXTENSION: &str = "jsonl";
+
+/// A command as journaled; replies and bookkeeping are left out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JournaledCommand {
+    NewOrder {
+        order: Order,
+    },
+    CancelOrder {
+        order_id: Uuid,
+        symbol: Symbol,
+    },
+    ReplaceOrder {
+        order_id: Uuid,
+        replacement: Order,
+    },
+    LogPosition {
+        partition: i32,
+        offset: i64,
+    },
+    ReferencePrice {
+        symbol: Symbol,
+        source: TriggerSource,
+        #[serde(with = "rust_decimal::serde::str")]
+        price: Decimal,
+    },
+    AddSymbol {
+        symbol: Symbol,
+        config: Option<SymbolConfig>,
+    },
+    DelistSymbol {
+        symbol: Symbol,
+    },
+    Uncross {
+        symbol: Symbol,
+    },
+    Halt {
+        symbol: Symbol,
+    },
+    Resume {
+        symbol: Symbol,
+    },
+    Transfer {
+        user_id: Uuid,
+        asset: String,
+        #[serde(with = "rust_decimal::serde::str")]
+        amount: Decimal,
+    },
+}
+
+impl JournaledCommand {
+    /// The journal form of `command`, `None` for commands that leave the
+    /// books unchanged
+    pub fn of(command: &OrderCommand) -> Option<Self> {
+        Some(match command {
+            OrderCommand::NewOrder(order) => Self::NewOrder {
+                order: order.clone(),
+            },
+            OrderCommand::CancelOrder { order_id, symbol } => Self::CancelOrder {
+                order_id: *order_id,
+                symbol: symbol.clone(),
+            },
+            OrderCommand::ReplaceOrder {
+                order_id,
+                replacement,
+            } => Self::ReplaceOrder {
+                order_id: *order_id,
+                replacement: replacement.clone(),
+            },
+            OrderCommand::LogPosition { partition, offset } => Self::LogPosition {
+                partition: *partition,
+                offset: *offset,
+            },
+            OrderCommand::ReferencePrice {
+                symbol,
+                source,
+                price,
+            } => Self::ReferencePrice {
+                symbol: symbol.clone(),
+                source: *source,
+                price: *price,
+            },
+            OrderCommand::AddSymbol { symbol, config, .. } => Self::AddSymbol {
+                symbol: symbol.clone(),
+                config: config.clone(),
+            },
+            OrderCommand::DelistSymbol { symbol, .. } => Self::DelistSymbol {
+                symbol: symbol.clone(),
+            },
+            OrderCommand::Uncross { symbol } => Self::Uncross {
+                symbol: symbol.clone(),
+            },
+            OrderCommand::Halt { symbol, .. } => Self::Halt {
+                symbol: symbol.clone(),
+            },
+            OrderCommand::Resume { symbol, .. } => Self::Resume {
+                symbol: symbol.clone(),
+            },
+            OrderCommand::Transfer {
+                user_id,
+                asset,
+                amount,
+                ..
+            } => Self::Transfer {
+                user_id: *user_id,
+                asset: asset.clone(),
+                amount: *amount,
+            },
+            OrderCommand::Snapshot(_)
+            | OrderCommand::EvictIdle
+            | OrderCommand::FlushBookFeed
+            | OrderCommand::Batch(_)
+            | OrderCommand::Replay { .. } => return None,
+        })
+    }
+
+    /// The command to apply; nobody waits on its reply
+    pub fn into_command(self) -> OrderCommand {
+        match self {
+            Self::NewOrder { order } => OrderCommand::NewOrder(order),
+            Self::CancelOrder { order_id, symbol } => {
+                OrderCommand::CancelOrder { order_id, symbol }
+            }
+            Self::ReplaceOrder {
+                order_id,
+                replacement,
+            } => OrderCommand::ReplaceOrder {
+                order_id,
+                replacement,
+            },
+            Self::LogPosition { partition, offset } => {
+                OrderCommand::LogPosition { partition, offset }
+            }
+            Self::ReferencePrice {
+                symbol,
+                source,
+                price,
+            } => OrderCommand::ReferencePrice {
+                symbol,
+                source,
+                price,
+            },
+            Self::AddSymbol { symbol, config } => OrderCommand::AddSymbol {
+                symbol,
+                config,
+                reply: oneshot::channel().0,
+            },
+            Self::DelistSymbol { symbol } => OrderCommand::DelistSymbol {
+                symbol,
+                reply: oneshot::channel().0,
+            },
+            Self::Uncross { symbol } => OrderCommand::Uncross { symbol },
+            Self::Halt { symbol } => OrderCommand::Halt {
+                symbol,
+                reply: oneshot::channel().0,
+            },
+            Self::Resume { symbol } => OrderCommand::Resume {
+                symbol,
+                reply: oneshot::channel().0,
+            },
+            Self::Transfer {
+                user_id,
+                asset,
+                amount,
+            } => OrderCommand::Transfer {
+                user_id,
+                asset,
+                amount,
+                reply: oneshot::channel().0,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub sequence: u64,
+    /// Engine time the command was applied at
+    pub at: DateTime<Utc>,
+    pub command: JournaledCommand,
+}
+
+/// Writer appending to the newest segment
+pub struct CommandJournal {
+    dir: PathBuf,
+    max_segment_bytes: u64,
+    segment: Option<BufWriter<File>>,
+    segment_bytes: u64,
+    last_sequence: u64,
+}
+
+impl CommandJournal {
+    /// Open the journal in `dir`, continuing after its last entry
+    pub fn open(dir: impl Into<PathBuf>, max_segment_bytes: u64) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).with_context(|| format!("creating {}", dir.display()))?;
+
+        let mut last_sequence = 0;
+        for (_, path) in segments(&dir)?.iter().rev() {
+            if let Some(last) = read_segment(path)?.last() {
+                last_sequence = last.sequence;
+                break;
+            }
+        }
+        info!(dir = %dir.display(), last_sequence, "Command journal opened");
+
+        Ok(Self {
+            dir,
+            max_segment_bytes,
+            segment: None,
+            segment_bytes: 0,
+            last_sequence,
+        })
+    }
+
+    /// Sequence of the last entry written, 0 for an empty journal
+    pub fn last_sequence(&self) -> u64 {
+        self.last_sequence
+    }
+
+    /// Append `command` applied at `at`, returning its sequence number
+    pub fn append(&mut self, at: DateTime<Utc>, command: JournaledCommand) -> Result<u64> {
+        let entry = JournalEntry {
+            sequence: self.last_sequence + 1,
+            at,
+            command,
+        };
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+
+        if self.segment.is_none() || self.segment_bytes >= self.max_segment_bytes {
+            self.roll(entry.sequence)?;
+        }
+        let segment = self.segment.as_mut().expect("segment opened above");
+        if let Err(e) = segment.write_all(&line).and_then(|()| segment.flush()) {
+            // A partial line may have been written; only ever leave one
+            // as the last line of a segment
+            self.segment = None;
+            return Err(e.into());
+        }
+
+        self.segment_bytes += line.len() as u64;
+        self.last_sequence = entry.sequence;
+        metrics::counter!("journal_entries_written").increment(1);
+        Ok(entry.sequence)
+    }
+
+    /// Close the current segment and start one at `first_sequence`
+    fn roll(&mut self, first_sequence: u64) -> Result<()> {
+        if let Some(mut segment) = self.segment.take() {
+            segment.flush()?;
+            segment.get_ref().sync_all()?;
+        }
+
+        let path = segment_path(&self.dir, first_sequence);
+        // A segment already starting here holds no complete entries
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&path)
+            .with_context(|| format!("creating {}", path.display()))?;
+        self.segment = Some(BufWriter::new(file));
+        self.segment_bytes = 0;
+        metrics::counter!("journal_segments_created").increment(1);
+        Ok(())
+    }
+}
+
+/// Reads the entries after a sequence number, a segment at a time so a
+/// long journal is never held in memory at once
+pub struct JournalReader {
+    segments: Vec<(u64, PathBuf)>,
+    after: u64,
+    next: usize,
+}
+
+impl JournalReader {
+    pub fn open(dir: &Path, after: u64) -> Result<Self> {
+        let mut segments = segments(dir)?;
+        // Skip segments that end at or before `after`
+        let first = segments
+            .windows(2)
+            .take_while(|pair| pair[1].0 <= after + 1)
+            .count();
+        segments.drain(..first);
+
+        Ok(Self {
+            segments,
+            after,
+            next: 0,
+        })
+    }
+
+    /// Entries of the next segment, `None` once all have been read
+    pub fn next_segment(&mut self) -> Result<Option<Vec<JournalEntry>>> {
+        let Some((_, path)) = self.segments.get(self.next) else {
+            return Ok(None);
+        };
+        self.next += 1;
+
+        let mut entries = read_segment(path)?;
+        entries.retain(|entry| entry.sequence > self.after);
+        if let Some(last) = entries.last() {
+            self.after = last.sequence;
+        }
+        Ok(Some(entries))
+    }
+}
+
+/// Segment files in `dir` by first sequence number
+fn segments(dir: &Path) -> Result<Vec<(u64, PathBuf)>> {
+    let mut segments = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some(SEGMENT_EXTENSION) {
+            continue;
+        }
+        if let Some(first) = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.parse().ok())
+        {
+            segments.push((first, path));
+        }
+    }
+    segments.sort();
+    Ok(segments)
+}
+
+fn segment_path(dir: &Path, first_sequence: u64) -> PathBuf {
+    dir.join(format!("{first_sequence:020}.{SEGMENT_EXTENSION}"))
+}
+
+/// Read a segment's entries, checking they are numbered consecutively
+///
+/// A crash can leave the last line of a segment half written; that line
+/// is dropped. Anything else unreadable is an error.
+fn read_segment(path: &Path) -> Result<Vec<JournalEntry>> {
+    let file = File::open(path).with_context(|| format!("opening {}", path.display()))?;
+    let mut lines = BufReader::new(file).lines().peekable();
+    let mut entries: Vec<JournalEntry> = Vec::new();
+
+    while let Some(line) = lines.next() {
+        let line = line?;
+        let entry = match serde_json::from_str::<JournalEntry>(&line) {
+            Ok(entry) => entry,
+            Err(e) if lines.peek().is_none() => {
+                warn!(segment = %path.display(), error = %e, "Dropping torn journal entry");
+                break;
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("corrupt entry in {}", path.display()))
+            }
+        };
+        if let Some(previous) = entries.last() {
+            if entry.sequence != previous.sequence + 1 {
+                bail!(
+                    "journal gap in {}: {} follows {}",
+                    path.display(),
+                    entry.sequence,
+                    previous.sequence
+                );
+            }
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Wall-clock time, or the journal time of the command being applied
+///
+/// Sits under the engine's hybrid clock, which keeps the times it hands
+/// out strictly increasing while pinned.
+#[derive(Debug, Default)]
+pub struct JournalClock {
+    pinned: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl JournalClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pin(&self, at: DateTime<Utc>) {
+        *self.pinned.lock().expect("journal clock poisoned") = Some(at);
+    }
+
+    pub fn unpin(&self) {
+        *self.pinned.lock().expect("journal clock poisoned") = None;
+    }
+}
+
+impl Clock for JournalClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.pinned
+            .lock()
+            .expect("journal clock poisoned")
+            .unwrap_or_else(Utc::now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("wal-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn cancel() -> JournaledCommand {
+        JournaledCommand::CancelOrder {
+            order_id: Uuid::new_v4(),
+            symbol: Symbol::new("BTC", "USDT"),
+        }
+    }
+
+    fn read_all(dir: &Path, after: u64) -> Vec<u64> {
+        let mut reader = JournalReader::open(dir, after).unwrap();
+        let mut sequences = Vec::new();
+        while let Some(entries) = reader.next_segment().unwrap() {
+            sequences.extend(entries.iter().map(|e| e.sequence));
+        }
+        sequences
+    }
+
+    #[test]
+    fn test_append_roll_and_read_after() {
+        let dir = temp_dir("roll");
+        let mut journal = CommandJournal::open(&dir, 1).unwrap();
+        for _ in 0..3 {
+            journal.append(Utc::now(), cancel()).unwrap();
+        }
+        // One entry per segment with a 1-byte limit
+        assert_eq!(segments(&dir).unwrap().len(), 3);
+
+        assert_eq!(read_all(&dir, 0), [1, 2, 3]);
+        assert_eq!(read_all(&dir, 2), [3]);
+        assert!(read_all(&dir, 3).is_empty());
+
+        // Reopening continues the numbering in a new segment
+        let mut journal = CommandJournal::open(&dir, 1 << 20).unwrap();
+        assert_eq!(journal.last_sequence(), 3);
+        assert_eq!(journal.append(Utc::now(), cancel()).unwrap(), 4);
+        assert_eq!(read_all(&dir, 1), [2, 3, 4]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_torn_tail_is_dropped() {
+        let dir = temp_dir("torn");
+        let mut journal = CommandJournal::open(&dir, 1 << 20).unwrap();
+        journal.append(Utc::now(), cancel()).unwrap();
+        journal.append(Utc::now(), cancel()).unwrap();
+        drop(journal);
+
+        let (_, path) = segments(&dir).unwrap().pop().unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"sequence\":3,\"at\":").unwrap();
+
+        assert_eq!(read_all(&dir, 0), [1, 2]);
+        let journal = CommandJournal::open(&dir, 1 << 20).unwrap();
+        assert_eq!(journal.last_sequence(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_pinned_clock() {
+        let clock = JournalClock::new();
+        let at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        clock.pin(at);
+        assert_eq!(clock.now(), at);
+        clock.unpin();
+        assert!(clock.now() > at);
+    }
+}
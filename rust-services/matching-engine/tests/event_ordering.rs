@@ -0,0 +1,137 @@
+//! Event ordering contract against a real broker
+//!
+//! Order events are keyed by symbol, so each symbol's events stay on one
+//! partition of a multi-partition topic and are consumed in publish order.
+//! Needs Kafka at `KAFKA_BROKERS` (default `localhost:9092`):
+//!
+//! ```text
+//! cargo test -p matching-engine --test event_ordering -- --ignored
+//! ```
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::Utc;
+use common::{Event, OrderStatus, OrderUpdated, Symbol, TypedEvent};
+use rdkafka::{
+    admin::{AdminClient, AdminOptions, NewTopic, TopicReplication},
+    client::DefaultClientContext,
+    consumer::{Consumer, StreamConsumer},
+    producer::{FutureProducer, FutureRecord},
+    ClientConfig, Message,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+const PARTITIONS: i32 = 4;
+const EVENTS_PER_SYMBOL: usize = 25;
+
+fn brokers() -> String {
+    std::env::var("KAFKA_BROKERS").unwrap_or_else(|_| "localhost:9092".to_string())
+}
+
+fn update(symbol: &Symbol, filled: usize) -> OrderUpdated {
+    OrderUpdated {
+        order_id: Uuid::new_v4(),
+        client_order_id: format!("ordering-{filled}"),
+        symbol: symbol.clone(),
+        status: OrderStatus::PartiallyFilled,
+        filled_quantity: Decimal::from(filled as u64),
+        remaining_quantity: Decimal::ONE,
+        avg_fill_price: None,
+        fills: vec![],
+        fills_truncated: false,
+        timestamp: Utc::now(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires a Kafka broker"]
+async fn test_symbol_events_stay_ordered_across_partitions() {
+    let topic = format!("test.ordering.{}", Uuid::new_v4());
+    let admin: AdminClient<DefaultClientContext> = ClientConfig::new()
+        .set("bootstrap.servers", brokers())
+        .create()
+        .unwrap();
+    admin
+        .create_topics(
+            &[NewTopic::new(
+                &topic,
+                PARTITIONS,
+                TopicReplication::Fixed(1),
+            )],
+            &AdminOptions::new(),
+        )
+        .await
+        .unwrap();
+
+    let producer: FutureProducer = ClientConfig::new()
+        .set("bootstrap.servers", brokers())
+        .create()
+        .unwrap();
+    let symbols = [
+        Symbol::new("BTC", "USDT"),
+        Symbol::new("ETH", "USDT"),
+        Symbol::new("SOL", "USDT"),
+        Symbol::new("LINK", "USDT"),
+    ];
+
+    // Interleave symbols the way the matching loop does
+    for filled in 0..EVENTS_PER_SYMBOL {
+        for symbol in &symbols {
+            let outbound = Event::builder(update(symbol, filled))
+                .source("ordering-test")
+                .build();
+            assert_eq!(outbound.key, symbol.to_string());
+            producer
+                .send(
+                    FutureRecord::to(&topic)
+                        .key(&outbound.key)
+                        .payload(&outbound.to_json().unwrap()),
+                    Duration::from_secs(5),
+                )
+                .await
+                .unwrap();
+        }
+    }
+
+    let consumer: StreamConsumer = ClientConfig::new()
+        .set("bootstrap.servers", brokers())
+        .set("group.id", format!("ordering-{}", Uuid::new_v4()))
+        .set("auto.offset.reset", "earliest")
+        .create()
+        .unwrap();
+    consumer.subscribe(&[&topic]).unwrap();
+
+    let mut partitions: HashMap<String, i32> = HashMap::new();
+    let mut filled: HashMap<String, Vec<Decimal>> = HashMap::new();
+    for _ in 0..symbols.len() * EVENTS_PER_SYMBOL {
+        let message = tokio::time::timeout(Duration::from_secs(30), consumer.recv())
+            .await
+            .expect("timed out waiting for events")
+            .unwrap();
+        let event: Event<OrderUpdated> =
+            serde_json::from_slice(message.payload().unwrap()).unwrap();
+        let key = event.payload.key();
+
+        // A symbol never spans partitions
+        let partition = *partitions.entry(key.clone()).or_insert(message.partition());
+        assert_eq!(partition, message.partition(), "{key} changed partition");
+        filled
+            .entry(key)
+            .or_default()
+            .push(event.payload.filled_quantity);
+    }
+
+    let expected: Vec<Decimal> = (0..EVENTS_PER_SYMBOL)
+        .map(|n| Decimal::from(n as u64))
+        .collect();
+    for symbol in &symbols {
+        assert_eq!(filled[&symbol.to_string()], expected, "{symbol} reordered");
+    }
+
+    admin
+        .delete_topics(&[&topic], &AdminOptions::new())
+        .await
+        .unwrap();
+}
@@ -0,0 +1,461 @@
+//! OMS HTTP and WebSocket API
+//!
+//! Clients submit, query, replace and cancel orders here; the OMS is the single
+//! upstream of the matching engine.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::Utc;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tower_http::trace::TraceLayer;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::auth::{AuthUser, JwtVerifier};
+use crate::config::Config;
+use crate::kafka::OrderPublisher;
+use crate::risk::RiskClient;
+use crate::session;
+use crate::store::OrderStore;
+use common::{
+    AnyId, Order, OrderStatus, OrderType, SelfTradePrevention, Side, Symbol, TimeInForce,
+};
+
+#[derive(Clone)]
+pub struct AppState {
+    pub store: Arc<OrderStore>,
+    pub publisher: Arc<OrderPublisher>,
+    pub risk: Arc<RiskClient>,
+    pub auth: Arc<JwtVerifier>,
+    /// Inbound message limit per order entry session (0 = unlimited)
+    pub session_messages_per_sec: u32,
+    /// Silence tolerated on a cancel-on-disconnect session
+    pub session_heartbeat_timeout: Duration,
+    pub sessions: Arc<session::Sessions>,
+}
+
+pub async fn run_server(state: AppState, config: &Config) -> anyhow::Result<()> {
+    let app = Router::new()
+        .route("/health", get(health))
+        .route("/orders", get(list_orders).post(submit_order))
+        .route(
+            "/orders/:order_id",
+            get(get_order).put(replace_order).delete(cancel_order),
+        )
+        .route("/ws", get(order_stream))
+        .route("/ws/orders", get(order_entry))
+        .with_state(state)
+        .layer(TraceLayer::new_for_http());
+
+    let addr = format!("{}:{}", config.host, config.port);
+    info!("Starting order management API on {}", addr);
+
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
+
+// ============== Request/Response Types ==============
+
+#[derive(Debug, Deserialize)]
+pub struct SubmitOrderRequest {
+    pub client_order_id: Option<String>,
+    pub symbol: String,
+    pub side: Side,
+    pub order_type: OrderType,
+    #[serde(with = "common::decimal::flex")]
+    pub quantity: Decimal,
+    #[serde(default, with = "common::decimal::flex_option")]
+    pub price: Option<Decimal>,
+    pub time_in_force: Option<TimeInForce>,
+    pub expire_at: Option<chrono::DateTime<Utc>>,
+    /// Iceberg slice shown on the book
+    #[serde(default, with = "common::decimal::flex_option")]
+    pub display_quantity: Option<Decimal>,
+    /// Defaults to cancelling the resting order
+    pub self_trade_prevention: Option<SelfTradePrevention>,
+}
+
+/// Cancel/replace of a resting limit order
+///
+/// `quantity` is the open quantity of the replacement and defaults to the
+/// original's remaining quantity; `price` defaults to the original price.
+#[derive(Debug, Deserialize)]
+pub struct ReplaceOrderRequest {
+    pub client_order_id: Option<String>,
+    #[serde(default, with = "common::decimal::flex_option")]
+    pub quantity: Option<Decimal>,
+    #[serde(default, with = "common::decimal::flex_option")]
+    pub price: Option<Decimal>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiError {
+    pub error: String,
+    pub code: String,
+}
+
+impl ApiError {
+    pub fn new(code: &str, error: impl ToString) -> Self {
+        Self {
+            error: error.to_string(),
+            code: code.to_string(),
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self.code.as_str() {
+            "UNAUTHORIZED" => StatusCode::UNAUTHORIZED,
+            "ORDER_NOT_FOUND" => StatusCode::NOT_FOUND,
+            "PUBLISH_FAILED" => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status(), Json(self)).into_response()
+    }
+}
+
+// ============== Handlers ==============
+
+async fn health() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "status": "healthy",
+        "service": "order-management"
+    }))
+}
+
+async fn submit_order(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(req): Json<SubmitOrderRequest>,
+) -> Result<Json<Order>, ApiError> {
+    place_order(&state, user_id, req).await.map(Json)
+}
+
+/// Risk-check a new order and route it to the matching engine
+///
+/// Resubmitting a client order id returns the original order.
+pub(crate) async fn place_order(
+    state: &AppState,
+    user_id: Uuid,
+    req: SubmitOrderRequest,
+) -> Result<Order, ApiError> {
+    let order = build_order(user_id, req)?;
+
+    if let Some(existing) = state.store.insert(order.clone()) {
+        info!(order_id = %existing.id, "Duplicate client order id");
+        return Ok(existing);
+    }
+
+    if let Err(e) = state.risk.check(&order).await {
+        state.store.set_status(order.id, OrderStatus::Rejected);
+        return Err(ApiError::new("RISK_REJECTED", e));
+    }
+
+    let correlation_id = Uuid::new_v4();
+    if let Err(e) = state
+        .publisher
+        .publish_submitted(&order, correlation_id)
+        .await
+    {
+        warn!(order_id = %order.id, "Failed to publish order: {}", e);
+        state.store.set_status(order.id, OrderStatus::Rejected);
+        return Err(ApiError::new("PUBLISH_FAILED", e));
+    }
+
+    info!(
+        order_id = %order.id,
+        user_id = %user_id,
+        correlation_id = %correlation_id,
+        "Order submitted"
+    );
+
+    Ok(order)
+}
+
+/// Validate a submit request and build the order
+fn build_order(user_id: Uuid, req: SubmitOrderRequest) -> Result<Order, ApiError> {
+    let quantity = req.quantity;
+    if quantity <= Decimal::ZERO {
+        return Err(ApiError::new("INVALID_QUANTITY", "Invalid quantity"));
+    }
+    let price = req.price;
+
+    if req.order_type == OrderType::Limit && price.is_none() {
+        return Err(ApiError::new(
+            "PRICE_REQUIRED",
+            "Limit order requires price",
+        ));
+    }
+
+    if req.time_in_force == Some(TimeInForce::GTD) && req.expire_at.is_none() {
+        return Err(ApiError::new(
+            "EXPIRY_REQUIRED",
+            "GTD order requires expire_at",
+        ));
+    }
+
+    let parts: Vec<&str> = req.symbol.split('-').collect();
+    if parts.len() != 2 {
+        return Err(ApiError::new("INVALID_SYMBOL", "Invalid symbol format"));
+    }
+
+    let now = Utc::now();
+    let id = Uuid::new_v4();
+
+    let order = Order {
+        id,
+        client_order_id: req.client_order_id.unwrap_or_else(|| id.to_string()),
+        user_id,
+        symbol: Symbol::new(parts[0], parts[1]),
+        side: req.side,
+        order_type: req.order_type,
+        time_in_force: req.time_in_force.unwrap_or(TimeInForce::GTC),
+        status: OrderStatus::Pending,
+        price,
+        stop_price: None,
+        trigger_source: None,
+        quantity,
+        filled_quantity: Decimal::ZERO,
+        remaining_quantity: quantity,
+        avg_fill_price: None,
+        sequence: 0,
+        created_at: now,
+        updated_at: now,
+        expire_at: req.expire_at,
+        display_quantity: req.display_quantity,
+        self_trade_prevention: req.self_trade_prevention.unwrap_or_default(),
+    };
+
+    order
+        .validate_display_quantity()
+        .map_err(|e| ApiError::new("INVALID_ORDER", e))?;
+
+    Ok(order)
+}
+
+async fn list_orders(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Json<Vec<Order>> {
+    Json(state.store.list_for_user(user_id))
+}
+
+/// Look up an order owned by the user
+fn owned_order(state: &AppState, user_id: Uuid, order_id: Uuid) -> Result<Order, ApiError> {
+    state
+        .store
+        .get(order_id)
+        .filter(|o| o.user_id == user_id)
+        .ok_or_else(|| ApiError::new("ORDER_NOT_FOUND", "Order not found"))
+}
+
+async fn get_order(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(AnyId(order_id)): Path<AnyId>,
+) -> Result<Json<Order>, ApiError> {
+    owned_order(&state, user_id, order_id).map(Json)
+}
+
+async fn cancel_order(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(AnyId(order_id)): Path<AnyId>,
+) -> Result<StatusCode, ApiError> {
+    request_cancel(&state, user_id, order_id).await?;
+
+    // Final state arrives with the engine's cancellation event
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Ask the matching engine to cancel an open order owned by the user
+pub(crate) async fn request_cancel(
+    state: &AppState,
+    user_id: Uuid,
+    order_id: Uuid,
+) -> Result<Order, ApiError> {
+    let order = owned_order(state, user_id, order_id)?;
+
+    if !matches!(
+        order.status,
+        OrderStatus::Pending | OrderStatus::Open | OrderStatus::PartiallyFilled
+    ) {
+        return Err(ApiError::new(
+            "ORDER_NOT_CANCELLABLE",
+            format!("Order is {:?}", order.status),
+        ));
+    }
+
+    state
+        .publisher
+        .publish_cancel(&order)
+        .await
+        .map_err(|e| ApiError::new("PUBLISH_FAILED", e))?;
+
+    Ok(order)
+}
+
+/// Ask the matching engine to cancel every open order of the user,
+/// returning how many cancels were sent
+pub(crate) async fn cancel_open_orders(state: &AppState, user_id: Uuid) -> usize {
+    let mut sent = 0;
+    for order in state.store.list_for_user(user_id) {
+        if !matches!(
+            order.status,
+            OrderStatus::Pending | OrderStatus::Open | OrderStatus::PartiallyFilled
+        ) {
+            continue;
+        }
+        match state.publisher.publish_cancel(&order).await {
+            Ok(()) => sent += 1,
+            Err(e) => warn!(order_id = %order.id, "Failed to publish cancel: {}", e),
+        }
+    }
+    sent
+}
+
+async fn replace_order(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(AnyId(order_id)): Path<AnyId>,
+    Json(req): Json<ReplaceOrderRequest>,
+) -> Result<Json<Order>, ApiError> {
+    request_replace(&state, user_id, order_id, req)
+        .await
+        .map(Json)
+}
+
+/// Ask the matching engine to replace a resting limit order owned by the
+/// user, returning the replacement
+pub(crate) async fn request_replace(
+    state: &AppState,
+    user_id: Uuid,
+    order_id: Uuid,
+    req: ReplaceOrderRequest,
+) -> Result<Order, ApiError> {
+    let order = owned_order(state, user_id, order_id)?;
+
+    if order.order_type != OrderType::Limit
+        || !matches!(
+            order.status,
+            OrderStatus::Pending | OrderStatus::Open | OrderStatus::PartiallyFilled
+        )
+    {
+        return Err(ApiError::new(
+            "ORDER_NOT_REPLACEABLE",
+            format!("{:?} order is {:?}", order.order_type, order.status),
+        ));
+    }
+
+    let replacement = build_order(
+        user_id,
+        SubmitOrderRequest {
+            client_order_id: req.client_order_id,
+            symbol: order.symbol.to_string(),
+            side: order.side,
+            order_type: order.order_type,
+            quantity: req.quantity.unwrap_or(order.remaining_quantity),
+            price: req.price.or(order.price),
+            time_in_force: Some(order.time_in_force),
+            expire_at: order.expire_at,
+            display_quantity: order.display_quantity,
+            self_trade_prevention: Some(order.self_trade_prevention),
+        },
+    )?;
+
+    if let Some(existing) = state.store.insert(replacement.clone()) {
+        info!(order_id = %existing.id, "Duplicate client order id");
+        return Ok(existing);
+    }
+
+    if let Err(e) = state.risk.check(&replacement).await {
+        state
+            .store
+            .set_status(replacement.id, OrderStatus::Rejected);
+        return Err(ApiError::new("RISK_REJECTED", e));
+    }
+
+    if let Err(e) = state.publisher.publish_replace(&order, &replacement).await {
+        warn!(order_id = %order.id, "Failed to publish replace: {}", e);
+        state
+            .store
+            .set_status(replacement.id, OrderStatus::Rejected);
+        return Err(ApiError::new("PUBLISH_FAILED", e));
+    }
+
+    info!(
+        order_id = %order.id,
+        replacement_id = %replacement.id,
+        user_id = %user_id,
+        "Order replace submitted"
+    );
+
+    // The original's cancellation and the replacement's state arrive as
+    // engine events
+    Ok(replacement)
+}
+
+async fn order_stream(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    ws: WebSocketUpgrade,
+) -> Response {
+    ws.on_upgrade(move |socket| stream_orders(socket, state.store, user_id))
+}
+
+/// Push the user's order updates until the client disconnects
+async fn stream_orders(mut socket: WebSocket, store: Arc<OrderStore>, user_id: Uuid) {
+    let mut updates = store.subscribe();
+
+    loop {
+        let order = match updates.recv().await {
+            Ok(order) => order,
+            Err(RecvError::Lagged(skipped)) => {
+                warn!(user_id = %user_id, skipped, "Order stream lagged");
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+
+        if order.user_id != user_id {
+            continue;
+        }
+
+        let Ok(payload) = serde_json::to_string(&order) else {
+            continue;
+        };
+        if socket.send(Message::Text(payload)).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Order entry session: submit, replace and cancel orders as messages and
+/// receive acknowledgments and execution reports on the same connection
+async fn order_entry(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(params): Query<session::SessionParams>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    ws.on_upgrade(move |socket| session::serve(socket, state, user_id, params))
+}
@@ -0,0 +1,76 @@
+//! Access Token Authentication
+//!
+//! Validates HS256 access tokens issued by the backend. The `sub` claim
+//! is the user id every order is attributed to.
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts},
+};
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::api::{ApiError, AppState};
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    sub: Uuid,
+    #[serde(rename = "type")]
+    token_type: String,
+}
+
+/// Verifies access tokens
+pub struct JwtVerifier {
+    key: DecodingKey,
+    validation: Validation,
+}
+
+impl JwtVerifier {
+    pub fn new(secret: &str) -> Self {
+        Self {
+            key: DecodingKey::from_secret(secret.as_bytes()),
+            validation: Validation::new(Algorithm::HS256),
+        }
+    }
+
+    /// Validate a token and return its user id
+    pub fn verify(&self, token: &str) -> Option<Uuid> {
+        let claims = decode::<Claims>(token, &self.key, &self.validation)
+            .ok()?
+            .claims;
+
+        (claims.token_type == "access").then_some(claims.sub)
+    }
+}
+
+/// Authenticated user extracted from the request
+///
+/// Reads `Authorization: Bearer <token>`, falling back to a `token`
+/// query parameter for WebSocket clients that cannot set headers.
+pub struct AuthUser(pub Uuid);
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, ApiError> {
+        let header_token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+
+        let query_token = parts
+            .uri
+            .query()
+            .and_then(|q| q.split('&').find_map(|pair| pair.strip_prefix("token=")));
+
+        header_token
+            .or(query_token)
+            .and_then(|token| state.auth.verify(token))
+            .map(AuthUser)
+            .ok_or_else(|| ApiError::new("UNAUTHORIZED", "Missing or invalid access token"))
+    }
+}
@@ -0,0 +1,82 @@
+//! Order Management Service Configuration
+
+use common::config::{schemes, Checks, Validate};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default = "default_host")]
+    pub host: String,
+
+    #[serde(default = "default_port")]
+    pub port: u16,
+
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+
+    pub kafka_brokers: String,
+
+    #[serde(default = "default_kafka_group")]
+    pub kafka_group_id: String,
+
+    // Auth
+    /// HS256 secret shared with the backend that issues access tokens
+    pub jwt_secret: String,
+
+    // Risk
+    /// Base URL of the pre-trade risk service; local limits only when unset
+    pub risk_url: Option<String>,
+
+    #[serde(default = "default_risk_timeout")]
+    pub risk_timeout_ms: u64,
+
+    /// Reject limit orders above this quote notional
+    pub max_order_notional: Option<Decimal>,
+
+    /// Requests accepted per second on each WebSocket order entry
+    /// session; 0 is unlimited
+    #[serde(default = "default_session_messages_per_sec")]
+    pub session_messages_per_sec: u32,
+
+    /// Silence after which a cancel-on-disconnect session is dropped and
+    /// its user's open orders cancelled
+    #[serde(default = "default_session_heartbeat_timeout")]
+    pub session_heartbeat_timeout_secs: u64,
+}
+
+fn default_host() -> String {
+    "0.0.0.0".to_string()
+}
+fn default_port() -> u16 {
+    8082
+}
+fn default_log_level() -> String {
+    "info".to_string()
+}
+fn default_kafka_group() -> String {
+    "order-management".to_string()
+}
+fn default_risk_timeout() -> u64 {
+    200
+}
+fn default_session_messages_per_sec() -> u32 {
+    50
+}
+fn default_session_heartbeat_timeout() -> u64 {
+    10
+}
+
+impl Validate for Config {
+    fn validate(&self, checks: &mut Checks) {
+        checks.ports(&[("port", self.port)]);
+        checks.brokers("kafka_brokers", &self.kafka_brokers);
+        checks.non_empty("jwt_secret", &self.jwt_secret);
+        checks.optional_url("risk_url", self.risk_url.as_deref(), schemes::HTTP);
+        checks.duration("risk_timeout_ms", self.risk_timeout_ms);
+        checks.duration(
+            "session_heartbeat_timeout_secs",
+            self.session_heartbeat_timeout_secs,
+        );
+    }
+}
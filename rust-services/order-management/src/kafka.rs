@@ -0,0 +1,154 @@
+//! Kafka Integration
+//!
+//! Publishes order submissions, cancels and replaces to the matching
+//! engine, and folds the engine's order events back into the store.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Utc;
+use rdkafka::{
+    consumer::{Consumer, StreamConsumer},
+    producer::{FutureProducer, FutureRecord},
+    ClientConfig, Message,
+};
+use tokio_stream::StreamExt;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::store::OrderStore;
+use common::{
+    events::{
+        topics, Event, OrderCancelRequested, OrderCancelled, OrderCommandEnvelope,
+        OrderReplaceRequested, OrderSubmitted, OrderUpdated, OutboundEvent,
+    },
+    Order,
+};
+
+const SOURCE: &str = "order-management";
+
+pub struct OrderPublisher {
+    producer: FutureProducer,
+}
+
+impl OrderPublisher {
+    pub fn new(config: &Config) -> Result<Self> {
+        let producer: FutureProducer = ClientConfig::new()
+            .set("bootstrap.servers", &config.kafka_brokers)
+            .set("message.timeout.ms", "5000")
+            .set("acks", "all")
+            .set("enable.idempotence", "true")
+            .create()?;
+
+        Ok(Self { producer })
+    }
+
+    /// Publish a new order to the matching engine
+    pub async fn publish_submitted(&self, order: &Order, correlation_id: Uuid) -> Result<()> {
+        let command = OrderCommandEnvelope::Submit(OrderSubmitted {
+            order: order.clone(),
+        });
+        self.send(
+            Event::builder(command)
+                .source(SOURCE)
+                .correlation_id(correlation_id)
+                .build(),
+        )
+        .await
+    }
+
+    /// Ask the matching engine to cancel an order
+    pub async fn publish_cancel(&self, order: &Order) -> Result<()> {
+        let command = OrderCommandEnvelope::Cancel(OrderCancelRequested {
+            order_id: order.id,
+            user_id: order.user_id,
+            symbol: order.symbol.clone(),
+            timestamp: Utc::now(),
+        });
+        self.send(Event::builder(command).source(SOURCE).build())
+            .await
+    }
+
+    /// Ask the matching engine to replace `order` with `replacement`
+    pub async fn publish_replace(&self, order: &Order, replacement: &Order) -> Result<()> {
+        let command = OrderCommandEnvelope::Replace(OrderReplaceRequested {
+            order_id: order.id,
+            user_id: order.user_id,
+            symbol: order.symbol.clone(),
+            replacement: replacement.clone(),
+            timestamp: Utc::now(),
+        });
+        self.send(Event::builder(command).source(SOURCE).build())
+            .await
+    }
+
+    /// Commands are keyed by symbol so a cancel never overtakes the
+    /// order it targets
+    async fn send(&self, outbound: OutboundEvent<OrderCommandEnvelope>) -> Result<()> {
+        let payload = outbound.to_json()?;
+
+        self.producer
+            .send(
+                FutureRecord::to(outbound.topic)
+                    .key(&outbound.key)
+                    .payload(&payload),
+                Duration::from_secs(5),
+            )
+            .await
+            .map_err(|(e, _)| anyhow::anyhow!("Kafka send error: {e}"))?;
+
+        Ok(())
+    }
+}
+
+/// Track order state from matching engine events
+pub async fn run_event_consumer(store: Arc<OrderStore>, config: &Config) -> Result<()> {
+    let consumer: StreamConsumer = ClientConfig::new()
+        .set("bootstrap.servers", &config.kafka_brokers)
+        .set("group.id", &config.kafka_group_id)
+        .set("enable.auto.commit", "true")
+        .set("auto.offset.reset", "latest")
+        .create()?;
+
+    consumer.subscribe(&[topics::ORDERS])?;
+
+    info!("Order event consumer started on {}", topics::ORDERS);
+
+    let mut stream = consumer.stream();
+
+    while let Some(message) = stream.next().await {
+        match message {
+            Ok(msg) => {
+                if let Some(payload) = msg.payload() {
+                    if let Err(e) = process_event(&store, payload) {
+                        error!("Failed to process order event: {}", e);
+                    }
+                }
+            }
+            Err(e) => warn!("Kafka error: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+fn process_event(store: &OrderStore, payload: &[u8]) -> Result<()> {
+    let event: Event<serde_json::Value> = serde_json::from_slice(payload)?;
+
+    match event.event_type.as_str() {
+        "order_updated" => {
+            let update: OrderUpdated = serde_json::from_value(event.payload)?;
+            store.apply_update(&update);
+        }
+        "order_cancelled" => {
+            let cancelled: OrderCancelled = serde_json::from_value(event.payload)?;
+            store.apply_cancel(&cancelled);
+        }
+        // Our own order commands share the topic
+        _ => {}
+    }
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+//! FastTrading Order Management Service
+//!
+//! Thin OMS between clients and the matching engine:
+//! - Authenticates REST/WebSocket clients
+//! - Accepts order entry over REST or WebSocket sessions
+//! - Cancels open orders when a cancel-on-disconnect session drops
+//! - Deduplicates client order ids and runs pre-trade risk
+//! - Publishes `OrderCommandEnvelope` commands to Kafka
+//! - Tracks order state from matching engine events
+
+use anyhow::Result;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+mod api;
+mod auth;
+mod config;
+mod kafka;
+mod risk;
+mod session;
+mod store;
+
+use config::Config;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+    let config: Config = common::config::load()?;
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::new(&config.log_level))
+        .with(tracing_subscriber::fmt::layer().json())
+        .init();
+
+    info!(
+        "Starting FastTrading Order Management v{}",
+        env!("CARGO_PKG_VERSION")
+    );
+
+    let store = Arc::new(store::OrderStore::new());
+
+    // Track order state from engine events
+    let store_clone = store.clone();
+    let config_clone = config.clone();
+    tokio::spawn(async move {
+        if let Err(e) = kafka::run_event_consumer(store_clone, &config_clone).await {
+            tracing::error!("Order event consumer error: {}", e);
+        }
+    });
+
+    let state = api::AppState {
+        store,
+        publisher: Arc::new(kafka::OrderPublisher::new(&config)?),
+        risk: Arc::new(risk::RiskClient::new(&config)?),
+        auth: Arc::new(auth::JwtVerifier::new(&config.jwt_secret)),
+        session_messages_per_sec: config.session_messages_per_sec,
+        session_heartbeat_timeout: Duration::from_secs(config.session_heartbeat_timeout_secs),
+        sessions: Arc::new(session::Sessions::default()),
+    };
+
+    api::run_server(state, &config).await?;
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+//! Pre-Trade Risk Checks
+//!
+//! Applies local limits, then asks the risk service (if configured) to
+//! approve the order. The remote check fails closed: an unreachable risk
+//! service rejects the order.
+
+use std::time::Duration;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::Config;
+use common::{Order, TradingError};
+
+#[derive(Debug, Serialize)]
+struct RiskCheckRequest<'a> {
+    order: &'a Order,
+}
+
+#[derive(Debug, Deserialize)]
+struct RiskDecision {
+    approved: bool,
+    reason: Option<String>,
+}
+
+pub struct RiskClient {
+    http: reqwest::Client,
+    url: Option<String>,
+    max_order_notional: Option<Decimal>,
+}
+
+impl RiskClient {
+    pub fn new(config: &Config) -> anyhow::Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.risk_timeout_ms))
+            .build()?;
+
+        Ok(Self {
+            http,
+            url: config.risk_url.clone(),
+            max_order_notional: config.max_order_notional,
+        })
+    }
+
+    /// Approve or reject an order before it is routed
+    pub async fn check(&self, order: &Order) -> Result<(), TradingError> {
+        if let (Some(max), Some(price)) = (self.max_order_notional, order.price) {
+            let notional = price * order.quantity;
+            if notional > max {
+                return Err(TradingError::OrderRejected(format!(
+                    "Order notional {notional} exceeds limit {max}"
+                )));
+            }
+        }
+
+        let Some(url) = &self.url else {
+            return Ok(());
+        };
+
+        let decision: RiskDecision = self
+            .http
+            .post(format!("{url}/risk/pre-trade"))
+            .json(&RiskCheckRequest { order })
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| {
+                warn!(order_id = %order.id, "Risk service call failed: {}", e);
+                TradingError::OrderRejected("Risk service unavailable".to_string())
+            })?
+            .json()
+            .await
+            .map_err(|e| {
+                warn!(order_id = %order.id, "Invalid risk service response: {}", e);
+                TradingError::OrderRejected("Risk service unavailable".to_string())
+            })?;
+
+        if decision.approved {
+            Ok(())
+        } else {
+            Err(TradingError::OrderRejected(
+                decision
+                    .reason
+                    .unwrap_or_else(|| "Rejected by risk".to_string()),
+            ))
+        }
+    }
+}
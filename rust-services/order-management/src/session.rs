@@ -0,0 +1,386 @@
+//! WebSocket Order Entry Sessions
+//!
+//! An authenticated client submits, replaces and cancels orders as JSON
+//! messages and receives acknowledgments, rejects and execution reports on
+//! the same connection, saving an HTTP round trip per order.
+//!
+//! Every client request carries a `seq` that must increase; a repeated or
+//! out-of-order `seq` is rejected without acting on it, so a replayed
+//! message never places an order twice. Every server message carries its
+//! own per-session `seq` so clients can detect gaps. Inbound requests are
+//! limited per second; excess requests are rejected with `THROTTLED`.
+//! Clients that fall behind the execution report stream are resent the
+//! state of their open orders.
+//!
+//! A session opened with `cancel_on_disconnect=true` protects a market
+//! maker from stale quotes: once the user's last such session drops, or
+//! goes silent for the heartbeat timeout, all their open orders are
+//! cancelled. Any frame counts as a heartbeat, WebSocket pings included.
+
+use std::time::{Duration, Instant};
+
+use axum::extract::ws::{Message, WebSocket};
+use dashmap::{mapref::entry::Entry, DashMap};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::api::{self, ApiError, AppState, ReplaceOrderRequest, SubmitOrderRequest};
+use common::{Order, OrderStatus};
+
+/// Request from an order entry client
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum ClientRequest {
+    Submit {
+        seq: u64,
+        #[serde(flatten)]
+        order: SubmitOrderRequest,
+    },
+    Replace {
+        seq: u64,
+        #[serde(deserialize_with = "common::ids::flex::deserialize")]
+        order_id: Uuid,
+        #[serde(flatten)]
+        replace: ReplaceOrderRequest,
+    },
+    Cancel {
+        seq: u64,
+        #[serde(deserialize_with = "common::ids::flex::deserialize")]
+        order_id: Uuid,
+    },
+}
+
+impl ClientRequest {
+    fn seq(&self) -> u64 {
+        match self {
+            Self::Submit { seq, .. } | Self::Replace { seq, .. } | Self::Cancel { seq, .. } => *seq,
+        }
+    }
+}
+
+/// Message sent to order entry clients
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SessionMessage {
+    /// First message of a session
+    Session {
+        user_id: Uuid,
+        /// Inbound request limit; 0 is unlimited
+        max_messages_per_sec: u32,
+        cancel_on_disconnect: bool,
+        /// Silence after which the session is dropped, if cancel-on-disconnect
+        #[serde(skip_serializing_if = "Option::is_none")]
+        heartbeat_timeout_secs: Option<u64>,
+    },
+    /// Request accepted; `order` is the submitted, replacement or
+    /// cancel-pending order
+    Ack { request_seq: u64, order: Order },
+    /// Request refused; `request_seq` is absent for unreadable messages
+    Reject {
+        request_seq: Option<u64>,
+        code: String,
+        error: String,
+    },
+    /// Order state changed
+    ExecutionReport { order: Order },
+}
+
+#[derive(Debug, Serialize)]
+struct Envelope<'a> {
+    seq: u64,
+    #[serde(flatten)]
+    message: &'a SessionMessage,
+}
+
+/// Options a session is opened with
+#[derive(Debug, Default, Deserialize)]
+pub struct SessionParams {
+    /// Cancel the user's open orders when the session ends
+    #[serde(default)]
+    pub cancel_on_disconnect: bool,
+}
+
+/// Open cancel-on-disconnect sessions per user
+#[derive(Debug, Default)]
+pub struct Sessions {
+    guarded: DashMap<Uuid, usize>,
+}
+
+impl Sessions {
+    fn open(&self, user_id: Uuid) {
+        *self.guarded.entry(user_id).or_default() += 1;
+    }
+
+    /// A session closed; true if it was the user's last
+    fn close(&self, user_id: Uuid) -> bool {
+        match self.guarded.entry(user_id) {
+            Entry::Occupied(mut open) if *open.get() > 1 => {
+                *open.get_mut() -= 1;
+                false
+            }
+            Entry::Occupied(open) => {
+                open.remove();
+                true
+            }
+            Entry::Vacant(_) => false,
+        }
+    }
+}
+
+/// Sequencing and flow control of one session's inbound requests
+#[derive(Debug)]
+struct Inbound {
+    last_seq: Option<u64>,
+    max_per_sec: u32,
+    window_start: Instant,
+    window_count: u32,
+}
+
+impl Inbound {
+    fn new(max_per_sec: u32) -> Self {
+        Self {
+            last_seq: None,
+            max_per_sec,
+            window_start: Instant::now(),
+            window_count: 0,
+        }
+    }
+
+    /// Admit request `seq` received at `now`
+    fn admit(&mut self, seq: u64, now: Instant) -> Result<(), ApiError> {
+        if let Some(last) = self.last_seq.filter(|last| seq <= *last) {
+            return Err(ApiError::new(
+                "INVALID_SEQUENCE",
+                format!("Request seq {seq} not after {last}"),
+            ));
+        }
+
+        if self.max_per_sec > 0 {
+            if now.duration_since(self.window_start) >= Duration::from_secs(1) {
+                self.window_start = now;
+                self.window_count = 0;
+            }
+            if self.window_count >= self.max_per_sec {
+                return Err(ApiError::new(
+                    "THROTTLED",
+                    format!("More than {} requests per second", self.max_per_sec),
+                ));
+            }
+            self.window_count += 1;
+        }
+
+        self.last_seq = Some(seq);
+        Ok(())
+    }
+}
+
+/// Outbound side of a session, numbering every message sent
+struct Outbound {
+    socket: WebSocket,
+    seq: u64,
+}
+
+impl Outbound {
+    /// Send a message, returning false once the client is gone
+    async fn send(&mut self, message: &SessionMessage) -> bool {
+        self.seq += 1;
+        let text = match serde_json::to_string(&Envelope {
+            seq: self.seq,
+            message,
+        }) {
+            Ok(text) => text,
+            Err(e) => {
+                warn!("Failed to encode session message: {}", e);
+                return true;
+            }
+        };
+        let sent = self.socket.send(Message::Text(text)).await.is_ok();
+        if !sent {
+            debug!("Order entry client disconnected");
+        }
+        sent
+    }
+
+    async fn reject(&mut self, request_seq: Option<u64>, error: ApiError) -> bool {
+        debug!(code = %error.code, "Order entry request rejected");
+        self.send(&SessionMessage::Reject {
+            request_seq,
+            code: error.code,
+            error: error.error,
+        })
+        .await
+    }
+}
+
+/// Serve one order entry session until the client disconnects
+pub async fn serve(socket: WebSocket, state: AppState, user_id: Uuid, params: SessionParams) {
+    // Subscribe before acting on any request so no report is missed
+    let mut updates = state.store.subscribe();
+    let mut inbound = Inbound::new(state.session_messages_per_sec);
+    let mut out = Outbound { socket, seq: 0 };
+    let guarded = params.cancel_on_disconnect;
+    let timeout = state.session_heartbeat_timeout;
+
+    info!(user_id = %user_id, cancel_on_disconnect = guarded, "Order entry session opened");
+    let hello = SessionMessage::Session {
+        user_id,
+        max_messages_per_sec: state.session_messages_per_sec,
+        cancel_on_disconnect: guarded,
+        heartbeat_timeout_secs: guarded.then_some(timeout.as_secs()),
+    };
+    if !out.send(&hello).await {
+        return;
+    }
+    if guarded {
+        state.sessions.open(user_id);
+    }
+
+    let silence = tokio::time::sleep(timeout);
+    tokio::pin!(silence);
+
+    loop {
+        tokio::select! {
+            received = out.socket.recv() => {
+                silence.as_mut().reset(tokio::time::Instant::now() + timeout);
+                let text = match received {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                    Some(Ok(_)) => continue,
+                };
+                if !handle_request(&state, user_id, &mut inbound, &mut out, &text).await {
+                    break;
+                }
+            }
+            update = updates.recv() => {
+                let ok = match update {
+                    Ok(order) if order.user_id == user_id => {
+                        out.send(&SessionMessage::ExecutionReport { order }).await
+                    }
+                    Ok(_) => true,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(user_id = %user_id, skipped, "Order entry session lagged, resending open orders");
+                        resend_open_orders(&state, user_id, &mut out).await
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                if !ok {
+                    break;
+                }
+            }
+            _ = &mut silence, if guarded => {
+                warn!(user_id = %user_id, "Order entry session heartbeat timed out");
+                break;
+            }
+        }
+    }
+
+    info!(user_id = %user_id, "Order entry session closed");
+    if guarded && state.sessions.close(user_id) {
+        let cancelled = api::cancel_open_orders(&state, user_id).await;
+        info!(user_id = %user_id, cancelled, "Cancelled open orders on disconnect");
+    }
+}
+
+/// Act on one client request, returning false once the client is gone
+async fn handle_request(
+    state: &AppState,
+    user_id: Uuid,
+    inbound: &mut Inbound,
+    out: &mut Outbound,
+    text: &str,
+) -> bool {
+    let request = match serde_json::from_str::<ClientRequest>(text) {
+        Ok(request) => request,
+        Err(e) => return out.reject(None, ApiError::new("INVALID_MESSAGE", e)).await,
+    };
+
+    let seq = request.seq();
+    if let Err(e) = inbound.admit(seq, Instant::now()) {
+        return out.reject(Some(seq), e).await;
+    }
+
+    let result = match request {
+        ClientRequest::Submit { order, .. } => api::place_order(state, user_id, order).await,
+        ClientRequest::Replace {
+            order_id, replace, ..
+        } => api::request_replace(state, user_id, order_id, replace).await,
+        ClientRequest::Cancel { order_id, .. } => {
+            api::request_cancel(state, user_id, order_id).await
+        }
+    };
+
+    match result {
+        Ok(order) => {
+            out.send(&SessionMessage::Ack {
+                request_seq: seq,
+                order,
+            })
+            .await
+        }
+        Err(e) => out.reject(Some(seq), e).await,
+    }
+}
+
+/// Resend the state of the user's open orders after missed reports
+async fn resend_open_orders(state: &AppState, user_id: Uuid, out: &mut Outbound) -> bool {
+    for order in state.store.list_for_user(user_id) {
+        if matches!(
+            order.status,
+            OrderStatus::Pending | OrderStatus::Open | OrderStatus::PartiallyFilled
+        ) && !out.send(&SessionMessage::ExecutionReport { order }).await
+        {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inbound_sequencing_and_flow_control() {
+        let mut inbound = Inbound::new(2);
+        let start = inbound.window_start;
+
+        assert!(inbound.admit(1, start).is_ok());
+        // Replays and out-of-order requests are refused
+        assert_eq!(
+            inbound.admit(1, start).unwrap_err().code,
+            "INVALID_SEQUENCE"
+        );
+        // Gaps are allowed
+        assert!(inbound.admit(5, start).is_ok());
+        assert_eq!(inbound.admit(6, start).unwrap_err().code, "THROTTLED");
+
+        // A throttled seq may be retried in the next window
+        assert!(inbound.admit(6, start + Duration::from_secs(1)).is_ok());
+
+        let mut unlimited = Inbound::new(0);
+        assert!((1..=100).all(|seq| unlimited.admit(seq, start).is_ok()));
+
+        let request: ClientRequest = serde_json::from_str(
+            r#"{"op":"submit","seq":7,"symbol":"ETH-USDT","side":"buy",
+                "order_type":"limit","quantity":"1","price":2000}"#,
+        )
+        .unwrap();
+        assert_eq!(request.seq(), 7);
+    }
+
+    #[test]
+    fn test_orders_cancelled_after_last_guarded_session() {
+        let sessions = Sessions::default();
+        let user = Uuid::new_v4();
+
+        sessions.open(user);
+        sessions.open(user);
+        assert!(!sessions.close(user));
+        assert!(sessions.close(user));
+
+        // Sessions without the option were never counted
+        assert!(!sessions.close(user));
+    }
+}
@@ -0,0 +1,170 @@
+//! Order State Store
+//!
+//! Tracks every order accepted by the OMS and folds engine events into
+//! it. Client order ids are unique per user, which makes resubmission
+//! idempotent.
+
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use common::{
+    events::{OrderCancelled, OrderUpdated},
+    Order, OrderStatus,
+};
+
+/// Capacity of the order update broadcast
+const UPDATE_CHANNEL_CAPACITY: usize = 4096;
+
+pub struct OrderStore {
+    orders: DashMap<Uuid, Order>,
+
+    /// (user, client order id) -> order id
+    client_ids: DashMap<(Uuid, String), Uuid>,
+
+    updates: broadcast::Sender<Order>,
+}
+
+impl Default for OrderStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrderStore {
+    pub fn new() -> Self {
+        let (updates, _) = broadcast::channel(UPDATE_CHANNEL_CAPACITY);
+        Self {
+            orders: DashMap::new(),
+            client_ids: DashMap::new(),
+            updates,
+        }
+    }
+
+    /// Insert a new order
+    ///
+    /// Returns the existing order instead if the user already used this
+    /// client order id.
+    pub fn insert(&self, order: Order) -> Option<Order> {
+        match self
+            .client_ids
+            .entry((order.user_id, order.client_order_id.clone()))
+        {
+            Entry::Occupied(existing) => {
+                let existing = self.orders.get(existing.get()).map(|o| o.clone());
+                Some(existing.unwrap_or(order))
+            }
+            Entry::Vacant(slot) => {
+                slot.insert(order.id);
+                self.orders.insert(order.id, order.clone());
+                let _ = self.updates.send(order);
+                None
+            }
+        }
+    }
+
+    pub fn get(&self, order_id: Uuid) -> Option<Order> {
+        self.orders.get(&order_id).map(|o| o.clone())
+    }
+
+    /// Orders belonging to a user, newest first
+    pub fn list_for_user(&self, user_id: Uuid) -> Vec<Order> {
+        let mut orders: Vec<Order> = self
+            .orders
+            .iter()
+            .filter(|o| o.user_id == user_id)
+            .map(|o| o.clone())
+            .collect();
+        orders.sort_by_key(|o| std::cmp::Reverse(o.created_at));
+        orders
+    }
+
+    /// Set the status of an order the OMS itself rejected or failed to route
+    pub fn set_status(&self, order_id: Uuid, status: OrderStatus) {
+        self.modify(order_id, |order| order.status = status);
+    }
+
+    /// Apply an engine order update
+    pub fn apply_update(&self, update: &OrderUpdated) {
+        self.modify(update.order_id, |order| {
+            order.status = update.status;
+            order.filled_quantity = update.filled_quantity;
+            order.remaining_quantity = update.remaining_quantity;
+            order.avg_fill_price = update.avg_fill_price;
+            order.updated_at = update.timestamp;
+        });
+    }
+
+    /// Apply an engine cancellation
+    pub fn apply_cancel(&self, cancelled: &OrderCancelled) {
+        self.modify(cancelled.order_id, |order| {
+            order.status = OrderStatus::Cancelled;
+            order.updated_at = cancelled.timestamp;
+        });
+    }
+
+    /// Subscribe to order changes
+    pub fn subscribe(&self) -> broadcast::Receiver<Order> {
+        self.updates.subscribe()
+    }
+
+    fn modify(&self, order_id: Uuid, f: impl FnOnce(&mut Order)) {
+        let updated = self.orders.get_mut(&order_id).map(|mut order| {
+            f(&mut order);
+            order.clone()
+        });
+
+        if let Some(order) = updated {
+            let _ = self.updates.send(order);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+
+    fn order(user_id: Uuid, client_order_id: &str) -> Order {
+        Order::builder()
+            .user(user_id)
+            .client_order_id(client_order_id)
+            .build()
+    }
+
+    #[test]
+    fn test_dedup_and_engine_updates() {
+        let store = OrderStore::new();
+        let user = Uuid::new_v4();
+
+        let first = order(user, "abc");
+        assert!(store.insert(first.clone()).is_none());
+
+        // Same client id from the same user returns the original order
+        let duplicate = store.insert(order(user, "abc")).unwrap();
+        assert_eq!(duplicate.id, first.id);
+
+        // Other users may reuse the client id
+        assert!(store.insert(order(Uuid::new_v4(), "abc")).is_none());
+
+        store.apply_update(&OrderUpdated {
+            order_id: first.id,
+            client_order_id: "abc".to_string(),
+            symbol: first.symbol.clone(),
+            status: OrderStatus::Filled,
+            filled_quantity: Decimal::ONE,
+            remaining_quantity: Decimal::ZERO,
+            avg_fill_price: Some(Decimal::new(2000, 0)),
+            fills: Vec::new(),
+            fills_truncated: false,
+            timestamp: Utc::now(),
+        });
+
+        let tracked = store.get(first.id).unwrap();
+        assert_eq!(tracked.status, OrderStatus::Filled);
+        assert_eq!(tracked.remaining_quantity, Decimal::ZERO);
+        assert_eq!(store.list_for_user(user).len(), 1);
+    }
+}
@@ -0,0 +1,153 @@
+//! Reference Data API
+//!
+//! Versioned snapshot and per-symbol reads for all services; admin
+//! writes bump the version and publish a change event.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use tokio::sync::Mutex;
+use tower_http::trace::TraceLayer;
+use tracing::{error, info};
+
+use crate::config::Config;
+use crate::kafka::ChangePublisher;
+use common::{ReferenceSnapshot, Symbol, SymbolInfo, SymbolRegistry};
+
+#[derive(Clone)]
+pub struct AppState {
+    pub registry: Arc<SymbolRegistry>,
+    pub publisher: Arc<ChangePublisher>,
+    pub admin_token: Option<String>,
+
+    /// Serializes commit + publish so events leave in version order
+    pub write_lock: Arc<Mutex<()>>,
+}
+
+type ApiResult<T> = Result<T, (StatusCode, Json<serde_json::Value>)>;
+
+fn api_error(status: StatusCode, e: impl ToString) -> (StatusCode, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({ "error": e.to_string() })))
+}
+
+pub async fn run_server(state: AppState, config: &Config) -> anyhow::Result<()> {
+    let app = Router::new()
+        .route("/health", get(health))
+        .route("/snapshot", get(snapshot))
+        .route(
+            "/symbols/:symbol",
+            get(get_symbol).put(put_symbol).delete(delete_symbol),
+        )
+        .with_state(state)
+        .layer(TraceLayer::new_for_http());
+
+    let addr = format!("{}:{}", config.host, config.port);
+    info!("Starting reference data API on {}", addr);
+
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
+
+async fn health(State(state): State<AppState>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "status": "healthy",
+        "service": "reference-data",
+        "version": state.registry.version()
+    }))
+}
+
+async fn snapshot(State(state): State<AppState>) -> Json<ReferenceSnapshot> {
+    Json(state.registry.snapshot())
+}
+
+fn parse_symbol(symbol: &str) -> ApiResult<Symbol> {
+    symbol
+        .split_once('-')
+        .map(|(base, quote)| Symbol::new(base, quote))
+        .ok_or_else(|| api_error(StatusCode::BAD_REQUEST, "Invalid symbol format"))
+}
+
+async fn get_symbol(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+) -> ApiResult<Json<SymbolInfo>> {
+    let symbol = parse_symbol(&symbol)?;
+    state
+        .registry
+        .get(&symbol)
+        .map(Json)
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Unknown symbol"))
+}
+
+fn authorize(state: &AppState, headers: &HeaderMap) -> ApiResult<()> {
+    let provided = headers.get("x-admin-token").and_then(|v| v.to_str().ok());
+
+    match (&state.admin_token, provided) {
+        (Some(expected), Some(provided)) if expected == provided => Ok(()),
+        (None, _) => Err(api_error(StatusCode::FORBIDDEN, "Writes are disabled")),
+        _ => Err(api_error(StatusCode::UNAUTHORIZED, "Invalid admin token")),
+    }
+}
+
+/// Commit a change and publish it
+async fn write(
+    state: &AppState,
+    upserted: Vec<SymbolInfo>,
+    removed: Vec<Symbol>,
+) -> ApiResult<u64> {
+    let _guard = state.write_lock.lock().await;
+
+    let change = state.registry.commit(upserted, removed);
+    if let Err(e) = state.publisher.publish(&change).await {
+        // Consumers detect the skipped version on the next change and reload
+        error!(
+            version = change.version,
+            "Failed to publish reference data change: {}", e
+        );
+    }
+
+    info!(version = change.version, "Reference data changed");
+    Ok(change.version)
+}
+
+async fn put_symbol(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+    headers: HeaderMap,
+    Json(info): Json<SymbolInfo>,
+) -> ApiResult<Json<serde_json::Value>> {
+    authorize(&state, &headers)?;
+
+    if parse_symbol(&symbol)? != info.symbol {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Path symbol does not match body",
+        ));
+    }
+
+    let version = write(&state, vec![info], vec![]).await?;
+    Ok(Json(serde_json::json!({ "version": version })))
+}
+
+async fn delete_symbol(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+    headers: HeaderMap,
+) -> ApiResult<Json<serde_json::Value>> {
+    authorize(&state, &headers)?;
+
+    let symbol = parse_symbol(&symbol)?;
+    if state.registry.get(&symbol).is_none() {
+        return Err(api_error(StatusCode::NOT_FOUND, "Unknown symbol"));
+    }
+
+    let version = write(&state, vec![], vec![symbol]).await?;
+    Ok(Json(serde_json::json!({ "version": version })))
+}
@@ -0,0 +1,42 @@
+//! Reference Data Service Configuration
+
+use serde::{Deserialize, Serialize};
+
+use common::config::{Checks, Validate};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default = "default_host")]
+    pub host: String,
+
+    #[serde(default = "default_port")]
+    pub port: u16,
+
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+
+    pub kafka_brokers: String,
+
+    /// JSON file of `SymbolInfo` to seed from; built-in listing when unset
+    pub symbols_file: Option<String>,
+
+    /// Token required in `X-Admin-Token` for writes; writes disabled when unset
+    pub admin_token: Option<String>,
+}
+
+fn default_host() -> String {
+    "0.0.0.0".to_string()
+}
+fn default_port() -> u16 {
+    8083
+}
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+impl Validate for Config {
+    fn validate(&self, checks: &mut Checks) {
+        checks.ports(&[("port", self.port)]);
+        checks.brokers("kafka_brokers", &self.kafka_brokers);
+    }
+}
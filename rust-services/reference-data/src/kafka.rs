@@ -0,0 +1,49 @@
+//! Reference Data Change Publisher
+
+use std::time::Duration;
+
+use anyhow::Result;
+use rdkafka::{
+    producer::{FutureProducer, FutureRecord},
+    ClientConfig,
+};
+
+use crate::config::Config;
+use common::events::{topics, Event, ReferenceDataChanged};
+
+/// Single key keeps every change on one partition, in version order
+const CHANGE_KEY: &str = "symbols";
+
+pub struct ChangePublisher {
+    producer: FutureProducer,
+}
+
+impl ChangePublisher {
+    pub fn new(config: &Config) -> Result<Self> {
+        let producer: FutureProducer = ClientConfig::new()
+            .set("bootstrap.servers", &config.kafka_brokers)
+            .set("message.timeout.ms", "5000")
+            .set("acks", "all")
+            .set("enable.idempotence", "true")
+            .create()?;
+
+        Ok(Self { producer })
+    }
+
+    pub async fn publish(&self, change: &ReferenceDataChanged) -> Result<()> {
+        let event = Event::new("reference_data_changed", "reference-data", change.clone());
+        let payload = serde_json::to_string(&event)?;
+
+        self.producer
+            .send(
+                FutureRecord::to(topics::REFERENCE_DATA)
+                    .key(CHANGE_KEY)
+                    .payload(&payload),
+                Duration::from_secs(5),
+            )
+            .await
+            .map_err(|(e, _)| anyhow::anyhow!("Kafka send error: {e}"))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+//! FastTrading Reference Data Service
+//!
+//! Owns symbol/instrument metadata for every component:
+//! - Versioned snapshot API for service startup
+//! - Kafka change events on every update
+//!
+//! Consumers: matching engine (tick/lot validation), exchange gateway
+//! (venue symbol mapping), data pipeline (display metadata).
+
+use anyhow::Result;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::info;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+mod api;
+mod config;
+mod kafka;
+mod seed;
+
+use common::SymbolRegistry;
+use config::Config;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+    let config: Config = common::config::load()?;
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::new(&config.log_level))
+        .with(tracing_subscriber::fmt::layer().json())
+        .init();
+
+    info!(
+        "Starting FastTrading Reference Data v{}",
+        env!("CARGO_PKG_VERSION")
+    );
+
+    let symbols = seed::load(config.symbols_file.as_deref())?;
+
+    // Versions are not persisted; starting from the clock keeps them
+    // increasing across restarts so consumers never see one go backwards
+    let initial_version = chrono::Utc::now().timestamp_millis() as u64;
+    let registry = Arc::new(SymbolRegistry::with_symbols(initial_version, symbols));
+
+    info!(
+        version = initial_version,
+        symbols = registry.all().len(),
+        "Reference data loaded"
+    );
+
+    let state = api::AppState {
+        registry,
+        publisher: Arc::new(kafka::ChangePublisher::new(&config)?),
+        admin_token: config.admin_token.clone(),
+        write_lock: Arc::new(Mutex::new(())),
+    };
+
+    api::run_server(state, &config).await?;
+
+    Ok(())
+}
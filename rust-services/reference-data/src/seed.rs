@@ -0,0 +1,46 @@
+//! Initial Symbol Listing
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use rust_decimal::Decimal;
+
+use common::{Symbol, SymbolInfo, SymbolStatus};
+
+/// Symbols listed on the matching engine by default
+fn default_symbols() -> Vec<SymbolInfo> {
+    // (base, quote, price precision, quantity precision)
+    let listing = [
+        ("BTC", "USDT", 2, 5),
+        ("ETH", "USDT", 2, 4),
+        ("SOL", "USDT", 3, 2),
+        ("AVAX", "USDT", 3, 2),
+    ];
+
+    listing
+        .iter()
+        .map(|&(base, quote, price_dp, qty_dp)| {
+            let symbol = Symbol::new(base, quote);
+            SymbolInfo {
+                base_asset: base.to_string(),
+                quote_asset: quote.to_string(),
+                status: SymbolStatus::Trading,
+                price_precision: price_dp,
+                quantity_precision: qty_dp,
+                tick_size: Some(Decimal::new(1, price_dp)),
+                lot_size: Some(Decimal::new(1, qty_dp)),
+                min_quantity: Some(Decimal::new(1, qty_dp)),
+                venues: HashMap::from([("binance".to_string(), format!("{base}{quote}"))]),
+                symbol,
+            }
+        })
+        .collect()
+}
+
+/// Load the initial listing from `path`, or the built-in default
+pub fn load(path: Option<&str>) -> Result<Vec<SymbolInfo>> {
+    match path {
+        Some(path) => Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?),
+        None => Ok(default_symbols()),
+    }
+}